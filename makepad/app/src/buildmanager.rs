@@ -266,11 +266,27 @@ impl BuildManager {
                 column: err.column,
                 body: err.message.clone(),
                 range: Some((off, off + err.len)),
-                rendered: None,
+                rendered: Some(err.rendered.clone()),
                 explanation: None
             };
             self.process_loc_message_for_textbuffers(cx, &msg, TextBufferMessageLevel::Error, makepad_storage);
             self.log_items.push(HubLogItem::LocError(msg));
+            // underline the spans the error points at as well
+            for label in &err.labels {
+                let path = format!("main/makepad/{}", label.file);
+                let mtb = makepad_storage.text_buffer_from_path(cx, &path);
+                let off = mtb.text_buffer.text_pos_to_offset(TextPos {row: label.line - 1, col: label.column - 1});
+                let msg = LocMessage {
+                    path: path,
+                    line: label.line,
+                    column: label.column,
+                    body: label.message.clone(),
+                    range: Some((off, off + label.len)),
+                    rendered: None,
+                    explanation: None
+                };
+                self.process_loc_message_for_textbuffers(cx, &msg, TextBufferMessageLevel::Log, makepad_storage);
+            }
         }
         cx.send_signal(self.signal, BuildManager::status_new_log_item());
     }
@@ -12,7 +12,8 @@ use std::collections::{HashMap, HashSet, BTreeSet};
 use crate::builder;
 use crate::liveitems::*;
 
-#[derive(Debug, Clone, SerRon, DeRon)]
+#[derive(Debug, Clone, SerRon, DeRon, SerToml, DeToml)]
+#[nserde(default)]
 pub struct MakepadSettings {
     pub build_on_save: bool,
     pub exec_when_done: bool,
@@ -74,7 +75,7 @@ impl MakepadSettings {
     }
 }
 
-#[derive(Clone, Debug, SerRon, DeRon, PartialEq)]
+#[derive(Clone, Debug, SerRon, DeRon, SerToml, DeToml, PartialEq)]
 pub struct BuildTarget {
     pub builder: String,
     pub workspace: String,
@@ -113,7 +114,10 @@ pub struct MakepadTextBuffer {
     pub live_items_list: LiveItemsList
 }
 
+// compact: most of the stream is small ints and short strings, the channel
+// server forwards the message bytes as they are and only writes the wrap header
 #[derive(Clone, SerBin, DeBin)]
+#[nserde(tagged, compact)]
 pub enum MakepadChannelMessage {
     Connect,
     XRChannelUpdate {self_user: XRChannelUser},
@@ -204,7 +208,7 @@ impl MakepadStorage {
                 }
             },
             Err(e) => {
-                println!("Cannot deserialize settings {:?}", e);
+                print!("Cannot deserialize settings\n{}", e.render("makepad_settings.ron", utf8_data));
             }
         }
     }
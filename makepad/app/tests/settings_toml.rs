@@ -0,0 +1,18 @@
+use makepad_app::makepadstorage::*;
+use makepad_microserde::*;
+
+#[test]
+fn settings_toml_round_trip() {
+    let settings = MakepadSettings::initial();
+    let toml = settings.serialize_toml();
+    let back = MakepadSettings::deserialize_toml(&toml).unwrap();
+    assert_eq!(back.serialize_toml(), toml);
+    assert_eq!(back.serialize_ron(), settings.serialize_ron());
+    assert_eq!(back.builds, settings.builds);
+    assert!(toml.contains("[builders.main.http_server]\nLocalhost = [8000]\n"));
+    assert!(toml.contains("[[builds]]\nbuilder = \"main\"\n"));
+    
+    // missing fields take the defaults
+    let settings = MakepadSettings::deserialize_toml("build_on_save = false\n").unwrap();
+    assert!(!settings.build_on_save && settings.live_on_self && settings.builds.is_empty());
+}
@@ -8,7 +8,7 @@ use makepad_microserde::*;
 use makepad_http::httputil::*;
 use makepad_http::channel::*;
 
-#[derive(Debug, Clone, SerBin, DeBin, PartialEq, SerRon, DeRon)]
+#[derive(Debug, Clone, SerBin, DeBin, PartialEq, SerRon, DeRon, SerToml, DeToml)]
 pub enum HttpServerConfig {
     Offline,
     Localhost(u16),
@@ -148,7 +148,27 @@ impl HubBuilder {
             
             let rx_read = hub_client.rx_read.take().unwrap();
             
-            while let Ok(htc) = rx_read.recv() {
+            while let Ok(recv) = rx_read.recv() {
+                let htc = match recv {
+                    HubRecv::Msg(htc) => htc,
+                    HubRecv::Buf(msg_buf) => {
+                        // file writes go to disk straight from the receive buffer, they block like is_blocking anyway
+                        if let Ok(FromHubMsgRef {from, msg: HubMsgRef::FileWriteRequest {uid, path, data}}) = FromHubMsgRef::deserialize_bin_ref(&msg_buf) {
+                            let mut hub_builder = HubBuilder {
+                                route_send: route_send.clone(),
+                                websocket_channels: WebSocketChannels::default(),
+                                http_server: Arc::clone(&http_server),
+                                workspaces: Arc::clone(&workspaces),
+                                processes: Arc::clone(&processes),
+                                builder: builder.to_string(),
+                                abs_cwd_path: abs_cwd_path.clone(),
+                            };
+                            hub_builder.file_write(from, uid, path, data);
+                            continue
+                        }
+                        DeBin::deserialize_bin(&msg_buf).expect("Cannot parse binary")
+                    }
+                };
                 match &htc.msg {
                     HubMsg::ConnectionError(_e) => {
                         println!("Got connection error, need to restart loop TODO kill all processes!");
@@ -426,7 +446,7 @@ impl HubBuilder {
                 Ok(())
             },
             HubMsg::FileWriteRequest {uid, path, data} => {
-                ws.file_write(htc.from, uid, &path, data);
+                ws.file_write(htc.from, uid, &path, &data);
                 Ok(())
             },
             HubMsg::BuildKill {uid} => {
@@ -666,12 +686,10 @@ impl HubBuilder {
         
         let mut extargs = args.to_vec();
         extargs.push("--message-format=json");
-        let mut process = Process::start("cargo", &extargs, &abs_root_path, env).expect("Cannot start process");
+        let (process, rx_out) = Process::start_json::<RustcCompilerMessage>("cargo", &extargs, &abs_root_path, env).expect("Cannot start process");
         
         let route_send = self.route_send.clone();
         
-        let rx_line = process.rx_line.take().unwrap();
-        
         if let Ok(mut processes) = self.processes.lock() {
             processes.push(HubProcess {
                 uid: uid,
@@ -688,28 +706,28 @@ impl HubBuilder {
         
         let mut errors = Vec::new();
         let mut build_result = BuildResult::NoOutput;
-        while let Ok(line) = rx_line.recv() {
-            if let Some((is_stderr, line)) = line {
-                if is_stderr && line != "\n"
-                    && !line.contains("Finished")
-                    && !line.contains("Blocking")
-                    && !line.contains("Compiling")
-                    && !line.contains("--verbose") {
-                    route_send.send(ToHubMsg {
-                        to: HubMsgTo::UI,
-                        msg: HubMsg::LogItem {
-                            uid: uid,
-                            item: HubLogItem::Error(line.clone())
-                        }
-                    });
-                }
-                
-                let mut parsed: Result<RustcCompilerMessage, DeJsonErr> = DeJson::deserialize_json(&line);
-                match &mut parsed {
+        while let Ok(out) = rx_out.recv() {
+            match out {
+                ProcessOutput::Stderr(line) => {
+                    if line != "\n"
+                        && !line.contains("Finished")
+                        && !line.contains("Blocking")
+                        && !line.contains("Compiling")
+                        && !line.contains("--verbose") {
+                        route_send.send(ToHubMsg {
+                            to: HubMsgTo::UI,
+                            msg: HubMsg::LogItem {
+                                uid: uid,
+                                item: HubLogItem::Error(line)
+                            }
+                        });
+                    }
+                },
+                ProcessOutput::Json(parsed) => match parsed {
                     Err(_e) => {
-                        //eprintln!("ERROR PARSING {} {:?}", line, e);
+                        //eprintln!("ERROR PARSING {:?}", e);
                     },
-                    Ok(parsed) => {
+                    Ok(mut parsed) => {
                         if let Some(message) = &mut parsed.message { //.spans;
                             let spans = &message.spans;
                             for i in 0..spans.len() {
@@ -809,9 +827,6 @@ impl HubBuilder {
                     }
                 }
             }
-            else { // process terminated
-                break;
-            }
         }
         
         // process ends as well
@@ -921,7 +936,7 @@ impl HubBuilder {
                     Ok(v) => v
                 };
                 
-                if let Some(Toml::Array(members)) = toml.get("workspace.members") {
+                if let Some(Toml::Array(members)) = toml.path("workspace.members") {
                     for member in members {
                         if let Toml::Str(member) = member {
                             let file_path = format!("{}/{}/Cargo.toml", abs_path, member);
@@ -940,7 +955,7 @@ impl HubBuilder {
                                 },
                                 Ok(v) => v
                             };
-                            if let Some(Toml::Str(name)) = toml.get("package.name") {
+                            if let Some(Toml::Str(name)) = toml.path("package.name") {
                                 packages.push((workspace.clone(), name.clone()));
                             }
                         }
@@ -982,7 +997,7 @@ impl HubBuilder {
         }
     }
     
-    pub fn file_write(&mut self, from: HubAddr, uid: HubUid, path: &str, data: Vec<u8>) {
+    pub fn file_write(&mut self, from: HubAddr, uid: HubUid, path: &str, data: &[u8]) {
         if let Ok((abs_dir, _project, sub_path)) = self.workspace_split_from_path(uid, path) {
             
             if path.contains("..") {
@@ -991,7 +1006,7 @@ impl HubBuilder {
                 return
             }
             
-            let done = std::fs::write(format!("{}/{}", abs_dir, sub_path), &data).is_ok();
+            let done = std::fs::write(format!("{}/{}", abs_dir, sub_path), data).is_ok();
             
             if let Ok(mut http_server) = self.http_server.lock() {
                 if let Some(http_server) = &mut *http_server {
@@ -1202,6 +1217,8 @@ pub struct RustcCode {
 
 #[derive(Clone, DeJson, Default)]
 pub struct RustcMessage {
+    #[nserde(rename = "$message_type")]
+    message_type: Option<String>,
     message: String,
     code: Option<RustcCode>,
     level: String,
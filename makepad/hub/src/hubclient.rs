@@ -111,14 +111,20 @@ pub fn write_block_to_tcp_stream(tcp_stream: &mut TcpStream, msg_buf: &[u8], dig
     Ok(())
 }
 
+// what the read thread hands on, file transfers stay in their receive buffer so they can be read with FromHubMsgRef
+pub enum HubRecv {
+    Msg(FromHubMsg),
+    Buf(Vec<u8>)
+}
+
 pub struct HubClient {
     pub own_addr: HubAddr,
     pub server_addr: HubAddr,
     pub uid_alloc: u64,
     read_thread: Option<thread::JoinHandle<()>>,
     write_thread: Option<thread::JoinHandle<()>>,
-    pub tx_read: mpsc::Sender<FromHubMsg>,
-    pub rx_read: Option<mpsc::Receiver<FromHubMsg>>,
+    pub tx_read: mpsc::Sender<HubRecv>,
+    pub rx_read: Option<mpsc::Receiver<HubRecv>>,
     pub tx_write: mpsc::Sender<ToHubMsg>
 }
 
@@ -145,7 +151,7 @@ impl HubClient {
         
         let own_addr = HubAddr::from_socket_addr(tcp_stream.local_addr().expect("Cannot get client local address"));
         
-        let (tx_read, rx_read) = mpsc::channel::<FromHubMsg>();
+        let (tx_read, rx_read) = mpsc::channel::<HubRecv>();
         let (tx_write, rx_write) = mpsc::channel::<ToHubMsg>();
         let tx_read_copy = tx_read.clone();
         let tx_write_copy = tx_write.clone();
@@ -159,16 +165,21 @@ impl HubClient {
                 loop {
                     match read_block_from_tcp_stream(&mut tcp_stream, digest.clone()) {
                         Ok(msg_buf) => {
+                            if let Ok(htc_ref) = FromHubMsgRef::deserialize_bin_ref(&msg_buf) {
+                                hub_log.msg("HubClient received", &htc_ref);
+                                tx_read.send(HubRecv::Buf(msg_buf)).expect("tx_read.send fails - should never happen");
+                                continue
+                            }
                             let htc_msg: FromHubMsg = DeBin::deserialize_bin(&msg_buf).expect("Cannot parse binary");
                             hub_log.msg("HubClient received", &htc_msg);
-                            tx_read.send(htc_msg).expect("tx_read.send fails - should never happen");
+                            tx_read.send(HubRecv::Msg(htc_msg)).expect("tx_read.send fails - should never happen");
                         },
                         Err(e) => {
                             let _ = tcp_stream.shutdown(Shutdown::Both);
-                            tx_read.send(FromHubMsg {
+                            tx_read.send(HubRecv::Msg(FromHubMsg {
                                 from: server_hubaddr.clone(),
                                 msg: HubMsg::ConnectionError(e.clone())
-                            }).expect("tx_read.send fails - should never happen");
+                            })).expect("tx_read.send fails - should never happen");
                             // lets break rx write
                             let _ = tx_write_copy.send(ToHubMsg {
                                 to: HubMsgTo::Hub,
@@ -200,10 +211,10 @@ impl HubClient {
                     if let Err(e) = write_block_to_tcp_stream(&mut tcp_stream, &msg_buf, digest.clone()) {
                         // disconnect the socket and send shutdown
                         let _ = tcp_stream.shutdown(Shutdown::Both);
-                        let _ = tx_read.send(FromHubMsg {
+                        let _ = tx_read.send(HubRecv::Msg(FromHubMsg {
                             from: server_hubaddr.clone(),
                             msg: HubMsg::ConnectionError(e)
-                        });
+                        }));
                         return
                     }
                 }
@@ -6,6 +6,7 @@ use crate::httpserver::*;
 use crate::hubclient::*;
 
 #[derive(Clone, Debug, SerBin, DeBin)]
+#[nserde(tagged)]
 pub enum HubMsg {
     ConnectBuilder(String),
     ConnectClone(String),
@@ -133,6 +134,23 @@ pub enum HubMsg {
     },
 }
 
+// the file transfer messages read borrowed out of the receive buffer, so the payload isn't copied
+#[derive(Debug, DeBinRef)]
+#[nserde(tagged)]
+pub enum HubMsgRef<'a> {
+    FileReadResponse {
+        uid: HubUid,
+        path: &'a str,
+        data: Option<&'a [u8]>
+    },
+    
+    FileWriteRequest {
+        uid: HubUid,
+        path: &'a str,
+        data: &'a [u8]
+    },
+}
+
 impl HubMsg{
     pub fn is_blocking(&self)->bool{
         match self{
@@ -144,6 +162,7 @@ impl HubMsg{
 }
 
 #[derive(Eq, PartialEq, Debug, Clone, SerBin, DeBin, SerRon, DeRon)]
+#[nserde(tagged)]
 pub enum BuilderFileTreeNode {
     File {name: String, digest:Option<Box<Digest>>},
     Folder {name: String, digest:Option<Box<Digest>>, folder: Vec<BuilderFileTreeNode>}
@@ -184,6 +203,7 @@ impl PartialOrd for BuilderFileTreeNode {
 
 
 #[derive(Debug, Clone, SerBin, DeBin)]
+#[nserde(tagged)]
 pub enum BuildResult {
     Executable {path: String},
     Wasm {path: String},
@@ -193,6 +213,7 @@ pub enum BuildResult {
 }
 
 #[derive(Debug, Clone, SerBin, DeBin)]
+#[nserde(tagged)]
 pub struct HubPackage {
     pub project: String,
     pub package_name: String,
@@ -210,7 +231,8 @@ impl HubPackage {
 }
 
 
-#[derive(Debug, Clone, SerBin, DeBin, PartialEq, SerRon, DeRon)]
+#[derive(Debug, Clone, SerBin, DeBin, PartialEq, SerRon, DeRon, SerToml, DeToml)]
+#[nserde(tagged)]
 pub struct HubBuilderConfig {
     pub http_server: HttpServerConfig,
     pub workspaces: HashMap<String, String>,
@@ -218,6 +240,7 @@ pub struct HubBuilderConfig {
 
 
 #[derive(Debug, Clone, PartialEq, SerBin, DeBin)]
+#[nserde(tagged)]
 pub struct LocMessage {
     pub path: String,
     pub line: usize,
@@ -229,6 +252,7 @@ pub struct LocMessage {
 }
 
 #[derive(Debug, Clone, PartialEq, SerBin, DeBin)]
+#[nserde(tagged)]
 pub enum HubLogItem {
     LocPanic(LocMessage),
     LocError(LocMessage),
@@ -276,7 +300,7 @@ pub struct HubCargoCheck {
     pub args: String,
 }
 
-#[derive(PartialEq, Copy, Debug, Clone, SerBin, DeBin)]
+#[derive(PartialEq, Copy, Debug, Clone, SerBin, DeBin, DeBinRef)]
 pub enum HubAddr {
     None,
     Local {uid: u64},
@@ -317,7 +341,7 @@ pub enum HubMsgTo {
     Hub
 }
 
-#[derive(PartialEq, Copy, Debug, Clone, SerBin, DeBin)]
+#[derive(PartialEq, Copy, Debug, Clone, SerBin, DeBin, DeBinRef)]
 pub struct HubUid {
     pub addr: HubAddr,
     pub id: u64
@@ -341,7 +365,14 @@ pub struct FromHubMsg {
     pub msg: HubMsg
 }
 
+#[derive(Debug, DeBinRef)]
+pub struct FromHubMsgRef<'a> {
+    pub from: HubAddr,
+    pub msg: HubMsgRef<'a>
+}
+
 #[derive(Clone, Debug, SerBin, DeBin)]
+#[nserde(tagged)]
 pub struct HubError {
     pub msg: String
 }
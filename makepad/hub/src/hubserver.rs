@@ -6,7 +6,7 @@ use crate::hubclient::*;
 use crate::hubrouter::*;
 use makepad_microserde::*;
 
-#[derive(Debug, Clone, SerBin, DeBin, SerRon, DeRon, PartialEq)]
+#[derive(Debug, Clone, SerBin, DeBin, SerRon, DeRon, SerToml, DeToml, PartialEq)]
 pub enum HubServerConfig {
     Offline, // no network connectivity
     Network(u16), // 0.0.0.0:port
@@ -4,6 +4,75 @@ use std::process::{Command, Child, Stdio};
 use std::sync::{mpsc};
 use std::io::{Read};
 use std::str;
+use makepad_microserde::*;
+
+fn create_process(cmd: &str, args: &[&str], current_dir: &str, env: &[(&str, &str)]) -> Result<Child, std::io::Error> {
+    let mut cbuild = if cmd.find("/").is_some() {
+        Command::new(&format!("{}/{}", current_dir, cmd))
+    }
+    else {
+        Command::new(cmd)
+    };
+    cbuild.args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .current_dir(current_dir);
+    for (key, value) in env {
+        cbuild.env(key, value);
+    }
+    cbuild.spawn()
+}
+
+fn read_lines(read: &mut dyn Read, mut line_fn: impl FnMut(String)) {
+    let mut storage = Vec::new();
+    loop {
+        let offset = storage.len();
+        storage.resize(offset + 1024, 0u8);
+        let new_len = storage.len();
+        let n_bytes_read = read.read(&mut storage[offset..new_len]).expect("cannot read");
+        if n_bytes_read == 0 {
+            return;
+        }
+        storage.resize(offset + n_bytes_read, 0u8);
+        let mut start = 0;
+        for (index, ch) in storage.iter().enumerate() {
+            if *ch == '\n' as u8 {
+                // emit a line
+                if let Ok(line) = str::from_utf8(&storage[start..(index + 1)]) {
+                    line_fn(line.to_string());
+                }
+                start = index + 1;
+            }
+        }
+        storage.drain(0..start);
+    }
+}
+
+// reads a stream of json values and decodes each into T on its own, so a value T rejects
+// (another cargo message reason, a field cargo added) doesn't end the stream.
+// value_fn returns false to stop, a value that isn't json at all stops it too
+pub fn read_json_values<T: DeJson>(read: &mut dyn Read, mut value_fn: impl FnMut(Result<T, DeJsonErr>) -> bool) {
+    let mut state = DeJsonState::from_reader(read);
+    while let Some(value) = state.read_value::<JsonValue>() {
+        let value = match value {
+            Ok(value) => T::deserialize_json(&value.serialize_json()),
+            Err(err) => {
+                value_fn(Err(err));
+                return
+            }
+        };
+        if !value_fn(value) {
+            return
+        }
+    }
+}
+
+// what start_json hands out, stdout is parsed as a stream of json values
+pub enum ProcessOutput<T> {
+    Stderr(String),
+    Json(Result<T, DeJsonErr>),
+}
 
 pub struct Process {
     pub child: Option<Child>,
@@ -13,24 +82,6 @@ pub struct Process {
 impl Process {
     
     pub fn start(cmd: &str, args: &[&str], current_dir: &str, env: &[(&str, &str)]) -> Result<Process, std::io::Error> {
-        fn create_process(cmd: &str, args: &[&str], current_dir: &str, env: &[(&str, &str)]) -> Result<Child, std::io::Error> {
-            let mut cbuild = if cmd.find("/").is_some() {
-                Command::new(&format!("{}/{}", current_dir, cmd))
-            }
-            else {
-                Command::new(cmd)
-            };
-            cbuild.args(args)
-                .stdin(Stdio::null())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .current_dir(current_dir);
-            for (key, value) in env {
-                cbuild.env(key, value);
-            }
-            cbuild.spawn()
-        }
-        
         let mut child = create_process(cmd, args, current_dir, env) ?;
         
         let (tx_line, rx_line) = mpsc::channel();
@@ -38,60 +89,14 @@ impl Process {
         let mut stdout = child.stdout.take().expect("stdout cannot be taken!");
         let mut stderr = child.stderr.take().expect("stderr cannot be taken!");
         
-        let _stdout_thread = {
-            std::thread::spawn(move || {
-                let mut storage = Vec::new();
-                loop {
-                    let offset = storage.len();
-                    storage.resize(offset + 1024, 0u8);
-                    let new_len = storage.len();
-                    let n_bytes_read = stdout.read(&mut storage[offset..new_len]).expect("cannot read");
-                    if n_bytes_read == 0 {
-                        tx_line.send(None).expect("tx_line cannot send - unexpected");
-                        return;
-                    }
-                    storage.resize(offset + n_bytes_read, 0u8);
-                    let mut start = 0;
-                    for (index, ch) in storage.iter().enumerate() {
-                        if *ch == '\n' as u8 {
-                            // emit a line
-                            if let Ok(line) = str::from_utf8(&storage[start..(index + 1)]) {
-                                tx_line.send(Some((false, line.to_string()))).expect("tx_line cannot send - unexpected");
-                            }
-                            start = index + 1;
-                        }
-                    }
-                    storage.drain(0..start);
-                }
-            })
-        };
+        let _stdout_thread = std::thread::spawn(move || {
+            read_lines(&mut stdout, | line | tx_line.send(Some((false, line))).expect("tx_line cannot send - unexpected"));
+            tx_line.send(None).expect("tx_line cannot send - unexpected");
+        });
         
-        let _stderr_thread = {
-            std::thread::spawn(move || {
-                let mut storage = Vec::new();
-                loop {
-                    let offset = storage.len();
-                    storage.resize(offset + 1024, 0u8);
-                    let new_len = storage.len();
-                    let n_bytes_read = stderr.read(&mut storage[offset..new_len]).expect("cannot read");
-                    if n_bytes_read == 0 {
-                        return;
-                    }
-                    storage.resize(offset + n_bytes_read, 0u8);
-                    let mut start = 0;
-                    for (index, ch) in storage.iter().enumerate() {
-                        if *ch == '\n' as u8 {
-                            // emit a line
-                            if let Ok(line) = str::from_utf8(&storage[start..(index + 1)]) {
-                                tx_err.send(Some((true, line.to_string()))).expect("tx_err cannot send - unexpected");
-                            }
-                            start = index + 1;
-                        }
-                    }
-                    storage.drain(0..start);
-                }
-            })
-        };
+        let _stderr_thread = std::thread::spawn(move || {
+            read_lines(&mut stderr, | line | tx_err.send(Some((true, line))).expect("tx_err cannot send - unexpected"));
+        });
         
         Ok(Process {
             child: Some(child),
@@ -99,6 +104,30 @@ impl Process {
         })
     }
     
+    pub fn start_json<T: DeJson + Send + 'static>(cmd: &str, args: &[&str], current_dir: &str, env: &[(&str, &str)]) -> Result<(Process, mpsc::Receiver<ProcessOutput<T >>), std::io::Error> {
+        let mut child = create_process(cmd, args, current_dir, env) ?;
+        
+        let (tx_out, rx_out) = mpsc::channel();
+        let tx_err = tx_out.clone();
+        let mut stdout = child.stdout.take().expect("stdout cannot be taken!");
+        let mut stderr = child.stderr.take().expect("stderr cannot be taken!");
+        
+        let _stdout_thread = std::thread::spawn(move || {
+            read_json_values(&mut stdout, | value | tx_out.send(ProcessOutput::Json(value)).is_ok());
+            // after a syntax error the stream can't be resynced, keep draining so the child doesn't block
+            let _ = std::io::copy(&mut stdout, &mut std::io::sink());
+        });
+        
+        let _stderr_thread = std::thread::spawn(move || {
+            read_lines(&mut stderr, | line | {let _ = tx_err.send(ProcessOutput::Stderr(line));});
+        });
+        
+        Ok((Process {
+            child: Some(child),
+            rx_line: None,
+        }, rx_out))
+    }
+    
     pub fn wait(&mut self) {
         if let Some(child) = &mut self.child {
             let _ = child.wait();
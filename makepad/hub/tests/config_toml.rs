@@ -0,0 +1,36 @@
+use makepad_hub::*;
+use makepad_microserde::*;
+use std::collections::HashMap;
+
+#[test]
+fn builder_config_toml_round_trip() {
+    let mut workspaces = HashMap::new();
+    workspaces.insert("makepad".to_string(), ".".to_string());
+    workspaces.insert("other".to_string(), "../other".to_string());
+    for http_server in [
+        HttpServerConfig::Offline,
+        HttpServerConfig::Localhost(8000),
+        HttpServerConfig::InterfaceV4(80, [10, 0, 0, 1]),
+    ] {
+        let config = HubBuilderConfig {http_server, workspaces: workspaces.clone()};
+        let toml = config.serialize_toml();
+        assert_eq!(HubBuilderConfig::deserialize_toml(&toml).unwrap(), config, "{}", toml);
+    }
+    
+    // the form people write by hand
+    let config = HubBuilderConfig::deserialize_toml("http_server = { Localhost = [8000] }\n\n[workspaces]\nmakepad = \".\"\n").unwrap();
+    assert_eq!(config.http_server, HttpServerConfig::Localhost(8000));
+    assert_eq!(config.workspaces["makepad"], ".");
+}
+
+#[test]
+fn server_config_toml_round_trip() {
+    #[derive(SerToml, DeToml, Debug, PartialEq)]
+    struct Settings {
+        hub_server: HubServerConfig,
+    }
+    for hub_server in [HubServerConfig::Offline, HubServerConfig::Network(7243), HubServerConfig::InterfaceV4(7243, [127, 0, 0, 1])] {
+        let settings = Settings {hub_server};
+        assert_eq!(Settings::deserialize_toml(&settings.serialize_toml()).unwrap(), settings);
+    }
+}
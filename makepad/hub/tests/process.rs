@@ -0,0 +1,44 @@
+use makepad_hub::*;
+use makepad_microserde::*;
+
+// like RustcCompilerMessage, package_id is required and unknown keys are rejected
+#[derive(DeJson)]
+struct Message {
+    reason: String,
+    package_id: String,
+    message: Option<String>,
+}
+
+#[test]
+fn json_values_skip_undecodable() {
+    let stdout = r#"{"reason":"build-script-executed","package_id":"a 0.1.0","out_dir":"/tmp/out"}
+{"reason":"compiler-message","package_id":"a 0.1.0","message":"unused variable"}
+{"reason":"build-finished","success":false}
+{"reason":"compiler-message","package_id":"b 0.1.0","message":"mismatched types"}
+"#;
+    let mut messages = Vec::new();
+    let mut errors = 0;
+    read_json_values::<Message>(&mut stdout.as_bytes(), | value | {
+        match value {
+            Ok(value) => messages.push((value.reason, value.package_id, value.message)),
+            Err(_) => errors += 1
+        }
+        true
+    });
+    assert_eq!(errors, 2);
+    assert_eq!(messages, vec![
+        ("compiler-message".to_string(), "a 0.1.0".to_string(), Some("unused variable".to_string())),
+        ("compiler-message".to_string(), "b 0.1.0".to_string(), Some("mismatched types".to_string())),
+    ]);
+}
+
+#[test]
+fn json_values_stop_at_syntax_error() {
+    let stdout = "{\"reason\":\"a\",\"package_id\":\"a\"}\n{\"reason\":\n";
+    let mut results = Vec::new();
+    read_json_values::<Message>(&mut stdout.as_bytes(), | value | {
+        results.push(value.is_ok());
+        true
+    });
+    assert_eq!(results, vec![true, false]);
+}
@@ -36,7 +36,7 @@ pub fn derive_de_tok_impl(input: TokenStream) -> TokenStream {
             else if let Some(fields) = parser.eat_all_struct_fields() { // if all our fields are f32's
                 // we can use a special all() function for f32 only structs
                 let mut all_f32 = true;
-                for (_field, ty) in &fields{
+                for Field {ty, ..} in &fields{
                     let ty_str = ty.to_string();
                     if ty_str != "f32"{
                         all_f32 = false;
@@ -49,7 +49,7 @@ pub fn derive_de_tok_impl(input: TokenStream) -> TokenStream {
                     tb.add("let f = f32 :: de_tok ( p ) ? ;");
                     tb.add("p . expect_token ( Token :: RightParen ) ? ;");
                     tb.add("return std :: result :: Result :: Ok ( Self {");
-                    for (field, _ty) in &fields {
+                    for Field {name: field, ..} in &fields {
                         tb.ident(&field).add(": f ,");
                     }
                     tb.add("} ) }");
@@ -57,12 +57,12 @@ pub fn derive_de_tok_impl(input: TokenStream) -> TokenStream {
                 
                 tb.add("let mut default = Self :: default ( ) ;");
                 tb.add("p . expect_token ( Token :: LeftBrace )  ? ;");
-                for (field, _ty) in &fields {
+                for Field {name: field, ..} in &fields {
                     tb.add("let mut").ident(&format!("_{}", field)).add("= None ;");
                 }
                 tb.add("while let Ok ( ident ) = p . parse_ident ( ) {");
 
-                for (index, (field, _ty)) in fields.iter().enumerate() {
+                for (index, Field {name: field, ..}) in fields.iter().enumerate() {
                     if index != 0{
                         tb.add("else");
                     }
@@ -78,7 +78,7 @@ pub fn derive_de_tok_impl(input: TokenStream) -> TokenStream {
                 tb.add("}");
                 tb.add("p . expect_token ( Token :: RightBrace )  ? ;");
                 tb.add("std :: result :: Result :: Ok ( Self {");
-                for (field, ty) in fields {
+                for Field {name: field, ty, ..} in fields {
                     tb.ident(&field).add(":");
                     if ty.into_iter().next().unwrap().to_string() == "Option" {
                         tb.add("if let Some ( t ) =").ident(&format!("_{}", field));
@@ -133,11 +133,11 @@ pub fn derive_de_tok_impl(input: TokenStream) -> TokenStream {
                     }
                     else if let Some(fields) = parser.eat_all_struct_fields() { // named variant
                         tb.add("p . expect_token ( Token :: LeftBrace )  ? ;");
-                        for (field, _ty) in &fields {
+                        for Field {name: field, ..} in &fields {
                             tb.add("let mut").ident(&format!("_{}", field)).add("= None ;");
                         }
                         tb.add("while let Ok ( ident ) = p . parse_ident ( ) {");
-                        for (index, (field, _ty)) in fields.iter().enumerate() {
+                        for (index, Field {name: field, ..}) in fields.iter().enumerate() {
                             if index != 0{
                                 tb.add("else");
                             }
@@ -150,7 +150,7 @@ pub fn derive_de_tok_impl(input: TokenStream) -> TokenStream {
                         tb.add("}");
                         tb.add("p . expect_token ( Token :: RightBrace )  ? ;");
                         tb.add("return Ok ( Self ::").ident(&variant).add("{");
-                        for (field, ty) in fields {
+                        for Field {name: field, ty, ..} in fields {
                             tb.ident(&field).add(":");
                             if ty.into_iter().next().unwrap().to_string() == "Option" {
                                 tb.add("if let Some ( t ) =").ident(&format!("_{}", field));
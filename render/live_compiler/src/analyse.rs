@@ -5,12 +5,14 @@ use crate::const_gather::ConstGatherer;
 use crate::dep_analyse::DepAnalyser;
 use crate::env::{Env, Sym, VarKind};
 use crate::error::LiveError;
+use crate::optimise::ShaderOptimisations;
 use crate::ident::{Ident,IdentPath};
 use crate::span::Span;
 use crate::ty::Ty;
 use crate::ty_check::TyChecker;
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet, BTreeSet};
+use std::fmt::Write;
 /*
 pub fn analyse(shader: &ShaderAst, base_props:&[PropDef], sub_props: &[&PropDef], gather_all: bool) -> Result<(), Error> {
     let builtins = builtin::generate_builtins();
@@ -45,7 +47,55 @@ pub fn analyse(shader: &ShaderAst, base_props:&[PropDef], sub_props: &[&PropDef]
 pub struct ShaderCompileOptions{
     pub gather_all: bool,
     pub create_const_table: bool,
-    pub no_const_collapse: bool
+    pub no_const_collapse: bool,
+    pub optimisations: ShaderOptimisations,
+}
+
+// gives every overload of a function a name of its own, made from its name and parameter
+// types: `fn sdf(p: vec2, r: float)` becomes `sdf_mpsc_vec2_float`. declarations with the
+// same parameter types keep sharing a name, so the last one still overrides the others
+pub fn mangle_overloaded_fns(shader: &mut ShaderAst) -> Result<(), LiveError> {
+    let mut signatures: HashMap<IdentPath, HashSet<Vec<String>>> = HashMap::new();
+    for decl in &shader.decls {
+        if let Decl::Fn(decl) = decl {
+            signatures.entry(decl.source_ident_path).or_default().insert(param_ty_names(decl));
+        }
+    }
+    for decl in &mut shader.decls {
+        if let Decl::Fn(decl) = decl {
+            if signatures[&decl.source_ident_path].len() < 2 {
+                continue;
+            }
+            if decl.source_ident_path == IdentPath::from_str("vertex") || decl.source_ident_path == IdentPath::from_str("pixel") {
+                return Err(LiveError {
+                    span: decl.span,
+                    message: format!("function `{}` can't be overloaded", decl.source_ident_path),
+                    ..LiveError::default()
+                });
+            }
+            let last = decl.ident_path.len - 1;
+            let mut name = format!("{}_mpsc", decl.ident_path.segs[last]);
+            for ty_name in param_ty_names(decl) {
+                write!(name, "_{}", ty_name).unwrap();
+            }
+            decl.ident_path.segs[last] = Ident::new(name);
+        }
+    }
+    Ok(())
+}
+
+// parameter types as written, before analysis. a type has only one spelling, so these
+// tell signatures apart as well as the analysed types would. `self` is left out, it is
+// the same for every overload of a method
+fn param_ty_names(decl: &FnDecl) -> Vec<String> {
+    fn ty_name(ty_expr: &TyExpr) -> String {
+        match &ty_expr.kind {
+            TyExprKind::Array {elem_ty_expr, len, ..} => format!("{}x{}", ty_name(elem_ty_expr), len),
+            TyExprKind::Var {ident, ..} => ident.to_string(),
+            TyExprKind::Lit {ty_lit, ..} => ty_lit.to_string(),
+        }
+    }
+    decl.params.iter().filter( | param | param.ident != Ident::new("self")).map( | param | ty_name(&param.ty_expr)).collect()
 }
 
 #[derive(Debug)]
@@ -177,6 +227,7 @@ impl<'a,'b> ShaderAnalyser<'a,'b> {
                     message: String::from(
                         "attribute must be either a floating-point scalar or vector or mat4",
                     ),
+                    ..LiveError::default()
                 })
             }
         }
@@ -229,6 +280,7 @@ impl<'a,'b> ShaderAnalyser<'a,'b> {
                         message: String::from(
                             "function `vertex` must return a value of type `vec4`",
                         ),
+                        ..LiveError::default()
                     })
                 }
             }
@@ -241,6 +293,7 @@ impl<'a,'b> ShaderAnalyser<'a,'b> {
                         message: String::from(
                             "function `fragment` must return a value of type `vec4`",
                         ),
+                        ..LiveError::default()
                     })
                 }
             }
@@ -250,13 +303,14 @@ impl<'a,'b> ShaderAnalyser<'a,'b> {
                     return Err(LiveError {
                         span: decl.span,
                         message: String::from("functions can't return arrays"),
+                        ..LiveError::default()
                     })
                 }
                 _ => {}
             }
         }
         *decl.return_ty.borrow_mut() = Some(return_ty);
-        self.env.insert_sym(decl.span, decl.ident_path, Sym::Fn).ok();
+        self.env.insert_sym(decl.span, decl.source_ident_path, Sym::Fn).ok();
         Ok(())
     }
     
@@ -271,6 +325,7 @@ impl<'a,'b> ShaderAnalyser<'a,'b> {
                     message: String::from(
                         "attribute must be either a floating-point scalar or vector or mat4",
                     ),
+                    ..LiveError::default()
                 })
             }
         }
@@ -301,11 +356,12 @@ impl<'a,'b> ShaderAnalyser<'a,'b> {
     fn analyse_texture_decl(&mut self, decl: &TextureDecl) -> Result<(), LiveError> {
         let ty = self.ty_checker().ty_check_ty_expr(&decl.ty_expr) ?;
         match ty {
-            Ty::Texture2D => {}
+            Ty::Texture2D | Ty::TextureCube | Ty::Texture3D => {}
             _ => {
                 return Err(LiveError {
                     span: decl.span,
-                    message: String::from("texture must be a texture2D"),
+                    message: String::from("texture must be a texture2D, textureCube or texture3D"),
+                    ..LiveError::default()
                 })
             }
         }
@@ -343,6 +399,7 @@ impl<'a,'b> ShaderAnalyser<'a,'b> {
                     message: String::from(
                         "varying must be either a floating-point scalar or vector",
                     ),
+                    ..LiveError::default()
                 })
             }
         }
@@ -376,6 +433,7 @@ impl<'a,'b> ShaderAnalyser<'a,'b> {
                 return Err(LiveError {
                     span: decl.span,
                     message: format!("function `{}` recursively calls `{}`", decl.ident_path, callee),
+                    ..LiveError::default()
                 });
             }
             self.analyse_call_tree(kind, call_stack, callee_decl) ?;
@@ -434,6 +492,15 @@ impl<'a,'b> ShaderAnalyser<'a,'b> {
                     .iter()
                     .cloned(),
             );
+            decl.int_op_fn_deps.borrow_mut().as_mut().unwrap().extend(
+                callee_decl
+                    .int_op_fn_deps
+                    .borrow()
+                    .as_ref()
+                    .unwrap()
+                    .iter()
+                    .cloned(),
+            );
         }
         if decl.is_used_in_vertex_shader.get().unwrap()
             && decl.is_used_in_fragment_shader.get().unwrap()
@@ -445,6 +512,7 @@ impl<'a,'b> ShaderAnalyser<'a,'b> {
                         "function `{}` can't access any geometries, since it's used in both the vertex and fragment shader",
                         decl.ident_path
                     ),
+                    ..LiveError::default()
                 });
             }
             if !decl.instance_deps.borrow().as_ref().unwrap().is_empty() {
@@ -454,6 +522,7 @@ impl<'a,'b> ShaderAnalyser<'a,'b> {
                         "function `{}` can't access any instances, since it's used in both the vertex and fragment shader",
                         decl.ident_path
                     ),
+                    ..LiveError::default()
                 });
             }
             if decl.has_varying_deps.get().unwrap() {
@@ -463,6 +532,7 @@ impl<'a,'b> ShaderAnalyser<'a,'b> {
                         "function `{}` can't access any varyings, since it's used in both the vertex and fragment shader",
                         decl.ident_path
                     ),
+                    ..LiveError::default()
                 });
             }
         }
@@ -540,6 +610,7 @@ impl<'a,'b> FnDefAnalyser<'a,'b> {
         self.decl.has_varying_deps.set(Some(false));
         *self.decl.builtin_deps.borrow_mut() = Some(BTreeSet::new());
         *self.decl.cons_fn_deps.borrow_mut() = Some(BTreeSet::new());
+        *self.decl.int_op_fn_deps.borrow_mut() = Some(BTreeSet::new());
         self.analyse_block(&self.decl.block) ?;
         self.env.pop_scope();
         Ok(())
@@ -562,8 +633,20 @@ impl<'a,'b> FnDefAnalyser<'a,'b> {
                 ref from_expr,
                 ref to_expr,
                 ref step_expr,
+                ref max_expr,
+                ref block,
+            } => self.analyse_for_stmt(span, ident, from_expr, to_expr, step_expr, max_expr, block),
+            Stmt::Loop {
+                span,
+                ref max_expr,
                 ref block,
-            } => self.analyse_for_stmt(span, ident, from_expr, to_expr, step_expr, block),
+            } => self.analyse_loop_stmt(span, max_expr, block),
+            Stmt::While {
+                span,
+                ref expr,
+                ref max_expr,
+                ref block,
+            } => self.analyse_while_stmt(span, expr, max_expr, block),
             Stmt::If {
                 span,
                 ref expr,
@@ -588,6 +671,7 @@ impl<'a,'b> FnDefAnalyser<'a,'b> {
             return Err(LiveError {
                 span,
                 message: String::from("break outside loop"),
+                ..LiveError::default()
             } .into());
         }
         Ok(())
@@ -598,6 +682,7 @@ impl<'a,'b> FnDefAnalyser<'a,'b> {
             return Err(LiveError {
                 span,
                 message: String::from("continue outside loop"),
+                ..LiveError::default()
             } .into());
         }
         Ok(())
@@ -610,24 +695,11 @@ impl<'a,'b> FnDefAnalyser<'a,'b> {
         from_expr: &Expr,
         to_expr: &Expr,
         step_expr: &Option<Expr>,
+        max_expr: &Option<Expr>,
         block: &Block,
     ) -> Result<(), LiveError> {
-        self.ty_checker()
-            .ty_check_expr_with_expected_ty(span, from_expr, &Ty::Int) ?;
-        let from = self
-        .const_evaluator()
-            .const_eval_expr(from_expr) ?
-        .to_int()
-            .unwrap();
-        self.dep_analyser().dep_analyse_expr(from_expr);
-        self.ty_checker()
-            .ty_check_expr_with_expected_ty(span, to_expr, &Ty::Int) ?;
-        let to = self
-        .const_evaluator()
-            .const_eval_expr(to_expr) ?
-        .to_int()
-            .unwrap();
-        self.dep_analyser().dep_analyse_expr(to_expr);
+        let from = self.analyse_for_bound(span, from_expr) ?;
+        let to = self.analyse_for_bound(span, to_expr) ?;
         if let Some(step_expr) = step_expr {
             self.ty_checker()
                 .ty_check_expr_with_expected_ty(span, step_expr, &Ty::Int) ?;
@@ -640,22 +712,28 @@ impl<'a,'b> FnDefAnalyser<'a,'b> {
                 return Err(LiveError {
                     span,
                     message: String::from("step must not be zero"),
+                    ..LiveError::default()
                 } .into());
             }
-            if from < to && step < 0 {
-                return Err(LiveError {
-                    span,
-                    message: String::from("step must not be positive"),
-                } .into());
-            }
-            if from > to && step > 0 {
-                return Err(LiveError {
-                    span,
-                    message: String::from("step must not be negative"),
-                } .into());
+            if let (Some(from), Some(to)) = (from, to) {
+                if from < to && step < 0 {
+                    return Err(LiveError {
+                        span,
+                        message: String::from("step must not be positive"),
+                        ..LiveError::default()
+                    });
+                }
+                if from > to && step > 0 {
+                    return Err(LiveError {
+                        span,
+                        message: String::from("step must not be negative"),
+                        ..LiveError::default()
+                    });
+                }
             }
             self.dep_analyser().dep_analyse_expr(step_expr);
         }
+        self.analyse_max_expr(span, max_expr) ?;
         self.env.push_scope();
         self.env.insert_sym(
             span,
@@ -666,11 +744,82 @@ impl<'a,'b> FnDefAnalyser<'a,'b> {
                 kind: VarKind::Local,
             },
         ) ?;
+        self.analyse_loop_block(block) ?;
+        self.env.pop_scope();
+        Ok(())
+    }
+    
+    // for bounds don't have to be const, the ones that aren't are counted up
+    // unless the step says otherwise
+    fn analyse_for_bound(&mut self, span: Span, expr: &Expr) -> Result<Option<i32>, LiveError> {
+        self.ty_checker()
+            .ty_check_expr_with_expected_ty(span, expr, &Ty::Int) ?;
+        let val = self.const_evaluator().try_const_eval_expr(expr);
+        if val.is_none() {
+            self.const_gatherer().const_gather_expr(expr);
+        }
+        self.dep_analyser().dep_analyse_expr(expr);
+        Ok(val.map( | val | val.to_int().unwrap()))
+    }
+    
+    fn analyse_loop_stmt(
+        &mut self,
+        span: Span,
+        max_expr: &Option<Expr>,
+        block: &Block,
+    ) -> Result<(), LiveError> {
+        self.analyse_max_expr(span, max_expr) ?;
+        self.env.push_scope();
+        self.analyse_loop_block(block) ?;
+        self.env.pop_scope();
+        Ok(())
+    }
+    
+    fn analyse_while_stmt(
+        &mut self,
+        span: Span,
+        expr: &Expr,
+        max_expr: &Option<Expr>,
+        block: &Block,
+    ) -> Result<(), LiveError> {
+        self.ty_checker()
+            .ty_check_expr_with_expected_ty(span, expr, &Ty::Bool) ?;
+        self.const_evaluator().try_const_eval_expr(expr);
+        self.const_gatherer().const_gather_expr(expr);
+        self.dep_analyser().dep_analyse_expr(expr);
+        self.analyse_max_expr(span, max_expr) ?;
+        self.env.push_scope();
+        self.analyse_loop_block(block) ?;
+        self.env.pop_scope();
+        Ok(())
+    }
+    
+    fn analyse_max_expr(&mut self, span: Span, max_expr: &Option<Expr>) -> Result<(), LiveError> {
+        if let Some(max_expr) = max_expr {
+            self.ty_checker()
+                .ty_check_expr_with_expected_ty(span, max_expr, &Ty::Int) ?;
+            let max = self
+            .const_evaluator()
+                .const_eval_expr(max_expr) ?
+            .to_int()
+                .unwrap();
+            if max <= 0 {
+                return Err(LiveError {
+                    span,
+                    message: String::from("max must be positive"),
+                    ..LiveError::default()
+                });
+            }
+            self.dep_analyser().dep_analyse_expr(max_expr);
+        }
+        Ok(())
+    }
+    
+    fn analyse_loop_block(&mut self, block: &Block) -> Result<(), LiveError> {
         let was_inside_loop = self.is_inside_loop;
         self.is_inside_loop = true;
         self.analyse_block(block) ?;
         self.is_inside_loop = was_inside_loop;
-        self.env.pop_scope();
         Ok(())
     }
     
@@ -721,7 +870,8 @@ impl<'a,'b> FnDefAnalyser<'a,'b> {
             if ty == Ty::Void {
                 return Err(LiveError {
                     span,
-                    message: String::from("init expression cannot be void")
+                    message: String::from("init expression cannot be void"),
+                    ..LiveError::default()
                 });
             }
             self.const_evaluator().try_const_eval_expr(expr);
@@ -732,6 +882,7 @@ impl<'a,'b> FnDefAnalyser<'a,'b> {
             return Err(LiveError {
                 span,
                 message: format!("can't infer type of variable `{}`", ident),
+                ..LiveError::default()
             });
         });
         self.env.insert_sym(
@@ -762,6 +913,7 @@ impl<'a,'b> FnDefAnalyser<'a,'b> {
             return Err(LiveError {
                 span,
                 message: String::from("missing return expression"),
+                ..LiveError::default()
             } .into());
         }
         Ok(())
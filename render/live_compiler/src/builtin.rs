@@ -88,7 +88,14 @@ pub fn generate_builtins() -> HashMap<Ident, Builtin> {
             (Ty::Vec4, Ty::Vec4, Ty::Vec4) -> Ty::Vec4,
             (Ty::Vec2, Ty::Float, Ty::Float) -> Ty::Vec2,
             (Ty::Vec3, Ty::Float, Ty::Float) -> Ty::Vec3,
-            (Ty::Vec4, Ty::Float, Ty::Float) -> Ty::Vec4
+            (Ty::Vec4, Ty::Float, Ty::Float) -> Ty::Vec4,
+            (Ty::Uint, Ty::Uint, Ty::Uint) -> Ty::Uint,
+            (Ty::Uvec2, Ty::Uvec2, Ty::Uvec2) -> Ty::Uvec2,
+            (Ty::Uvec3, Ty::Uvec3, Ty::Uvec3) -> Ty::Uvec3,
+            (Ty::Uvec4, Ty::Uvec4, Ty::Uvec4) -> Ty::Uvec4,
+            (Ty::Uvec2, Ty::Uint, Ty::Uint) -> Ty::Uvec2,
+            (Ty::Uvec3, Ty::Uint, Ty::Uint) -> Ty::Uvec3,
+            (Ty::Uvec4, Ty::Uint, Ty::Uint) -> Ty::Uvec4
         ]),
         builtin!(cos, [
             (Ty::Float) -> Ty::Float,
@@ -138,7 +145,10 @@ pub fn generate_builtins() -> HashMap<Ident, Builtin> {
             (Ty::Ivec4, Ty::Ivec4) -> Ty::Bvec4,
             (Ty::Vec2, Ty::Vec2) -> Ty::Bvec2,
             (Ty::Vec3, Ty::Vec3) -> Ty::Bvec3,
-            (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4
+            (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4,
+            (Ty::Uvec2, Ty::Uvec2) -> Ty::Bvec2,
+            (Ty::Uvec3, Ty::Uvec3) -> Ty::Bvec3,
+            (Ty::Uvec4, Ty::Uvec4) -> Ty::Bvec4
         ]),
         builtin!(exp, [
             (Ty::Float) -> Ty::Float,
@@ -176,7 +186,10 @@ pub fn generate_builtins() -> HashMap<Ident, Builtin> {
             (Ty::Ivec4, Ty::Ivec4) -> Ty::Bvec4,
             (Ty::Vec2, Ty::Vec2) -> Ty::Bvec2,
             (Ty::Vec3, Ty::Vec3) -> Ty::Bvec3,
-            (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4
+            (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4,
+            (Ty::Uvec2, Ty::Uvec2) -> Ty::Bvec2,
+            (Ty::Uvec3, Ty::Uvec3) -> Ty::Bvec3,
+            (Ty::Uvec4, Ty::Uvec4) -> Ty::Bvec4
         ]),
         builtin!(greaterThanEqual, [
             (Ty::Ivec2, Ty::Ivec2) -> Ty::Bvec2,
@@ -184,7 +197,10 @@ pub fn generate_builtins() -> HashMap<Ident, Builtin> {
             (Ty::Ivec4, Ty::Ivec4) -> Ty::Bvec4,
             (Ty::Vec2, Ty::Vec2) -> Ty::Bvec2,
             (Ty::Vec3, Ty::Vec3) -> Ty::Bvec3,
-            (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4
+            (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4,
+            (Ty::Uvec2, Ty::Uvec2) -> Ty::Bvec2,
+            (Ty::Uvec3, Ty::Uvec3) -> Ty::Bvec3,
+            (Ty::Uvec4, Ty::Uvec4) -> Ty::Bvec4
         ]),
         builtin!(inversesqrt, [
             (Ty::Float) -> Ty::Float,
@@ -207,7 +223,10 @@ pub fn generate_builtins() -> HashMap<Ident, Builtin> {
             (Ty::Ivec4, Ty::Ivec4) -> Ty::Bvec4,
             (Ty::Vec2, Ty::Vec2) -> Ty::Bvec2,
             (Ty::Vec3, Ty::Vec3) -> Ty::Bvec3,
-            (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4
+            (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4,
+            (Ty::Uvec2, Ty::Uvec2) -> Ty::Bvec2,
+            (Ty::Uvec3, Ty::Uvec3) -> Ty::Bvec3,
+            (Ty::Uvec4, Ty::Uvec4) -> Ty::Bvec4
         ]),
         builtin!(lessThanEqual, [
             (Ty::Ivec2, Ty::Ivec2) -> Ty::Bvec2,
@@ -215,7 +234,10 @@ pub fn generate_builtins() -> HashMap<Ident, Builtin> {
             (Ty::Ivec4, Ty::Ivec4) -> Ty::Bvec4,
             (Ty::Vec2, Ty::Vec2) -> Ty::Bvec2,
             (Ty::Vec3, Ty::Vec3) -> Ty::Bvec3,
-            (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4
+            (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4,
+            (Ty::Uvec2, Ty::Uvec2) -> Ty::Bvec2,
+            (Ty::Uvec3, Ty::Uvec3) -> Ty::Bvec3,
+            (Ty::Uvec4, Ty::Uvec4) -> Ty::Bvec4
         ]),
         builtin!(log, [
             (Ty::Float) -> Ty::Float,
@@ -241,7 +263,14 @@ pub fn generate_builtins() -> HashMap<Ident, Builtin> {
             (Ty::Vec4, Ty::Vec4) -> Ty::Vec4,
             (Ty::Vec2, Ty::Float) -> Ty::Vec2,
             (Ty::Vec3, Ty::Float) -> Ty::Vec3,
-            (Ty::Vec4, Ty::Float) -> Ty::Vec4
+            (Ty::Vec4, Ty::Float) -> Ty::Vec4,
+            (Ty::Uint, Ty::Uint) -> Ty::Uint,
+            (Ty::Uvec2, Ty::Uvec2) -> Ty::Uvec2,
+            (Ty::Uvec3, Ty::Uvec3) -> Ty::Uvec3,
+            (Ty::Uvec4, Ty::Uvec4) -> Ty::Uvec4,
+            (Ty::Uvec2, Ty::Uint) -> Ty::Uvec2,
+            (Ty::Uvec3, Ty::Uint) -> Ty::Uvec3,
+            (Ty::Uvec4, Ty::Uint) -> Ty::Uvec4
         ]),
         builtin!(min, [
             (Ty::Float, Ty::Float) -> Ty::Float,
@@ -250,7 +279,14 @@ pub fn generate_builtins() -> HashMap<Ident, Builtin> {
             (Ty::Vec4, Ty::Vec4) -> Ty::Vec4,
             (Ty::Vec2, Ty::Float) -> Ty::Vec2,
             (Ty::Vec3, Ty::Float) -> Ty::Vec3,
-            (Ty::Vec4, Ty::Float) -> Ty::Vec4
+            (Ty::Vec4, Ty::Float) -> Ty::Vec4,
+            (Ty::Uint, Ty::Uint) -> Ty::Uint,
+            (Ty::Uvec2, Ty::Uvec2) -> Ty::Uvec2,
+            (Ty::Uvec3, Ty::Uvec3) -> Ty::Uvec3,
+            (Ty::Uvec4, Ty::Uvec4) -> Ty::Uvec4,
+            (Ty::Uvec2, Ty::Uint) -> Ty::Uvec2,
+            (Ty::Uvec3, Ty::Uint) -> Ty::Uvec3,
+            (Ty::Uvec4, Ty::Uint) -> Ty::Uvec4
         ]),
         builtin!(mix, [
             (Ty::Float, Ty::Float, Ty::Float) -> Ty::Float,
@@ -290,7 +326,10 @@ pub fn generate_builtins() -> HashMap<Ident, Builtin> {
             (Ty::Ivec4, Ty::Ivec4) -> Ty::Bvec4,
             (Ty::Vec2, Ty::Vec2) -> Ty::Bvec2,
             (Ty::Vec3, Ty::Vec3) -> Ty::Bvec3,
-            (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4
+            (Ty::Vec4, Ty::Vec4) -> Ty::Bvec4,
+            (Ty::Uvec2, Ty::Uvec2) -> Ty::Bvec2,
+            (Ty::Uvec3, Ty::Uvec3) -> Ty::Bvec3,
+            (Ty::Uvec4, Ty::Uvec4) -> Ty::Bvec4
         ]),
         builtin!(pow, [
             (Ty::Float, Ty::Float) -> Ty::Float,
@@ -319,6 +358,12 @@ pub fn generate_builtins() -> HashMap<Ident, Builtin> {
         builtin!(sample2d, [
             (Ty::Texture2D, Ty::Vec2) -> Ty::Vec4
         ]),
+        builtin!(sample3d, [
+            (Ty::Texture3D, Ty::Vec3) -> Ty::Vec4
+        ]),
+        builtin!(sample_cube, [
+            (Ty::TextureCube, Ty::Vec3) -> Ty::Vec4
+        ]),
         builtin!(sign, [
             (Ty::Float) -> Ty::Float,
             (Ty::Vec2) -> Ty::Vec2,
@@ -19,6 +19,7 @@ impl<'a> ConstEvaluator<'a> {
         self.try_const_eval_expr(expr).ok_or_else(|| LiveError {
             span: expr.span,
             message: String::from("expression is not const"),
+            ..LiveError::default()
         })
     }
 
@@ -125,55 +126,98 @@ impl<'a> ConstEvaluator<'a> {
             BinOp::Eq => match (&left_val, &right_val) {
                 (Val::Bool(x), Val::Bool(y)) => Some(Val::Bool(x == y)),
                 (Val::Int(x), Val::Int(y)) => Some(Val::Bool(x == y)),
+                (Val::Uint(x), Val::Uint(y)) => Some(Val::Bool(x == y)),
                 (Val::Float(x), Val::Float(y)) => Some(Val::Bool(x == y)),
                 _ => None,
             },
             BinOp::Ne => match (&left_val, &right_val) {
                 (Val::Bool(x), Val::Bool(y)) => Some(Val::Bool(x != y)),
                 (Val::Int(x), Val::Int(y)) => Some(Val::Bool(x != y)),
+                (Val::Uint(x), Val::Uint(y)) => Some(Val::Bool(x != y)),
                 (Val::Float(x), Val::Float(y)) => Some(Val::Bool(x != y)),
                 _ => None,
             },
             BinOp::Lt => match (&left_val, &right_val) {
                 (Val::Int(x), Val::Int(y)) => Some(Val::Bool(x < y)),
+                (Val::Uint(x), Val::Uint(y)) => Some(Val::Bool(x < y)),
                 (Val::Float(x), Val::Float(y)) => Some(Val::Bool(x < y)),
                 _ => None,
             },
             BinOp::Le => match (&left_val, &right_val) {
                 (Val::Int(x), Val::Int(y)) => Some(Val::Bool(x <= y)),
+                (Val::Uint(x), Val::Uint(y)) => Some(Val::Bool(x <= y)),
                 (Val::Float(x), Val::Float(y)) => Some(Val::Bool(x <= y)),
                 _ => None,
             },
             BinOp::Gt => match (&left_val, &right_val) {
                 (Val::Int(x), Val::Int(y)) => Some(Val::Bool(x > y)),
+                (Val::Uint(x), Val::Uint(y)) => Some(Val::Bool(x > y)),
                 (Val::Float(x), Val::Float(y)) => Some(Val::Bool(x > y)),
                 _ => None,
             },
             BinOp::Ge => match (&left_val, &right_val) {
                 (Val::Int(x), Val::Int(y)) => Some(Val::Bool(x >= y)),
+                (Val::Uint(x), Val::Uint(y)) => Some(Val::Bool(x >= y)),
                 (Val::Float(x), Val::Float(y)) => Some(Val::Bool(x >= y)),
                 _ => None,
             },
             BinOp::Add => match (&left_val, &right_val) {
                 (Val::Int(x), Val::Int(y)) => Some(Val::Int(x + y)),
+                (Val::Uint(x), Val::Uint(y)) => Some(Val::Uint(x.wrapping_add(*y))),
                 (Val::Float(x), Val::Float(y)) => Some(Val::Float(x + y)),
                 _ => None,
             },
             BinOp::Sub => match (&left_val, &right_val) {
                 (Val::Int(x), Val::Int(y)) => Some(Val::Int(x - y)),
+                (Val::Uint(x), Val::Uint(y)) => Some(Val::Uint(x.wrapping_sub(*y))),
                 (Val::Float(x), Val::Float(y)) => Some(Val::Float(x - y)),
                 _ => None,
             },
             BinOp::Mul => match (&left_val, &right_val) {
                 (Val::Int(x), Val::Int(y)) => Some(Val::Int(x * y)),
+                (Val::Uint(x), Val::Uint(y)) => Some(Val::Uint(x.wrapping_mul(*y))),
                 (Val::Float(x), Val::Float(y)) => Some(Val::Float(x * y)),
                 _ => None,
             },
             BinOp::Div => match (&left_val, &right_val) {
-                (Val::Int(x), Val::Int(y)) => Some(Val::Int(x / y)),
+                (Val::Int(x), Val::Int(y)) => x.checked_div(*y).map(Val::Int),
+                (Val::Uint(x), Val::Uint(y)) => x.checked_div(*y).map(Val::Uint),
                 (Val::Float(x), Val::Float(y)) => Some(Val::Float(x / y)),
                 _ => None,
             },
+            BinOp::Rem => match (&left_val, &right_val) {
+                (Val::Int(x), Val::Int(y)) => x.checked_rem(*y).map(Val::Int),
+                (Val::Uint(x), Val::Uint(y)) => x.checked_rem(*y).map(Val::Uint),
+                _ => None,
+            },
+            BinOp::BitOr => match (&left_val, &right_val) {
+                (Val::Int(x), Val::Int(y)) => Some(Val::Int(x | y)),
+                (Val::Uint(x), Val::Uint(y)) => Some(Val::Uint(x | y)),
+                _ => None,
+            },
+            BinOp::BitXor => match (&left_val, &right_val) {
+                (Val::Int(x), Val::Int(y)) => Some(Val::Int(x ^ y)),
+                (Val::Uint(x), Val::Uint(y)) => Some(Val::Uint(x ^ y)),
+                _ => None,
+            },
+            BinOp::BitAnd => match (&left_val, &right_val) {
+                (Val::Int(x), Val::Int(y)) => Some(Val::Int(x & y)),
+                (Val::Uint(x), Val::Uint(y)) => Some(Val::Uint(x & y)),
+                _ => None,
+            },
+            // shifting by a negative amount or by the width of an int is left to the target
+            BinOp::Shl => match (&left_val, &right_val) {
+                (Val::Int(x), Val::Int(y)) if (0..32).contains(y) => Some(Val::Int(x << y)),
+                (Val::Uint(x), Val::Int(y)) if (0..32).contains(y) => Some(Val::Uint(x << y)),
+                (Val::Uint(x), Val::Uint(y)) if *y < 32 => Some(Val::Uint(x << y)),
+                _ => None,
+            },
+            BinOp::Shr => match (&left_val, &right_val) {
+                (Val::Int(x), Val::Int(y)) if (0..32).contains(y) => Some(Val::Int(x >> y)),
+                (Val::Uint(x), Val::Int(y)) if (0..32).contains(y) => Some(Val::Uint(x >> y)),
+                (Val::Uint(x), Val::Uint(y)) if *y < 32 => Some(Val::Uint(x >> y)),
+                _ => None,
+            },
             _ => None,
         }
     }
@@ -187,6 +231,8 @@ impl<'a> ConstEvaluator<'a> {
         match op {
             UnOp::Not => match val {
                 Val::Bool(x) => Some(Val::Bool(!x)),
+                Val::Int(x) => Some(Val::Int(!x)),
+                Val::Uint(x) => Some(Val::Uint(!x)),
                 _ => None,
             },
             UnOp::Neg => match val {
@@ -85,12 +85,24 @@ impl<'a,'b> DepAnalyser<'a,'b> {
     fn dep_analyse_bin_expr(
         &mut self,
         _span: Span,
-        _op: BinOp,
+        op: BinOp,
         left_expr: &Expr,
         right_expr: &Expr,
     ) {
         self.dep_analyse_expr(left_expr);
         self.dep_analyse_expr(right_expr);
+        if let Some(op) = op.to_int_op() {
+            self.decl
+                .int_op_fn_deps
+                .borrow_mut()
+                .as_mut()
+                .unwrap()
+                .insert((
+                    op,
+                    left_expr.ty.borrow().as_ref().unwrap().clone(),
+                    right_expr.ty.borrow().as_ref().unwrap().clone(),
+                ));
+        }
     }
 
     fn dep_analyse_un_expr(&mut self, _span: Span, _op: UnOp, expr: &Expr) {
@@ -139,12 +151,13 @@ impl<'a,'b> DepAnalyser<'a,'b> {
                     .insert(ident_path.get_single().expect("Builtin cant use ::"));
             }
             Sym::Fn => {
+                let callee = self.shader.find_fn_decl_for_call(ident_path, arg_exprs).unwrap();
                 self.decl
                     .callees
                     .borrow_mut()
                     .as_mut()
                     .unwrap()
-                    .insert(ident_path);
+                    .insert(callee.ident_path);
             }
             _ => panic!(),
         }
@@ -94,6 +94,7 @@ impl<'a> DeTokParser for DeTokParserImpl<'a> {
                 end: self.token_with_span.span.end,
             },
             message,
+            ..LiveError::default()
         }
     }
     
@@ -240,6 +241,7 @@ impl SpanTracker {
                 end: parser.token_end(),
             },
             message,
+            ..LiveError::default()
         }
     }
 }
@@ -1,4 +1,4 @@
-use crate::error::LiveError;
+use crate::error::{LiveError, LiveErrorLabel};
 use crate::ident::{IdentPath, QualifiedIdentPath};
 use crate::span::{Span,LiveBodyId};
 use crate::ty::Ty;
@@ -19,8 +19,8 @@ impl<'a> Env<'a> {
 
     pub fn find_sym(&self, ident_path: IdentPath, span:Span) -> Option<Sym> {
         let ret = self.scopes.iter().rev().find_map(|scope| scope.get(&ident_path));
-        if ret.is_some(){
-            return Some(ret.unwrap().clone())
+        if let Some((_, sym)) = ret {
+            return Some(sym.clone())
         }
         // lets look up ident_path in our live_styles
         // we support color and float lookups, and soon animation lookups too.
@@ -42,6 +42,11 @@ impl<'a> Env<'a> {
         return None
     }
 
+    // where a sym was defined, builtins have no span of their own
+    pub fn find_sym_span(&self, ident_path: IdentPath) -> Option<Span> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(&ident_path)).map(|(span, _)| *span).filter(|span| *span != Span::default())
+    }
+
     pub fn qualify_ident_path(&self, live_body_id:LiveBodyId, ident_path:IdentPath)->QualifiedIdentPath{
         ident_path.qualify(&self.live_styles.live_bodies[live_body_id.0].module_path)
     }
@@ -57,12 +62,17 @@ impl<'a> Env<'a> {
     pub fn insert_sym(&mut self, span: Span, ident_path: IdentPath, sym: Sym) -> Result<(), LiveError> {
         match self.scopes.last_mut().unwrap().entry(ident_path) {
             Entry::Vacant(entry) => {
-                entry.insert(sym);
+                entry.insert((span, sym));
                 Ok(())
             }
-            Entry::Occupied(_) => Err(LiveError {
+            Entry::Occupied(entry) => Err(LiveError {
                 span,
                 message: format!("`{}` is already defined in this scope", ident_path),
+                labels: Some(entry.get().0).filter(|prev_span| *prev_span != Span::default()).map(|prev_span| LiveErrorLabel {
+                    span: prev_span,
+                    message: format!("`{}` was first defined here", ident_path),
+                }).into_iter().collect(),
+                ..LiveError::default()
             }),
         }
     }
@@ -76,7 +86,7 @@ pub enum Sym {
     Var { is_mut: bool, ty: Ty, kind: VarKind },
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum VarKind {
     Geometry,
     Const,
@@ -88,4 +98,4 @@ pub enum VarKind {
     LiveStyle
 }
 
-type Scope = HashMap<IdentPath, Sym>;
+type Scope = HashMap<IdentPath, (Span, Sym)>;
@@ -2,10 +2,20 @@ use crate::span::Span;
 //use std::error;
 use std::fmt;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct LiveError {
     pub span: Span,
     pub message: String,
+    // other spans that explain the error, such as where a variable was declared
+    pub labels: Vec<LiveErrorLabel>,
+    pub notes: Vec<String>,
+    pub helps: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LiveErrorLabel {
+    pub span: Span,
+    pub message: String,
 }
 
 //impl error::Error for Error {}
@@ -0,0 +1,850 @@
+// Prints shaders and live bodies back as canonical source, the live_body! counterpart of
+// the editor's rust auto_format. Comments aren't tokens, so they're recovered from the
+// gaps between token spans. Blank lines between items are kept, at most one.
+use crate::detok::DeTokParserImpl;
+use crate::error::LiveError;
+use crate::ident::{Ident, IdentPath, IdentPathWithSpan, QualifiedIdentPath};
+use crate::lex;
+use crate::lit::Lit;
+use crate::livestyles::LiveStyles;
+use crate::shaderast::*;
+use crate::span::LiveBodyId;
+use crate::token::{Token, TokenWithSpan};
+use std::fmt::Write;
+
+// prints the contents of a `Shader { }` block, for instance of an analysed or optimised shader.
+// there is no source to take comments and literal spellings from
+pub fn print_shader(shader: &ShaderAst) -> String {
+    let mut printer = SourcePrinter::new(&[], &[]);
+    printer.write_shader(shader);
+    printer.string
+}
+
+// formats the code of a live body. shaders are parsed and printed from their ast, the other
+// values are reindented and respaced token by token
+pub fn format_live_body(code: &str) -> Result<String, LiveError> {
+    let source = code.chars().collect::<Vec<_ >> ();
+    let tokens = lex::lex(source.iter().cloned(), LiveBodyId(0)).collect::<Result<Vec<_>, _ >> () ?;
+    let mut printer = SourcePrinter::new(&source, &tokens);
+    printer.write_live_body() ?;
+    Ok(printer.string)
+}
+
+struct Comment {
+    start: usize,
+    end: usize,
+    // on the same line as the token before it
+    is_trailing: bool,
+}
+
+struct SourcePrinter<'a> {
+    source: &'a [char],
+    tokens: &'a [TokenWithSpan],
+    // for every opening bracket, the index of its closing one
+    closers: Vec<Option<usize>>,
+    comments: Vec<Comment>,
+    next_comment: usize,
+    line_has_comment: bool,
+    indent_level: usize,
+    string: String,
+}
+
+impl<'a> SourcePrinter<'a> {
+    fn new(source: &'a [char], tokens: &'a [TokenWithSpan]) -> SourcePrinter<'a> {
+        let mut closers = vec![None; tokens.len()];
+        let mut open = Vec::new();
+        let mut comments = Vec::new();
+        let mut prev_end = None;
+        for (index, token) in tokens.iter().enumerate() {
+            match token.token {
+                Token::LeftParen | Token::LeftBracket | Token::LeftBrace => open.push(index),
+                Token::RightParen | Token::RightBracket | Token::RightBrace => {
+                    if let Some(open_index) = open.pop() {
+                        if closing_token(tokens[open_index].token) == Some(token.token) {
+                            closers[open_index] = Some(index);
+                        }
+                    }
+                }
+                _ => ()
+            }
+            // the lexer guarantees the gap holds nothing but whitespace and comments
+            let gap_start = prev_end.unwrap_or(0);
+            let mut i = gap_start;
+            while i < token.span.start {
+                let start = i;
+                match (source[i], source.get(i + 1)) {
+                    ('/', Some('/')) => {
+                        while i < token.span.start && source[i] != '\n' {
+                            i += 1;
+                        }
+                    }
+                    ('/', Some('*')) => {
+                        i += 2;
+                        while i < token.span.start && !(source[i - 2] == '*' && source[i - 1] == '/' && i - 2 > start) {
+                            i += 1;
+                        }
+                    }
+                    _ => {
+                        i += 1;
+                        continue;
+                    }
+                }
+                comments.push(Comment {
+                    start,
+                    end: i,
+                    is_trailing: prev_end.is_some() && !source[gap_start..start].contains(&'\n'),
+                });
+            }
+            prev_end = Some(token.span.end);
+        }
+        SourcePrinter {
+            source,
+            tokens,
+            closers,
+            comments,
+            next_comment: 0,
+            line_has_comment: false,
+            indent_level: 0,
+            string: String::new(),
+        }
+    }
+
+    fn has_source(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    fn source_text(&self, start: usize, end: usize) -> String {
+        self.source[start..end].iter().collect::<String>().trim_end().to_string()
+    }
+
+    // index of the first token at or after pos
+    fn token_index(&self, pos: usize) -> usize {
+        self.tokens.partition_point( | token | token.span.start < pos)
+    }
+
+    // where an item starts that has `count` tokens in front of its span, like the keyword
+    // of a uniform
+    fn start_before(&self, pos: usize, count: usize) -> usize {
+        let index = self.token_index(pos);
+        if index < count {
+            return pos
+        }
+        self.tokens[index - count].span.start
+    }
+
+    // the position of the closing brace of the first block that opens after pos
+    fn block_end(&self, after: usize) -> usize {
+        let index = self.token_index(after);
+        self.tokens[index.min(self.tokens.len())..].iter().position( | token | token.token == Token::LeftBrace)
+            .and_then( | offset | self.closers[index + offset])
+            .map_or(0, | closer | self.tokens[closer].span.start)
+    }
+
+    fn write_indent(&mut self) {
+        for _ in 0..self.indent_level {
+            self.string.push_str("    ");
+        }
+        self.line_has_comment = false;
+    }
+
+    fn has_comments_before(&self, pos: usize) -> bool {
+        self.comments.get(self.next_comment).is_some_and( | comment | comment.start < pos)
+    }
+
+    // comments go on lines of their own, except the ones that trailed a line in the source
+    fn write_comments_before(&mut self, pos: usize) {
+        while self.has_comments_before(pos) {
+            let comment = &self.comments[self.next_comment];
+            let text = self.source_text(comment.start, comment.end);
+            if comment.is_trailing && !self.line_has_comment && self.string.ends_with('\n') {
+                self.string.pop();
+                writeln!(self.string, " {}", text).unwrap();
+            }
+            else {
+                if self.wants_blank_line(comment.start) {
+                    self.string.push('\n');
+                }
+                self.write_indent();
+                writeln!(self.string, "{}", text).unwrap();
+            }
+            self.line_has_comment = true;
+            self.next_comment += 1;
+        }
+    }
+
+    // whether the source has an empty line in front of pos that is worth keeping
+    fn wants_blank_line(&self, pos: usize) -> bool {
+        let after_open = self.string.trim_end_matches('\n').ends_with(['{', '(', '[']);
+        if !self.has_source() || self.string.is_empty() || self.string.ends_with("\n\n") || after_open {
+            return false
+        }
+        let gap = self.source[..pos].iter().rev().take_while( | ch | ch.is_whitespace());
+        gap.filter( | ch | **ch == '\n').count() >= 2
+    }
+
+    fn begin_item(&mut self, start: usize) {
+        self.write_comments_before(start);
+        if self.wants_blank_line(start) {
+            self.string.push('\n');
+        }
+        self.write_indent();
+    }
+
+    fn write_live_body(&mut self) -> Result<(), LiveError> {
+        let mut index = 0;
+        while self.tokens[index].token != Token::Eof {
+            self.begin_item(self.tokens[index].span.start);
+            loop {
+                match self.tokens[index].token {
+                    Token::Ident(_) | Token::PathSep => self.write_token(index),
+                    Token::Colon => break,
+                    token => return Err(self.error(index, format!("expected ident_path, unexpected token `{}`", token)))
+                }
+                index += 1;
+            }
+            self.string.push_str(": ");
+            index += 1;
+            match self.tokens[index].token {
+                Token::Ident(ident) if ident == Ident::new("Shader") || ident == Ident::new("ShaderLib") => {
+                    let shader = DeTokParserImpl::new(&self.tokens[index..], &mut LiveStyles::default())
+                        .parse_shader(QualifiedIdentPath(IdentPath::from_ident(ident))) ?;
+                    let closer = self.closer(index + 1) ?;
+                    write!(self.string, "{} {{", ident).unwrap();
+                    let end = self.tokens[closer].span.start;
+                    let is_empty = shader.decls.is_empty() && shader.uses.is_empty() && shader.default_geometry.is_none() && !shader.debug;
+                    if is_empty && !self.has_comments_before(end) {
+                        self.string.push('}');
+                    }
+                    else {
+                        self.string.push('\n');
+                        self.indent_level += 1;
+                        self.write_shader(&shader);
+                        self.write_comments_before(end);
+                        self.indent_level -= 1;
+                        self.write_indent();
+                        self.string.push('}');
+                    }
+                    index = closer + 1;
+                }
+                Token::Ident(_) => {
+                    let closer = self.closer(index + 1) ?;
+                    self.write_tokens(index, closer);
+                    index = closer + 1;
+                }
+                Token::TyLit(_) => {
+                    let closer = self.closer(index + 1) ?;
+                    self.write_tokens(index, closer);
+                    index = self.expect_semi(closer + 1) ?;
+                }
+                Token::Lit(_) => {
+                    self.write_token(index);
+                    index = self.expect_semi(index + 1) ?;
+                }
+                token => return Err(self.error(index, format!("Unexpected token in parse_live {:?}", token)))
+            }
+            self.string.push('\n');
+        }
+        self.write_comments_before(self.tokens[index].span.start);
+        Ok(())
+    }
+
+    fn closer(&self, index: usize) -> Result<usize, LiveError> {
+        match self.tokens[index].token {
+            Token::LeftParen | Token::LeftBrace | Token::LeftBracket => {
+                self.closers[index].ok_or_else( || self.error(index, format!("unmatched `{}`", self.tokens[index].token)))
+            }
+            token => Err(self.error(index, format!("expected {{ unexpected token `{}`", token)))
+        }
+    }
+
+    fn expect_semi(&mut self, index: usize) -> Result<usize, LiveError> {
+        if self.tokens[index].token != Token::Semi {
+            return Err(self.error(index, format!("expected ; unexpected token `{}`", self.tokens[index].token)))
+        }
+        self.string.push(';');
+        Ok(index + 1)
+    }
+
+    fn error(&self, index: usize, message: String) -> LiveError {
+        LiveError {
+            span: self.tokens[index].span,
+            message,
+            ..LiveError::default()
+        }
+    }
+
+    fn write_token(&mut self, index: usize) {
+        let span = self.tokens[index].span;
+        let text = self.source_text(span.start, span.end);
+        self.string.push_str(&text);
+    }
+
+    // values other than shaders keep their line breaks, but only inside brackets that were
+    // followed by one. the others are printed on a single line
+    fn write_tokens(&mut self, first: usize, last: usize) {
+        let mut is_multi_line = Vec::new();
+        for index in first..=last {
+            let token = self.tokens[index];
+            while self.has_comments_before(token.span.start) {
+                let comment = &self.comments[self.next_comment];
+                let (start, is_line_comment) = (comment.start, self.source[comment.start + 1] == '/');
+                let text = self.source_text(comment.start, comment.end);
+                if self.string.ends_with('\n') {
+                    if self.wants_blank_line(start) {
+                        self.string.push('\n');
+                    }
+                    self.write_indent();
+                }
+                else {
+                    self.string.push(' ');
+                }
+                self.string.push_str(&text);
+                if is_line_comment {
+                    self.string.push('\n');
+                }
+                self.next_comment += 1;
+            }
+            let is_closer = matches!(token.token, Token::RightParen | Token::RightBracket | Token::RightBrace);
+            let line_break = if is_closer {
+                let was_multi_line = is_multi_line.pop().unwrap_or(false);
+                if was_multi_line {
+                    self.indent_level -= 1;
+                }
+                was_multi_line
+            }
+            else {
+                index > first && is_multi_line.last() == Some(&true)
+                    && self.source[self.tokens[index - 1].span.end..token.span.start].contains(&'\n')
+            };
+            if line_break && !self.string.ends_with('\n') {
+                self.string.push('\n');
+            }
+            if self.string.ends_with('\n') {
+                if self.wants_blank_line(token.span.start) && !is_closer {
+                    self.string.push('\n');
+                }
+                self.write_indent();
+            }
+            else if index > first && self.needs_space(index) {
+                self.string.push(' ');
+            }
+            self.write_token(index);
+            if closing_token(token.token).is_some() {
+                let next = &self.tokens[index + 1];
+                let multi_line = self.source[token.span.end..next.span.start].contains(&'\n');
+                if multi_line {
+                    self.indent_level += 1;
+                }
+                is_multi_line.push(multi_line);
+            }
+        }
+    }
+
+    // rust style spacing between a token and the one in front of it
+    fn needs_space(&self, index: usize) -> bool {
+        let token = self.tokens[index].token;
+        let prev = self.tokens[index - 1].token;
+        match token {
+            Token::RightParen | Token::RightBracket | Token::RightBrace => return false,
+            Token::Comma | Token::Semi | Token::Colon | Token::Dot | Token::PathSep => return false,
+            Token::LeftParen | Token::LeftBracket => {
+                if matches!(prev, Token::Ident(_) | Token::TyLit(_) | Token::RightParen | Token::RightBracket) {
+                    return false
+                }
+            }
+            _ => ()
+        }
+        match prev {
+            Token::LeftParen | Token::LeftBracket | Token::LeftBrace => false,
+            Token::Dot | Token::PathSep | Token::Splat | Token::Not => false,
+            // a minus that starts an operand is unary
+            Token::Minus => index < 2 || !matches!(
+                self.tokens[index - 2].token,
+                Token::LeftParen | Token::LeftBracket | Token::LeftBrace | Token::Comma | Token::Colon | Token::Semi
+            ),
+            _ => true
+        }
+    }
+
+    fn write_shader(&mut self, shader: &ShaderAst) {
+        if shader.debug {
+            self.write_indent();
+            self.string.push_str("debug\n");
+        }
+        // the ast keeps uses and the default geometry apart from the decls, put them back
+        // where they were
+        let mut items = Vec::new();
+        for ident_path in &shader.uses {
+            items.push((self.start_before(ident_path.span.start, 1), ShaderItem::Use(ident_path)));
+        }
+        if let Some(ident_path) = &shader.default_geometry {
+            items.push((self.start_before(ident_path.span.start, 2), ShaderItem::DefaultGeometry(ident_path)));
+        }
+        for decl in &shader.decls {
+            let start = match decl {
+                Decl::Geometry(decl) => self.start_before(decl.span.start, 1),
+                Decl::Instance(decl) => self.start_before(decl.span.start, 1),
+                Decl::Texture(decl) => self.start_before(decl.span.start, 1),
+                Decl::Uniform(decl) => self.start_before(decl.span.start, 1),
+                Decl::Varying(decl) => self.start_before(decl.span.start, 1),
+                Decl::Const(decl) => decl.span.start,
+                Decl::Fn(decl) => decl.span.start,
+                Decl::Struct(decl) => decl.span.start,
+            };
+            items.push((start, ShaderItem::Decl(decl)));
+        }
+        if self.has_source() {
+            items.sort_by_key( | (start, _) | *start);
+        }
+        let mut index = 0;
+        let mut prev_is_block = false;
+        while index < items.len() {
+            let (start, item) = items[index];
+            // without a source, blocks get an empty line around them
+            let is_block = matches!(item, ShaderItem::Decl(Decl::Fn(_)) | ShaderItem::Decl(Decl::Struct(_)));
+            if !self.has_source() && index > 0 && (is_block || prev_is_block) {
+                self.string.push('\n');
+            }
+            prev_is_block = is_block;
+            match item {
+                ShaderItem::Use(ident_path) => {
+                    self.begin_item(start);
+                    writeln!(self.string, "use {}::*;", ident_path.ident_path).unwrap();
+                }
+                ShaderItem::DefaultGeometry(ident_path) => {
+                    self.begin_item(start);
+                    writeln!(self.string, "default_geometry: {};", ident_path.ident_path).unwrap();
+                }
+                ShaderItem::Decl(Decl::Fn(decl)) if decl.source_ident_path.len() > 1 => {
+                    let mut decls = vec![decl];
+                    while let Some((_, ShaderItem::Decl(Decl::Fn(next)))) = items.get(index + 1) {
+                        if next.source_ident_path.segs[0] != decl.source_ident_path.segs[0] || self.is_impl_start(next) {
+                            break;
+                        }
+                        decls.push(next);
+                        index += 1;
+                    }
+                    self.write_impl(&decls);
+                }
+                ShaderItem::Decl(decl) => self.write_decl(decl),
+            }
+            index += 1;
+        }
+    }
+
+    fn is_impl_start(&self, decl: &FnDecl) -> bool {
+        let index = self.token_index(decl.span.start);
+        self.has_source() && index >= 3 && self.tokens[index - 3].token == Token::Ident(Ident::new("impl"))
+    }
+
+    fn write_impl(&mut self, decls: &[&FnDecl]) {
+        let (start, end) = if self.is_impl_start(decls[0]) {
+            let index = self.token_index(decls[0].span.start);
+            (self.tokens[index - 3].span.start, self.block_end(self.tokens[index - 3].span.start))
+        }
+        else {
+            (decls[0].span.start, 0)
+        };
+        self.begin_item(start);
+        writeln!(self.string, "impl {} {{", decls[0].source_ident_path.segs[0]).unwrap();
+        self.indent_level += 1;
+        for decl in decls {
+            self.write_fn_decl(decl);
+        }
+        self.write_comments_before(end);
+        self.indent_level -= 1;
+        self.write_indent();
+        self.string.push_str("}\n");
+    }
+
+    fn write_decl(&mut self, decl: &Decl) {
+        match decl {
+            Decl::Geometry(decl) => {
+                self.begin_item(self.start_before(decl.span.start, 1));
+                writeln!(self.string, "geometry {}: {};", decl.ident, TyExprDisplay(&decl.ty_expr)).unwrap();
+            }
+            Decl::Const(decl) => {
+                self.begin_item(decl.span.start);
+                write!(self.string, "const {}: {} = ", decl.ident, TyExprDisplay(&decl.ty_expr)).unwrap();
+                self.write_expr(&decl.expr, 0);
+                self.string.push_str(";\n");
+            }
+            Decl::Fn(decl) => self.write_fn_decl(decl),
+            Decl::Instance(decl) => {
+                self.begin_item(self.start_before(decl.span.start, 1));
+                writeln!(self.string, "instance {}: {};", decl.ident, TyExprDisplay(&decl.ty_expr)).unwrap();
+            }
+            Decl::Struct(decl) => self.write_struct_decl(decl),
+            Decl::Texture(decl) => {
+                self.begin_item(self.start_before(decl.span.start, 1));
+                writeln!(self.string, "texture {}: {};", decl.ident, TyExprDisplay(&decl.ty_expr)).unwrap();
+            }
+            Decl::Uniform(decl) => {
+                self.begin_item(self.start_before(decl.span.start, 1));
+                write!(self.string, "uniform {}: {}", decl.ident, TyExprDisplay(&decl.ty_expr)).unwrap();
+                if let Some(block_ident) = decl.block_ident {
+                    write!(self.string, " in {}", block_ident).unwrap();
+                }
+                self.string.push_str(";\n");
+            }
+            Decl::Varying(decl) => {
+                self.begin_item(self.start_before(decl.span.start, 1));
+                writeln!(self.string, "varying {}: {};", decl.ident, TyExprDisplay(&decl.ty_expr)).unwrap();
+            }
+        }
+    }
+
+    fn write_struct_decl(&mut self, decl: &StructDecl) {
+        self.begin_item(decl.span.start);
+        writeln!(self.string, "struct {} {{", decl.ident).unwrap();
+        // fields have no spans, they start after the brace and after every comma
+        let end = self.block_end(decl.span.start);
+        let field_starts = if self.has_source() {
+            let first = self.token_index(decl.span.start);
+            self.tokens[first..self.token_index(end)].iter().enumerate().filter_map( | (offset, token) | {
+                match token.token {
+                    Token::LeftBrace | Token::Comma => self.tokens.get(first + offset + 1).map( | token | token.span.start),
+                    _ => None
+                }
+            }).collect()
+        }
+        else {
+            vec![0; decl.fields.len()]
+        };
+        self.indent_level += 1;
+        for (index, (field, start)) in decl.fields.iter().zip(field_starts).enumerate() {
+            self.begin_item(start);
+            write!(self.string, "{}: {}", field.ident, TyExprDisplay(&field.ty_expr)).unwrap();
+            if index + 1 < decl.fields.len() {
+                self.string.push(',');
+            }
+            self.string.push('\n');
+        }
+        self.write_comments_before(end);
+        self.indent_level -= 1;
+        self.write_indent();
+        self.string.push_str("}\n");
+    }
+
+    fn write_fn_decl(&mut self, decl: &FnDecl) {
+        self.begin_item(decl.span.start);
+        let path = decl.source_ident_path;
+        write!(self.string, "fn {}(", path.segs[path.len - 1]).unwrap();
+        for (index, param) in decl.params.iter().enumerate() {
+            if index > 0 {
+                self.string.push_str(", ");
+            }
+            if param.is_inout {
+                self.string.push_str("inout ");
+            }
+            if index == 0 && path.len > 1 && param.ident == Ident::new("self") {
+                self.string.push_str("self");
+            }
+            else {
+                write!(self.string, "{}: {}", param.ident, TyExprDisplay(&param.ty_expr)).unwrap();
+            }
+        }
+        self.string.push(')');
+        if let Some(return_ty_expr) = &decl.return_ty_expr {
+            write!(self.string, " -> {}", TyExprDisplay(return_ty_expr)).unwrap();
+        }
+        self.string.push(' ');
+        self.write_block(&decl.block, self.block_end(decl.span.start));
+        self.string.push('\n');
+    }
+
+    fn write_block(&mut self, block: &Block, end: usize) {
+        if block.stmts.is_empty() && !self.has_comments_before(end) {
+            self.string.push_str("{}");
+            return
+        }
+        self.string.push_str("{\n");
+        self.indent_level += 1;
+        for stmt in &block.stmts {
+            self.write_stmt(stmt);
+        }
+        self.write_comments_before(end);
+        self.indent_level -= 1;
+        self.write_indent();
+        self.string.push('}');
+    }
+
+    fn write_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Break {span} => {
+                self.begin_item(span.start);
+                self.string.push_str("break;\n");
+            }
+            Stmt::Continue {span} => {
+                self.begin_item(span.start);
+                self.string.push_str("continue;\n");
+            }
+            Stmt::For {span, ident, from_expr, to_expr, step_expr, max_expr, block} => {
+                self.begin_item(span.start);
+                write!(self.string, "for {} from ", ident).unwrap();
+                self.write_expr(from_expr, 0);
+                self.string.push_str(" to ");
+                self.write_expr(to_expr, 0);
+                let mut header_end = to_expr.span.end;
+                if let Some(step_expr) = step_expr {
+                    self.string.push_str(" step ");
+                    self.write_expr(step_expr, 0);
+                    header_end = step_expr.span.end;
+                }
+                self.write_loop_block(max_expr, header_end, block);
+            }
+            Stmt::Loop {span, max_expr, block} => {
+                self.begin_item(span.start);
+                self.string.push_str("loop");
+                self.write_loop_block(max_expr, span.start, block);
+            }
+            Stmt::While {span, expr, max_expr, block} => {
+                self.begin_item(span.start);
+                self.string.push_str("while ");
+                self.write_expr(expr, 0);
+                self.write_loop_block(max_expr, expr.span.end, block);
+            }
+            Stmt::If {span, expr, block_if_true, block_if_false} => {
+                self.begin_item(span.start);
+                self.write_if_stmt(expr, block_if_true, block_if_false);
+                self.string.push('\n');
+            }
+            Stmt::Let {span, ident, ty_expr, expr, ..} => {
+                self.begin_item(span.start);
+                write!(self.string, "let {}", ident).unwrap();
+                if let Some(ty_expr) = ty_expr {
+                    write!(self.string, ": {}", TyExprDisplay(ty_expr)).unwrap();
+                }
+                if let Some(expr) = expr {
+                    self.string.push_str(" = ");
+                    self.write_expr(expr, 0);
+                }
+                self.string.push_str(";\n");
+            }
+            Stmt::Return {span, expr} => {
+                self.begin_item(span.start);
+                self.string.push_str("return");
+                if let Some(expr) = expr {
+                    self.string.push(' ');
+                    self.write_expr(expr, 0);
+                }
+                self.string.push_str(";\n");
+            }
+            Stmt::Block {span, block} => {
+                self.begin_item(span.start);
+                self.write_block(block, self.block_end(span.start));
+                self.string.push('\n');
+            }
+            Stmt::Expr {span, expr} => {
+                self.begin_item(span.start);
+                self.write_expr(expr, 0);
+                self.string.push_str(";\n");
+            }
+        }
+    }
+
+    fn write_loop_block(&mut self, max_expr: &Option<Expr>, header_end: usize, block: &Block) {
+        let mut header_end = header_end;
+        if let Some(max_expr) = max_expr {
+            self.string.push_str(" max ");
+            self.write_expr(max_expr, 0);
+            header_end = max_expr.span.end;
+        }
+        self.string.push(' ');
+        self.write_block(block, self.block_end(header_end));
+        self.string.push('\n');
+    }
+
+    fn write_if_stmt(&mut self, expr: &Expr, block_if_true: &Block, block_if_false: &Option<Box<Block>>) {
+        self.string.push_str("if ");
+        self.write_expr(expr, 0);
+        self.string.push(' ');
+        let end = self.block_end(expr.span.end);
+        self.write_block(block_if_true, end);
+        if let Some(block_if_false) = block_if_false {
+            self.string.push_str(" else ");
+            // `else if` is parsed as an else block with just the if in it
+            let is_else_if = match block_if_false.stmts.as_slice() {
+                [Stmt::If {..}] if self.has_source() => {
+                    let index = self.token_index(end);
+                    self.tokens.get(index + 2).map( | token | token.token) == Some(Token::If)
+                }
+                [Stmt::If {..}] => true,
+                _ => false
+            };
+            match block_if_false.stmts.as_slice() {
+                [Stmt::If {expr, block_if_true, block_if_false, ..}] if is_else_if => {
+                    self.write_if_stmt(expr, block_if_true, block_if_false)
+                }
+                _ => self.write_block(block_if_false, self.block_end(end + 1)),
+            }
+        }
+    }
+
+    // writes an expression, in parentheses when it binds less tightly than prec
+    fn write_expr(&mut self, expr: &Expr, prec: usize) {
+        let expr_prec = self.expr_prec(expr);
+        if expr_prec < prec {
+            self.string.push('(');
+        }
+        match &expr.kind {
+            ExprKind::Cond {expr, expr_if_true, expr_if_false, ..} => {
+                self.write_expr(expr, COND_PREC + 1);
+                self.string.push_str(" ? ");
+                self.write_expr(expr_if_true, 0);
+                self.string.push_str(" : ");
+                self.write_expr(expr_if_false, COND_PREC);
+            }
+            ExprKind::Bin {op, left_expr, right_expr, ..} => {
+                let op_prec = bin_op_prec(*op);
+                // assignments are right associative, the rest is left associative
+                if op.is_assign() {
+                    self.write_expr(left_expr, COND_PREC);
+                    write!(self.string, " {} ", op).unwrap();
+                    self.write_expr(right_expr, op_prec);
+                }
+                else {
+                    self.write_expr(left_expr, op_prec);
+                    write!(self.string, " {} ", op).unwrap();
+                    self.write_expr(right_expr, op_prec + 1);
+                }
+            }
+            ExprKind::Un {op, expr, ..} => {
+                write!(self.string, "{}", op).unwrap();
+                self.write_expr(expr, UN_PREC);
+            }
+            ExprKind::MethodCall {ident, arg_exprs, ..} => {
+                self.write_expr(&arg_exprs[0], POSTFIX_PREC + 1);
+                write!(self.string, ".{}", ident).unwrap();
+                self.write_arg_exprs(&arg_exprs[1..]);
+            }
+            ExprKind::Field {expr, field_ident, ..} => {
+                self.write_expr(expr, POSTFIX_PREC + 1);
+                write!(self.string, ".{}", field_ident).unwrap();
+            }
+            ExprKind::Index {expr, index_expr, ..} => {
+                self.write_expr(expr, POSTFIX_PREC);
+                self.string.push('[');
+                self.write_expr(index_expr, 0);
+                self.string.push(']');
+            }
+            ExprKind::Call {ident_path, arg_exprs, ..} => {
+                write!(self.string, "{}", ident_path).unwrap();
+                self.write_arg_exprs(arg_exprs);
+            }
+            ExprKind::MacroCall {ident, arg_exprs, ..} => {
+                write!(self.string, "{}!", ident).unwrap();
+                self.write_arg_exprs(arg_exprs);
+            }
+            ExprKind::ConsCall {ty_lit, arg_exprs, ..} => {
+                write!(self.string, "{}", ty_lit).unwrap();
+                self.write_arg_exprs(arg_exprs);
+            }
+            ExprKind::Var {ident_path, ..} => {
+                write!(self.string, "{}", ident_path).unwrap();
+            }
+            ExprKind::Lit {lit, ..} => {
+                if self.has_source() {
+                    let text = self.source_text(expr.span.start, expr.span.end);
+                    self.string.push_str(&text);
+                }
+                else {
+                    self.string.push_str(&lit_to_string(*lit));
+                }
+            }
+        }
+        if expr_prec < prec {
+            self.string.push(')');
+        }
+    }
+
+    fn write_arg_exprs(&mut self, arg_exprs: &[Expr]) {
+        self.string.push('(');
+        for (index, arg_expr) in arg_exprs.iter().enumerate() {
+            if index > 0 {
+                self.string.push_str(", ");
+            }
+            self.write_expr(arg_expr, 0);
+        }
+        self.string.push(')');
+    }
+
+    fn expr_prec(&self, expr: &Expr) -> usize {
+        match &expr.kind {
+            ExprKind::Cond {..} => COND_PREC,
+            ExprKind::Bin {op, ..} => bin_op_prec(*op),
+            ExprKind::Un {..} => UN_PREC,
+            // `1.x` would lex as a float, and `-1.0` is an operand of its own
+            ExprKind::Lit {lit: Lit::Color(_), ..} => POSTFIX_PREC + 1,
+            ExprKind::Lit {..} => POSTFIX_PREC,
+            _ => POSTFIX_PREC + 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ShaderItem<'a> {
+    Use(&'a IdentPathWithSpan),
+    DefaultGeometry(&'a IdentPathWithSpan),
+    Decl(&'a Decl),
+}
+
+const COND_PREC: usize = 2;
+const UN_PREC: usize = 13;
+const POSTFIX_PREC: usize = 14;
+
+// the binding strength of each level of the shader parser's expression grammar
+fn bin_op_prec(op: BinOp) -> usize {
+    match op {
+        BinOp::Or => 3,
+        BinOp::And => 4,
+        BinOp::Eq | BinOp::Ne => 5,
+        BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => 6,
+        BinOp::BitOr => 7,
+        BinOp::BitXor => 8,
+        BinOp::BitAnd => 9,
+        BinOp::Shl | BinOp::Shr => 10,
+        BinOp::Add | BinOp::Sub => 11,
+        BinOp::Mul | BinOp::Div | BinOp::Rem => 12,
+        _ => 1,
+    }
+}
+
+fn closing_token(token: Token) -> Option<Token> {
+    match token {
+        Token::LeftParen => Some(Token::RightParen),
+        Token::LeftBracket => Some(Token::RightBracket),
+        Token::LeftBrace => Some(Token::RightBrace),
+        _ => None
+    }
+}
+
+fn lit_to_string(lit: Lit) -> String {
+    match lit {
+        Lit::Float(lit) => {
+            let string = format!("{}", lit);
+            if string.contains('.') {string} else {format!("{}.0", string)}
+        }
+        Lit::Color(color) => {
+            let byte = | value: f32 | (value * 255.0).round() as u8;
+            let mut string = format!("#{:02X}{:02X}{:02X}", byte(color.r), byte(color.g), byte(color.b));
+            if byte(color.a) != 255 {
+                write!(string, "{:02X}", byte(color.a)).unwrap();
+            }
+            string
+        }
+        lit => format!("{}", lit)
+    }
+}
+
+struct TyExprDisplay<'a>(&'a TyExpr);
+
+impl<'a> std::fmt::Display for TyExprDisplay<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.0.kind {
+            TyExprKind::Array {elem_ty_expr, len, ..} => write!(f, "{}[{}]", TyExprDisplay(elem_ty_expr), len),
+            TyExprKind::Var {ident, ..} => write!(f, "{}", ident),
+            TyExprKind::Lit {ty_lit, ..} => write!(f, "{}", ty_lit),
+        }
+    }
+}
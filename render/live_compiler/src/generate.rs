@@ -40,11 +40,88 @@ pub trait BackendWriter {
     
     fn needs_unpack_for_matrix_multiplication(&self) -> bool;
     
+    fn needs_fn_for_int_ops(&self) -> bool;
+    
+    fn needs_const_loop_bounds(&self) -> bool;
+    
+    fn needs_int_for_uint(&self) -> bool;
+    
+    fn needs_assign_as_stmt(&self) -> bool;
+    
+    fn needs_select_for_cond_expr(&self) -> bool;
+    
+    fn needs_uint_shift_amount(&self) -> bool;
+    
+    fn needs_operators_for_relational_fns(&self) -> bool;
+    
+    fn needs_splat_for_scalar_args(&self) -> bool;
+    
+    fn needs_block_for_if_body(&self) -> bool;
+    
+    fn needs_pointer_for_inout(&self) -> bool;
+    
     fn const_table_is_vec4(&self) -> bool;
     
     fn use_cons_fn(&self, what: &str) -> bool;
 }
 
+// the function a backend without integer operators calls instead, one per BinOp::to_int_op
+pub fn int_op_fn_name(op: BinOp) -> &'static str {
+    match op {
+        BinOp::Rem => "mpsc_rem",
+        BinOp::BitAnd => "mpsc_bit_and",
+        BinOp::BitOr => "mpsc_bit_or",
+        BinOp::BitXor => "mpsc_bit_xor",
+        BinOp::Shl => "mpsc_shl",
+        BinOp::Shr => "mpsc_shr",
+        _ => panic!("{} is not an integer operator", op),
+    }
+}
+
+// the iterations a loop without a max gets where loops need a const bound
+pub const DEFAULT_LOOP_MAX: i32 = 1024;
+
+fn const_int(expr: &Expr) -> Option<i32> {
+    expr.const_val.borrow().as_ref().unwrap().as_ref().map( | val | val.to_int().unwrap())
+}
+
+fn uint_to_float_ty_lit(ty: &Ty) -> Option<TyLit> {
+    match ty {
+        Ty::Uint => Some(TyLit::Float),
+        Ty::Uvec2 => Some(TyLit::Vec2),
+        Ty::Uvec3 => Some(TyLit::Vec3),
+        Ty::Uvec4 => Some(TyLit::Vec4),
+        _ => None,
+    }
+}
+
+fn unsigned_ty_lit(ty: &Ty) -> TyLit {
+    match ty {
+        Ty::Int | Ty::Uint => TyLit::Uint,
+        Ty::Ivec2 | Ty::Uvec2 => TyLit::Uvec2,
+        Ty::Ivec3 | Ty::Uvec3 => TyLit::Uvec3,
+        Ty::Ivec4 | Ty::Uvec4 => TyLit::Uvec4,
+        _ => panic!("{} can't be shifted", ty),
+    }
+}
+
+fn relational_fn_op(ident: Ident) -> Option<&'static str> {
+    ident.with( | ident_string | match ident_string {
+        "equal" => Some("=="),
+        "notEqual" => Some("!="),
+        "lessThan" => Some("<"),
+        "lessThanEqual" => Some("<="),
+        "greaterThan" => Some(">"),
+        "greaterThanEqual" => Some(">="),
+        _ => None,
+    })
+}
+
+// the builtins that take a scalar in place of a vector
+fn has_splat_scalar_args(ident: Ident) -> bool {
+    ident.with( | ident_string | matches!(ident_string, "clamp" | "max" | "min" | "mod" | "smoothstep" | "step"))
+}
+
 pub struct BlockGenerator<'a> {
     pub shader: &'a ShaderAst,
     pub decl: &'a FnDecl,
@@ -81,8 +158,20 @@ impl<'a> BlockGenerator<'a> {
                 ref from_expr,
                 ref to_expr,
                 ref step_expr,
+                ref max_expr,
+                ref block,
+            } => self.generate_for_stmt(span, ident, from_expr, to_expr, step_expr, max_expr, block),
+            Stmt::Loop {
+                span,
+                ref max_expr,
                 ref block,
-            } => self.generate_for_stmt(span, ident, from_expr, to_expr, step_expr, block),
+            } => self.generate_loop_stmt(span, max_expr, block),
+            Stmt::While {
+                span,
+                ref expr,
+                ref max_expr,
+                ref block,
+            } => self.generate_while_stmt(span, expr, max_expr, block),
             Stmt::If {
                 span,
                 ref expr,
@@ -117,54 +206,147 @@ impl<'a> BlockGenerator<'a> {
         from_expr: &Expr,
         to_expr: &Expr,
         step_expr: &Option<Expr>,
+        max_expr: &Option<Expr>,
         block: &Block,
     ) {
-        let from = from_expr
-            .const_val
-            .borrow()
-            .as_ref()
-            .unwrap()
-            .as_ref()
-            .unwrap()
-            .to_int()
-            .unwrap();
-        let to = to_expr
-            .const_val
-            .borrow()
-            .as_ref()
-            .unwrap()
-            .as_ref()
-            .unwrap()
-            .to_int()
-            .unwrap();
+        let from = const_int(from_expr);
+        let to = const_int(to_expr);
         let step = if let Some(step_expr) = step_expr {
-            step_expr
-                .const_val
-                .borrow()
-                .as_ref()
-                .unwrap()
-                .as_ref()
-                .unwrap()
-                .to_int()
-                .unwrap()
-        } else if from < to {
-            1
-        } else {
+            const_int(step_expr).unwrap()
+        } else if from.is_some() && to.is_some() && from > to {
             -1
+        } else {
+            1
         };
-        write!(
-            self.string,
-            "for (int {0} = {1}; {0} {2} {3}; {0} {4} {5}) ",
-            ident,
-            if from <= to {from} else {from - 1},
-            if from <= to {"<"} else {">="},
-            to,
-            if step > 0 {"+="} else {"-="},
-            step.abs()
-        )
-            .unwrap();
-        self.generate_block(block);
+        if let (Some(from), Some(to)) = (from, to) {
+            write!(self.string, "for (").unwrap();
+            self.write_var_decl(false, false, ident, &Ty::Int);
+            write!(
+                self.string,
+                " = {1}; {0} {2} {3}; {0} {4} {5}) ",
+                ident,
+                if from <= to {from} else {from - 1},
+                if from <= to {"<"} else {">="},
+                to,
+                if step > 0 {"+="} else {"-="},
+                step.abs()
+            )
+                .unwrap();
+            self.generate_block(block);
+            writeln!(self.string).unwrap();
+            return
+        }
+        if max_expr.is_none() && !self.backend_writer.needs_const_loop_bounds() {
+            write!(self.string, "for (").unwrap();
+            self.write_var_decl(false, false, ident, &Ty::Int);
+            write!(self.string, " = ").unwrap();
+            self.generate_expr(from_expr);
+            if step < 0 {
+                write!(self.string, " - 1").unwrap();
+            }
+            write!(self.string, "; {} {} ", ident, if step > 0 {"<"} else {">="}).unwrap();
+            self.generate_expr(to_expr);
+            write!(self.string, "; {} {} {}) ", ident, if step > 0 {"+="} else {"-="}, step.abs()).unwrap();
+            self.generate_block(block);
+            writeln!(self.string).unwrap();
+            return
+        }
+        // the bounds aren't const, so the loop counts to the max and the loop
+        // variable is derived from the counter with the from value taken once
+        let depth = self.indent_level;
+        writeln!(self.string, "{{").unwrap();
+        self.indent_level += 1;
+        self.write_indent();
+        self.write_var_decl(false, false, Ident::new(format!("mpsc_from_{}", depth)), &Ty::Int);
+        write!(self.string, " = ").unwrap();
+        self.generate_expr(from_expr);
+        writeln!(self.string, ";").unwrap();
+        self.write_indent();
+        self.write_loop_head(depth, max_expr);
+        self.generate_loop_block(block, | this | {
+            this.write_indent();
+            this.write_var_decl(false, false, ident, &Ty::Int);
+            writeln!(
+                this.string,
+                " = mpsc_from_{0}{1} {2} mpsc_loop_{0} * {3};",
+                depth,
+                if step > 0 {""} else {" - 1"},
+                if step > 0 {"+"} else {"-"},
+                step.abs()
+            )
+                .unwrap();
+            this.write_indent();
+            write!(this.string, "if ({} {} ", ident, if step > 0 {">="} else {"<"}).unwrap();
+            this.generate_expr(to_expr);
+            write!(this.string, ")").unwrap();
+            this.write_if_break();
+        });
         writeln!(self.string).unwrap();
+        self.indent_level -= 1;
+        self.write_indent();
+        writeln!(self.string, "}}").unwrap();
+    }
+    
+    fn generate_loop_stmt(&mut self, _span: Span, max_expr: &Option<Expr>, block: &Block) {
+        if max_expr.is_none() && !self.backend_writer.needs_const_loop_bounds() {
+            write!(self.string, "for (;;) ").unwrap();
+            self.generate_block(block);
+        } else {
+            self.write_loop_head(self.indent_level, max_expr);
+            self.generate_loop_block(block, | _ | ());
+        }
+        writeln!(self.string).unwrap();
+    }
+    
+    fn generate_while_stmt(&mut self, _span: Span, expr: &Expr, max_expr: &Option<Expr>, block: &Block) {
+        if max_expr.is_none() && !self.backend_writer.needs_const_loop_bounds() {
+            write!(self.string, "while (").unwrap();
+            self.generate_expr(expr);
+            write!(self.string, ") ").unwrap();
+            self.generate_block(block);
+        } else {
+            self.write_loop_head(self.indent_level, max_expr);
+            self.generate_loop_block(block, | this | {
+                this.write_indent();
+                write!(this.string, "if (!(").unwrap();
+                this.generate_expr(expr);
+                write!(this.string, "))").unwrap();
+                this.write_if_break();
+            });
+        }
+        writeln!(self.string).unwrap();
+    }
+    
+    // the body of the if a loop head breaks out with
+    fn write_if_break(&mut self) {
+        if self.backend_writer.needs_block_for_if_body() {
+            writeln!(self.string, " {{ break; }}").unwrap();
+        }
+        else {
+            writeln!(self.string, " break;").unwrap();
+        }
+    }
+    
+    // a counter with a const bound, which is all a loop can have in glsl es 1.00,
+    // the depth keeps the counters of nested loops apart
+    fn write_loop_head(&mut self, depth: usize, max_expr: &Option<Expr>) {
+        let max = max_expr.as_ref().map( | max_expr | const_int(max_expr).unwrap()).unwrap_or(DEFAULT_LOOP_MAX);
+        write!(self.string, "for (").unwrap();
+        self.write_var_decl(false, false, Ident::new(format!("mpsc_loop_{}", depth)), &Ty::Int);
+        write!(self.string, " = 0; mpsc_loop_{0} < {1}; mpsc_loop_{0}++) ", depth, max).unwrap();
+    }
+    
+    // a block that starts with the statements written by head
+    fn generate_loop_block(&mut self, block: &Block, head: impl FnOnce(&mut Self)) {
+        writeln!(self.string, "{{").unwrap();
+        self.indent_level += 1;
+        head(self);
+        for stmt in &block.stmts {
+            self.generate_stmt(stmt);
+        }
+        self.indent_level -= 1;
+        self.write_indent();
+        write!(self.string, "}}").unwrap();
     }
     
     fn generate_if_stmt(
@@ -174,9 +356,9 @@ impl<'a> BlockGenerator<'a> {
         block_if_true: &Block,
         block_if_false: &Option<Box<Block>>,
     ) {
-        write!(self.string, "if").unwrap();
+        write!(self.string, "if (").unwrap();
         self.generate_expr(expr);
-        write!(self.string, " ").unwrap();
+        write!(self.string, ") ").unwrap();
         self.generate_block(block_if_true);
         if let Some(block_if_false) = block_if_false {
             write!(self.string, "else").unwrap();
@@ -216,6 +398,30 @@ impl<'a> BlockGenerator<'a> {
     }
     
     fn generate_expr_stmt(&mut self, _span: Span, expr: &Expr) {
+        // where assignment is a statement and not an expression, it can't be wrapped in parens
+        if self.backend_writer.needs_assign_as_stmt() {
+            if let ExprKind::Bin {op, ref left_expr, ref right_expr, ..} = expr.kind {
+                if op.is_assign() && expr.const_val.borrow().as_ref().unwrap().is_none() {
+                    // a = b = c is written as b = c; a = b;
+                    if let ExprKind::Bin {op: right_op, left_expr: ref right_left_expr, ..} = right_expr.kind {
+                        if right_op.is_assign() {
+                            self.generate_expr_stmt(_span, right_expr);
+                            self.write_indent();
+                            self.generate_expr(left_expr);
+                            write!(self.string, " {} ", op).unwrap();
+                            self.generate_expr(right_left_expr);
+                            writeln!(self.string, ";").unwrap();
+                            return
+                        }
+                    }
+                    self.generate_expr(left_expr);
+                    write!(self.string, " {} ", op).unwrap();
+                    self.generate_expr(right_expr);
+                    writeln!(self.string, ";").unwrap();
+                    return
+                }
+            }
+        }
         self.generate_expr(expr);
         writeln!(self.string, ";").unwrap();
     }
@@ -309,6 +515,9 @@ impl<'a> ExprGenerator<'a> {
             (Some(Some(Val::Float(val))), _) => {
                 write!(self.string, "{}", PrettyPrintedFloat(*val)).unwrap();
             },
+            (Some(Some(Val::Uint(val))), _) if self.backend_writer.needs_int_for_uint() => {
+                write!(self.string, "{}", *val as i32).unwrap();
+            },
             (Some(Some(val)), _) => {
                 write!(self.string, "{}", val).unwrap();
             },
@@ -375,6 +584,16 @@ impl<'a> ExprGenerator<'a> {
         expr_if_true: &Expr,
         expr_if_false: &Expr,
     ) {
+        if self.backend_writer.needs_select_for_cond_expr() {
+            write!(self.string, "select(").unwrap();
+            self.generate_expr(expr_if_false);
+            write!(self.string, ", ").unwrap();
+            self.generate_expr(expr_if_true);
+            write!(self.string, ", ").unwrap();
+            self.generate_expr(expr);
+            write!(self.string, ")").unwrap();
+            return
+        }
         write!(self.string, "(").unwrap();
         self.generate_expr(expr);
         write!(self.string, " ? ").unwrap();
@@ -386,6 +605,37 @@ impl<'a> ExprGenerator<'a> {
     
     fn generate_bin_expr(&mut self, _span: Span, op: BinOp, left_expr: &Expr, right_expr: &Expr) {
         
+        // a op b becomes mpsc_op(a, b), and a op= b becomes (a = mpsc_op(a, b))
+        if let Some(int_op) = op.to_int_op() {
+            if self.backend_writer.needs_fn_for_int_ops() {
+                if op.is_assign() {
+                    write!(self.string, "(").unwrap();
+                    self.generate_expr(left_expr);
+                    write!(self.string, " = ").unwrap();
+                }
+                write!(self.string, "{}(", int_op_fn_name(int_op)).unwrap();
+                self.generate_expr(left_expr);
+                write!(self.string, ", ").unwrap();
+                self.generate_expr(right_expr);
+                write!(self.string, ")").unwrap();
+                if op.is_assign() {
+                    write!(self.string, ")").unwrap();
+                }
+                return
+            }
+            // the shift amount has to be unsigned, with as many components as the shifted value
+            if matches!(int_op, BinOp::Shl | BinOp::Shr) && self.backend_writer.needs_uint_shift_amount() {
+                write!(self.string, "(").unwrap();
+                self.generate_expr(left_expr);
+                write!(self.string, " {} ", op).unwrap();
+                self.write_ty_lit(unsigned_ty_lit(left_expr.ty.borrow().as_ref().unwrap()));
+                write!(self.string, "(").unwrap();
+                self.generate_expr(right_expr);
+                write!(self.string, "))").unwrap();
+                return
+            }
+        }
+        
         // if left_expr or right_expr is a matrix, HLSL needs to use mul()
         let left_is_mat = match left_expr.ty.borrow().as_ref().unwrap() {
             Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => true,
@@ -467,6 +717,19 @@ impl<'a> ExprGenerator<'a> {
     }
     
     fn generate_un_expr(&mut self, _span: Span, op: UnOp, expr: &Expr) {
+        // ! on an int flips its bits, which is ~x or without integer operators -x - 1
+        if let (UnOp::Not, false) = (op, *expr.ty.borrow().as_ref().unwrap() == Ty::Bool) {
+            if self.backend_writer.needs_fn_for_int_ops() {
+                write!(self.string, "(-(").unwrap();
+                self.generate_expr(expr);
+                write!(self.string, ") - 1)").unwrap();
+            }
+            else {
+                write!(self.string, "~").unwrap();
+                self.generate_expr(expr);
+            }
+            return
+        }
         write!(self.string, "{}", op).unwrap();
         self.generate_expr(expr);
     }
@@ -499,17 +762,90 @@ impl<'a> ExprGenerator<'a> {
     }
     
     fn generate_call_expr(&mut self, _span: Span, ident_path: IdentPath, arg_exprs: &[Expr]) {
+        // an overloaded function is called by the mangled name of the overload the args pick
+        let ident_path = self.shader.find_fn_decl_for_call(ident_path, arg_exprs).map_or(ident_path, | decl | decl.ident_path);
         let ident = ident_path.to_struct_fn_ident();
+        
+        // without unsigned ints there are no integer min, max and clamp either,
+        // so the uint ones go through their float form
+        if self.backend_writer.needs_int_for_uint()
+            && (ident == Ident::new("min") || ident == Ident::new("max") || ident == Ident::new("clamp")) {
+            let ty = arg_exprs[0].ty.borrow().clone().unwrap();
+            if uint_to_float_ty_lit(&ty).is_some() {
+                self.write_ty_lit(ty.maybe_ty_lit().unwrap());
+                write!(self.string, "(").unwrap();
+                self.backend_writer.write_call_ident(self.string, ident, arg_exprs);
+                write!(self.string, "(").unwrap();
+                let mut sep = "";
+                for arg_expr in arg_exprs {
+                    write!(self.string, "{}", sep).unwrap();
+                    let arg_ty = arg_expr.ty.borrow().clone().unwrap();
+                    self.write_ty_lit(uint_to_float_ty_lit(&arg_ty).unwrap());
+                    write!(self.string, "(").unwrap();
+                    self.generate_expr(arg_expr);
+                    write!(self.string, ")").unwrap();
+                    sep = ", ";
+                }
+                write!(self.string, "))").unwrap();
+                return
+            }
+        }
 
+        let is_builtin = self.shader.find_fn_decl(ident_path).is_none();
+        
+        // equal(a, b) becomes (a == b) where comparisons work per component
+        if is_builtin && self.backend_writer.needs_operators_for_relational_fns() {
+            if let Some(op) = relational_fn_op(ident) {
+                write!(self.string, "(").unwrap();
+                self.generate_expr(&arg_exprs[0]);
+                write!(self.string, " {} ", op).unwrap();
+                self.generate_expr(&arg_exprs[1]);
+                write!(self.string, ")").unwrap();
+                return
+            }
+            if ident == Ident::new("not") {
+                write!(self.string, "!").unwrap();
+                self.generate_expr(&arg_exprs[0]);
+                return
+            }
+        }
+        
+        // min(v, 0.5) becomes min(v, vec2(0.5)) where the builtin wants all its args the same type
+        let splat_ty_lit = if is_builtin && self.backend_writer.needs_splat_for_scalar_args() && has_splat_scalar_args(ident) {
+            arg_exprs.iter().find_map( | arg_expr | {
+                let ty = arg_expr.ty.borrow().clone().unwrap();
+                if ty.is_vector() {ty.maybe_ty_lit()} else {None}
+            })
+        } else {
+            None
+        };
+        
+        // an inout parameter takes a pointer where there are no references
+        let inout_params = match self.shader.find_fn_decl(ident_path) {
+            Some(decl) if self.backend_writer.needs_pointer_for_inout() => decl.params.iter().map( | param | param.is_inout).collect(),
+            _ => Vec::new()
+        };
+        
         //TODO add built-in check
         self.backend_writer.write_call_ident(&mut self.string, ident, arg_exprs);
         
         write!(self.string, "(").unwrap();
         let mut sep = "";
-        for arg_expr in arg_exprs {
+        for (index, arg_expr) in arg_exprs.iter().enumerate() {
             write!(self.string, "{}", sep).unwrap();
+            if inout_params.get(index) == Some(&true) {
+                write!(self.string, "&").unwrap();
+            }
             
-            self.generate_expr(arg_expr);
+            match splat_ty_lit {
+                Some(splat_ty_lit) if arg_expr.ty.borrow().as_ref().unwrap().is_scalar() => {
+                    self.write_ty_lit(splat_ty_lit);
+                    write!(self.string, "(").unwrap();
+                    self.generate_expr(arg_expr);
+                    write!(self.string, ")").unwrap();
+                }
+                _ => self.generate_expr(arg_expr)
+            }
             
             sep = ", ";
         }
@@ -558,6 +894,12 @@ impl<'a> ExprGenerator<'a> {
     }
 
     fn generate_lit_expr(&mut self, _span: Span, lit: Lit) {
+        if let Lit::Uint(lit) = lit {
+            if self.backend_writer.needs_int_for_uint() {
+                write!(self.string, "{}", lit as i32).unwrap();
+                return
+            }
+        }
         write!(self.string, "{}", lit).unwrap();
     }
     
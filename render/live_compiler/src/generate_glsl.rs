@@ -4,49 +4,109 @@ use {
         span::Span,
         env::{Env, VarKind},
         analyse::ShaderCompileOptions,
-        generate::{BackendWriter, BlockGenerator, ExprGenerator},
+        error::LiveError,
+        generate::{BackendWriter, BlockGenerator, ExprGenerator, int_op_fn_name},
         ident::{Ident, IdentPath},
         livestyles::LiveStyles,
         lit::TyLit,
         swizzle::Swizzle,
         ty::Ty,
     },
-    std::collections::HashSet,
+    std::collections::{BTreeSet, HashSet},
     std::fmt::Write,
     std::cell::Cell,
 };
 
+// the glsl dialect a shader is written in, the #version line and precision
+// qualifiers are left to the platform that compiles it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GlslVersion {
+    // GLSL ES 1.00 for WebGL1 and the GL contexts that take #version 100. it has
+    // no unsigned integers, so uint and uvec are written as int and ivec: their
+    // values have to stay below 2^31, >> on them shifts in the sign bit, and
+    // unsigned literals and constants are written as signed ones. min, max and
+    // clamp on them go through float, which is exact below 2^24. it has no
+    // sampler3D either, the OpenGL and WebGL platforms both generate Es100, so
+    // texture3D only works on DX11 and Metal
+    Es100,
+    // GLSL ES 3.00 for WebGL2 and GL 3.3, where uint and the integer operators are native
+    Es300,
+}
+
+// ES 1.00 has no sampler3D, and WebGL1 has no OES_texture_3D to add it, so the
+// platforms that compile #version 100 reject a texture3D before generating it
+pub fn check_shader_support(shader: &ShaderAst, version: GlslVersion) -> Result<(), LiveError> {
+    if version == GlslVersion::Es300 {
+        return Ok(())
+    }
+    for decl in &shader.decls {
+        if let Decl::Texture(decl) = decl {
+            if decl.ty_expr.ty.borrow().as_ref() == Some(&Ty::Texture3D) {
+                return Err(LiveError {
+                    span: decl.span,
+                    message: format!("texture {} is a texture3D, which GLSL ES 1.00 can't sample", decl.ident),
+                    helps: vec!["use a texture2D with the slices laid out next to each other".to_string()],
+                    ..LiveError::default()
+                })
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn generate_vertex_shader(shader: &ShaderAst, live_styles: &LiveStyles, options:ShaderCompileOptions) -> String {
+    generate_vertex_shader_with_version(shader, live_styles, options, GlslVersion::Es100)
+}
+
+pub fn generate_fragment_shader(shader: &ShaderAst, live_styles: &LiveStyles, options:ShaderCompileOptions) -> String {
+    generate_fragment_shader_with_version(shader, live_styles, options, GlslVersion::Es100)
+}
+
+pub fn generate_vertex_shader_with_version(shader: &ShaderAst, live_styles: &LiveStyles, options:ShaderCompileOptions, version: GlslVersion) -> String {
     let mut string = String::new();
     let env = Env::new(live_styles);
     ShaderGenerator {
         shader,
         env: &env,
+        version,
         create_const_table: options.create_const_table,
         string: &mut string,
-        backend_writer: &GlslBackendWriter {env: &env}
+        backend_writer: &GlslBackendWriter {env: &env, version}
     }
     .generate_vertex_shader();
     string
 }
 
-pub fn generate_fragment_shader(shader: &ShaderAst, live_styles: &LiveStyles, options:ShaderCompileOptions) -> String {
+pub fn generate_fragment_shader_with_version(shader: &ShaderAst, live_styles: &LiveStyles, options:ShaderCompileOptions, version: GlslVersion) -> String {
     let mut string = String::new();
     let env = Env::new(live_styles);
     ShaderGenerator {
         shader,
         env: &env,
+        version,
         create_const_table: options.create_const_table,
         string: &mut string,
-        backend_writer: &GlslBackendWriter {env: &env}
+        backend_writer: &GlslBackendWriter {env: &env, version}
     }
     .generate_fragment_shader();
     string
 }
 
+// the int type a uint type is written as on GLSL ES 1.00
+fn signed_ty(ty: &Ty) -> Ty {
+    match ty {
+        Ty::Uint => Ty::Int,
+        Ty::Uvec2 => Ty::Ivec2,
+        Ty::Uvec3 => Ty::Ivec3,
+        Ty::Uvec4 => Ty::Ivec4,
+        ty => ty.clone(),
+    }
+}
+
 struct ShaderGenerator<'a, 'b> {
     shader: &'a ShaderAst,
     env: &'a Env<'b>,
+    version: GlslVersion,
     create_const_table: bool,
     string: &'a mut String,
     backend_writer: &'a dyn BackendWriter
@@ -54,12 +114,14 @@ struct ShaderGenerator<'a, 'b> {
 
 impl<'a, 'b> ShaderGenerator<'a, 'b> {
     fn write_ty_init(&mut self, ty: &Ty) {
+        let ty = if self.version == GlslVersion::Es100 {signed_ty(ty)} else {ty.clone()};
         write!(
             self.string,
             "{}",
             match ty {
                 Ty::Bool => "false",
                 Ty::Int => "0",
+                Ty::Uint => "0u",
                 Ty::Float => "0.0",
                 Ty::Bvec2 => "bvec2(0)",
                 Ty::Bvec3 => "bvec3(0)",
@@ -67,6 +129,9 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
                 Ty::Ivec2 => "ivec2(0)",
                 Ty::Ivec3 => "ivec3(0)",
                 Ty::Ivec4 => "ivec4(0)",
+                Ty::Uvec2 => "uvec2(0u)",
+                Ty::Uvec3 => "uvec3(0u)",
+                Ty::Uvec4 => "uvec4(0u)",
                 Ty::Vec2 => "vec2(0.0)",
                 Ty::Vec3 => "vec3(0.0)",
                 Ty::Vec4 => "vec4(0.0)",
@@ -124,6 +189,7 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
             }
         }
         let vertex_decl = self.shader.find_fn_decl(IdentPath::from_str("vertex")).unwrap();
+        self.generate_int_op_fns(vertex_decl);
         for &(ty_lit, ref param_tys) in vertex_decl
             .cons_fn_deps
             .borrow_mut()
@@ -225,6 +291,7 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
         }
         
         let pixel_decl = self.shader.find_fn_decl(IdentPath::from_str("pixel")).unwrap();
+        self.generate_int_op_fns(pixel_decl);
         for &(ty_lit, ref param_tys) in pixel_decl
             .cons_fn_deps
             .borrow_mut()
@@ -257,7 +324,11 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
                 _ => {}
             }
         }
-        writeln!(self.string, "    gl_FragColor = pixel();").unwrap();
+        if self.version == GlslVersion::Es100 {
+            writeln!(self.string, "    gl_FragColor = pixel();").unwrap();
+        } else {
+            writeln!(self.string, "    mpsc_frag_color = pixel();").unwrap();
+        }
         writeln!(self.string, "}}").unwrap();
     }
     
@@ -315,9 +386,17 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
             }
         }
         
+        self.generate_sample_fns();
+        
+        let is_vertex = packed_attributes_size.is_some();
+        let (attribute, varying) = match self.version {
+            GlslVersion::Es100 => ("attribute", "varying"),
+            GlslVersion::Es300 => ("in", if is_vertex {"out"} else {"in"}),
+        };
+        
         if let Some(packed_attributes_size) = packed_attributes_size {
             self.generate_packed_var_decls(
-                "attribute",
+                attribute,
                 "mpsc_packed_geometry",
                 packed_attributes_size,
             );
@@ -325,13 +404,17 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
         
         if let Some(packed_instances_size) = packed_instances_size {
             self.generate_packed_var_decls(
-                "attribute",
+                attribute,
                 "mpsc_packed_instance",
                 packed_instances_size,
             );
         }
         
-        self.generate_packed_var_decls("varying", "mpsc_packed_varying", packed_varyings_size);
+        self.generate_packed_var_decls(varying, "mpsc_packed_varying", packed_varyings_size);
+        
+        if !is_vertex && self.version == GlslVersion::Es300 {
+            writeln!(self.string, "out vec4 mpsc_frag_color;").unwrap();
+        }
     }
     
     fn generate_struct_decl(&mut self, decl: &StructDecl) {
@@ -383,6 +466,36 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
         writeln!(self.string, ";").unwrap();
     }
     
+    // sample2d comes from the platform, the cube and volume samplers are only written
+    // when the shader declares such a texture. sample3d is only valid ES 3.00, see
+    // check_shader_support
+    fn generate_sample_fns(&mut self) {
+        let mut texture_tys = BTreeSet::new();
+        for decl in &self.shader.decls {
+            if let Decl::Texture(decl) = decl {
+                texture_tys.insert(decl.ty_expr.ty.borrow().clone().unwrap());
+            }
+        }
+        let (texture_cube, texture_3d) = match self.version {
+            GlslVersion::Es100 => ("textureCube", "texture3D"),
+            GlslVersion::Es300 => ("texture", "texture"),
+        };
+        if texture_tys.contains(&Ty::TextureCube) {
+            writeln!(
+                self.string,
+                "vec4 sample_cube(samplerCube sampler, vec3 dir) {{ return {}(sampler, dir); }}",
+                texture_cube
+            ).unwrap();
+        }
+        if texture_tys.contains(&Ty::Texture3D) {
+            writeln!(
+                self.string,
+                "vec4 sample3d(sampler3D sampler, vec3 pos) {{ return {}(sampler, vec3(pos.x, 1.0 - pos.y, pos.z)); }}",
+                texture_3d
+            ).unwrap();
+        }
+    }
+    
     fn compute_packed_geometries_size(&self) -> usize {
         let mut packed_attributes_size = 0;
         for decl in &self.shader.decls {
@@ -452,6 +565,99 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
         }
     }
     
+    // GLSL ES 1.0 has no integer %, bitwise or shift operators, so they become functions that
+    // work on the two's complement value with integer arithmetic, the ivec forms go per component
+    fn generate_int_op_fns(&mut self, decl: &FnDecl) {
+        if !self.backend_writer.needs_fn_for_int_ops() {
+            return
+        }
+        // uint and uvec are written as int and ivec here, so they share the functions
+        let int_op_fn_deps = decl.int_op_fn_deps.borrow().as_ref().unwrap().iter().map(
+            | (op, left_ty, right_ty) | (*op, signed_ty(left_ty), signed_ty(right_ty))
+        ).collect::<BTreeSet<_>>();
+        let ops = int_op_fn_deps.iter().map( | (op, _, _) | *op).collect::<BTreeSet<_>>();
+        for op in ops {
+            self.generate_int_op_fn(op);
+        }
+        for (op, left_ty, right_ty) in &int_op_fn_deps {
+            if *left_ty != Ty::Int {
+                self.generate_ivec_op_fn(*op, left_ty, right_ty);
+            }
+        }
+    }
+    
+    fn generate_int_op_fn(&mut self, op: BinOp) {
+        let name = int_op_fn_name(op);
+        writeln!(self.string, "int {}(int a, int b) {{", name).unwrap();
+        match op {
+            BinOp::Rem => {
+                writeln!(self.string, "    return a - (a / b) * b;").unwrap();
+            }
+            BinOp::Shl | BinOp::Shr => {
+                writeln!(self.string, "    for (int i = 0; i < 32; i++) {{").unwrap();
+                writeln!(self.string, "        if (i >= b) break;").unwrap();
+                if let BinOp::Shl = op {
+                    writeln!(self.string, "        a *= 2;").unwrap();
+                } else {
+                    // rounds towards negative infinity like an arithmetic shift
+                    writeln!(self.string, "        a = a < 0 ? (a + 1) / 2 - 1 : a / 2;").unwrap();
+                }
+                writeln!(self.string, "    }}").unwrap();
+                writeln!(self.string, "    return a;").unwrap();
+            }
+            _ => {
+                let (bit, sign) = match op {
+                    BinOp::BitAnd => ("x * y == 1", "sa && sb"),
+                    BinOp::BitOr => ("x + y > 0", "sa || sb"),
+                    BinOp::BitXor => ("x != y", "sa != sb"),
+                    _ => panic!(),
+                };
+                // the sign bit is split off so the low 31 bits can be walked with / and *
+                writeln!(self.string, "    bool sa = a < 0;").unwrap();
+                writeln!(self.string, "    bool sb = b < 0;").unwrap();
+                writeln!(self.string, "    if (sa) a = a + 1073741824 + 1073741824;").unwrap();
+                writeln!(self.string, "    if (sb) b = b + 1073741824 + 1073741824;").unwrap();
+                writeln!(self.string, "    int r = 0;").unwrap();
+                writeln!(self.string, "    int bit = 1;").unwrap();
+                writeln!(self.string, "    for (int i = 0; i < 31; i++) {{").unwrap();
+                writeln!(self.string, "        int x = a - (a / 2) * 2;").unwrap();
+                writeln!(self.string, "        int y = b - (b / 2) * 2;").unwrap();
+                writeln!(self.string, "        if ({}) r += bit;", bit).unwrap();
+                writeln!(self.string, "        a /= 2;").unwrap();
+                writeln!(self.string, "        b /= 2;").unwrap();
+                writeln!(self.string, "        if (i < 30) bit *= 2;").unwrap();
+                writeln!(self.string, "    }}").unwrap();
+                writeln!(self.string, "    if ({}) r = r - 1073741824 - 1073741824;", sign).unwrap();
+                writeln!(self.string, "    return r;").unwrap();
+            }
+        }
+        writeln!(self.string, "}}").unwrap();
+    }
+    
+    fn generate_ivec_op_fn(&mut self, op: BinOp, left_ty: &Ty, right_ty: &Ty) {
+        let name = int_op_fn_name(op);
+        let ty_lit = left_ty.maybe_ty_lit().unwrap();
+        self.write_ty_lit(ty_lit);
+        write!(self.string, " {}(", name).unwrap();
+        self.write_var_decl(false, Ident::new("a"), left_ty);
+        write!(self.string, ", ").unwrap();
+        self.write_var_decl(false, Ident::new("b"), right_ty);
+        writeln!(self.string, ") {{").unwrap();
+        write!(self.string, "    return ").unwrap();
+        self.write_ty_lit(ty_lit);
+        write!(self.string, "(").unwrap();
+        let mut sep = "";
+        for component in ["x", "y", "z", "w"].iter().take(left_ty.size()) {
+            if *right_ty == Ty::Int {
+                write!(self.string, "{}{}(a.{}, b)", sep, name, component).unwrap();
+            } else {
+                write!(self.string, "{}{}(a.{}, b.{})", sep, name, component, component).unwrap();
+            }
+            sep = ", ";
+        }
+        writeln!(self.string, ");").unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
     
     fn generate_cons_fn(&mut self, ty_lit: TyLit, param_tys: &[Ty]) {
         let mut cons_name = format!("mpsc_{}", ty_lit);
@@ -482,7 +688,7 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
         if param_tys.len() == 1 {
             let param_ty = &param_tys[0];
             match param_ty {
-                Ty::Bool | Ty::Int | Ty::Float => {
+                Ty::Bool | Ty::Int | Ty::Uint | Ty::Float => {
                     let mut sep = "";
                     for _ in 0..ty.size() {
                         write!(self.string, "{}x", sep).unwrap();
@@ -827,7 +1033,8 @@ impl<'a> VarUnpacker<'a> {
 }
 
 struct GlslBackendWriter<'a, 'b> {
-    pub env: &'a Env<'b>
+    pub env: &'a Env<'b>,
+    pub version: GlslVersion,
 }
 
 impl<'a, 'b> BackendWriter for GlslBackendWriter<'a, 'b> {
@@ -856,6 +1063,46 @@ impl<'a, 'b> BackendWriter for GlslBackendWriter<'a, 'b> {
         false
     }
     
+    fn needs_fn_for_int_ops(&self) -> bool {
+        self.version == GlslVersion::Es100
+    }
+    
+    fn needs_const_loop_bounds(&self) -> bool {
+        self.version == GlslVersion::Es100
+    }
+    
+    fn needs_int_for_uint(&self) -> bool {
+        self.version == GlslVersion::Es100
+    }
+    
+    fn needs_assign_as_stmt(&self) -> bool {
+        false
+    }
+    
+    fn needs_select_for_cond_expr(&self) -> bool {
+        false
+    }
+    
+    fn needs_uint_shift_amount(&self) -> bool {
+        false
+    }
+    
+    fn needs_operators_for_relational_fns(&self) -> bool {
+        false
+    }
+    
+    fn needs_splat_for_scalar_args(&self) -> bool {
+        false
+    }
+    
+    fn needs_block_for_if_body(&self) -> bool {
+        false
+    }
+    
+    fn needs_pointer_for_inout(&self) -> bool {
+        false
+    }
+    
     fn const_table_is_vec4(&self) -> bool {
         false
     }
@@ -891,6 +1138,11 @@ impl<'a, 'b> BackendWriter for GlslBackendWriter<'a, 'b> {
                 write!(string, " ").unwrap();
                 self.write_ident(string, ident);
             }
+            Ty::Uint => {
+                self.write_ty_lit(string, TyLit::Uint);
+                write!(string, " ").unwrap();
+                self.write_ident(string, ident);
+            }
             Ty::Float => {
                 self.write_ty_lit(string, TyLit::Float);
                 write!(string, " ").unwrap();
@@ -926,6 +1178,21 @@ impl<'a, 'b> BackendWriter for GlslBackendWriter<'a, 'b> {
                 write!(string, " ").unwrap();
                 self.write_ident(string, ident);
             }
+            Ty::Uvec2 => {
+                self.write_ty_lit(string, TyLit::Uvec2);
+                write!(string, " ").unwrap();
+                self.write_ident(string, ident);
+            }
+            Ty::Uvec3 => {
+                self.write_ty_lit(string, TyLit::Uvec3);
+                write!(string, " ").unwrap();
+                self.write_ident(string, ident);
+            }
+            Ty::Uvec4 => {
+                self.write_ty_lit(string, TyLit::Uvec4);
+                write!(string, " ").unwrap();
+                self.write_ident(string, ident);
+            }
             Ty::Vec2 => {
                 self.write_ty_lit(string, TyLit::Vec2);
                 write!(string, " ").unwrap();
@@ -961,6 +1228,16 @@ impl<'a, 'b> BackendWriter for GlslBackendWriter<'a, 'b> {
                 write!(string, " ").unwrap();
                 self.write_ident(string, ident);
             }
+            Ty::TextureCube => {
+                self.write_ty_lit(string, TyLit::TextureCube);
+                write!(string, " ").unwrap();
+                self.write_ident(string, ident);
+            }
+            Ty::Texture3D => {
+                self.write_ty_lit(string, TyLit::Texture3D);
+                write!(string, " ").unwrap();
+                self.write_ident(string, ident);
+            }
             Ty::Array {ref elem_ty, len} => {
                 self.write_var_decl(string, is_inout, is_packed, ident, elem_ty);
                 write!(string, "[{}]", len).unwrap();
@@ -981,6 +1258,8 @@ impl<'a, 'b> BackendWriter for GlslBackendWriter<'a, 'b> {
             match ty_lit {
                 TyLit::Bool => "bool",
                 TyLit::Int => "int",
+                TyLit::Uint if self.version == GlslVersion::Es100 => "int",
+                TyLit::Uint => "uint",
                 TyLit::Float => "float",
                 TyLit::Bvec2 => "bvec2",
                 TyLit::Bvec3 => "bvec3",
@@ -988,6 +1267,12 @@ impl<'a, 'b> BackendWriter for GlslBackendWriter<'a, 'b> {
                 TyLit::Ivec2 => "ivec2",
                 TyLit::Ivec3 => "ivec3",
                 TyLit::Ivec4 => "ivec4",
+                TyLit::Uvec2 if self.version == GlslVersion::Es100 => "ivec2",
+                TyLit::Uvec2 => "uvec2",
+                TyLit::Uvec3 if self.version == GlslVersion::Es100 => "ivec3",
+                TyLit::Uvec3 => "uvec3",
+                TyLit::Uvec4 if self.version == GlslVersion::Es100 => "ivec4",
+                TyLit::Uvec4 => "uvec4",
                 TyLit::Vec2 => "vec2",
                 TyLit::Vec3 => "vec3",
                 TyLit::Vec4 => "vec4",
@@ -995,6 +1280,8 @@ impl<'a, 'b> BackendWriter for GlslBackendWriter<'a, 'b> {
                 TyLit::Mat3 => "mat3",
                 TyLit::Mat4 => "mat4",
                 TyLit::Texture2D => "sampler2D",
+                TyLit::TextureCube => "samplerCube",
+                TyLit::Texture3D => "sampler3D",
             }
         )
             .unwrap();
@@ -45,8 +45,10 @@ struct ShaderGenerator<'a, 'b> {
 
 impl<'a, 'b> ShaderGenerator<'a, 'b> {
     fn generate_shader(&mut self) {
-        writeln!(self.string, "SamplerState mpsc_default_texture_sampler{{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;}};").unwrap();
+        writeln!(self.string, "SamplerState mpsc_default_texture_sampler{{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;AddressW=Wrap;}};").unwrap();
         writeln!(self.string, "float4 sample2d(Texture2D tex, float2 pos){{return tex.Sample(mpsc_default_texture_sampler,pos);}}").unwrap();
+        writeln!(self.string, "float4 sample_cube(TextureCube tex, float3 dir){{return tex.Sample(mpsc_default_texture_sampler,dir);}}").unwrap();
+        writeln!(self.string, "float4 sample3d(Texture3D tex, float3 pos){{return tex.Sample(mpsc_default_texture_sampler,pos);}}").unwrap();
         self.generate_struct_decls();
         self.generate_uniform_structs();
         self.generate_texture_defs();
@@ -177,10 +179,16 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
         for decl in &self.shader.decls {
             match decl {
                 Decl::Texture(decl) => {
-                    assert_eq!(*decl.ty_expr.ty.borrow().as_ref().unwrap(), Ty::Texture2D);
+                    let texture_ty = match decl.ty_expr.ty.borrow().as_ref().unwrap() {
+                        Ty::Texture2D => "Texture2D",
+                        Ty::TextureCube => "TextureCube",
+                        Ty::Texture3D => "Texture3D",
+                        _ => panic!(),
+                    };
                     writeln!(
                         self.string,
-                        "Texture2D {}: register(t{});",
+                        "{} {}: register(t{});",
+                        texture_ty,
                         decl.ident,
                         index
                     )
@@ -386,7 +394,7 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
         if param_tys.len() == 1 {
             let param_ty = &param_tys[0];
             match param_ty {
-                Ty::Bool | Ty::Int | Ty::Float => {
+                Ty::Bool | Ty::Int | Ty::Uint | Ty::Float => {
                     let mut sep = "";
                     for _ in 0..ty.size() {
                         write!(self.string, "{}x", sep).unwrap();
@@ -556,6 +564,7 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
         match ty {
             Ty::Bool => write!(self.string, "false").unwrap(),
             Ty::Int => write!(self.string, "0").unwrap(),
+            Ty::Uint => write!(self.string, "0u").unwrap(),
             Ty::Float => write!(self.string, "0.0").unwrap(),
             Ty::Bvec2 => write!(self.string, "bool2(0,0)").unwrap(),
             Ty::Bvec3 => write!(self.string, "bool3(0,0,0)").unwrap(),
@@ -563,6 +572,9 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
             Ty::Ivec2 => write!(self.string, "int2(0,0)").unwrap(),
             Ty::Ivec3 => write!(self.string, "int3(0,0,0)").unwrap(),
             Ty::Ivec4 => write!(self.string, "int4(0,0,0,0)").unwrap(),
+            Ty::Uvec2 => write!(self.string, "uint2(0u,0u)").unwrap(),
+            Ty::Uvec3 => write!(self.string, "uint3(0u,0u,0u)").unwrap(),
+            Ty::Uvec4 => write!(self.string, "uint4(0u,0u,0u,0u)").unwrap(),
             Ty::Vec2 => write!(self.string, "float2(0.0,0.0)").unwrap(),
             Ty::Vec3 => write!(self.string, "float3(0.0,0.0,0.0)").unwrap(),
             Ty::Vec4 => write!(self.string, "float4(0.0,0.0,0.0,0.0)").unwrap(),
@@ -834,6 +846,46 @@ impl<'a, 'b> BackendWriter for HlslBackendWriter<'a, 'b> {
         false
     }
 
+    fn needs_fn_for_int_ops(&self)->bool{
+        false
+    }
+
+    fn needs_const_loop_bounds(&self)->bool{
+        false
+    }
+
+    fn needs_int_for_uint(&self)->bool{
+        false
+    }
+
+    fn needs_assign_as_stmt(&self)->bool{
+        false
+    }
+
+    fn needs_select_for_cond_expr(&self)->bool{
+        false
+    }
+
+    fn needs_uint_shift_amount(&self)->bool{
+        false
+    }
+
+    fn needs_operators_for_relational_fns(&self)->bool{
+        false
+    }
+
+    fn needs_splat_for_scalar_args(&self)->bool{
+        false
+    }
+
+    fn needs_block_for_if_body(&self)->bool{
+        false
+    }
+
+    fn needs_pointer_for_inout(&self)->bool{
+        false
+    }
+
     fn  const_table_is_vec4(&self) -> bool{
         true
     }
@@ -874,6 +926,11 @@ impl<'a, 'b> BackendWriter for HlslBackendWriter<'a, 'b> {
                 write!(string, " ").unwrap();
                 self.write_ident(string, ident);
             }
+            Ty::Uint => {
+                self.write_ty_lit(string, TyLit::Uint);
+                write!(string, " ").unwrap();
+                self.write_ident(string, ident);
+            }
             Ty::Float => {
                 self.write_ty_lit(string, TyLit::Float);
                 write!(string, " ").unwrap();
@@ -909,6 +966,21 @@ impl<'a, 'b> BackendWriter for HlslBackendWriter<'a, 'b> {
                 write!(string, " ").unwrap();
                 self.write_ident(string, ident);
             }
+            Ty::Uvec2 => {
+                self.write_ty_lit(string, TyLit::Uvec2);
+                write!(string, " ").unwrap();
+                self.write_ident(string, ident);
+            }
+            Ty::Uvec3 => {
+                self.write_ty_lit(string, TyLit::Uvec3);
+                write!(string, " ").unwrap();
+                self.write_ident(string, ident);
+            }
+            Ty::Uvec4 => {
+                self.write_ty_lit(string, TyLit::Uvec4);
+                write!(string, " ").unwrap();
+                self.write_ident(string, ident);
+            }
             Ty::Vec2 => {
                 self.write_ty_lit(string, TyLit::Vec2);
                 write!(string, " ").unwrap();
@@ -939,7 +1011,7 @@ impl<'a, 'b> BackendWriter for HlslBackendWriter<'a, 'b> {
                 write!(string, " ").unwrap();
                 self.write_ident(string, ident);
             }
-            Ty::Texture2D => panic!(), // TODO
+            Ty::Texture2D | Ty::TextureCube | Ty::Texture3D => panic!(), // TODO
             Ty::Array {ref elem_ty, len} => {
                 self.write_var_decl(string, is_inout, is_packed, ident, elem_ty);
                 write!(string, " ").unwrap();
@@ -962,6 +1034,7 @@ impl<'a, 'b> BackendWriter for HlslBackendWriter<'a, 'b> {
             match ty_lit {
                 TyLit::Bool => "bool",
                 TyLit::Int => "int",
+                TyLit::Uint => "uint",
                 TyLit::Float => "float",
                 TyLit::Bvec2 => "bool2",
                 TyLit::Bvec3 => "bool3",
@@ -969,13 +1042,16 @@ impl<'a, 'b> BackendWriter for HlslBackendWriter<'a, 'b> {
                 TyLit::Ivec2 => "int2",
                 TyLit::Ivec3 => "int3",
                 TyLit::Ivec4 => "int4",
+                TyLit::Uvec2 => "uint2",
+                TyLit::Uvec3 => "uint3",
+                TyLit::Uvec4 => "uint4",
                 TyLit::Vec2 => "float2",
                 TyLit::Vec3 => "float3",
                 TyLit::Vec4 => "float4",
                 TyLit::Mat2 => "float2x2",
                 TyLit::Mat3 => "float3x3",
                 TyLit::Mat4 => "float4x4",
-                TyLit::Texture2D => panic!(), // TODO
+                TyLit::Texture2D | TyLit::TextureCube | TyLit::Texture3D => panic!(), // TODO
             }
         )
             .unwrap();
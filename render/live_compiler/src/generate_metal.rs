@@ -44,6 +44,8 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
         writeln!(self.string, "#include <metal_stdlib>").unwrap();
         writeln!(self.string, "using namespace metal;").unwrap();
         writeln!(self.string, "float4 sample2d(texture2d<float> tex, float2 pos){{return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}}").unwrap();
+        writeln!(self.string, "float4 sample_cube(texturecube<float> tex, float3 dir){{return tex.sample(sampler(mag_filter::linear,min_filter::linear),dir);}}").unwrap();
+        writeln!(self.string, "float4 sample3d(texture3d<float> tex, float3 pos){{return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}}").unwrap();
         self.generate_struct_decls();
         self.generate_uniform_structs();
         self.generate_texture_struct();
@@ -148,10 +150,16 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
         for decl in &self.shader.decls {
             match decl {
                 Decl::Texture(decl) => {
-                    assert_eq!(*decl.ty_expr.ty.borrow().as_ref().unwrap(), Ty::Texture2D);
+                    let texture_ty = match decl.ty_expr.ty.borrow().as_ref().unwrap() {
+                        Ty::Texture2D => "texture2d",
+                        Ty::TextureCube => "texturecube",
+                        Ty::Texture3D => "texture3d",
+                        _ => panic!(),
+                    };
                     writeln!(
                         self.string,
-                        "    texture2d<float> {} [[texture({})]];",
+                        "    {}<float> {} [[texture({})]];",
+                        texture_ty,
                         decl.ident,
                         index
                     )
@@ -294,7 +302,7 @@ impl<'a, 'b> ShaderGenerator<'a, 'b> {
         if param_tys.len() == 1 {
             let param_ty = &param_tys[0];
             match param_ty {
-                Ty::Bool | Ty::Int | Ty::Float => {
+                Ty::Bool | Ty::Int | Ty::Uint | Ty::Float => {
                     let mut sep = "";
                     for _ in 0..ty.size() {
                         write!(self.string, "{}x", sep).unwrap();
@@ -777,6 +785,46 @@ impl<'a, 'b> BackendWriter for MetalBackendWriter<'a, 'b> {
         true
     }
     
+    fn needs_fn_for_int_ops(&self) -> bool {
+        false
+    }
+    
+    fn needs_const_loop_bounds(&self) -> bool {
+        false
+    }
+    
+    fn needs_int_for_uint(&self) -> bool {
+        false
+    }
+    
+    fn needs_assign_as_stmt(&self) -> bool {
+        false
+    }
+    
+    fn needs_select_for_cond_expr(&self) -> bool {
+        false
+    }
+    
+    fn needs_uint_shift_amount(&self) -> bool {
+        false
+    }
+    
+    fn needs_operators_for_relational_fns(&self) -> bool {
+        false
+    }
+    
+    fn needs_splat_for_scalar_args(&self) -> bool {
+        false
+    }
+    
+    fn needs_block_for_if_body(&self) -> bool {
+        false
+    }
+    
+    fn needs_pointer_for_inout(&self) -> bool {
+        false
+    }
+    
     fn const_table_is_vec4(&self) -> bool {
         false
     }
@@ -820,6 +868,11 @@ impl<'a, 'b> BackendWriter for MetalBackendWriter<'a, 'b> {
                 write!(string, " {}", ref_prefix).unwrap();
                 self.write_ident(string, ident);
             }
+            Ty::Uint => {
+                self.write_ty_lit(string, TyLit::Uint);
+                write!(string, " {}", ref_prefix).unwrap();
+                self.write_ident(string, ident);
+            }
             Ty::Float => {
                 self.write_ty_lit(string, TyLit::Float);
                 write!(string, " {}", ref_prefix).unwrap();
@@ -861,6 +914,24 @@ impl<'a, 'b> BackendWriter for MetalBackendWriter<'a, 'b> {
                 write!(string, " {}", ref_prefix).unwrap();
                 self.write_ident(string, ident);
             }
+            Ty::Uvec2 => {
+                write!(string, "{}", packed_prefix).unwrap();
+                self.write_ty_lit(string, TyLit::Uvec2);
+                write!(string, " {}", ref_prefix).unwrap();
+                self.write_ident(string, ident);
+            }
+            Ty::Uvec3 => {
+                write!(string, "{}", packed_prefix).unwrap();
+                self.write_ty_lit(string, TyLit::Uvec3);
+                write!(string, " {}", ref_prefix).unwrap();
+                self.write_ident(string, ident);
+            }
+            Ty::Uvec4 => {
+                write!(string, "{}", packed_prefix).unwrap();
+                self.write_ty_lit(string, TyLit::Uvec4);
+                write!(string, " {}", ref_prefix).unwrap();
+                self.write_ident(string, ident);
+            }
             Ty::Vec2 => {
                 write!(string, "{}", packed_prefix).unwrap();
                 self.write_ty_lit(string, TyLit::Vec2);
@@ -896,7 +967,7 @@ impl<'a, 'b> BackendWriter for MetalBackendWriter<'a, 'b> {
                 write!(string, " {}", ref_prefix).unwrap();
                 self.write_ident(string, ident);
             }
-            Ty::Texture2D => panic!(), // TODO
+            Ty::Texture2D | Ty::TextureCube | Ty::Texture3D => panic!(), // TODO
             Ty::Array {ref elem_ty, len} => {
                 self.write_var_decl(string, is_inout, is_packed, ident, elem_ty);
                 write!(string, "[{}]", len).unwrap();
@@ -917,6 +988,7 @@ impl<'a, 'b> BackendWriter for MetalBackendWriter<'a, 'b> {
             match ty_lit {
                 TyLit::Bool => "bool",
                 TyLit::Int => "int",
+                TyLit::Uint => "uint",
                 TyLit::Float => "float",
                 TyLit::Bvec2 => "bool2",
                 TyLit::Bvec3 => "bool3",
@@ -924,13 +996,16 @@ impl<'a, 'b> BackendWriter for MetalBackendWriter<'a, 'b> {
                 TyLit::Ivec2 => "int2",
                 TyLit::Ivec3 => "int3",
                 TyLit::Ivec4 => "int4",
+                TyLit::Uvec2 => "uint2",
+                TyLit::Uvec3 => "uint3",
+                TyLit::Uvec4 => "uint4",
                 TyLit::Vec2 => "float2",
                 TyLit::Vec3 => "float3",
                 TyLit::Vec4 => "float4",
                 TyLit::Mat2 => "float2x2",
                 TyLit::Mat3 => "float3x3",
                 TyLit::Mat4 => "float4x4",
-                TyLit::Texture2D => panic!(), // TODO
+                TyLit::Texture2D | TyLit::TextureCube | TyLit::Texture3D => panic!(), // TODO
             }
         )
             .unwrap();
@@ -0,0 +1,992 @@
+use {
+    crate::{
+        shaderast::*,
+        env::{VarKind, Env},
+        error::LiveError,
+        span::Span,
+        analyse::ShaderCompileOptions,
+        generate::{BackendWriter, BlockGenerator, ExprGenerator},
+        ident::{Ident, IdentPath},
+        livestyles::LiveStyles,
+        lit::TyLit,
+        ty::Ty,
+    },
+    std::{
+        cell::Cell,
+        collections::{BTreeMap, HashSet},
+        fmt::Write,
+    },
+};
+
+// The WGSL module for both stages, laid out the way CxShaderMapping sees the shader:
+// - the uniform blocks are structs in group 0, pass at binding 0, view at 1, draw at 2,
+//   the default block at 3, the live uniforms at 4 and the const table at 5. vec3 fields
+//   take 16 bytes, as with metal_uniform_packing
+// - the default sampler is at group 1 binding 0 and the textures follow in declaration order
+// - geometries and instances are packed into vec4 vertex attributes in declaration order,
+//   geometries first, so the float offsets of InstanceProps carry over. varyings are packed
+//   the same way
+// - an inout parameter is a ptr<function, T>, the caller passes &arg and the callee reads
+//   and writes *param
+// assignments are statements in WGSL, so they can't be used as expressions other than in
+// a = b = c, and a swizzle with more than one component can't be assigned to
+pub fn generate_shader(shader: &ShaderAst, live_styles: &LiveStyles, options: ShaderCompileOptions) -> String {
+    let mut string = String::new();
+    let env = Env::new(live_styles);
+    ShaderGenerator {
+        shader,
+        create_const_table: options.create_const_table,
+        string: &mut string,
+        backend_writer: &WgslBackendWriter {env: &env}
+    }
+    .generate_shader();
+    string
+}
+
+// a pointer to function memory can only be taken of a local, or a struct field or array
+// element of one, and a local that hides an inout parameter would be read as a pointer
+pub fn check_shader_support(shader: &ShaderAst) -> Result<(), LiveError> {
+    for decl in &shader.decls {
+        if let Decl::Fn(decl) = decl {
+            check_block(shader, decl, &decl.block) ?;
+        }
+    }
+    Ok(())
+}
+
+fn is_inout_param(decl: &FnDecl, ident: Ident) -> bool {
+    decl.params.iter().any( | param | param.is_inout && param.ident == ident)
+}
+
+fn check_block(shader: &ShaderAst, decl: &FnDecl, block: &Block) -> Result<(), LiveError> {
+    for stmt in &block.stmts {
+        let hidden = match *stmt {
+            Stmt::Let {span, ident, ..} | Stmt::For {span, ident, ..} if is_inout_param(decl, ident) => Some((span, ident)),
+            _ => None,
+        };
+        if let Some((span, ident)) = hidden {
+            return Err(LiveError {
+                span,
+                message: format!("`{}` hides the inout parameter of the same name, which WGSL can't tell apart", ident),
+                ..LiveError::default()
+            })
+        }
+        match stmt {
+            Stmt::For {from_expr, to_expr, step_expr, block, ..} => {
+                for expr in [from_expr, to_expr].iter().copied().chain(step_expr.iter()) {
+                    check_expr(shader, expr) ?;
+                }
+                check_block(shader, decl, block) ?;
+            }
+            Stmt::Loop {block, ..} => check_block(shader, decl, block) ?,
+            Stmt::While {expr, block, ..} => {
+                check_expr(shader, expr) ?;
+                check_block(shader, decl, block) ?;
+            }
+            Stmt::If {expr, block_if_true, block_if_false, ..} => {
+                check_expr(shader, expr) ?;
+                check_block(shader, decl, block_if_true) ?;
+                if let Some(block_if_false) = block_if_false {
+                    check_block(shader, decl, block_if_false) ?;
+                }
+            }
+            Stmt::Let {expr: Some(expr), ..} | Stmt::Return {expr: Some(expr), ..} | Stmt::Expr {expr, ..} => check_expr(shader, expr) ?,
+            Stmt::Block {block, ..} => check_block(shader, decl, block) ?,
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+fn check_expr(shader: &ShaderAst, expr: &Expr) -> Result<(), LiveError> {
+    let (callee, arg_exprs) = match &expr.kind {
+        ExprKind::Cond {expr, expr_if_true, expr_if_false, ..} => {
+            check_expr(shader, expr) ?;
+            check_expr(shader, expr_if_true) ?;
+            return check_expr(shader, expr_if_false)
+        }
+        ExprKind::Bin {left_expr, right_expr, ..} | ExprKind::Index {expr: left_expr, index_expr: right_expr, ..} => {
+            check_expr(shader, left_expr) ?;
+            return check_expr(shader, right_expr)
+        }
+        ExprKind::Un {expr, ..} | ExprKind::Field {expr, ..} => return check_expr(shader, expr),
+        ExprKind::MethodCall {ident, arg_exprs, ..} => match arg_exprs[0].ty.borrow().as_ref() {
+            Some(Ty::Struct {ident: struct_ident}) => (shader.find_fn_decl_for_call(IdentPath::from_two(*struct_ident, *ident), arg_exprs), arg_exprs),
+            _ => (None, arg_exprs),
+        },
+        ExprKind::Call {ident_path, arg_exprs, ..} => (shader.find_fn_decl_for_call(*ident_path, arg_exprs), arg_exprs),
+        ExprKind::MacroCall {arg_exprs, ..} | ExprKind::ConsCall {arg_exprs, ..} => (None, arg_exprs),
+        ExprKind::Var {..} | ExprKind::Lit {..} => return Ok(()),
+    };
+    for (index, arg_expr) in arg_exprs.iter().enumerate() {
+        check_expr(shader, arg_expr) ?;
+        if let Some(param) = callee.and_then( | callee | callee.params.get(index)).filter( | param | param.is_inout) {
+            if !is_function_place(arg_expr) {
+                return Err(LiveError {
+                    span: arg_expr.span,
+                    message: format!(
+                        "WGSL can only pass a local, or a field or array element of one, to inout parameter `{}`",
+                        param.ident
+                    ),
+                    ..LiveError::default()
+                })
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_function_place(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Var {kind, ..} => kind.get() == Some(VarKind::Local),
+        ExprKind::Field {expr, ..} => matches!(expr.ty.borrow().as_ref(), Some(Ty::Struct {..})) && is_function_place(expr),
+        ExprKind::Index {expr, ..} => matches!(expr.ty.borrow().as_ref(), Some(Ty::Array {..})) && is_function_place(expr),
+        _ => false,
+    }
+}
+
+struct ShaderGenerator<'a, 'b> {
+    shader: &'a ShaderAst,
+    create_const_table: bool,
+    string: &'a mut String,
+    backend_writer: &'a WgslBackendWriter<'a, 'b>
+}
+
+impl<'a, 'b> ShaderGenerator<'a, 'b> {
+    fn generate_shader(&mut self) {
+        writeln!(self.string, "@group(1) @binding(0) var mpsc_default_sampler: sampler;").unwrap();
+        writeln!(self.string, "fn sample2d(tex: texture_2d<f32>, pos: vec2<f32>) -> vec4<f32> {{return textureSampleLevel(tex, mpsc_default_sampler, pos, 0.0);}}").unwrap();
+        writeln!(self.string, "fn sample_cube(tex: texture_cube<f32>, dir: vec3<f32>) -> vec4<f32> {{return textureSampleLevel(tex, mpsc_default_sampler, dir, 0.0);}}").unwrap();
+        writeln!(self.string, "fn sample3d(tex: texture_3d<f32>, pos: vec3<f32>) -> vec4<f32> {{return textureSampleLevel(tex, mpsc_default_sampler, pos, 0.0);}}").unwrap();
+        // WGSL % truncates where mod floors
+        for ty_lit in &[TyLit::Float, TyLit::Vec2, TyLit::Vec3, TyLit::Vec4] {
+            write!(self.string, "fn mpsc_mod_{}(a: ", ty_lit).unwrap();
+            self.write_ty_lit(*ty_lit);
+            write!(self.string, ", b: ").unwrap();
+            self.write_ty_lit(*ty_lit);
+            write!(self.string, ") -> ").unwrap();
+            self.write_ty_lit(*ty_lit);
+            writeln!(self.string, " {{return a - b * floor(a / b);}}").unwrap();
+        }
+        self.generate_struct_decls();
+        self.generate_uniform_decls();
+        self.generate_texture_decls();
+        self.generate_private_decls();
+        self.generate_varying_struct();
+        self.generate_const_decls();
+        let vertex_decl = self.shader.find_fn_decl(IdentPath::from_str("vertex")).unwrap();
+        let fragment_decl = self.shader.find_fn_decl(IdentPath::from_str("pixel")).unwrap();
+        for &(ty_lit, ref param_tys) in vertex_decl
+            .cons_fn_deps
+            .borrow_mut()
+            .as_ref()
+            .unwrap()
+            .union(fragment_decl.cons_fn_deps.borrow().as_ref().unwrap())
+        {
+            self.generate_cons_fn(ty_lit, param_tys);
+        }
+        let mut visited = HashSet::new();
+        self.generate_fn_decl(vertex_decl, &mut visited);
+        self.generate_fn_decl(fragment_decl, &mut visited);
+        self.generate_vertex_main();
+        self.generate_fragment_main();
+    }
+
+    fn generate_struct_decls(&mut self) {
+        for decl in &self.shader.decls {
+            if let Decl::Struct(decl) = decl {
+                writeln!(self.string, "struct {} {{", decl.ident).unwrap();
+                for field in &decl.fields {
+                    write!(self.string, "    ").unwrap();
+                    self.write_field_decl(false, field.ident, field.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                writeln!(self.string, "}}").unwrap();
+            }
+        }
+    }
+
+    fn generate_uniform_decls(&mut self) {
+        let mut uniform_blocks = BTreeMap::new();
+        for decl in &self.shader.decls {
+            if let Decl::Uniform(decl) = decl {
+                let uniform_block = uniform_blocks
+                    .entry(decl.block_ident.unwrap_or(Ident::new("default")))
+                    .or_insert(Vec::new());
+                uniform_block.push(decl);
+            }
+        }
+        for (ident, decls) in uniform_blocks {
+            writeln!(self.string, "struct mpsc_{}_Uniforms {{", ident).unwrap();
+            for decl in decls {
+                write!(self.string, "    ").unwrap();
+                self.write_field_decl(true, decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+            }
+            writeln!(self.string, "}}").unwrap();
+            writeln!(
+                self.string,
+                "@group(0) @binding({1}) var<uniform> mpsc_{0}_uniforms: mpsc_{0}_Uniforms;",
+                ident,
+                uniform_block_binding(ident)
+            ).unwrap();
+        }
+
+        let livestyle_uniform_deps = self.shader.livestyle_uniform_deps.borrow();
+        let livestyle_uniform_deps = livestyle_uniform_deps.as_ref().unwrap();
+        if !livestyle_uniform_deps.is_empty() {
+            writeln!(self.string, "struct mpsc_live_Uniforms {{").unwrap();
+            for (ty, qualified_ident_path) in livestyle_uniform_deps {
+                write!(self.string, "    ").unwrap();
+                if *ty == Ty::Vec3 {
+                    write!(self.string, "@size(16) ").unwrap();
+                }
+                qualified_ident_path.write_underscored_ident(self.string);
+                write!(self.string, ": ").unwrap();
+                self.write_ty(ty);
+                writeln!(self.string, ",").unwrap();
+            }
+            writeln!(self.string, "}}").unwrap();
+            writeln!(
+                self.string,
+                "@group(0) @binding({}) var<uniform> mpsc_live_uniforms: mpsc_live_Uniforms;",
+                uniform_block_binding(Ident::new("live"))
+            ).unwrap();
+        }
+
+        if self.create_const_table {
+            writeln!(
+                self.string,
+                "@group(0) @binding({}) var<storage, read> mpsc_const_table: array<f32>;",
+                uniform_block_binding(Ident::new("const_table"))
+            ).unwrap();
+        }
+    }
+
+    fn generate_texture_decls(&mut self) {
+        let mut index = 1;
+        for decl in &self.shader.decls {
+            if let Decl::Texture(decl) = decl {
+                write!(self.string, "@group(1) @binding({}) var ", index).unwrap();
+                self.write_ident(decl.ident);
+                write!(self.string, ": ").unwrap();
+                self.write_ty(decl.ty_expr.ty.borrow().as_ref().unwrap());
+                writeln!(self.string, ";").unwrap();
+                index += 1;
+            }
+        }
+    }
+
+    // geometries, instances and varyings are module scope vars that the entry points
+    // unpack into and pack from, so the functions don't need them passed in
+    fn generate_private_decls(&mut self) {
+        for decl in &self.shader.decls {
+            let (ident, ty_expr) = match decl {
+                Decl::Geometry(decl) => (decl.ident, &decl.ty_expr),
+                Decl::Instance(decl) => (decl.ident, &decl.ty_expr),
+                Decl::Varying(decl) => (decl.ident, &decl.ty_expr),
+                _ => continue,
+            };
+            write!(self.string, "var<private> ").unwrap();
+            self.write_ident(ident);
+            write!(self.string, ": ").unwrap();
+            self.write_ty(ty_expr.ty.borrow().as_ref().unwrap());
+            writeln!(self.string, ";").unwrap();
+        }
+    }
+
+    fn generate_varying_struct(&mut self) {
+        writeln!(self.string, "struct mpsc_Varyings {{").unwrap();
+        writeln!(self.string, "    @builtin(position) mpsc_position: vec4<f32>,").unwrap();
+        let mut packed_varyings_size = self.compute_packed_varyings_size();
+        let mut location = 0;
+        while packed_varyings_size > 0 {
+            let packed_var_size = packed_varyings_size.min(4);
+            writeln!(
+                self.string,
+                "    @location({0}) mpsc_packed_varying_{0}: {1},",
+                location,
+                packed_ty(packed_var_size)
+            ).unwrap();
+            packed_varyings_size -= packed_var_size;
+            location += 1;
+        }
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_const_decls(&mut self) {
+        for decl in &self.shader.decls {
+            if let Decl::Const(decl) = decl {
+                write!(self.string, "const ").unwrap();
+                self.write_ident(decl.ident);
+                write!(self.string, ": ").unwrap();
+                self.write_ty(decl.ty_expr.ty.borrow().as_ref().unwrap());
+                write!(self.string, " = ").unwrap();
+                self.generate_expr(&decl.expr);
+                writeln!(self.string, ";").unwrap();
+            }
+        }
+    }
+
+    fn generate_cons_fn(&mut self, ty_lit: TyLit, param_tys: &[Ty]) {
+        let mut cons_name = format!("mpsc_{}", ty_lit);
+        for param_ty in param_tys {
+            write!(cons_name, "_{}", param_ty).unwrap();
+        }
+        if !self.backend_writer.use_cons_fn(&cons_name) {
+            return
+        }
+
+        write!(self.string, "fn {}(", cons_name).unwrap();
+        let mut sep = "";
+        if param_tys.len() == 1 {
+            write!(self.string, "x: ").unwrap();
+            self.write_ty(&param_tys[0]);
+        } else {
+            for (index, param_ty) in param_tys.iter().enumerate() {
+                write!(self.string, "{}x{}: ", sep, index).unwrap();
+                self.write_ty(param_ty);
+                sep = ", ";
+            }
+        }
+        write!(self.string, ") -> ").unwrap();
+        self.write_ty_lit(ty_lit);
+        writeln!(self.string, " {{").unwrap();
+        write!(self.string, "    return ").unwrap();
+        self.write_ty_lit(ty_lit);
+        write!(self.string, "(").unwrap();
+        let ty = ty_lit.to_ty();
+        if param_tys.len() == 1 {
+            let param_ty = &param_tys[0];
+            match param_ty {
+                Ty::Bool | Ty::Int | Ty::Uint | Ty::Float => {
+                    let mut sep = "";
+                    for _ in 0..ty.size() {
+                        write!(self.string, "{}x", sep).unwrap();
+                        sep = ", ";
+                    }
+                }
+                Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => {
+                    let dst_size = matrix_dim(&ty);
+                    let src_size = matrix_dim(param_ty);
+                    let mut sep = "";
+                    for col_index in 0..dst_size {
+                        for row_index in 0..dst_size {
+                            if row_index < src_size && col_index < src_size {
+                                write!(self.string, "{}x[{}][{}]", sep, col_index, row_index)
+                                    .unwrap();
+                            } else {
+                                write!(
+                                    self.string,
+                                    "{}{}",
+                                    sep,
+                                    if col_index == row_index {"1.0"} else {"0.0"}
+                                )
+                                    .unwrap();
+                            }
+                            sep = ", ";
+                        }
+                    }
+                }
+                _ => panic!(),
+            }
+        } else {
+            let mut sep = "";
+            for (index_0, param_ty) in param_tys.iter().enumerate() {
+                if param_ty.size() == 1 {
+                    write!(self.string, "{}x{}", sep, index_0).unwrap();
+                    sep = ", ";
+                } else {
+                    for index_1 in 0..param_ty.size() {
+                        write!(self.string, "{}x{}[{}]", sep, index_0, index_1).unwrap();
+                        sep = ", ";
+                    }
+                }
+            }
+        }
+        writeln!(self.string, ");").unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_fn_decl(&mut self, decl: &FnDecl, visited: &mut HashSet<IdentPath>) {
+        FnDeclGenerator {
+            shader: self.shader,
+            decl,
+            create_const_table: self.create_const_table,
+            visited,
+            backend_writer: self.backend_writer,
+            string: self.string,
+        }
+        .generate_fn_decl()
+    }
+
+    fn generate_vertex_main(&mut self) {
+        writeln!(self.string, "@vertex").unwrap();
+        write!(self.string, "fn mpsc_vertex_main(").unwrap();
+        let packed_geometries_size = self.compute_packed_size( | decl | match decl {
+            Decl::Geometry(decl) => Some(&decl.ty_expr),
+            _ => None,
+        });
+        let packed_instances_size = self.compute_packed_size( | decl | match decl {
+            Decl::Instance(decl) => Some(&decl.ty_expr),
+            _ => None,
+        });
+        let mut location = 0;
+        let mut sep = "";
+        for (packed_var_name, mut packed_vars_size) in [
+            ("mpsc_packed_geometry", packed_geometries_size),
+            ("mpsc_packed_instance", packed_instances_size)
+        ] {
+            let mut packed_var_index = 0;
+            while packed_vars_size > 0 {
+                let packed_var_size = packed_vars_size.min(4);
+                write!(
+                    self.string,
+                    "{}@location({}) {}_{}: {}",
+                    sep,
+                    location,
+                    packed_var_name,
+                    packed_var_index,
+                    packed_ty(packed_var_size)
+                ).unwrap();
+                sep = ", ";
+                packed_vars_size -= packed_var_size;
+                packed_var_index += 1;
+                location += 1;
+            }
+        }
+        writeln!(self.string, ") -> mpsc_Varyings {{").unwrap();
+        let mut geometry_unpacker = VarPacker::new("mpsc_packed_geometry", packed_geometries_size);
+        let mut instance_unpacker = VarPacker::new("mpsc_packed_instance", packed_instances_size);
+        for decl in &self.shader.decls {
+            match decl {
+                Decl::Geometry(decl) => {
+                    geometry_unpacker.unpack_var(self.string, self.backend_writer, decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                Decl::Instance(decl) => {
+                    instance_unpacker.unpack_var(self.string, self.backend_writer, decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                _ => {}
+            }
+        }
+        writeln!(self.string, "    var mpsc_varyings: mpsc_Varyings;").unwrap();
+        write!(self.string, "    mpsc_varyings.mpsc_position = ").unwrap();
+        self.write_ident(Ident::new("vertex"));
+        writeln!(self.string, "();").unwrap();
+        let mut varying_packer = VarPacker::new("mpsc_varyings.mpsc_packed_varying", self.compute_packed_varyings_size());
+        for decl in &self.shader.decls {
+            match decl {
+                Decl::Geometry(decl) if decl.is_used_in_fragment_shader.get().unwrap() => {
+                    varying_packer.pack_var(self.string, self.backend_writer, decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                Decl::Instance(decl) if decl.is_used_in_fragment_shader.get().unwrap() => {
+                    varying_packer.pack_var(self.string, self.backend_writer, decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                Decl::Varying(decl) => {
+                    varying_packer.pack_var(self.string, self.backend_writer, decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                _ => {}
+            }
+        }
+        writeln!(self.string, "    return mpsc_varyings;").unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_fragment_main(&mut self) {
+        writeln!(self.string, "@fragment").unwrap();
+        writeln!(self.string, "fn mpsc_fragment_main(mpsc_varyings: mpsc_Varyings) -> @location(0) vec4<f32> {{").unwrap();
+        let mut varying_unpacker = VarPacker::new("mpsc_varyings.mpsc_packed_varying", self.compute_packed_varyings_size());
+        for decl in &self.shader.decls {
+            match decl {
+                Decl::Geometry(decl) if decl.is_used_in_fragment_shader.get().unwrap() => {
+                    varying_unpacker.unpack_var(self.string, self.backend_writer, decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                Decl::Instance(decl) if decl.is_used_in_fragment_shader.get().unwrap() => {
+                    varying_unpacker.unpack_var(self.string, self.backend_writer, decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                Decl::Varying(decl) => {
+                    varying_unpacker.unpack_var(self.string, self.backend_writer, decl.ident, decl.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                _ => {}
+            }
+        }
+        write!(self.string, "    return ").unwrap();
+        self.write_ident(Ident::new("pixel"));
+        writeln!(self.string, "();").unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn compute_packed_size(&self, select: impl Fn(&Decl) -> Option<&TyExpr>) -> usize {
+        self.shader.decls.iter().filter_map(select).map( | ty_expr | {
+            ty_expr.ty.borrow().as_ref().unwrap().size()
+        }).sum()
+    }
+
+    fn compute_packed_varyings_size(&self) -> usize {
+        self.compute_packed_size( | decl | match decl {
+            Decl::Geometry(decl) if decl.is_used_in_fragment_shader.get().unwrap() => Some(&decl.ty_expr),
+            Decl::Instance(decl) if decl.is_used_in_fragment_shader.get().unwrap() => Some(&decl.ty_expr),
+            Decl::Varying(decl) => Some(&decl.ty_expr),
+            _ => None,
+        })
+    }
+
+    fn generate_expr(&mut self, expr: &Expr) {
+        ExprGenerator {
+            shader: self.shader,
+            decl: None,
+            backend_writer: self.backend_writer,
+            create_const_table: self.create_const_table,
+            string: self.string,
+        }
+        .generate_expr(expr)
+    }
+
+    fn write_field_decl(&mut self, is_uniform: bool, ident: Ident, ty: &Ty) {
+        if is_uniform && *ty == Ty::Vec3 {
+            write!(self.string, "@size(16) ").unwrap();
+        }
+        self.write_ident(ident);
+        write!(self.string, ": ").unwrap();
+        self.write_ty(ty);
+        writeln!(self.string, ",").unwrap();
+    }
+
+    fn write_ty(&mut self, ty: &Ty) {
+        self.backend_writer.write_ty(self.string, ty);
+    }
+
+    fn write_ident(&mut self, ident: Ident) {
+        self.backend_writer.write_ident(self.string, ident);
+    }
+
+    fn write_ty_lit(&mut self, ty_lit: TyLit) {
+        self.backend_writer.write_ty_lit(self.string, ty_lit);
+    }
+}
+
+fn uniform_block_binding(ident: Ident) -> usize {
+    ident.with( | ident_string | match ident_string {
+        "pass" => 0,
+        "view" => 1,
+        "draw" => 2,
+        "default" => 3,
+        "live" => 4,
+        "const_table" => 5,
+        _ => panic!("unexpected uniform block {}", ident_string),
+    })
+}
+
+fn packed_ty(packed_var_size: usize) -> &'static str {
+    match packed_var_size {
+        1 => "f32",
+        2 => "vec2<f32>",
+        3 => "vec3<f32>",
+        4 => "vec4<f32>",
+        _ => panic!(),
+    }
+}
+
+fn matrix_dim(ty: &Ty) -> usize {
+    match ty {
+        Ty::Mat2 => 2,
+        Ty::Mat3 => 3,
+        Ty::Mat4 => 4,
+        _ => panic!(),
+    }
+}
+
+struct FnDeclGenerator<'a, 'b> {
+    shader: &'a ShaderAst,
+    decl: &'a FnDecl,
+    create_const_table: bool,
+    visited: &'a mut HashSet<IdentPath>,
+    string: &'a mut String,
+    backend_writer: &'a WgslBackendWriter<'a, 'b>
+}
+
+impl<'a, 'b> FnDeclGenerator<'a, 'b> {
+    fn generate_fn_decl(&mut self) {
+        if self.visited.contains(&self.decl.ident_path) {
+            return;
+        }
+        for &callee in self.decl.callees.borrow().as_ref().unwrap().iter() {
+            FnDeclGenerator {
+                shader: self.shader,
+                backend_writer: self.backend_writer,
+                decl: self.shader.find_fn_decl(callee).unwrap(),
+                create_const_table: self.create_const_table,
+                visited: self.visited,
+                string: self.string,
+            }
+            .generate_fn_decl()
+        }
+        write!(self.string, "fn ").unwrap();
+        self.backend_writer.write_ident(self.string, self.decl.ident_path.to_struct_fn_ident());
+        write!(self.string, "(").unwrap();
+        let mut sep = "";
+        for param in &self.decl.params {
+            write!(self.string, "{}", sep).unwrap();
+            self.backend_writer.write_ident(self.string, param.ident);
+            write!(self.string, ": ").unwrap();
+            if param.is_inout {
+                write!(self.string, "ptr<function, ").unwrap();
+                self.backend_writer.write_ty(self.string, param.ty_expr.ty.borrow().as_ref().unwrap());
+                write!(self.string, ">").unwrap();
+            }
+            else {
+                self.backend_writer.write_ty(self.string, param.ty_expr.ty.borrow().as_ref().unwrap());
+            }
+            sep = ", ";
+        }
+        write!(self.string, ") ").unwrap();
+        let return_ty = self.decl.return_ty.borrow();
+        if *return_ty.as_ref().unwrap() != Ty::Void {
+            write!(self.string, "-> ").unwrap();
+            self.backend_writer.write_ty(self.string, return_ty.as_ref().unwrap());
+            write!(self.string, " ").unwrap();
+        }
+        self.generate_block(&self.decl.block);
+        writeln!(self.string).unwrap();
+        self.visited.insert(self.decl.ident_path);
+    }
+
+    fn generate_block(&mut self, block: &Block) {
+        BlockGenerator {
+            shader: self.shader,
+            decl: self.decl,
+            backend_writer: self.backend_writer,
+            create_const_table: self.create_const_table,
+            indent_level: 0,
+            string: self.string,
+        }
+        .generate_block(block)
+    }
+}
+
+// packs vars into, or unpacks them from, a run of vec4s. WGSL can only assign a single
+// component of a vector, so it goes one component at a time, converting to and from float
+struct VarPacker<'a> {
+    packed_var_name: &'a str,
+    packed_vars_size: usize,
+    packed_var_index: usize,
+    packed_var_size: usize,
+    packed_var_offset: usize,
+}
+
+impl<'a> VarPacker<'a> {
+    fn new(packed_var_name: &'a str, packed_vars_size: usize) -> VarPacker<'a> {
+        VarPacker {
+            packed_var_name,
+            packed_vars_size,
+            packed_var_index: 0,
+            packed_var_size: packed_vars_size.min(4),
+            packed_var_offset: 0,
+        }
+    }
+
+    fn next_packed_component(&mut self) -> String {
+        let mut component = format!("{}_{}", self.packed_var_name, self.packed_var_index);
+        if self.packed_var_size > 1 {
+            write!(component, ".{}", ["x", "y", "z", "w"][self.packed_var_offset]).unwrap();
+        }
+        self.packed_var_offset += 1;
+        if self.packed_var_offset == self.packed_var_size {
+            self.packed_vars_size -= self.packed_var_size;
+            self.packed_var_index += 1;
+            self.packed_var_size = self.packed_vars_size.min(4);
+            self.packed_var_offset = 0;
+        }
+        component
+    }
+
+    fn pack_var(&mut self, string: &mut String, backend_writer: &WgslBackendWriter, ident: Ident, ty: &Ty) {
+        for index in 0..ty.size() {
+            let packed_component = self.next_packed_component();
+            write!(string, "    {} = ", packed_component).unwrap();
+            if !is_float_ty(ty) {
+                write!(string, "f32(").unwrap();
+            }
+            backend_writer.write_ident(string, ident);
+            write_component(string, ty, index);
+            if !is_float_ty(ty) {
+                write!(string, ")").unwrap();
+            }
+            writeln!(string, ";").unwrap();
+        }
+    }
+
+    fn unpack_var(&mut self, string: &mut String, backend_writer: &WgslBackendWriter, ident: Ident, ty: &Ty) {
+        for index in 0..ty.size() {
+            let packed_component = self.next_packed_component();
+            write!(string, "    ").unwrap();
+            backend_writer.write_ident(string, ident);
+            write_component(string, ty, index);
+            write!(string, " = ").unwrap();
+            if is_float_ty(ty) {
+                writeln!(string, "{};", packed_component).unwrap();
+            } else {
+                backend_writer.write_ty_lit(string, scalar_ty_lit(ty));
+                writeln!(string, "({});", packed_component).unwrap();
+            }
+        }
+    }
+}
+
+fn write_component(string: &mut String, ty: &Ty, index: usize) {
+    if ty.is_matrix() {
+        let dim = matrix_dim(ty);
+        write!(string, "[{}][{}]", index / dim, index % dim).unwrap();
+    } else if ty.is_vector() {
+        write!(string, ".{}", ["x", "y", "z", "w"][index]).unwrap();
+    }
+}
+
+fn scalar_ty_lit(ty: &Ty) -> TyLit {
+    match ty {
+        Ty::Bool | Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => TyLit::Bool,
+        Ty::Int | Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => TyLit::Int,
+        Ty::Uint | Ty::Uvec2 | Ty::Uvec3 | Ty::Uvec4 => TyLit::Uint,
+        _ => TyLit::Float,
+    }
+}
+
+fn is_float_ty(ty: &Ty) -> bool {
+    scalar_ty_lit(ty) == TyLit::Float
+}
+
+struct WgslBackendWriter<'a, 'b> {
+    pub env: &'a Env<'b>
+}
+
+impl<'a, 'b> WgslBackendWriter<'a, 'b> {
+    fn write_ty(&self, string: &mut String, ty: &Ty) {
+        match ty {
+            Ty::Array {elem_ty, len} => {
+                write!(string, "array<").unwrap();
+                self.write_ty(string, elem_ty);
+                write!(string, ", {}>", len).unwrap();
+            }
+            Ty::Struct {ident} => {
+                write!(string, "{}", ident).unwrap();
+            }
+            _ => self.write_ty_lit(string, ty.maybe_ty_lit().unwrap()),
+        }
+    }
+}
+
+impl<'a, 'b> BackendWriter for WgslBackendWriter<'a, 'b> {
+    fn write_call_expr_hidden_args(&self, _string: &mut String, _use_const_table: bool, _ident_path: IdentPath, _shader: &ShaderAst, _sep: &str) {
+    }
+
+    fn generate_var_expr(&self, string: &mut String, span: Span, ident_path: IdentPath, kind: &Cell<Option<VarKind >>, shader: &ShaderAst, decl: &FnDecl, _ty: &Option<Ty>) {
+        match kind.get().unwrap() {
+            // an inout parameter is a pointer, check_shader_support makes sure no local hides it
+            VarKind::Local if is_inout_param(decl, ident_path.get_single().expect("unexpected")) => {
+                write!(string, "(*").unwrap();
+                self.write_ident(string, ident_path.get_single().expect("unexpected"));
+                write!(string, ")").unwrap();
+                return
+            }
+            VarKind::Uniform => {
+                write!(
+                    string,
+                    "mpsc_{}_uniforms.",
+                    shader
+                        .find_uniform_decl(ident_path.get_single().expect("unexpected"))
+                        .unwrap()
+                        .block_ident
+                        .unwrap_or(Ident::new("default")),
+                )
+                    .unwrap();
+            }
+            VarKind::LiveStyle => {
+                let qualified = self.env.qualify_ident_path(span.live_body_id, ident_path);
+                write!(string, "mpsc_live_uniforms.").unwrap();
+                qualified.write_underscored_ident(string);
+                return
+            },
+            _ => ()
+        }
+        self.write_ident(string, ident_path.get_single().expect("unexpected"));
+    }
+
+    fn needs_mul_fn_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+
+    fn needs_unpack_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+
+    fn needs_fn_for_int_ops(&self) -> bool {
+        false
+    }
+
+    fn needs_const_loop_bounds(&self) -> bool {
+        false
+    }
+
+    fn needs_int_for_uint(&self) -> bool {
+        false
+    }
+
+    fn needs_assign_as_stmt(&self) -> bool {
+        true
+    }
+
+    fn needs_select_for_cond_expr(&self) -> bool {
+        true
+    }
+
+    fn needs_uint_shift_amount(&self) -> bool {
+        true
+    }
+
+    fn needs_operators_for_relational_fns(&self) -> bool {
+        true
+    }
+
+    fn needs_splat_for_scalar_args(&self) -> bool {
+        true
+    }
+
+    fn needs_block_for_if_body(&self) -> bool {
+        true
+    }
+
+    fn needs_pointer_for_inout(&self) -> bool {
+        true
+    }
+
+    fn const_table_is_vec4(&self) -> bool {
+        false
+    }
+
+    fn use_cons_fn(&self, what: &str) -> bool {
+        matches!(what, "mpsc_mat3_mat4" | "mpsc_mat2_mat4" | "mpsc_mat2_mat3")
+    }
+
+    // only used for locals, which are always vars
+    fn write_var_decl(
+        &self,
+        string: &mut String,
+        _is_inout: bool,
+        _is_packed: bool,
+        ident: Ident,
+        ty: &Ty,
+    ) {
+        write!(string, "var ").unwrap();
+        self.write_ident(string, ident);
+        write!(string, ": ").unwrap();
+        self.write_ty(string, ty);
+    }
+
+    fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit) {
+        write!(
+            string,
+            "{}",
+            match ty_lit {
+                TyLit::Bool => "bool",
+                TyLit::Int => "i32",
+                TyLit::Uint => "u32",
+                TyLit::Float => "f32",
+                TyLit::Bvec2 => "vec2<bool>",
+                TyLit::Bvec3 => "vec3<bool>",
+                TyLit::Bvec4 => "vec4<bool>",
+                TyLit::Ivec2 => "vec2<i32>",
+                TyLit::Ivec3 => "vec3<i32>",
+                TyLit::Ivec4 => "vec4<i32>",
+                TyLit::Uvec2 => "vec2<u32>",
+                TyLit::Uvec3 => "vec3<u32>",
+                TyLit::Uvec4 => "vec4<u32>",
+                TyLit::Vec2 => "vec2<f32>",
+                TyLit::Vec3 => "vec3<f32>",
+                TyLit::Vec4 => "vec4<f32>",
+                TyLit::Mat2 => "mat2x2<f32>",
+                TyLit::Mat3 => "mat3x3<f32>",
+                TyLit::Mat4 => "mat4x4<f32>",
+                TyLit::Texture2D => "texture_2d<f32>",
+                TyLit::TextureCube => "texture_cube<f32>",
+                TyLit::Texture3D => "texture_3d<f32>",
+            }
+        )
+            .unwrap();
+    }
+
+    fn write_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]) {
+        if ident == Ident::new("atan") && arg_exprs.len() == 2 {
+            write!(string, "atan2").unwrap();
+        }
+        else if ident == Ident::new("mod") {
+            write!(string, "mpsc_mod_{}", arg_exprs[0].ty.borrow().as_ref().unwrap()).unwrap();
+        }
+        else if ident == Ident::new("dFdx") {
+            write!(string, "dpdx").unwrap();
+        }
+        else if ident == Ident::new("dFdy") {
+            write!(string, "dpdy").unwrap();
+        }
+        else if ident == Ident::new("inversesqrt") {
+            write!(string, "inverseSqrt").unwrap();
+        }
+        else if ident == Ident::new("faceforward") {
+            write!(string, "faceForward").unwrap();
+        }
+        else {
+            self.write_ident(string, ident);
+        }
+    }
+
+    fn write_ident(&self, string: &mut String, ident: Ident) {
+        ident.with( | ident_string | {
+            if ident_string.contains("::") {
+                write!(string, "mpsc_{}", ident_string.replace("::", "_")).unwrap()
+            } else {
+                // do a remapping
+                write!(
+                    string,
+                    "{}",
+                    match ident_string {
+                        "alias" => "mpsc_alias",
+                        "array" => "mpsc_array",
+                        "bitcast" => "mpsc_bitcast",
+                        "case" => "mpsc_case",
+                        "const_assert" => "mpsc_const_assert",
+                        "continuing" => "mpsc_continuing",
+                        "default" => "mpsc_default",
+                        "diagnostic" => "mpsc_diagnostic",
+                        "discard" => "mpsc_discard",
+                        "enable" => "mpsc_enable",
+                        "f16" => "mpsc_f16",
+                        "f32" => "mpsc_f32",
+                        "fallthrough" => "mpsc_fallthrough",
+                        "fn" => "mpsc_fn",
+                        "function" => "mpsc_function",
+                        "i32" => "mpsc_i32",
+                        "let" => "mpsc_let",
+                        "mat2x2" => "mpsc_mat2x2",
+                        "mat3x3" => "mpsc_mat3x3",
+                        "mat4x4" => "mpsc_mat4x4",
+                        "override" => "mpsc_override",
+                        "private" => "mpsc_private",
+                        "ptr" => "mpsc_ptr",
+                        "read" => "mpsc_read",
+                        "read_write" => "mpsc_read_write",
+                        "requires" => "mpsc_requires",
+                        "sampler" => "mpsc_sampler",
+                        "select" => "mpsc_select",
+                        "self" => "mpsc_self",
+                        "storage" => "mpsc_storage",
+                        "switch" => "mpsc_switch",
+                        "type" => "mpsc_type",
+                        "u32" => "mpsc_u32",
+                        "var" => "mpsc_var",
+                        "workgroup" => "mpsc_workgroup",
+                        "write" => "mpsc_write",
+                        _ => ident_string,
+                    }
+                )
+                    .unwrap()
+            }
+        })
+    }
+}
@@ -0,0 +1,1216 @@
+use crate::env::VarKind;
+use crate::error::LiveError;
+use crate::generate::DEFAULT_LOOP_MAX;
+use crate::ident::{Ident, IdentPath};
+use crate::lit::TyLit;
+use crate::livestyles::LiveStyles;
+use crate::math::{Mat4, Vec2, Vec3, Vec4};
+use crate::shaderast::*;
+use crate::span::Span;
+use crate::swizzle::Swizzle;
+use crate::ty::Ty;
+use crate::val::Val;
+use std::collections::HashMap;
+
+// Runs the functions of an analysed shader on the CPU, so a shader can be checked pixel
+// by pixel without a GPU. Geometries, instances, uniforms and varyings start out zeroed
+// and are set by name, running vertex() leaves the varyings it writes for pixel(), there
+// is no rasterizer in between. dFdx and dFdy have no neighbouring pixels to look at and
+// return zero. Textures are sampled with linear filtering and wrap addressing.
+pub struct Interpreter<'a> {
+    shader: &'a ShaderAst,
+    live_styles: &'a LiveStyles,
+    globals: HashMap<Ident, Value>,
+    textures: HashMap<Ident, TextureData>,
+    // the scopes of each function being called
+    frames: Vec<Vec<HashMap<Ident, Value>>>,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(shader: &'a ShaderAst, live_styles: &'a LiveStyles) -> Interpreter<'a> {
+        let mut interpreter = Interpreter {
+            shader,
+            live_styles,
+            globals: HashMap::new(),
+            textures: HashMap::new(),
+            frames: Vec::new(),
+        };
+        for decl in &shader.decls {
+            let (ident, ty_expr) = match decl {
+                Decl::Geometry(decl) => (decl.ident, &decl.ty_expr),
+                Decl::Instance(decl) => (decl.ident, &decl.ty_expr),
+                Decl::Uniform(decl) => (decl.ident, &decl.ty_expr),
+                Decl::Varying(decl) => (decl.ident, &decl.ty_expr),
+                _ => continue,
+            };
+            let value = interpreter.zero_value(ty_expr.ty.borrow().as_ref().unwrap());
+            interpreter.globals.insert(ident, value);
+        }
+        interpreter
+    }
+
+    // sets a geometry, instance, uniform or varying
+    pub fn set_var(&mut self, ident: &str, value: impl Into<Value>) {
+        let value = value.into();
+        match self.globals.get_mut(&Ident::new(ident)) {
+            Some(global) => *global = value,
+            None => panic!("shader has no geometry, instance, uniform or varying `{}`", ident),
+        }
+    }
+
+    pub fn get_var(&self, ident: &str) -> Option<&Value> {
+        self.globals.get(&Ident::new(ident))
+    }
+
+    pub fn set_texture(&mut self, ident: &str, texture: TextureData) {
+        self.textures.insert(Ident::new(ident), texture);
+    }
+
+    pub fn run_vertex(&mut self) -> Result<Vec4, LiveError> {
+        Ok(self.call("vertex", Vec::new())?.to_vec4().unwrap())
+    }
+
+    pub fn run_pixel(&mut self) -> Result<Vec4, LiveError> {
+        Ok(self.call("pixel", Vec::new())?.to_vec4().unwrap())
+    }
+
+    // calls a function of the shader, struct functions are named as in `Cx::foo`
+    pub fn call(&mut self, name: &str, args: Vec<Value>) -> Result<Value, LiveError> {
+        let ident_path = match name.find("::") {
+            Some(index) => IdentPath::from_two(Ident::new(&name[..index]), Ident::new(&name[index + 2..])),
+            None => IdentPath::from_str(name),
+        };
+        let decl = self.shader.find_fn_decl(ident_path).unwrap_or_else( || panic!("shader has no function `{}`", name));
+        let scope = decl.params.iter().map( | param | param.ident).zip(args).collect();
+        let (value, _) = self.call_fn_decl(decl, scope)?;
+        Ok(value)
+    }
+
+    fn call_fn_decl(&mut self, decl: &FnDecl, scope: HashMap<Ident, Value>) -> Result<(Value, HashMap<Ident, Value>), LiveError> {
+        self.frames.push(vec![scope]);
+        let flow = self.exec_block(&decl.block);
+        let mut frame = self.frames.pop().unwrap();
+        let value = match flow? {
+            Flow::Return(Some(value)) => value,
+            _ => Value::Void,
+        };
+        Ok((value, frame.remove(0)))
+    }
+
+    fn scopes_mut(&mut self) -> &mut Vec<HashMap<Ident, Value>> {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn exec_block(&mut self, block: &Block) -> Result<Flow, LiveError> {
+        self.scopes_mut().push(HashMap::new());
+        let mut result = Ok(Flow::Next);
+        for stmt in &block.stmts {
+            match self.exec_stmt(stmt) {
+                Ok(Flow::Next) => {}
+                other => {
+                    result = other;
+                    break;
+                }
+            }
+        }
+        self.scopes_mut().pop();
+        result
+    }
+
+    fn exec_stmt(&mut self, stmt: &Stmt) -> Result<Flow, LiveError> {
+        match stmt {
+            Stmt::Break {..} => Ok(Flow::Break),
+            Stmt::Continue {..} => Ok(Flow::Continue),
+            Stmt::For {
+                span,
+                ident,
+                from_expr,
+                to_expr,
+                step_expr,
+                max_expr,
+                block,
+            } => self.exec_for_stmt(*span, *ident, from_expr, to_expr, step_expr, max_expr, block),
+            Stmt::Loop {span, max_expr, block} => self.exec_loop(*span, None, max_expr, block),
+            Stmt::While {span, expr, max_expr, block} => self.exec_loop(*span, Some(expr), max_expr, block),
+            Stmt::If {
+                expr,
+                block_if_true,
+                block_if_false,
+                ..
+            } => {
+                if self.eval_expr(expr)?.to_bool().unwrap() {
+                    self.exec_block(block_if_true)
+                } else if let Some(block_if_false) = block_if_false {
+                    self.exec_block(block_if_false)
+                } else {
+                    Ok(Flow::Next)
+                }
+            }
+            Stmt::Let {ty, ident, expr, ..} => {
+                let value = match expr {
+                    Some(expr) => self.eval_expr(expr)?,
+                    None => self.zero_value(ty.borrow().as_ref().unwrap()),
+                };
+                self.scopes_mut().last_mut().unwrap().insert(*ident, value);
+                Ok(Flow::Next)
+            }
+            Stmt::Return {expr, ..} => Ok(Flow::Return(match expr {
+                Some(expr) => Some(self.eval_expr(expr)?),
+                None => None,
+            })),
+            Stmt::Block {block, ..} => self.exec_block(block),
+            Stmt::Expr {expr, ..} => {
+                self.eval_expr(expr)?;
+                Ok(Flow::Next)
+            }
+        }
+    }
+
+    // the loop bounds work out the way generate_for_stmt writes them
+    #[allow(clippy::too_many_arguments)]
+    fn exec_for_stmt(
+        &mut self,
+        span: Span,
+        ident: Ident,
+        from_expr: &Expr,
+        to_expr: &Expr,
+        step_expr: &Option<Expr>,
+        max_expr: &Option<Expr>,
+        block: &Block,
+    ) -> Result<Flow, LiveError> {
+        let is_const = is_const(from_expr) && is_const(to_expr);
+        let from = self.eval_expr(from_expr)?.to_int().unwrap();
+        let step = match step_expr {
+            Some(step_expr) => self.eval_expr(step_expr)?.to_int().unwrap(),
+            None if is_const && from > self.eval_expr(to_expr)?.to_int().unwrap() => -1,
+            None => 1,
+        };
+        let mut counter = LoopCounter::new(span, is_const, max_expr);
+        let mut value = if step > 0 {from} else {from - 1};
+        while counter.next()? {
+            let to = self.eval_expr(to_expr)?.to_int().unwrap();
+            if (step > 0 && value >= to) || (step < 0 && value < to) {
+                break;
+            }
+            let mut scope = HashMap::new();
+            scope.insert(ident, Value::Ints(vec![value]));
+            self.scopes_mut().push(scope);
+            let flow = self.exec_block(block);
+            self.scopes_mut().pop();
+            match flow? {
+                Flow::Break => break,
+                Flow::Return(value) => return Ok(Flow::Return(value)),
+                _ => {}
+            }
+            value = value.wrapping_add(step);
+        }
+        Ok(Flow::Next)
+    }
+
+    fn exec_loop(&mut self, span: Span, expr: Option<&Expr>, max_expr: &Option<Expr>, block: &Block) -> Result<Flow, LiveError> {
+        let mut counter = LoopCounter::new(span, false, max_expr);
+        while counter.next()? {
+            if let Some(expr) = expr {
+                if !self.eval_expr(expr)?.to_bool().unwrap() {
+                    break;
+                }
+            }
+            match self.exec_block(block)? {
+                Flow::Break => break,
+                Flow::Return(value) => return Ok(Flow::Return(value)),
+                _ => {}
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn eval_expr(&mut self, expr: &Expr) -> Result<Value, LiveError> {
+        if let Some(Some(val)) = expr.const_val.borrow().as_ref() {
+            return Ok(Value::from_val(val));
+        }
+        match &expr.kind {
+            ExprKind::Cond {
+                expr,
+                expr_if_true,
+                expr_if_false,
+                ..
+            } => {
+                if self.eval_expr(expr)?.to_bool().unwrap() {
+                    self.eval_expr(expr_if_true)
+                } else {
+                    self.eval_expr(expr_if_false)
+                }
+            }
+            ExprKind::Bin {
+                span,
+                op,
+                left_expr,
+                right_expr,
+            } => self.eval_bin_expr(*span, *op, left_expr, right_expr),
+            ExprKind::Un {op, expr, ..} => {
+                let value = self.eval_expr(expr)?;
+                Ok(match (op, value) {
+                    (UnOp::Not, Value::Bools(xs)) => Value::Bools(xs.iter().map( | x | !x).collect()),
+                    (UnOp::Not, Value::Ints(xs)) => Value::Ints(xs.iter().map( | x | !x).collect()),
+                    (UnOp::Not, Value::Uints(xs)) => Value::Uints(xs.iter().map( | x | !x).collect()),
+                    (UnOp::Neg, Value::Ints(xs)) => Value::Ints(xs.iter().map( | x | x.wrapping_neg()).collect()),
+                    (UnOp::Neg, Value::Floats(xs)) => Value::Floats(xs.iter().map( | x | -x).collect()),
+                    _ => panic!(),
+                })
+            }
+            ExprKind::MethodCall {span, ident, arg_exprs} => match arg_exprs[0].ty.borrow().as_ref().unwrap() {
+                Ty::Struct {ident: struct_ident} => {
+                    self.eval_call_expr(*span, IdentPath::from_two(*struct_ident, *ident), arg_exprs)
+                }
+                _ => panic!(),
+            },
+            ExprKind::Field {expr, field_ident, ..} => {
+                let value = self.eval_expr(expr)?;
+                let ty = expr.ty.borrow();
+                match ty.as_ref().unwrap() {
+                    Ty::Struct {ident} => match value {
+                        Value::Struct(mut fields) => Ok(fields.swap_remove(self.field_index(*ident, *field_ident))),
+                        _ => panic!(),
+                    },
+                    _ => Ok(value.select(&swizzle_indices(*field_ident))),
+                }
+            }
+            ExprKind::Index {span, expr, index_expr} => {
+                let value = self.eval_expr(expr)?;
+                let index = self.eval_index(*span, expr, index_expr)?;
+                match value {
+                    Value::Array(mut elems) => Ok(elems.swap_remove(index)),
+                    value => Ok(value.select(&component_indices(expr.ty.borrow().as_ref().unwrap(), index))),
+                }
+            }
+            ExprKind::Call {span, ident_path, arg_exprs} => self.eval_call_expr(*span, *ident_path, arg_exprs),
+            ExprKind::MacroCall {span, ident, ..} => Err(LiveError {
+                span: *span,
+                message: format!("macro `{}!` can't be interpreted", ident),
+                ..LiveError::default()
+            }),
+            ExprKind::ConsCall {ty_lit, arg_exprs, ..} => {
+                let mut args = Vec::new();
+                for arg_expr in arg_exprs {
+                    args.push(self.eval_expr(arg_expr)?);
+                }
+                Ok(self.eval_cons_call(*ty_lit, arg_exprs, args))
+            }
+            ExprKind::Var {span, kind, ident_path} => self.eval_var_expr(*span, kind.get().unwrap(), *ident_path),
+            ExprKind::Lit {lit, ..} => Ok(Value::from_val(&lit.to_val())),
+        }
+    }
+
+    fn eval_bin_expr(&mut self, span: Span, op: BinOp, left_expr: &Expr, right_expr: &Expr) -> Result<Value, LiveError> {
+        let left_ty = left_expr.ty.borrow().clone().unwrap();
+        let right_ty = right_expr.ty.borrow().clone().unwrap();
+        match op {
+            BinOp::Or | BinOp::And => {
+                let left = self.eval_expr(left_expr)?.to_bool().unwrap();
+                if left == (op == BinOp::Or) {
+                    return Ok(Value::Bools(vec![left]));
+                }
+                self.eval_expr(right_expr)
+            }
+            op if op.is_assign() => {
+                let place = self.eval_place(left_expr)?;
+                let right = self.eval_expr(right_expr)?;
+                let value = match assign_op(op) {
+                    Some(op) => {
+                        let left = self.read_place(&place);
+                        eval_bin_op(span, op, left, &left_ty, right, &right_ty)?
+                    }
+                    None => right,
+                };
+                self.write_place(&place, value.clone());
+                Ok(value)
+            }
+            op => {
+                let left = self.eval_expr(left_expr)?;
+                let right = self.eval_expr(right_expr)?;
+                eval_bin_op(span, op, left, &left_ty, right, &right_ty)
+            }
+        }
+    }
+
+    fn eval_call_expr(&mut self, span: Span, ident_path: IdentPath, arg_exprs: &[Expr]) -> Result<Value, LiveError> {
+        if let Some(decl) = self.shader.find_fn_decl_for_call(ident_path, arg_exprs) {
+            let mut scope = HashMap::new();
+            let mut inout_places = Vec::new();
+            for (param, arg_expr) in decl.params.iter().zip(arg_exprs) {
+                let value = if param.is_inout {
+                    let place = self.eval_place(arg_expr)?;
+                    let value = self.read_place(&place);
+                    inout_places.push((param.ident, place));
+                    value
+                } else {
+                    self.eval_expr(arg_expr)?
+                };
+                scope.insert(param.ident, value);
+            }
+            let (value, mut scope) = self.call_fn_decl(decl, scope)?;
+            for (ident, place) in inout_places {
+                self.write_place(&place, scope.remove(&ident).unwrap());
+            }
+            return Ok(value);
+        }
+        let mut args = Vec::new();
+        for arg_expr in arg_exprs {
+            args.push(self.eval_expr(arg_expr)?);
+        }
+        self.eval_builtin(span, ident_path.get_single().unwrap(), args)
+    }
+
+    fn eval_var_expr(&mut self, span: Span, kind: VarKind, ident_path: IdentPath) -> Result<Value, LiveError> {
+        match kind {
+            VarKind::LiveStyle => {
+                let module_path = &self.live_styles.live_bodies[span.live_body_id.0].module_path;
+                let live_item_id = ident_path.qualify(module_path).to_live_item_id();
+                if let Some(color) = self.live_styles.colors.get(&live_item_id) {
+                    return Ok(color.to_vec4().into());
+                }
+                Ok(self.live_styles.floats.get(&live_item_id).unwrap().value.into())
+            }
+            VarKind::Const => {
+                let decl = self.shader.find_const_decl(ident_path.get_single().unwrap()).unwrap();
+                self.eval_expr(&decl.expr)
+            }
+            VarKind::Texture => Ok(Value::Texture(ident_path.get_single().unwrap())),
+            VarKind::Local => Ok(self.find_local(ident_path.get_single().unwrap()).clone()),
+            _ => Ok(self.globals[&ident_path.get_single().unwrap()].clone()),
+        }
+    }
+
+    fn find_local(&mut self, ident: Ident) -> &mut Value {
+        self.scopes_mut().iter_mut().rev().find_map( | scope | scope.get_mut(&ident)).unwrap()
+    }
+
+    fn eval_index(&mut self, span: Span, expr: &Expr, index_expr: &Expr) -> Result<usize, LiveError> {
+        let index = self.eval_expr(index_expr)?.to_int().unwrap();
+        let ty = expr.ty.borrow();
+        let len = match ty.as_ref().unwrap() {
+            Ty::Array {len, ..} => *len,
+            ty if ty.is_matrix() => matrix_dim(ty),
+            ty => ty.size(),
+        };
+        if index < 0 || index as usize >= len {
+            return Err(LiveError {
+                span,
+                message: format!("index {} is out of bounds for `{}`", index, ty.as_ref().unwrap()),
+                ..LiveError::default()
+            });
+        }
+        Ok(index as usize)
+    }
+
+    fn eval_place(&mut self, expr: &Expr) -> Result<Place, LiveError> {
+        match &expr.kind {
+            ExprKind::Var {kind, ident_path, ..} => Ok(Place {
+                is_local: matches!(kind.get().unwrap(), VarKind::Local),
+                ident: ident_path.get_single().unwrap(),
+                path: Vec::new(),
+                comps: None,
+            }),
+            ExprKind::Field {expr, field_ident, ..} => {
+                let mut place = self.eval_place(expr)?;
+                let ty = expr.ty.borrow();
+                match ty.as_ref().unwrap() {
+                    Ty::Struct {ident} => place.path.push(self.field_index(*ident, *field_ident)),
+                    _ => place.select_comps(&swizzle_indices(*field_ident)),
+                }
+                Ok(place)
+            }
+            ExprKind::Index {span, expr, index_expr} => {
+                let mut place = self.eval_place(expr)?;
+                let index = self.eval_index(*span, expr, index_expr)?;
+                let ty = expr.ty.borrow();
+                match ty.as_ref().unwrap() {
+                    Ty::Array {..} => place.path.push(index),
+                    ty => place.select_comps(&component_indices(ty, index)),
+                }
+                Ok(place)
+            }
+            _ => panic!(),
+        }
+    }
+
+    fn place_root(&mut self, place: &Place) -> &mut Value {
+        let mut value = if place.is_local {
+            self.find_local(place.ident)
+        } else {
+            self.globals.get_mut(&place.ident).unwrap()
+        };
+        for &index in &place.path {
+            value = match value {
+                Value::Array(elems) | Value::Struct(elems) => &mut elems[index],
+                _ => panic!(),
+            };
+        }
+        value
+    }
+
+    fn read_place(&mut self, place: &Place) -> Value {
+        let comps = place.comps.clone();
+        let value = self.place_root(place);
+        match comps {
+            Some(comps) => value.select(&comps),
+            None => value.clone(),
+        }
+    }
+
+    fn write_place(&mut self, place: &Place, new_value: Value) {
+        let comps = place.comps.clone();
+        let value = self.place_root(place);
+        match comps {
+            Some(comps) => value.assign_comps(&comps, new_value),
+            None => *value = new_value,
+        }
+    }
+
+    fn field_index(&self, struct_ident: Ident, field_ident: Ident) -> usize {
+        let decl = self.shader.find_struct_decl(struct_ident).unwrap();
+        decl.fields.iter().position( | field | field.ident == field_ident).unwrap()
+    }
+
+    fn zero_value(&self, ty: &Ty) -> Value {
+        match ty {
+            Ty::Void => Value::Void,
+            Ty::Bool | Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => Value::Bools(vec![false; ty.size()]),
+            Ty::Int | Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => Value::Ints(vec![0; ty.size()]),
+            Ty::Uint | Ty::Uvec2 | Ty::Uvec3 | Ty::Uvec4 => Value::Uints(vec![0; ty.size()]),
+            Ty::Texture2D | Ty::TextureCube | Ty::Texture3D => panic!(),
+            Ty::Array {elem_ty, len} => Value::Array(vec![self.zero_value(elem_ty); *len]),
+            Ty::Struct {ident} => Value::Struct(
+                self.shader.find_struct_decl(*ident).unwrap().fields.iter().map( | field | {
+                    self.zero_value(field.ty_expr.ty.borrow().as_ref().unwrap())
+                }).collect()
+            ),
+            _ => Value::Floats(vec![0.0; ty.size()]),
+        }
+    }
+
+    // the GLSL constructor rules: a scalar fills a vector or the diagonal of a matrix, a
+    // matrix from a matrix keeps the overlap and fills in the identity, otherwise the
+    // components of the args are taken in order
+    pub(crate) fn eval_cons_call(&self, ty_lit: TyLit, arg_exprs: &[Expr], args: Vec<Value>) -> Value {
+        let ty = ty_lit.to_ty();
+        let scalar_ty_lit = scalar_ty_lit(&ty);
+        let arg_ty = arg_exprs[0].ty.borrow().clone().unwrap();
+        if ty.is_matrix() {
+            let dim = matrix_dim(&ty);
+            let mut floats = vec![0.0; dim * dim];
+            if args.len() == 1 && arg_ty.is_scalar() {
+                let x = args[0].convert(TyLit::Float).floats()[0];
+                for index in 0..dim {
+                    floats[index * dim + index] = x;
+                }
+            } else if args.len() == 1 && arg_ty.is_matrix() {
+                let arg_dim = matrix_dim(&arg_ty);
+                let arg_floats = args[0].floats();
+                for col_index in 0..dim {
+                    for row_index in 0..dim {
+                        floats[col_index * dim + row_index] = if col_index < arg_dim && row_index < arg_dim {
+                            arg_floats[col_index * arg_dim + row_index]
+                        } else if col_index == row_index {
+                            1.0
+                        } else {
+                            0.0
+                        }
+                    }
+                }
+            } else {
+                let comps = args.iter().flat_map( | arg | arg.convert(TyLit::Float).floats().to_vec()).collect::<Vec<_>>();
+                floats.copy_from_slice(&comps[..dim * dim]);
+            }
+            return Value::Floats(floats);
+        }
+        let size = ty.size();
+        if args.len() == 1 && arg_ty.is_scalar() {
+            return args[0].convert(scalar_ty_lit).select(&vec![0; size]);
+        }
+        let mut comps = Vec::new();
+        for arg in &args {
+            let arg = arg.convert(scalar_ty_lit);
+            comps.extend((0..arg.len()).map( | index | arg.select(&[index])));
+        }
+        let mut value = comps[0].clone();
+        for comp in &comps[1..size] {
+            value.extend(comp);
+        }
+        value
+    }
+
+    pub(crate) fn eval_builtin(&mut self, span: Span, ident: Ident, args: Vec<Value>) -> Result<Value, LiveError> {
+        let name = ident.with( | string | string.to_string());
+        Ok(match name.as_ref() {
+            "abs" => match &args[0] {
+                Value::Ints(xs) => Value::Ints(xs.iter().map( | x | x.wrapping_abs()).collect()),
+                _ => map_floats(&args, | x | x[0].abs()),
+            },
+            "acos" => map_floats(&args, | x | x[0].acos()),
+            "all" => Value::Bools(vec![args[0].bools().iter().all( | x | *x)]),
+            "any" => Value::Bools(vec![args[0].bools().iter().any( | x | *x)]),
+            "asin" => map_floats(&args, | x | x[0].asin()),
+            "atan" if args.len() == 2 => map_floats(&args, | x | x[0].atan2(x[1])),
+            "atan" => map_floats(&args, | x | x[0].atan()),
+            "ceil" => map_floats(&args, | x | x[0].ceil()),
+            "clamp" => match &args[0] {
+                Value::Uints(_) => map_uints(&args, | x | x[0].max(x[1]).min(x[2])),
+                _ => map_floats(&args, | x | x[0].max(x[1]).min(x[2])),
+            },
+            "cos" => map_floats(&args, | x | x[0].cos()),
+            "cross" => {
+                let (a, b) = (args[0].floats(), args[1].floats());
+                Value::Floats(vec![
+                    a[1] * b[2] - b[1] * a[2],
+                    a[2] * b[0] - b[2] * a[0],
+                    a[0] * b[1] - b[0] * a[1],
+                ])
+            }
+            "degrees" => map_floats(&args, | x | x[0].to_degrees()),
+            "dFdx" | "dFdy" => map_floats(&args, | _ | 0.0),
+            "distance" => {
+                let d = map_floats(&args, | x | x[0] - x[1]);
+                Value::Floats(vec![length(d.floats())])
+            }
+            "dot" => Value::Floats(vec![dot(args[0].floats(), args[1].floats())]),
+            "equal" => Value::Bools(compare(&args[0], &args[1], | ordering | ordering == Some(std::cmp::Ordering::Equal))),
+            "exp" => map_floats(&args, | x | x[0].exp()),
+            "exp2" => map_floats(&args, | x | x[0].exp2()),
+            "faceforward" => {
+                if dot(args[2].floats(), args[1].floats()) < 0.0 {
+                    args[0].clone()
+                } else {
+                    map_floats(&args[..1], | x | -x[0])
+                }
+            }
+            "floor" => map_floats(&args, | x | x[0].floor()),
+            "fract" => map_floats(&args, | x | x[0] - x[0].floor()),
+            "greaterThan" => Value::Bools(compare(&args[0], &args[1], | ordering | ordering == Some(std::cmp::Ordering::Greater))),
+            "greaterThanEqual" => Value::Bools(compare(&args[0], &args[1], | ordering | {
+                matches!(ordering, Some(std::cmp::Ordering::Greater) | Some(std::cmp::Ordering::Equal))
+            })),
+            "inverse" => {
+                let mut v = [0.0; 16];
+                v.copy_from_slice(args[0].floats());
+                Value::Floats(Mat4 {v}.invert().v.to_vec())
+            }
+            "inversesqrt" => map_floats(&args, | x | 1.0 / x[0].sqrt()),
+            "length" => Value::Floats(vec![length(args[0].floats())]),
+            "lessThan" => Value::Bools(compare(&args[0], &args[1], | ordering | ordering == Some(std::cmp::Ordering::Less))),
+            "lessThanEqual" => Value::Bools(compare(&args[0], &args[1], | ordering | {
+                matches!(ordering, Some(std::cmp::Ordering::Less) | Some(std::cmp::Ordering::Equal))
+            })),
+            "log" => map_floats(&args, | x | x[0].ln()),
+            "log2" => map_floats(&args, | x | x[0].log2()),
+            "matrixCompMult" => map_floats(&args, | x | x[0] * x[1]),
+            "max" => match &args[0] {
+                Value::Uints(_) => map_uints(&args, | x | x[0].max(x[1])),
+                _ => map_floats(&args, | x | x[0].max(x[1])),
+            },
+            "min" => match &args[0] {
+                Value::Uints(_) => map_uints(&args, | x | x[0].min(x[1])),
+                _ => map_floats(&args, | x | x[0].min(x[1])),
+            },
+            "mix" => map_floats(&args, | x | x[0] * (1.0 - x[2]) + x[1] * x[2]),
+            "mod" => map_floats(&args, | x | x[0] - x[1] * (x[0] / x[1]).floor()),
+            "normalize" => {
+                let length = length(args[0].floats());
+                map_floats(&args, | x | x[0] / length)
+            }
+            "not" => Value::Bools(args[0].bools().iter().map( | x | !x).collect()),
+            "notEqual" => Value::Bools(compare(&args[0], &args[1], | ordering | ordering != Some(std::cmp::Ordering::Equal))),
+            "pow" => map_floats(&args, | x | x[0].powf(x[1])),
+            "radians" => map_floats(&args, | x | x[0].to_radians()),
+            "reflect" => {
+                let d = dot(args[1].floats(), args[0].floats());
+                map_floats(&args, | x | x[0] - 2.0 * d * x[1])
+            }
+            "refract" => {
+                let d = dot(args[1].floats(), args[0].floats());
+                let eta = args[2].floats()[0];
+                let k = 1.0 - eta * eta * (1.0 - d * d);
+                if k < 0.0 {
+                    map_floats(&args[..1], | _ | 0.0)
+                } else {
+                    map_floats(&args, | x | eta * x[0] - (eta * d + k.sqrt()) * x[1])
+                }
+            }
+            "sample2d" | "sample3d" | "sample_cube" => {
+                let texture_ident = match args[0] {
+                    Value::Texture(ident) => ident,
+                    _ => panic!(),
+                };
+                let texture = self.textures.get(&texture_ident).ok_or_else( || LiveError {
+                    span,
+                    message: format!("texture `{}` has no data", texture_ident),
+                    ..LiveError::default()
+                })?;
+                let pos = args[1].floats();
+                let color = match name.as_ref() {
+                    "sample2d" => texture.sample2d(pos[0], pos[1]),
+                    "sample3d" => texture.sample3d(pos[0], pos[1], pos[2]),
+                    _ => texture.sample_cube(pos[0], pos[1], pos[2]),
+                };
+                color.into()
+            }
+            "sign" => map_floats(&args, | x | if x[0] == 0.0 {0.0} else {x[0].signum()}),
+            "sin" => map_floats(&args, | x | x[0].sin()),
+            "smoothstep" => map_floats(&args, | x | {
+                let t = ((x[2] - x[0]) / (x[1] - x[0])).clamp(0.0, 1.0);
+                t * t * (3.0 - 2.0 * t)
+            }),
+            "sqrt" => map_floats(&args, | x | x[0].sqrt()),
+            "step" => map_floats(&args, | x | if x[1] < x[0] {0.0} else {1.0}),
+            "tan" => map_floats(&args, | x | x[0].tan()),
+            "transpose" => {
+                let floats = args[0].floats();
+                let dim = if floats.len() == 16 {4} else {3};
+                Value::Floats((0..dim * dim).map( | index | floats[(index % dim) * dim + index / dim]).collect())
+            }
+            _ => panic!("unknown builtin `{}`", name),
+        })
+    }
+}
+
+// a value computed by the interpreter. vectors and matrices are their components, matrices
+// in column major order, struct fields are in declaration order
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Void,
+    Bools(Vec<bool>),
+    Ints(Vec<i32>),
+    Uints(Vec<u32>),
+    Floats(Vec<f32>),
+    Array(Vec<Value>),
+    Struct(Vec<Value>),
+    Texture(Ident),
+}
+
+impl Value {
+    pub(crate) fn from_val(val: &Val) -> Value {
+        match *val {
+            Val::Bool(val) => val.into(),
+            Val::Int(val) => val.into(),
+            Val::Uint(val) => val.into(),
+            Val::Float(val) => val.into(),
+            Val::Vec4(val) => val.into(),
+        }
+    }
+
+    pub(crate) fn to_val(&self) -> Option<Val> {
+        match self {
+            Value::Bools(xs) if xs.len() == 1 => Some(Val::Bool(xs[0])),
+            Value::Ints(xs) if xs.len() == 1 => Some(Val::Int(xs[0])),
+            Value::Uints(xs) if xs.len() == 1 => Some(Val::Uint(xs[0])),
+            Value::Floats(xs) if xs.len() == 1 => Some(Val::Float(xs[0])),
+            _ => self.to_vec4().map(Val::Vec4),
+        }
+    }
+
+    pub fn to_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bools(xs) if xs.len() == 1 => Some(xs[0]),
+            _ => None,
+        }
+    }
+
+    pub fn to_int(&self) -> Option<i32> {
+        match self {
+            Value::Ints(xs) if xs.len() == 1 => Some(xs[0]),
+            _ => None,
+        }
+    }
+
+    pub fn to_uint(&self) -> Option<u32> {
+        match self {
+            Value::Uints(xs) if xs.len() == 1 => Some(xs[0]),
+            _ => None,
+        }
+    }
+
+    pub fn to_float(&self) -> Option<f32> {
+        match self {
+            Value::Floats(xs) if xs.len() == 1 => Some(xs[0]),
+            _ => None,
+        }
+    }
+
+    pub fn to_vec2(&self) -> Option<Vec2> {
+        match self {
+            Value::Floats(xs) if xs.len() == 2 => Some(Vec2 {x: xs[0], y: xs[1]}),
+            _ => None,
+        }
+    }
+
+    pub fn to_vec3(&self) -> Option<Vec3> {
+        match self {
+            Value::Floats(xs) if xs.len() == 3 => Some(Vec3 {x: xs[0], y: xs[1], z: xs[2]}),
+            _ => None,
+        }
+    }
+
+    pub fn to_vec4(&self) -> Option<Vec4> {
+        match self {
+            Value::Floats(xs) if xs.len() == 4 => Some(Vec4 {x: xs[0], y: xs[1], z: xs[2], w: xs[3]}),
+            _ => None,
+        }
+    }
+
+    fn bools(&self) -> &[bool] {
+        match self {
+            Value::Bools(xs) => xs,
+            _ => panic!(),
+        }
+    }
+
+    fn floats(&self) -> &[f32] {
+        match self {
+            Value::Floats(xs) => xs,
+            _ => panic!(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Value::Bools(xs) => xs.len(),
+            Value::Ints(xs) => xs.len(),
+            Value::Uints(xs) => xs.len(),
+            Value::Floats(xs) => xs.len(),
+            _ => panic!(),
+        }
+    }
+
+    fn select(&self, indices: &[usize]) -> Value {
+        match self {
+            Value::Bools(xs) => Value::Bools(indices.iter().map( | index | xs[*index]).collect()),
+            Value::Ints(xs) => Value::Ints(indices.iter().map( | index | xs[*index]).collect()),
+            Value::Uints(xs) => Value::Uints(indices.iter().map( | index | xs[*index]).collect()),
+            Value::Floats(xs) => Value::Floats(indices.iter().map( | index | xs[*index]).collect()),
+            _ => panic!(),
+        }
+    }
+
+    fn assign_comps(&mut self, indices: &[usize], value: Value) {
+        fn assign<T: Copy>(xs: &mut [T], indices: &[usize], ys: &[T]) {
+            for (index, y) in indices.iter().zip(ys) {
+                xs[*index] = *y;
+            }
+        }
+        match (self, &value) {
+            (Value::Bools(xs), Value::Bools(ys)) => assign(xs, indices, ys),
+            (Value::Ints(xs), Value::Ints(ys)) => assign(xs, indices, ys),
+            (Value::Uints(xs), Value::Uints(ys)) => assign(xs, indices, ys),
+            (Value::Floats(xs), Value::Floats(ys)) => assign(xs, indices, ys),
+            _ => panic!(),
+        }
+    }
+
+    fn extend(&mut self, value: &Value) {
+        match (self, value) {
+            (Value::Bools(xs), Value::Bools(ys)) => xs.extend(ys),
+            (Value::Ints(xs), Value::Ints(ys)) => xs.extend(ys),
+            (Value::Uints(xs), Value::Uints(ys)) => xs.extend(ys),
+            (Value::Floats(xs), Value::Floats(ys)) => xs.extend(ys),
+            _ => panic!(),
+        }
+    }
+
+    // the conversions of the scalar constructors, per component
+    fn convert(&self, ty_lit: TyLit) -> Value {
+        match (self, ty_lit) {
+            (Value::Bools(xs), TyLit::Int) => Value::Ints(xs.iter().map( | x | *x as i32).collect()),
+            (Value::Bools(xs), TyLit::Uint) => Value::Uints(xs.iter().map( | x | *x as u32).collect()),
+            (Value::Bools(xs), TyLit::Float) => Value::Floats(xs.iter().map( | x | if *x {1.0} else {0.0}).collect()),
+            (Value::Ints(xs), TyLit::Bool) => Value::Bools(xs.iter().map( | x | *x != 0).collect()),
+            (Value::Ints(xs), TyLit::Uint) => Value::Uints(xs.iter().map( | x | *x as u32).collect()),
+            (Value::Ints(xs), TyLit::Float) => Value::Floats(xs.iter().map( | x | *x as f32).collect()),
+            (Value::Uints(xs), TyLit::Bool) => Value::Bools(xs.iter().map( | x | *x != 0).collect()),
+            (Value::Uints(xs), TyLit::Int) => Value::Ints(xs.iter().map( | x | *x as i32).collect()),
+            (Value::Uints(xs), TyLit::Float) => Value::Floats(xs.iter().map( | x | *x as f32).collect()),
+            (Value::Floats(xs), TyLit::Bool) => Value::Bools(xs.iter().map( | x | *x != 0.0).collect()),
+            (Value::Floats(xs), TyLit::Int) => Value::Ints(xs.iter().map( | x | *x as i32).collect()),
+            (Value::Floats(xs), TyLit::Uint) => Value::Uints(xs.iter().map( | x | *x as u32).collect()),
+            (value, _) => value.clone(),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(x: bool) -> Value {
+        Value::Bools(vec![x])
+    }
+}
+
+impl From<i32> for Value {
+    fn from(x: i32) -> Value {
+        Value::Ints(vec![x])
+    }
+}
+
+impl From<u32> for Value {
+    fn from(x: u32) -> Value {
+        Value::Uints(vec![x])
+    }
+}
+
+impl From<f32> for Value {
+    fn from(x: f32) -> Value {
+        Value::Floats(vec![x])
+    }
+}
+
+impl From<Vec2> for Value {
+    fn from(v: Vec2) -> Value {
+        Value::Floats(vec![v.x, v.y])
+    }
+}
+
+impl From<Vec3> for Value {
+    fn from(v: Vec3) -> Value {
+        Value::Floats(vec![v.x, v.y, v.z])
+    }
+}
+
+impl From<Vec4> for Value {
+    fn from(v: Vec4) -> Value {
+        Value::Floats(vec![v.x, v.y, v.z, v.w])
+    }
+}
+
+impl From<Mat4> for Value {
+    fn from(m: Mat4) -> Value {
+        Value::Floats(m.v.to_vec())
+    }
+}
+
+// the pixels of a texture, rows top to bottom. a cube texture has six faces in +x, -x,
+// +y, -y, +z, -z order and a volume texture depth slices front to back, both as layers
+// of width x height pixels
+#[derive(Clone, Debug)]
+pub struct TextureData {
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    pub pixels: Vec<Vec4>,
+}
+
+impl TextureData {
+    pub fn new(width: usize, height: usize, depth: usize, pixels: Vec<Vec4>) -> TextureData {
+        assert_eq!(pixels.len(), width * height * depth);
+        TextureData {width, height, depth, pixels}
+    }
+
+    // from the image_u32 of a Texture
+    pub fn from_bgra(width: usize, height: usize, depth: usize, image_u32: &[u32]) -> TextureData {
+        TextureData::new(width, height, depth, image_u32.iter().map( | pixel | Vec4 {
+            x: ((pixel >> 16) & 0xff) as f32 / 255.0,
+            y: ((pixel >> 8) & 0xff) as f32 / 255.0,
+            z: (pixel & 0xff) as f32 / 255.0,
+            w: ((pixel >> 24) & 0xff) as f32 / 255.0,
+        }).collect())
+    }
+
+    fn fetch(&self, x: isize, y: isize, layer: usize, wrap: bool) -> Vec4 {
+        let (x, y) = if wrap {
+            (x.rem_euclid(self.width as isize), y.rem_euclid(self.height as isize))
+        } else {
+            (x.max(0).min(self.width as isize - 1), y.max(0).min(self.height as isize - 1))
+        };
+        self.pixels[(layer * self.height + y as usize) * self.width + x as usize]
+    }
+
+    fn sample_layer(&self, u: f32, v: f32, layer: usize, wrap: bool) -> Vec4 {
+        let x = u * self.width as f32 - 0.5;
+        let y = v * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+        let top = lerp(self.fetch(x0, y0, layer, wrap), self.fetch(x0 + 1, y0, layer, wrap), fx);
+        let bottom = lerp(self.fetch(x0, y0 + 1, layer, wrap), self.fetch(x0 + 1, y0 + 1, layer, wrap), fx);
+        lerp(top, bottom, fy)
+    }
+
+    fn sample2d(&self, u: f32, v: f32) -> Vec4 {
+        self.sample_layer(u, v, 0, true)
+    }
+
+    fn sample3d(&self, u: f32, v: f32, w: f32) -> Vec4 {
+        let z = w * self.depth as f32 - 0.5;
+        let z0 = z.floor();
+        let layer0 = (z0 as isize).rem_euclid(self.depth as isize) as usize;
+        let layer1 = (z0 as isize + 1).rem_euclid(self.depth as isize) as usize;
+        lerp(self.sample_layer(u, v, layer0, true), self.sample_layer(u, v, layer1, true), z - z0)
+    }
+
+    // picks the face by the major axis of the direction, as in the GL spec
+    fn sample_cube(&self, x: f32, y: f32, z: f32) -> Vec4 {
+        let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+        let (face, sc, tc, ma) = if ax >= ay && ax >= az {
+            if x > 0.0 {(0, -z, -y, ax)} else {(1, z, -y, ax)}
+        } else if ay >= az {
+            if y > 0.0 {(2, x, z, ay)} else {(3, x, -z, ay)}
+        } else if z > 0.0 {
+            (4, x, -y, az)
+        } else {
+            (5, -x, -y, az)
+        };
+        self.sample_layer((sc / ma + 1.0) * 0.5, (tc / ma + 1.0) * 0.5, face, false)
+    }
+}
+
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    Return(Option<Value>),
+}
+
+// counts the iterations of a loop. a loop with a max stops there, as the counted loops of
+// GLSL ES 1.00 do, a dynamic loop without one is an error past DEFAULT_LOOP_MAX because that
+// is where those stop it
+struct LoopCounter {
+    span: Span,
+    max: Option<i32>,
+    is_bounded: bool,
+    count: i32,
+}
+
+impl LoopCounter {
+    fn new(span: Span, is_const: bool, max_expr: &Option<Expr>) -> LoopCounter {
+        LoopCounter {
+            span,
+            max: max_expr.as_ref().map( | max_expr | max_expr.const_val.borrow().as_ref().unwrap().as_ref().unwrap().to_int().unwrap()),
+            is_bounded: is_const,
+            count: 0,
+        }
+    }
+
+    fn next(&mut self) -> Result<bool, LiveError> {
+        if let Some(max) = self.max {
+            if self.count >= max {
+                return Ok(false);
+            }
+        } else if !self.is_bounded && self.count >= DEFAULT_LOOP_MAX {
+            return Err(LiveError {
+                span: self.span,
+                message: format!("loop ran for more than {} iterations without a max", DEFAULT_LOOP_MAX),
+                ..LiveError::default()
+            });
+        }
+        self.count += 1;
+        Ok(true)
+    }
+}
+
+// a var, the struct fields and array elements in it, and the components of the vector
+// or matrix at the end
+struct Place {
+    is_local: bool,
+    ident: Ident,
+    path: Vec<usize>,
+    comps: Option<Vec<usize>>,
+}
+
+impl Place {
+    fn select_comps(&mut self, indices: &[usize]) {
+        self.comps = Some(match &self.comps {
+            Some(comps) => indices.iter().map( | index | comps[*index]).collect(),
+            None => indices.to_vec(),
+        });
+    }
+}
+
+fn is_const(expr: &Expr) -> bool {
+    matches!(expr.const_val.borrow().as_ref(), Some(Some(_)))
+}
+
+fn assign_op(op: BinOp) -> Option<BinOp> {
+    match op {
+        BinOp::AddAssign => Some(BinOp::Add),
+        BinOp::SubAssign => Some(BinOp::Sub),
+        BinOp::MulAssign => Some(BinOp::Mul),
+        BinOp::DivAssign => Some(BinOp::Div),
+        op => op.to_int_op(),
+    }
+}
+
+fn swizzle_indices(ident: Ident) -> Vec<usize> {
+    Swizzle::parse(ident).unwrap().iter().cloned().collect()
+}
+
+// the components an index picks, a column of a matrix or a component of a vector
+fn component_indices(ty: &Ty, index: usize) -> Vec<usize> {
+    if ty.is_matrix() {
+        let dim = matrix_dim(ty);
+        (index * dim..(index + 1) * dim).collect()
+    } else {
+        vec![index]
+    }
+}
+
+fn matrix_dim(ty: &Ty) -> usize {
+    match ty {
+        Ty::Mat2 => 2,
+        Ty::Mat3 => 3,
+        Ty::Mat4 => 4,
+        _ => panic!(),
+    }
+}
+
+fn scalar_ty_lit(ty: &Ty) -> TyLit {
+    match ty {
+        Ty::Bool | Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => TyLit::Bool,
+        Ty::Int | Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => TyLit::Int,
+        Ty::Uint | Ty::Uvec2 | Ty::Uvec3 | Ty::Uvec4 => TyLit::Uint,
+        _ => TyLit::Float,
+    }
+}
+
+fn eval_bin_op(span: Span, op: BinOp, left: Value, left_ty: &Ty, right: Value, right_ty: &Ty) -> Result<Value, LiveError> {
+    match op {
+        BinOp::Eq => return Ok(Value::Bools(vec![left == right])),
+        BinOp::Ne => return Ok(Value::Bools(vec![left != right])),
+        BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+            let ordering = compare(&left, &right, | ordering | ordering)[0];
+            return Ok(Value::Bools(vec![match op {
+                BinOp::Lt => ordering == Some(std::cmp::Ordering::Less),
+                BinOp::Le => matches!(ordering, Some(std::cmp::Ordering::Less) | Some(std::cmp::Ordering::Equal)),
+                BinOp::Gt => ordering == Some(std::cmp::Ordering::Greater),
+                _ => matches!(ordering, Some(std::cmp::Ordering::Greater) | Some(std::cmp::Ordering::Equal)),
+            }]));
+        }
+        BinOp::Mul if (left_ty.is_matrix() && !right_ty.is_scalar()) || (right_ty.is_matrix() && !left_ty.is_scalar()) => {
+            return Ok(Value::Floats(mat_mul(left_ty, left.floats(), right_ty, right.floats())));
+        }
+        _ => {}
+    }
+    let int_error = | message: &str | LiveError {
+        span,
+        message: message.to_string(),
+        ..LiveError::default()
+    };
+    Ok(match (left, right) {
+        (Value::Ints(xs), Value::Ints(ys)) => Value::Ints(zip(&xs, &ys, | x, y | {
+            Ok(match op {
+                BinOp::Add => x.wrapping_add(y),
+                BinOp::Sub => x.wrapping_sub(y),
+                BinOp::Mul => x.wrapping_mul(y),
+                BinOp::Div | BinOp::Rem if y == 0 => return Err(int_error("integer division by zero")),
+                BinOp::Div => x.wrapping_div(y),
+                BinOp::Rem => x.wrapping_rem(y),
+                BinOp::BitAnd => x & y,
+                BinOp::BitOr => x | y,
+                BinOp::BitXor => x ^ y,
+                BinOp::Shl => x.wrapping_shl(y as u32),
+                BinOp::Shr => x.wrapping_shr(y as u32),
+                _ => panic!(),
+            })
+        })?),
+        (Value::Uints(xs), ys) => {
+            // a uint can be shifted by an int amount
+            let ys = match ys.convert(TyLit::Uint) {
+                Value::Uints(ys) => ys,
+                _ => panic!(),
+            };
+            Value::Uints(zip(&xs, &ys, | x, y | {
+                Ok(match op {
+                    BinOp::Add => x.wrapping_add(y),
+                    BinOp::Sub => x.wrapping_sub(y),
+                    BinOp::Mul => x.wrapping_mul(y),
+                    BinOp::Div | BinOp::Rem if y == 0 => return Err(int_error("integer division by zero")),
+                    BinOp::Div => x / y,
+                    BinOp::Rem => x % y,
+                    BinOp::BitAnd => x & y,
+                    BinOp::BitOr => x | y,
+                    BinOp::BitXor => x ^ y,
+                    BinOp::Shl => x.wrapping_shl(y),
+                    BinOp::Shr => x.wrapping_shr(y),
+                    _ => panic!(),
+                })
+            })?)
+        }
+        (Value::Floats(xs), Value::Floats(ys)) => Value::Floats(zip(&xs, &ys, | x, y | {
+            Ok(match op {
+                BinOp::Add => x + y,
+                BinOp::Sub => x - y,
+                BinOp::Mul => x * y,
+                BinOp::Div => x / y,
+                _ => panic!(),
+            })
+        })?),
+        _ => panic!(),
+    })
+}
+
+// applies f per component, a scalar on either side goes with every component
+fn zip<T: Copy>(xs: &[T], ys: &[T], f: impl Fn(T, T) -> Result<T, LiveError>) -> Result<Vec<T>, LiveError> {
+    (0..xs.len().max(ys.len())).map( | index | {
+        f(xs[if xs.len() == 1 {0} else {index}], ys[if ys.len() == 1 {0} else {index}])
+    }).collect()
+}
+
+fn mat_mul(left_ty: &Ty, xs: &[f32], right_ty: &Ty, ys: &[f32]) -> Vec<f32> {
+    if left_ty.is_matrix() && right_ty.is_matrix() {
+        let dim = matrix_dim(left_ty);
+        (0..dim * dim).map( | index | {
+            let (col, row) = (index / dim, index % dim);
+            (0..dim).map( | k | xs[k * dim + row] * ys[col * dim + k]).sum()
+        }).collect()
+    } else if left_ty.is_matrix() {
+        let dim = matrix_dim(left_ty);
+        (0..dim).map( | row | (0..dim).map( | col | xs[col * dim + row] * ys[col]).sum()).collect()
+    } else {
+        let dim = matrix_dim(right_ty);
+        (0..dim).map( | col | (0..dim).map( | row | xs[row] * ys[col * dim + row]).sum()).collect()
+    }
+}
+
+fn compare<T>(x: &Value, y: &Value, f: impl Fn(Option<std::cmp::Ordering>) -> T) -> Vec<T> {
+    match (x, y) {
+        (Value::Bools(xs), Value::Bools(ys)) => xs.iter().zip(ys).map( | (x, y) | f(x.partial_cmp(y))).collect(),
+        (Value::Ints(xs), Value::Ints(ys)) => xs.iter().zip(ys).map( | (x, y) | f(x.partial_cmp(y))).collect(),
+        (Value::Uints(xs), Value::Uints(ys)) => xs.iter().zip(ys).map( | (x, y) | f(x.partial_cmp(y))).collect(),
+        (Value::Floats(xs), Value::Floats(ys)) => xs.iter().zip(ys).map( | (x, y) | f(x.partial_cmp(y))).collect(),
+        _ => panic!(),
+    }
+}
+
+// applies f per component across float args, scalar args go with every component
+fn map_floats(args: &[Value], f: impl Fn(&[f32]) -> f32) -> Value {
+    let args = args.iter().map( | arg | arg.floats()).collect::<Vec<_>>();
+    let len = args.iter().map( | arg | arg.len()).max().unwrap();
+    Value::Floats((0..len).map( | index | {
+        let xs = args.iter().map( | arg | arg[if arg.len() == 1 {0} else {index}]).collect::<Vec<_>>();
+        f(&xs)
+    }).collect())
+}
+
+fn map_uints(args: &[Value], f: impl Fn(&[u32]) -> u32) -> Value {
+    let args = args.iter().map( | arg | match arg {
+        Value::Uints(xs) => xs,
+        _ => panic!(),
+    }).collect::<Vec<_>>();
+    let len = args.iter().map( | arg | arg.len()).max().unwrap();
+    Value::Uints((0..len).map( | index | {
+        let xs = args.iter().map( | arg | arg[if arg.len() == 1 {0} else {index}]).collect::<Vec<_>>();
+        f(&xs)
+    }).collect())
+}
+
+fn dot(xs: &[f32], ys: &[f32]) -> f32 {
+    xs.iter().zip(ys).map( | (x, y) | x * y).sum()
+}
+
+fn length(xs: &[f32]) -> f32 {
+    dot(xs, xs).sqrt()
+}
+
+fn lerp(a: Vec4, b: Vec4, t: f32) -> Vec4 {
+    Vec4 {
+        x: a.x + (b.x - a.x) * t,
+        y: a.y + (b.y - a.y) * t,
+        z: a.z + (b.z - a.z) * t,
+        w: a.w + (b.w - a.w) * t,
+    }
+}
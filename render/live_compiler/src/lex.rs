@@ -97,10 +97,26 @@ where
                     return Err(span.error(self, "Cannot parse color".into()));
                 }
             }
+            ('%', '=') => {
+                self.skip_two_chars();
+                Token::PercentEq
+            }
+            ('%', _) => {
+                self.skip_char();
+                Token::Percent
+            }
             ('&', '&') => {
                 self.skip_two_chars();
                 Token::AndAnd
             }
+            ('&', '=') => {
+                self.skip_two_chars();
+                Token::AndEq
+            }
+            ('&', _) => {
+                self.skip_char();
+                Token::And
+            }
             ('(', _) => {
                 self.skip_char();
                 Token::LeftParen
@@ -178,6 +194,14 @@ where
                 };
                 if has_frac_part || has_exp_part {
                     Token::Lit(Lit::Float(string.parse::<f32>().unwrap()))
+                } else if self.ch_0 == 'u' && !(self.ch_1.is_ascii_alphanumeric() || self.ch_1 == '_') {
+                    self.skip_char();
+                    if string.starts_with('-') {
+                        return Err(span.error(self, "negative unsigned integer literal".into()));
+                    }
+                    Token::Lit(Lit::Uint(string.parse::<u32>().map_err(|_| {
+                        span.error(self, "unsigned integer literal out of range".into())
+                    })?))
                 } else {
                     Token::Lit(Lit::Int(string.parse::<i32>().map_err(|_| {
                         span.error(self, "overflowing integer literal".into())
@@ -220,6 +244,14 @@ where
                 self.skip_two_chars();
                 Token::LtEq
             }
+            ('<', '<') => {
+                self.skip_two_chars();
+                if self.skip_char_if(|ch| ch == '=') {
+                    Token::ShlEq
+                } else {
+                    Token::Shl
+                }
+            }
             ('<', _) => {
                 self.skip_char();
                 Token::Lt
@@ -236,6 +268,14 @@ where
                 self.skip_two_chars();
                 Token::GtEq
             }
+            ('>', '>') => {
+                self.skip_two_chars();
+                if self.skip_char_if(|ch| ch == '=') {
+                    Token::ShrEq
+                } else {
+                    Token::Shr
+                }
+            }
             ('>', _) => {
                 self.skip_char();
                 Token::Gt
@@ -244,6 +284,14 @@ where
                 self.skip_char();
                 Token::Question
             }
+            ('^', '=') => {
+                self.skip_two_chars();
+                Token::CaretEq
+            }
+            ('^', _) => {
+                self.skip_char();
+                Token::Caret
+            }
             (ch, _) if ch.is_ascii_alphabetic() || ch == '_' => {
                 let mut string = String::new();
                 string.push(self.read_char());
@@ -255,6 +303,8 @@ where
                     "bvec3" => Token::TyLit(TyLit::Bvec3),
                     "bvec4" => Token::TyLit(TyLit::Bvec4),
                     "texture2D" => Token::TyLit(TyLit::Texture2D),
+                    "textureCube" => Token::TyLit(TyLit::TextureCube),
+                    "texture3D" => Token::TyLit(TyLit::Texture3D),
                     "const" => Token::Const,
                     "continue" => Token::Continue,
                     "else" => Token::Else,
@@ -272,6 +322,7 @@ where
                     "ivec3" => Token::TyLit(TyLit::Ivec3),
                     "ivec4" => Token::TyLit(TyLit::Ivec4),
                     "let" => Token::Let,
+                    "loop" => Token::Loop,
                     "mat2" => Token::TyLit(TyLit::Mat2),
                     "mat3" => Token::TyLit(TyLit::Mat3),
                     "mat4" => Token::TyLit(TyLit::Mat4),
@@ -279,11 +330,16 @@ where
                     //"self" => Token::Self_,
                     //"crate"=>Token::Crate,
                     "struct" => Token::Struct,
+                    "uint" => Token::TyLit(TyLit::Uint),
+                    "uvec2" => Token::TyLit(TyLit::Uvec2),
+                    "uvec3" => Token::TyLit(TyLit::Uvec3),
+                    "uvec4" => Token::TyLit(TyLit::Uvec4),
                     //"to" => Token::To,
                     "vec2" => Token::TyLit(TyLit::Vec2),
                     "vec3" => Token::TyLit(TyLit::Vec3),
                     "vec4" => Token::TyLit(TyLit::Vec4),
                     "true" => Token::Lit(Lit::Bool(true)),
+                    "while" => Token::While,
                     _ => Token::Ident(Ident::new(string)),
                 }
             }
@@ -303,6 +359,14 @@ where
                 self.skip_two_chars();
                 Token::OrOr
             }
+            ('|', '=') => {
+                self.skip_two_chars();
+                Token::OrEq
+            }
+            ('|', _) => {
+                self.skip_char();
+                Token::Or
+            }
             ('}', _) => {
                 self.skip_char();
                 Token::RightBrace
@@ -441,6 +505,7 @@ impl SpanTracker {
                 end: lex.index,
             },
             message,
+            ..LiveError::default()
         }
     }
 }
@@ -83,6 +83,7 @@ impl<'a,'b> LhsChecker<'a,'b> {
         return Err(LiveError {
             span,
             message: String::from("expression is not a valid left hand side"),
+            ..LiveError::default()
         });
     }
 
@@ -96,6 +97,7 @@ impl<'a,'b> LhsChecker<'a,'b> {
         return Err(LiveError {
             span,
             message: String::from("expression is not a valid left hand side"),
+            ..LiveError::default()
         });
     }
 
@@ -103,6 +105,7 @@ impl<'a,'b> LhsChecker<'a,'b> {
         return Err(LiveError {
             span,
             message: String::from("expression is not a valid left hand side"),
+            ..LiveError::default()
         });
     }
 
@@ -115,6 +118,7 @@ impl<'a,'b> LhsChecker<'a,'b> {
         return Err(LiveError {
             span,
             message: String::from("expression is not a valid left hand side"),
+            ..LiveError::default()
         });
     }
 
@@ -145,6 +149,7 @@ impl<'a,'b> LhsChecker<'a,'b> {
         return Err(LiveError {
             span,
             message: String::from("expression is not a valid left hand side"),
+            ..LiveError::default()
         });
     }
 
@@ -158,6 +163,7 @@ impl<'a,'b> LhsChecker<'a,'b> {
         return Err(LiveError {
             span,
             message: String::from("expression is not a valid left hand side"),
+            ..LiveError::default()
         });
     }
 
@@ -170,6 +176,7 @@ impl<'a,'b> LhsChecker<'a,'b> {
         return Err(LiveError {
             span,
             message: String::from("expression is not a valid left hand side"),
+            ..LiveError::default()
         });
     }
 
@@ -186,6 +193,7 @@ impl<'a,'b> LhsChecker<'a,'b> {
                     return Err(LiveError {
                         span,
                         message: String::from("expression is not a valid left hand side"),
+                        ..LiveError::default()
                     });
                 }
                 Ok(())
@@ -204,6 +212,7 @@ impl<'a,'b> LhsChecker<'a,'b> {
         return Err(LiveError {
             span,
             message: String::from("liveid is not a valid left hand side"),
+            ..LiveError::default()
         });
     }
 
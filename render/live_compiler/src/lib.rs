@@ -12,11 +12,15 @@ pub mod generate;
 pub mod generate_glsl;
 pub mod generate_metal;
 pub mod generate_hlsl;
+pub mod generate_wgsl;
+pub mod format;
 pub mod ident;
+pub mod interpret;
 pub mod lex;
 pub mod lhs_check;
 pub mod lit;
 pub mod math;
+pub mod optimise;
 pub mod shaderparser;
 pub mod liveparser;
 pub mod span;
@@ -7,6 +7,7 @@ use std::fmt;
 pub enum Lit {
     Bool(bool),
     Int(i32),
+    Uint(u32),
     Float(f32),
     Color(Color)
     
@@ -17,6 +18,7 @@ impl Lit {
         match self {
             Lit::Bool(_) => Ty::Bool,
             Lit::Int(_) => Ty::Int,
+            Lit::Uint(_) => Ty::Uint,
             Lit::Float(_) => Ty::Float,
             Lit::Color(_) => Ty::Vec4
         }
@@ -26,6 +28,7 @@ impl Lit {
         match self {
             Lit::Bool(lit) => Val::Bool(lit),
             Lit::Int(lit) => Val::Int(lit as i32),
+            Lit::Uint(lit) => Val::Uint(lit),
             Lit::Float(lit) => Val::Float(lit),
             Lit::Color(lit) => Val::Vec4(lit.to_vec4())
         }
@@ -37,6 +40,7 @@ impl fmt::Display for Lit {
         match self {
             Lit::Bool(lit) => write!(f, "{}", lit),
             Lit::Int(lit) => write!(f, "{}", lit),
+            Lit::Uint(lit) => write!(f, "{}u", lit),
             Lit::Float(lit) => {
                 if lit.abs().fract() < 0.00000001 {
                     write!(f, "{}.0", lit)
@@ -55,6 +59,7 @@ impl fmt::Display for Lit {
 pub enum TyLit {
     Bool,
     Int,
+    Uint,
     Float,
     Bvec2,
     Bvec3,
@@ -62,6 +67,9 @@ pub enum TyLit {
     Ivec2,
     Ivec3,
     Ivec4,
+    Uvec2,
+    Uvec3,
+    Uvec4,
     Vec2,
     Vec3,
     Vec4,
@@ -69,6 +77,8 @@ pub enum TyLit {
     Mat3,
     Mat4,
     Texture2D,
+    TextureCube,
+    Texture3D,
 }
 
 impl TyLit {
@@ -76,6 +86,7 @@ impl TyLit {
         match self {
             TyLit::Bool => Ty::Bool,
             TyLit::Int => Ty::Int,
+            TyLit::Uint => Ty::Uint,
             TyLit::Float => Ty::Float,
             TyLit::Bvec2 => Ty::Bvec2,
             TyLit::Bvec3 => Ty::Bvec3,
@@ -83,6 +94,9 @@ impl TyLit {
             TyLit::Ivec2 => Ty::Ivec2,
             TyLit::Ivec3 => Ty::Ivec3,
             TyLit::Ivec4 => Ty::Ivec4,
+            TyLit::Uvec2 => Ty::Uvec2,
+            TyLit::Uvec3 => Ty::Uvec3,
+            TyLit::Uvec4 => Ty::Uvec4,
             TyLit::Vec2 => Ty::Vec2,
             TyLit::Vec3 => Ty::Vec3,
             TyLit::Vec4 => Ty::Vec4,
@@ -90,6 +104,8 @@ impl TyLit {
             TyLit::Mat3 => Ty::Mat3,
             TyLit::Mat4 => Ty::Mat4,
             TyLit::Texture2D => Ty::Texture2D,
+            TyLit::TextureCube => Ty::TextureCube,
+            TyLit::Texture3D => Ty::Texture3D,
         }
     }
     /*
@@ -122,6 +138,7 @@ impl fmt::Display for TyLit {
             match self {
                 TyLit::Bool => "bool",
                 TyLit::Int => "int",
+                TyLit::Uint => "uint",
                 TyLit::Float => "float",
                 TyLit::Bvec2 => "bvec2",
                 TyLit::Bvec3 => "bvec3",
@@ -129,6 +146,9 @@ impl fmt::Display for TyLit {
                 TyLit::Ivec2 => "ivec2",
                 TyLit::Ivec3 => "ivec3",
                 TyLit::Ivec4 => "ivec4",
+                TyLit::Uvec2 => "uvec2",
+                TyLit::Uvec3 => "uvec3",
+                TyLit::Uvec4 => "uvec4",
                 TyLit::Vec2 => "vec2",
                 TyLit::Vec3 => "vec3",
                 TyLit::Vec4 => "vec4",
@@ -136,6 +156,8 @@ impl fmt::Display for TyLit {
                 TyLit::Mat3 => "mat3",
                 TyLit::Mat4 => "mat4",
                 TyLit::Texture2D => "texture2D",
+                TyLit::TextureCube => "textureCube",
+                TyLit::Texture3D => "texture3D",
             }
         )
     }
@@ -2,8 +2,9 @@ use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet};
 use crate::shaderast::{ShaderAst};
 use crate::span::LiveBodyId;
 use crate::lex;
-use crate::analyse::{ShaderCompileOptions, ShaderAnalyser};
+use crate::analyse::{ShaderCompileOptions, ShaderAnalyser, mangle_overloaded_fns};
 use crate::env::Env;
+use crate::optimise::optimise_shader;
 use crate::span::Span;
 use crate::token::{TokenWithSpan};
 use crate::builtin::{self, Builtin};
@@ -15,6 +16,7 @@ use crate::math::*;
 use std::fmt;
 use crate::error::LiveError;
 use std::cell::RefCell;
+use makepad_microserde::*;
 
 #[derive(Clone, Debug)]
 pub struct LiveBody {
@@ -25,13 +27,27 @@ pub struct LiveBody {
     pub code: String,
 }
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, SerBin, DeBin, SerJson, DeJson, SerRon, DeRon)]
 pub struct LiveBodyError {
     pub file: String,
     pub line: usize,
     pub column: usize,
     pub len: usize,
     pub message: String,
+    pub labels: Vec<LiveBodyErrorLabel>,
+    pub notes: Vec<String>,
+    pub helps: Vec<String>,
+    // the error with source snippets of all its spans, as printed in the terminal
+    pub rendered: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, SerBin, DeBin, SerJson, DeJson, SerRon, DeRon)]
+pub struct LiveBodyErrorLabel {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub len: usize,
+    pub message: String,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -482,26 +498,90 @@ impl LiveStyles {
     }
     
     pub fn live_error_to_live_body_error(&self, err: LiveError) -> LiveBodyError {
-        let live_body = &self.live_bodies[err.span.live_body_id.0];
-        fn byte_to_row_col(byte: usize, source: &str) -> (usize, usize) {
-            let lines = source.split("\n");
-            let mut o = 0;
-            for (index, line) in lines.enumerate() {
-                if byte >= o && byte < o + line.len() {
-                    return (index, byte - o);
+        let primary = self.span_to_live_body_error_label(err.span, String::new());
+        LiveBodyError {
+            file: primary.file.clone(),
+            line: primary.line,
+            column: primary.column,
+            len: primary.len,
+            message: err.to_string(),
+            labels: err.labels.iter().map( | label | {
+                self.span_to_live_body_error_label(label.span, label.message.clone())
+            }).collect(),
+            notes: err.notes.clone(),
+            helps: err.helps.clone(),
+            rendered: self.render_live_error(&err),
+        }
+    }
+    
+    // renders an error the way rustc does, with the source line of every span and the
+    // primary span underlined by ^ and the labels by -
+    pub fn render_live_error(&self, err: &LiveError) -> String {
+        let mut spans = vec![(err.span, '^', "")];
+        for label in &err.labels {
+            spans.push((label.span, '-', &label.message));
+        }
+        // labels in the live body of the error come first, in source order
+        spans.sort_by_key( | (span, _, _) | (span.live_body_id != err.span.live_body_id, span.live_body_id, span.start));
+        let lines: Vec<_> = spans.iter().map( | (span, _, _) | {
+            let live_body = &self.live_bodies[span.live_body_id.0];
+            let (row, col) = byte_to_row_col(span.start, &live_body.code);
+            (row, col, live_body.code.split("\n").nth(row).unwrap_or(""))
+        }).collect();
+        let gutter = spans.iter().zip(&lines).map( | ((span, _, _), (row, _, _)) | {
+            (row + self.live_bodies[span.live_body_id.0].line).to_string().len()
+        }).max().unwrap();
+        let blank = format!("{:1$} |", "", gutter);
+        
+        let primary = self.span_to_live_body_error_label(err.span, String::new());
+        let mut out = format!("error: {}\n{:w$}--> {}:{}:{}\n{}\n", err.message, "", primary.file, primary.line, primary.column, blank, w = gutter);
+        let mut last: Option<(LiveBodyId, usize)> = None;
+        for ((span, marker, message), (row, col, text)) in spans.iter().zip(&lines) {
+            let live_body = &self.live_bodies[span.live_body_id.0];
+            let line = row + live_body.line;
+            let location = format!("{}:{}:{}", live_body.file, line, col + 1);
+            match last {
+                None => {}
+                Some((live_body_id, _)) if live_body_id != span.live_body_id => {
+                    out.push_str(&format!("{:w$}::: {}\n{}\n", "", location, blank, w = gutter))
                 }
-                o += line.len() + 1;
+                Some((_, last_row)) if last_row + 1 < *row => out.push_str("...\n"),
+                _ => {}
+            }
+            if last != Some((span.live_body_id, *row)) {
+                out.push_str(&format!("{:>2$} | {}\n", line, text, gutter));
             }
-            return (0, 0);
+            // keep tabs in the padding so the marker lines up with the text
+            let padding: String = text[..*col].chars().map( | c | if c == '\t' {'\t'} else {' '}).collect();
+            let len = (span.end - span.start).min(text.len() - col).max(1);
+            out.push_str(&format!("{} {}{}", blank, padding, marker.to_string().repeat(len)));
+            if !message.is_empty() {
+                out.push_str(&format!(" {}", message));
+            }
+            out.push('\n');
+            last = Some((span.live_body_id, *row));
         }
-        // lets find the span info
-        let start = byte_to_row_col(err.span.start, &live_body.code);
-        LiveBodyError {
+        if !err.notes.is_empty() || !err.helps.is_empty() {
+            out.push_str(&format!("{}\n", blank));
+        }
+        for note in &err.notes {
+            out.push_str(&format!("{:2$} = note: {}\n", "", note, gutter));
+        }
+        for help in &err.helps {
+            out.push_str(&format!("{:2$} = help: {}\n", "", help, gutter));
+        }
+        out
+    }
+    
+    fn span_to_live_body_error_label(&self, span: Span, message: String) -> LiveBodyErrorLabel {
+        let live_body = &self.live_bodies[span.live_body_id.0];
+        let (row, col) = byte_to_row_col(span.start, &live_body.code);
+        LiveBodyErrorLabel {
             file: live_body.file.clone(),
-            line: start.0 + live_body.line,
-            column: start.1 + 1,
-            len: err.span.end - err.span.start,
-            message: err.to_string(),
+            line: row + live_body.line,
+            column: col + 1,
+            len: span.end - span.start,
+            message,
         }
     }
     
@@ -751,7 +831,8 @@ impl LiveStyles {
             None=>{
                 return Err(self.live_error_to_live_body_error(LiveError {
                     span: Span::default(),
-                    message: format!("Cannot find library or shader")
+                    message: "Cannot find library or shader".to_string(),
+                    ..LiveError::default()
                 }))
             }
         };
@@ -770,7 +851,8 @@ impl LiveStyles {
                     else { // error
                         return Err(live_styles.live_error_to_live_body_error(LiveError {
                             span: use_ipws.span,
-                            message: format!("Cannot find library or shader: {}", use_ipws.ident_path)
+                            message: format!("Cannot find library or shader: {}", use_ipws.ident_path),
+                            ..LiveError::default()
                         }))
                     }
                 }
@@ -785,7 +867,9 @@ impl LiveStyles {
             Ok(())
         }
         recur(&mut visited, in_ast, &mut out_ast, self) ?;
-        let mut env = Env::new(self);
+        if let Err(err) = mangle_overloaded_fns(&mut out_ast) {
+            return Err(self.live_error_to_live_body_error(err))
+        }
         
         let default_geometry = if let Some(geom_ipws) = out_ast.default_geometry {
             let live_id = geom_ipws.to_live_item_id(self);
@@ -793,7 +877,8 @@ impl LiveStyles {
                 None => {
                     return Err(self.live_error_to_live_body_error(LiveError {
                         span: geom_ipws.span,
-                        message: format!("Cannot find default geometry {}", geom_ipws.ident_path)
+                        message: format!("Cannot find default geometry {}", geom_ipws.ident_path),
+                        ..LiveError::default()
                     }))
                 }
                 Some(geometry) => Some(*geometry)
@@ -803,12 +888,17 @@ impl LiveStyles {
             None
         };
         
-        if let Err(err) = (ShaderAnalyser {
+        let analyse = | shader: &ShaderAst | ShaderAnalyser {
             builtins: &self.builtins,
-            shader: &out_ast,
-            env: &mut env,
+            shader,
+            env: &mut Env::new(self),
             options,
-        }.analyse_shader()) {
+        }.analyse_shader();
+        if let Err(err) = analyse(&out_ast) {
+            return Err(self.live_error_to_live_body_error(err))
+        }
+        // the passes re-analyse as they go, so errors here are bugs in a pass
+        if let Err(err) = optimise_shader(&mut out_ast, self, options.optimisations, &analyse) {
             return Err(self.live_error_to_live_body_error(err))
         }
         
@@ -816,4 +906,14 @@ impl LiveStyles {
     }
 }
 
-
+fn byte_to_row_col(byte: usize, source: &str) -> (usize, usize) {
+    let lines = source.split("\n");
+    let mut o = 0;
+    for (index, line) in lines.enumerate() {
+        if byte >= o && byte <= o + line.len() {
+            return (index, byte - o);
+        }
+        o += line.len() + 1;
+    }
+    (0, 0)
+}
@@ -45,6 +45,10 @@ pub enum TextureFormat {
     RenderBGRA,
     RenderBGRAf16,
     RenderBGRAf32,
+    // six width x height faces, packed in image_u32 in +x, -x, +y, -y, +z, -z order
+    ImageCubeBGRA,
+    // depth width x height slices, packed in image_u32 front to back
+    ImageVolumeBGRA,
     //    ImageBGRAf32,
     //    ImageRf32,
     //    ImageRGf32,
@@ -59,6 +63,7 @@ pub struct TextureDesc {
     pub format: TextureFormat,
     pub width: Option<usize>,
     pub height: Option<usize>,
+    pub depth: Option<usize>,
     pub multisample: Option<usize>
 }
 
@@ -68,6 +73,7 @@ impl Default for TextureDesc {
             format: TextureFormat::Default,
             width: None,
             height: None,
+            depth: None,
             multisample: None
         }
     }
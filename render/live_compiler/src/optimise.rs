@@ -0,0 +1,936 @@
+use crate::colors::Color;
+use crate::const_eval::ConstEvaluator;
+use crate::env::VarKind;
+use crate::error::LiveError;
+use crate::ident::{Ident, IdentPath};
+use crate::interpret::{Interpreter, Value};
+use crate::lit::Lit;
+use crate::livestyles::LiveStyles;
+use crate::shaderast::*;
+use crate::ty::Ty;
+use crate::val::Val;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ShaderOptimisations {
+    // drops functions neither stage calls, varyings nobody reads and unused lets
+    pub remove_dead_code: bool,
+    // replaces expressions with a known value by a literal, live values are left alone
+    pub fold_consts: bool,
+    // substitutes calls to small single-return helpers with their body
+    pub inline_fns: bool,
+    // computes repeated pure expressions once into a local
+    pub eliminate_common_subexprs: bool,
+}
+
+impl ShaderOptimisations {
+    pub const NONE: ShaderOptimisations = ShaderOptimisations {
+        remove_dead_code: false,
+        fold_consts: false,
+        inline_fns: false,
+        eliminate_common_subexprs: false,
+    };
+
+    pub const ALL: ShaderOptimisations = ShaderOptimisations {
+        remove_dead_code: true,
+        fold_consts: true,
+        inline_fns: true,
+        eliminate_common_subexprs: true,
+    };
+}
+
+// variables by name, with how they were resolved
+type Vars = HashSet<(Ident, Option<VarKind>)>;
+
+// the largest helper body, counted in expressions, that is inlined at its call sites
+const INLINE_MAX_SIZE: usize = 16;
+
+// the smallest expression, counted in expressions, worth hoisting into a local
+const CSE_MIN_SIZE: usize = 3;
+
+// runs the enabled passes over an analysed shader. every pass leaves the ast unanalysed,
+// so `analyse` runs again after each one to give the next pass (and the generators)
+// fresh types, const values and usage
+pub fn optimise_shader(
+    shader: &mut ShaderAst,
+    live_styles: &LiveStyles,
+    optimisations: ShaderOptimisations,
+    analyse: &dyn Fn(&ShaderAst) -> Result<(), LiveError>,
+) -> Result<(), LiveError> {
+    if optimisations.inline_fns {
+        inline_fns(shader);
+        analyse(shader)?;
+    }
+    if optimisations.fold_consts {
+        fold_consts(shader, live_styles);
+        analyse(shader)?;
+    }
+    if optimisations.eliminate_common_subexprs {
+        eliminate_common_subexprs(shader);
+        analyse(shader)?;
+    }
+    if optimisations.remove_dead_code {
+        remove_dead_code(shader);
+        analyse(shader)?;
+    }
+    Ok(())
+}
+
+// a rough measure of how much code a shader generates: the statements and expressions of
+// the functions either stage uses, plus one for every varying
+pub fn shader_size(shader: &ShaderAst) -> usize {
+    shader.decls.iter().map( | decl | match decl {
+        Decl::Fn(decl) if is_used(decl) => block_size(&decl.block),
+        Decl::Varying(_) => 1,
+        _ => 0,
+    }).sum()
+}
+
+fn is_used(decl: &FnDecl) -> bool {
+    decl.is_used_in_vertex_shader.get() == Some(true) || decl.is_used_in_fragment_shader.get() == Some(true)
+}
+
+fn block_size(block: &Block) -> usize {
+    block.stmts.iter().map( | stmt | {
+        1 + stmt_exprs(stmt).into_iter().map(expr_size).sum::<usize>()
+            + stmt_blocks(stmt).into_iter().map(block_size).sum::<usize>()
+    }).sum()
+}
+
+fn expr_size(expr: &Expr) -> usize {
+    1 + expr_children(expr).into_iter().map(expr_size).sum::<usize>()
+}
+
+fn inline_fns(shader: &mut ShaderAst) {
+    let user_fns = user_fns(shader);
+    let fn_paths = fn_paths(shader);
+    let mut inlinable = HashMap::new();
+    for decl in &shader.decls {
+        if let Decl::Fn(decl) = decl {
+            if let Some(expr) = inlinable_body(decl, &user_fns) {
+                let params = decl.params.iter().map( | param | param.ident).collect::<Vec<_>>();
+                inlinable.insert(decl.ident_path, (params, expr.clone()));
+            }
+        }
+    }
+    for decl in &mut shader.decls {
+        if let Decl::Fn(decl) = decl {
+            let locals = fn_locals(decl);
+            walk_block_mut(&mut decl.block, &mut | expr | {
+                if let Some(inlined) = inline_call(expr, &inlinable, &locals, &user_fns, &fn_paths) {
+                    *expr = inlined;
+                }
+            });
+        }
+    }
+}
+
+fn inlinable_body<'a>(decl: &'a FnDecl, user_fns: &HashSet<IdentPath>) -> Option<&'a Expr> {
+    if decl.ident_path == IdentPath::from_str("vertex") || decl.ident_path == IdentPath::from_str("pixel") {
+        return None;
+    }
+    if decl.params.iter().any( | param | param.is_inout) {
+        return None;
+    }
+    match decl.block.stmts.as_slice() {
+        [Stmt::Return {expr: Some(expr), ..}] if expr_size(expr) <= INLINE_MAX_SIZE && !has_side_effects(expr, user_fns) => {
+            Some(expr)
+        }
+        _ => None,
+    }
+}
+
+fn inline_call(
+    expr: &Expr,
+    inlinable: &HashMap<IdentPath, (Vec<Ident>, Expr)>,
+    locals: &HashSet<Ident>,
+    user_fns: &HashSet<IdentPath>,
+    fn_paths: &FnPaths,
+) -> Option<Expr> {
+    let arg_exprs = match &expr.kind {
+        ExprKind::Call {arg_exprs, ..} | ExprKind::MethodCall {arg_exprs, ..} => arg_exprs,
+        _ => return None,
+    };
+    let (params, body) = inlinable.get(&call_path(expr, fn_paths)?)?;
+    // the body may not see a local of the caller where it meant a global
+    let mut body_vars = HashSet::new();
+    collect_vars(body, &mut body_vars);
+    if body_vars.iter().any( | (ident, _) | !params.contains(ident) && locals.contains(ident)) {
+        return None;
+    }
+    // evaluating the args in a different order or number of times must not be observable
+    if arg_exprs.iter().any( | arg_expr | has_side_effects(arg_expr, user_fns)) {
+        return None;
+    }
+    for (param, arg_expr) in params.iter().zip(arg_exprs) {
+        if count_var_uses(body, *param) > 1 && !matches!(arg_expr.kind, ExprKind::Var {..} | ExprKind::Lit {..}) {
+            return None;
+        }
+    }
+    let mut inlined = body.clone();
+    walk_expr_mut(&mut inlined, &mut | expr | {
+        let arg_expr = match &expr.kind {
+            ExprKind::Var {kind, ident_path, ..} if kind.get() == Some(VarKind::Local) => ident_path
+                .get_single()
+                .and_then( | ident | params.iter().position( | param | *param == ident))
+                .map( | index | arg_exprs[index].clone()),
+            _ => None,
+        };
+        if let Some(arg_expr) = arg_expr {
+            *expr = arg_expr;
+        }
+    });
+    Some(inlined)
+}
+
+// the (mangled) path of every function by the path calls use and its parameter types
+type FnPaths = HashMap<(IdentPath, Vec<Option<Ty>>), IdentPath>;
+
+fn fn_paths(shader: &ShaderAst) -> FnPaths {
+    shader.decls.iter().filter_map( | decl | match decl {
+        Decl::Fn(decl) => {
+            let param_tys = decl.params.iter().map( | param | param.ty_expr.ty.borrow().clone()).collect();
+            Some(((decl.source_ident_path, param_tys), decl.ident_path))
+        }
+        _ => None,
+    }).collect()
+}
+
+// the function a call or method call ends up in
+fn call_path(expr: &Expr, fn_paths: &FnPaths) -> Option<IdentPath> {
+    let (ident_path, arg_exprs) = match &expr.kind {
+        ExprKind::Call {ident_path, arg_exprs, ..} => (*ident_path, arg_exprs),
+        ExprKind::MethodCall {ident, arg_exprs, ..} => match arg_exprs[0].ty.borrow().as_ref() {
+            Some(Ty::Struct {ident: struct_ident}) => (IdentPath::from_two(*struct_ident, *ident), arg_exprs),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let arg_tys = arg_exprs.iter().map( | arg_expr | arg_expr.ty.borrow().clone()).collect();
+    fn_paths.get(&(ident_path, arg_tys)).copied()
+}
+
+fn fn_locals(decl: &FnDecl) -> HashSet<Ident> {
+    fn collect(block: &Block, locals: &mut HashSet<Ident>) {
+        for stmt in &block.stmts {
+            match stmt {
+                Stmt::Let {ident, ..} | Stmt::For {ident, ..} => {
+                    locals.insert(*ident);
+                }
+                _ => (),
+            }
+            for block in stmt_blocks(stmt) {
+                collect(block, locals);
+            }
+        }
+    }
+    let mut locals = decl.params.iter().map( | param | param.ident).collect();
+    collect(&decl.block, &mut locals);
+    locals
+}
+
+fn count_var_uses(expr: &Expr, ident: Ident) -> usize {
+    let own = match &expr.kind {
+        ExprKind::Var {ident_path, ..} if ident_path.get_single() == Some(ident) => 1,
+        _ => 0,
+    };
+    own + expr_children(expr).into_iter().map( | child | count_var_uses(child, ident)).sum::<usize>()
+}
+
+fn fold_consts(shader: &mut ShaderAst, live_styles: &LiveStyles) {
+    // the evaluators only ever look up consts, so they get a shader with just those
+    let consts = ShaderAst {
+        decls: shader.decls.iter().filter( | decl | matches!(decl, Decl::Const(_))).cloned().collect(),
+        ..ShaderAst::default()
+    };
+    let mut folder = ConstFolder {
+        const_evaluator: ConstEvaluator {shader: &consts, no_const_collapse: false},
+        interpreter: Interpreter::new(&consts, live_styles),
+        user_fns: user_fns(shader),
+    };
+    for decl in &mut shader.decls {
+        if let Decl::Fn(decl) = decl {
+            folder.fold_block(&mut decl.block);
+        }
+    }
+}
+
+struct ConstFolder<'a> {
+    const_evaluator: ConstEvaluator<'a>,
+    interpreter: Interpreter<'a>,
+    user_fns: HashSet<IdentPath>,
+}
+
+impl<'a> ConstFolder<'a> {
+    fn fold_block(&mut self, block: &mut Block) {
+        for stmt in &mut block.stmts {
+            for expr in stmt_exprs_mut(stmt) {
+                walk_expr_mut(expr, &mut | expr | self.fold_expr(expr));
+            }
+            for block in stmt_blocks_mut(stmt) {
+                self.fold_block(block);
+            }
+            // an if with a known condition is replaced by the block it takes
+            let taken = match stmt {
+                Stmt::If {span, expr, block_if_true, block_if_false} => match foldable_val(expr) {
+                    Some(Val::Bool(true)) => Some((*span, std::mem::replace(block_if_true, Box::new(Block {stmts: Vec::new()})))),
+                    Some(Val::Bool(false)) => Some((*span, block_if_false.take().unwrap_or_else( || Box::new(Block {stmts: Vec::new()})))),
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let Some((span, block)) = taken {
+                *stmt = Stmt::Block {span, block};
+            }
+        }
+        // a block that declares nothing can join the enclosing one
+        block.stmts = block.stmts.drain(..).flat_map( | stmt | match stmt {
+            Stmt::Block {block, ..} if !block.stmts.iter().any( | stmt | matches!(stmt, Stmt::Let {..})) => block.stmts,
+            stmt => vec![stmt],
+        }).collect();
+    }
+
+    fn fold_expr(&mut self, expr: &mut Expr) {
+        if expr.const_index.get().is_some() {
+            return;
+        }
+        let folded = match &expr.kind {
+            ExprKind::Lit {..} => None,
+            ExprKind::Cond {expr: cond_expr, expr_if_true, expr_if_false, ..} => match foldable_val(cond_expr) {
+                Some(Val::Bool(true)) => Some((**expr_if_true).clone()),
+                Some(Val::Bool(false)) => Some((**expr_if_false).clone()),
+                _ => None,
+            },
+            _ if foldable_val(expr).is_some() => self.lit_expr(expr, foldable_val(expr)),
+            ExprKind::Bin {op, left_expr, right_expr, ..} if !op.is_assign() => {
+                if foldable_val(left_expr).is_some() && foldable_val(right_expr).is_some() {
+                    self.lit_expr(expr, self.const_evaluator.try_const_eval_expr(expr))
+                } else {
+                    fold_identity(expr, *op, left_expr, right_expr)
+                }
+            }
+            ExprKind::Un {expr: un_expr, ..} if foldable_val(un_expr).is_some() => {
+                self.lit_expr(expr, self.const_evaluator.try_const_eval_expr(expr))
+            }
+            ExprKind::Call {span, ident_path, arg_exprs} if !self.user_fns.contains(ident_path) => {
+                // derivatives and texture lookups depend on more than their args
+                let ident = ident_path.get_single().filter( | ident | {
+                    ident.with( | string | !matches!(string, "dFdx" | "dFdy" | "sample2d" | "sample3d" | "sample_cube"))
+                });
+                match (ident, foldable_args(arg_exprs)) {
+                    (Some(ident), Some(args)) => {
+                        let val = self.interpreter.eval_builtin(*span, ident, args).ok().and_then( | value | value.to_val());
+                        self.lit_expr(expr, val)
+                    }
+                    _ => None,
+                }
+            }
+            ExprKind::ConsCall {ty_lit, arg_exprs, ..} => match foldable_args(arg_exprs) {
+                Some(args) => {
+                    let val = self.interpreter.eval_cons_call(*ty_lit, arg_exprs, args).to_val();
+                    self.lit_expr(expr, val)
+                }
+                None => None,
+            },
+            _ => None,
+        };
+        if let Some(folded) = folded {
+            *expr = folded;
+        }
+    }
+
+    fn lit_expr(&self, expr: &Expr, val: Option<Val>) -> Option<Expr> {
+        let ty = expr.ty.borrow().clone()?;
+        // a nan or infinity has no literal in any of the backends
+        let val = val?;
+        let lit = match val {
+            Val::Bool(x) if ty == Ty::Bool => Lit::Bool(x),
+            Val::Int(x) if ty == Ty::Int => Lit::Int(x),
+            Val::Uint(x) if ty == Ty::Uint => Lit::Uint(x),
+            Val::Float(x) if ty == Ty::Float && x.is_finite() => Lit::Float(x),
+            Val::Vec4(v) if ty == Ty::Vec4 && [v.x, v.y, v.z, v.w].iter().all( | x | x.is_finite()) => {
+                Lit::Color(Color {r: v.x, g: v.y, b: v.z, a: v.w})
+            }
+            _ => return None,
+        };
+        Some(Expr {
+            span: expr.span,
+            ty: RefCell::new(Some(ty)),
+            const_val: RefCell::new(Some(Some(val))),
+            const_index: Cell::new(None),
+            kind: ExprKind::Lit {span: expr.span, lit},
+        })
+    }
+}
+
+// values in the const table can be changed without a recompile, so those are not foldable
+fn foldable_val(expr: &Expr) -> Option<Val> {
+    if expr.const_index.get().is_some() {
+        return None;
+    }
+    expr.const_val.borrow().clone().flatten()
+}
+
+fn foldable_args(arg_exprs: &[Expr]) -> Option<Vec<Value>> {
+    arg_exprs.iter().map( | arg_expr | foldable_val(arg_expr).map( | val | Value::from_val(&val))).collect()
+}
+
+// x + 0, x - 0, x * 1 and x / 1, as long as dropping the constant doesn't change the type
+fn fold_identity(expr: &Expr, op: BinOp, left_expr: &Expr, right_expr: &Expr) -> Option<Expr> {
+    fn is(expr: &Expr, x: f32) -> bool {
+        match foldable_val(expr) {
+            Some(Val::Int(val)) => val as f32 == x,
+            Some(Val::Uint(val)) => val as f32 == x,
+            Some(Val::Float(val)) => val == x,
+            _ => false,
+        }
+    }
+    let kept = match op {
+        BinOp::Add if is(right_expr, 0.0) => left_expr,
+        BinOp::Add if is(left_expr, 0.0) => right_expr,
+        BinOp::Sub if is(right_expr, 0.0) => left_expr,
+        BinOp::Mul if is(right_expr, 1.0) => left_expr,
+        BinOp::Mul if is(left_expr, 1.0) => right_expr,
+        BinOp::Div if is(right_expr, 1.0) => left_expr,
+        _ => return None,
+    };
+    if *kept.ty.borrow() != *expr.ty.borrow() {
+        return None;
+    }
+    Some(kept.clone())
+}
+
+struct Calls {
+    user_fns: HashSet<IdentPath>,
+    fn_paths: FnPaths,
+    varying_writers: HashSet<IdentPath>,
+}
+
+fn eliminate_common_subexprs(shader: &mut ShaderAst) {
+    let calls = Calls {user_fns: user_fns(shader), fn_paths: fn_paths(shader), varying_writers: varying_writers(shader)};
+    for decl in &mut shader.decls {
+        if let Decl::Fn(decl) = decl {
+            let mut count = 0;
+            eliminate_common_subexprs_in_block(&mut decl.block, &calls, &mut count);
+        }
+    }
+}
+
+// the functions that assign to a varying, themselves or through a callee
+fn varying_writers(shader: &ShaderAst) -> HashSet<IdentPath> {
+    fn assigns_varying(expr: &Expr) -> bool {
+        let own = match &expr.kind {
+            ExprKind::Bin {op, left_expr, ..} if op.is_assign() => matches!(place_root(left_expr), Some((_, Some(VarKind::Varying)))),
+            _ => false,
+        };
+        own || expr_children(expr).into_iter().any(assigns_varying)
+    }
+    fn block_assigns_varying(block: &Block) -> bool {
+        block.stmts.iter().any( | stmt | {
+            stmt_exprs(stmt).into_iter().any(assigns_varying) || stmt_blocks(stmt).into_iter().any(block_assigns_varying)
+        })
+    }
+    let mut writers = HashSet::new();
+    loop {
+        let len = writers.len();
+        for decl in &shader.decls {
+            if let Decl::Fn(decl) = decl {
+                let calls_writer = decl.callees.borrow().as_ref().is_none_or( | callees | callees.iter().any( | callee | writers.contains(callee)));
+                if calls_writer || block_assigns_varying(&decl.block) {
+                    writers.insert(decl.ident_path);
+                }
+            }
+        }
+        if writers.len() == len {
+            return writers;
+        }
+    }
+}
+
+fn eliminate_common_subexprs_in_block(block: &mut Block, calls: &Calls, count: &mut usize) {
+    let user_fns = &calls.user_fns;
+    for stmt in &mut block.stmts {
+        for block in stmt_blocks_mut(stmt) {
+            eliminate_common_subexprs_in_block(block, calls, count);
+        }
+    }
+    while let Some((key, first, last)) = find_common_subexpr(block, calls) {
+        let ident = Ident::new(format!("mpsc_cse_{}", count));
+        *count += 1;
+        let mut hoisted = None;
+        for stmt in &mut block.stmts[first..=last] {
+            if let Some(expr) = stmt_head_expr_mut(stmt) {
+                replace_subexpr(expr, &key, ident, user_fns, &mut hoisted);
+            }
+        }
+        let expr = hoisted.unwrap();
+        block.stmts.insert(first, Stmt::Let {
+            span: expr.span,
+            ty: RefCell::new(None),
+            ident,
+            ty_expr: None,
+            expr: Some(expr),
+        });
+    }
+}
+
+struct CommonSubexpr {
+    size: usize,
+    vars: Vars,
+    first: usize,
+    last: usize,
+    count: usize,
+}
+
+// finds the repeated pure expression that saves the most, only looking at the statements
+// of this block up to the first one that changes something the expression reads
+fn find_common_subexpr(block: &Block, calls: &Calls) -> Option<(String, usize, usize)> {
+    let mut open: BTreeMap<String, CommonSubexpr> = BTreeMap::new();
+    let mut best: Option<(usize, String, usize, usize)> = None;
+    let consider = | key: String, subexpr: CommonSubexpr, best: &mut Option<(usize, String, usize, usize)> | {
+        let saving = (subexpr.count - 1) * subexpr.size;
+        if subexpr.count > 1 && best.as_ref().is_none_or( | best | saving > best.0) {
+            *best = Some((saving, key, subexpr.first, subexpr.last));
+        }
+    };
+    for (index, stmt) in block.stmts.iter().enumerate() {
+        let writes = stmt_writes(stmt, calls);
+        let closed = open.iter().filter( | (_, subexpr) | writes.touches(&subexpr.vars)).map( | (key, _) | key.clone()).collect::<Vec<_>>();
+        for key in closed {
+            let subexpr = open.remove(&key).unwrap();
+            consider(key, subexpr, &mut best);
+        }
+        if let Some(expr) = stmt_head_expr(stmt) {
+            collect_subexprs(expr, &calls.user_fns, &mut | key, size, vars | {
+                if writes.touches(&vars) {
+                    return;
+                }
+                let subexpr = open.entry(key).or_insert(CommonSubexpr {size, vars, first: index, last: index, count: 0});
+                subexpr.last = index;
+                subexpr.count += 1;
+            });
+        }
+    }
+    for (key, subexpr) in open {
+        consider(key, subexpr, &mut best);
+    }
+    best.map( | (_, key, first, last) | (key, first, last))
+}
+
+// calls back with the key, size and variables of every subexpression worth hoisting
+fn collect_subexprs(
+    expr: &Expr,
+    user_fns: &HashSet<IdentPath>,
+    f: &mut dyn FnMut(String, usize, Vars),
+) {
+    match &expr.kind {
+        // the place being assigned to is not a value
+        ExprKind::Bin {op, right_expr, ..} if op.is_assign() => {
+            collect_subexprs(right_expr, user_fns, f);
+            return;
+        }
+        _ => (),
+    }
+    for child in expr_children(expr) {
+        collect_subexprs(child, user_fns, f);
+    }
+    if let Some(key) = hoistable_key(expr, user_fns) {
+        let mut vars = HashSet::new();
+        collect_vars(expr, &mut vars);
+        f(key, expr_size(expr), vars);
+    }
+}
+
+fn hoistable_key(expr: &Expr, user_fns: &HashSet<IdentPath>) -> Option<String> {
+    if expr_size(expr) < CSE_MIN_SIZE || foldable_val(expr).is_some() {
+        return None;
+    }
+    match expr.ty.borrow().as_ref() {
+        None | Some(Ty::Void) | Some(Ty::Array {..}) => return None,
+        _ => (),
+    }
+    let mut key = String::new();
+    write_expr_key(expr, user_fns, &mut key)?;
+    Some(key)
+}
+
+// a structural key for a pure expression, none if evaluating it has side effects
+fn write_expr_key(expr: &Expr, user_fns: &HashSet<IdentPath>, key: &mut String) -> Option<()> {
+    match &expr.kind {
+        ExprKind::Bin {op, ..} if op.is_assign() => return None,
+        ExprKind::MethodCall {..} | ExprKind::MacroCall {..} => return None,
+        ExprKind::Call {ident_path, ..} if user_fns.contains(ident_path) => return None,
+        ExprKind::Cond {..} => write!(key, "(?").unwrap(),
+        ExprKind::Bin {op, ..} => write!(key, "({}", op).unwrap(),
+        ExprKind::Un {op, ..} => write!(key, "({}", op).unwrap(),
+        ExprKind::Field {field_ident, ..} => write!(key, "(.{}", field_ident).unwrap(),
+        ExprKind::Index {..} => write!(key, "([]").unwrap(),
+        ExprKind::Call {ident_path, ..} => write!(key, "({}", ident_path).unwrap(),
+        ExprKind::ConsCall {ty_lit, ..} => write!(key, "({}", ty_lit).unwrap(),
+        ExprKind::Var {ident_path, ..} => write!(key, "({}", ident_path).unwrap(),
+        ExprKind::Lit {lit, ..} => write!(key, "({:?}", lit).unwrap(),
+    }
+    for child in expr_children(expr) {
+        key.push(' ');
+        write_expr_key(child, user_fns, key)?;
+    }
+    key.push(')');
+    Some(())
+}
+
+fn replace_subexpr(expr: &mut Expr, key: &str, ident: Ident, user_fns: &HashSet<IdentPath>, hoisted: &mut Option<Expr>) {
+    if hoistable_key(expr, user_fns).as_deref() == Some(key) {
+        let span = expr.span;
+        let expr = std::mem::replace(expr, Expr {
+            span,
+            ty: RefCell::new(None),
+            const_val: RefCell::new(None),
+            const_index: Cell::new(None),
+            kind: ExprKind::Var {span, kind: Cell::new(None), ident_path: ident.to_ident_path()},
+        });
+        hoisted.get_or_insert(expr);
+        return;
+    }
+    if let ExprKind::Bin {op, right_expr, ..} = &mut expr.kind {
+        if op.is_assign() {
+            replace_subexpr(right_expr, key, ident, user_fns, hoisted);
+            return;
+        }
+    }
+    for child in expr_children_mut(expr) {
+        replace_subexpr(child, key, ident, user_fns, hoisted);
+    }
+}
+
+// what a statement may change: locals it declares, assigns or passes to a user function
+// (which may take them inout), and with a call to a function that writes one any varying
+#[derive(Default)]
+struct Writes {
+    idents: HashSet<Ident>,
+    varyings: bool,
+}
+
+impl Writes {
+    fn touches(&self, vars: &Vars) -> bool {
+        vars.iter().any( | (ident, kind) | self.idents.contains(ident) || self.varyings && *kind == Some(VarKind::Varying))
+    }
+}
+
+fn stmt_writes(stmt: &Stmt, calls: &Calls) -> Writes {
+    fn expr_writes(expr: &Expr, calls: &Calls, writes: &mut Writes) {
+        match &expr.kind {
+            ExprKind::Bin {op, left_expr, ..} if op.is_assign() => {
+                if let Some((ident, _)) = place_root(left_expr) {
+                    writes.idents.insert(ident);
+                }
+            }
+            ExprKind::MethodCall {arg_exprs, ..} | ExprKind::Call {arg_exprs, ..} | ExprKind::MacroCall {arg_exprs, ..} => {
+                let is_user_fn = match &expr.kind {
+                    ExprKind::Call {ident_path, ..} => calls.user_fns.contains(ident_path),
+                    _ => true,
+                };
+                if is_user_fn {
+                    writes.varyings |= call_path(expr, &calls.fn_paths).is_none_or( | path | calls.varying_writers.contains(&path));
+                    for arg_expr in arg_exprs {
+                        if let Some((ident, _)) = place_root(arg_expr) {
+                            writes.idents.insert(ident);
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+        for child in expr_children(expr) {
+            expr_writes(child, calls, writes);
+        }
+    }
+    fn writes_of(stmt: &Stmt, calls: &Calls, writes: &mut Writes) {
+        match stmt {
+            Stmt::Let {ident, ..} | Stmt::For {ident, ..} => {
+                writes.idents.insert(*ident);
+            }
+            _ => (),
+        }
+        for expr in stmt_exprs(stmt) {
+            expr_writes(expr, calls, writes);
+        }
+        for block in stmt_blocks(stmt) {
+            for stmt in &block.stmts {
+                writes_of(stmt, calls, writes);
+            }
+        }
+    }
+    let mut writes = Writes::default();
+    writes_of(stmt, calls, &mut writes);
+    writes
+}
+
+fn remove_dead_code(shader: &mut ShaderAst) {
+    shader.decls.retain( | decl | match decl {
+        Decl::Fn(decl) => is_used(decl),
+        _ => true,
+    });
+    let user_fns = user_fns(shader);
+
+    // a varying only needs to exist if something reads it
+    let mut read = HashSet::new();
+    for decl in &shader.decls {
+        if let Decl::Fn(decl) = decl {
+            collect_varying_reads(&decl.block, &user_fns, &mut read);
+        }
+    }
+    let unread = shader.decls.iter().filter_map( | decl | match decl {
+        Decl::Varying(decl) if !read.contains(&decl.ident) => Some(decl.ident),
+        _ => None,
+    }).collect::<HashSet<_>>();
+    shader.decls.retain( | decl | match decl {
+        Decl::Varying(decl) => !unread.contains(&decl.ident),
+        _ => true,
+    });
+
+    for decl in &mut shader.decls {
+        if let Decl::Fn(decl) = decl {
+            remove_varying_writes(&mut decl.block, &unread, &user_fns);
+            // removing a let can make the lets it reads unused in turn
+            loop {
+                let mut vars = HashSet::new();
+                for stmt in &decl.block.stmts {
+                    collect_stmt_vars(stmt, &mut vars);
+                }
+                let used = vars.into_iter().map( | (ident, _) | ident).collect::<HashSet<_>>();
+                if !remove_unused_lets(&mut decl.block, &used, &user_fns) {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// the varying a statement assigns to, if it does nothing else
+fn varying_write(stmt: &Stmt, user_fns: &HashSet<IdentPath>) -> Option<Ident> {
+    match stmt {
+        Stmt::Expr {expr: Expr {kind: ExprKind::Bin {op: BinOp::Assign, left_expr, right_expr, ..}, ..}, ..}
+            if !has_side_effects(left_expr, user_fns) && !has_side_effects(right_expr, user_fns) =>
+        {
+            match place_root(left_expr) {
+                Some((ident, Some(VarKind::Varying))) => Some(ident),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn collect_varying_reads(block: &Block, user_fns: &HashSet<IdentPath>, read: &mut HashSet<Ident>) {
+    for stmt in &block.stmts {
+        let mut vars = HashSet::new();
+        match (varying_write(stmt, user_fns), stmt) {
+            // the varying being written to is the one var in the statement that isn't read
+            (Some(ident), Stmt::Expr {expr, ..}) => {
+                collect_vars(expr, &mut vars);
+                if count_var_uses(expr, ident) > 1 {
+                    read.insert(ident);
+                }
+                vars.retain( | (var_ident, _) | *var_ident != ident);
+            }
+            _ => {
+                for expr in stmt_exprs(stmt) {
+                    collect_vars(expr, &mut vars);
+                }
+            }
+        }
+        read.extend(vars.into_iter().filter( | (_, kind) | *kind == Some(VarKind::Varying)).map( | (ident, _) | ident));
+        for block in stmt_blocks(stmt) {
+            collect_varying_reads(block, user_fns, read);
+        }
+    }
+}
+
+fn remove_varying_writes(block: &mut Block, unread: &HashSet<Ident>, user_fns: &HashSet<IdentPath>) {
+    block.stmts.retain( | stmt | !varying_write(stmt, user_fns).is_some_and( | ident | unread.contains(&ident)));
+    for stmt in &mut block.stmts {
+        for block in stmt_blocks_mut(stmt) {
+            remove_varying_writes(block, unread, user_fns);
+        }
+    }
+}
+
+fn remove_unused_lets(block: &mut Block, used: &HashSet<Ident>, user_fns: &HashSet<IdentPath>) -> bool {
+    let len = block.stmts.len();
+    block.stmts.retain( | stmt | match stmt {
+        Stmt::Let {ident, expr, ..} => used.contains(ident) || expr.as_ref().is_some_and( | expr | has_side_effects(expr, user_fns)),
+        _ => true,
+    });
+    let mut removed = block.stmts.len() != len;
+    for stmt in &mut block.stmts {
+        for block in stmt_blocks_mut(stmt) {
+            removed |= remove_unused_lets(block, used, user_fns);
+        }
+    }
+    removed
+}
+
+// the paths calls use, so overloads share one
+fn user_fns(shader: &ShaderAst) -> HashSet<IdentPath> {
+    shader.decls.iter().filter_map( | decl | match decl {
+        Decl::Fn(decl) => Some(decl.source_ident_path),
+        _ => None,
+    }).collect()
+}
+
+// assignments and calls to user functions (which may have inout params or write varyings)
+fn has_side_effects(expr: &Expr, user_fns: &HashSet<IdentPath>) -> bool {
+    let own = match &expr.kind {
+        ExprKind::Bin {op, ..} => op.is_assign(),
+        ExprKind::MethodCall {..} | ExprKind::MacroCall {..} => true,
+        ExprKind::Call {ident_path, ..} => user_fns.contains(ident_path),
+        _ => false,
+    };
+    own || expr_children(expr).into_iter().any( | child | has_side_effects(child, user_fns))
+}
+
+// the variable at the root of a place expression such as `a.b[i]`
+fn place_root(expr: &Expr) -> Option<(Ident, Option<VarKind>)> {
+    match &expr.kind {
+        ExprKind::Var {kind, ident_path, ..} => ident_path.get_single().map( | ident | (ident, kind.get())),
+        ExprKind::Field {expr, ..} | ExprKind::Index {expr, ..} => place_root(expr),
+        _ => None,
+    }
+}
+
+fn collect_vars(expr: &Expr, vars: &mut Vars) {
+    if let ExprKind::Var {kind, ident_path, ..} = &expr.kind {
+        if let Some(ident) = ident_path.get_single() {
+            vars.insert((ident, kind.get()));
+        }
+    }
+    for child in expr_children(expr) {
+        collect_vars(child, vars);
+    }
+}
+
+fn collect_stmt_vars(stmt: &Stmt, vars: &mut Vars) {
+    for expr in stmt_exprs(stmt) {
+        collect_vars(expr, vars);
+    }
+    for block in stmt_blocks(stmt) {
+        for stmt in &block.stmts {
+            collect_stmt_vars(stmt, vars);
+        }
+    }
+}
+
+fn walk_block_mut(block: &mut Block, f: &mut dyn FnMut(&mut Expr)) {
+    for stmt in &mut block.stmts {
+        for expr in stmt_exprs_mut(stmt) {
+            walk_expr_mut(expr, f);
+        }
+        for block in stmt_blocks_mut(stmt) {
+            walk_block_mut(block, f);
+        }
+    }
+}
+
+// visits the children of an expression before the expression itself
+fn walk_expr_mut(expr: &mut Expr, f: &mut dyn FnMut(&mut Expr)) {
+    for child in expr_children_mut(expr) {
+        walk_expr_mut(child, f);
+    }
+    f(expr);
+}
+
+fn expr_children(expr: &Expr) -> Vec<&Expr> {
+    match &expr.kind {
+        ExprKind::Cond {expr, expr_if_true, expr_if_false, ..} => vec![expr, expr_if_true, expr_if_false],
+        ExprKind::Bin {left_expr, right_expr, ..} => vec![left_expr, right_expr],
+        ExprKind::Un {expr, ..} | ExprKind::Field {expr, ..} => vec![expr],
+        ExprKind::Index {expr, index_expr, ..} => vec![expr, index_expr],
+        ExprKind::MethodCall {arg_exprs, ..}
+        | ExprKind::Call {arg_exprs, ..}
+        | ExprKind::MacroCall {arg_exprs, ..}
+        | ExprKind::ConsCall {arg_exprs, ..} => arg_exprs.iter().collect(),
+        ExprKind::Var {..} | ExprKind::Lit {..} => Vec::new(),
+    }
+}
+
+fn expr_children_mut(expr: &mut Expr) -> Vec<&mut Expr> {
+    match &mut expr.kind {
+        ExprKind::Cond {expr, expr_if_true, expr_if_false, ..} => vec![expr, expr_if_true, expr_if_false],
+        ExprKind::Bin {left_expr, right_expr, ..} => vec![left_expr, right_expr],
+        ExprKind::Un {expr, ..} | ExprKind::Field {expr, ..} => vec![expr],
+        ExprKind::Index {expr, index_expr, ..} => vec![expr, index_expr],
+        ExprKind::MethodCall {arg_exprs, ..}
+        | ExprKind::Call {arg_exprs, ..}
+        | ExprKind::MacroCall {arg_exprs, ..}
+        | ExprKind::ConsCall {arg_exprs, ..} => arg_exprs.iter_mut().collect(),
+        ExprKind::Var {..} | ExprKind::Lit {..} => Vec::new(),
+    }
+}
+
+// the expression a statement evaluates before anything else it contains, the only ones
+// common subexpressions are taken from
+fn stmt_head_expr(stmt: &Stmt) -> Option<&Expr> {
+    match stmt {
+        Stmt::Let {expr, ..} | Stmt::Return {expr, ..} => expr.as_ref(),
+        Stmt::If {expr, ..} | Stmt::Expr {expr, ..} => Some(expr),
+        _ => None,
+    }
+}
+
+fn stmt_head_expr_mut(stmt: &mut Stmt) -> Option<&mut Expr> {
+    match stmt {
+        Stmt::Let {expr, ..} | Stmt::Return {expr, ..} => expr.as_mut(),
+        Stmt::If {expr, ..} | Stmt::Expr {expr, ..} => Some(expr),
+        _ => None,
+    }
+}
+
+fn stmt_exprs(stmt: &Stmt) -> Vec<&Expr> {
+    match stmt {
+        Stmt::For {from_expr, to_expr, step_expr, max_expr, ..} => {
+            let mut exprs = vec![from_expr, to_expr];
+            exprs.extend(step_expr.iter().chain(max_expr.iter()));
+            exprs
+        }
+        Stmt::Loop {max_expr, ..} => max_expr.iter().collect(),
+        Stmt::While {expr, max_expr, ..} => std::iter::once(expr).chain(max_expr.iter()).collect(),
+        Stmt::If {expr, ..} | Stmt::Expr {expr, ..} => vec![expr],
+        Stmt::Let {expr, ..} | Stmt::Return {expr, ..} => expr.iter().collect(),
+        Stmt::Break {..} | Stmt::Continue {..} | Stmt::Block {..} => Vec::new(),
+    }
+}
+
+fn stmt_exprs_mut(stmt: &mut Stmt) -> Vec<&mut Expr> {
+    match stmt {
+        Stmt::For {from_expr, to_expr, step_expr, max_expr, ..} => {
+            let mut exprs = vec![from_expr, to_expr];
+            exprs.extend(step_expr.iter_mut().chain(max_expr.iter_mut()));
+            exprs
+        }
+        Stmt::Loop {max_expr, ..} => max_expr.iter_mut().collect(),
+        Stmt::While {expr, max_expr, ..} => std::iter::once(expr).chain(max_expr.iter_mut()).collect(),
+        Stmt::If {expr, ..} | Stmt::Expr {expr, ..} => vec![expr],
+        Stmt::Let {expr, ..} | Stmt::Return {expr, ..} => expr.iter_mut().collect(),
+        Stmt::Break {..} | Stmt::Continue {..} | Stmt::Block {..} => Vec::new(),
+    }
+}
+
+fn stmt_blocks(stmt: &Stmt) -> Vec<&Block> {
+    match stmt {
+        Stmt::For {block, ..} | Stmt::Loop {block, ..} | Stmt::While {block, ..} | Stmt::Block {block, ..} => vec![block],
+        Stmt::If {block_if_true, block_if_false, ..} => std::iter::once(&**block_if_true).chain(block_if_false.as_deref()).collect(),
+        Stmt::Break {..} | Stmt::Continue {..} | Stmt::Let {..} | Stmt::Return {..} | Stmt::Expr {..} => Vec::new(),
+    }
+}
+
+fn stmt_blocks_mut(stmt: &mut Stmt) -> Vec<&mut Block> {
+    match stmt {
+        Stmt::For {block, ..} | Stmt::Loop {block, ..} | Stmt::While {block, ..} | Stmt::Block {block, ..} => vec![block],
+        Stmt::If {block_if_true, block_if_false, ..} => {
+            std::iter::once(&mut **block_if_true).chain(block_if_false.as_deref_mut()).collect()
+        }
+        Stmt::Break {..} | Stmt::Continue {..} | Stmt::Let {..} | Stmt::Return {..} | Stmt::Expr {..} => Vec::new(),
+    }
+}
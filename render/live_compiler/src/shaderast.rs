@@ -54,6 +54,23 @@ impl ShaderAst {
         })
     }
 
+    // the function a call with these args goes to. a later declaration with the same
+    // parameter types overrides an earlier one
+    pub fn find_fn_decl_for_call(&self, ident_path: IdentPath, arg_exprs: &[Expr]) -> Option<&FnDecl> {
+        self.find_fn_decls(ident_path).rev().find( | decl | decl.takes_args(arg_exprs))
+    }
+
+    // all declarations of a function, its overloads included
+    pub fn find_fn_decls(&self, ident_path: IdentPath) -> impl DoubleEndedIterator<Item = &FnDecl> {
+        self.decls.iter().filter_map(move | decl | {
+            match decl {
+                Decl::Fn(decl) => Some(decl),
+                _ => None,
+            }
+            .filter( | decl | decl.source_ident_path == ident_path)
+        })
+    }
+
     pub fn find_instance_decl(&self, ident: Ident) -> Option<&InstanceDecl> {
         self.decls.iter().find_map(|decl| {
             match decl {
@@ -138,12 +155,24 @@ pub struct FnDecl {
     pub has_varying_deps: Cell<Option<bool>>,
     pub builtin_deps: RefCell<Option<BTreeSet<Ident>>>,
     pub cons_fn_deps: RefCell<Option<BTreeSet<(TyLit, Vec<Ty>)>>>,
+    pub int_op_fn_deps: RefCell<Option<BTreeSet<(BinOp, Ty, Ty)>>>,
     pub ident_path: IdentPath,
+    // the path calls use, ident_path is mangled when the function is overloaded
+    pub source_ident_path: IdentPath,
     pub params: Vec<Param>,
     pub return_ty_expr: Option<TyExpr>,
     pub block: Block,
 }
 
+impl FnDecl {
+    // overloads are told apart by parameter types only, there are no conversions
+    pub fn takes_args(&self, arg_exprs: &[Expr]) -> bool {
+        self.params.len() == arg_exprs.len() && self.params.iter().zip(arg_exprs).all( | (param, arg_expr) | {
+            *param.ty_expr.ty.borrow() == *arg_expr.ty.borrow()
+        })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct InstanceDecl {
     pub is_used_in_fragment_shader: Cell<Option<bool>>,
@@ -223,6 +252,18 @@ pub enum Stmt {
         from_expr: Expr,
         to_expr: Expr,
         step_expr: Option<Expr>,
+        max_expr: Option<Expr>,
+        block: Box<Block>,
+    },
+    Loop {
+        span: Span,
+        max_expr: Option<Expr>,
+        block: Box<Block>,
+    },
+    While {
+        span: Span,
+        expr: Expr,
+        max_expr: Option<Expr>,
         block: Box<Block>,
     },
     If {
@@ -350,13 +391,19 @@ pub enum ExprKind {
 pub enum MacroCallAnalysis {
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum BinOp {
     Assign,
     AddAssign,
     SubAssign,
     MulAssign,
     DivAssign,
+    RemAssign,
+    BitAndAssign,
+    BitOrAssign,
+    BitXorAssign,
+    ShlAssign,
+    ShrAssign,
     Or,
     And,
     Eq,
@@ -365,10 +412,49 @@ pub enum BinOp {
     Le,
     Gt,
     Ge,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Shl,
+    Shr,
     Add,
     Sub,
     Mul,
     Div,
+    Rem,
+}
+
+impl BinOp {
+    pub fn is_assign(self) -> bool {
+        matches!(
+            self,
+            BinOp::Assign
+                | BinOp::AddAssign
+                | BinOp::SubAssign
+                | BinOp::MulAssign
+                | BinOp::DivAssign
+                | BinOp::RemAssign
+                | BinOp::BitAndAssign
+                | BinOp::BitOrAssign
+                | BinOp::BitXorAssign
+                | BinOp::ShlAssign
+                | BinOp::ShrAssign
+        )
+    }
+
+    // the operators that only apply to int and ivec operands, compound assignments
+    // map to the operator they apply
+    pub fn to_int_op(self) -> Option<BinOp> {
+        match self {
+            BinOp::Rem | BinOp::RemAssign => Some(BinOp::Rem),
+            BinOp::BitAnd | BinOp::BitAndAssign => Some(BinOp::BitAnd),
+            BinOp::BitOr | BinOp::BitOrAssign => Some(BinOp::BitOr),
+            BinOp::BitXor | BinOp::BitXorAssign => Some(BinOp::BitXor),
+            BinOp::Shl | BinOp::ShlAssign => Some(BinOp::Shl),
+            BinOp::Shr | BinOp::ShrAssign => Some(BinOp::Shr),
+            _ => None,
+        }
+    }
 }
  
 impl fmt::Display for BinOp {
@@ -382,6 +468,12 @@ impl fmt::Display for BinOp {
                 BinOp::SubAssign => "-=",
                 BinOp::MulAssign => "*=",
                 BinOp::DivAssign => "/=",
+                BinOp::RemAssign => "%=",
+                BinOp::BitAndAssign => "&=",
+                BinOp::BitOrAssign => "|=",
+                BinOp::BitXorAssign => "^=",
+                BinOp::ShlAssign => "<<=",
+                BinOp::ShrAssign => ">>=",
                 BinOp::Or => "||",
                 BinOp::And => "&&",
                 BinOp::Eq => "==",
@@ -390,10 +482,16 @@ impl fmt::Display for BinOp {
                 BinOp::Le => "<=",
                 BinOp::Gt => ">",
                 BinOp::Ge => ">=",
+                BinOp::BitOr => "|",
+                BinOp::BitXor => "^",
+                BinOp::BitAnd => "&",
+                BinOp::Shl => "<<",
+                BinOp::Shr => ">>",
                 BinOp::Add => "+",
                 BinOp::Sub => "-",
                 BinOp::Mul => "*",
                 BinOp::Div => "/",
+                BinOp::Rem => "%",
             }
         )
     }
@@ -185,7 +185,9 @@ impl<'a> DeTokParserImpl<'a>{
             has_varying_deps: Cell::new(None),
             builtin_deps: RefCell::new(None),
             cons_fn_deps: RefCell::new(None),
+            int_op_fn_deps: RefCell::new(None),
             ident_path,
+            source_ident_path: ident_path,
             params,
             return_ty_expr,
             block,
@@ -326,6 +328,8 @@ impl<'a> DeTokParserImpl<'a>{
             Token::Break => self.parse_break_stmt(),
             Token::Continue => self.parse_continue_stmt(),
             Token::For => self.parse_for_stmt(),
+            Token::Loop => self.parse_loop_stmt(),
+            Token::While => self.parse_while_stmt(),
             Token::If => self.parse_if_stmt(),
             Token::Let => self.parse_let_stmt(),
             Token::Return => self.parse_return_stmt(),
@@ -360,6 +364,7 @@ impl<'a> DeTokParserImpl<'a>{
         } else {
             None
         };
+        let max_expr = self.parse_max_clause() ?;
         let block = Box::new(self.parse_block() ?);
         Ok(span.end(self, | span | Stmt::For {
             span,
@@ -367,10 +372,46 @@ impl<'a> DeTokParserImpl<'a>{
             from_expr,
             to_expr,
             step_expr,
+            max_expr,
             block,
         }))
     }
     
+    fn parse_loop_stmt(&mut self) -> Result<Stmt, LiveError> {
+        let span = self.begin_span();
+        self.expect_token(Token::Loop) ?;
+        let max_expr = self.parse_max_clause() ?;
+        let block = Box::new(self.parse_block() ?);
+        Ok(span.end(self, | span | Stmt::Loop {
+            span,
+            max_expr,
+            block,
+        }))
+    }
+    
+    fn parse_while_stmt(&mut self) -> Result<Stmt, LiveError> {
+        let span = self.begin_span();
+        self.expect_token(Token::While) ?;
+        let expr = self.parse_expr() ?;
+        let max_expr = self.parse_max_clause() ?;
+        let block = Box::new(self.parse_block() ?);
+        Ok(span.end(self, | span | Stmt::While {
+            span,
+            expr,
+            max_expr,
+            block,
+        }))
+    }
+    
+    // `max N` in front of a loop body caps the number of iterations
+    fn parse_max_clause(&mut self) -> Result<Option<Expr>, LiveError> {
+        if self.accept_ident("max") {
+            Ok(Some(self.parse_expr() ?))
+        } else {
+            Ok(None)
+        }
+    }
+    
     fn parse_if_stmt(&mut self) -> Result<Stmt, LiveError> {
         let span = self.begin_span();
         self.expect_token(Token::If) ?;
@@ -610,8 +651,100 @@ impl<'a> DeTokParserImpl<'a>{
     
     fn parse_rel_expr(&mut self) -> Result<Expr, LiveError> {
         let span = self.begin_span();
-        let mut acc = self.parse_add_expr() ?;
+        let mut acc = self.parse_bit_or_expr() ?;
         while let Some(op) = self.peek_token().to_rel_op() {
+            self.skip_token();
+            let left_expr = Box::new(acc);
+            let right_expr = Box::new(self.parse_bit_or_expr() ?);
+            acc = span.end(self, | span | Expr {
+                span,
+                ty: RefCell::new(None),
+                const_val: RefCell::new(None),
+                const_index: Cell::new(None),
+                kind: ExprKind::Bin {
+                    span,
+                    op,
+                    left_expr,
+                    right_expr,
+                },
+            });
+        }
+        Ok(acc)
+    }
+    
+    fn parse_bit_or_expr(&mut self) -> Result<Expr, LiveError> {
+        let span = self.begin_span();
+        let mut acc = self.parse_bit_xor_expr() ?;
+        while let Some(op) = self.peek_token().to_bit_or_op() {
+            self.skip_token();
+            let left_expr = Box::new(acc);
+            let right_expr = Box::new(self.parse_bit_xor_expr() ?);
+            acc = span.end(self, | span | Expr {
+                span,
+                ty: RefCell::new(None),
+                const_val: RefCell::new(None),
+                const_index: Cell::new(None),
+                kind: ExprKind::Bin {
+                    span,
+                    op,
+                    left_expr,
+                    right_expr,
+                },
+            });
+        }
+        Ok(acc)
+    }
+    
+    fn parse_bit_xor_expr(&mut self) -> Result<Expr, LiveError> {
+        let span = self.begin_span();
+        let mut acc = self.parse_bit_and_expr() ?;
+        while let Some(op) = self.peek_token().to_bit_xor_op() {
+            self.skip_token();
+            let left_expr = Box::new(acc);
+            let right_expr = Box::new(self.parse_bit_and_expr() ?);
+            acc = span.end(self, | span | Expr {
+                span,
+                ty: RefCell::new(None),
+                const_val: RefCell::new(None),
+                const_index: Cell::new(None),
+                kind: ExprKind::Bin {
+                    span,
+                    op,
+                    left_expr,
+                    right_expr,
+                },
+            });
+        }
+        Ok(acc)
+    }
+    
+    fn parse_bit_and_expr(&mut self) -> Result<Expr, LiveError> {
+        let span = self.begin_span();
+        let mut acc = self.parse_shift_expr() ?;
+        while let Some(op) = self.peek_token().to_bit_and_op() {
+            self.skip_token();
+            let left_expr = Box::new(acc);
+            let right_expr = Box::new(self.parse_shift_expr() ?);
+            acc = span.end(self, | span | Expr {
+                span,
+                ty: RefCell::new(None),
+                const_val: RefCell::new(None),
+                const_index: Cell::new(None),
+                kind: ExprKind::Bin {
+                    span,
+                    op,
+                    left_expr,
+                    right_expr,
+                },
+            });
+        }
+        Ok(acc)
+    }
+    
+    fn parse_shift_expr(&mut self) -> Result<Expr, LiveError> {
+        let span = self.begin_span();
+        let mut acc = self.parse_add_expr() ?;
+        while let Some(op) = self.peek_token().to_shift_op() {
             self.skip_token();
             let left_expr = Box::new(acc);
             let right_expr = Box::new(self.parse_add_expr() ?);
@@ -914,6 +1047,12 @@ impl Token {
             Token::MinusEq => Some(BinOp::SubAssign),
             Token::StarEq => Some(BinOp::MulAssign),
             Token::SlashEq => Some(BinOp::DivAssign),
+            Token::PercentEq => Some(BinOp::RemAssign),
+            Token::AndEq => Some(BinOp::BitAndAssign),
+            Token::OrEq => Some(BinOp::BitOrAssign),
+            Token::CaretEq => Some(BinOp::BitXorAssign),
+            Token::ShlEq => Some(BinOp::ShlAssign),
+            Token::ShrEq => Some(BinOp::ShrAssign),
             _ => None,
         }
     }
@@ -950,6 +1089,35 @@ impl Token {
         }
     }
     
+    fn to_bit_or_op(self) -> Option<BinOp> {
+        match self {
+            Token::Or => Some(BinOp::BitOr),
+            _ => None,
+        }
+    }
+    
+    fn to_bit_xor_op(self) -> Option<BinOp> {
+        match self {
+            Token::Caret => Some(BinOp::BitXor),
+            _ => None,
+        }
+    }
+    
+    fn to_bit_and_op(self) -> Option<BinOp> {
+        match self {
+            Token::And => Some(BinOp::BitAnd),
+            _ => None,
+        }
+    }
+    
+    fn to_shift_op(self) -> Option<BinOp> {
+        match self {
+            Token::Shl => Some(BinOp::Shl),
+            Token::Shr => Some(BinOp::Shr),
+            _ => None,
+        }
+    }
+    
     fn to_add_op(self) -> Option<BinOp> {
         match self {
             Token::Plus => Some(BinOp::Add),
@@ -962,6 +1130,7 @@ impl Token {
         match self {
             Token::Star => Some(BinOp::Mul),
             Token::Slash => Some(BinOp::Div),
+            Token::Percent => Some(BinOp::Rem),
             _ => None,
         }
     }
@@ -1,4 +1,6 @@
-#[derive(Clone, Copy, Debug, Default, Hash, Eq, Ord, PartialOrd, PartialEq)]
+use makepad_microserde::*;
+
+#[derive(Clone, Copy, Debug, Default, Hash, Eq, Ord, PartialOrd, PartialEq, SerBin, DeBin, SerJson, DeJson, SerRon, DeRon)]
 pub struct LiveBodyId(pub usize);
 
 impl LiveBodyId{
@@ -14,7 +14,11 @@ pub enum Token {
     Eof,
     Not,
     NotEq,
+    Percent,
+    PercentEq,
+    And,
     AndAnd,
+    AndEq,
     LeftParen,
     RightParen,
     Star,
@@ -33,10 +37,14 @@ pub enum Token {
     Semi,
     Lt,
     LtEq,
+    Shl,
+    ShlEq,
     Eq,
     EqEq,
     Gt,
     GtEq,
+    Shr,
+    ShrEq,
     Question,
     Break,
     Const,
@@ -49,15 +57,21 @@ pub enum Token {
     //In,
     Inout,
     Let,
+    Loop,
     Return,
     //Crate,
     Splat,
     //Self_,
     Struct,
     //To,
+    While,
     LeftBracket,
     RightBracket,
     LeftBrace,
+    Caret,
+    CaretEq,
+    Or,
+    OrEq,
     OrOr,
     RightBrace,
     String(Ident),
@@ -72,7 +86,11 @@ impl fmt::Display for Token {
             Token::Eof => write!(f, "<eof>"),
             Token::Not => write!(f, "!"),
             Token::NotEq => write!(f, "!="),
+            Token::Percent => write!(f, "%"),
+            Token::PercentEq => write!(f, "%="),
+            Token::And => write!(f, "&"),
             Token::AndAnd => write!(f, "&&"),
+            Token::AndEq => write!(f, "&="),
             Token::LeftParen => write!(f, "("),
             Token::RightParen => write!(f, ")"),
             Token::Star => write!(f, "*"),
@@ -92,10 +110,14 @@ impl fmt::Display for Token {
             Token::Semi => write!(f, ";"),
             Token::Lt => write!(f, "<"),
             Token::LtEq => write!(f, "<="),
+            Token::Shl => write!(f, "<<"),
+            Token::ShlEq => write!(f, "<<="),
             Token::Eq => write!(f, "="),
             Token::EqEq => write!(f, "=="),
             Token::Gt => write!(f, ">"),
             Token::GtEq => write!(f, ">="),
+            Token::Shr => write!(f, ">>"),
+            Token::ShrEq => write!(f, ">>="),
             Token::Question => write!(f, "?"),
             Token::Break => write!(f, "break"),
             Token::Const => write!(f, "const"),
@@ -106,11 +128,17 @@ impl fmt::Display for Token {
             Token::If => write!(f, "if"),
             Token::Inout => write!(f, "inout"),
             Token::Let => write!(f, "let"),
+            Token::Loop => write!(f, "loop"),
             Token::Return => write!(f, "return"),
             Token::Struct => write!(f, "struct"),
+            Token::While => write!(f, "while"),
             Token::LeftBracket => write!(f, "["),
             Token::RightBracket => write!(f, "]"),
             Token::LeftBrace => write!(f, "{{"),
+            Token::Caret => write!(f, "^"),
+            Token::CaretEq => write!(f, "^="),
+            Token::Or => write!(f, "|"),
+            Token::OrEq => write!(f, "|="),
             Token::OrOr => write!(f, "||"),
             Token::RightBrace => write!(f, "}}"),
             Token::Ident(ident) => write!(f, "{}", ident),
@@ -8,6 +8,7 @@ pub enum Ty {
     Void,
     Bool,
     Int,
+    Uint,
     Float,
     Bvec2,
     Bvec3,
@@ -15,6 +16,9 @@ pub enum Ty {
     Ivec2,
     Ivec3,
     Ivec4,
+    Uvec2,
+    Uvec3,
+    Uvec4,
     Vec2,
     Vec3,
     Vec4,
@@ -22,6 +26,8 @@ pub enum Ty {
     Mat3,
     Mat4,
     Texture2D,
+    TextureCube,
+    Texture3D,
     Array { elem_ty: Rc<Ty>, len: usize },
     Struct { ident: Ident },
 }
@@ -32,6 +38,7 @@ impl Ty {
             Ty::Void => None,
             Ty::Bool => Some(TyLit::Bool),
             Ty::Int =>  Some(TyLit::Int),
+            Ty::Uint => Some(TyLit::Uint),
             Ty::Float => Some(TyLit::Float),
             Ty::Bvec2 => Some(TyLit::Bvec2),
             Ty::Bvec3 => Some(TyLit::Bvec3),
@@ -39,13 +46,18 @@ impl Ty {
             Ty::Ivec2 => Some(TyLit::Ivec2),
             Ty::Ivec3 => Some(TyLit::Ivec3),
             Ty::Ivec4 => Some(TyLit::Ivec4),
+            Ty::Uvec2 => Some(TyLit::Uvec2),
+            Ty::Uvec3 => Some(TyLit::Uvec3),
+            Ty::Uvec4 => Some(TyLit::Uvec4),
             Ty::Vec2 => Some(TyLit::Vec2),
             Ty::Vec3 => Some(TyLit::Vec3),
             Ty::Vec4 => Some(TyLit::Vec4),
             Ty::Mat2 => Some(TyLit::Mat2),
             Ty::Mat3 => Some(TyLit::Mat3),
             Ty::Mat4 => Some(TyLit::Mat4),
-            Ty::Texture2D => Some(TyLit::Bool),
+            Ty::Texture2D => Some(TyLit::Texture2D),
+            Ty::TextureCube => Some(TyLit::TextureCube),
+            Ty::Texture3D => Some(TyLit::Texture3D),
             Ty::Array { .. } => None,
             Ty::Struct { .. } => None
         }
@@ -53,7 +65,7 @@ impl Ty {
     
     pub fn is_scalar(&self) -> bool {
         match self {
-            Ty::Bool | Ty::Int | Ty::Float => true,
+            Ty::Bool | Ty::Int | Ty::Uint | Ty::Float => true,
             _ => false,
         }
     }
@@ -66,6 +78,9 @@ impl Ty {
             | Ty::Ivec2
             | Ty::Ivec3
             | Ty::Ivec4
+            | Ty::Uvec2
+            | Ty::Uvec3
+            | Ty::Uvec4
             | Ty::Vec2
             | Ty::Vec3
             | Ty::Vec4 => true,
@@ -83,13 +98,13 @@ impl Ty {
     pub fn size(&self) -> usize {
         match self {
             Ty::Void => 0,
-            Ty::Bool | Ty::Int | Ty::Float => 1,
-            Ty::Bvec2 | Ty::Ivec2 | Ty::Vec2 => 2,
-            Ty::Bvec3 | Ty::Ivec3 | Ty::Vec3 => 3,
-            Ty::Bvec4 | Ty::Ivec4 | Ty::Vec4 | Ty::Mat2 => 4,
+            Ty::Bool | Ty::Int | Ty::Uint | Ty::Float => 1,
+            Ty::Bvec2 | Ty::Ivec2 | Ty::Uvec2 | Ty::Vec2 => 2,
+            Ty::Bvec3 | Ty::Ivec3 | Ty::Uvec3 | Ty::Vec3 => 3,
+            Ty::Bvec4 | Ty::Ivec4 | Ty::Uvec4 | Ty::Vec4 | Ty::Mat2 => 4,
             Ty::Mat3 => 9,
             Ty::Mat4 => 16,
-            Ty::Texture2D { .. } => panic!(),
+            Ty::Texture2D | Ty::TextureCube | Ty::Texture3D => panic!(),
             Ty::Array { elem_ty, len } => elem_ty.size() * len,
             Ty::Struct { .. } => panic!(),
         }
@@ -102,6 +117,7 @@ impl fmt::Display for Ty {
             Ty::Void => write!(f, "void"),
             Ty::Bool => write!(f, "bool"),
             Ty::Int => write!(f, "int"),
+            Ty::Uint => write!(f, "uint"),
             Ty::Float => write!(f, "float"),
             Ty::Bvec2 => write!(f, "bvec2"),
             Ty::Bvec3 => write!(f, "bvec3"),
@@ -109,6 +125,9 @@ impl fmt::Display for Ty {
             Ty::Ivec2 => write!(f, "ivec2"),
             Ty::Ivec3 => write!(f, "ivec3"),
             Ty::Ivec4 => write!(f, "ivec4"),
+            Ty::Uvec2 => write!(f, "uvec2"),
+            Ty::Uvec3 => write!(f, "uvec3"),
+            Ty::Uvec4 => write!(f, "uvec4"),
             Ty::Vec2 => write!(f, "vec2"),
             Ty::Vec3 => write!(f, "vec3"),
             Ty::Vec4 => write!(f, "vec4"),
@@ -116,6 +135,8 @@ impl fmt::Display for Ty {
             Ty::Mat3 => write!(f, "mat3"),
             Ty::Mat4 => write!(f, "mat4"),
             Ty::Texture2D => write!(f, "texture2D"),
+            Ty::TextureCube => write!(f, "textureCube"),
+            Ty::Texture3D => write!(f, "texture3D"),
             Ty::Array { elem_ty, len } => write!(f, "{}[{}]", elem_ty, len),
             Ty::Struct { ident, .. } => write!(f, "{}", ident),
         }
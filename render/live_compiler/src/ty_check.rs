@@ -1,7 +1,7 @@
 use crate::shaderast::*;
 use crate::builtin::Builtin;
 use crate::env::{Env, Sym, VarKind};
-use crate::error::LiveError;
+use crate::error::{LiveError, LiveErrorLabel};
 use crate::ident::{Ident, IdentPath};
 use crate::lhs_check::LhsChecker;
 use crate::lit::{Lit, TyLit};
@@ -55,11 +55,13 @@ impl<'a, 'b> TyChecker<'a, 'b> {
         match self.env.find_sym(ident.to_ident_path(), span).ok_or_else( || LiveError {
             span,
             message: format!("`{}` is not defined in this scope", ident),
+            ..LiveError::default()
         }) ? {
             Sym::TyVar {ty} => Ok(ty.clone()),
             _ => Err(LiveError {
                 span,
                 message: format!("`{}` is not a type variable", ident),
+                ..LiveError::default()
             }),
         }
     }
@@ -79,15 +81,29 @@ impl<'a, 'b> TyChecker<'a, 'b> {
             return Err(LiveError {
                 span,
                 message: format!(
-                    "can't match expected type `{}` with actual type `{}",
+                    "can't match expected type `{}` with actual type `{}`",
                     expected_ty,
                     actual_ty
                 ),
+                labels: self.var_decl_label(expr, &actual_ty).into_iter().collect(),
+                helps: conversion_help(expected_ty, &actual_ty).into_iter().collect(),
+                ..LiveError::default()
             });
         }
         Ok(actual_ty)
     }
     
+    // points at the declaration of a variable that has the wrong type
+    fn var_decl_label(&self, expr: &Expr, ty: &Ty) -> Option<LiveErrorLabel> {
+        match expr.kind {
+            ExprKind::Var {ident_path, ..} => self.env.find_sym_span(ident_path).map( | span | LiveErrorLabel {
+                span,
+                message: format!("`{}` is declared here with type `{}`", ident_path, ty),
+            }),
+            _ => None,
+        }
+    }
+    
     pub fn ty_check_expr(&mut self, expr: &Expr) -> Result<Ty, LiveError> {
         let ty = match expr.kind {
             ExprKind::Cond {
@@ -169,15 +185,8 @@ impl<'a, 'b> TyChecker<'a, 'b> {
     ) -> Result<Ty, LiveError> {
         let left_ty = self.ty_check_expr(left_expr) ?;
         let right_ty = self.ty_check_expr(right_expr) ?;
-        match op {
-            BinOp::Assign
-                | BinOp::AddAssign
-                | BinOp::SubAssign
-                | BinOp::MulAssign
-                | BinOp::DivAssign => {
-                self.lhs_checker().lhs_check_expr(left_expr) ?;
-            }
-            _ => {}
+        if op.is_assign() {
+            self.lhs_checker().lhs_check_expr(left_expr) ?;
         }
         match op {
             BinOp::Assign => {
@@ -196,6 +205,13 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                 (Ty::Ivec3, Ty::Ivec3) => Some(Ty::Ivec3),
                 (Ty::Ivec4, Ty::Int) => Some(Ty::Ivec4),
                 (Ty::Ivec4, Ty::Ivec4) => Some(Ty::Ivec4),
+                (Ty::Uint, Ty::Uint) => Some(Ty::Uint),
+                (Ty::Uvec2, Ty::Uint) => Some(Ty::Uvec2),
+                (Ty::Uvec2, Ty::Uvec2) => Some(Ty::Uvec2),
+                (Ty::Uvec3, Ty::Uint) => Some(Ty::Uvec3),
+                (Ty::Uvec3, Ty::Uvec3) => Some(Ty::Uvec3),
+                (Ty::Uvec4, Ty::Uint) => Some(Ty::Uvec4),
+                (Ty::Uvec4, Ty::Uvec4) => Some(Ty::Uvec4),
                 (Ty::Vec2, Ty::Float) => Some(Ty::Vec2),
                 (Ty::Vec2, Ty::Vec2) => Some(Ty::Vec2),
                 (Ty::Vec3, Ty::Float) => Some(Ty::Vec3),
@@ -219,6 +235,13 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                 (Ty::Ivec3, Ty::Ivec3) => Some(Ty::Ivec3),
                 (Ty::Ivec4, Ty::Int) => Some(Ty::Ivec4),
                 (Ty::Ivec4, Ty::Ivec4) => Some(Ty::Ivec4),
+                (Ty::Uint, Ty::Uint) => Some(Ty::Uint),
+                (Ty::Uvec2, Ty::Uint) => Some(Ty::Uvec2),
+                (Ty::Uvec2, Ty::Uvec2) => Some(Ty::Uvec2),
+                (Ty::Uvec3, Ty::Uint) => Some(Ty::Uvec3),
+                (Ty::Uvec3, Ty::Uvec3) => Some(Ty::Uvec3),
+                (Ty::Uvec4, Ty::Uint) => Some(Ty::Uvec4),
+                (Ty::Uvec4, Ty::Uvec4) => Some(Ty::Uvec4),
                 (Ty::Vec2, Ty::Float) => Some(Ty::Vec2),
                 (Ty::Vec2, Ty::Vec2) => Some(Ty::Vec2),
                 (Ty::Vec2, Ty::Mat2) => Some(Ty::Vec2),
@@ -236,6 +259,39 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                 (Ty::Mat4, Ty::Mat4) => Some(Ty::Mat4),
                 _ => None,
             },
+            BinOp::RemAssign
+                | BinOp::BitAndAssign
+                | BinOp::BitOrAssign
+                | BinOp::BitXorAssign
+                | BinOp::ShlAssign
+                | BinOp::ShrAssign
+                | BinOp::BitOr
+                | BinOp::BitXor
+                | BinOp::BitAnd
+                | BinOp::Shl
+                | BinOp::Shr
+                | BinOp::Rem => match (&left_ty, &right_ty) {
+                // a uint can be shifted by an int amount
+                (Ty::Uint, Ty::Int)
+                    | (Ty::Uvec2, Ty::Int)
+                    | (Ty::Uvec3, Ty::Int)
+                    | (Ty::Uvec4, Ty::Int) if matches!(op.to_int_op(), Some(BinOp::Shl) | Some(BinOp::Shr)) => Some(left_ty.clone()),
+                (Ty::Int, Ty::Int) => Some(Ty::Int),
+                (Ty::Ivec2, Ty::Int) => Some(Ty::Ivec2),
+                (Ty::Ivec2, Ty::Ivec2) => Some(Ty::Ivec2),
+                (Ty::Ivec3, Ty::Int) => Some(Ty::Ivec3),
+                (Ty::Ivec3, Ty::Ivec3) => Some(Ty::Ivec3),
+                (Ty::Ivec4, Ty::Int) => Some(Ty::Ivec4),
+                (Ty::Ivec4, Ty::Ivec4) => Some(Ty::Ivec4),
+                (Ty::Uint, Ty::Uint) => Some(Ty::Uint),
+                (Ty::Uvec2, Ty::Uint) => Some(Ty::Uvec2),
+                (Ty::Uvec2, Ty::Uvec2) => Some(Ty::Uvec2),
+                (Ty::Uvec3, Ty::Uint) => Some(Ty::Uvec3),
+                (Ty::Uvec3, Ty::Uvec3) => Some(Ty::Uvec3),
+                (Ty::Uvec4, Ty::Uint) => Some(Ty::Uvec4),
+                (Ty::Uvec4, Ty::Uvec4) => Some(Ty::Uvec4),
+                _ => None,
+            },
             BinOp::Or | BinOp::And => match (&left_ty, &right_ty) {
                 (Ty::Bool, Ty::Bool) => Some(Ty::Bool),
                 _ => None,
@@ -247,6 +303,10 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                 (Ty::Ivec2, Ty::Ivec2) => Some(Ty::Bool),
                 (Ty::Ivec3, Ty::Ivec3) => Some(Ty::Bool),
                 (Ty::Ivec4, Ty::Ivec4) => Some(Ty::Bool),
+                (Ty::Uint, Ty::Uint) => Some(Ty::Bool),
+                (Ty::Uvec2, Ty::Uvec2) => Some(Ty::Bool),
+                (Ty::Uvec3, Ty::Uvec3) => Some(Ty::Bool),
+                (Ty::Uvec4, Ty::Uvec4) => Some(Ty::Bool),
                 (Ty::Vec2, Ty::Vec2) => Some(Ty::Bool),
                 (Ty::Vec3, Ty::Vec3) => Some(Ty::Bool),
                 (Ty::Vec4, Ty::Vec4) => Some(Ty::Bool),
@@ -257,6 +317,7 @@ impl<'a, 'b> TyChecker<'a, 'b> {
             },
             BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => match (&left_ty, &right_ty) {
                 (Ty::Int, Ty::Int) => Some(Ty::Bool),
+                (Ty::Uint, Ty::Uint) => Some(Ty::Bool),
                 (Ty::Float, Ty::Float) => Some(Ty::Bool),
                 _ => None,
             },
@@ -275,6 +336,13 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                 (Ty::Ivec3, Ty::Ivec3) => Some(Ty::Ivec3),
                 (Ty::Ivec4, Ty::Int) => Some(Ty::Ivec4),
                 (Ty::Ivec4, Ty::Ivec4) => Some(Ty::Ivec4),
+                (Ty::Uint, Ty::Uint) => Some(Ty::Uint),
+                (Ty::Uvec2, Ty::Uint) => Some(Ty::Uvec2),
+                (Ty::Uvec2, Ty::Uvec2) => Some(Ty::Uvec2),
+                (Ty::Uvec3, Ty::Uint) => Some(Ty::Uvec3),
+                (Ty::Uvec3, Ty::Uvec3) => Some(Ty::Uvec3),
+                (Ty::Uvec4, Ty::Uint) => Some(Ty::Uvec4),
+                (Ty::Uvec4, Ty::Uvec4) => Some(Ty::Uvec4),
                 (Ty::Vec2, Ty::Float) => Some(Ty::Vec2),
                 (Ty::Vec2, Ty::Vec2) => Some(Ty::Vec2),
                 (Ty::Vec3, Ty::Float) => Some(Ty::Vec3),
@@ -304,6 +372,13 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                 (Ty::Ivec3, Ty::Ivec3) => Some(Ty::Ivec3),
                 (Ty::Ivec4, Ty::Int) => Some(Ty::Ivec4),
                 (Ty::Ivec4, Ty::Ivec4) => Some(Ty::Ivec4),
+                (Ty::Uint, Ty::Uint) => Some(Ty::Uint),
+                (Ty::Uvec2, Ty::Uint) => Some(Ty::Uvec2),
+                (Ty::Uvec2, Ty::Uvec2) => Some(Ty::Uvec2),
+                (Ty::Uvec3, Ty::Uint) => Some(Ty::Uvec3),
+                (Ty::Uvec3, Ty::Uvec3) => Some(Ty::Uvec3),
+                (Ty::Uvec4, Ty::Uint) => Some(Ty::Uvec4),
+                (Ty::Uvec4, Ty::Uvec4) => Some(Ty::Uvec4),
                 (Ty::Vec2, Ty::Float) => Some(Ty::Vec2),
                 (Ty::Vec2, Ty::Vec2) => Some(Ty::Vec2),
                 (Ty::Vec2, Ty::Mat2) => Some(Ty::Vec2),
@@ -328,12 +403,24 @@ impl<'a, 'b> TyChecker<'a, 'b> {
         .ok_or_else( || LiveError {
             span,
             message: format!(
-                "can't apply binary operator `{}` to operands of type `{}` and `{}",
+                "can't apply binary operator `{}` to operands of type `{}` and `{}`",
                 op,
                 left_ty,
                 right_ty
             )
                 .into(),
+            labels: vec![
+                LiveErrorLabel {
+                    span: left_expr.span,
+                    message: format!("this is of type `{}`", left_ty),
+                },
+                LiveErrorLabel {
+                    span: right_expr.span,
+                    message: format!("this is of type `{}`", right_ty),
+                },
+            ],
+            helps: conversion_help(&left_ty, &right_ty).into_iter().collect(),
+            ..LiveError::default()
         })
     }
     
@@ -342,6 +429,14 @@ impl<'a, 'b> TyChecker<'a, 'b> {
         match op {
             UnOp::Not => match ty {
                 Ty::Bool => Some(Ty::Bool),
+                Ty::Int => Some(Ty::Int),
+                Ty::Ivec2 => Some(Ty::Ivec2),
+                Ty::Ivec3 => Some(Ty::Ivec3),
+                Ty::Ivec4 => Some(Ty::Ivec4),
+                Ty::Uint => Some(Ty::Uint),
+                Ty::Uvec2 => Some(Ty::Uvec2),
+                Ty::Uvec3 => Some(Ty::Uvec3),
+                Ty::Uvec4 => Some(Ty::Uvec4),
                 _ => None,
             },
             UnOp::Neg => match ty {
@@ -361,6 +456,7 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                 ty
             )
                 .into(),
+            ..LiveError::default()
         })
     }
     
@@ -382,6 +478,7 @@ impl<'a, 'b> TyChecker<'a, 'b> {
             _ => Err(LiveError {
                 span,
                 message: format!("method `{}` is not defined on type `{}`", ident, ty),
+                ..LiveError::default()
             }),
         }
     }
@@ -411,6 +508,7 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                     .ok_or_else( || LiveError {
                     span,
                     message: format!("field `{}` is not defined on type `{}`", field_ident, ty),
+                    ..LiveError::default()
                 }) ?;
                 Ok(match ty {
                     Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => match swizzle.len() {
@@ -427,6 +525,13 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                         4 => Ty::Ivec4,
                         _ => panic!(),
                     },
+                    Ty::Uvec2 | Ty::Uvec3 | Ty::Uvec4 => match swizzle.len() {
+                        1 => Ty::Uint,
+                        2 => Ty::Uvec2,
+                        3 => Ty::Uvec3,
+                        4 => Ty::Uvec4,
+                        _ => panic!(),
+                    },
                     Ty::Vec2 | Ty::Vec3 | Ty::Vec4 => match swizzle.len() {
                         1 => Ty::Float,
                         2 => Ty::Vec2,
@@ -440,10 +545,12 @@ impl<'a, 'b> TyChecker<'a, 'b> {
             Ty::Struct {ident} => Ok(self .shader .find_struct_decl(ident) .unwrap() .find_field(field_ident) .ok_or(LiveError {
                 span,
                 message: format!("field `{}` is not defined on type `{}`", field_ident, ident),
+                ..LiveError::default()
             }) ? .ty_expr .ty .borrow() .as_ref() .unwrap() .clone()),
             _ => Err(LiveError {
                 span,
                 message: format!("can't access field on value of type `{}`", ty).into(),
+                ..LiveError::default()
             }),
         }
     }
@@ -459,6 +566,7 @@ impl<'a, 'b> TyChecker<'a, 'b> {
         let elem_ty = match ty {
             Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => Ty::Bool,
             Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => Ty::Int,
+            Ty::Uvec2 | Ty::Uvec3 | Ty::Uvec4 => Ty::Uint,
             Ty::Vec2 | Ty::Vec3 | Ty::Vec4 => Ty::Float,
             Ty::Mat2 => Ty::Vec2,
             Ty::Mat3 => Ty::Vec3,
@@ -467,6 +575,7 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                 return Err(LiveError {
                     span,
                     message: format!("can't index into value of type `{}`", ty).into(),
+                    ..LiveError::default()
                 })
             }
         };
@@ -474,6 +583,7 @@ impl<'a, 'b> TyChecker<'a, 'b> {
             return Err(LiveError {
                 span,
                 message: "index is not an integer".into(),
+                ..LiveError::default()
             });
         }
         Ok(elem_ty)
@@ -493,6 +603,7 @@ impl<'a, 'b> TyChecker<'a, 'b> {
         match self.env.find_sym(ident_path, span).ok_or_else( || LiveError {
             span,
             message: format!("`{}` is not defined", ident_path),
+            ..LiveError::default()
         }) ? {
             Sym::Builtin => {
                 let builtin = self.builtins.get(&ident_path.get_single().expect("unexpected")).unwrap();
@@ -513,11 +624,16 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                         write!(message, "{}{}", sep, arg_ty).unwrap();
                         sep = ", ";
                     }
-                    LiveError {span, message}
+                    LiveError {span, message, ..LiveError::default()}
                 }) ? .clone())
             }
             Sym::Fn => {
-                let fn_decl = self.shader.find_fn_decl(ident_path).unwrap();
+                let fn_decl = match self.shader.find_fn_decl(ident_path) {
+                    // not overloaded, so mismatched args are reported against its one signature
+                    Some(fn_decl) => fn_decl,
+                    None => self.resolve_overload(span, ident_path, arg_exprs) ?,
+                };
+                self.check_unambiguous_call(span, ident_path, fn_decl) ?;
                 if arg_exprs.len() < fn_decl.params.len() {
                     return Err(LiveError {
                         span,
@@ -528,6 +644,7 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                             arg_exprs.len(),
                         )
                             .into(),
+                        ..LiveError::default()
                     });
                 }
                 if arg_exprs.len() > fn_decl.params.len() {
@@ -540,6 +657,7 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                             arg_exprs.len()
                         )
                             .into(),
+                        ..LiveError::default()
                     });
                 }
                 for (index, (arg_expr, param)) in
@@ -558,7 +676,8 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                                 ident_path,
                                 param_ty,
                                 arg_ty,
-                            ).into()
+                            ).into(),
+                            ..LiveError::default()
                         });
                     }
                     if param.is_inout {
@@ -570,10 +689,47 @@ impl<'a, 'b> TyChecker<'a, 'b> {
             _ => Err(LiveError {
                 span,
                 message: format!("`{}` is not a function", ident_path).into(),
+                ..LiveError::default()
             }),
         }
     }
     
+    fn resolve_overload(&self, span: Span, ident_path: IdentPath, arg_exprs: &[Expr]) -> Result<&'a FnDecl, LiveError> {
+        self.shader.find_fn_decl_for_call(ident_path, arg_exprs).ok_or_else( || {
+            let arg_tys = arg_exprs.iter().map( | arg_expr | arg_expr.ty.borrow().clone().unwrap()).collect::<Vec<_>>();
+            LiveError {
+                span,
+                message: format!("no overload of function `{}` takes arguments of types `({})`", ident_path, CommaSep(&arg_tys)),
+                labels: self.shader.find_fn_decls(ident_path).map( | decl | LiveErrorLabel {
+                    span: decl.span,
+                    message: format!("this overload takes `({})`", CommaSep(&param_tys(decl))),
+                }).collect(),
+                ..LiveError::default()
+            }
+        })
+    }
+    
+    // a declaration with the same parameter types as one from a used shader overrides
+    // it, but within one live body there is no telling which of the two was meant
+    fn check_unambiguous_call(&self, span: Span, ident_path: IdentPath, fn_decl: &FnDecl) -> Result<(), LiveError> {
+        let candidates = self.shader.find_fn_decls(ident_path).filter( | decl | {
+            decl.ident_path == fn_decl.ident_path && decl.span.live_body_id == fn_decl.span.live_body_id
+        }).collect::<Vec<_>>();
+        if candidates.len() < 2 {
+            return Ok(());
+        }
+        Err(LiveError {
+            span,
+            message: format!("call to function `{}` is ambiguous", ident_path),
+            labels: candidates.iter().map( | decl | LiveErrorLabel {
+                span: decl.span,
+                message: format!("this declaration takes `({})`", CommaSep(&param_tys(decl))),
+            }).collect(),
+            helps: vec!["overloads need different parameter types".to_string()],
+            ..LiveError::default()
+        })
+    }
+    
     fn ty_check_macro_call_expr(
         &mut self,
         span: Span,
@@ -584,6 +740,7 @@ impl<'a, 'b> TyChecker<'a, 'b> {
         return Err(LiveError {
             span,
             message: "macro not found!".into(),
+            ..LiveError::default()
         });
     }
     
@@ -630,7 +787,8 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                             actual_size,
                             expected_size,
                         )
-                            .into()
+                            .into(),
+                        ..LiveError::default()
                     });
                 }
                 if actual_size > expected_size {
@@ -643,6 +801,7 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                             actual_size,
                         )
                             .into(),
+                        ..LiveError::default()
                     });
                 }
                 Ok(ty.clone())
@@ -655,6 +814,7 @@ impl<'a, 'b> TyChecker<'a, 'b> {
                     CommaSep(&arg_tys)
                 )
                     .into(),
+                ..LiveError::default()
             }),
         }
     }
@@ -669,6 +829,7 @@ impl<'a, 'b> TyChecker<'a, 'b> {
         match self.env.find_sym(ident_path, span).ok_or_else( || LiveError {
             span,
             message: format!("`{}` is not defined in this scope", ident_path),
+            ..LiveError::default()
         }) ? {
             Sym::Var {
                 ref ty,
@@ -693,6 +854,7 @@ impl<'a, 'b> TyChecker<'a, 'b> {
             _ => Err(LiveError {
                 span,
                 message: format!("`{}` is not a variable", ident_path).into(),
+                ..LiveError::default()
             }),
         }
     }
@@ -701,3 +863,16 @@ impl<'a, 'b> TyChecker<'a, 'b> {
         Ok(lit.to_ty())
     }
 }
+
+fn param_tys(decl: &FnDecl) -> Vec<Ty> {
+    decl.params.iter().map( | param | param.ty_expr.ty.borrow().clone().unwrap()).collect()
+}
+
+// int and float values of the same shape only differ by a constructor call
+fn conversion_help(expected_ty: &Ty, actual_ty: &Ty) -> Option<String> {
+    let is_numeric = | ty: &Ty | (ty.is_scalar() || ty.is_vector()) && ty.maybe_ty_lit().is_some();
+    if is_numeric(expected_ty) && is_numeric(actual_ty) && expected_ty.size() == actual_ty.size() {
+        return Some(format!("convert the value with `{}(...)`", expected_ty));
+    }
+    None
+}
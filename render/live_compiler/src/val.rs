@@ -6,6 +6,7 @@ use std::fmt;
 pub enum Val {
     Bool(bool),
     Int(i32),
+    Uint(u32),
     Float(f32),
     Vec4(Vec4),
 }
@@ -31,6 +32,7 @@ impl fmt::Display for Val {
         match *self {
             Val::Bool(val) => write!(f, "{}", val),
             Val::Int(val) => write!(f, "{}", val),
+            Val::Uint(val) => write!(f, "{}u", val),
             Val::Float(v) => write!(f, "{}", PrettyPrintedFloat(v)),
             Val::Vec4(val) => write!(f, "{}", val),
         }
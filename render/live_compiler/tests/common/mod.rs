@@ -0,0 +1,42 @@
+// The live body fixture the integration tests share, each test file only uses part of it.
+#![allow(dead_code)]
+
+use makepad_live_compiler::analyse::ShaderCompileOptions;
+use makepad_live_compiler::ident::{Ident, IdentPath, QualifiedIdentPath};
+use makepad_live_compiler::livestyles::{LiveBody, LiveBodyError, LiveStyles};
+use makepad_live_compiler::optimise::ShaderOptimisations;
+use makepad_live_compiler::shaderast::ShaderAst;
+
+pub const OPTIONS: ShaderCompileOptions = ShaderCompileOptions {
+    gather_all: false,
+    create_const_table: false,
+    no_const_collapse: false,
+    optimisations: ShaderOptimisations::NONE,
+};
+
+// adds code as a live body of file and compiles the module::shader it defines
+pub fn compile_live_body(file: &str, module: &str, code: &str, options: ShaderCompileOptions) -> Result<(LiveStyles, ShaderAst), Vec<LiveBodyError>> {
+    let mut live_styles = LiveStyles::new();
+    live_styles.add_live_body(LiveBody {
+        file: file.to_string(),
+        module_path: module.to_string(),
+        line: 1,
+        column: 1,
+        code: code.to_string(),
+    });
+    let mut errors = Vec::new();
+    live_styles.process_changed_live_bodies(&mut errors);
+    live_styles.process_changed_deps(&mut errors);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let live_item_id = QualifiedIdentPath(IdentPath::from_two(Ident::new(module), Ident::new("shader"))).to_live_item_id();
+    let (shader_ast, _) = live_styles.collect_and_analyse_shader(live_item_id, options).map_err(|err| vec![err])?;
+    Ok((live_styles, shader_ast))
+}
+
+// compiles `self::shader: Shader { code }` after the live_values, as module.rs would, failing with the first error message
+pub fn compile_shader(module: &str, live_values: &str, code: &str, options: ShaderCompileOptions) -> Result<(LiveStyles, ShaderAst), String> {
+    let code = format!("{} self::shader: Shader {{ {} }}", live_values, code);
+    compile_live_body(&format!("{}.rs", module), module, &code, options).map_err(|errors| errors[0].message.clone())
+}
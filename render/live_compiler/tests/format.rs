@@ -0,0 +1,180 @@
+mod common;
+
+use makepad_live_compiler::analyse::ShaderCompileOptions;
+use makepad_live_compiler::format;
+use makepad_live_compiler::generate_glsl;
+use makepad_live_compiler::livestyles::LiveStyles;
+use makepad_live_compiler::optimise::ShaderOptimisations;
+use makepad_live_compiler::shaderast::ShaderAst;
+use std::fs;
+use common::OPTIONS;
+
+const SHADER: &str = r#"
+    struct Acc {
+        sum: float,
+        count: int
+    }
+    impl Acc {
+        fn push(inout self, value: float) {
+            self.sum += value;
+            self.count += 1;
+        }
+        fn mean(self) -> float {
+            return self.sum / float(self.count);
+        }
+    }
+    const STEPS: int = 4;
+    instance rect: vec4;
+    geometry geom: vec2;
+    varying pos: vec2;
+    fn falloff(d: float, r: float) -> float {
+        return clamp(1.0 - d / r, 0.0, 1.0);
+    }
+    fn falloff2(d: vec2, r: float) -> float {
+        return falloff(length(d), r);
+    }
+    fn vertex() -> vec4 {
+        pos = geom;
+        let offset = vec2(2.0 * 0.5, -(sqrt(16.0) - 4.0));
+        return vec4(rect.xy + geom * rect.zw + offset, 0.0, 1.0);
+    }
+    fn pixel() -> vec4 {
+        let acc: Acc;
+        for i from 0 to STEPS {
+            acc.push(pos.x * pos.x + float(i) * 0.0);
+        }
+        let glow = falloff2(pos - vec2(0.5), 0.25 + 0.25) * (pos.x * pos.y + 1.0);
+        if STEPS > 2 && pos.x < 0.5 {
+            glow += 0.1;
+        } else if pos.y > 0.5 {
+            glow = pos.x > 0.75 ? 1.0 : 0.5;
+        }
+        return vec4(acc.mean(), glow, #E27D3A.r, 1.0);
+    }
+"#;
+
+fn compile(code: &str, options: ShaderCompileOptions) -> (LiveStyles, ShaderAst) {
+    common::compile_shader("format", "", code, options).unwrap()
+}
+
+fn generate(code: &str, options: ShaderCompileOptions) -> (String, String) {
+    let (live_styles, shader) = compile(code, options);
+    (
+        generate_glsl::generate_vertex_shader(&shader, &live_styles, OPTIONS),
+        generate_glsl::generate_fragment_shader(&shader, &live_styles, OPTIONS),
+    )
+}
+
+#[test]
+fn golden_sources_are_canonical() {
+    for entry in fs::read_dir("tests/golden").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or( | extension | extension != "live") {
+            continue;
+        }
+        let code = fs::read_to_string(&path).unwrap();
+        match format::format_live_body(&code) {
+            Ok(formatted) => assert_eq!(formatted, code, "{} is not formatted canonically", path.display()),
+            Err(err) => assert!(path.ends_with("parse_error.live"), "{}: {}", path.display(), err.message),
+        }
+    }
+}
+
+#[test]
+fn formats_live_body() {
+    let code = "// colors\nself::tint:#E27D3A;  // orange\n\n\n\nself::size: vec2( 1.0,2.0 );\n\
+        self::layout: Layout {\n  walk: {width: Fill,height:Fix(20.)}, // walk\n\n  padding: {l: 5., t: 0.},\n}\n\
+        self::anim: Anim {play: Cut {duration: 0.2}, tracks: [\n Float {bind_to: self::shader::hover, keys: {1.0: 0.0}}\n]}\n\
+        self::shader: Shader { use crate::base::*;\n  instance hover: float; struct S { a: float, // a\n b: int }\n\
+        impl S { fn f(inout self, x: float) -> float { return ((self.a + x)) * 2.0; } // f\n }\n\
+        fn pixel() -> vec4 {\n    // start\n    let x = -(1.0 + 2.0) * (3.0 - (4.0 - 5.0)); let y = x > 0.0 ? 1.0 : 2.0;\n\n\n\
+        if x > 1.0 { x = 2.0; } else if x < 0.0 { x = 3.0; } else { x += 1.0; /* inc */ }\n\
+        loop max 4 {} return vec4(x, -x, y, (1.0 - hover)); }\n}\n// end\n";
+    let expected = "// colors
+self::tint: #E27D3A; // orange
+
+self::size: vec2(1.0, 2.0);
+self::layout: Layout {
+    walk: {width: Fill, height: Fix(20.)}, // walk
+
+    padding: {l: 5., t: 0.},
+}
+self::anim: Anim {play: Cut {duration: 0.2}, tracks: [
+    Float {bind_to: self::shader::hover, keys: {1.0: 0.0}}
+]}
+self::shader: Shader {
+    use crate::base::*;
+    instance hover: float;
+    struct S {
+        a: float, // a
+        b: int
+    }
+    impl S {
+        fn f(inout self, x: float) -> float {
+            return (self.a + x) * 2.0;
+        } // f
+    }
+    fn pixel() -> vec4 {
+        // start
+        let x = -(1.0 + 2.0) * (3.0 - (4.0 - 5.0));
+        let y = x > 0.0 ? 1.0 : 2.0;
+
+        if x > 1.0 {
+            x = 2.0;
+        } else if x < 0.0 {
+            x = 3.0;
+        } else {
+            x += 1.0; /* inc */
+        }
+        loop max 4 {}
+        return vec4(x, -x, y, 1.0 - hover);
+    }
+}
+// end
+";
+    let formatted = format::format_live_body(code).unwrap();
+    assert_eq!(formatted, expected);
+    assert_eq!(format::format_live_body(&formatted).unwrap(), formatted);
+}
+
+#[test]
+fn reports_parse_errors() {
+    let err = format::format_live_body("self::shader: Shader { fn pixel() -> vec4 { return 1.0 } }").unwrap_err();
+    assert_eq!(err.message, "expected ; unexpected token `}`");
+    assert!(format::format_live_body("self::x: 1.0").is_err());
+}
+
+#[test]
+fn round_trips_printed_shaders() {
+    let optimised = ShaderCompileOptions {optimisations: ShaderOptimisations::ALL, ..OPTIONS};
+    for options in [OPTIONS, optimised].iter() {
+        let (_, shader) = compile(SHADER, *options);
+        let printed = format::print_shader(&shader);
+        assert_eq!(generate(&printed, OPTIONS), generate(SHADER, *options), "{}", printed);
+        // printing is stable once the shader went through the printer
+        let (_, reparsed) = compile(&printed, OPTIONS);
+        assert_eq!(format::print_shader(&reparsed), printed);
+    }
+}
+
+#[test]
+fn prints_overloads_by_source_name() {
+    let code = r#"
+        fn area(r: float) -> float {
+            return 3.0 * r * r;
+        }
+        fn area(size: vec2) -> float {
+            return size.x * size.y;
+        }
+        fn vertex() -> vec4 {
+            return vec4(0.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(area(2.0), area(vec2(2.0, 3.0)), 0.0, 0.0);
+        }
+    "#;
+    let (_, shader) = compile(code, OPTIONS);
+    let printed = format::print_shader(&shader);
+    assert!(printed.contains("fn area(size: vec2) -> float {"), "{}", printed);
+    assert_eq!(generate(&printed, OPTIONS), generate(code, OPTIONS));
+}
@@ -0,0 +1,108 @@
+// Golden output tests. Every tests/golden/<name>.live is a live body that defines
+// self::shader, compiled with the same steps as the live_body! path. A program that
+// compiles is checked against <name>.vertex.glsl, <name>.pixel.glsl, <name>.hlsl,
+// <name>.metal and <name>.wgsl, one that doesn't against <name>.error, which holds
+// the rendered LiveBodyErrors. Sources named optimise_*.live are compiled with every
+// optimisation pass enabled.
+// Run with MAKEPAD_BLESS=1 to (re)write the expected files after a codegen change.
+mod common;
+
+use makepad_live_compiler::analyse::ShaderCompileOptions;
+use makepad_live_compiler::optimise::ShaderOptimisations;
+use makepad_live_compiler::generate_glsl;
+use makepad_live_compiler::generate_hlsl;
+use makepad_live_compiler::generate_metal;
+use makepad_live_compiler::generate_wgsl;
+use makepad_live_compiler::livestyles::LiveBodyError;
+use std::fs;
+use std::path::{Path, PathBuf};
+use common::OPTIONS;
+
+const OPTIMISED_OPTIONS: ShaderCompileOptions = ShaderCompileOptions {
+    optimisations: ShaderOptimisations::ALL,
+    ..OPTIONS
+};
+
+fn compile(file: &str, code: &str) -> Result<Vec<(&'static str, String)>, Vec<LiveBodyError>> {
+    let options = if file.starts_with("optimise_") { OPTIMISED_OPTIONS } else { OPTIONS };
+    let (live_styles, shader_ast) = common::compile_live_body(file, "golden", code, options)?;
+    generate_wgsl::check_shader_support(&shader_ast).map_err(|err| vec![live_styles.live_error_to_live_body_error(err)])?;
+    Ok(vec![
+        ("vertex.glsl", generate_glsl::generate_vertex_shader(&shader_ast, &live_styles, options)),
+        ("pixel.glsl", generate_glsl::generate_fragment_shader(&shader_ast, &live_styles, options)),
+        ("hlsl", generate_hlsl::generate_shader(&shader_ast, &live_styles, options)),
+        ("metal", generate_metal::generate_shader(&shader_ast, &live_styles, options)),
+        ("wgsl", generate_wgsl::generate_shader(&shader_ast, &live_styles, options)),
+    ])
+}
+
+// returns a description of the mismatch, if any
+fn check(path: &Path, actual: &str, bless: bool) -> Option<String> {
+    if bless {
+        fs::write(path, actual).unwrap();
+        return None;
+    }
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) => expected,
+        Err(_) => return Some(format!("{} is missing", path.display())),
+    };
+    if expected == actual {
+        return None;
+    }
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(expected), Some(actual)) if expected == actual => line += 1,
+            (expected, actual) => {
+                return Some(format!(
+                    "{}:{}\n  expected: {}\n  actual:   {}",
+                    path.display(),
+                    line,
+                    expected.unwrap_or("<end of file>"),
+                    actual.unwrap_or("<end of file>")
+                ))
+            }
+        }
+    }
+}
+
+#[test]
+fn golden() {
+    let bless = std::env::var_os("MAKEPAD_BLESS").is_some();
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let mut sources = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "live"))
+        .collect::<Vec<_>>();
+    sources.sort();
+    assert!(!sources.is_empty(), "no golden sources in {}", dir.display());
+
+    let mut failures = Vec::new();
+    for source in &sources {
+        let name = source.file_stem().unwrap().to_str().unwrap();
+        let code = fs::read_to_string(source).unwrap();
+        let outputs = match compile(&format!("{}.live", name), &code) {
+            Ok(outputs) => {
+                let error_path = dir.join(format!("{}.error", name));
+                if !bless && error_path.exists() {
+                    failures.push(format!("{} compiled, but {} expects errors", source.display(), error_path.display()));
+                }
+                outputs
+            }
+            Err(errors) => {
+                let mut string = String::new();
+                for error in errors {
+                    string.push_str(&error.rendered);
+                }
+                vec![("error", string)]
+            }
+        };
+        for (ext, actual) in outputs {
+            failures.extend(check(&dir.join(format!("{}.{}", name, ext)), &actual, bless));
+        }
+    }
+    assert!(failures.is_empty(), "golden output mismatch (rerun with MAKEPAD_BLESS=1 to update):\n{}", failures.join("\n"));
+}
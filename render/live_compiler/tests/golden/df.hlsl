@@ -0,0 +1,236 @@
+SamplerState mpsc_default_texture_sampler{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;AddressW=Wrap;};
+float4 sample2d(Texture2D tex, float2 pos){return tex.Sample(mpsc_default_texture_sampler,pos);}
+float4 sample_cube(TextureCube tex, float3 dir){return tex.Sample(mpsc_default_texture_sampler,dir);}
+float4 sample3d(Texture3D tex, float3 pos){return tex.Sample(mpsc_default_texture_sampler,pos);}
+struct Df {
+    float2 pos;
+    float4 result;
+    float2 last_pos;
+    float2 start_pos;
+    float shape;
+    float clip;
+    float has_clip;
+    float old_shape;
+    float blur;
+    float aa;
+    float scale;
+    float field;
+};
+cbuffer mpsc_default_Uniforms : register(b3){};
+cbuffer mpsc_live_Uniforms : register(b4) {
+}
+struct mpsc_Geometries {
+    float2 geom: GEOMA;
+};
+struct mpsc_Instances {
+};
+struct mpsc_Varyings {
+    float4 mpsc_position: SV_POSITION;
+    float2 pos: VARYA;
+};
+static const float TORAD = 0.017453292;
+float2 mpsc_vec2_float(float x) {
+    return float2(x, x);
+}
+float4 mpsc_vec4_float(float x) {
+    return float4(x, x, x, x);
+}
+float4 mpsc_vec4_vec2_float_float(float2 x0, float x1, float x2) {
+    return float4(x0[0], x0[1], x1, x2);
+}
+float4 mpsc_vec4_vec3_float(float3 x0, float x1) {
+    return float4(x0[0], x0[1], x0[2], x1);
+}
+float4 mpsc_vertex(in mpsc_Geometries mpsc_geometries, inout mpsc_Varyings mpsc_varyings) {
+    (mpsc_varyings.pos = mpsc_geometries.geom);
+    return mpsc_vec4_vec2_float_float(((mpsc_geometries.geom * 2.0) - 1.0), 0.0, 1.0);
+}
+void Df_blend(inout Df self, float k) {
+    (self.old_shape = (self.shape = lerp(self.old_shape, self.field, k)));
+}
+void Df_box(inout Df self, float x, float y, float w, float h, float r) {
+    float2 p = (self.pos - float2(x, y));
+    float2 size = float2((0.5 * w), (0.5 * h));
+    float2 bp = max((abs((p - size.xy)) - (size.xy - float2((2.0 * r), (2.0 * r)).xy)), float2(0.0, 0.0));
+    (self.field = ((length(bp) - (2.0 * r)) / self.scale));
+    (self.old_shape = self.shape);
+    (self.shape = min(self.shape, self.field));
+}
+void Df_circle(inout Df self, float x, float y, float r) {
+    float2 c = (self.pos - float2(x, y));
+    float len = sqrt(((c.x * c.x) + (c.y * c.y)));
+    (self.field = ((len - r) / self.scale));
+    (self.old_shape = self.shape);
+    (self.shape = min(self.shape, self.field));
+}
+void Df_clear(inout Df self, float4 color) {
+    (self.result = mpsc_vec4_vec3_float(((color.rgb * color.a) + (self.result.rgb * (1.0 - color.a))), color.a));
+}
+void Df_line_to(inout Df self, float x, float y) {
+    float2 p = float2(x, y);
+    float2 pa = (self.pos - self.last_pos);
+    float2 ba = (p - self.last_pos);
+    float h = clamp((dot(pa, ba) / dot(ba, ba)), 0.0, 1.0);
+    float s = sign(((pa.x * ba.y) - (pa.y * ba.x)));
+    (self.field = (length((pa - (ba * h))) / self.scale));
+    (self.old_shape = self.shape);
+    (self.shape = min(self.shape, self.field));
+    (self.clip = max(self.clip, (self.field * s)));
+    (self.has_clip = 1.0);
+    (self.last_pos = p);
+}
+void Df_close_path(inout Df self) {
+    Df_line_to(self, self.start_pos.x, self.start_pos.y);
+}
+float Df_calc_blur(inout Df self, float w) {
+    float wa = clamp((-w * self.aa), 0.0, 1.0);
+    float wb = 1.0;
+    if ((self.blur > 0.001)) {
+        (wb = clamp((-w / self.blur), 0.0, 1.0));
+    }
+    return (wa * wb);
+}
+float4 Df_fill_keep(inout Df self, float4 color) {
+    float f = Df_calc_blur(self, self.shape);
+    float4 source = mpsc_vec4_vec3_float((color.rgb * color.a), color.a);
+    (self.result = ((source * f) + (self.result * (1.0 - (source.a * f)))));
+    if ((self.has_clip > 0.5)) {
+        float f2 = (1.0 - Df_calc_blur(self, -self.clip));
+        (self.result = ((source * f2) + (self.result * (1.0 - (source.a * f2)))));
+    }
+    return self.result;
+}
+float4 Df_fill(inout Df self, float4 color) {
+    Df_fill_keep(self, color);
+    (self.old_shape = (self.shape = 100000000000000000000.0));
+    (self.clip = -100000000000000000000.0);
+    (self.has_clip = 0.0);
+    return self.result;
+}
+void Df_gloop(inout Df self, float k) {
+    float h = clamp((0.5 + ((0.5 * (self.old_shape - self.field)) / k)), 0.0, 1.0);
+    (self.old_shape = (self.shape = (lerp(self.old_shape, self.field, h) - ((k * h) * (1.0 - h)))));
+}
+float4 Df_glow_keep(inout Df self, float4 color, float width) {
+    float f = Df_calc_blur(self, (abs(self.shape) - (width / self.scale)));
+    float4 source = mpsc_vec4_vec3_float((color.rgb * color.a), color.a);
+    float4 dest = self.result;
+    (self.result = (mpsc_vec4_vec3_float((source.rgb * f), 0.0) + dest));
+    return self.result;
+}
+float4 Df_glow(inout Df self, float4 color, float width) {
+    Df_glow_keep(self, color, width);
+    (self.old_shape = (self.shape = 100000000000000000000.0));
+    (self.clip = -100000000000000000000.0);
+    (self.has_clip = 0.0);
+    return self.result;
+}
+void Df_hexagon(inout Df self, float x, float y, float r) {
+    float dx = (abs((x - self.pos.x)) * 1.15);
+    float dy = abs((y - self.pos.y));
+    (self.field = max(((dy + (cos(1.0471976) * dx)) - r), (dx - r)));
+    (self.old_shape = self.shape);
+    (self.shape = min(self.shape, self.field));
+}
+void Df_intersect(inout Df self) {
+    (self.old_shape = (self.shape = max(self.field, self.old_shape)));
+}
+void Df_move_to(inout Df self, float x, float y) {
+    (self.last_pos = (self.start_pos = float2(x, y)));
+}
+void Df_rect(inout Df self, float x, float y, float w, float h) {
+    float2 s = (float2(w, h) * 0.5);
+    float2 d = (abs(((float2(x, y) - self.pos) + s)) - s);
+    float2 dm = min(d, float2(0.0, 0.0));
+    (self.field = (max(dm.x, dm.y) + length(max(d, float2(0.0, 0.0)))));
+    (self.old_shape = self.shape);
+    (self.shape = min(self.shape, self.field));
+}
+void Df_rotate(inout Df self, float a, float x, float y) {
+    float ca = cos(-a);
+    float sa = sin(-a);
+    float2 p = (self.pos - float2(x, y));
+    (self.pos = (float2(((p.x * ca) - (p.y * sa)), ((p.x * sa) + (p.y * ca))) + float2(x, y)));
+}
+void Df_scale(inout Df self, float f, float x, float y) {
+    (self.scale *= f);
+    (self.pos = (((self.pos - float2(x, y)) * f) + float2(x, y)));
+}
+float4 Df_stroke_keep(inout Df self, float4 color, float width) {
+    float f = Df_calc_blur(self, (abs(self.shape) - (width / self.scale)));
+    float4 source = mpsc_vec4_vec3_float((color.rgb * color.a), color.a);
+    float4 dest = self.result;
+    (self.result = ((source * f) + (dest * (1.0 - (source.a * f)))));
+    return self.result;
+}
+float4 Df_stroke(inout Df self, float4 color, float width) {
+    Df_stroke_keep(self, color, width);
+    (self.old_shape = (self.shape = 100000000000000000000.0));
+    (self.clip = -100000000000000000000.0);
+    (self.has_clip = 0.0);
+    return self.result;
+}
+void Df_subtract(inout Df self) {
+    (self.old_shape = (self.shape = max(-self.field, self.old_shape)));
+}
+float2 Df_translate(inout Df self, float x, float y) {
+    (self.pos -= float2(x, y));
+    return self.pos;
+}
+void Df_union(inout Df self) {
+    (self.old_shape = (self.shape = min(self.field, self.old_shape)));
+}
+float Df_antialias(float2 p) {
+    return (1.0 / length(float2(length(ddx(p)), length(ddy(p)))));
+}
+Df Df_viewport(float2 pos) {
+    Df df;
+    (df.pos = pos);
+    (df.result = mpsc_vec4_float(0.0));
+    (df.last_pos = mpsc_vec2_float(0.0));
+    (df.start_pos = mpsc_vec2_float(0.0));
+    (df.shape = 100000000000000000000.0);
+    (df.clip = -100000000000000000000.0);
+    (df.has_clip = 0.0);
+    (df.old_shape = 100000000000000000000.0);
+    (df.blur = 0.00001);
+    (df.aa = Df_antialias(pos));
+    (df.scale = 1.0);
+    (df.field = 0.0);
+    return df;
+}
+float4 mpsc_pixel(inout mpsc_Varyings mpsc_varyings) {
+    Df df = Df_viewport((mpsc_varyings.pos * mpsc_vec2_float(100.0)));
+    Df_clear(df, float4(0.1, 0.1, 0.1, 1.0));
+    Df_translate(df, 5.0, 5.0);
+    Df_rotate(df, 0.5, 50.0, 50.0);
+    Df_scale(df, 0.9, 50.0, 50.0);
+    Df_box(df, 10.0, 10.0, 80.0, 80.0, 4.0);
+    Df_circle(df, 50.0, 50.0, 20.0);
+    Df_subtract(df);
+    Df_fill_keep(df, float4(1.0, 0.5, 0.0, 1.0));
+    Df_stroke(df, mpsc_vec4_float(1.0), 1.5);
+    Df_hexagon(df, 50.0, 50.0, 10.0);
+    Df_gloop(df, 2.0);
+    Df_glow(df, float4(0.0, 0.5, 1.0, 1.0), 2.0);
+    Df_rect(df, 20.0, 20.0, 10.0, 10.0);
+    Df_circle(df, 25.0, 25.0, 4.0);
+    Df_union(df);
+    Df_blend(df, 0.5);
+    Df_intersect(df);
+    Df_fill(df, float4(0.0, 1.0, 0.0, 1.0));
+    Df_move_to(df, 10.0, 10.0);
+    Df_line_to(df, 90.0, 10.0);
+    Df_line_to(df, 50.0, 90.0);
+    Df_close_path(df);
+    return Df_fill(df, float4(1.0, 1.0, 0.0, 0.5));
+}
+mpsc_Varyings mpsc_vertex_main(mpsc_Geometries mpsc_geometries, mpsc_Instances mpsc_instances, uint inst_id: SV_InstanceID) {
+    mpsc_Varyings mpsc_varyings = 
+{float4(0.0,0.0,0.0,0.0), float2(0.0,0.0)};
+    mpsc_varyings.mpsc_position = mpsc_vertex(mpsc_geometries, mpsc_varyings);
+    return mpsc_varyings;
+}
+float4 mpsc_fragment_main(mpsc_Varyings mpsc_varyings) : SV_TARGET{
+    return mpsc_pixel(mpsc_varyings);
+}
@@ -0,0 +1,228 @@
+#include <metal_stdlib>
+using namespace metal;
+float4 sample2d(texture2d<float> tex, float2 pos){return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}
+float4 sample_cube(texturecube<float> tex, float3 dir){return tex.sample(sampler(mag_filter::linear,min_filter::linear),dir);}
+float4 sample3d(texture3d<float> tex, float3 pos){return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}
+struct Df {
+    float2 pos;
+    float4 result;
+    float2 last_pos;
+    float2 start_pos;
+    float shape;
+    float clip;
+    float has_clip;
+    float old_shape;
+    float blur;
+    float aa;
+    float scale;
+    float field;
+};
+struct mpsc_default_Uniforms{};
+struct mpsc_live_Uniforms {
+};
+struct mpsc_Textures {
+};
+struct mpsc_Geometries {
+    packed_float2 geom;
+};
+struct mpsc_Instances {
+};
+struct mpsc_Varyings {
+    float4 mpsc_position [[position]];
+    float2 pos;
+};
+constant float TORAD = 0.017453292;
+float4 mpsc_vertex(thread mpsc_Geometries &mpsc_geometries, thread mpsc_Varyings &mpsc_varyings) {
+    (mpsc_varyings.pos = mpsc_geometries.geom);
+    return float4(((mpsc_geometries.geom * 2.0) - 1.0), 0.0, 1.0);
+}
+void Df_blend(thread Df &self, float k) {
+    (self.old_shape = (self.shape = mix(self.old_shape, self.field, k)));
+}
+void Df_box(thread Df &self, float x, float y, float w, float h, float r) {
+    float2 p = (self.pos - float2(x, y));
+    float2 size = float2((0.5 * w), (0.5 * h));
+    float2 bp = max((abs((p - size.xy)) - (size.xy - float2((2.0 * r), (2.0 * r)).xy)), float2(0.0, 0.0));
+    (self.field = ((length(bp) - (2.0 * r)) / self.scale));
+    (self.old_shape = self.shape);
+    (self.shape = min(self.shape, self.field));
+}
+void Df_circle(thread Df &self, float x, float y, float r) {
+    float2 c = (self.pos - float2(x, y));
+    float len = sqrt(((c.x * c.x) + (c.y * c.y)));
+    (self.field = ((len - r) / self.scale));
+    (self.old_shape = self.shape);
+    (self.shape = min(self.shape, self.field));
+}
+void Df_clear(thread Df &self, float4 color) {
+    (self.result = float4(((color.rgb * color.a) + (self.result.rgb * (1.0 - color.a))), color.a));
+}
+void Df_line_to(thread Df &self, float x, float y) {
+    float2 p = float2(x, y);
+    float2 pa = (self.pos - self.last_pos);
+    float2 ba = (p - self.last_pos);
+    float h = clamp((dot(pa, ba) / dot(ba, ba)), 0.0, 1.0);
+    float s = sign(((pa.x * ba.y) - (pa.y * ba.x)));
+    (self.field = (length((pa - (ba * h))) / self.scale));
+    (self.old_shape = self.shape);
+    (self.shape = min(self.shape, self.field));
+    (self.clip = max(self.clip, (self.field * s)));
+    (self.has_clip = 1.0);
+    (self.last_pos = p);
+}
+void Df_close_path(thread Df &self) {
+    Df_line_to(self, self.start_pos.x, self.start_pos.y);
+}
+float Df_calc_blur(thread Df &self, float w) {
+    float wa = clamp((-w * self.aa), 0.0, 1.0);
+    float wb = 1.0;
+    if ((self.blur > 0.001)) {
+        (wb = clamp((-w / self.blur), 0.0, 1.0));
+    }
+    return (wa * wb);
+}
+float4 Df_fill_keep(thread Df &self, float4 color) {
+    float f = Df_calc_blur(self, self.shape);
+    float4 source = float4((color.rgb * color.a), color.a);
+    (self.result = ((source * f) + (self.result * (1.0 - (source.a * f)))));
+    if ((self.has_clip > 0.5)) {
+        float f2 = (1.0 - Df_calc_blur(self, -self.clip));
+        (self.result = ((source * f2) + (self.result * (1.0 - (source.a * f2)))));
+    }
+    return self.result;
+}
+float4 Df_fill(thread Df &self, float4 color) {
+    Df_fill_keep(self, color);
+    (self.old_shape = (self.shape = 100000000000000000000.0));
+    (self.clip = -100000000000000000000.0);
+    (self.has_clip = 0.0);
+    return self.result;
+}
+void Df_gloop(thread Df &self, float k) {
+    float h = clamp((0.5 + ((0.5 * (self.old_shape - self.field)) / k)), 0.0, 1.0);
+    (self.old_shape = (self.shape = (mix(self.old_shape, self.field, h) - ((k * h) * (1.0 - h)))));
+}
+float4 Df_glow_keep(thread Df &self, float4 color, float width) {
+    float f = Df_calc_blur(self, (abs(self.shape) - (width / self.scale)));
+    float4 source = float4((color.rgb * color.a), color.a);
+    float4 dest = self.result;
+    (self.result = (float4((source.rgb * f), 0.0) + dest));
+    return self.result;
+}
+float4 Df_glow(thread Df &self, float4 color, float width) {
+    Df_glow_keep(self, color, width);
+    (self.old_shape = (self.shape = 100000000000000000000.0));
+    (self.clip = -100000000000000000000.0);
+    (self.has_clip = 0.0);
+    return self.result;
+}
+void Df_hexagon(thread Df &self, float x, float y, float r) {
+    float dx = (abs((x - self.pos.x)) * 1.15);
+    float dy = abs((y - self.pos.y));
+    (self.field = max(((dy + (cos(1.0471976) * dx)) - r), (dx - r)));
+    (self.old_shape = self.shape);
+    (self.shape = min(self.shape, self.field));
+}
+void Df_intersect(thread Df &self) {
+    (self.old_shape = (self.shape = max(self.field, self.old_shape)));
+}
+void Df_move_to(thread Df &self, float x, float y) {
+    (self.last_pos = (self.start_pos = float2(x, y)));
+}
+void Df_rect(thread Df &self, float x, float y, float w, float h) {
+    float2 s = (float2(w, h) * 0.5);
+    float2 d = (abs(((float2(x, y) - self.pos) + s)) - s);
+    float2 dm = min(d, float2(0.0, 0.0));
+    (self.field = (max(dm.x, dm.y) + length(max(d, float2(0.0, 0.0)))));
+    (self.old_shape = self.shape);
+    (self.shape = min(self.shape, self.field));
+}
+void Df_rotate(thread Df &self, float a, float x, float y) {
+    float ca = cos(-a);
+    float sa = sin(-a);
+    float2 p = (self.pos - float2(x, y));
+    (self.pos = (float2(((p.x * ca) - (p.y * sa)), ((p.x * sa) + (p.y * ca))) + float2(x, y)));
+}
+void Df_scale(thread Df &self, float f, float x, float y) {
+    (self.scale *= f);
+    (self.pos = (((self.pos - float2(x, y)) * f) + float2(x, y)));
+}
+float4 Df_stroke_keep(thread Df &self, float4 color, float width) {
+    float f = Df_calc_blur(self, (abs(self.shape) - (width / self.scale)));
+    float4 source = float4((color.rgb * color.a), color.a);
+    float4 dest = self.result;
+    (self.result = ((source * f) + (dest * (1.0 - (source.a * f)))));
+    return self.result;
+}
+float4 Df_stroke(thread Df &self, float4 color, float width) {
+    Df_stroke_keep(self, color, width);
+    (self.old_shape = (self.shape = 100000000000000000000.0));
+    (self.clip = -100000000000000000000.0);
+    (self.has_clip = 0.0);
+    return self.result;
+}
+void Df_subtract(thread Df &self) {
+    (self.old_shape = (self.shape = max(-self.field, self.old_shape)));
+}
+float2 Df_translate(thread Df &self, float x, float y) {
+    (self.pos -= float2(x, y));
+    return self.pos;
+}
+void Df_union(thread Df &self) {
+    (self.old_shape = (self.shape = min(self.field, self.old_shape)));
+}
+float Df_antialias(float2 p) {
+    return (1.0 / length(float2(length(dfdx(p)), length(dfdy(p)))));
+}
+Df Df_viewport(float2 pos) {
+    Df df;
+    (df.pos = pos);
+    (df.result = float4(0.0));
+    (df.last_pos = float2(0.0));
+    (df.start_pos = float2(0.0));
+    (df.shape = 100000000000000000000.0);
+    (df.clip = -100000000000000000000.0);
+    (df.has_clip = 0.0);
+    (df.old_shape = 100000000000000000000.0);
+    (df.blur = 0.00001);
+    (df.aa = Df_antialias(pos));
+    (df.scale = 1.0);
+    (df.field = 0.0);
+    return df;
+}
+float4 mpsc_pixel(thread mpsc_Varyings &mpsc_varyings) {
+    Df df = Df_viewport((mpsc_varyings.pos * float2(100.0)));
+    Df_clear(df, float4(0.1, 0.1, 0.1, 1.0));
+    Df_translate(df, 5.0, 5.0);
+    Df_rotate(df, 0.5, 50.0, 50.0);
+    Df_scale(df, 0.9, 50.0, 50.0);
+    Df_box(df, 10.0, 10.0, 80.0, 80.0, 4.0);
+    Df_circle(df, 50.0, 50.0, 20.0);
+    Df_subtract(df);
+    Df_fill_keep(df, float4(1.0, 0.5, 0.0, 1.0));
+    Df_stroke(df, float4(1.0), 1.5);
+    Df_hexagon(df, 50.0, 50.0, 10.0);
+    Df_gloop(df, 2.0);
+    Df_glow(df, float4(0.0, 0.5, 1.0, 1.0), 2.0);
+    Df_rect(df, 20.0, 20.0, 10.0, 10.0);
+    Df_circle(df, 25.0, 25.0, 4.0);
+    Df_union(df);
+    Df_blend(df, 0.5);
+    Df_intersect(df);
+    Df_fill(df, float4(0.0, 1.0, 0.0, 1.0));
+    Df_move_to(df, 10.0, 10.0);
+    Df_line_to(df, 90.0, 10.0);
+    Df_line_to(df, 50.0, 90.0);
+    Df_close_path(df);
+    return Df_fill(df, float4(1.0, 1.0, 0.0, 0.5));
+}
+vertex mpsc_Varyings mpsc_vertex_main(mpsc_Textures mpsc_textures, const device mpsc_Geometries *in_geometries [[buffer(0)]], const device mpsc_Instances *in_instances [[buffer(1)]], constant mpsc_pass_Uniforms &mpsc_pass_uniforms [[buffer(2)]], constant mpsc_view_Uniforms &mpsc_view_uniforms [[buffer(3)]], constant mpsc_draw_Uniforms &mpsc_draw_uniforms [[buffer(4)]], constant mpsc_default_Uniforms &mpsc_default_uniforms [[buffer(5)]], constant mpsc_live_Uniforms &mpsc_live_uniforms [[buffer(6)]], uint vtx_id [[vertex_id]], uint inst_id [[instance_id]]) {
+    mpsc_Geometries mpsc_geometries = in_geometries[vtx_id];
+    mpsc_Instances mpsc_instances = in_instances[inst_id];
+    mpsc_Varyings mpsc_varyings;
+    mpsc_varyings.mpsc_position = mpsc_vertex(mpsc_geometries, mpsc_varyings);
+    return mpsc_varyings;
+}
+fragment float4 mpsc_fragment_main(mpsc_Varyings mpsc_varyings[[stage_in]], constant mpsc_pass_Uniforms &mpsc_pass_uniforms [[buffer(0)]], constant mpsc_view_Uniforms &mpsc_view_uniforms [[buffer(1)]], constant mpsc_draw_Uniforms &mpsc_draw_uniforms [[buffer(2)]], constant mpsc_default_Uniforms &mpsc_default_uniforms [[buffer(3)]], constant mpsc_live_Uniforms &mpsc_live_uniforms [[buffer(4)]], mpsc_Textures mpsc_textures) {
+    return mpsc_pixel(mpsc_varyings);
+}
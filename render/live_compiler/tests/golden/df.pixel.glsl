@@ -0,0 +1,201 @@
+struct Df {
+    vec2 pos;
+    vec4 result;
+    vec2 last_pos;
+    vec2 start_pos;
+    float shape;
+    float clip;
+    float has_clip;
+    float old_shape;
+    float blur;
+    float aa;
+    float scale;
+    float field;
+};
+const float TORAD = 0.017453292;
+varying vec2 mpsc_packed_varying_0;
+vec2 pos=vec2(0.0);
+void Df_blend(inout Df self, float k) {
+    (self.old_shape = (self.shape = mix(self.old_shape, self.field, k)));
+}
+void Df_box(inout Df self, float x, float y, float w, float h, float r) {
+    vec2 p = (self.pos - vec2(x, y));
+    vec2 size = vec2((0.5 * w), (0.5 * h));
+    vec2 bp = max((abs((p - size.xy)) - (size.xy - vec2((2.0 * r), (2.0 * r)).xy)), vec2(0.0, 0.0));
+    (self.field = ((length(bp) - (2.0 * r)) / self.scale));
+    (self.old_shape = self.shape);
+    (self.shape = min(self.shape, self.field));
+}
+void Df_circle(inout Df self, float x, float y, float r) {
+    vec2 c = (self.pos - vec2(x, y));
+    float len = sqrt(((c.x * c.x) + (c.y * c.y)));
+    (self.field = ((len - r) / self.scale));
+    (self.old_shape = self.shape);
+    (self.shape = min(self.shape, self.field));
+}
+void Df_clear(inout Df self, vec4 color) {
+    (self.result = vec4(((color.rgb * color.a) + (self.result.rgb * (1.0 - color.a))), color.a));
+}
+void Df_line_to(inout Df self, float x, float y) {
+    vec2 p = vec2(x, y);
+    vec2 pa = (self.pos - self.last_pos);
+    vec2 ba = (p - self.last_pos);
+    float h = clamp((dot(pa, ba) / dot(ba, ba)), 0.0, 1.0);
+    float s = sign(((pa.x * ba.y) - (pa.y * ba.x)));
+    (self.field = (length((pa - (ba * h))) / self.scale));
+    (self.old_shape = self.shape);
+    (self.shape = min(self.shape, self.field));
+    (self.clip = max(self.clip, (self.field * s)));
+    (self.has_clip = 1.0);
+    (self.last_pos = p);
+}
+void Df_close_path(inout Df self) {
+    Df_line_to(self, self.start_pos.x, self.start_pos.y);
+}
+float Df_calc_blur(inout Df self, float w) {
+    float wa = clamp((-w * self.aa), 0.0, 1.0);
+    float wb = 1.0;
+    if ((self.blur > 0.001)) {
+        (wb = clamp((-w / self.blur), 0.0, 1.0));
+    }
+    return (wa * wb);
+}
+vec4 Df_fill_keep(inout Df self, vec4 color) {
+    float f = Df_calc_blur(self, self.shape);
+    vec4 source = vec4((color.rgb * color.a), color.a);
+    (self.result = ((source * f) + (self.result * (1.0 - (source.a * f)))));
+    if ((self.has_clip > 0.5)) {
+        float f2 = (1.0 - Df_calc_blur(self, -self.clip));
+        (self.result = ((source * f2) + (self.result * (1.0 - (source.a * f2)))));
+    }
+    return self.result;
+}
+vec4 Df_fill(inout Df self, vec4 color) {
+    Df_fill_keep(self, color);
+    (self.old_shape = (self.shape = 100000000000000000000.0));
+    (self.clip = -100000000000000000000.0);
+    (self.has_clip = 0.0);
+    return self.result;
+}
+void Df_gloop(inout Df self, float k) {
+    float h = clamp((0.5 + ((0.5 * (self.old_shape - self.field)) / k)), 0.0, 1.0);
+    (self.old_shape = (self.shape = (mix(self.old_shape, self.field, h) - ((k * h) * (1.0 - h)))));
+}
+vec4 Df_glow_keep(inout Df self, vec4 color, float width) {
+    float f = Df_calc_blur(self, (abs(self.shape) - (width / self.scale)));
+    vec4 source = vec4((color.rgb * color.a), color.a);
+    vec4 dest = self.result;
+    (self.result = (vec4((source.rgb * f), 0.0) + dest));
+    return self.result;
+}
+vec4 Df_glow(inout Df self, vec4 color, float width) {
+    Df_glow_keep(self, color, width);
+    (self.old_shape = (self.shape = 100000000000000000000.0));
+    (self.clip = -100000000000000000000.0);
+    (self.has_clip = 0.0);
+    return self.result;
+}
+void Df_hexagon(inout Df self, float x, float y, float r) {
+    float dx = (abs((x - self.pos.x)) * 1.15);
+    float dy = abs((y - self.pos.y));
+    (self.field = max(((dy + (cos(1.0471976) * dx)) - r), (dx - r)));
+    (self.old_shape = self.shape);
+    (self.shape = min(self.shape, self.field));
+}
+void Df_intersect(inout Df self) {
+    (self.old_shape = (self.shape = max(self.field, self.old_shape)));
+}
+void Df_move_to(inout Df self, float x, float y) {
+    (self.last_pos = (self.start_pos = vec2(x, y)));
+}
+void Df_rect(inout Df self, float x, float y, float w, float h) {
+    vec2 s = (vec2(w, h) * 0.5);
+    vec2 d = (abs(((vec2(x, y) - self.pos) + s)) - s);
+    vec2 dm = min(d, vec2(0.0, 0.0));
+    (self.field = (max(dm.x, dm.y) + length(max(d, vec2(0.0, 0.0)))));
+    (self.old_shape = self.shape);
+    (self.shape = min(self.shape, self.field));
+}
+void Df_rotate(inout Df self, float a, float x, float y) {
+    float ca = cos(-a);
+    float sa = sin(-a);
+    vec2 p = (self.pos - vec2(x, y));
+    (self.pos = (vec2(((p.x * ca) - (p.y * sa)), ((p.x * sa) + (p.y * ca))) + vec2(x, y)));
+}
+void Df_scale(inout Df self, float f, float x, float y) {
+    (self.scale *= f);
+    (self.pos = (((self.pos - vec2(x, y)) * f) + vec2(x, y)));
+}
+vec4 Df_stroke_keep(inout Df self, vec4 color, float width) {
+    float f = Df_calc_blur(self, (abs(self.shape) - (width / self.scale)));
+    vec4 source = vec4((color.rgb * color.a), color.a);
+    vec4 dest = self.result;
+    (self.result = ((source * f) + (dest * (1.0 - (source.a * f)))));
+    return self.result;
+}
+vec4 Df_stroke(inout Df self, vec4 color, float width) {
+    Df_stroke_keep(self, color, width);
+    (self.old_shape = (self.shape = 100000000000000000000.0));
+    (self.clip = -100000000000000000000.0);
+    (self.has_clip = 0.0);
+    return self.result;
+}
+void Df_subtract(inout Df self) {
+    (self.old_shape = (self.shape = max(-self.field, self.old_shape)));
+}
+vec2 Df_translate(inout Df self, float x, float y) {
+    (self.pos -= vec2(x, y));
+    return self.pos;
+}
+void Df_union(inout Df self) {
+    (self.old_shape = (self.shape = min(self.field, self.old_shape)));
+}
+float Df_antialias(vec2 p) {
+    return (1.0 / length(vec2(length(dFdx(p)), length(dFdy(p)))));
+}
+Df Df_viewport(vec2 pos) {
+    Df df;
+    (df.pos = pos);
+    (df.result = vec4(0.0));
+    (df.last_pos = vec2(0.0));
+    (df.start_pos = vec2(0.0));
+    (df.shape = 100000000000000000000.0);
+    (df.clip = -100000000000000000000.0);
+    (df.has_clip = 0.0);
+    (df.old_shape = 100000000000000000000.0);
+    (df.blur = 0.00001);
+    (df.aa = Df_antialias(pos));
+    (df.scale = 1.0);
+    (df.field = 0.0);
+    return df;
+}
+vec4 pixel() {
+    Df df = Df_viewport((pos * vec2(100.0)));
+    Df_clear(df, vec4(0.1, 0.1, 0.1, 1.0));
+    Df_translate(df, 5.0, 5.0);
+    Df_rotate(df, 0.5, 50.0, 50.0);
+    Df_scale(df, 0.9, 50.0, 50.0);
+    Df_box(df, 10.0, 10.0, 80.0, 80.0, 4.0);
+    Df_circle(df, 50.0, 50.0, 20.0);
+    Df_subtract(df);
+    Df_fill_keep(df, vec4(1.0, 0.5, 0.0, 1.0));
+    Df_stroke(df, vec4(1.0), 1.5);
+    Df_hexagon(df, 50.0, 50.0, 10.0);
+    Df_gloop(df, 2.0);
+    Df_glow(df, vec4(0.0, 0.5, 1.0, 1.0), 2.0);
+    Df_rect(df, 20.0, 20.0, 10.0, 10.0);
+    Df_circle(df, 25.0, 25.0, 4.0);
+    Df_union(df);
+    Df_blend(df, 0.5);
+    Df_intersect(df);
+    Df_fill(df, vec4(0.0, 1.0, 0.0, 1.0));
+    Df_move_to(df, 10.0, 10.0);
+    Df_line_to(df, 90.0, 10.0);
+    Df_line_to(df, 50.0, 90.0);
+    Df_close_path(df);
+    return Df_fill(df, vec4(1.0, 1.0, 0.0, 0.5));
+}
+void main() {
+    pos.xy = mpsc_packed_varying_0.xy;
+    gl_FragColor = pixel();
+}
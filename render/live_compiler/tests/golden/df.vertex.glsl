@@ -0,0 +1,28 @@
+struct Df {
+    vec2 pos;
+    vec4 result;
+    vec2 last_pos;
+    vec2 start_pos;
+    float shape;
+    float clip;
+    float has_clip;
+    float old_shape;
+    float blur;
+    float aa;
+    float scale;
+    float field;
+};
+const float TORAD = 0.017453292;
+attribute vec2 mpsc_packed_geometry_0;
+varying vec2 mpsc_packed_varying_0;
+vec2 geom=vec2(0.0);
+vec2 pos=vec2(0.0);
+vec4 vertex() {
+    (pos = geom);
+    return vec4(((geom * 2.0) - 1.0), 0.0, 1.0);
+}
+void main() {
+    geom.xy = mpsc_packed_geometry_0.xy;
+    gl_Position = vertex();
+    mpsc_packed_varying_0.xy = pos.xy;
+}
@@ -0,0 +1,238 @@
+@group(1) @binding(0) var mpsc_default_sampler: sampler;
+fn sample2d(tex: texture_2d<f32>, pos: vec2<f32>) -> vec4<f32> {return textureSampleLevel(tex, mpsc_default_sampler, pos, 0.0);}
+fn sample_cube(tex: texture_cube<f32>, dir: vec3<f32>) -> vec4<f32> {return textureSampleLevel(tex, mpsc_default_sampler, dir, 0.0);}
+fn sample3d(tex: texture_3d<f32>, pos: vec3<f32>) -> vec4<f32> {return textureSampleLevel(tex, mpsc_default_sampler, pos, 0.0);}
+fn mpsc_mod_float(a: f32, b: f32) -> f32 {return a - b * floor(a / b);}
+fn mpsc_mod_vec2(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {return a - b * floor(a / b);}
+fn mpsc_mod_vec3(a: vec3<f32>, b: vec3<f32>) -> vec3<f32> {return a - b * floor(a / b);}
+fn mpsc_mod_vec4(a: vec4<f32>, b: vec4<f32>) -> vec4<f32> {return a - b * floor(a / b);}
+struct Df {
+    pos: vec2<f32>,
+    result: vec4<f32>,
+    last_pos: vec2<f32>,
+    start_pos: vec2<f32>,
+    shape: f32,
+    clip: f32,
+    has_clip: f32,
+    old_shape: f32,
+    blur: f32,
+    aa: f32,
+    scale: f32,
+    field: f32,
+}
+var<private> geom: vec2<f32>;
+var<private> pos: vec2<f32>;
+struct mpsc_Varyings {
+    @builtin(position) mpsc_position: vec4<f32>,
+    @location(0) mpsc_packed_varying_0: vec2<f32>,
+}
+const TORAD: f32 = 0.017453292;
+fn vertex() -> vec4<f32> {
+    pos = geom;
+    return vec4<f32>(((geom * 2.0) - 1.0), 0.0, 1.0);
+}
+fn Df_blend(mpsc_self: ptr<function, Df>, k: f32) {
+    (*mpsc_self).shape = mix((*mpsc_self).old_shape, (*mpsc_self).field, k);
+    (*mpsc_self).old_shape = (*mpsc_self).shape;
+}
+fn Df_box(mpsc_self: ptr<function, Df>, x: f32, y: f32, w: f32, h: f32, r: f32) {
+    var p: vec2<f32> = ((*mpsc_self).pos - vec2<f32>(x, y));
+    var size: vec2<f32> = vec2<f32>((0.5 * w), (0.5 * h));
+    var bp: vec2<f32> = max((abs((p - size.xy)) - (size.xy - vec2<f32>((2.0 * r), (2.0 * r)).xy)), vec2<f32>(0.0, 0.0));
+    (*mpsc_self).field = ((length(bp) - (2.0 * r)) / (*mpsc_self).scale);
+    (*mpsc_self).old_shape = (*mpsc_self).shape;
+    (*mpsc_self).shape = min((*mpsc_self).shape, (*mpsc_self).field);
+}
+fn Df_circle(mpsc_self: ptr<function, Df>, x: f32, y: f32, r: f32) {
+    var c: vec2<f32> = ((*mpsc_self).pos - vec2<f32>(x, y));
+    var len: f32 = sqrt(((c.x * c.x) + (c.y * c.y)));
+    (*mpsc_self).field = ((len - r) / (*mpsc_self).scale);
+    (*mpsc_self).old_shape = (*mpsc_self).shape;
+    (*mpsc_self).shape = min((*mpsc_self).shape, (*mpsc_self).field);
+}
+fn Df_clear(mpsc_self: ptr<function, Df>, color: vec4<f32>) {
+    (*mpsc_self).result = vec4<f32>(((color.rgb * color.a) + ((*mpsc_self).result.rgb * (1.0 - color.a))), color.a);
+}
+fn Df_line_to(mpsc_self: ptr<function, Df>, x: f32, y: f32) {
+    var p: vec2<f32> = vec2<f32>(x, y);
+    var pa: vec2<f32> = ((*mpsc_self).pos - (*mpsc_self).last_pos);
+    var ba: vec2<f32> = (p - (*mpsc_self).last_pos);
+    var h: f32 = clamp((dot(pa, ba) / dot(ba, ba)), 0.0, 1.0);
+    var s: f32 = sign(((pa.x * ba.y) - (pa.y * ba.x)));
+    (*mpsc_self).field = (length((pa - (ba * h))) / (*mpsc_self).scale);
+    (*mpsc_self).old_shape = (*mpsc_self).shape;
+    (*mpsc_self).shape = min((*mpsc_self).shape, (*mpsc_self).field);
+    (*mpsc_self).clip = max((*mpsc_self).clip, ((*mpsc_self).field * s));
+    (*mpsc_self).has_clip = 1.0;
+    (*mpsc_self).last_pos = p;
+}
+fn Df_close_path(mpsc_self: ptr<function, Df>) {
+    Df_line_to(&(*mpsc_self), (*mpsc_self).start_pos.x, (*mpsc_self).start_pos.y);
+}
+fn Df_calc_blur(mpsc_self: ptr<function, Df>, w: f32) -> f32 {
+    var wa: f32 = clamp((-w * (*mpsc_self).aa), 0.0, 1.0);
+    var wb: f32 = 1.0;
+    if (((*mpsc_self).blur > 0.001)) {
+        wb = clamp((-w / (*mpsc_self).blur), 0.0, 1.0);
+    }
+    return (wa * wb);
+}
+fn Df_fill_keep(mpsc_self: ptr<function, Df>, color: vec4<f32>) -> vec4<f32> {
+    var f: f32 = Df_calc_blur(&(*mpsc_self), (*mpsc_self).shape);
+    var source: vec4<f32> = vec4<f32>((color.rgb * color.a), color.a);
+    (*mpsc_self).result = ((source * f) + ((*mpsc_self).result * (1.0 - (source.a * f))));
+    if (((*mpsc_self).has_clip > 0.5)) {
+        var f2: f32 = (1.0 - Df_calc_blur(&(*mpsc_self), -(*mpsc_self).clip));
+        (*mpsc_self).result = ((source * f2) + ((*mpsc_self).result * (1.0 - (source.a * f2))));
+    }
+    return (*mpsc_self).result;
+}
+fn Df_fill(mpsc_self: ptr<function, Df>, color: vec4<f32>) -> vec4<f32> {
+    Df_fill_keep(&(*mpsc_self), color);
+    (*mpsc_self).shape = 100000000000000000000.0;
+    (*mpsc_self).old_shape = (*mpsc_self).shape;
+    (*mpsc_self).clip = -100000000000000000000.0;
+    (*mpsc_self).has_clip = 0.0;
+    return (*mpsc_self).result;
+}
+fn Df_gloop(mpsc_self: ptr<function, Df>, k: f32) {
+    var h: f32 = clamp((0.5 + ((0.5 * ((*mpsc_self).old_shape - (*mpsc_self).field)) / k)), 0.0, 1.0);
+    (*mpsc_self).shape = (mix((*mpsc_self).old_shape, (*mpsc_self).field, h) - ((k * h) * (1.0 - h)));
+    (*mpsc_self).old_shape = (*mpsc_self).shape;
+}
+fn Df_glow_keep(mpsc_self: ptr<function, Df>, color: vec4<f32>, width: f32) -> vec4<f32> {
+    var f: f32 = Df_calc_blur(&(*mpsc_self), (abs((*mpsc_self).shape) - (width / (*mpsc_self).scale)));
+    var source: vec4<f32> = vec4<f32>((color.rgb * color.a), color.a);
+    var dest: vec4<f32> = (*mpsc_self).result;
+    (*mpsc_self).result = (vec4<f32>((source.rgb * f), 0.0) + dest);
+    return (*mpsc_self).result;
+}
+fn Df_glow(mpsc_self: ptr<function, Df>, color: vec4<f32>, width: f32) -> vec4<f32> {
+    Df_glow_keep(&(*mpsc_self), color, width);
+    (*mpsc_self).shape = 100000000000000000000.0;
+    (*mpsc_self).old_shape = (*mpsc_self).shape;
+    (*mpsc_self).clip = -100000000000000000000.0;
+    (*mpsc_self).has_clip = 0.0;
+    return (*mpsc_self).result;
+}
+fn Df_hexagon(mpsc_self: ptr<function, Df>, x: f32, y: f32, r: f32) {
+    var dx: f32 = (abs((x - (*mpsc_self).pos.x)) * 1.15);
+    var dy: f32 = abs((y - (*mpsc_self).pos.y));
+    (*mpsc_self).field = max(((dy + (cos(1.0471976) * dx)) - r), (dx - r));
+    (*mpsc_self).old_shape = (*mpsc_self).shape;
+    (*mpsc_self).shape = min((*mpsc_self).shape, (*mpsc_self).field);
+}
+fn Df_intersect(mpsc_self: ptr<function, Df>) {
+    (*mpsc_self).shape = max((*mpsc_self).field, (*mpsc_self).old_shape);
+    (*mpsc_self).old_shape = (*mpsc_self).shape;
+}
+fn Df_move_to(mpsc_self: ptr<function, Df>, x: f32, y: f32) {
+    (*mpsc_self).start_pos = vec2<f32>(x, y);
+    (*mpsc_self).last_pos = (*mpsc_self).start_pos;
+}
+fn Df_rect(mpsc_self: ptr<function, Df>, x: f32, y: f32, w: f32, h: f32) {
+    var s: vec2<f32> = (vec2<f32>(w, h) * 0.5);
+    var d: vec2<f32> = (abs(((vec2<f32>(x, y) - (*mpsc_self).pos) + s)) - s);
+    var dm: vec2<f32> = min(d, vec2<f32>(0.0, 0.0));
+    (*mpsc_self).field = (max(dm.x, dm.y) + length(max(d, vec2<f32>(0.0, 0.0))));
+    (*mpsc_self).old_shape = (*mpsc_self).shape;
+    (*mpsc_self).shape = min((*mpsc_self).shape, (*mpsc_self).field);
+}
+fn Df_rotate(mpsc_self: ptr<function, Df>, a: f32, x: f32, y: f32) {
+    var ca: f32 = cos(-a);
+    var sa: f32 = sin(-a);
+    var p: vec2<f32> = ((*mpsc_self).pos - vec2<f32>(x, y));
+    (*mpsc_self).pos = (vec2<f32>(((p.x * ca) - (p.y * sa)), ((p.x * sa) + (p.y * ca))) + vec2<f32>(x, y));
+}
+fn Df_scale(mpsc_self: ptr<function, Df>, f: f32, x: f32, y: f32) {
+    (*mpsc_self).scale *= f;
+    (*mpsc_self).pos = ((((*mpsc_self).pos - vec2<f32>(x, y)) * f) + vec2<f32>(x, y));
+}
+fn Df_stroke_keep(mpsc_self: ptr<function, Df>, color: vec4<f32>, width: f32) -> vec4<f32> {
+    var f: f32 = Df_calc_blur(&(*mpsc_self), (abs((*mpsc_self).shape) - (width / (*mpsc_self).scale)));
+    var source: vec4<f32> = vec4<f32>((color.rgb * color.a), color.a);
+    var dest: vec4<f32> = (*mpsc_self).result;
+    (*mpsc_self).result = ((source * f) + (dest * (1.0 - (source.a * f))));
+    return (*mpsc_self).result;
+}
+fn Df_stroke(mpsc_self: ptr<function, Df>, color: vec4<f32>, width: f32) -> vec4<f32> {
+    Df_stroke_keep(&(*mpsc_self), color, width);
+    (*mpsc_self).shape = 100000000000000000000.0;
+    (*mpsc_self).old_shape = (*mpsc_self).shape;
+    (*mpsc_self).clip = -100000000000000000000.0;
+    (*mpsc_self).has_clip = 0.0;
+    return (*mpsc_self).result;
+}
+fn Df_subtract(mpsc_self: ptr<function, Df>) {
+    (*mpsc_self).shape = max(-(*mpsc_self).field, (*mpsc_self).old_shape);
+    (*mpsc_self).old_shape = (*mpsc_self).shape;
+}
+fn Df_translate(mpsc_self: ptr<function, Df>, x: f32, y: f32) -> vec2<f32> {
+    (*mpsc_self).pos -= vec2<f32>(x, y);
+    return (*mpsc_self).pos;
+}
+fn Df_union(mpsc_self: ptr<function, Df>) {
+    (*mpsc_self).shape = min((*mpsc_self).field, (*mpsc_self).old_shape);
+    (*mpsc_self).old_shape = (*mpsc_self).shape;
+}
+fn Df_antialias(p: vec2<f32>) -> f32 {
+    return (1.0 / length(vec2<f32>(length(dpdx(p)), length(dpdy(p)))));
+}
+fn Df_viewport(pos: vec2<f32>) -> Df {
+    var df: Df;
+    df.pos = pos;
+    df.result = vec4<f32>(0.0);
+    df.last_pos = vec2<f32>(0.0);
+    df.start_pos = vec2<f32>(0.0);
+    df.shape = 100000000000000000000.0;
+    df.clip = -100000000000000000000.0;
+    df.has_clip = 0.0;
+    df.old_shape = 100000000000000000000.0;
+    df.blur = 0.00001;
+    df.aa = Df_antialias(pos);
+    df.scale = 1.0;
+    df.field = 0.0;
+    return df;
+}
+fn pixel() -> vec4<f32> {
+    var df: Df = Df_viewport((pos * vec2<f32>(100.0)));
+    Df_clear(&df, vec4<f32>(0.1, 0.1, 0.1, 1.0));
+    Df_translate(&df, 5.0, 5.0);
+    Df_rotate(&df, 0.5, 50.0, 50.0);
+    Df_scale(&df, 0.9, 50.0, 50.0);
+    Df_box(&df, 10.0, 10.0, 80.0, 80.0, 4.0);
+    Df_circle(&df, 50.0, 50.0, 20.0);
+    Df_subtract(&df);
+    Df_fill_keep(&df, vec4<f32>(1.0, 0.5, 0.0, 1.0));
+    Df_stroke(&df, vec4<f32>(1.0), 1.5);
+    Df_hexagon(&df, 50.0, 50.0, 10.0);
+    Df_gloop(&df, 2.0);
+    Df_glow(&df, vec4<f32>(0.0, 0.5, 1.0, 1.0), 2.0);
+    Df_rect(&df, 20.0, 20.0, 10.0, 10.0);
+    Df_circle(&df, 25.0, 25.0, 4.0);
+    Df_union(&df);
+    Df_blend(&df, 0.5);
+    Df_intersect(&df);
+    Df_fill(&df, vec4<f32>(0.0, 1.0, 0.0, 1.0));
+    Df_move_to(&df, 10.0, 10.0);
+    Df_line_to(&df, 90.0, 10.0);
+    Df_line_to(&df, 50.0, 90.0);
+    Df_close_path(&df);
+    return Df_fill(&df, vec4<f32>(1.0, 1.0, 0.0, 0.5));
+}
+@vertex
+fn mpsc_vertex_main(@location(0) mpsc_packed_geometry_0: vec2<f32>) -> mpsc_Varyings {
+    geom.x = mpsc_packed_geometry_0.x;
+    geom.y = mpsc_packed_geometry_0.y;
+    var mpsc_varyings: mpsc_Varyings;
+    mpsc_varyings.mpsc_position = vertex();
+    mpsc_varyings.mpsc_packed_varying_0.x = pos.x;
+    mpsc_varyings.mpsc_packed_varying_0.y = pos.y;
+    return mpsc_varyings;
+}
+@fragment
+fn mpsc_fragment_main(mpsc_varyings: mpsc_Varyings) -> @location(0) vec4<f32> {
+    pos.x = mpsc_varyings.mpsc_packed_varying_0.x;
+    pos.y = mpsc_varyings.mpsc_packed_varying_0.y;
+    return pixel();
+}
@@ -0,0 +1,45 @@
+SamplerState mpsc_default_texture_sampler{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;AddressW=Wrap;};
+float4 sample2d(Texture2D tex, float2 pos){return tex.Sample(mpsc_default_texture_sampler,pos);}
+float4 sample_cube(TextureCube tex, float3 dir){return tex.Sample(mpsc_default_texture_sampler,dir);}
+float4 sample3d(Texture3D tex, float3 pos){return tex.Sample(mpsc_default_texture_sampler,pos);}
+cbuffer mpsc_default_Uniforms : register(b3){};
+cbuffer mpsc_live_Uniforms : register(b4) {
+    float mpsc_live_golden_scale;
+    float4 mpsc_live_golden_tint;
+}
+struct mpsc_Geometries {
+    float2 geom: GEOMA;
+};
+struct mpsc_Instances {
+    float4 rect: INSTA;
+};
+struct mpsc_Varyings {
+    float4 mpsc_position: SV_POSITION;
+    float2 pos: VARYA;
+};
+float4 mpsc_vec4_vec2_float_float(float2 x0, float x1, float x2) {
+    return float4(x0[0], x0[1], x1, x2);
+}
+float4 mpsc_vec4_vec3_float(float3 x0, float x1) {
+    return float4(x0[0], x0[1], x0[2], x1);
+}
+float4 mpsc_vertex(in mpsc_Geometries mpsc_geometries, in mpsc_Instances mpsc_instances, inout mpsc_Varyings mpsc_varyings) {
+    (mpsc_varyings.pos = (mpsc_geometries.geom * mpsc_live_golden_scale));
+    return mpsc_vec4_vec2_float_float((mpsc_instances.rect.xy + (mpsc_varyings.pos * mpsc_instances.rect.zw)), 0.0, 1.0);
+}
+float4 mpsc_pixel(inout mpsc_Varyings mpsc_varyings) {
+    float4 c = mpsc_live_golden_tint;
+    if ((mpsc_varyings.pos.x > 0.5)) {
+        return mpsc_vec4_vec3_float((c.rgb * 0.5), c.a);
+    }
+    return c;
+}
+mpsc_Varyings mpsc_vertex_main(mpsc_Geometries mpsc_geometries, mpsc_Instances mpsc_instances, uint inst_id: SV_InstanceID) {
+    mpsc_Varyings mpsc_varyings = 
+{float4(0.0,0.0,0.0,0.0), float2(0.0,0.0)};
+    mpsc_varyings.mpsc_position = mpsc_vertex(mpsc_geometries, mpsc_instances, mpsc_varyings);
+    return mpsc_varyings;
+}
+float4 mpsc_fragment_main(mpsc_Varyings mpsc_varyings) : SV_TARGET{
+    return mpsc_pixel(mpsc_varyings);
+}
@@ -0,0 +1,43 @@
+#include <metal_stdlib>
+using namespace metal;
+float4 sample2d(texture2d<float> tex, float2 pos){return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}
+float4 sample_cube(texturecube<float> tex, float3 dir){return tex.sample(sampler(mag_filter::linear,min_filter::linear),dir);}
+float4 sample3d(texture3d<float> tex, float3 pos){return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}
+struct mpsc_default_Uniforms{};
+struct mpsc_live_Uniforms {
+    float golden_scale;
+    float4 golden_tint;
+};
+struct mpsc_Textures {
+};
+struct mpsc_Geometries {
+    packed_float2 geom;
+};
+struct mpsc_Instances {
+    packed_float4 rect;
+};
+struct mpsc_Varyings {
+    float4 mpsc_position [[position]];
+    float2 pos;
+};
+float4 mpsc_vertex(constant mpsc_live_Uniforms &mpsc_live_uniforms, thread mpsc_Geometries &mpsc_geometries, thread mpsc_Instances &mpsc_instances, thread mpsc_Varyings &mpsc_varyings) {
+    (mpsc_varyings.pos = (mpsc_geometries.geom * mpsc_live_uniforms.golden_scale));
+    return float4((mpsc_instances.rect.xy + (mpsc_varyings.pos * mpsc_instances.rect.zw)), 0.0, 1.0);
+}
+float4 mpsc_pixel(constant mpsc_live_Uniforms &mpsc_live_uniforms, thread mpsc_Varyings &mpsc_varyings) {
+    float4 c = mpsc_live_uniforms.golden_tint;
+    if ((mpsc_varyings.pos.x > 0.5)) {
+        return float4((c.rgb * 0.5), c.a);
+    }
+    return c;
+}
+vertex mpsc_Varyings mpsc_vertex_main(mpsc_Textures mpsc_textures, const device mpsc_Geometries *in_geometries [[buffer(0)]], const device mpsc_Instances *in_instances [[buffer(1)]], constant mpsc_pass_Uniforms &mpsc_pass_uniforms [[buffer(2)]], constant mpsc_view_Uniforms &mpsc_view_uniforms [[buffer(3)]], constant mpsc_draw_Uniforms &mpsc_draw_uniforms [[buffer(4)]], constant mpsc_default_Uniforms &mpsc_default_uniforms [[buffer(5)]], constant mpsc_live_Uniforms &mpsc_live_uniforms [[buffer(6)]], uint vtx_id [[vertex_id]], uint inst_id [[instance_id]]) {
+    mpsc_Geometries mpsc_geometries = in_geometries[vtx_id];
+    mpsc_Instances mpsc_instances = in_instances[inst_id];
+    mpsc_Varyings mpsc_varyings;
+    mpsc_varyings.mpsc_position = mpsc_vertex(mpsc_live_uniforms, mpsc_geometries, mpsc_instances, mpsc_varyings);
+    return mpsc_varyings;
+}
+fragment float4 mpsc_fragment_main(mpsc_Varyings mpsc_varyings[[stage_in]], constant mpsc_pass_Uniforms &mpsc_pass_uniforms [[buffer(0)]], constant mpsc_view_Uniforms &mpsc_view_uniforms [[buffer(1)]], constant mpsc_draw_Uniforms &mpsc_draw_uniforms [[buffer(2)]], constant mpsc_default_Uniforms &mpsc_default_uniforms [[buffer(3)]], constant mpsc_live_Uniforms &mpsc_live_uniforms [[buffer(4)]], mpsc_Textures mpsc_textures) {
+    return mpsc_pixel(mpsc_live_uniforms, mpsc_varyings);
+}
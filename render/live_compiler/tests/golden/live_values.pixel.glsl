@@ -0,0 +1,15 @@
+uniform float mpsc_live_golden_scale;
+uniform vec4 mpsc_live_golden_tint;
+varying vec2 mpsc_packed_varying_0;
+vec2 pos=vec2(0.0);
+vec4 pixel() {
+    vec4 c = mpsc_live_golden_tint;
+    if ((pos.x > 0.5)) {
+        return vec4((c.rgb * 0.5), c.a);
+    }
+    return c;
+}
+void main() {
+    pos.xy = mpsc_packed_varying_0.xy;
+    gl_FragColor = pixel();
+}
@@ -0,0 +1,18 @@
+uniform float mpsc_live_golden_scale;
+uniform vec4 mpsc_live_golden_tint;
+attribute vec2 mpsc_packed_geometry_0;
+attribute vec4 mpsc_packed_instance_0;
+varying vec2 mpsc_packed_varying_0;
+vec2 geom=vec2(0.0);
+vec4 rect=vec4(0.0);
+vec2 pos=vec2(0.0);
+vec4 vertex() {
+    (pos = (geom * mpsc_live_golden_scale));
+    return vec4((rect.xy + (pos * rect.zw)), 0.0, 1.0);
+}
+void main() {
+    geom.xy = mpsc_packed_geometry_0.xy;
+    rect.xyzw = mpsc_packed_instance_0.xyzw;
+    gl_Position = vertex();
+    mpsc_packed_varying_0.xy = pos.xy;
+}
@@ -0,0 +1,51 @@
+@group(1) @binding(0) var mpsc_default_sampler: sampler;
+fn sample2d(tex: texture_2d<f32>, pos: vec2<f32>) -> vec4<f32> {return textureSampleLevel(tex, mpsc_default_sampler, pos, 0.0);}
+fn sample_cube(tex: texture_cube<f32>, dir: vec3<f32>) -> vec4<f32> {return textureSampleLevel(tex, mpsc_default_sampler, dir, 0.0);}
+fn sample3d(tex: texture_3d<f32>, pos: vec3<f32>) -> vec4<f32> {return textureSampleLevel(tex, mpsc_default_sampler, pos, 0.0);}
+fn mpsc_mod_float(a: f32, b: f32) -> f32 {return a - b * floor(a / b);}
+fn mpsc_mod_vec2(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {return a - b * floor(a / b);}
+fn mpsc_mod_vec3(a: vec3<f32>, b: vec3<f32>) -> vec3<f32> {return a - b * floor(a / b);}
+fn mpsc_mod_vec4(a: vec4<f32>, b: vec4<f32>) -> vec4<f32> {return a - b * floor(a / b);}
+struct mpsc_live_Uniforms {
+    golden_scale: f32,
+    golden_tint: vec4<f32>,
+}
+@group(0) @binding(4) var<uniform> mpsc_live_uniforms: mpsc_live_Uniforms;
+var<private> geom: vec2<f32>;
+var<private> rect: vec4<f32>;
+var<private> pos: vec2<f32>;
+struct mpsc_Varyings {
+    @builtin(position) mpsc_position: vec4<f32>,
+    @location(0) mpsc_packed_varying_0: vec2<f32>,
+}
+fn vertex() -> vec4<f32> {
+    pos = (geom * mpsc_live_uniforms.golden_scale);
+    return vec4<f32>((rect.xy + (pos * rect.zw)), 0.0, 1.0);
+}
+fn pixel() -> vec4<f32> {
+    var c: vec4<f32> = mpsc_live_uniforms.golden_tint;
+    if ((pos.x > 0.5)) {
+        return vec4<f32>((c.rgb * 0.5), c.a);
+    }
+    return c;
+}
+@vertex
+fn mpsc_vertex_main(@location(0) mpsc_packed_geometry_0: vec2<f32>, @location(1) mpsc_packed_instance_0: vec4<f32>) -> mpsc_Varyings {
+    geom.x = mpsc_packed_geometry_0.x;
+    geom.y = mpsc_packed_geometry_0.y;
+    rect.x = mpsc_packed_instance_0.x;
+    rect.y = mpsc_packed_instance_0.y;
+    rect.z = mpsc_packed_instance_0.z;
+    rect.w = mpsc_packed_instance_0.w;
+    var mpsc_varyings: mpsc_Varyings;
+    mpsc_varyings.mpsc_position = vertex();
+    mpsc_varyings.mpsc_packed_varying_0.x = pos.x;
+    mpsc_varyings.mpsc_packed_varying_0.y = pos.y;
+    return mpsc_varyings;
+}
+@fragment
+fn mpsc_fragment_main(mpsc_varyings: mpsc_Varyings) -> @location(0) vec4<f32> {
+    pos.x = mpsc_varyings.mpsc_packed_varying_0.x;
+    pos.y = mpsc_varyings.mpsc_packed_varying_0.y;
+    return pixel();
+}
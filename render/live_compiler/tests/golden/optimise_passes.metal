@@ -0,0 +1,44 @@
+#include <metal_stdlib>
+using namespace metal;
+float4 sample2d(texture2d<float> tex, float2 pos){return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}
+float4 sample_cube(texturecube<float> tex, float3 dir){return tex.sample(sampler(mag_filter::linear,min_filter::linear),dir);}
+float4 sample3d(texture3d<float> tex, float3 pos){return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}
+struct mpsc_default_Uniforms {
+    float time;
+};
+struct mpsc_live_Uniforms {
+};
+struct mpsc_Textures {
+};
+struct mpsc_Geometries {
+    packed_float2 geom;
+};
+struct mpsc_Instances {
+    packed_float4 rect;
+};
+struct mpsc_Varyings {
+    float4 mpsc_position [[position]];
+    float2 pos;
+};
+constant float SCALE = 2.0;
+float4 mpsc_vertex(thread mpsc_Geometries &mpsc_geometries, thread mpsc_Instances &mpsc_instances, thread mpsc_Varyings &mpsc_varyings) {
+    (mpsc_varyings.pos = mpsc_geometries.geom);
+    float scale = 3.0;
+    return float4(((mpsc_instances.rect.xy + (mpsc_geometries.geom * mpsc_instances.rect.zw)) * scale), 0.0, 1.0);
+}
+float4 mpsc_pixel(constant mpsc_default_Uniforms &mpsc_default_uniforms, thread mpsc_Varyings &mpsc_varyings) {
+    float mpsc_cse_0 = (length((mpsc_varyings.pos - float2(0.5))) * mpsc_default_uniforms.time);
+    float d = mpsc_cse_0;
+    (d = (d + mpsc_cse_0));
+    return float4(float3(d), 1.0);
+}
+vertex mpsc_Varyings mpsc_vertex_main(mpsc_Textures mpsc_textures, const device mpsc_Geometries *in_geometries [[buffer(0)]], const device mpsc_Instances *in_instances [[buffer(1)]], constant mpsc_pass_Uniforms &mpsc_pass_uniforms [[buffer(2)]], constant mpsc_view_Uniforms &mpsc_view_uniforms [[buffer(3)]], constant mpsc_draw_Uniforms &mpsc_draw_uniforms [[buffer(4)]], constant mpsc_default_Uniforms &mpsc_default_uniforms [[buffer(5)]], constant mpsc_live_Uniforms &mpsc_live_uniforms [[buffer(6)]], uint vtx_id [[vertex_id]], uint inst_id [[instance_id]]) {
+    mpsc_Geometries mpsc_geometries = in_geometries[vtx_id];
+    mpsc_Instances mpsc_instances = in_instances[inst_id];
+    mpsc_Varyings mpsc_varyings;
+    mpsc_varyings.mpsc_position = mpsc_vertex(mpsc_geometries, mpsc_instances, mpsc_varyings);
+    return mpsc_varyings;
+}
+fragment float4 mpsc_fragment_main(mpsc_Varyings mpsc_varyings[[stage_in]], constant mpsc_pass_Uniforms &mpsc_pass_uniforms [[buffer(0)]], constant mpsc_view_Uniforms &mpsc_view_uniforms [[buffer(1)]], constant mpsc_draw_Uniforms &mpsc_draw_uniforms [[buffer(2)]], constant mpsc_default_Uniforms &mpsc_default_uniforms [[buffer(3)]], constant mpsc_live_Uniforms &mpsc_live_uniforms [[buffer(4)]], mpsc_Textures mpsc_textures) {
+    return mpsc_pixel(mpsc_default_uniforms, mpsc_varyings);
+}
@@ -0,0 +1,14 @@
+const float SCALE = 2.0;
+uniform float time;
+varying vec2 mpsc_packed_varying_0;
+vec2 pos=vec2(0.0);
+vec4 pixel() {
+    float mpsc_cse_0 = (length((pos - vec2(0.5))) * time);
+    float d = mpsc_cse_0;
+    (d = (d + mpsc_cse_0));
+    return vec4(vec3(d), 1.0);
+}
+void main() {
+    pos.xy = mpsc_packed_varying_0.xy;
+    gl_FragColor = pixel();
+}
@@ -0,0 +1,61 @@
+SamplerState mpsc_default_texture_sampler{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;AddressW=Wrap;};
+float4 sample2d(Texture2D tex, float2 pos){return tex.Sample(mpsc_default_texture_sampler,pos);}
+float4 sample_cube(TextureCube tex, float3 dir){return tex.Sample(mpsc_default_texture_sampler,dir);}
+float4 sample3d(Texture3D tex, float3 pos){return tex.Sample(mpsc_default_texture_sampler,pos);}
+struct Shape {
+    float radius;
+};
+cbuffer mpsc_default_Uniforms : register(b3){};
+cbuffer mpsc_live_Uniforms : register(b4) {
+}
+struct mpsc_Geometries {
+};
+struct mpsc_Instances {
+};
+struct mpsc_Varyings {
+    float4 mpsc_position: SV_POSITION;
+    float3 pos: VARYA;
+};
+float2 mpsc_vec2_float(float x) {
+    return float2(x, x);
+}
+float3 mpsc_vec3_float(float x) {
+    return float3(x, x, x);
+}
+float4 mpsc_vec4_float(float x) {
+    return float4(x, x, x, x);
+}
+float4 mpsc_vec4_vec2_float_float(float2 x0, float x1, float x2) {
+    return float4(x0[0], x0[1], x1, x2);
+}
+float4 mpsc_vertex(inout mpsc_Varyings mpsc_varyings) {
+    (mpsc_varyings.pos = mpsc_vec3_float(0.0));
+    return mpsc_vec4_float(0.0);
+}
+float Shape_scale_mpsc_float(Shape self, float factor) {
+    return (self.radius * factor);
+}
+float2 Shape_scale_mpsc_vec2(Shape self, float2 factor) {
+    return (self.radius * factor);
+}
+float sdf_circle_mpsc_vec2_float(float2 p, float r) {
+    return (length(p) - r);
+}
+float sdf_circle_mpsc_vec3_float(float3 p, float r) {
+    return (length(p.xy) - r);
+}
+float4 mpsc_pixel(inout mpsc_Varyings mpsc_varyings) {
+    Shape shape;
+    (shape.radius = 0.5);
+    float d = (sdf_circle_mpsc_vec2_float(mpsc_varyings.pos.xy, Shape_scale_mpsc_float(shape, 2.0)) + sdf_circle_mpsc_vec3_float(mpsc_varyings.pos, 1.0));
+    return mpsc_vec4_vec2_float_float(Shape_scale_mpsc_vec2(shape, mpsc_vec2_float(d)), 0.0, 1.0);
+}
+mpsc_Varyings mpsc_vertex_main(mpsc_Geometries mpsc_geometries, mpsc_Instances mpsc_instances, uint inst_id: SV_InstanceID) {
+    mpsc_Varyings mpsc_varyings = 
+{float4(0.0,0.0,0.0,0.0), float3(0.0,0.0,0.0)};
+    mpsc_varyings.mpsc_position = mpsc_vertex(mpsc_varyings);
+    return mpsc_varyings;
+}
+float4 mpsc_fragment_main(mpsc_Varyings mpsc_varyings) : SV_TARGET{
+    return mpsc_pixel(mpsc_varyings);
+}
@@ -0,0 +1,53 @@
+#include <metal_stdlib>
+using namespace metal;
+float4 sample2d(texture2d<float> tex, float2 pos){return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}
+float4 sample_cube(texturecube<float> tex, float3 dir){return tex.sample(sampler(mag_filter::linear,min_filter::linear),dir);}
+float4 sample3d(texture3d<float> tex, float3 pos){return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}
+struct Shape {
+    float radius;
+};
+struct mpsc_default_Uniforms{};
+struct mpsc_live_Uniforms {
+};
+struct mpsc_Textures {
+};
+struct mpsc_Geometries {
+};
+struct mpsc_Instances {
+};
+struct mpsc_Varyings {
+    float4 mpsc_position [[position]];
+    float3 pos;
+};
+float4 mpsc_vertex(thread mpsc_Varyings &mpsc_varyings) {
+    (mpsc_varyings.pos = float3(0.0));
+    return float4(0.0);
+}
+float Shape_scale_mpsc_float(Shape self, float factor) {
+    return (self.radius * factor);
+}
+float2 Shape_scale_mpsc_vec2(Shape self, float2 factor) {
+    return (self.radius * factor);
+}
+float sdf_circle_mpsc_vec2_float(float2 p, float r) {
+    return (length(p) - r);
+}
+float sdf_circle_mpsc_vec3_float(float3 p, float r) {
+    return (length(p.xy) - r);
+}
+float4 mpsc_pixel(thread mpsc_Varyings &mpsc_varyings) {
+    Shape shape;
+    (shape.radius = 0.5);
+    float d = (sdf_circle_mpsc_vec2_float(mpsc_varyings.pos.xy, Shape_scale_mpsc_float(shape, 2.0)) + sdf_circle_mpsc_vec3_float(mpsc_varyings.pos, 1.0));
+    return float4(Shape_scale_mpsc_vec2(shape, float2(d)), 0.0, 1.0);
+}
+vertex mpsc_Varyings mpsc_vertex_main(mpsc_Textures mpsc_textures, const device mpsc_Geometries *in_geometries [[buffer(0)]], const device mpsc_Instances *in_instances [[buffer(1)]], constant mpsc_pass_Uniforms &mpsc_pass_uniforms [[buffer(2)]], constant mpsc_view_Uniforms &mpsc_view_uniforms [[buffer(3)]], constant mpsc_draw_Uniforms &mpsc_draw_uniforms [[buffer(4)]], constant mpsc_default_Uniforms &mpsc_default_uniforms [[buffer(5)]], constant mpsc_live_Uniforms &mpsc_live_uniforms [[buffer(6)]], uint vtx_id [[vertex_id]], uint inst_id [[instance_id]]) {
+    mpsc_Geometries mpsc_geometries = in_geometries[vtx_id];
+    mpsc_Instances mpsc_instances = in_instances[inst_id];
+    mpsc_Varyings mpsc_varyings;
+    mpsc_varyings.mpsc_position = mpsc_vertex(mpsc_varyings);
+    return mpsc_varyings;
+}
+fragment float4 mpsc_fragment_main(mpsc_Varyings mpsc_varyings[[stage_in]], constant mpsc_pass_Uniforms &mpsc_pass_uniforms [[buffer(0)]], constant mpsc_view_Uniforms &mpsc_view_uniforms [[buffer(1)]], constant mpsc_draw_Uniforms &mpsc_draw_uniforms [[buffer(2)]], constant mpsc_default_Uniforms &mpsc_default_uniforms [[buffer(3)]], constant mpsc_live_Uniforms &mpsc_live_uniforms [[buffer(4)]], mpsc_Textures mpsc_textures) {
+    return mpsc_pixel(mpsc_varyings);
+}
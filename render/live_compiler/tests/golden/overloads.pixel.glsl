@@ -0,0 +1,27 @@
+struct Shape {
+    float radius;
+};
+varying vec3 mpsc_packed_varying_0;
+vec3 pos=vec3(0.0);
+float Shape_scale_mpsc_float(Shape self, float factor) {
+    return (self.radius * factor);
+}
+vec2 Shape_scale_mpsc_vec2(Shape self, vec2 factor) {
+    return (self.radius * factor);
+}
+float sdf_circle_mpsc_vec2_float(vec2 p, float r) {
+    return (length(p) - r);
+}
+float sdf_circle_mpsc_vec3_float(vec3 p, float r) {
+    return (length(p.xy) - r);
+}
+vec4 pixel() {
+    Shape shape;
+    (shape.radius = 0.5);
+    float d = (sdf_circle_mpsc_vec2_float(pos.xy, Shape_scale_mpsc_float(shape, 2.0)) + sdf_circle_mpsc_vec3_float(pos, 1.0));
+    return vec4(Shape_scale_mpsc_vec2(shape, vec2(d)), 0.0, 1.0);
+}
+void main() {
+    pos.xyz = mpsc_packed_varying_0.xyz;
+    gl_FragColor = pixel();
+}
@@ -0,0 +1,13 @@
+struct Shape {
+    float radius;
+};
+varying vec3 mpsc_packed_varying_0;
+vec3 pos=vec3(0.0);
+vec4 vertex() {
+    (pos = vec3(0.0));
+    return vec4(0.0);
+}
+void main() {
+    gl_Position = vertex();
+    mpsc_packed_varying_0.xyz = pos.xyz;
+}
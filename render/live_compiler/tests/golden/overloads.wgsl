@@ -0,0 +1,54 @@
+@group(1) @binding(0) var mpsc_default_sampler: sampler;
+fn sample2d(tex: texture_2d<f32>, pos: vec2<f32>) -> vec4<f32> {return textureSampleLevel(tex, mpsc_default_sampler, pos, 0.0);}
+fn sample_cube(tex: texture_cube<f32>, dir: vec3<f32>) -> vec4<f32> {return textureSampleLevel(tex, mpsc_default_sampler, dir, 0.0);}
+fn sample3d(tex: texture_3d<f32>, pos: vec3<f32>) -> vec4<f32> {return textureSampleLevel(tex, mpsc_default_sampler, pos, 0.0);}
+fn mpsc_mod_float(a: f32, b: f32) -> f32 {return a - b * floor(a / b);}
+fn mpsc_mod_vec2(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {return a - b * floor(a / b);}
+fn mpsc_mod_vec3(a: vec3<f32>, b: vec3<f32>) -> vec3<f32> {return a - b * floor(a / b);}
+fn mpsc_mod_vec4(a: vec4<f32>, b: vec4<f32>) -> vec4<f32> {return a - b * floor(a / b);}
+struct Shape {
+    radius: f32,
+}
+var<private> pos: vec3<f32>;
+struct mpsc_Varyings {
+    @builtin(position) mpsc_position: vec4<f32>,
+    @location(0) mpsc_packed_varying_0: vec3<f32>,
+}
+fn vertex() -> vec4<f32> {
+    pos = vec3<f32>(0.0);
+    return vec4<f32>(0.0);
+}
+fn Shape_scale_mpsc_float(mpsc_self: Shape, factor: f32) -> f32 {
+    return (mpsc_self.radius * factor);
+}
+fn Shape_scale_mpsc_vec2(mpsc_self: Shape, factor: vec2<f32>) -> vec2<f32> {
+    return (mpsc_self.radius * factor);
+}
+fn sdf_circle_mpsc_vec2_float(p: vec2<f32>, r: f32) -> f32 {
+    return (length(p) - r);
+}
+fn sdf_circle_mpsc_vec3_float(p: vec3<f32>, r: f32) -> f32 {
+    return (length(p.xy) - r);
+}
+fn pixel() -> vec4<f32> {
+    var shape: Shape;
+    shape.radius = 0.5;
+    var d: f32 = (sdf_circle_mpsc_vec2_float(pos.xy, Shape_scale_mpsc_float(shape, 2.0)) + sdf_circle_mpsc_vec3_float(pos, 1.0));
+    return vec4<f32>(Shape_scale_mpsc_vec2(shape, vec2<f32>(d)), 0.0, 1.0);
+}
+@vertex
+fn mpsc_vertex_main() -> mpsc_Varyings {
+    var mpsc_varyings: mpsc_Varyings;
+    mpsc_varyings.mpsc_position = vertex();
+    mpsc_varyings.mpsc_packed_varying_0.x = pos.x;
+    mpsc_varyings.mpsc_packed_varying_0.y = pos.y;
+    mpsc_varyings.mpsc_packed_varying_0.z = pos.z;
+    return mpsc_varyings;
+}
+@fragment
+fn mpsc_fragment_main(mpsc_varyings: mpsc_Varyings) -> @location(0) vec4<f32> {
+    pos.x = mpsc_varyings.mpsc_packed_varying_0.x;
+    pos.y = mpsc_varyings.mpsc_packed_varying_0.y;
+    pos.z = mpsc_varyings.mpsc_packed_varying_0.z;
+    return pixel();
+}
@@ -0,0 +1,72 @@
+SamplerState mpsc_default_texture_sampler{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;AddressW=Wrap;};
+float4 sample2d(Texture2D tex, float2 pos){return tex.Sample(mpsc_default_texture_sampler,pos);}
+float4 sample_cube(TextureCube tex, float3 dir){return tex.Sample(mpsc_default_texture_sampler,dir);}
+float4 sample3d(Texture3D tex, float3 pos){return tex.Sample(mpsc_default_texture_sampler,pos);}
+struct Cx {
+    float2 pos;
+};
+cbuffer mpsc_default_Uniforms : register(b3) {
+    float uniform_1;
+};
+cbuffer mpsc_draw_Uniforms : register(b2) {
+    float4x4 uniform_2;
+};
+cbuffer mpsc_live_Uniforms : register(b4) {
+}
+Texture2D texture_foo: register(t0);
+Texture2D texture_bar: register(t1);
+struct mpsc_Geometries {
+    float3 position: GEOMA;
+    float3 color: GEOMB;
+};
+struct mpsc_Instances {
+    float rotation: INSTA;
+};
+struct mpsc_Varyings {
+    float4 mpsc_position: SV_POSITION;
+    float3 v_color: VARYA;
+};
+static const float FOO = 1.0;
+float mpsc_float_int(int x) {
+    return float(x);
+}
+float2 mpsc_vec2_float(float x) {
+    return float2(x, x);
+}
+float4 mpsc_vec4_vec2_float_float(float2 x0, float x1, float x2) {
+    return float4(x0[0], x0[1], x1, x2);
+}
+void Cx_bar(inout Cx self) {
+    (self.pos = -self.pos);
+}
+Cx Cx_foo() {
+    Cx cx;
+    (cx.pos = mpsc_vec2_float(uniform_1));
+    return cx;
+}
+float4 mpsc_vertex(in mpsc_Geometries mpsc_geometries, in mpsc_Instances mpsc_instances, inout mpsc_Varyings mpsc_varyings) {
+    Cx cx = Cx_foo();
+    Cx_bar(cx);
+    (mpsc_varyings.v_color = (mpsc_geometries.color * mpsc_instances.rotation));
+    for (int i = 0; i < 10; i += 2) {
+        (cx.pos += mpsc_vec2_float(mpsc_float_int(i)));
+    }
+    return mul(uniform_2, mpsc_vec4_vec2_float_float((mpsc_geometries.position.xy + cx.pos), mpsc_geometries.position.z, 1.0));
+}
+float Cx_qux(Cx self, float x) {
+    return ((2.0 * x) + self.pos.x);
+}
+float4 mpsc_pixel(inout mpsc_Varyings mpsc_varyings) {
+    Cx cx = Cx_foo();
+    float a = Cx_qux(cx, 30.0);
+    return (sample2d(texture_foo, mpsc_varyings.v_color.xy) * sample2d(texture_bar, mpsc_vec2_float(a)));
+}
+mpsc_Varyings mpsc_vertex_main(mpsc_Geometries mpsc_geometries, mpsc_Instances mpsc_instances, uint inst_id: SV_InstanceID) {
+    mpsc_Varyings mpsc_varyings = 
+{float4(0.0,0.0,0.0,0.0), float3(0.0,0.0,0.0)};
+    mpsc_varyings.mpsc_position = mpsc_vertex(mpsc_geometries, mpsc_instances, mpsc_varyings);
+    return mpsc_varyings;
+}
+float4 mpsc_fragment_main(mpsc_Varyings mpsc_varyings) : SV_TARGET{
+    return mpsc_pixel(mpsc_varyings);
+}
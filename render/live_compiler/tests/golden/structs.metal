@@ -0,0 +1,67 @@
+#include <metal_stdlib>
+using namespace metal;
+float4 sample2d(texture2d<float> tex, float2 pos){return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}
+float4 sample_cube(texturecube<float> tex, float3 dir){return tex.sample(sampler(mag_filter::linear,min_filter::linear),dir);}
+float4 sample3d(texture3d<float> tex, float3 pos){return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}
+struct Cx {
+    float2 pos;
+};
+struct mpsc_default_Uniforms {
+    float uniform_1;
+};
+struct mpsc_draw_Uniforms {
+    float4x4 uniform_2;
+};
+struct mpsc_live_Uniforms {
+};
+struct mpsc_Textures {
+    texture2d<float> texture_foo [[texture(0)]];
+    texture2d<float> texture_bar [[texture(1)]];
+};
+struct mpsc_Geometries {
+    packed_float3 position;
+    packed_float3 color;
+};
+struct mpsc_Instances {
+    float rotation;
+};
+struct mpsc_Varyings {
+    float4 mpsc_position [[position]];
+    float3 v_color;
+};
+constant float FOO = 1.0;
+void Cx_bar(thread Cx &self) {
+    (self.pos = -self.pos);
+}
+Cx Cx_foo(constant mpsc_default_Uniforms &mpsc_default_uniforms) {
+    Cx cx;
+    (cx.pos = float2(mpsc_default_uniforms.uniform_1));
+    return cx;
+}
+float4 mpsc_vertex(constant mpsc_default_Uniforms &mpsc_default_uniforms, constant mpsc_draw_Uniforms &mpsc_draw_uniforms, thread mpsc_Geometries &mpsc_geometries, thread mpsc_Instances &mpsc_instances, thread mpsc_Varyings &mpsc_varyings) {
+    Cx cx = Cx_foo(mpsc_default_uniforms);
+    Cx_bar(cx);
+    (mpsc_varyings.v_color = (mpsc_geometries.color * mpsc_instances.rotation));
+    for (int i = 0; i < 10; i += 2) {
+        (cx.pos += float2(float(i)));
+    }
+    return (mpsc_draw_uniforms.uniform_2 * float4(float4((mpsc_geometries.position.xy + cx.pos), mpsc_geometries.position.z, 1.0)));
+}
+float Cx_qux(Cx self, float x) {
+    return ((2.0 * x) + self.pos.x);
+}
+float4 mpsc_pixel(constant mpsc_default_Uniforms &mpsc_default_uniforms, mpsc_Textures mpsc_textures, thread mpsc_Varyings &mpsc_varyings) {
+    Cx cx = Cx_foo(mpsc_default_uniforms);
+    float a = Cx_qux(cx, 30.0);
+    return (sample2d(mpsc_textures.texture_foo, mpsc_varyings.v_color.xy) * sample2d(mpsc_textures.texture_bar, float2(a)));
+}
+vertex mpsc_Varyings mpsc_vertex_main(mpsc_Textures mpsc_textures, const device mpsc_Geometries *in_geometries [[buffer(0)]], const device mpsc_Instances *in_instances [[buffer(1)]], constant mpsc_pass_Uniforms &mpsc_pass_uniforms [[buffer(2)]], constant mpsc_view_Uniforms &mpsc_view_uniforms [[buffer(3)]], constant mpsc_draw_Uniforms &mpsc_draw_uniforms [[buffer(4)]], constant mpsc_default_Uniforms &mpsc_default_uniforms [[buffer(5)]], constant mpsc_live_Uniforms &mpsc_live_uniforms [[buffer(6)]], uint vtx_id [[vertex_id]], uint inst_id [[instance_id]]) {
+    mpsc_Geometries mpsc_geometries = in_geometries[vtx_id];
+    mpsc_Instances mpsc_instances = in_instances[inst_id];
+    mpsc_Varyings mpsc_varyings;
+    mpsc_varyings.mpsc_position = mpsc_vertex(mpsc_default_uniforms, mpsc_draw_uniforms, mpsc_geometries, mpsc_instances, mpsc_varyings);
+    return mpsc_varyings;
+}
+fragment float4 mpsc_fragment_main(mpsc_Varyings mpsc_varyings[[stage_in]], constant mpsc_pass_Uniforms &mpsc_pass_uniforms [[buffer(0)]], constant mpsc_view_Uniforms &mpsc_view_uniforms [[buffer(1)]], constant mpsc_draw_Uniforms &mpsc_draw_uniforms [[buffer(2)]], constant mpsc_default_Uniforms &mpsc_default_uniforms [[buffer(3)]], constant mpsc_live_Uniforms &mpsc_live_uniforms [[buffer(4)]], mpsc_Textures mpsc_textures) {
+    return mpsc_pixel(mpsc_default_uniforms, mpsc_textures, mpsc_varyings);
+}
@@ -0,0 +1,27 @@
+struct Cx {
+    vec2 pos;
+};
+const float FOO = 1.0;
+uniform float uniform_1;
+uniform mat4 uniform_2;
+uniform sampler2D texture_foo;
+uniform sampler2D texture_bar;
+varying vec3 mpsc_packed_varying_0;
+vec3 v_color=vec3(0.0);
+Cx Cx_foo() {
+    Cx cx;
+    (cx.pos = vec2(uniform_1));
+    return cx;
+}
+float Cx_qux(Cx self, float x) {
+    return ((2.0 * x) + self.pos.x);
+}
+vec4 pixel() {
+    Cx cx = Cx_foo();
+    float a = Cx_qux(cx, 30.0);
+    return (sample2d(texture_foo, v_color.xy) * sample2d(texture_bar, vec2(a)));
+}
+void main() {
+    v_color.xyz = mpsc_packed_varying_0.xyz;
+    gl_FragColor = pixel();
+}
@@ -0,0 +1,41 @@
+struct Cx {
+    vec2 pos;
+};
+const float FOO = 1.0;
+uniform float uniform_1;
+uniform mat4 uniform_2;
+uniform sampler2D texture_foo;
+uniform sampler2D texture_bar;
+attribute vec4 mpsc_packed_geometry_0;
+attribute vec2 mpsc_packed_geometry_1;
+attribute float mpsc_packed_instance_0;
+varying vec3 mpsc_packed_varying_0;
+vec3 position=vec3(0.0);
+vec3 color=vec3(0.0);
+float rotation=0.0;
+vec3 v_color=vec3(0.0);
+void Cx_bar(inout Cx self) {
+    (self.pos = -self.pos);
+}
+Cx Cx_foo() {
+    Cx cx;
+    (cx.pos = vec2(uniform_1));
+    return cx;
+}
+vec4 vertex() {
+    Cx cx = Cx_foo();
+    Cx_bar(cx);
+    (v_color = (color * rotation));
+    for (int i = 0; i < 10; i += 2) {
+        (cx.pos += vec2(float(i)));
+    }
+    return (uniform_2 * vec4((position.xy + cx.pos), position.z, 1.0));
+}
+void main() {
+    position.xyz = mpsc_packed_geometry_0.xyz;
+    color.x = mpsc_packed_geometry_0.w;
+    color.yz = mpsc_packed_geometry_1.xy;
+    rotation = mpsc_packed_instance_0;
+    gl_Position = vertex();
+    mpsc_packed_varying_0.xyz = v_color.xyz;
+}
@@ -0,0 +1,78 @@
+@group(1) @binding(0) var mpsc_default_sampler: sampler;
+fn sample2d(tex: texture_2d<f32>, pos: vec2<f32>) -> vec4<f32> {return textureSampleLevel(tex, mpsc_default_sampler, pos, 0.0);}
+fn sample_cube(tex: texture_cube<f32>, dir: vec3<f32>) -> vec4<f32> {return textureSampleLevel(tex, mpsc_default_sampler, dir, 0.0);}
+fn sample3d(tex: texture_3d<f32>, pos: vec3<f32>) -> vec4<f32> {return textureSampleLevel(tex, mpsc_default_sampler, pos, 0.0);}
+fn mpsc_mod_float(a: f32, b: f32) -> f32 {return a - b * floor(a / b);}
+fn mpsc_mod_vec2(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {return a - b * floor(a / b);}
+fn mpsc_mod_vec3(a: vec3<f32>, b: vec3<f32>) -> vec3<f32> {return a - b * floor(a / b);}
+fn mpsc_mod_vec4(a: vec4<f32>, b: vec4<f32>) -> vec4<f32> {return a - b * floor(a / b);}
+struct Cx {
+    pos: vec2<f32>,
+}
+struct mpsc_default_Uniforms {
+    uniform_1: f32,
+}
+@group(0) @binding(3) var<uniform> mpsc_default_uniforms: mpsc_default_Uniforms;
+struct mpsc_draw_Uniforms {
+    uniform_2: mat4x4<f32>,
+}
+@group(0) @binding(2) var<uniform> mpsc_draw_uniforms: mpsc_draw_Uniforms;
+@group(1) @binding(1) var texture_foo: texture_2d<f32>;
+@group(1) @binding(2) var texture_bar: texture_2d<f32>;
+var<private> position: vec3<f32>;
+var<private> color: vec3<f32>;
+var<private> rotation: f32;
+var<private> v_color: vec3<f32>;
+struct mpsc_Varyings {
+    @builtin(position) mpsc_position: vec4<f32>,
+    @location(0) mpsc_packed_varying_0: vec3<f32>,
+}
+const FOO: f32 = 1.0;
+fn Cx_bar(mpsc_self: ptr<function, Cx>) {
+    (*mpsc_self).pos = -(*mpsc_self).pos;
+}
+fn Cx_foo() -> Cx {
+    var cx: Cx;
+    cx.pos = vec2<f32>(mpsc_default_uniforms.uniform_1);
+    return cx;
+}
+fn vertex() -> vec4<f32> {
+    var cx: Cx = Cx_foo();
+    Cx_bar(&cx);
+    v_color = (color * rotation);
+    for (var i: i32 = 0; i < 10; i += 2) {
+        cx.pos += vec2<f32>(f32(i));
+    }
+    return (mpsc_draw_uniforms.uniform_2 * vec4<f32>((position.xy + cx.pos), position.z, 1.0));
+}
+fn Cx_qux(mpsc_self: Cx, x: f32) -> f32 {
+    return ((2.0 * x) + mpsc_self.pos.x);
+}
+fn pixel() -> vec4<f32> {
+    var cx: Cx = Cx_foo();
+    var a: f32 = Cx_qux(cx, 30.0);
+    return (sample2d(texture_foo, v_color.xy) * sample2d(texture_bar, vec2<f32>(a)));
+}
+@vertex
+fn mpsc_vertex_main(@location(0) mpsc_packed_geometry_0: vec4<f32>, @location(1) mpsc_packed_geometry_1: vec2<f32>, @location(2) mpsc_packed_instance_0: f32) -> mpsc_Varyings {
+    position.x = mpsc_packed_geometry_0.x;
+    position.y = mpsc_packed_geometry_0.y;
+    position.z = mpsc_packed_geometry_0.z;
+    color.x = mpsc_packed_geometry_0.w;
+    color.y = mpsc_packed_geometry_1.x;
+    color.z = mpsc_packed_geometry_1.y;
+    rotation = mpsc_packed_instance_0;
+    var mpsc_varyings: mpsc_Varyings;
+    mpsc_varyings.mpsc_position = vertex();
+    mpsc_varyings.mpsc_packed_varying_0.x = v_color.x;
+    mpsc_varyings.mpsc_packed_varying_0.y = v_color.y;
+    mpsc_varyings.mpsc_packed_varying_0.z = v_color.z;
+    return mpsc_varyings;
+}
+@fragment
+fn mpsc_fragment_main(mpsc_varyings: mpsc_Varyings) -> @location(0) vec4<f32> {
+    v_color.x = mpsc_varyings.mpsc_packed_varying_0.x;
+    v_color.y = mpsc_varyings.mpsc_packed_varying_0.y;
+    v_color.z = mpsc_varyings.mpsc_packed_varying_0.z;
+    return pixel();
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use makepad_live_compiler::generate_glsl;
+use makepad_live_compiler::generate_hlsl;
+use makepad_live_compiler::generate_metal;
+use makepad_live_compiler::livestyles::LiveStyles;
+use makepad_live_compiler::shaderast::ShaderAst;
+use common::OPTIONS;
+
+fn compile(code: &str) -> Result<(LiveStyles, ShaderAst), String> {
+    common::compile_shader("int_ops", "", code, OPTIONS)
+}
+
+#[test]
+fn int_ops() {
+    let (live_styles, shader_ast) = compile(r#"
+        fn hash(x: int) -> int {
+            let h = x * 747796405 + 1891336453 % 7;
+            h ^= h >> 16;
+            h = (h & 255) | (h << 3) ^ !h % 5;
+            return h;
+        }
+        fn vertex() -> vec4 {
+            let v = ivec2(3, 4) % 2 & ivec2(1, 1);
+            return vec4(float(hash(v.x)));
+        }
+        fn pixel() -> vec4 {
+            return vec4(1.0);
+        }
+    "#).unwrap();
+    let glsl = generate_glsl::generate_vertex_shader(&shader_ast, &live_styles, OPTIONS);
+    // glsl es 1.00 has no integer operators, they go through helper functions
+    assert!(glsl.contains("int mpsc_rem(int a, int b) {"));
+    assert!(glsl.contains("ivec2 mpsc_rem(ivec2 a, int b) {"));
+    assert!(glsl.contains("ivec2 mpsc_bit_and(ivec2 a, ivec2 b) {"));
+    assert!(glsl.contains("(h = mpsc_bit_xor(h, mpsc_shr(h, 16)));"));
+    assert!(glsl.contains("mpsc_rem((-(h) - 1), 5)"));
+    // the constant operand is folded
+    assert!(glsl.contains("int h = ((x * 747796405) + 6);"));
+    let hlsl = generate_hlsl::generate_shader(&shader_ast, &live_styles, OPTIONS);
+    assert!(hlsl.contains("(h ^= (h >> 16));"));
+    assert!(hlsl.contains("(h = ((h & 255) | ((h << 3) ^ (~h % 5))));"));
+    assert!(!hlsl.contains("mpsc_rem"));
+    let metal = generate_metal::generate_shader(&shader_ast, &live_styles, OPTIONS);
+    assert!(metal.contains("int2 v = ((int2(3, 4) % 2) & int2(1, 1));"));
+    assert!(metal.contains("(h = ((h & 255) | ((h << 3) ^ (~h % 5))));"));
+}
+
+#[test]
+fn int_ops_ty_errors() {
+    let err = compile(r#"
+        fn vertex() -> vec4 {
+            return vec4(1.0 % 2.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(1.0);
+        }
+    "#).err().unwrap();
+    assert!(err.contains("can't apply binary operator"), "{}", err);
+}
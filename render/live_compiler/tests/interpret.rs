@@ -0,0 +1,200 @@
+mod common;
+
+use makepad_live_compiler::interpret::{Interpreter, TextureData, Value};
+use makepad_live_compiler::livestyles::LiveStyles;
+use makepad_live_compiler::math::{Mat4, Vec2, Vec4};
+use makepad_live_compiler::shaderast::ShaderAst;
+use common::OPTIONS;
+
+fn compile(live_values: &str, code: &str) -> (LiveStyles, ShaderAst) {
+    common::compile_shader("interpret", live_values, code, OPTIONS).unwrap()
+}
+
+fn assert_vec4_eq(actual: Vec4, expected: Vec4) {
+    let close = | a: f32, b: f32 | (a - b).abs() < 1e-5;
+    assert!(
+        close(actual.x, expected.x) && close(actual.y, expected.y) && close(actual.z, expected.z) && close(actual.w, expected.w),
+        "expected {:?}, got {:?}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn quad() {
+    let (live_styles, shader) = compile("self::tint: #FF0000; self::alpha: 0.5;", r#"
+        geometry geom: vec2;
+        instance rect: vec4;
+        uniform camera_projection: mat4 in pass;
+        varying pos: vec2;
+        fn vertex() -> vec4 {
+            pos = geom;
+            return camera_projection * vec4(rect.xy + geom * rect.zw, 0.0, 1.0);
+        }
+        fn pixel() -> vec4 {
+            let c = self::tint;
+            c.a = self::alpha;
+            if pos.x > 0.5 {
+                c.gb = vec2(1.0);
+            }
+            return c * mix(1.0, 0.5, pos.y);
+        }
+    "#);
+    let mut interpreter = Interpreter::new(&shader, &live_styles);
+    interpreter.set_var("geom", Vec2 {x: 1.0, y: 0.0});
+    interpreter.set_var("rect", Vec4 {x: 10.0, y: 20.0, z: 100.0, w: 50.0});
+    interpreter.set_var("camera_projection", Mat4::scaled_translation(2.0, 1.0, 0.0, 0.0));
+    assert_vec4_eq(interpreter.run_vertex().unwrap(), Vec4 {x: 221.0, y: 40.0, z: 0.0, w: 1.0});
+    assert_eq!(interpreter.get_var("pos").unwrap().to_vec2(), Some(Vec2 {x: 1.0, y: 0.0}));
+    assert_vec4_eq(interpreter.run_pixel().unwrap(), Vec4 {x: 1.0, y: 1.0, z: 1.0, w: 0.5});
+    interpreter.set_var("pos", Vec2 {x: 0.0, y: 1.0});
+    assert_vec4_eq(interpreter.run_pixel().unwrap(), Vec4 {x: 0.5, y: 0.0, z: 0.0, w: 0.25});
+}
+
+#[test]
+fn structs_loops_and_inout() {
+    let (live_styles, shader) = compile("", r#"
+        struct Acc {
+            sum: int,
+            items: ivec4
+        }
+        impl Acc {
+            fn push(inout self, i: int, value: int) {
+                self.items[i] = value;
+                self.sum += value;
+            }
+        }
+        fn fill(n: int) -> int {
+            let acc: Acc;
+            for i from 0 to n {
+                acc.push(i, i * i);
+            }
+            let down = 0;
+            for i from 4 to 0 {
+                down = down * 10 + i;
+            }
+            let count = 0;
+            loop max 100 {
+                count += 1;
+                if count == 7 {
+                    break;
+                }
+            }
+            return acc.sum + acc.items[3] * 100 + down * 1000 + count * 1000000;
+        }
+        fn vertex() -> vec4 {
+            return vec4(0.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(0.0);
+        }
+    "#);
+    let mut interpreter = Interpreter::new(&shader, &live_styles);
+    assert_eq!(interpreter.call("fill", vec![4.into()]).unwrap(), Value::Ints(vec![10_210_914]));
+    assert_eq!(interpreter.call("fill", vec![2.into()]).unwrap(), Value::Ints(vec![10_210_001]));
+}
+
+#[test]
+fn overloads() {
+    let (live_styles, shader) = compile("", r#"
+        fn area(r: float) -> float {
+            return 3.0 * r * r;
+        }
+        fn area(size: vec2) -> float {
+            return size.x * size.y;
+        }
+        fn area(size: ivec2) -> int {
+            return size.x * size.y;
+        }
+        fn vertex() -> vec4 {
+            return vec4(0.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(area(2.0), area(vec2(2.0, 3.0)), float(area(ivec2(4, 5))), 0.0);
+        }
+    "#);
+    let mut interpreter = Interpreter::new(&shader, &live_styles);
+    assert_vec4_eq(interpreter.run_pixel().unwrap(), Vec4 {x: 12.0, y: 6.0, z: 20.0, w: 0.0});
+}
+
+#[test]
+fn uint_hash() {
+    let (live_styles, shader) = compile("", r#"
+        fn hash(x: uint) -> uint {
+            x ^= x >> 16;
+            x *= 2146121005u;
+            x ^= x >> 15;
+            x *= 2221713035u;
+            x ^= x >> 16;
+            return x;
+        }
+        fn vertex() -> vec4 {
+            return vec4(0.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(0.0);
+        }
+    "#);
+    let hash = | mut x: u32 | {
+        x ^= x >> 16;
+        x = x.wrapping_mul(0x7feb352d);
+        x ^= x >> 15;
+        x = x.wrapping_mul(0x846ca68b);
+        x ^= x >> 16;
+        x
+    };
+    let mut interpreter = Interpreter::new(&shader, &live_styles);
+    for x in [0u32, 1, 12345, 0xffff_ffff].iter() {
+        assert_eq!(interpreter.call("hash", vec![(*x).into()]).unwrap().to_uint(), Some(hash(*x)));
+    }
+}
+
+#[test]
+fn texture_sampling() {
+    let (live_styles, shader) = compile("", r#"
+        texture image: texture2D;
+        varying pos: vec2;
+        fn vertex() -> vec4 {
+            return vec4(0.0);
+        }
+        fn pixel() -> vec4 {
+            return sample2d(image, pos);
+        }
+    "#);
+    let mut interpreter = Interpreter::new(&shader, &live_styles);
+    interpreter.set_texture("image", TextureData::from_bgra(2, 1, 1, &[0xffff0000, 0xff0000ff]));
+    interpreter.set_var("pos", Vec2 {x: 0.25, y: 0.5});
+    assert_vec4_eq(interpreter.run_pixel().unwrap(), Vec4 {x: 1.0, y: 0.0, z: 0.0, w: 1.0});
+    interpreter.set_var("pos", Vec2 {x: 0.5, y: 0.5});
+    assert_vec4_eq(interpreter.run_pixel().unwrap(), Vec4 {x: 0.5, y: 0.0, z: 0.5, w: 1.0});
+    // wraps around to the first pixel
+    interpreter.set_var("pos", Vec2 {x: 1.0, y: 0.5});
+    assert_vec4_eq(interpreter.run_pixel().unwrap(), Vec4 {x: 0.5, y: 0.0, z: 0.5, w: 1.0});
+}
+
+#[test]
+fn runtime_errors() {
+    let (live_styles, shader) = compile("", r#"
+        fn divide(a: int, b: int) -> int {
+            return a / b;
+        }
+        fn spin(n: int) -> int {
+            let i = 0;
+            while i < n {
+                i += 1;
+            }
+            return i;
+        }
+        fn vertex() -> vec4 {
+            return vec4(0.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(0.0);
+        }
+    "#);
+    let mut interpreter = Interpreter::new(&shader, &live_styles);
+    assert_eq!(interpreter.call("divide", vec![7.into(), 2.into()]).unwrap(), Value::Ints(vec![3]));
+    assert_eq!(interpreter.call("divide", vec![7.into(), 0.into()]).unwrap_err().message, "integer division by zero");
+    assert_eq!(interpreter.call("spin", vec![1000.into()]).unwrap(), Value::Ints(vec![1000]));
+    assert!(interpreter.call("spin", vec![5000.into()]).unwrap_err().message.contains("without a max"));
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use makepad_live_compiler::generate_glsl;
+use makepad_live_compiler::generate_hlsl;
+use makepad_live_compiler::generate_metal;
+use makepad_live_compiler::livestyles::LiveStyles;
+use makepad_live_compiler::shaderast::ShaderAst;
+use common::OPTIONS;
+
+fn compile(code: &str) -> Result<(LiveStyles, ShaderAst), String> {
+    common::compile_shader("loops", "", code, OPTIONS)
+}
+
+const MARCH: &str = r#"
+    instance steps: float;
+    fn march(d: float) -> float {
+        let t = 0.0;
+        while t < d max 64 {
+            t += 0.5;
+        }
+        loop {
+            t -= 1.0;
+            if t < 0.0 {
+                break;
+            }
+        }
+        for i from 0 to int(steps) {
+            t += float(i);
+        }
+        for i from int(steps) to 0 step -2 max 16 {
+            if i == 4 {
+                continue;
+            }
+            while t > 1.0 {
+                t *= 0.5;
+            }
+        }
+        return t;
+    }
+    fn vertex() -> vec4 {
+        return vec4(march(10.0));
+    }
+    fn pixel() -> vec4 {
+        return vec4(1.0);
+    }
+"#;
+
+#[test]
+fn loops_glsl() {
+    let (live_styles, shader_ast) = compile(MARCH).unwrap();
+    let glsl = generate_glsl::generate_vertex_shader(&shader_ast, &live_styles, OPTIONS);
+    // every loop counts up to a const so webgl1 takes it
+    assert!(glsl.contains("    for (int mpsc_loop_1 = 0; mpsc_loop_1 < 64; mpsc_loop_1++) {\n        if (!((t < d))) break;\n"));
+    assert!(glsl.contains("    for (int mpsc_loop_1 = 0; mpsc_loop_1 < 1024; mpsc_loop_1++) {\n        (t -= 1.0);\n"));
+    assert!(glsl.contains("        int mpsc_from_1 = 0;\n        for (int mpsc_loop_1 = 0; mpsc_loop_1 < 1024; mpsc_loop_1++) {\n            int i = mpsc_from_1 + mpsc_loop_1 * 1;\n            if (i >= int(steps)) break;\n"));
+    assert!(glsl.contains("            int i = mpsc_from_1 - 1 - mpsc_loop_1 * 2;\n            if (i < 0) break;\n"));
+    assert!(glsl.contains("            for (int mpsc_loop_3 = 0; mpsc_loop_3 < 1024; mpsc_loop_3++) {"));
+    assert!(!glsl.contains("while"));
+}
+
+#[test]
+fn loops_hlsl_metal() {
+    let (live_styles, shader_ast) = compile(MARCH).unwrap();
+    for code in &[
+        generate_hlsl::generate_shader(&shader_ast, &live_styles, OPTIONS),
+        generate_metal::generate_shader(&shader_ast, &live_styles, OPTIONS),
+    ] {
+        // only loops with a max are capped
+        assert!(code.contains("for (int mpsc_loop_1 = 0; mpsc_loop_1 < 64; mpsc_loop_1++) {"));
+        assert!(code.contains("    for (;;) {\n        (t -= 1.0);"));
+        assert!(code.contains("for (int i = 0; i < ") && code.contains("steps); i += 1) {"));
+        assert!(code.contains("            int i = mpsc_from_1 - 1 - mpsc_loop_1 * 2;"));
+        assert!(code.contains("            while ((t > 1.0)) {"));
+    }
+}
+
+#[test]
+fn loops_errors() {
+    let err = compile(r#"
+        fn vertex() -> vec4 {
+            while 1.0 { }
+            return vec4(1.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(1.0);
+        }
+    "#).err().unwrap();
+    assert!(err.contains("expected type `bool`"), "{}", err);
+
+    let err = compile(r#"
+        instance n: float;
+        fn vertex() -> vec4 {
+            loop max int(n) {
+                break;
+            }
+            return vec4(1.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(1.0);
+        }
+    "#).err().unwrap();
+    assert_eq!(err, "expression is not const");
+
+    let err = compile(r#"
+        fn vertex() -> vec4 {
+            for i from 0 to 4 step 1 max 0 { }
+            return vec4(1.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(1.0);
+        }
+    "#).err().unwrap();
+    assert_eq!(err, "max must be positive");
+}
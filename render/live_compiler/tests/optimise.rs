@@ -0,0 +1,121 @@
+mod common;
+
+use makepad_live_compiler::analyse::ShaderCompileOptions;
+use makepad_live_compiler::generate_glsl;
+use makepad_live_compiler::interpret::Interpreter;
+use makepad_live_compiler::livestyles::LiveStyles;
+use makepad_live_compiler::math::{Vec2, Vec4};
+use makepad_live_compiler::optimise::{self, ShaderOptimisations};
+use makepad_live_compiler::shaderast::ShaderAst;
+use common::OPTIONS;
+
+const SHADER: &str = r#"
+    struct Acc {
+        sum: float,
+        count: int
+    }
+    impl Acc {
+        fn push(inout self, value: float) {
+            self.sum += value;
+            self.count += 1;
+        }
+        fn mean(self) -> float {
+            return self.sum / float(self.count);
+        }
+    }
+    const STEPS: int = 4;
+    instance rect: vec4;
+    geometry geom: vec2;
+    varying pos: vec2;
+    varying debug: vec4;
+    fn square(x: float) -> float {
+        return x * x;
+    }
+    fn falloff(d: float, r: float) -> float {
+        return clamp(1.0 - d / r, 0.0, 1.0);
+    }
+    fn unused(x: float) -> float {
+        return x + 1.0;
+    }
+    fn vertex() -> vec4 {
+        pos = geom;
+        debug = vec4(1.0, 0.0, 1.0, 1.0);
+        let offset = vec2(2.0 * 0.5, sqrt(16.0) - 4.0);
+        return vec4(rect.xy + geom * rect.zw + offset, 0.0, 1.0);
+    }
+    fn pixel() -> vec4 {
+        let acc: Acc;
+        for i from 0 to STEPS {
+            acc.push(square(pos.x) + float(i) * 0.0 + square(pos.y));
+        }
+        let d = length(pos - vec2(0.5));
+        let glow = falloff(d, 0.25 + 0.25) * (pos.x * pos.y + 1.0);
+        let edge = falloff(d, 0.5) * (pos.x * pos.y + 1.0);
+        let never = edge * 3.0;
+        if STEPS > 2 {
+            glow += 0.1;
+        }
+        return vec4(acc.mean() * 1.0, glow, edge, 1.0);
+    }
+"#;
+
+fn compile(live_values: &str, code: &str, options: ShaderCompileOptions) -> (LiveStyles, ShaderAst) {
+    common::compile_shader("optimise", live_values, code, options).unwrap()
+}
+
+#[test]
+fn shrinks_shader() {
+    let (_, plain) = compile("", SHADER, OPTIONS);
+    let (_, optimised) = compile("", SHADER, ShaderCompileOptions {optimisations: ShaderOptimisations::ALL, ..OPTIONS});
+    let (plain_size, optimised_size) = (optimise::shader_size(&plain), optimise::shader_size(&optimised));
+    assert!(optimised_size < plain_size, "{} is not smaller than {}", optimised_size, plain_size);
+    // every pass on its own makes the shader smaller as well
+    let passes = [
+        ShaderOptimisations {remove_dead_code: true, ..ShaderOptimisations::NONE},
+        ShaderOptimisations {fold_consts: true, ..ShaderOptimisations::NONE},
+        ShaderOptimisations {inline_fns: true, ..ShaderOptimisations::NONE},
+        ShaderOptimisations {eliminate_common_subexprs: true, ..ShaderOptimisations::NONE},
+    ];
+    for optimisations in passes.iter() {
+        let (_, optimised) = compile("", SHADER, ShaderCompileOptions {optimisations: *optimisations, ..OPTIONS});
+        assert!(optimise::shader_size(&optimised) < plain_size, "{:?} doesn't shrink the shader", optimisations);
+    }
+}
+
+#[test]
+fn preserves_results() {
+    let (plain_styles, plain) = compile("", SHADER, OPTIONS);
+    let (optimised_styles, optimised) = compile("", SHADER, ShaderCompileOptions {optimisations: ShaderOptimisations::ALL, ..OPTIONS});
+    let mut plain = Interpreter::new(&plain, &plain_styles);
+    let mut optimised = Interpreter::new(&optimised, &optimised_styles);
+    for (x, y) in [(0.0, 0.0), (0.25, 0.75), (0.5, 0.5), (1.0, 0.3)].iter() {
+        for interpreter in [&mut plain, &mut optimised].iter_mut() {
+            interpreter.set_var("geom", Vec2 {x: *x, y: *y});
+            interpreter.set_var("rect", Vec4 {x: 10.0, y: 20.0, z: 100.0, w: 50.0});
+            interpreter.set_var("pos", Vec2 {x: *x, y: *y});
+        }
+        assert_eq!(optimised.run_vertex().unwrap(), plain.run_vertex().unwrap());
+        assert_eq!(optimised.run_pixel().unwrap(), plain.run_pixel().unwrap());
+    }
+}
+
+#[test]
+fn keeps_live_values() {
+    let options = ShaderCompileOptions {
+        gather_all: true,
+        create_const_table: true,
+        optimisations: ShaderOptimisations::ALL,
+        ..OPTIONS
+    };
+    let (live_styles, shader) = compile("self::radius: 0.25;", r#"
+        varying pos: vec2;
+        fn vertex() -> vec4 {
+            return vec4(0.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(length(pos) * (self::radius * 2.0));
+        }
+    "#, options);
+    let pixel = generate_glsl::generate_fragment_shader(&shader, &live_styles, options);
+    assert!(pixel.contains("mpsc_const_table"), "{}", pixel);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use makepad_live_compiler::generate_glsl::{self, GlslVersion};
+use makepad_live_compiler::generate_hlsl;
+use makepad_live_compiler::generate_metal;
+use makepad_live_compiler::livestyles::LiveStyles;
+use makepad_live_compiler::shaderast::ShaderAst;
+use common::OPTIONS;
+
+fn compile(code: &str) -> Result<(LiveStyles, ShaderAst), String> {
+    common::compile_shader("textures", "", code, OPTIONS)
+}
+
+const SKY: &str = r#"
+    texture sky: textureCube;
+    texture noise: texture3D;
+    varying dir: vec3;
+    fn vertex() -> vec4 {
+        dir = vec3(1.0, 0.0, 0.0);
+        return vec4(0.0);
+    }
+    fn pixel() -> vec4 {
+        return sample_cube(sky, dir) * sample3d(noise, dir).x;
+    }
+"#;
+
+#[test]
+fn cube_and_volume_textures() {
+    let (live_styles, shader_ast) = compile(SKY).unwrap();
+    // ES 1.00 can't sample the volume, the #version 100 platforms stop at the check
+    let err = generate_glsl::check_shader_support(&shader_ast, GlslVersion::Es100).unwrap_err();
+    assert_eq!(err.message, "texture noise is a texture3D, which GLSL ES 1.00 can't sample");
+    assert!(generate_glsl::check_shader_support(&shader_ast, GlslVersion::Es300).is_ok());
+    let glsl = generate_glsl::generate_fragment_shader_with_version(&shader_ast, &live_styles, OPTIONS, GlslVersion::Es300);
+    assert!(glsl.contains("uniform samplerCube sky;"));
+    assert!(glsl.contains("uniform sampler3D noise;"));
+    assert!(glsl.contains("vec4 sample_cube(samplerCube sampler, vec3 dir) { return texture(sampler, dir); }"));
+    assert!(glsl.contains("return texture(sampler, vec3(pos.x, 1.0 - pos.y, pos.z));"));
+    assert!(glsl.contains("return (sample_cube(sky, dir) * sample3d(noise, dir).x);"));
+    let hlsl = generate_hlsl::generate_shader(&shader_ast, &live_styles, OPTIONS);
+    assert!(hlsl.contains("TextureCube sky: register(t0);"));
+    assert!(hlsl.contains("Texture3D noise: register(t1);"));
+    assert!(hlsl.contains("float4 sample_cube(TextureCube tex, float3 dir)"));
+    let metal = generate_metal::generate_shader(&shader_ast, &live_styles, OPTIONS);
+    assert!(metal.contains("texturecube<float> sky [[texture(0)]];"));
+    assert!(metal.contains("texture3d<float> noise [[texture(1)]];"));
+    assert!(metal.contains("float4 sample3d(texture3d<float> tex, float3 pos)"));
+}
+
+#[test]
+fn sample_fns_only_for_declared_textures() {
+    let (live_styles, shader_ast) = compile(r#"
+        texture image: texture2D;
+        fn vertex() -> vec4 {
+            return vec4(0.0);
+        }
+        fn pixel() -> vec4 {
+            return sample2d(image, vec2(0.5));
+        }
+    "#).unwrap();
+    let glsl = generate_glsl::generate_fragment_shader(&shader_ast, &live_styles, OPTIONS);
+    assert!(!glsl.contains("samplerCube"));
+    assert!(!glsl.contains("sampler3D"));
+
+    // a cube alone is fine for ES 1.00
+    let (live_styles, shader_ast) = compile(r#"
+        texture sky: textureCube;
+        fn vertex() -> vec4 {
+            return vec4(0.0);
+        }
+        fn pixel() -> vec4 {
+            return sample_cube(sky, vec3(1.0, 0.0, 0.0));
+        }
+    "#).unwrap();
+    assert!(generate_glsl::check_shader_support(&shader_ast, GlslVersion::Es100).is_ok());
+    let glsl = generate_glsl::generate_fragment_shader(&shader_ast, &live_styles, OPTIONS);
+    assert!(glsl.contains("vec4 sample_cube(samplerCube sampler, vec3 dir) { return textureCube(sampler, dir); }"));
+    assert!(!glsl.contains("sampler3D"));
+}
+
+#[test]
+fn texture_ty_errors() {
+    let err = compile(r#"
+        texture sky: textureCube;
+        fn vertex() -> vec4 {
+            return vec4(0.0);
+        }
+        fn pixel() -> vec4 {
+            return sample_cube(sky, vec2(0.5));
+        }
+    "#).err().unwrap();
+    assert!(err.contains("sample_cube"), "{}", err);
+
+    let err = compile(r#"
+        texture sky: vec3;
+        fn vertex() -> vec4 {
+            return vec4(0.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(0.0);
+        }
+    "#).err().unwrap();
+    assert!(err.contains("texture must be a texture2D, textureCube or texture3D"), "{}", err);
+}
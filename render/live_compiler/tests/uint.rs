@@ -0,0 +1,117 @@
+mod common;
+
+use makepad_live_compiler::generate_glsl::{self, GlslVersion};
+use makepad_live_compiler::generate_hlsl;
+use makepad_live_compiler::generate_metal;
+use makepad_live_compiler::livestyles::LiveStyles;
+use makepad_live_compiler::shaderast::ShaderAst;
+use common::OPTIONS;
+
+fn compile(code: &str) -> Result<(LiveStyles, ShaderAst), String> {
+    common::compile_shader("uint", "", code, OPTIONS)
+}
+
+const HASH: &str = r#"
+    fn hash(x: uint) -> uint {
+        let h = x * 747796405u + 3u;
+        h ^= h >> 16;
+        return min(h % 1000u, 999u);
+    }
+    fn vertex() -> vec4 {
+        let v = uvec2(3u, 4u) & uvec2(1u);
+        return vec4(float(hash(v.x + 2u * 4u)));
+    }
+    fn pixel() -> vec4 {
+        return vec4(1.0);
+    }
+"#;
+
+#[test]
+fn uint_es300() {
+    let (live_styles, shader_ast) = compile(HASH).unwrap();
+    let glsl = generate_glsl::generate_vertex_shader_with_version(&shader_ast, &live_styles, OPTIONS, GlslVersion::Es300);
+    assert!(glsl.contains("uint hash(uint x) {"));
+    assert!(glsl.contains("uint h = ((x * 747796405u) + 3u);"));
+    assert!(glsl.contains("(h ^= (h >> 16));"));
+    assert!(glsl.contains("return min((h % 1000u), 999u);"));
+    assert!(glsl.contains("uvec2 v = (uvec2(3u, 4u) & uvec2(1u));"));
+    // the constant operand is folded
+    assert!(glsl.contains("(v.x + 8u)"));
+    assert!(!glsl.contains("mpsc_rem"));
+    assert!(!glsl.contains("attribute "));
+    let glsl = generate_glsl::generate_fragment_shader_with_version(&shader_ast, &live_styles, OPTIONS, GlslVersion::Es300);
+    assert!(glsl.contains("out vec4 mpsc_frag_color;"));
+    assert!(glsl.contains("mpsc_frag_color = pixel"));
+}
+
+#[test]
+fn uint_es100_fallback() {
+    let (live_styles, shader_ast) = compile(HASH).unwrap();
+    let glsl = generate_glsl::generate_vertex_shader(&shader_ast, &live_styles, OPTIONS);
+    assert!(glsl.contains("int hash(int x) {"));
+    assert!(glsl.contains("int h = ((x * 747796405) + 3);"));
+    assert!(glsl.contains("(h = mpsc_bit_xor(h, mpsc_shr(h, 16)));"));
+    assert!(glsl.contains("return int(min(float(mpsc_rem(h, 1000)), float(999)));"));
+    assert!(glsl.contains("ivec2 v = mpsc_bit_and(ivec2(3, 4), ivec2(1));"));
+    assert!(!glsl.contains("uint"));
+    assert!(!glsl.contains("uvec"));
+}
+
+#[test]
+fn uint_hlsl_metal() {
+    let (live_styles, shader_ast) = compile(HASH).unwrap();
+    let hlsl = generate_hlsl::generate_shader(&shader_ast, &live_styles, OPTIONS);
+    assert!(hlsl.contains("uint h = ((x * 747796405u) + 3u);"));
+    assert!(hlsl.contains("uint2 v = "));
+    let metal = generate_metal::generate_shader(&shader_ast, &live_styles, OPTIONS);
+    assert!(metal.contains("uint h = ((x * 747796405u) + 3u);"));
+    assert!(metal.contains("uint2 v = (uint2(3u, 4u) & uint2(1u));"));
+}
+
+#[test]
+fn uint_errors() {
+    let err = compile(r#"
+        fn vertex() -> vec4 {
+            let x = 3u;
+            let y = -x;
+            return vec4(1.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(1.0);
+        }
+    "#).err().unwrap();
+    assert!(err.contains("can't apply unary operator"), "{}", err);
+
+    let err = compile(r#"
+        fn vertex() -> vec4 {
+            let x = -3u;
+            return vec4(1.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(1.0);
+        }
+    "#).err().unwrap();
+    assert!(err.contains("negative unsigned integer literal"), "{}", err);
+
+    let err = compile(r#"
+        fn vertex() -> vec4 {
+            let x = 3u + 1;
+            return vec4(1.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(1.0);
+        }
+    "#).err().unwrap();
+    assert!(err.contains("can't apply binary operator"), "{}", err);
+
+    let err = compile(r#"
+        fn vertex() -> vec4 {
+            let x = 4294967296u;
+            return vec4(1.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(1.0);
+        }
+    "#).err().unwrap();
+    assert!(err.contains("unsigned integer literal out of range"), "{}", err);
+}
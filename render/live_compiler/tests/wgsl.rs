@@ -0,0 +1,138 @@
+mod common;
+
+use makepad_live_compiler::generate_wgsl;
+use makepad_live_compiler::livestyles::LiveStyles;
+use makepad_live_compiler::shaderast::ShaderAst;
+use common::OPTIONS;
+
+fn compile(code: &str) -> Result<(LiveStyles, ShaderAst), String> {
+    common::compile_shader("wgsl", "", code, OPTIONS)
+}
+
+const QUAD: &str = r#"
+    geometry geom: vec2;
+    instance rect: vec4;
+    instance tint: vec3;
+    instance count: float;
+    uniform camera_projection: mat4 in pass;
+    uniform zoom: float;
+    texture image: texture2D;
+    varying pos: vec2;
+    fn scale(v: vec2, s: float) -> vec2 {
+        return v * s;
+    }
+    fn vertex() -> vec4 {
+        pos = scale(geom, zoom);
+        let n = 0;
+        for i from 0 to 4 {
+            n += i;
+        }
+        return camera_projection * vec4(rect.xy + pos * rect.zw, 0.0, 1.0);
+    }
+    fn pixel() -> vec4 {
+        let c = clamp(tint, 0.0, 1.0);
+        let m = mod(pos.x, 0.5);
+        let b = pos.x > 0.5 ? 1.0 : 0.0;
+        if any(lessThan(pos, vec2(0.0))) {
+            return vec4(0.0);
+        }
+        return sample2d(image, pos) * vec4(c, m + b);
+    }
+"#;
+
+#[test]
+fn wgsl_quad() {
+    let (live_styles, shader_ast) = compile(QUAD).unwrap();
+    let wgsl = generate_wgsl::generate_shader(&shader_ast, &live_styles, OPTIONS);
+    assert!(wgsl.contains("@group(0) @binding(0) var<uniform> mpsc_pass_uniforms: mpsc_pass_Uniforms;"));
+    assert!(wgsl.contains("@group(0) @binding(3) var<uniform> mpsc_default_uniforms: mpsc_default_Uniforms;"));
+    assert!(wgsl.contains("@group(1) @binding(1) var image: texture_2d<f32>;"));
+    assert!(wgsl.contains("var<private> rect: vec4<f32>;"));
+    assert!(wgsl.contains("fn scale(v: vec2<f32>, s: f32) -> vec2<f32> {"));
+    assert!(wgsl.contains("pos = scale(geom, mpsc_default_uniforms.zoom);"));
+    assert!(wgsl.contains("for (var i: i32 = 0; i < 4; i += 1) {"));
+    assert!(wgsl.contains("n += i;"));
+    assert!(wgsl.contains("return (mpsc_pass_uniforms.camera_projection * vec4<f32>((rect.xy + (pos * rect.zw)), 0.0, 1.0));"));
+    assert!(wgsl.contains("var c: vec3<f32> = clamp(tint, vec3<f32>(0.0), vec3<f32>(1.0));"));
+    assert!(wgsl.contains("var m: f32 = mpsc_mod_float(pos.x, 0.5);"));
+    assert!(wgsl.contains("var b: f32 = select(0.0, 1.0, (pos.x > 0.5));"));
+    assert!(wgsl.contains("if (any((pos < vec2<f32>(0.0)))) {"));
+    // geometries and instances are unpacked in InstanceProps order, a component at a time
+    assert!(wgsl.contains(
+        "fn mpsc_vertex_main(@location(0) mpsc_packed_geometry_0: vec2<f32>, @location(1) mpsc_packed_instance_0: vec4<f32>, @location(2) mpsc_packed_instance_1: vec4<f32>) -> mpsc_Varyings {"
+    ));
+    assert!(wgsl.contains("    tint.z = mpsc_packed_instance_1.z;\n    count = mpsc_packed_instance_1.w;\n"));
+    assert!(wgsl.contains("    mpsc_varyings.mpsc_position = vertex();\n"));
+    assert!(wgsl.contains("    mpsc_varyings.mpsc_packed_varying_0.w = pos.x;\n    mpsc_varyings.mpsc_packed_varying_1 = pos.y;\n"));
+    assert!(wgsl.contains("    @location(1) mpsc_packed_varying_1: f32,\n"));
+    assert!(wgsl.contains("fn mpsc_fragment_main(mpsc_varyings: mpsc_Varyings) -> @location(0) vec4<f32> {"));
+    assert!(wgsl.contains("    pos.y = mpsc_varyings.mpsc_packed_varying_1;\n    return pixel();\n"));
+}
+
+#[test]
+fn wgsl_ints_and_matrices() {
+    let (live_styles, shader_ast) = compile(r#"
+        instance id: float;
+        uniform view_transform: mat4 in view;
+        uniform light: vec3 in draw;
+        fn hash(x: uint) -> uint {
+            let h = x;
+            h ^= h >> 16u;
+            return h;
+        }
+        fn vertex() -> vec4 {
+            let i = int(id);
+            let u = hash(uint(i) << 2u);
+            let m = mat3(view_transform);
+            return vec4(m * light, float(u));
+        }
+        fn pixel() -> vec4 {
+            return vec4(1.0);
+        }
+    "#).unwrap();
+    let wgsl = generate_wgsl::generate_shader(&shader_ast, &live_styles, OPTIONS);
+    assert!(wgsl.contains("struct mpsc_draw_Uniforms {\n    @size(16) light: vec3<f32>,\n}"));
+    assert!(wgsl.contains("@group(0) @binding(1) var<uniform> mpsc_view_uniforms: mpsc_view_Uniforms;"));
+    assert!(wgsl.contains("fn hash(x: u32) -> u32 {"));
+    assert!(wgsl.contains("h ^= (h >> u32(16u));"));
+    assert!(wgsl.contains("var u: u32 = hash((u32(i) << u32(2u)));"));
+    assert!(wgsl.contains("fn mpsc_mat3_mat4(x: mat4x4<f32>) -> mat3x3<f32> {"));
+    assert!(wgsl.contains("var m: mat3x3<f32> = mpsc_mat3_mat4(mpsc_view_uniforms.view_transform);"));
+}
+
+#[test]
+fn wgsl_inout_needs_a_function_place() {
+    let (_, shader_ast) = compile(r#"
+        varying pos: vec2;
+        fn nudge(inout v: vec2) {
+            v += vec2(1.0);
+        }
+        fn vertex() -> vec4 {
+            let p = pos;
+            nudge(p);
+            return vec4(p, 0.0, 1.0);
+        }
+        fn pixel() -> vec4 {
+            nudge(pos);
+            return vec4(pos, 0.0, 1.0);
+        }
+    "#).unwrap();
+    let err = generate_wgsl::check_shader_support(&shader_ast).unwrap_err();
+    assert!(err.message.contains("inout parameter `v`"));
+
+    let (_, shader_ast) = compile(r#"
+        fn nudge(inout v: vec2) {
+            for i from 0 to 2 {
+                let v = vec2(1.0);
+            }
+        }
+        fn vertex() -> vec4 {
+            return vec4(1.0);
+        }
+        fn pixel() -> vec4 {
+            return vec4(1.0);
+        }
+    "#).unwrap();
+    let err = generate_wgsl::check_shader_support(&shader_ast).unwrap_err();
+    assert!(err.message.contains("`v` hides the inout parameter"));
+}
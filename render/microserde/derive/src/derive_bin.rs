@@ -1,190 +1,550 @@
 use proc_macro::{TokenStream};
 use crate::macro_lib::*;
 
+// the same hash as makepad_microserde::bin_tag, computed here so tags are literals
+fn bin_tag(name: &str) -> u32 {
+    let mut hash = 0x811c9dc5u32;
+    for b in name.bytes() {
+        hash = (hash ^ b as u32).wrapping_mul(0x01000193);
+    }
+    hash
+}
+
+fn bin_tags_unique(fields: &[Field]) -> bool {
+    let mut tags: Vec<u32> = fields.iter().filter( | f | !f.is_skip()).map( | f | bin_tag(&f.key())).collect();
+    let len = tags.len();
+    tags.sort_unstable();
+    tags.dedup();
+    tags.len() == len
+}
+
+// compact calls the _compact method or helper, ser_bin becomes ser_bin_compact
+fn bin_method(name: &str, compact: bool) -> String {
+    if compact {format!("{}_compact", name)} else {name.to_string()}
+}
+
+// tagged: tag, length, value for every field that is present
+fn ser_bin_tagged_fields(tb: &mut TokenBuilder, fields: &[Field], prefix: &str, compact: bool) {
+    let ser = bin_method("ser_bin", compact);
+    let len_begin = bin_method("makepad_microserde :: ser_bin_len_begin", compact);
+    let len_end = bin_method("makepad_microserde :: ser_bin_len_end", compact);
+    tb.add("let at =").add(&len_begin).add("( s ) ;");
+    for field in fields {
+        if field.is_skip() {
+            continue
+        }
+        if field.is_option() {
+            tb.add("if let Some ( t ) = &").add(prefix).ident(&field.name).add("{");
+        }
+        else {
+            tb.add("{ let t = &").add(prefix).ident(&field.name).add(";");
+        }
+        tb.suf_u32(bin_tag(&field.key())).add(". ser_bin ( s ) ;");
+        tb.add("let at =").add(&len_begin).add("( s ) ;");
+        tb.add("t .").add(&ser).add("( s ) ;").add(&len_end).add("( at , s ) ; }");
+    }
+    tb.add(&len_end).add("( at , s ) ;");
+}
+
+// tagged: every value gets a length so a newer writer can append more
+fn ser_bin_tagged_tuple(tb: &mut TokenBuilder, values: &[String], compact: bool) {
+    let ser = bin_method("ser_bin", compact);
+    let len_begin = bin_method("makepad_microserde :: ser_bin_len_begin", compact);
+    let len_end = bin_method("makepad_microserde :: ser_bin_len_end", compact);
+    tb.add("let at =").add(&len_begin).add("( s ) ;");
+    for value in values {
+        tb.add("{ let at =").add(&len_begin).add("( s ) ;");
+        tb.add(value).add(".").add(&ser).add("( s ) ;").add(&len_end).add("( at , s ) ; }");
+    }
+    tb.add(&len_end).add("( at , s ) ;");
+}
+
+enum BinVariantKind {
+    Tuple(usize),
+    Named(Vec<Field>),
+    Bare,
+}
+
+struct BinVariant {
+    name: String,
+    tag: u32,
+    kind: BinVariantKind,
+}
+
+// collects the variants up front, the derives write a method per encoding from them
+fn eat_bin_variants(parser: &mut TokenParser) -> Result<Vec<BinVariant>, TokenStream> {
+    if !parser.open_brace(){
+        return Err(parser.unexpected())
+    }
+    let mut variants = Vec::new();
+    while !parser.eat_eot(){
+        let attrs = if let Some(attrs) = parser.eat_attributes() {attrs} else {return Err(parser.unexpected())};
+        // parse ident
+        if let Some(name) = parser.eat_any_ident(){
+            let tag = bin_tag(&attrs_value(&attrs, "rename").unwrap_or(name.clone()));
+            let kind = if let Some(types) = parser.eat_all_types(){
+                BinVariantKind::Tuple(types.len())
+            }
+            else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
+                BinVariantKind::Named(fields)
+            }
+            else if parser.is_punct(',') || parser.is_eot(){ // bare variant
+                BinVariantKind::Bare
+            }
+            else{
+                return Err(parser.unexpected());
+            };
+            variants.push(BinVariant {name, tag, kind});
+            parser.eat_punct(',');
+        }
+        else{
+            return Err(parser.unexpected())
+        }
+    }
+    Ok(variants)
+}
+
+fn ser_bin_fn_begin(tb: &mut TokenBuilder, compact: bool) {
+    tb.add("fn").add(&bin_method("ser_bin", compact)).add("( & self , s : & mut Vec < u8 > ) {");
+}
+
 pub fn derive_ser_bin_impl(input: TokenStream) -> TokenStream {
 
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
-    
-    parser.eat_ident("pub");
+
+    let container_attrs = if let Some(attrs) = parser.eat_attributes() {attrs} else {return parser.unexpected()};
+    let tagged = attrs_has(&container_attrs, "tagged");
+    // #[nserde(compact)] types write the compact encoding from ser_bin as well
+    let container_compact = attrs_has(&container_attrs, "compact");
+    parser.eat_visibility();
     if parser.eat_ident("struct"){
         if let Some(name) = parser.eat_any_ident(){
-            let generic = parser.eat_generic();
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
             let types = parser.eat_all_types();
-            let where_clause = parser.eat_where_clause(Some("SerBin"));
+            parser.eat_where_predicates(&mut generics);
+            let where_clause = generics.where_clause("makepad_microserde :: SerBin");
 
-            tb.add("impl").stream(generic.clone());
-            tb.add("SerBin for").ident(&name).stream(generic).stream(where_clause);
-            tb.add("{ fn ser_bin ( & self , s : & mut Vec < u8 > ) {");
+            tb.add("impl").stream(generics.impl_generics());
+            tb.add("SerBin for").ident(&name).stream(generics.type_generics()).stream(where_clause).add("{");
 
             if let Some(types) = types{
-                for i in 0..types.len(){
-                     tb.add("self .").unsuf_usize(i).add(". ser_bin ( s ) ;");
+                for &method_compact in &[false, true] {
+                    let compact = container_compact || method_compact;
+                    ser_bin_fn_begin(&mut tb, method_compact);
+                    if tagged {
+                        let values: Vec<String> = (0..types.len()).map( | i | format!("self . {}", i)).collect();
+                        ser_bin_tagged_tuple(&mut tb, &values, compact);
+                    }
+                    else {
+                        for i in 0..types.len(){
+                             tb.add("self .").unsuf_usize(i).add(".").add(&bin_method("ser_bin", compact)).add("( s ) ;");
+                        }
+                    }
+                    tb.add("}");
                 }
             }
-            else if let Some(fields) = parser.eat_all_struct_fields(){ 
-                for (field,_ty) in fields{
-                    tb.add("self .").ident(&field).add(". ser_bin ( s ) ;");
+            else if let Some(fields) = parser.eat_all_struct_fields(){
+                if tagged && !bin_tags_unique(&fields) {
+                    return error("Two fields hash to the same tag, rename one")
+                }
+                for &method_compact in &[false, true] {
+                    let compact = container_compact || method_compact;
+                    ser_bin_fn_begin(&mut tb, method_compact);
+                    if tagged {
+                        ser_bin_tagged_fields(&mut tb, &fields, "self .", compact);
+                    }
+                    else {
+                        for field in &fields{
+                            if !field.is_skip() {
+                                tb.add("self .").ident(&field.name).add(".").add(&bin_method("ser_bin", compact)).add("( s ) ;");
+                            }
+                        }
+                    }
+                    tb.add("}");
                 }
             }
             else{
                 return parser.unexpected()
             }
-            tb.add("} } ;"); 
+            tb.add("} ;");
             return tb.end();
         }
     }
     else if parser.eat_ident("enum"){
         if let Some(name) = parser.eat_any_ident(){
-            let generic = parser.eat_generic();
-            let where_clause = parser.eat_where_clause(Some("SerBin"));
-            
-            tb.add("impl").stream(generic.clone());
-            tb.add("SerBin for").ident(&name).stream(generic).stream(where_clause);
-            tb.add("{ fn ser_bin ( & self , s : & mut Vec < u8 > ) {");
-            tb.add("match self {");
-            
-            if !parser.open_brace(){
-                return parser.unexpected()
-            }
-            let mut index = 0;
-            while !parser.eat_eot(){
-                // parse ident
-                if let Some(variant) = parser.eat_any_ident(){
-                    if let Some(types) = parser.eat_all_types(){
-                        tb.add("Self ::").ident(&variant).add("(");
-                        for i in 0..types.len(){
-                            tb.ident(&format!("n{}", i)).add(",");
-                        }
-                        tb.add(") => {").suf_u16(index).add(". ser_bin ( s ) ;");
-                        for i in 0..types.len(){
-                            tb.ident(&format!("n{}", i)).add(". ser_bin ( s ) ;");
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
+            parser.eat_where_predicates(&mut generics);
+            let where_clause = generics.where_clause("makepad_microserde :: SerBin");
+
+            let variants = match eat_bin_variants(&mut parser) {Ok(variants) => variants, Err(err) => return err};
+            if tagged {
+                let mut tags: Vec<u32> = variants.iter().map( | v | v.tag).collect();
+                tags.sort_unstable();
+                tags.dedup();
+                if tags.len() != variants.len() {
+                    return error("Two variants hash to the same tag, rename one")
+                }
+                for variant in &variants {
+                    if let BinVariantKind::Named(fields) = &variant.kind {
+                        if !bin_tags_unique(fields) {
+                            return error("Two fields hash to the same tag, rename one")
                         }
-                        tb.add("}");
                     }
-                    else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
-                        tb.add("Self ::").ident(&variant).add("{");
-                        for (field, _ty) in fields.iter(){
-                            tb.ident(field).add(",");
+                }
+            }
+
+            tb.add("impl").stream(generics.impl_generics());
+            tb.add("SerBin for").ident(&name).stream(generics.type_generics()).stream(where_clause).add("{");
+            for &method_compact in &[false, true] {
+                let compact = container_compact || method_compact;
+                let ser = bin_method("ser_bin", compact);
+                ser_bin_fn_begin(&mut tb, method_compact);
+                tb.add("match self {");
+                for (index, variant) in variants.iter().enumerate() {
+                    match &variant.kind {
+                        BinVariantKind::Tuple(count) => {
+                            tb.add("Self ::").ident(&variant.name).add("(");
+                            for i in 0..*count{
+                                tb.ident(&format!("n{}", i)).add(",");
+                            }
+                            tb.add(") => {");
+                            if tagged {
+                                tb.suf_u32(variant.tag).add(". ser_bin ( s ) ;");
+                                let values: Vec<String> = (0..*count).map( | i | format!("n{}", i)).collect();
+                                ser_bin_tagged_tuple(&mut tb, &values, compact);
+                            }
+                            else {
+                                tb.suf_u16(index as u16).add(".").add(&ser).add("( s ) ;");
+                                for i in 0..*count{
+                                    tb.ident(&format!("n{}", i)).add(".").add(&ser).add("( s ) ;");
+                                }
+                            }
+                            tb.add("}");
                         }
-                        tb.add("} => {").suf_u16(index).add(". ser_bin ( s ) ;");
-                        for (field, _ty) in fields{
-                            tb.ident(&field).add(". ser_bin ( s ) ;");
+                        BinVariantKind::Named(fields) => {
+                            tb.add("Self ::").ident(&variant.name).add("{");
+                            for field in fields.iter(){
+                                tb.ident(&field.name).add(",");
+                            }
+                            tb.add("} => {");
+                            if tagged {
+                                tb.suf_u32(variant.tag).add(". ser_bin ( s ) ;");
+                                ser_bin_tagged_fields(&mut tb, fields, "", compact);
+                            }
+                            else {
+                                tb.suf_u16(index as u16).add(".").add(&ser).add("( s ) ;");
+                                for field in fields{
+                                    if !field.is_skip() {
+                                        tb.ident(&field.name).add(".").add(&ser).add("( s ) ;");
+                                    }
+                                }
+                            }
+                            tb.add("}");
+                        }
+                        BinVariantKind::Bare => {
+                            tb.add("Self ::").ident(&variant.name).add("=> {");
+                            if tagged {
+                                tb.suf_u32(variant.tag).add(". ser_bin ( s ) ;");
+                                ser_bin_tagged_tuple(&mut tb, &[], compact);
+                            }
+                            else {
+                                tb.suf_u16(index as u16).add(".").add(&ser).add("( s ) ;");
+                            }
+                            tb.add("}");
                         }
-                        tb.add("}");
-                    }
-                    else if parser.is_punct(',') || parser.is_eot(){ // bare variant
-                        tb.add("Self ::").ident(&variant).add("=> {");
-                        tb.suf_u16(index).add(". ser_bin ( s ) ; }");
-                    }
-                    else{
-                        return parser.unexpected();
                     }
-                    index += 1;
-                    parser.eat_punct(',');
-                }
-                else{
-                    return parser.unexpected()
                 }
+                tb.add("} }");
             }
-            tb.add("} } } ;");
+            tb.add("} ;");
             return tb.end();
         }
     }
     return parser.unexpected()
-} 
+}
+
+// reads a value out of `d`, borrowed reads go through DeBinRef so &str and &[u8] point into `d`
+fn de_bin_value(tb: &mut TokenBuilder, borrowed: bool, compact: bool, d: &str) {
+    if borrowed {
+        tb.add("DeBinRef :: de_bin_ref ( o ,").add(d).add(")");
+    }
+    else {
+        tb.add("DeBin ::").add(&bin_method("de_bin", compact)).add("( o ,").add(d).add(")");
+    }
+}
 
-pub fn derive_de_bin_impl(input: TokenStream) -> TokenStream {
+// impl<'de, ..> DeBinRef<'de> for Name<..> where 'de outlives the lifetimes of Name
+fn de_bin_impl_header(tb: &mut TokenBuilder, generics: &Generics, name: &str, borrowed: bool) {
+    if !borrowed {
+        tb.add("impl").stream(generics.impl_generics());
+        tb.add("DeBin for").ident(name).stream(generics.type_generics()).stream(generics.where_clause("makepad_microserde :: DeBin"));
+        tb.add("{");
+        return
+    }
+    tb.add("impl <").lifetime("de").add(",");
+    for param in &generics.params {
+        for tt in &param.decl {
+            tb.extend(tt.clone());
+        }
+        tb.add(",");
+    }
+    tb.add("> makepad_microserde :: DeBinRef <").lifetime("de").add("> for").ident(name).stream(generics.type_generics());
+    tb.add("where");
+    for predicate in &generics.predicates {
+        for tt in predicate {
+            tb.extend(tt.clone());
+        }
+        tb.add(",");
+    }
+    for param in &generics.params {
+        if param.is_lifetime {
+            tb.lifetime("de").add(":");
+            for tt in &param.name {
+                tb.extend(tt.clone());
+            }
+            tb.add(",");
+        }
+        else if param.is_type {
+            for tt in &param.name {
+                tb.extend(tt.clone());
+            }
+            tb.add(": makepad_microserde :: DeBinRef <").lifetime("de").add("> ,");
+        }
+    }
+    tb.add("{");
+}
+
+fn de_bin_fn_begin(tb: &mut TokenBuilder, borrowed: bool, compact: bool) {
+    if borrowed {
+        tb.add("fn de_bin_ref ( o : & mut usize , d : & ").lifetime("de").add("[ u8 ] )");
+    }
+    else {
+        tb.add("fn").add(&bin_method("de_bin", compact)).add("( o : & mut usize , d : & [ u8 ] )");
+    }
+    tb.add("-> std :: result :: Result < Self , makepad_microserde :: DeBinErr > {");
+}
+
+// DeBinRef only reads the fixed width encoding, DeBin writes a method for both
+fn de_bin_methods(borrowed: bool) -> &'static [bool] {
+    if borrowed {&[false]} else {&[false, true]}
+}
+
+// skipped fields aren't in the stream and come from their default
+fn de_bin_field(tb: &mut TokenBuilder, field: &Field, borrowed: bool, compact: bool) {
+    tb.ident(&field.name).add(":");
+    if field.is_skip() {
+        field.default_value(tb);
+        tb.add(",");
+    }
+    else {
+        de_bin_value(tb, borrowed, compact, "d");
+        err_at_field(tb, &field.name);
+        tb.add("? ,");
+    }
+}
+
+// tagged: reads the fields in any order, skips unknown tags and fills in missing fields
+fn de_bin_tagged_named(tb: &mut TokenBuilder, path: &str, fields: &[Field], container_default: bool, borrowed: bool, compact: bool) {
+    let len = bin_method("makepad_microserde :: de_bin_len", compact);
+    tb.add("let end =").add(&len).add("( o , d ) ? ;");
+    for field in fields {
+        if !field.is_skip() {
+            tb.add("let mut").ident(&format!("_{}", field.name)).add("= None ;");
+        }
+    }
+    tb.add("while * o < end {");
+    tb.add("let tag : u32 = DeBin :: de_bin ( o , d ) ? ;");
+    tb.add("let field_end =").add(&len).add("( o , d . split_at ( end ) . 0 ) ? ;");
+    tb.add("match tag {");
+    for field in fields {
+        if !field.is_skip() {
+            tb.suf_u32(bin_tag(&field.key())).add("=>").ident(&format!("_{}", field.name));
+            tb.add("= Some (");
+            de_bin_value(tb, borrowed, compact, "d . split_at ( field_end ) . 0");
+            err_at_field(tb, &field.name);
+            tb.add("? ) ,");
+        }
+    }
+    tb.add("_ => ( ) } * o = field_end ; }");
+    tb.add(path).add("{");
+    for field in fields {
+        tb.ident(&field.name).add(":");
+        if field.is_skip() {
+            field.default_value(tb);
+        }
+        else if field.is_option() {
+            tb.ident(&format!("_{}", field.name));
+        }
+        else {
+            tb.add("match").ident(&format!("_{}", field.name)).add("{ Some ( t ) => t , None => {");
+            if field.is_default() {
+                field.default_value(tb);
+            }
+            else if container_default {
+                tb.add("< Self as Default > :: default ( ) .").ident(&field.name);
+            }
+            else {
+                tb.add("return std :: result :: Result :: Err ( makepad_microserde :: DeBinErr { o : * o , l :");
+                tb.unsuf_usize(0).add(", s : d . len ( ) , msg :").string(&format!("missing field {}", field.key())).add(". to_string ( ) , path : Default :: default ( ) } )");
+            }
+            tb.add("} }");
+        }
+        tb.add(",");
+    }
+    tb.add("}");
+}
+
+// tagged: values in order, each behind a length, extra values from a newer writer are skipped
+fn de_bin_tagged_tuple(tb: &mut TokenBuilder, path: &str, count: usize, borrowed: bool, compact: bool) {
+    let len = bin_method("makepad_microserde :: de_bin_len", compact);
+    tb.add("let end =").add(&len).add("( o , d ) ? ;");
+    tb.add("let r =").add(path).add("(");
+    for index in 0..count {
+        tb.add("{ let value_end =").add(&len).add("( o , d . split_at ( end ) . 0 ) ? ;");
+        tb.add("let r =");
+        de_bin_value(tb, borrowed, compact, "d . split_at ( value_end ) . 0");
+        err_at_index(tb, index);
+        tb.add("? ; * o = value_end ; r } ,");
+    }
+    tb.add(") ; * o = end ; r");
+}
+
+// borrowed derives DeBinRef, the same format read without copying strings and byte slices
+pub fn derive_de_bin_impl(input: TokenStream, borrowed: bool) -> TokenStream {
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
-    
-    parser.eat_ident("pub");
+
+    let container_attrs = if let Some(attrs) = parser.eat_attributes() {attrs} else {return parser.unexpected()};
+    let tagged = attrs_has(&container_attrs, "tagged");
+    let container_default = attrs_has(&container_attrs, "default");
+    let container_compact = attrs_has(&container_attrs, "compact");
+    if borrowed && container_compact {
+        return error("DeBinRef only reads the fixed width encoding, it can't derive for a compact type")
+    }
+    parser.eat_visibility();
     if parser.eat_ident("struct"){
         if let Some(name) = parser.eat_any_ident(){
-            let generic = parser.eat_generic();
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
             let types = parser.eat_all_types();
-            let where_clause = parser.eat_where_clause(Some("DeBin"));
+            parser.eat_where_predicates(&mut generics);
 
-            tb.add("impl").stream(generic.clone());
-            tb.add("DeBin for").ident(&name).stream(generic).stream(where_clause);
-            tb.add("{ fn de_bin ( o : & mut usize , d : & [ u8 ] )");
-            tb.add("-> std :: result :: Result < Self , makepad_microserde :: DeBinErr > { ");
-            tb.add("std :: result :: Result :: Ok ( Self");
+            de_bin_impl_header(&mut tb, &generics, &name, borrowed);
 
             if let Some(types) = types{
-                tb.add("(");
-                for _ in 0..types.len(){
-                     tb.add("DeBin :: de_bin ( o , d ) ? ,");
+                for &method_compact in de_bin_methods(borrowed) {
+                    let compact = container_compact || method_compact;
+                    de_bin_fn_begin(&mut tb, borrowed, method_compact);
+                    tb.add("std :: result :: Result :: Ok ( {");
+                    if tagged {
+                        de_bin_tagged_tuple(&mut tb, "Self", types.len(), borrowed, compact);
+                    }
+                    else {
+                        tb.add("Self (");
+                        for index in 0..types.len(){
+                             de_bin_value(&mut tb, borrowed, compact, "d");
+                             err_at_index(&mut tb, index);
+                             tb.add("? ,");
+                        }
+                        tb.add(")");
+                    }
+                    tb.add("} ) }");
                 }
-                tb.add(")");
             }
-            else if let Some(fields) = parser.eat_all_struct_fields(){ 
-                tb.add("{");
-                for (field,_ty) in fields{
-                    tb.ident(&field).add(": DeBin :: de_bin ( o , d ) ? ,");
+            else if let Some(fields) = parser.eat_all_struct_fields(){
+                for &method_compact in de_bin_methods(borrowed) {
+                    let compact = container_compact || method_compact;
+                    de_bin_fn_begin(&mut tb, borrowed, method_compact);
+                    tb.add("std :: result :: Result :: Ok ( {");
+                    if tagged {
+                        de_bin_tagged_named(&mut tb, "Self", &fields, container_default, borrowed, compact);
+                    }
+                    else {
+                        tb.add("Self {");
+                        for field in &fields{
+                            de_bin_field(&mut tb, field, borrowed, compact);
+                        }
+                        tb.add("}");
+                    }
+                    tb.add("} ) }");
                 }
-                tb.add("}");
             }
             else{
                 return parser.unexpected()
             }
-            tb.add(") } } ;"); 
+            tb.add("} ;");
             return tb.end();
         }
     }
     else if parser.eat_ident("enum"){
         if let Some(name) = parser.eat_any_ident(){
-            let generic = parser.eat_generic();
-            let where_clause = parser.eat_where_clause(Some("DeBin"));
-            
-            tb.add("impl").stream(generic.clone());
-            tb.add("DeBin for").ident(&name).stream(generic).stream(where_clause);
-            tb.add("{ fn de_bin ( o : & mut usize , d : & [ u8 ] )");
-            tb.add("-> std :: result :: Result < Self , makepad_microserde :: DeBinErr > {");
-            tb.add("let id : u16 = DeBin :: de_bin ( o , d ) ? ;");
-            tb.add("match id {");
-            
-            if !parser.open_brace(){
-                return parser.unexpected()
-            }
-            let mut index = 0;
-            while !parser.eat_eot(){
-                // parse ident
-                if let Some(variant) = parser.eat_any_ident(){
-                    tb.suf_u16(index as u16).add("=> {");
-                    tb.add("std :: result :: Result :: Ok ( Self ::");
-                    if let Some(types) = parser.eat_all_types(){
-                        tb.ident(&variant).add("(");
-                        for _ in 0..types.len(){
-                            tb.add("DeBin :: de_bin ( o , d ) ? ,");
-                        }
-                        tb.add(")");
-                    }
-                    else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
-                        tb.ident(&variant).add("{");
-                        for (field, _ty) in fields.iter(){
-                            tb.ident(field).add(": DeBin :: de_bin ( o , d ) ? ,");
-                        }
-                        tb.add("}");
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
+            parser.eat_where_predicates(&mut generics);
+
+            let variants = match eat_bin_variants(&mut parser) {Ok(variants) => variants, Err(err) => return err};
+
+            de_bin_impl_header(&mut tb, &generics, &name, borrowed);
+            for &method_compact in de_bin_methods(borrowed) {
+                let compact = container_compact || method_compact;
+                de_bin_fn_begin(&mut tb, borrowed, method_compact);
+                if tagged {
+                    tb.add("let id : u32 = DeBin :: de_bin ( o , d ) ? ;");
+                }
+                else {
+                    tb.add("let id : u16 = DeBin ::").add(&bin_method("de_bin", compact)).add("( o , d ) ? ;");
+                }
+                tb.add("match id {");
+                for (index, variant) in variants.iter().enumerate() {
+                    if tagged {
+                        tb.suf_u32(variant.tag);
                     }
-                    else if parser.is_punct(',') || parser.is_eot(){ // bare variant
-                        tb.ident(&variant);
+                    else {
+                        tb.suf_u16(index as u16);
                     }
-                    else{
-                        return parser.unexpected();
+                    tb.add("=> {");
+                    tb.add("std :: result :: Result :: Ok ( {");
+                    let path = format!("Self :: {}", variant.name);
+                    match &variant.kind {
+                        BinVariantKind::Tuple(count) => {
+                            if tagged {
+                                de_bin_tagged_tuple(&mut tb, &path, *count, borrowed, compact);
+                            }
+                            else {
+                                tb.add(&path).add("(");
+                                for index in 0..*count{
+                                    de_bin_value(&mut tb, borrowed, compact, "d");
+                                    err_at_index(&mut tb, index);
+                                    tb.add("? ,");
+                                }
+                                tb.add(")");
+                            }
+                        }
+                        BinVariantKind::Named(fields) => {
+                            if tagged {
+                                de_bin_tagged_named(&mut tb, &path, fields, false, borrowed, compact);
+                            }
+                            else {
+                                tb.add(&path).add("{");
+                                for field in fields.iter(){
+                                    de_bin_field(&mut tb, field, borrowed, compact);
+                                }
+                                tb.add("}");
+                            }
+                        }
+                        BinVariantKind::Bare => {
+                            if tagged {
+                                tb.add("* o =").add(&bin_method("makepad_microserde :: de_bin_len", compact)).add("( o , d ) ? ;");
+                            }
+                            tb.add(&path);
+                        }
                     }
-                    
-                    tb.add(") }");
-                    index += 1;
-                    parser.eat_punct(',');
-                }
-                else{
-                    return parser.unexpected()
+                    tb.add("} ) }");
                 }
-            } 
-            tb.add("_ => std :: result :: Result :: Err ( makepad_microserde :: DeBinErr { o : * o , l :");
-            tb.unsuf_usize(1).add(", s : d . len ( ) , msg : ").string(&name).add(". to_string ( ) } )");
-            tb.add("} } } ;");
+                tb.add("_ => std :: result :: Result :: Err ( makepad_microserde :: DeBinErr { o : * o , l :");
+                tb.unsuf_usize(1).add(", s : d . len ( ) , msg : ").string(&name).add(". to_string ( ) , path : Default :: default ( ) } )");
+                tb.add("} }");
+            }
+            tb.add("} ;");
             return tb.end();
         }
     }
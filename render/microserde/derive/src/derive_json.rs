@@ -1,23 +1,442 @@
 use proc_macro::{TokenStream};
 use crate::macro_lib::*;
 
+// writes "key":value, for every field, prefix is how a field is reached (self . or a match binding)
+fn ser_json_fields(tb: &mut TokenBuilder, fields: &[Field], prefix: &str) {
+    for field in fields {
+        if field.is_skip() {
+            continue
+        }
+        if field.is_flatten() {
+            tb.add("makepad_microserde :: SerJsonFlat :: ser_json_flat ( &").add(prefix).ident(&field.name).add(", d , s ) ;");
+        }
+        else if field.is_option() {
+            tb.add("if let Some ( t ) = &").add(prefix).ident(&field.name).add("{");
+            tb.add("s . field ( d + 1 ,").string(&field.key()).add(") ;");
+            tb.add("t . ser_json ( d + 1 , s ) ; s . conl ( ) ; } ;");
+        }
+        else {
+            tb.add("s . field ( d + 1 ,").string(&field.key()).add(" ) ;");
+            tb.add(prefix).ident(&field.name).add(". ser_json ( d + 1 , s ) ; s . conl ( ) ;");
+        }
+    }
+}
+
+// the per field slot a partially read struct keeps
+fn de_json_slot_type(tb: &mut TokenBuilder, field: &Field) {
+    if field.is_skip() {
+        tb.add("( )");
+    }
+    else if field.is_flatten() {
+        tb.add("<").stream(Some(field.ty.clone())).add("as makepad_microserde :: DeJsonFlat > :: Partial");
+    }
+    else {
+        tb.add("Option <").stream(Some(field.ty.clone())).add(">");
+    }
+}
+
+fn de_json_slot_init(tb: &mut TokenBuilder, field: &Field) {
+    if field.is_skip() {
+        tb.add("( )");
+    }
+    else if field.is_flatten() {
+        tb.add("<").stream(Some(field.ty.clone())).add("as makepad_microserde :: DeJsonFlat > :: de_json_flat_new ( )");
+    }
+    else {
+        tb.add("None");
+    }
+}
+
+// matches the key in s.strbuf against the fields, unknown keys go through the flattened fields before running `unknown`
+fn de_json_field_match(tb: &mut TokenBuilder, fields: &[Field], slots: &[String], unknown: &str) {
+    tb.add("match s . strbuf . as_ref ( ) {");
+    for (field, slot) in fields.iter().zip(slots) {
+        if field.is_skip() || field.is_flatten() {
+            continue
+        }
+        tb.string(&field.key()).add("=> { s . next_colon ( i ) ? ;");
+        tb.add(slot).add("= Some ( DeJson :: de_json ( s , i )");
+        err_at_field(tb, &field.key());
+        tb.add("? ) ; } ,");
+    }
+    tb.add("_ => {");
+    for (field, slot) in fields.iter().zip(slots) {
+        if field.is_flatten() {
+            tb.add("if <").stream(Some(field.ty.clone())).add("as makepad_microserde :: DeJsonFlat > :: de_json_flat_field ( & mut");
+            tb.add(slot).add(", s , i ) ? { } else");
+        }
+    }
+    tb.add("{").add(unknown).add("} } }");
+}
+
+// the `field: value,` list that builds the struct out of its slots
+fn de_json_field_values(tb: &mut TokenBuilder, fields: &[Field], slots: &[String], container_default: bool) {
+    for (field, slot) in fields.iter().zip(slots) {
+        tb.ident(&field.name).add(":");
+        if field.is_skip() {
+            field.default_value(tb);
+            tb.add(",");
+        }
+        else if field.is_flatten() {
+            tb.add("<").stream(Some(field.ty.clone())).add("as makepad_microserde :: DeJsonFlat > :: de_json_flat_finish (");
+            tb.add(slot).add(", s ) ? ,");
+        }
+        else {
+            tb.add("match").add(slot).add("{ Some ( t ) => t , None => {");
+            if field.is_default() {
+                field.default_value(tb);
+            }
+            else if field.is_option() {
+                tb.add("None");
+            }
+            else if container_default {
+                tb.add("< Self as Default > :: default ( ) .").ident(&field.name);
+            }
+            else {
+                tb.add("return Err ( s . err_nf (").string(&field.key()).add(") )");
+            }
+            tb.add("} } ,");
+        }
+    }
+}
+
+// reads a { } object into locals and builds `path { .. }` out of them
+fn de_json_named(tb: &mut TokenBuilder, path: &str, fields: &[Field], container_default: bool) {
+    let slots: Vec<String> = fields.iter().map( | f | format!("_{}", f.name)).collect();
+    tb.add("s . curly_open ( i ) ? ;");
+    for (field, slot) in fields.iter().zip(&slots) {
+        if !field.is_skip() {
+            tb.add("let mut").ident(slot).add("=");
+            de_json_slot_init(tb, field);
+            tb.add(";");
+        }
+    }
+    tb.add("while let Some ( _ ) = s . next_str ( ) {");
+    de_json_field_match(tb, fields, &slots, "return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
+    tb.add("s . eat_comma_curly ( i ) ? ;");
+    tb.add("} ; s . curly_close ( i ) ? ;");
+    tb.add(path).add("{");
+    de_json_field_values(tb, fields, &slots, container_default);
+    tb.add("}");
+}
+
+pub const INTERNAL_TUPLE: &str = "an enum with #[nserde(tag)] can only have unit, struct and single value variants";
+
+// how an enum is laid out, picked with container attributes
+pub enum EnumRepr {
+    // {"Variant":[..]} or {"Variant":{..}}
+    External,
+    // #[nserde(tag = "type")] puts the variant next to its fields, {"type":"Variant",..}
+    Internal(String),
+    // #[nserde(tag = "t", content = "c")] gives {"t":"Variant","c":..}
+    Adjacent(String, String),
+    // #[nserde(untagged)] writes just the content, reading picks the first variant that fits
+    Untagged,
+}
+
+pub fn enum_repr(attrs: &[Attribute]) -> Result<EnumRepr, TokenStream> {
+    let tag = attrs_value(attrs, "tag");
+    let content = attrs_value(attrs, "content");
+    if attrs_has(attrs, "untagged") {
+        if tag.is_some() || content.is_some() {
+            return Err(error("#[nserde(untagged)] can't be combined with tag or content"))
+        }
+        return Ok(EnumRepr::Untagged)
+    }
+    match (tag, content) {
+        (Some(tag), Some(content)) => Ok(EnumRepr::Adjacent(tag, content)),
+        (Some(tag), None) => Ok(EnumRepr::Internal(tag)),
+        (None, Some(_)) => Err(error("#[nserde(content)] needs a tag as well")),
+        (None, None) => Ok(EnumRepr::External),
+    }
+}
+
+pub enum VariantKind {
+    Unit,
+    Tuple(Vec<TokenStream>),
+    Named(Vec<Field>),
+}
+
+pub struct Variant {
+    pub name: String,
+    pub label: String,
+    pub kind: VariantKind,
+}
+
+pub fn parse_variants(parser: &mut TokenParser) -> Option<Vec<Variant>> {
+    if !parser.open_brace() {
+        return None
+    }
+    let mut variants = Vec::new();
+    while !parser.eat_eot() {
+        let attrs = parser.eat_attributes() ?;
+        let name = parser.eat_any_ident() ?;
+        let label = attrs_value(&attrs, "rename").unwrap_or(name.clone());
+        let kind = if let Some(types) = parser.eat_all_types() {
+            VariantKind::Tuple(types)
+        }
+        else if let Some(fields) = parser.eat_all_struct_fields() {
+            VariantKind::Named(fields)
+        }
+        else if parser.is_punct(',') || parser.is_eot() {
+            VariantKind::Unit
+        }
+        else {
+            return None
+        };
+        variants.push(Variant {name, label, kind});
+        parser.eat_punct(',');
+    }
+    Some(variants)
+}
+
+// Self :: V ( n0 , n1 , ) or Self :: V { a , b , }
+fn ser_json_variant_pattern(tb: &mut TokenBuilder, variant: &Variant) {
+    tb.add("Self ::").ident(&variant.name);
+    match &variant.kind {
+        VariantKind::Unit => (),
+        VariantKind::Tuple(types) => {
+            tb.add("(");
+            for i in 0..types.len() {
+                tb.ident(&format!("n{}", i)).add(",");
+            }
+            tb.add(")");
+        }
+        VariantKind::Named(fields) => {
+            tb.add("{");
+            for field in fields {
+                tb.ident(&field.name);
+                if field.is_skip() {
+                    tb.add(": _");
+                }
+                tb.add(",");
+            }
+            tb.add("}");
+        }
+    }
+}
+
+fn ser_json_tuple(tb: &mut TokenBuilder, len: usize) {
+    tb.add("s . out . push (").chr('[').add(") ;");
+    for i in 0..len {
+        tb.ident(&format!("n{}", i)).add(". ser_json ( d , s ) ;");
+        if i != len - 1 {
+            tb.add("s . out . push (").chr(',').add(") ;");
+        }
+    }
+    tb.add("s . out . push (").chr(']').add(") ;");
+}
+
+// the variant data on its own, a single value is written without wrapping it in [ ]
+fn ser_json_content(tb: &mut TokenBuilder, variant: &Variant) {
+    match &variant.kind {
+        VariantKind::Unit => {
+            tb.add("s . out . push_str (").string("null").add(") ;");
+        }
+        VariantKind::Tuple(types) if types.len() == 1 => {
+            tb.add("n0 . ser_json ( d , s ) ;");
+        }
+        VariantKind::Tuple(types) => ser_json_tuple(tb, types.len()),
+        VariantKind::Named(fields) => {
+            tb.add("s . st_pre ( ) ;");
+            ser_json_fields(tb, fields, "");
+            tb.add("s . st_post ( d ) ;");
+        }
+    }
+}
+
+fn de_json_tuple(tb: &mut TokenBuilder, variant: &str, len: usize) {
+    tb.add("s . block_open ( i ) ? ;");
+    tb.add("let r = Self ::").ident(variant).add("(");
+    for index in 0..len {
+        tb.add("{ let r = DeJson :: de_json ( s , i )");
+        err_at_index(tb, index);
+        tb.add("? ; s . eat_comma_block ( i ) ? ; r } ,");
+    }
+    tb.add(") ;");
+    tb.add("s . block_close ( i ) ? ; r");
+}
+
+// a block reading the variant data as ser_json_content wrote it
+fn de_json_content(tb: &mut TokenBuilder, variant: &Variant) {
+    tb.add("{");
+    match &variant.kind {
+        VariantKind::Unit => {
+            tb.add("s . null ( i ) ? ; Self ::").ident(&variant.name);
+        }
+        VariantKind::Tuple(types) if types.len() == 1 => {
+            tb.add("Self ::").ident(&variant.name).add("( DeJson :: de_json ( s , i ) ? )");
+        }
+        VariantKind::Tuple(types) => de_json_tuple(tb, &variant.name, types.len()),
+        VariantKind::Named(fields) => de_json_named(tb, &format!("Self :: {}", variant.name), fields, false),
+    }
+    tb.add("}");
+}
+
+// match label { "V" => content, .. } as an Ok(..) expression
+fn de_json_content_match(tb: &mut TokenBuilder, label: &str, variants: &[Variant]) {
+    tb.add("std :: result :: Result :: Ok ( match").add(label).add(". as_ref ( ) {");
+    for variant in variants {
+        tb.string(&variant.label).add("=>");
+        de_json_content(tb, variant);
+        tb.add(",");
+    }
+    tb.add("_ => return std :: result :: Result :: Err ( s . err_enum ( &").add(label).add(") ) } )");
+}
+
+fn de_json_external(tb: &mut TokenBuilder, variants: &[Variant]) {
+    tb.add("s . curly_open ( i ) ? ;");
+    tb.add("let _ = s . string ( i ) ? ;");
+    tb.add("s . colon ( i ) ? ;");
+    tb.add("let r = std :: result :: Result :: Ok ( match s . strbuf . as_ref ( ) {");
+    for variant in variants {
+        tb.string(&variant.label).add("=> {");
+        match &variant.kind {
+            VariantKind::Unit => {
+                tb.add("s . block_open ( i ) ? ; s . block_close ( i ) ? ; Self ::").ident(&variant.name);
+            }
+            VariantKind::Tuple(types) => de_json_tuple(tb, &variant.name, types.len()),
+            VariantKind::Named(fields) => de_json_named(tb, &format!("Self :: {}", variant.name), fields, false),
+        }
+        tb.add("}");
+    }
+    tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
+    tb.add("} ) ; s . curly_close ( i ) ? ; r");
+}
+
+// the tag is usually the first key, any keys in front of it are kept as JsonValue and
+// replayed once we know which variant they belong to
+fn de_json_internal(tb: &mut TokenBuilder, tag: &str, variants: &[Variant]) {
+    tb.add("s . curly_open ( i ) ? ;");
+    tb.add("let mut _buffered : Vec < ( String , makepad_microserde :: JsonValue ) > = Vec :: new ( ) ;");
+    tb.add("let _label = loop {");
+    tb.add("if s . next_str ( ) . is_none ( ) { return std :: result :: Result :: Err ( s . err_nf (").string(tag).add(") ) }");
+    tb.add("if s . strbuf ==").string(tag).add("{");
+    tb.add("s . next_colon ( i ) ? ; let label = s . as_string ( ) ? ; s . next_tok ( i ) ? ; s . eat_comma_curly ( i ) ? ; break label }");
+    tb.add("let key = s . as_string ( ) ? ; s . next_colon ( i ) ? ;");
+    tb.add("_buffered . push ( ( key , DeJson :: de_json ( s , i ) ? ) ) ; s . eat_comma_curly ( i ) ? ;");
+    tb.add("} ;");
+    tb.add("std :: result :: Result :: Ok ( match _label . as_ref ( ) {");
+    for variant in variants {
+        tb.string(&variant.label).add("=> {");
+        match &variant.kind {
+            VariantKind::Unit => {
+                tb.add("if let Some ( ( key , _ ) ) = _buffered . first ( ) { return std :: result :: Result :: Err ( s . err_exp ( key ) ) }");
+                tb.add("if s . next_str ( ) . is_some ( ) { return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) ) }");
+                tb.add("s . curly_close ( i ) ? ; Self ::").ident(&variant.name);
+            }
+            VariantKind::Tuple(types) => {
+                // the single value is a struct, its fields sit next to the tag
+                let ty = types[0].clone();
+                let field_match = | tb: &mut TokenBuilder | {
+                    tb.add("if ! <").stream(Some(ty.clone())).add("as makepad_microserde :: DeJsonFlat > :: de_json_flat_field ( & mut _p , s , i ) ?");
+                    tb.add("{ return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) ) }");
+                };
+                tb.add("let mut _p = <").stream(Some(ty.clone())).add("as makepad_microserde :: DeJsonFlat > :: de_json_flat_new ( ) ;");
+                de_json_replay_fields(tb, &field_match);
+                tb.add("Self ::").ident(&variant.name).add("( <").stream(Some(ty.clone()));
+                tb.add("as makepad_microserde :: DeJsonFlat > :: de_json_flat_finish ( _p , s ) ? )");
+            }
+            VariantKind::Named(fields) => {
+                let slots: Vec<String> = fields.iter().map( | f | format!("_{}", f.name)).collect();
+                for (field, slot) in fields.iter().zip(&slots) {
+                    if !field.is_skip() {
+                        tb.add("let mut").ident(slot).add(":");
+                        de_json_slot_type(tb, field);
+                        tb.add("=");
+                        de_json_slot_init(tb, field);
+                        tb.add(";");
+                    }
+                }
+                let field_match = | tb: &mut TokenBuilder | {
+                    de_json_field_match(tb, fields, &slots, "return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
+                };
+                de_json_replay_fields(tb, &field_match);
+                tb.add("Self ::").ident(&variant.name).add("{");
+                de_json_field_values(tb, fields, &slots, false);
+                tb.add("}");
+            }
+        }
+        tb.add("}");
+    }
+    tb.add("_ => return std :: result :: Result :: Err ( s . err_enum ( & _label ) ) } )");
+}
+
+// feeds the buffered keys and then the rest of the object through field_match
+fn de_json_replay_fields(tb: &mut TokenBuilder, field_match: &dyn Fn(&mut TokenBuilder)) {
+    tb.add("for ( key , value ) in _buffered {");
+    tb.add("let text = format ! (").string("{}:{}").add(", key . serialize_json ( ) , value . serialize_json ( ) ) ;");
+    tb.add("makepad_microserde :: de_json_replay ( & text , | s , i | {");
+    field_match(tb);
+    tb.add("std :: result :: Result :: Ok ( ( ) ) } ) ? ; }");
+    tb.add("while let Some ( _ ) = s . next_str ( ) {");
+    field_match(tb);
+    tb.add("s . eat_comma_curly ( i ) ? ; }");
+    tb.add("s . curly_close ( i ) ? ;");
+}
+
+fn de_json_adjacent(tb: &mut TokenBuilder, tag: &str, content: &str, variants: &[Variant]) {
+    tb.add("s . curly_open ( i ) ? ;");
+    tb.add("let mut _label : Option < String > = None ;");
+    tb.add("let mut _content : Option < makepad_microserde :: JsonValue > = None ;");
+    tb.add("let mut _value : Option < Self > = None ;");
+    tb.add("while let Some ( _ ) = s . next_str ( ) {");
+    tb.add("if s . strbuf ==").string(tag).add("{");
+    tb.add("s . next_colon ( i ) ? ; _label = Some ( s . as_string ( ) ? ) ; s . next_tok ( i ) ? ; }");
+    tb.add("else if s . strbuf ==").string(content).add("{ s . next_colon ( i ) ? ;");
+    // content in front of the tag has to wait until we know its type
+    tb.add("match & _label { Some ( label ) => { _value = Some ( {");
+    de_json_content_match(tb, "label", variants);
+    tb.add("} ? ) ; } None => { _content = Some ( DeJson :: de_json ( s , i ) ? ) ; } } }");
+    tb.add("else { return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) ) }");
+    tb.add("s . eat_comma_curly ( i ) ? ; }");
+    tb.add("s . curly_close ( i ) ? ;");
+    tb.add("let _label = match _label { Some ( label ) => label , None => return std :: result :: Result :: Err ( s . err_nf (").string(tag).add(") ) } ;");
+    tb.add("match ( _value , _content ) {");
+    tb.add("( Some ( value ) , _ ) => std :: result :: Result :: Ok ( value ) ,");
+    tb.add("( None , Some ( content ) ) => makepad_microserde :: de_json_replay ( & content . serialize_json ( ) , | s , i | {");
+    de_json_content_match(tb, "_label", variants);
+    tb.add("} ) ,");
+    tb.add("( None , None ) => std :: result :: Result :: Ok ( match _label . as_ref ( ) {");
+    for variant in variants {
+        if let VariantKind::Unit = variant.kind {
+            tb.string(&variant.label).add("=> Self ::").ident(&variant.name).add(",");
+        }
+    }
+    tb.add("_ => return std :: result :: Result :: Err ( s . err_nf (").string(content).add(") ) } ) }");
+}
+
+// buffers the value and tries the variants in order
+fn de_json_untagged(tb: &mut TokenBuilder, name: &str, variants: &[Variant]) {
+    tb.add("let _value : makepad_microserde :: JsonValue = DeJson :: de_json ( s , i ) ? ;");
+    tb.add("let _text = _value . serialize_json ( ) ;");
+    for variant in variants {
+        tb.add("if let Ok ( r ) = makepad_microserde :: de_json_replay ( & _text , | s , i | std :: result :: Result :: Ok (");
+        de_json_content(tb, variant);
+        tb.add(") ) { return std :: result :: Result :: Ok ( r ) }");
+    }
+    tb.add("std :: result :: Result :: Err ( s . err_parse (").string(name).add(") )");
+}
+
 pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
 
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
-    
-    parser.eat_ident("pub");
+
+    let container_attrs = if let Some(attrs) = parser.eat_attributes() {attrs} else {return parser.unexpected()};
+    parser.eat_visibility();
     if parser.eat_ident("struct"){
         if let Some(name) = parser.eat_any_ident(){
-            
-            let generic = parser.eat_generic();
+
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
             let types = parser.eat_all_types();
-            let where_clause = parser.eat_where_clause(Some("SerJson"));
+            parser.eat_where_predicates(&mut generics);
+            let where_clause = generics.where_clause("makepad_microserde :: SerJson");
 
-            tb.add("impl").stream(generic.clone());
-            tb.add("SerJson for").ident(&name).stream(generic).stream(where_clause);
+            tb.add("impl").stream(generics.impl_generics());
+            tb.add("SerJson for").ident(&name).stream(generics.type_generics()).stream(where_clause.clone());
             tb.add("{ fn ser_json ( & self , d : usize , s : & mut makepad_microserde :: SerJsonState ) {");
-            
+
             if let Some(types) = types{
                 tb.add("s . out . push (").chr('[').add(") ;");
                 for i in 0..types.len(){
@@ -27,109 +446,84 @@ pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
                      }
                 }
                 tb.add("s . out . push (").chr(']').add(") ;");
+                tb.add("} } ;");
             }
             else if let Some(fields) = parser.eat_all_struct_fields(){
                 tb.add("s . st_pre ( ) ;");
                 // named struct
-                for (field,ty) in fields{
-                    if ty.into_iter().next().unwrap().to_string() == "Option"{
-                        tb.add("if let Some ( t ) = ").add("& self .").ident(&field).add("{");
-                        tb.add("s . field ( d + 1 ,").string(&field).add(") ;");
-                        tb.add("t . ser_json ( d + 1 , s ) ; s . conl ( ) ; } ;");
-                    }
-                    else{
-                        tb.add("s . field ( d + 1 ,").string(&field).add(" ) ;");
-                        tb.add("self .").ident(&field).add(". ser_json ( d + 1 , s ) ; s . conl ( ) ;");
-                    }
-                }
+                ser_json_fields(&mut tb, &fields, "self .");
                 tb.add("s . st_post ( d ) ;");
+                tb.add("} }");
+
+                tb.add("impl").stream(generics.impl_generics());
+                tb.add("makepad_microserde :: SerJsonFlat for").ident(&name).stream(generics.type_generics()).stream(where_clause);
+                tb.add("{ fn ser_json_flat ( & self , d : usize , s : & mut makepad_microserde :: SerJsonState ) {");
+                ser_json_fields(&mut tb, &fields, "self .");
+                tb.add("} } ;");
             }
             else{
                 return parser.unexpected()
             }
-            tb.add("} } ;");
             return tb.end();
         }
     }
     else if parser.eat_ident("enum"){
         if let Some(name) = parser.eat_any_ident(){
-            let generic = parser.eat_generic();
-            let where_clause = parser.eat_where_clause(Some("SerJson"));
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
+            parser.eat_where_predicates(&mut generics);
+            let where_clause = generics.where_clause("makepad_microserde :: SerJson");
+            let repr = match enum_repr(&container_attrs) {Ok(repr) => repr, Err(err) => return err};
+            let variants = if let Some(variants) = parse_variants(&mut parser) {variants} else {return parser.unexpected()};
 
-            tb.add("impl").stream(generic.clone());
-            tb.add("SerJson for").ident(&name).stream(generic).stream(where_clause);
+            tb.add("impl").stream(generics.impl_generics());
+            tb.add("SerJson for").ident(&name).stream(generics.type_generics()).stream(where_clause);
             tb.add("{ fn ser_json ( & self , d : usize , s : & mut makepad_microserde :: SerJsonState ) {");
-            tb.add("s . out . push (").chr('{').add(") ;");
             tb.add("match self {");
-            
-            if !parser.open_brace(){
-                return parser.unexpected()
-            }
-
-            while !parser.eat_eot(){
-                // parse ident
-                if let Some(variant) = parser.eat_any_ident(){
-                    if let Some(types) = parser.eat_all_types(){
-                        
-                        tb.add("Self ::").ident(&variant).add("(");
-                        for i in 0..types.len(){
-                            tb.ident(&format!("n{}", i)).add(",");
-                        }
-                        tb.add(") => {");
-                        tb.add("s . label (").string(&variant).add(") ;");
+            for variant in &variants {
+                ser_json_variant_pattern(&mut tb, variant);
+                tb.add("=> {");
+                match &repr {
+                    EnumRepr::External => {
+                        tb.add("s . out . push (").chr('{').add(") ;");
+                        tb.add("s . label (").string(&variant.label).add(") ;");
                         tb.add("s . out . push (").chr(':').add(") ;");
-                        tb.add("s . out . push (").chr('[').add(") ;");
-                        
-                        for i in 0..types.len(){
-                            tb.ident(&format!("n{}", i)).add(". ser_json ( d , s ) ;");
-                            if i != types.len() - 1{
-                                tb.add("s . out . push (").chr(',').add(") ;");
+                        match &variant.kind {
+                            VariantKind::Unit => {
+                                tb.add("s . out . push_str (").string("[]").add(") ;");
                             }
+                            VariantKind::Tuple(types) => ser_json_tuple(&mut tb, types.len()),
+                            VariantKind::Named(_) => ser_json_content(&mut tb, variant),
                         }
-                        tb.add("s . out . push (").chr(']').add(") ;");
-                        tb.add("}");
+                        tb.add("s . out . push (").chr('}').add(") ;");
                     }
-                    else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
-                        tb.add("Self ::").ident(&variant).add("{");
-                        for (field, _ty) in fields.iter(){
-                            tb.ident(field).add(",");
-                        }
-                        tb.add("} => {");
-                        
-                        tb.add("s . label (").string(&variant).add(") ;");
-                        tb.add("s . out . push (").chr(':').add(") ;");
-                        tb.add("s . st_pre ( ) ;");
-                        
-                        for (field, ty) in fields{
-                            if ty.into_iter().next().unwrap().to_string() == "Option"{
-                                tb.add("if let Some ( t ) = ").ident(&field).add("{");
-                                tb.add("s . field ( d + 1 ,").string(&field).add(") ;");
-                                tb.add("t . ser_json ( d + 1 , s ) ; s . conl ( ) ; } ;");
-                            }
-                            else{
-                                tb.add("s . field ( d + 1 ,").string(&field).add(" ) ;");
-                                tb.ident(&field).add(". ser_json ( d + 1 , s ) ; s . conl ( ) ;");
+                    EnumRepr::Internal(tag) => {
+                        tb.add("s . st_pre ( ) ; s . field ( d + 1 ,").string(tag).add(") ;");
+                        tb.add("s . label (").string(&variant.label).add(") ; s . conl ( ) ;");
+                        match &variant.kind {
+                            VariantKind::Unit => (),
+                            VariantKind::Tuple(types) if types.len() == 1 => {
+                                tb.add("makepad_microserde :: SerJsonFlat :: ser_json_flat ( n0 , d , s ) ;");
                             }
+                            VariantKind::Tuple(_) => return error(INTERNAL_TUPLE),
+                            VariantKind::Named(fields) => ser_json_fields(&mut tb, fields, ""),
                         }
-                        tb.add("s . st_post ( d ) ; }");
+                        tb.add("s . st_post ( d ) ;");
                     }
-                    else if parser.is_punct(',') || parser.is_eot(){ // bare variant
-                        tb.add("Self ::").ident(&variant).add("=> {");
-                        tb.add("s . label (").string(&variant).add(") ;");
-                        tb.add("s . out . push_str (").string(":[]").add(") ; }");
-                    }
-                    else{
-                        return parser.unexpected();
+                    EnumRepr::Adjacent(tag, content) => {
+                        tb.add("s . st_pre ( ) ; s . field ( d + 1 ,").string(tag).add(") ;");
+                        tb.add("s . label (").string(&variant.label).add(") ; s . conl ( ) ;");
+                        if !matches!(variant.kind, VariantKind::Unit) {
+                            tb.add("s . field ( d + 1 ,").string(content).add(") ;");
+                            ser_json_content(&mut tb, variant);
+                            tb.add("s . conl ( ) ;");
+                        }
+                        tb.add("s . st_post ( d ) ;");
                     }
-                    parser.eat_punct(',');
-                }
-                else{
-                    return parser.unexpected()
+                    EnumRepr::Untagged => ser_json_content(&mut tb, variant),
                 }
+                tb.add("}");
             }
-            tb.add("}");
-            tb.add("s . out . push (").chr('}').add(") ;");
-            tb.add("} } ;");
+            tb.add("} } }");
             return tb.end();
         }
     }
@@ -139,16 +533,18 @@ pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
 pub fn derive_de_json_impl(input: TokenStream) -> TokenStream {
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
-    
-    parser.eat_ident("pub");
+
+    let container_attrs = if let Some(attrs) = parser.eat_attributes() {attrs} else {return parser.unexpected()};
+    parser.eat_visibility();
     if parser.eat_ident("struct"){
         if let Some(name) = parser.eat_any_ident(){
-            let generic = parser.eat_generic();
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
             let types = parser.eat_all_types();
-            let where_clause = parser.eat_where_clause(Some("DeJson"));
+            parser.eat_where_predicates(&mut generics);
+            let where_clause = generics.where_clause("makepad_microserde :: DeJson");
 
-            tb.add("impl").stream(generic.clone());
-            tb.add("DeJson for").ident(&name).stream(generic).stream(where_clause);
+            tb.add("impl").stream(generics.impl_generics());
+            tb.add("DeJson for").ident(&name).stream(generics.type_generics()).stream(where_clause.clone());
             tb.add("{ fn de_json ( s : &  mut makepad_microserde :: DeJsonState , i : & mut std :: str :: Chars )");
             tb.add("-> std :: result :: Result < Self , makepad_microserde :: DeJsonErr > { ");
 
@@ -156,128 +552,81 @@ pub fn derive_de_json_impl(input: TokenStream) -> TokenStream {
                 tb.add("s . block_open ( i ) ? ;");
                 tb.add("let r = Self");
                 tb.add("(");
-                for _ in 0..types.len(){
-                     tb.add("{ let r = DeJson :: de_json ( s , i ) ? ; s . eat_comma_block ( i ) ? ; r } ,");
+                for index in 0..types.len(){
+                     tb.add("{ let r = DeJson :: de_json ( s , i )");
+                     err_at_index(&mut tb, index);
+                     tb.add("? ; s . eat_comma_block ( i ) ? ; r } ,");
                 }
                 tb.add(") ;");
                 tb.add("s . block_close ( i ) ? ;");
                 tb.add("std :: result :: Result :: Ok ( r )");
+                tb.add("} } ;");
             }
-            else if let Some(fields) = parser.eat_all_struct_fields(){ 
-                tb.add("s . curly_open ( i ) ? ;");
-                for (field,_ty) in &fields{
-                    tb.add("let mut").ident(&format!("_{}",field)).add("= None ;");
-                }
-                tb.add("while let Some ( _ ) = s . next_str ( ) {");
-                tb.add("match s . strbuf . as_ref ( ) {");
-                for (field,_ty) in &fields{
-                    tb.string(&field).add("=> { s . next_colon ( i ) ? ;");
-                    tb.ident(&format!("_{}",field)).add("= Some ( DeJson :: de_json ( s , i ) ? ) ; } ,");
+            else if let Some(fields) = parser.eat_all_struct_fields(){
+                let container_default = attrs_has(&container_attrs, "default");
+                tb.add("std :: result :: Result :: Ok ( {");
+                de_json_named(&mut tb, "Self", &fields, container_default);
+                tb.add("} )");
+                tb.add("} }");
+
+                // the same fields, read one key at a time so a parent can #[nserde(flatten)] us
+                let slots: Vec<String> = (0..fields.len()).map( | i | format!("p . {}", i)).collect();
+                tb.add("impl").stream(generics.impl_generics());
+                tb.add("makepad_microserde :: DeJsonFlat for").ident(&name).stream(generics.type_generics()).stream(where_clause);
+                tb.add("{ type Partial = (");
+                for field in &fields {
+                    de_json_slot_type(&mut tb, field);
+                    tb.add(",");
                 }
-                tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
-                tb.add("} ; s . eat_comma_curly ( i ) ? ;");
-                tb.add("} ; s . curly_close ( i ) ? ;");
-                
-                tb.add("std :: result :: Result :: Ok ( Self {");
-                for (field,ty) in fields{
-                    tb.ident(&field).add(":");
-                    if ty.into_iter().next().unwrap().to_string() == "Option"{
-                        tb.add("if let Some ( t ) =").ident(&format!("_{}",field));
-                        tb.add("{ t } else { None } ,");
-                    }
-                    else{
-                        tb.add("if let Some ( t ) =").ident(&format!("_{}",field));
-                        tb.add("{ t } else { return Err ( s . err_nf (");
-                        tb.string(&field).add(") ) } ,");
-                    }
+                tb.add(") ;");
+                tb.add("fn de_json_flat_new ( ) -> Self :: Partial { (");
+                for field in &fields {
+                    de_json_slot_init(&mut tb, field);
+                    tb.add(",");
                 }
-                tb.add("} )");
+                tb.add(") }");
+                tb.add("fn de_json_flat_field ( p : & mut Self :: Partial , s : & mut makepad_microserde :: DeJsonState , i : & mut std :: str :: Chars )");
+                tb.add("-> std :: result :: Result < bool , makepad_microserde :: DeJsonErr > {");
+                tb.add("let _ = & p ;");
+                de_json_field_match(&mut tb, &fields, &slots, "return std :: result :: Result :: Ok ( false )");
+                tb.add("std :: result :: Result :: Ok ( true ) }");
+                tb.add("fn de_json_flat_finish ( p : Self :: Partial , s : & mut makepad_microserde :: DeJsonState )");
+                tb.add("-> std :: result :: Result < Self , makepad_microserde :: DeJsonErr > {");
+                tb.add("let _ = ( & p , & s ) ; std :: result :: Result :: Ok ( Self {");
+                de_json_field_values(&mut tb, &fields, &slots, container_default);
+                tb.add("} ) } } ;");
             }
             else{
                 return parser.unexpected()
             }
-            tb.add("} } ;"); 
             return tb.end();
         }
     }
     else if parser.eat_ident("enum"){
-        
+
         if let Some(name) = parser.eat_any_ident(){
-            let generic = parser.eat_generic();
-            let where_clause = parser.eat_where_clause(Some("DeJson"));
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
+            parser.eat_where_predicates(&mut generics);
+            let where_clause = generics.where_clause("makepad_microserde :: DeJson");
+            let repr = match enum_repr(&container_attrs) {Ok(repr) => repr, Err(err) => return err};
+            let variants = if let Some(variants) = parse_variants(&mut parser) {variants} else {return parser.unexpected()};
 
-            tb.add("impl").stream(generic.clone());
-            tb.add("DeJson for").ident(&name).stream(generic).stream(where_clause);
+            tb.add("impl").stream(generics.impl_generics());
+            tb.add("DeJson for").ident(&name).stream(generics.type_generics()).stream(where_clause);
             tb.add("{ fn de_json ( s : & mut makepad_microserde :: DeJsonState , i : & mut std :: str :: Chars )");
             tb.add("-> std :: result :: Result < Self , makepad_microserde :: DeJsonErr > { ");
-            tb.add("s . curly_open ( i ) ? ;");
-            tb.add("let _ = s . string ( i ) ? ;");
-            tb.add("s . colon ( i ) ? ;");
-            tb.add("let r = std :: result :: Result :: Ok ( match s . strbuf . as_ref ( ) {");
-            
-            if !parser.open_brace(){
-                return parser.unexpected()
-            }
-            while !parser.eat_eot(){
-                // parse ident
-                if let Some(variant) = parser.eat_any_ident(){
-                    tb.string(&variant).add("=> {");
-                    if let Some(types) = parser.eat_all_types(){
-                        
-                        tb.add("s . block_open ( i ) ? ;");
-                        tb.add("let r = Self ::").ident(&variant).add("(");
-                        for _ in 0..types.len(){
-                            tb.add("{ let r = DeJson :: de_json ( s , i ) ? ; s . eat_comma_block ( i ) ? ; r } ,");
-                        }
-                        tb.add(") ;");
-                        tb.add("s . block_close ( i ) ? ; r");
-                    }
-                    else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
-                        tb.add("s . curly_open ( i ) ? ;");
-                        for (field,_ty) in &fields{
-                            tb.add("let mut").ident(&format!("_{}",field)).add("= None ;");
-                        }
-                        tb.add("while let Some ( _ ) = s . next_str ( ) {");
-                        tb.add("match s . strbuf . as_ref ( ) {");
-                        for (field,_ty) in &fields{
-                            tb.string(&field).add("=> { s . next_colon ( i ) ? ;");
-                            tb.ident(&format!("_{}",field)).add("= Some ( DeJson :: de_json ( s , i ) ? ) ; } ,");
-                        }
-                        tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
-                        tb.add("} s . eat_comma_curly ( i ) ? ;");
-                        tb.add("} s . curly_close ( i ) ? ;");
-                        
-                        tb.add("Self ::").ident(&variant).add("{");
-                        for (field,ty) in fields{
-                            tb.ident(&field).add(":");
-                            if ty.into_iter().next().unwrap().to_string() == "Option"{
-                                tb.add("if let Some ( t ) =").ident(&format!("_{}",field));
-                                tb.add("{ t } else { None } ,");
-                            }
-                            else{
-                                tb.add("if let Some ( t ) =").ident(&format!("_{}",field));
-                                tb.add("{ t } else { return Err ( s . err_nf (");
-                                tb.string(&field).add(") ) } ,");
-                            }
-                        }
-                        tb.add("}");
+            match &repr {
+                EnumRepr::External => de_json_external(&mut tb, &variants),
+                EnumRepr::Internal(tag) => {
+                    if variants.iter().any( | v | matches!(&v.kind, VariantKind::Tuple(types) if types.len() != 1)) {
+                        return error(INTERNAL_TUPLE)
                     }
-                    else if parser.is_punct(',') || parser.is_eot(){ // bare variant
-                        tb.add("s . block_open ( i ) ? ; s . block_close ( i ) ? ; Self ::").ident(&variant);
-                    }
-                    else{
-                        return parser.unexpected();
-                    }
-                    
-                    tb.add("}");
-                    parser.eat_punct(',');
+                    de_json_internal(&mut tb, tag, &variants)
                 }
-                else{
-                    return parser.unexpected()
-                }
-            } 
-            tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
-            tb.add("} ) ; s . curly_close ( i ) ? ; r } }");
+                EnumRepr::Adjacent(tag, content) => de_json_adjacent(&mut tb, tag, content, &variants),
+                EnumRepr::Untagged => de_json_untagged(&mut tb, &name, &variants),
+            }
+            tb.add("} }");
             return tb.end();
         }
     }
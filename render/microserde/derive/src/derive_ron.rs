@@ -1,24 +1,146 @@
- 
 use proc_macro::{TokenStream};
 use crate::macro_lib::*;
 
+// writes key:value, for every field, prefix is how a field is reached (self . or a match binding)
+fn ser_ron_fields(tb: &mut TokenBuilder, fields: &[Field], prefix: &str) {
+    for field in fields {
+        if field.is_skip() {
+            continue
+        }
+        if field.is_flatten() {
+            tb.add("makepad_microserde :: SerRonFlat :: ser_ron_flat ( &").add(prefix).ident(&field.name).add(", d , s ) ;");
+        }
+        else if field.is_option() {
+            tb.add("if let Some ( t ) = &").add(prefix).ident(&field.name).add("{");
+            tb.add("s . field ( d + 1 ,").string(&field.key()).add(") ;");
+            tb.add("t . ser_ron ( d + 1 , s ) ; s . conl ( ) ; } ;");
+        }
+        else {
+            tb.add("s . field ( d + 1 ,").string(&field.key()).add(" ) ;");
+            tb.add(prefix).ident(&field.name).add(". ser_ron ( d + 1 , s ) ; s . conl ( ) ;");
+        }
+    }
+}
+
+// the per field slot a partially read struct keeps
+fn de_ron_slot_type(tb: &mut TokenBuilder, field: &Field) {
+    if field.is_skip() {
+        tb.add("( )");
+    }
+    else if field.is_flatten() {
+        tb.add("<").stream(Some(field.ty.clone())).add("as makepad_microserde :: DeRonFlat > :: Partial");
+    }
+    else {
+        tb.add("Option <").stream(Some(field.ty.clone())).add(">");
+    }
+}
+
+fn de_ron_slot_init(tb: &mut TokenBuilder, field: &Field) {
+    if field.is_skip() {
+        tb.add("( )");
+    }
+    else if field.is_flatten() {
+        tb.add("<").stream(Some(field.ty.clone())).add("as makepad_microserde :: DeRonFlat > :: de_ron_flat_new ( )");
+    }
+    else {
+        tb.add("None");
+    }
+}
+
+// matches the key in s.identbuf against the fields, unknown keys go through the flattened fields before running `unknown`
+fn de_ron_field_match(tb: &mut TokenBuilder, fields: &[Field], slots: &[String], unknown: &str) {
+    tb.add("match s . identbuf . as_ref ( ) {");
+    for (field, slot) in fields.iter().zip(slots) {
+        if field.is_skip() || field.is_flatten() {
+            continue
+        }
+        tb.string(&field.key()).add("=> { s . next_colon ( i ) ? ;");
+        tb.add(slot).add("= Some ( DeRon :: de_ron ( s , i )");
+        err_at_field(tb, &field.key());
+        tb.add("? ) ; } ,");
+    }
+    tb.add("_ => {");
+    for (field, slot) in fields.iter().zip(slots) {
+        if field.is_flatten() {
+            tb.add("if <").stream(Some(field.ty.clone())).add("as makepad_microserde :: DeRonFlat > :: de_ron_flat_field ( & mut");
+            tb.add(slot).add(", s , i ) ? { } else");
+        }
+    }
+    tb.add("{").add(unknown).add("} } }");
+}
+
+// the `field: value,` list that builds the struct out of its slots
+fn de_ron_field_values(tb: &mut TokenBuilder, fields: &[Field], slots: &[String], container_default: bool) {
+    for (field, slot) in fields.iter().zip(slots) {
+        tb.ident(&field.name).add(":");
+        if field.is_skip() {
+            field.default_value(tb);
+            tb.add(",");
+        }
+        else if field.is_flatten() {
+            tb.add("<").stream(Some(field.ty.clone())).add("as makepad_microserde :: DeRonFlat > :: de_ron_flat_finish (");
+            tb.add(slot).add(", s ) ? ,");
+        }
+        else {
+            tb.add("match").add(slot).add("{ Some ( t ) => t , None => {");
+            if field.is_default() {
+                field.default_value(tb);
+            }
+            else if field.is_option() {
+                tb.add("None");
+            }
+            else if container_default {
+                tb.add("< Self as Default > :: default ( ) .").ident(&field.name);
+            }
+            else {
+                tb.add("return Err ( s . err_nf (").string(&field.key()).add(") )");
+            }
+            tb.add("} } ,");
+        }
+    }
+}
+
+// reads a ( ) struct into locals and builds `path { .. }` out of them
+fn de_ron_named(tb: &mut TokenBuilder, path: &str, fields: &[Field], container_default: bool) {
+    let slots: Vec<String> = fields.iter().map( | f | format!("_{}", f.name)).collect();
+    tb.add("s . paren_open ( i ) ? ;");
+    for (field, slot) in fields.iter().zip(&slots) {
+        if !field.is_skip() {
+            tb.add("let mut").ident(slot).add("=");
+            de_ron_slot_init(tb, field);
+            tb.add(";");
+        }
+    }
+    tb.add("while let Some ( _ ) = s . next_ident ( ) {");
+    de_ron_field_match(tb, fields, &slots, "return std :: result :: Result :: Err ( s . err_exp ( & s . identbuf ) )");
+    tb.add("s . eat_comma_paren ( i ) ? ;");
+    tb.add("} ; s . paren_close ( i ) ? ;");
+    tb.add(path).add("{");
+    de_ron_field_values(tb, fields, &slots, container_default);
+    tb.add("}");
+}
+
 pub fn derive_ser_ron_impl(input: TokenStream) -> TokenStream {
 
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
-    
-    parser.eat_ident("pub");
+
+    if parser.eat_attributes().is_none() {
+        return parser.unexpected()
+    }
+    parser.eat_visibility();
     if parser.eat_ident("struct"){
         if let Some(name) = parser.eat_any_ident(){
-            
-            let generic = parser.eat_generic();
+
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
             let types = parser.eat_all_types();
-            let where_clause = parser.eat_where_clause(Some("SerRon"));
+            parser.eat_where_predicates(&mut generics);
+            let where_clause = generics.where_clause("makepad_microserde :: SerRon");
 
-            tb.add("impl").stream(generic.clone());
-            tb.add("SerRon for").ident(&name).stream(generic).stream(where_clause);
+            tb.add("impl").stream(generics.impl_generics());
+            tb.add("SerRon for").ident(&name).stream(generics.type_generics()).stream(where_clause.clone());
             tb.add("{ fn ser_ron ( & self , d : usize , s : & mut makepad_microserde :: SerRonState ) {");
-            
+
             if let Some(types) = types{
                 tb.add("s . out . push (").chr('(').add(") ;");
                 for i in 0..types.len(){
@@ -28,57 +150,57 @@ pub fn derive_ser_ron_impl(input: TokenStream) -> TokenStream {
                      }
                 }
                 tb.add("s . out . push (").chr(')').add(") ;");
+                tb.add("} } ;");
             }
-            else if let Some(fields) = parser.eat_all_struct_fields(){ 
+            else if let Some(fields) = parser.eat_all_struct_fields(){
                 tb.add("s . st_pre ( ) ;");
                 // named struct
-                for (field,ty) in fields{
-                    if ty.into_iter().next().unwrap().to_string() == "Option"{
-                        tb.add("if let Some ( t ) = ").add("& self .").ident(&field).add("{");
-                        tb.add("s . field ( d + 1 ,").string(&field).add(") ;");
-                        tb.add("t . ser_ron ( d + 1 , s ) ; s . conl ( ) ; } ;");
-                    }
-                    else{
-                        tb.add("s . field ( d + 1 ,").string(&field).add(" ) ;");
-                        tb.add("self .").ident(&field).add(". ser_ron ( d + 1 , s ) ; s . conl ( ) ;");
-                    }
-                }
+                ser_ron_fields(&mut tb, &fields, "self .");
                 tb.add("s . st_post ( d ) ;");
+                tb.add("} }");
+
+                tb.add("impl").stream(generics.impl_generics());
+                tb.add("makepad_microserde :: SerRonFlat for").ident(&name).stream(generics.type_generics()).stream(where_clause);
+                tb.add("{ fn ser_ron_flat ( & self , d : usize , s : & mut makepad_microserde :: SerRonState ) {");
+                ser_ron_fields(&mut tb, &fields, "self .");
+                tb.add("} } ;");
             }
             else{
                 return parser.unexpected()
             }
-            tb.add("} } ;");
             return tb.end();
         }
     }
     else if parser.eat_ident("enum"){
         if let Some(name) = parser.eat_any_ident(){
-            let generic = parser.eat_generic();
-            let where_clause = parser.eat_where_clause(Some("SerRon"));
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
+            parser.eat_where_predicates(&mut generics);
+            let where_clause = generics.where_clause("makepad_microserde :: SerRon");
 
-            tb.add("impl").stream(generic.clone());
-            tb.add("SerRon for").ident(&name).stream(generic).stream(where_clause);
+            tb.add("impl").stream(generics.impl_generics());
+            tb.add("SerRon for").ident(&name).stream(generics.type_generics()).stream(where_clause);
             tb.add("{ fn ser_ron ( & self , d : usize , s : & mut makepad_microserde :: SerRonState ) {");
             tb.add("match self {");
-            
+
             if !parser.open_brace(){
                 return parser.unexpected()
             }
 
             while !parser.eat_eot(){
+                let attrs = if let Some(attrs) = parser.eat_attributes() {attrs} else {return parser.unexpected()};
                 // parse ident
                 if let Some(variant) = parser.eat_any_ident(){
+                    let label = attrs_value(&attrs, "rename").unwrap_or(variant.clone());
                     if let Some(types) = parser.eat_all_types(){
-                        
+
                         tb.add("Self ::").ident(&variant).add("(");
                         for i in 0..types.len(){
                             tb.ident(&format!("n{}", i)).add(",");
                         }
                         tb.add(") => {");
-                        tb.add("s . out . push_str (").string(&variant).add(") ;");
+                        tb.add("s . out . push_str (").string(&label).add(") ;");
                         tb.add("s . out . push (").chr('(').add(") ;");
-                        
+
                         for i in 0..types.len(){
                             tb.ident(&format!("n{}", i)).add(". ser_ron ( d , s ) ;");
                             if i != types.len() - 1{
@@ -90,30 +212,19 @@ pub fn derive_ser_ron_impl(input: TokenStream) -> TokenStream {
                     }
                     else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
                         tb.add("Self ::").ident(&variant).add("{");
-                        for (field, _ty) in fields.iter(){
-                            tb.ident(field).add(",");
+                        for field in fields.iter(){
+                            tb.ident(&field.name).add(",");
                         }
                         tb.add("} => {");
-                        
-                        tb.add("s . out . push_str (").string(&variant).add(") ;");
+
+                        tb.add("s . out . push_str (").string(&label).add(") ;");
                         tb.add("s . st_pre ( ) ;");
-                        
-                        for (field, ty) in fields{
-                            if ty.into_iter().next().unwrap().to_string() == "Option"{
-                                tb.add("if ").ident(&field).add(". is_some ( ) {");
-                                tb.add("s . field ( d + 1 ,").string(&field).add(") ;");
-                                tb.ident(&field).add(" . ser_ron ( d + 1 , s ) ; s . conl ( ) ; } ;");
-                            }
-                            else{
-                                tb.add("s . field ( d + 1 ,").string(&field).add(" ) ;");
-                                tb.ident(&field).add(". ser_ron ( d + 1 , s ) ; s . conl ( ) ;");
-                            }
-                        }
+                        ser_ron_fields(&mut tb, &fields, "");
                         tb.add("s . st_post ( d ) ; }");
                     }
                     else if parser.is_punct(',') || parser.is_eot(){ // bare variant
                         tb.add("Self ::").ident(&variant).add("=> {");
-                        tb.add("s . out . push_str (").string(&variant).add(") ; }");
+                        tb.add("s . out . push_str (").string(&label).add(") ; }");
                     }
                     else{
                         return parser.unexpected();
@@ -135,15 +246,17 @@ pub fn derive_ser_ron_impl(input: TokenStream) -> TokenStream {
 pub fn derive_de_ron_impl(input: TokenStream) -> TokenStream {
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
-    parser.eat_ident("pub");
+    let container_attrs = if let Some(attrs) = parser.eat_attributes() {attrs} else {return parser.unexpected()};
+    parser.eat_visibility();
     if parser.eat_ident("struct"){
         if let Some(name) = parser.eat_any_ident(){
-            let generic = parser.eat_generic();
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
             let types = parser.eat_all_types();
-            let where_clause = parser.eat_where_clause(Some("DeRon"));
+            parser.eat_where_predicates(&mut generics);
+            let where_clause = generics.where_clause("makepad_microserde :: DeRon");
 
-            tb.add("impl").stream(generic.clone());
-            tb.add("DeRon for").ident(&name).stream(generic).stream(where_clause);
+            tb.add("impl").stream(generics.impl_generics());
+            tb.add("DeRon for").ident(&name).stream(generics.type_generics()).stream(where_clause.clone());
             tb.add("{ fn de_ron ( s : &  mut makepad_microserde :: DeRonState , i : & mut std :: str :: Chars )");
             tb.add("-> std :: result :: Result < Self , makepad_microserde :: DeRonErr > { ");
 
@@ -151,109 +264,92 @@ pub fn derive_de_ron_impl(input: TokenStream) -> TokenStream {
                 tb.add("s . paren_open ( i ) ? ;");
                 tb.add("let r = Self");
                 tb.add("(");
-                for _ in 0..types.len(){
-                     tb.add("{ let r = DeRon :: de_ron ( s , i ) ? ; s . eat_comma_paren ( i ) ? ; r } ,");
+                for index in 0..types.len(){
+                     tb.add("{ let r = DeRon :: de_ron ( s , i )");
+                     err_at_index(&mut tb, index);
+                     tb.add("? ; s . eat_comma_paren ( i ) ? ; r } ,");
                 }
                 tb.add(") ;");
                 tb.add("s . paren_close ( i ) ? ;");
                 tb.add("std :: result :: Result :: Ok ( r ) ");
+                tb.add("} } ;");
             }
-            else if let Some(fields) = parser.eat_all_struct_fields(){ 
-                tb.add("s . paren_open ( i ) ? ;");
-                for (field,_ty) in &fields{
-                    tb.add("let mut").ident(&format!("_{}",field)).add("= None ;");
-                }
-                tb.add("while let Some ( _ ) = s . next_ident ( ) {");
-                tb.add("match s . identbuf . as_ref ( ) {");
-                for (field,_ty) in &fields{
-                    tb.string(&field).add("=> { s . next_colon ( i ) ? ;");
-                    tb.ident(&format!("_{}",field)).add("= Some ( DeRon :: de_ron ( s , i ) ? ) ; } ,");
+            else if let Some(fields) = parser.eat_all_struct_fields(){
+                let container_default = attrs_has(&container_attrs, "default");
+                tb.add("std :: result :: Result :: Ok ( {");
+                de_ron_named(&mut tb, "Self", &fields, container_default);
+                tb.add("} )");
+                tb.add("} }");
+
+                // the same fields, read one key at a time so a parent can #[nserde(flatten)] us
+                let slots: Vec<String> = (0..fields.len()).map( | i | format!("p . {}", i)).collect();
+                tb.add("impl").stream(generics.impl_generics());
+                tb.add("makepad_microserde :: DeRonFlat for").ident(&name).stream(generics.type_generics()).stream(where_clause);
+                tb.add("{ type Partial = (");
+                for field in &fields {
+                    de_ron_slot_type(&mut tb, field);
+                    tb.add(",");
                 }
-                tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . identbuf ) )");
-                tb.add("} ; s . eat_comma_paren ( i ) ? ;");
-                tb.add("} ; s . paren_close ( i ) ? ;");
-                
-                tb.add("std :: result :: Result :: Ok ( Self {");
-                for (field,ty) in fields{
-                    tb.ident(&field).add(":");
-                    if ty.into_iter().next().unwrap().to_string() == "Option"{
-                        tb.add("if let Some ( t ) =").ident(&format!("_{}",field));
-                        tb.add("{ t } else { None } ,");
-                    }
-                    else{
-                        tb.add("if let Some ( t ) =").ident(&format!("_{}",field));
-                        tb.add("{ t } else { return Err ( s . err_nf (");
-                        tb.string(&field).add(") ) } ,");
-                    }
+                tb.add(") ;");
+                tb.add("fn de_ron_flat_new ( ) -> Self :: Partial { (");
+                for field in &fields {
+                    de_ron_slot_init(&mut tb, field);
+                    tb.add(",");
                 }
-                tb.add("} )");
+                tb.add(") }");
+                tb.add("fn de_ron_flat_field ( p : & mut Self :: Partial , s : & mut makepad_microserde :: DeRonState , i : & mut std :: str :: Chars )");
+                tb.add("-> std :: result :: Result < bool , makepad_microserde :: DeRonErr > {");
+                tb.add("let _ = & p ;");
+                de_ron_field_match(&mut tb, &fields, &slots, "return std :: result :: Result :: Ok ( false )");
+                tb.add("std :: result :: Result :: Ok ( true ) }");
+                tb.add("fn de_ron_flat_finish ( p : Self :: Partial , s : & mut makepad_microserde :: DeRonState )");
+                tb.add("-> std :: result :: Result < Self , makepad_microserde :: DeRonErr > {");
+                tb.add("let _ = ( & p , & s ) ; std :: result :: Result :: Ok ( Self {");
+                de_ron_field_values(&mut tb, &fields, &slots, container_default);
+                tb.add("} ) } } ;");
             }
             else{
                 return parser.unexpected()
             }
-            tb.add("} } ;"); 
             return tb.end();
         }
     }
     else if parser.eat_ident("enum"){
-         
+
         if let Some(name) = parser.eat_any_ident(){
-            let generic = parser.eat_generic();
-            let where_clause = parser.eat_where_clause(Some("DeRon"));
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
+            parser.eat_where_predicates(&mut generics);
+            let where_clause = generics.where_clause("makepad_microserde :: DeRon");
 
-            tb.add("impl").stream(generic.clone());
-            tb.add("DeRon for").ident(&name).stream(generic).stream(where_clause);
+            tb.add("impl").stream(generics.impl_generics());
+            tb.add("DeRon for").ident(&name).stream(generics.type_generics()).stream(where_clause);
             tb.add("{ fn de_ron ( s : & mut makepad_microserde :: DeRonState , i : & mut std :: str :: Chars )");
             tb.add("-> std :: result :: Result < Self , makepad_microserde :: DeRonErr > { ");
             tb.add("s . ident ( i ) ? ;");
             tb.add("std :: result :: Result :: Ok ( match s . identbuf . as_ref ( ) {");
-            
+
             if !parser.open_brace(){
                 return parser.unexpected()
             }
             while !parser.eat_eot(){
+                let attrs = if let Some(attrs) = parser.eat_attributes() {attrs} else {return parser.unexpected()};
                 // parse ident
                 if let Some(variant) = parser.eat_any_ident(){
-                    tb.string(&variant).add("=> {");
+                    tb.string(&attrs_value(&attrs, "rename").unwrap_or(variant.clone())).add("=> {");
                     if let Some(types) = parser.eat_all_types(){
-                        
+
                         tb.add("s . paren_open ( i ) ? ;");
                         tb.add("let r = Self ::").ident(&variant).add("(");
-                        for _ in 0..types.len(){
-                            tb.add("{ let r = DeRon :: de_ron ( s , i ) ? ; s . eat_comma_paren ( i ) ? ; r } ,");
+                        for index in 0..types.len(){
+                            tb.add("{ let r = DeRon :: de_ron ( s , i )");
+                            err_at_index(&mut tb, index);
+                            tb.add("? ; s . eat_comma_paren ( i ) ? ; r } ,");
                         }
                         tb.add(") ;");
                         tb.add("s . paren_close ( i ) ? ; r");
                     }
                     else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
-                        tb.add("s . paren_open ( i ) ? ;");
-                        for (field,_ty) in &fields{
-                            tb.add("let mut").ident(&format!("_{}",field)).add("= None ;");
-                        }
-                        tb.add("while let Some ( _ ) = s . next_ident ( ) {");
-                        tb.add("match s . identbuf . as_ref ( ) {");
-                        for (field,_ty) in &fields{
-                            tb.string(&field).add("=> { s . next_colon ( i ) ? ;");
-                            tb.ident(&format!("_{}",field)).add("= Some ( DeRon :: de_ron ( s , i ) ? ) ; } ,");
-                        }
-                        tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
-                        tb.add("} ; s . eat_comma_paren ( i ) ? ;");
-                        tb.add("} ; s . paren_close ( i ) ? ;");
-                        
-                        tb.add("Self ::").ident(&variant).add("{");
-                        for (field,ty) in fields{
-                            tb.ident(&field).add(":");
-                            if ty.into_iter().next().unwrap().to_string() == "Option"{
-                                tb.add("if let Some ( t ) =").ident(&format!("_{}",field));
-                                tb.add("{ t } else { None } ,");
-                            }
-                            else{
-                                tb.add("if let Some ( t ) =").ident(&format!("_{}",field));
-                                tb.add("{ t } else { return Err ( s . err_nf (");
-                                tb.string(&field).add(") ) } ,");
-                            }
-                        }
-                        tb.add("}");
+                        de_ron_named(&mut tb, &format!("Self :: {}", variant), &fields, false);
                     }
                     else if parser.is_punct(',') || parser.is_eot(){ // bare variant
                         tb.add("Self ::").ident(&variant);
@@ -261,14 +357,14 @@ pub fn derive_de_ron_impl(input: TokenStream) -> TokenStream {
                     else{
                         return parser.unexpected();
                     }
-                    
+
                     tb.add("}");
                     parser.eat_punct(',');
                 }
                 else{
                     return parser.unexpected()
                 }
-            } 
+            }
             tb.add("_ => return std :: result :: Result :: Err ( s . err_enum ( & s . identbuf ) )");
             tb.add("} ) } }");
            return tb.end();
@@ -0,0 +1,156 @@
+use proc_macro::{TokenStream};
+use crate::macro_lib::*;
+use crate::derive_json::*;
+
+// <ty as SchemaJson>::schema_json(defs)
+fn schema_json_value(tb: &mut TokenBuilder, ty: &TokenStream) {
+    tb.add("<").stream(Some(ty.clone())).add("as makepad_microserde :: SchemaJson > :: schema_json ( defs )");
+}
+
+// o.field(..) for every field DeJson reads, the ones it can leave out aren't required
+fn schema_json_fields(tb: &mut TokenBuilder, fields: &[Field], container_default: bool) {
+    for field in fields {
+        if field.is_skip() {
+            continue
+        }
+        if field.is_flatten() {
+            tb.add("o . flatten (");
+            schema_json_value(tb, &field.ty);
+            tb.add(", defs ) ;");
+        }
+        else {
+            let required = !(field.is_option() || field.is_default() || container_default);
+            tb.add("o . field (").string(&field.key()).add(",");
+            schema_json_value(tb, &field.ty);
+            tb.add(",").ident(if required {"true"} else {"false"}).add(") ;");
+        }
+    }
+}
+
+// a block building an object schema, `tag` puts a const label in front of the fields
+fn schema_json_object(tb: &mut TokenBuilder, tag: Option<(&str, &str)>, body: &dyn Fn(&mut TokenBuilder)) {
+    tb.add("{ let mut o = makepad_microserde :: SchemaJsonObject :: new ( ) ;");
+    if let Some((tag, label)) = tag {
+        tb.add("o . field (").string(tag).add(", makepad_microserde :: schema_json_const (").string(label).add(") , true ) ;");
+    }
+    body(tb);
+    tb.add("o . build ( ) }");
+}
+
+fn schema_json_tuple(tb: &mut TokenBuilder, types: &[TokenStream]) {
+    tb.add("makepad_microserde :: schema_json_tuple ( vec ! [");
+    for ty in types {
+        schema_json_value(tb, ty);
+        tb.add(",");
+    }
+    tb.add("] )");
+}
+
+// the variant data as ser_json_content writes it
+fn schema_json_content(tb: &mut TokenBuilder, variant: &Variant) {
+    match &variant.kind {
+        VariantKind::Unit => {
+            tb.add("makepad_microserde :: schema_json_type (").string("null").add(")");
+        }
+        VariantKind::Tuple(types) if types.len() == 1 => schema_json_value(tb, &types[0]),
+        VariantKind::Tuple(types) => schema_json_tuple(tb, types),
+        VariantKind::Named(fields) => schema_json_object(tb, None, &| tb | schema_json_fields(tb, fields, false)),
+    }
+}
+
+fn schema_json_variant(tb: &mut TokenBuilder, repr: &EnumRepr, variant: &Variant) {
+    match repr {
+        EnumRepr::External => {
+            // {"Variant":[..]} or {"Variant":{..}}, unit variants are an empty array
+            schema_json_object(tb, None, &| tb | {
+                tb.add("o . field (").string(&variant.label).add(",");
+                match &variant.kind {
+                    VariantKind::Unit => schema_json_tuple(tb, &[]),
+                    VariantKind::Tuple(types) => schema_json_tuple(tb, types),
+                    VariantKind::Named(_) => schema_json_content(tb, variant),
+                }
+                tb.add(", true ) ;");
+            });
+        }
+        EnumRepr::Internal(tag) => {
+            schema_json_object(tb, Some((tag, &variant.label)), &| tb | {
+                match &variant.kind {
+                    VariantKind::Unit => (),
+                    VariantKind::Tuple(types) => {
+                        tb.add("o . flatten (");
+                        schema_json_value(tb, &types[0]);
+                        tb.add(", defs ) ;");
+                    }
+                    VariantKind::Named(fields) => schema_json_fields(tb, fields, false),
+                }
+            });
+        }
+        EnumRepr::Adjacent(tag, content) => {
+            schema_json_object(tb, Some((tag, &variant.label)), &| tb | {
+                if !matches!(variant.kind, VariantKind::Unit) {
+                    tb.add("o . field (").string(content).add(",");
+                    schema_json_content(tb, variant);
+                    tb.add(", true ) ;");
+                }
+            });
+        }
+        EnumRepr::Untagged => schema_json_content(tb, variant),
+    }
+}
+
+pub fn derive_schema_json_impl(input: TokenStream) -> TokenStream {
+    let mut parser = TokenParser::new(input);
+    let mut tb = TokenBuilder::new();
+
+    let container_attrs = if let Some(attrs) = parser.eat_attributes() {attrs} else {return parser.unexpected()};
+    parser.eat_visibility();
+    let is_struct = parser.eat_ident("struct");
+    if !is_struct && !parser.eat_ident("enum") {
+        return parser.unexpected()
+    }
+    let name = if let Some(name) = parser.eat_any_ident() {name} else {return parser.unexpected()};
+    let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
+    let types = if is_struct {parser.eat_all_types()} else {None};
+    parser.eat_where_predicates(&mut generics);
+    let where_clause = generics.where_clause("makepad_microserde :: SchemaJson");
+
+    tb.add("impl").stream(generics.impl_generics());
+    tb.add("makepad_microserde :: SchemaJson for").ident(&name).stream(generics.type_generics()).stream(where_clause);
+    tb.add("{ fn schema_json ( defs : & mut makepad_microserde :: SchemaJsonDefs ) -> makepad_microserde :: JsonValue {");
+    tb.add("defs . define_type :: < Self > ( | defs | {");
+
+    if is_struct {
+        if let Some(types) = types {
+            schema_json_tuple(&mut tb, &types);
+        }
+        else if let Some(fields) = parser.eat_all_struct_fields() {
+            let container_default = attrs_has(&container_attrs, "default");
+            schema_json_object(&mut tb, None, &| tb | schema_json_fields(tb, &fields, container_default));
+        }
+        else {
+            return parser.unexpected()
+        }
+    }
+    else {
+        let repr = match enum_repr(&container_attrs) {Ok(repr) => repr, Err(err) => return err};
+        let variants = if let Some(variants) = parse_variants(&mut parser) {variants} else {return parser.unexpected()};
+        if let EnumRepr::Internal(_) = repr {
+            if variants.iter().any( | v | matches!(&v.kind, VariantKind::Tuple(types) if types.len() != 1)) {
+                return error(INTERNAL_TUPLE)
+            }
+        }
+        if let EnumRepr::Untagged = repr {
+            tb.add("makepad_microserde :: schema_json_any_of ( vec ! [");
+        }
+        else {
+            tb.add("makepad_microserde :: schema_json_one_of ( vec ! [");
+        }
+        for variant in &variants {
+            schema_json_variant(&mut tb, &repr, variant);
+            tb.add(",");
+        }
+        tb.add("] )");
+    }
+    tb.add("} ) } }");
+    tb.end()
+}
@@ -0,0 +1,289 @@
+use proc_macro::{TokenStream};
+use crate::macro_lib::*;
+
+// inserts "key" = value into the BTreeMap `map` for every field, prefix is how a field is reached
+fn ser_toml_fields(tb: &mut TokenBuilder, fields: &[Field], prefix: &str, map: &str) {
+    for field in fields {
+        if field.is_skip() {
+            continue
+        }
+        if field.is_flatten() {
+            tb.add("if let makepad_microserde :: Toml :: Table ( t ) =").add(prefix).ident(&field.name).add(". ser_toml ( ) {");
+            tb.ident(map).add(". extend ( t ) ; }");
+        }
+        else if field.is_option() {
+            tb.add("if let Some ( t ) = &").add(prefix).ident(&field.name).add("{");
+            tb.ident(map).add(". insert (").string(&field.key()).add(". to_string ( ) , t . ser_toml ( ) ) ; }");
+        }
+        else {
+            tb.ident(map).add(". insert (").string(&field.key()).add(". to_string ( ) ,");
+            tb.add(prefix).ident(&field.name).add(". ser_toml ( ) ) ;");
+        }
+    }
+}
+
+// builds `path { .. }` out of the table `t`, recording the keys it reads in `used`
+fn de_toml_named(tb: &mut TokenBuilder, path: &str, fields: &[Field], container_default: bool) {
+    tb.add("{ let m = t . de_table ( ) ? ;");
+    // flattened fields go last so a flattened Toml only picks up what is left
+    for field in fields.iter().filter( | f | !f.is_skip() && !f.is_flatten()) {
+        tb.add("let").ident(&format!("_{}", field.name)).add("= match m . get (").string(&field.key()).add(") {");
+        tb.add("Some ( v ) => DeToml :: de_toml_entry ( v )");
+        err_at_field(tb, &field.key());
+        tb.add("? ,");
+        tb.add("None => {");
+        if field.is_default() {
+            field.default_value(tb);
+        }
+        else if field.is_option() {
+            tb.add("None");
+        }
+        else if container_default {
+            tb.add("< Self as Default > :: default ( ) .").ident(&field.name);
+        }
+        else {
+            tb.add("return Err ( makepad_microserde :: DeTomlErr :: nf (").string(&field.key()).add(") )");
+        }
+        tb.add("} } ;");
+        tb.add("used . push (").string(&field.key()).add(". to_string ( ) ) ;");
+    }
+    for field in fields.iter().filter( | f | f.is_flatten()) {
+        tb.add("let").ident(&format!("_{}", field.name)).add("= <").stream(Some(field.ty.clone()));
+        tb.add("as DeToml > :: de_toml_flat ( t , used ) ? ;");
+    }
+    tb.add("let _ = m ;");
+    tb.add(path).add("{");
+    for field in fields {
+        tb.ident(&field.name).add(":");
+        if field.is_skip() {
+            field.default_value(tb);
+        }
+        else {
+            tb.ident(&format!("_{}", field.name));
+        }
+        tb.add(",");
+    }
+    tb.add("} }");
+}
+
+fn de_toml_unknown_keys(tb: &mut TokenBuilder) {
+    tb.add("for k in t . de_table ( ) ? . keys ( ) { if ! used . contains ( k ) {");
+    tb.add("return Err ( makepad_microserde :: DeTomlErr :: unknown ( k ) ) } }");
+}
+
+pub fn derive_ser_toml_impl(input: TokenStream) -> TokenStream {
+
+    let mut parser = TokenParser::new(input);
+    let mut tb = TokenBuilder::new();
+
+    if parser.eat_attributes().is_none() {
+        return parser.unexpected()
+    }
+    parser.eat_visibility();
+    if parser.eat_ident("struct"){
+        if let Some(name) = parser.eat_any_ident(){
+
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
+            let types = parser.eat_all_types();
+            parser.eat_where_predicates(&mut generics);
+            let where_clause = generics.where_clause("makepad_microserde :: SerToml");
+
+            tb.add("impl").stream(generics.impl_generics());
+            tb.add("SerToml for").ident(&name).stream(generics.type_generics()).stream(where_clause);
+            tb.add("{ fn ser_toml ( & self ) -> makepad_microserde :: Toml {");
+
+            if let Some(types) = types{
+                tb.add("makepad_microserde :: Toml :: Array ( vec ! [");
+                for i in 0..types.len(){
+                     tb.add("self .").unsuf_usize(i).add(". ser_toml ( ) ,");
+                }
+                tb.add("] )");
+            }
+            else if let Some(fields) = parser.eat_all_struct_fields(){
+                tb.add("let mut m = std :: collections :: BTreeMap :: new ( ) ;");
+                ser_toml_fields(&mut tb, &fields, "self .", "m");
+                tb.add("makepad_microserde :: Toml :: Table ( m )");
+            }
+            else{
+                return parser.unexpected()
+            }
+            tb.add("} } ;");
+            return tb.end();
+        }
+    }
+    else if parser.eat_ident("enum"){
+        if let Some(name) = parser.eat_any_ident(){
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
+            parser.eat_where_predicates(&mut generics);
+            let where_clause = generics.where_clause("makepad_microserde :: SerToml");
+
+            tb.add("impl").stream(generics.impl_generics());
+            tb.add("SerToml for").ident(&name).stream(generics.type_generics()).stream(where_clause);
+            tb.add("{ fn ser_toml ( & self ) -> makepad_microserde :: Toml {");
+            tb.add("match self {");
+
+            if !parser.open_brace(){
+                return parser.unexpected()
+            }
+
+            while !parser.eat_eot(){
+                let attrs = if let Some(attrs) = parser.eat_attributes() {attrs} else {return parser.unexpected()};
+                // parse ident
+                if let Some(variant) = parser.eat_any_ident(){
+                    let label = attrs_value(&attrs, "rename").unwrap_or(variant.clone());
+                    if let Some(types) = parser.eat_all_types(){
+                        tb.add("Self ::").ident(&variant).add("(");
+                        for i in 0..types.len(){
+                            tb.ident(&format!("n{}", i)).add(",");
+                        }
+                        tb.add(") => {");
+                        tb.add("let mut m = std :: collections :: BTreeMap :: new ( ) ;");
+                        tb.add("m . insert (").string(&label).add(". to_string ( ) , makepad_microserde :: Toml :: Array ( vec ! [");
+                        for i in 0..types.len(){
+                            tb.ident(&format!("n{}", i)).add(". ser_toml ( ) ,");
+                        }
+                        tb.add("] ) ) ; makepad_microserde :: Toml :: Table ( m ) }");
+                    }
+                    else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
+                        tb.add("Self ::").ident(&variant).add("{");
+                        for field in fields.iter(){
+                            tb.ident(&field.name).add(",");
+                        }
+                        tb.add("} => {");
+                        tb.add("let mut v = std :: collections :: BTreeMap :: new ( ) ;");
+                        ser_toml_fields(&mut tb, &fields, "", "v");
+                        tb.add("let mut m = std :: collections :: BTreeMap :: new ( ) ;");
+                        tb.add("m . insert (").string(&label).add(". to_string ( ) , makepad_microserde :: Toml :: Table ( v ) ) ;");
+                        tb.add("makepad_microserde :: Toml :: Table ( m ) }");
+                    }
+                    else if parser.is_punct(',') || parser.is_eot(){ // bare variant
+                        tb.add("Self ::").ident(&variant).add("=> {");
+                        tb.add("makepad_microserde :: Toml :: Str (").string(&label).add(". to_string ( ) ) }");
+                    }
+                    else{
+                        return parser.unexpected();
+                    }
+                    parser.eat_punct(',');
+                }
+                else{
+                    return parser.unexpected()
+                }
+            }
+            tb.add("} } } ;");
+            return tb.end();
+        }
+    }
+    return parser.unexpected()
+}
+
+pub fn derive_de_toml_impl(input: TokenStream) -> TokenStream {
+    let mut parser = TokenParser::new(input);
+    let mut tb = TokenBuilder::new();
+
+    let container_attrs = if let Some(attrs) = parser.eat_attributes() {attrs} else {return parser.unexpected()};
+    parser.eat_visibility();
+    if parser.eat_ident("struct"){
+        if let Some(name) = parser.eat_any_ident(){
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
+            let types = parser.eat_all_types();
+            parser.eat_where_predicates(&mut generics);
+            let where_clause = generics.where_clause("makepad_microserde :: DeToml");
+
+            tb.add("impl").stream(generics.impl_generics());
+            tb.add("DeToml for").ident(&name).stream(generics.type_generics()).stream(where_clause);
+            tb.add("{ fn de_toml ( t : & makepad_microserde :: Toml )");
+            tb.add("-> std :: result :: Result < Self , makepad_microserde :: DeTomlErr > {");
+
+            if let Some(types) = types{
+                tb.add("let a = t . de_tuple (").unsuf_usize(types.len()).add(") ? ;");
+                tb.add("std :: result :: Result :: Ok ( Self (");
+                for i in 0..types.len(){
+                     tb.add("DeToml :: de_toml ( & a [").unsuf_usize(i).add("] )");
+                     err_at_index(&mut tb, i);
+                     tb.add("? ,");
+                }
+                tb.add(") ) } } ;");
+            }
+            else if let Some(fields) = parser.eat_all_struct_fields(){
+                let container_default = attrs_has(&container_attrs, "default");
+                tb.add("let mut used = Vec :: new ( ) ;");
+                tb.add("let r = < Self as DeToml > :: de_toml_flat ( t , & mut used ) ? ;");
+                de_toml_unknown_keys(&mut tb);
+                tb.add("std :: result :: Result :: Ok ( r ) }");
+
+                tb.add("fn de_toml_flat ( t : & makepad_microserde :: Toml , used : & mut Vec < String > )");
+                tb.add("-> std :: result :: Result < Self , makepad_microserde :: DeTomlErr > {");
+                tb.add("let _ = & used ; std :: result :: Result :: Ok (");
+                de_toml_named(&mut tb, "Self", &fields, container_default);
+                tb.add(") } } ;");
+            }
+            else{
+                return parser.unexpected()
+            }
+            return tb.end();
+        }
+    }
+    else if parser.eat_ident("enum"){
+
+        if let Some(name) = parser.eat_any_ident(){
+            let mut generics = if let Some(generics) = parser.eat_generics() {generics} else {return parser.unexpected()};
+            parser.eat_where_predicates(&mut generics);
+            let where_clause = generics.where_clause("makepad_microserde :: DeToml");
+
+            tb.add("impl").stream(generics.impl_generics());
+            tb.add("DeToml for").ident(&name).stream(generics.type_generics()).stream(where_clause);
+            tb.add("{ fn de_toml ( t : & makepad_microserde :: Toml )");
+            tb.add("-> std :: result :: Result < Self , makepad_microserde :: DeTomlErr > {");
+            tb.add("let ( label , v ) = t . de_variant ( ) ? ;");
+            tb.add("let _ = & v ;");
+            tb.add("std :: result :: Result :: Ok ( match label {");
+
+            if !parser.open_brace(){
+                return parser.unexpected()
+            }
+            while !parser.eat_eot(){
+                let attrs = if let Some(attrs) = parser.eat_attributes() {attrs} else {return parser.unexpected()};
+                // parse ident
+                if let Some(variant) = parser.eat_any_ident(){
+                    tb.string(&attrs_value(&attrs, "rename").unwrap_or(variant.clone())).add("=> {");
+                    if let Some(types) = parser.eat_all_types(){
+                        tb.add("let t = makepad_microserde :: Toml :: de_variant_data ( v , label ) ? ;");
+                        tb.add("let a = t . de_tuple (").unsuf_usize(types.len()).add(") ? ;");
+                        tb.add("Self ::").ident(&variant).add("(");
+                        for i in 0..types.len(){
+                            tb.add("DeToml :: de_toml ( & a [").unsuf_usize(i).add("] )");
+                            err_at_index(&mut tb, i);
+                            tb.add("? ,");
+                        }
+                        tb.add(")");
+                    }
+                    else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
+                        tb.add("let t = makepad_microserde :: Toml :: de_variant_data ( v , label ) ? ;");
+                        tb.add("let used = & mut Vec :: new ( ) ;");
+                        tb.add("let r =");
+                        de_toml_named(&mut tb, &format!("Self :: {}", variant), &fields, false);
+                        tb.add(";");
+                        de_toml_unknown_keys(&mut tb);
+                        tb.add("r");
+                    }
+                    else if parser.is_punct(',') || parser.is_eot(){ // bare variant
+                        tb.add("Self ::").ident(&variant);
+                    }
+                    else{
+                        return parser.unexpected();
+                    }
+
+                    tb.add("}");
+                    parser.eat_punct(',');
+                }
+                else{
+                    return parser.unexpected()
+                }
+            }
+            tb.add("_ => return std :: result :: Result :: Err ( makepad_microserde :: DeTomlErr :: unknown_variant ( label ) )");
+            tb.add("} ) } } ;");
+            return tb.end();
+        }
+    }
+    return parser.unexpected()
+}
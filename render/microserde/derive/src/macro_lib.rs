@@ -67,7 +67,7 @@ impl TokenBuilder {
                 "]" => self.pop_group(Delimiter::Bracket),
                 "?" | ";" | "&" | "^" | ":" | "::" | "," | "!" | "." | "<<" | ">>" |
                 "->" | "=>" | "<" | ">" | "<=" | ">=" | "=" | "==" | "!=" |
-                "+" | "+=" | "-" | "-=" | "*" | "*=" | "/" | "/=" => self.punct(part),
+                "+" | "+=" | "-" | "-=" | "*" | "*=" | "/" | "/=" | "|" | "||" | ".." => self.punct(part),
                 _ => {
                     if part.len() == 0{
                         continue
@@ -102,8 +102,13 @@ impl TokenBuilder {
     pub fn string(&mut self, val: &str) -> &mut Self {self.extend(TokenTree::from(Literal::string(val)))}
     pub fn unsuf_usize(&mut self, val: usize) -> &mut Self {self.extend(TokenTree::from(Literal::usize_unsuffixed(val)))}
     pub fn suf_u16(&mut self, val: u16) -> &mut Self {self.extend(TokenTree::from(Literal::u16_suffixed(val)))}
+    pub fn suf_u32(&mut self, val: u32) -> &mut Self {self.extend(TokenTree::from(Literal::u32_suffixed(val)))}
     pub fn unsuf_f32(&mut self, val: f32) -> &mut Self {self.extend(TokenTree::from(Literal::f32_unsuffixed(val)))}
     pub fn chr(&mut self, val:char) -> &mut Self {self.extend(TokenTree::from(Literal::character(val)))}
+    pub fn lifetime(&mut self, name: &str) -> &mut Self {
+        self.extend(TokenTree::from(Punct::new('\'', Spacing::Joint)));
+        self.ident(name)
+    }
     pub fn _lit(&mut self, lit: Literal) -> &mut Self {self.extend(TokenTree::from(lit))}
     
     pub fn push_group(&mut self, delim: Delimiter) -> &mut Self {
@@ -172,6 +177,188 @@ impl<It> Iterator for Iter<It> where It: Iterator {
     }
 }
 
+// a single key or key = "value" entry out of #[nserde(...)]
+#[derive(Clone)]
+pub struct Attribute {
+    pub name: String,
+    pub value: Option<String>
+}
+
+pub fn attrs_has(attrs: &[Attribute], name: &str) -> bool {
+    attrs.iter().any( | a | a.name == name)
+}
+
+pub fn attrs_value(attrs: &[Attribute], name: &str) -> Option<String> {
+    attrs.iter().find( | a | a.name == name).and_then( | a | a.value.clone())
+}
+
+fn unquote_literal(lit: &Literal) -> String {
+    let s = lit.to_string();
+    let s = s.trim_start_matches('r').trim_matches('#');
+    if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+        s[1..s.len() - 1].replace("\\\"", "\"").replace("\\\\", "\\")
+    }
+    else {
+        s.to_string()
+    }
+}
+
+// appended to a value read so a deserialize error carries the field or index it came from
+pub fn err_at_field(tb: &mut TokenBuilder, name: &str) {
+    tb.add(". map_err ( | e | makepad_microserde :: DeErrAt :: at_field ( e ,").string(name).add(") )");
+}
+
+pub fn err_at_index(tb: &mut TokenBuilder, index: usize) {
+    tb.add(". map_err ( | e | makepad_microserde :: DeErrAt :: at_index ( e ,").unsuf_usize(index).add(") )");
+}
+
+pub struct Field {
+    pub attrs: Vec<Attribute>,
+    pub name: String,
+    pub ty: TokenStream
+}
+
+impl Field {
+    pub fn is_option(&self) -> bool {
+        self.ty.clone().into_iter().next().unwrap().to_string() == "Option"
+    }
+    
+    // the key used in text formats
+    pub fn key(&self) -> String {
+        attrs_value(&self.attrs, "rename").unwrap_or(self.name.clone())
+    }
+    
+    pub fn is_skip(&self) -> bool {
+        attrs_has(&self.attrs, "skip")
+    }
+    
+    pub fn is_flatten(&self) -> bool {
+        attrs_has(&self.attrs, "flatten")
+    }
+    
+    pub fn is_default(&self) -> bool {
+        attrs_has(&self.attrs, "default")
+    }
+    
+    // the value to use when the field is skipped or missing
+    pub fn default_value(&self, tb: &mut TokenBuilder) {
+        if let Some(path) = attrs_value(&self.attrs, "default") {
+            tb.stream(Some(path.parse().expect("Can't parse nserde default path")));
+            tb.add("( )");
+        }
+        else {
+            tb.add("Default :: default ( )");
+        }
+    }
+}
+
+// the <...> and where clause of a type definition, kept as tokens so lifetimes,
+// const parameters and defaults pass through as written
+#[derive(Default)]
+pub struct Generics {
+    pub params: Vec<GenericParam>,
+    pub predicates: Vec<Vec<TokenTree>>,
+}
+
+pub struct GenericParam {
+    // the parameter with its bounds but without its default
+    pub decl: Vec<TokenTree>,
+    pub name: Vec<TokenTree>,
+    pub is_type: bool,
+    pub is_lifetime: bool,
+}
+
+// splits tokens on the commas that aren't inside a <...>
+fn split_top_level(tokens: Vec<TokenTree>, at: char) -> Vec<Vec<TokenTree>> {
+    let mut parts = vec![Vec::new()];
+    let mut depth = 0;
+    let mut arrow = false;
+    for tt in tokens {
+        if let TokenTree::Punct(p) = &tt {
+            match p.as_char() {
+                '<' => depth += 1,
+                '>' if !arrow && depth > 0 => depth -= 1,
+                c if c == at && depth == 0 && p.spacing() == Spacing::Alone => {
+                    parts.push(Vec::new());
+                    arrow = false;
+                    continue
+                }
+                _ => ()
+            }
+            arrow = p.as_char() == '-' && p.spacing() == Spacing::Joint;
+        }
+        else {
+            arrow = false;
+        }
+        parts.last_mut().unwrap().push(tt);
+    }
+    parts.retain( | part | !part.is_empty());
+    parts
+}
+
+impl GenericParam {
+    fn parse(tokens: Vec<TokenTree>) -> Option<GenericParam> {
+        let decl = split_top_level(tokens, '=').into_iter().next() ?;
+        let (name, is_type, is_lifetime) = match (decl.first(), decl.get(1)) {
+            (Some(TokenTree::Punct(p)), Some(lifetime)) if p.as_char() == '\'' => (vec![decl[0].clone(), lifetime.clone()], false, true),
+            (Some(TokenTree::Ident(id)), Some(name)) if id.to_string() == "const" => (vec![name.clone()], false, false),
+            (Some(name), _) => (vec![name.clone()], true, false),
+            _ => return None
+        };
+        Some(GenericParam {decl, name, is_type, is_lifetime})
+    }
+}
+
+impl Generics {
+    fn list(&self, what: impl Fn(&GenericParam) -> &Vec<TokenTree>) -> Option<TokenStream> {
+        if self.params.is_empty() {
+            return None
+        }
+        let mut tb = TokenBuilder::new();
+        tb.add("<");
+        for param in &self.params {
+            for tt in what(param) {
+                tb.extend(tt.clone());
+            }
+            tb.add(",");
+        }
+        tb.add(">");
+        Some(tb.end())
+    }
+    
+    // for after `impl`
+    pub fn impl_generics(&self) -> Option<TokenStream> {
+        self.list( | param | &param.decl)
+    }
+    
+    // for after the type name
+    pub fn type_generics(&self) -> Option<TokenStream> {
+        self.list( | param | &param.name)
+    }
+    
+    // the where clause as written plus `T: bound` for every type parameter
+    pub fn where_clause(&self, bound: &str) -> Option<TokenStream> {
+        if self.predicates.is_empty() && !self.params.iter().any( | param | param.is_type) {
+            return None
+        }
+        let mut tb = TokenBuilder::new();
+        tb.add("where");
+        for predicate in &self.predicates {
+            for tt in predicate {
+                tb.extend(tt.clone());
+            }
+            tb.add(",");
+        }
+        for param in self.params.iter().filter( | param | param.is_type) {
+            for tt in &param.name {
+                tb.extend(tt.clone());
+            }
+            tb.add(":").add(bound).add(",");
+        }
+        Some(tb.end())
+    }
+}
+
 pub struct TokenParser {
     iter_stack: Vec<IntoIter>,
     current: Option<TokenTree>
@@ -288,7 +475,9 @@ impl TokenParser {
     pub fn eat_literal(&mut self) -> Option<Literal> {
         // check if our current thing is an ident, ifso eat it.
         if let Some(TokenTree::Literal(lit)) = &self.current {
-            return Some(lit.clone())
+            let ret = Some(lit.clone());
+            self.advance();
+            return ret
         }
         return None
     }
@@ -326,6 +515,59 @@ impl TokenParser {
         return None
     }
     
+    // the <...> after a type name, empty Generics when there is none
+    pub fn eat_generics(&mut self) -> Option<Generics> {
+        let mut generics = Generics::default();
+        if !self.eat_punct('<') {
+            return Some(generics)
+        }
+        let mut tokens = Vec::new();
+        let mut depth = 0;
+        let mut arrow = false;
+        loop {
+            let tt = self.current.clone() ?;
+            self.advance();
+            if let TokenTree::Punct(p) = &tt {
+                if p.as_char() == '<' {
+                    depth += 1;
+                }
+                else if p.as_char() == '>' && !arrow {
+                    if depth == 0 {
+                        break
+                    }
+                    depth -= 1;
+                }
+                arrow = p.as_char() == '-' && p.spacing() == Spacing::Joint;
+            }
+            else {
+                arrow = false;
+            }
+            tokens.push(tt);
+        }
+        for param in split_top_level(tokens, ',') {
+            generics.params.push(GenericParam::parse(param) ?);
+        }
+        Some(generics)
+    }
+    
+    // a where clause runs up to the { of the body or the ; after a tuple struct
+    pub fn eat_where_predicates(&mut self, generics: &mut Generics) {
+        if !self.eat_ident("where") {
+            return
+        }
+        let mut tokens = Vec::new();
+        while let Some(tt) = self.current.clone() {
+            match &tt {
+                TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => break,
+                TokenTree::Punct(p) if p.as_char() == ';' => break,
+                _ => ()
+            }
+            tokens.push(tt);
+            self.advance();
+        }
+        generics.predicates.extend(split_top_level(tokens, ','));
+    }
+    
     pub fn eat_where_clause(&mut self, add_where: Option<&str>) -> Option<TokenStream> {
         let mut tb = TokenBuilder::new();
         if self.eat_ident("where") {
@@ -379,26 +621,69 @@ impl TokenParser {
         return None
     }
     
-    pub fn eat_struct_field(&mut self) -> Option<(String, TokenStream)> {
+    // parses a run of #[...] attributes, only #[nserde(...)] ones are kept
+    pub fn eat_attributes(&mut self) -> Option<Vec<Attribute>> {
+        let mut attrs = Vec::new();
+        while self.eat_punct('#') {
+            if !self.open_bracket() {
+                return None
+            }
+            if self.eat_ident("nserde") {
+                if !self.open_paren() {
+                    return None
+                }
+                while !self.eat_eot() {
+                    if let Some(name) = self.eat_any_ident() {
+                        let value = if self.eat_punct('=') {
+                            if let Some(lit) = self.eat_literal() {
+                                Some(unquote_literal(&lit))
+                            }
+                            else {
+                                return None
+                            }
+                        }
+                        else {
+                            None
+                        };
+                        attrs.push(Attribute {name, value});
+                        self.eat_punct(',');
+                    }
+                    else {
+                        return None
+                    }
+                }
+            }
+            else {
+                while !self.is_eot() {
+                    self.advance();
+                }
+            }
+            self.eat_eot();
+        }
+        Some(attrs)
+    }
+    
+    pub fn eat_struct_field(&mut self) -> Option<Field> {
         // letsparse an ident
-        self.eat_ident("pub");
-        if let Some(field) = self.eat_any_ident() {
+        let attrs = self.eat_attributes() ?;
+        self.eat_visibility();
+        if let Some(name) = self.eat_any_ident() {
             if self.eat_punct(':') {
                 if let Some(ty) = self.eat_type() {
-                    return Some((field, ty))
+                    return Some(Field {attrs, name, ty})
                 }
             }
         }
         return None
     }
     
-    pub fn eat_all_struct_fields(&mut self, )->Option<Vec<(String, TokenStream)>>{
+    pub fn eat_all_struct_fields(&mut self, )->Option<Vec<Field>>{
         
         if self.open_brace(){
             let mut fields = Vec::new();
             while !self.eat_eot(){
-                if let Some((field, ty)) = self.eat_struct_field(){
-                    fields.push((field, ty));
+                if let Some(field) = self.eat_struct_field(){
+                    fields.push(field);
                     self.eat_punct(',');
                 }
                 else{
@@ -448,7 +733,7 @@ impl TokenParser {
         if self.open_paren(){
             let mut ret = Vec::new();
             while !self.eat_eot(){
-                self.eat_ident("pub");
+                self.eat_visibility();
                 if let Some(tt) = self.eat_type(){
                     ret.push(tt);
                     self.eat_punct(',');
@@ -464,34 +749,40 @@ impl TokenParser {
         }
     }
     
+    // a type runs up to the next comma outside of a <...>, so paths, references
+    // and lifetimes come along as written
     pub fn eat_type(&mut self) -> Option<TokenStream> {
         let mut tb = TokenBuilder::new();
-        if self.open_bracket(){ // array type
-            tb.add("[");
-            while !self.eat_eot(){
-                if let Some(current) = &self.current {
-                    tb.extend(current.clone());
+        let mut depth = 0;
+        let mut arrow = false;
+        let mut empty = true;
+        while let Some(tt) = self.current.clone() {
+            if let TokenTree::Punct(p) = &tt {
+                match p.as_char() {
+                    ',' if depth == 0 => break,
+                    '<' => depth += 1,
+                    '>' if !arrow => depth -= 1,
+                    _ => ()
                 }
-                self.advance();
+                arrow = p.as_char() == '-' && p.spacing() == Spacing::Joint;
             }
-            tb.add("]");
-            return Some(tb.end())
-        }
-        else if self.open_paren(){ // tuple type
-            tb.add("(");
-            while !self.eat_eot(){
-                tb.stream(self.eat_type());
-                self.eat_punct(',');
+            else {
+                arrow = false;
             }
-            tb.add(")");
-            return Some(tb.end());
+            tb.extend(tt);
+            self.advance();
+            empty = false;
         }
-        else if let Some(ty) = self.eat_any_ident() {
-            tb.ident(&ty);
-            tb.stream(self.eat_generic());
-
-            return Some(tb.end())
+        if empty {
+            return None
+        }
+        Some(tb.end())
+    }
+    
+    // pub, pub(crate) and pub(in path)
+    pub fn eat_visibility(&mut self) {
+        if self.eat_ident("pub") && self.is_paren() {
+            self.advance();
         }
-        return None
     }
 }
@@ -0,0 +1,148 @@
+// converts files between json, ron, toml and bin through a RonValue tree,
+// so key.ron, settings and test fixtures don't have to be rewritten by hand
+
+use makepad_microserde::*;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Json,
+    Ron,
+    Toml,
+    Bin,
+    BinCompact,
+}
+
+impl Format {
+    fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "json" => Some(Format::Json),
+            "ron" => Some(Format::Ron),
+            "toml" => Some(Format::Toml),
+            "bin" => Some(Format::Bin),
+            "bin-compact" => Some(Format::BinCompact),
+            _ => None
+        }
+    }
+
+    fn from_path(path: &str) -> Option<Format> {
+        Format::from_name(std::path::Path::new(path).extension()?.to_str()?)
+    }
+}
+
+fn print_help() {
+    println!("----- microserde format converter -----");
+    println!("microserde [--from <format>] [--to <format>] [--pretty|--compact] [input] [-o output]");
+    println!("formats are json, ron, toml, bin and bin-compact, they default to the file extensions");
+    println!("input and output default to stdin and stdout, output is pretty unless --compact");
+    println!("bin is written by ser_bin of a RonValue and bin-compact by ser_bin_compact,");
+    println!("a .bin file is read as bin, use --from bin-compact or --to bin-compact for the other");
+    println!("example: microserde key.ron -o key.json");
+    println!("example: cat settings.toml | microserde --from toml --to ron --compact");
+    println!();
+    println!("json and toml don't tell structs and maps apart, an object with identifier keys");
+    println!("comes back as a struct and one with a single capitalised key as an enum variant");
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    std::process::exit(1)
+}
+
+fn read_value(format: Format, name: &str, data: Vec<u8>) -> Result<RonValue, String> {
+    if format == Format::Bin || format == Format::BinCompact {
+        let mut o = 0;
+        let value = if format == Format::Bin {RonValue::de_bin(&mut o, &data)} else {RonValue::de_bin_compact(&mut o, &data)};
+        let value = value.map_err( | e | format!("error: {} is not a bin RonValue: {:?}", name, e)) ?;
+        if o != data.len() {
+            return Err(format!("error: {} has {} bytes after its bin RonValue", name, data.len() - o))
+        }
+        return Ok(value)
+    }
+    let source = String::from_utf8(data).map_err( | _ | format!("error: {} is not utf8", name)) ?;
+    match format {
+        Format::Json => JsonValue::deserialize_json(&source).map(RonValue::from).map_err( | e | e.render(name, &source)),
+        Format::Ron => RonValue::deserialize_ron(&source).map_err( | e | e.render(name, &source)),
+        Format::Toml => TomlParser::parse(&source).map(RonValue::from).map_err( | e | e.render(name, &source)),
+        Format::Bin | Format::BinCompact => unreachable!()
+    }
+}
+
+fn write_value(format: Format, compact: bool, value: RonValue) -> Result<Vec<u8>, String> {
+    let mut text = match format {
+        Format::Json if compact => JsonValue::from(value).serialize_json(),
+        Format::Json => JsonValue::from(value).serialize_json_pretty(),
+        Format::Ron if compact => value.serialize_ron_compact(),
+        Format::Ron => value.serialize_ron(),
+        Format::Toml => {
+            let toml = Toml::try_from(value).map_err( | e | format!("error: {:?}", e)) ?;
+            if !matches!(toml, Toml::Table(_)) {
+                return Err("error: a toml document has to be a table".to_string())
+            }
+            if compact {toml.serialize_toml_compact()} else {toml.serialize_toml()}
+        }
+        Format::Bin => return Ok(value.serialize_bin()),
+        Format::BinCompact => return Ok(value.serialize_bin_compact()),
+    };
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    Ok(text.into_bytes())
+}
+
+pub fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let mut from = None;
+    let mut to = None;
+    let mut compact = false;
+    let mut input = None;
+    let mut output = None;
+
+    let mut args = args.into_iter().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return print_help(),
+            "--from" | "--to" => {
+                let format = args.next().and_then( | name | Format::from_name(&name));
+                let format = format.unwrap_or_else( || fail(&format!("error: {} wants json, ron, toml, bin or bin-compact", arg)));
+                if arg == "--from" {from = Some(format)} else {to = Some(format)}
+            }
+            "--pretty" => compact = false,
+            "--compact" => compact = true,
+            "-o" => output = Some(args.next().unwrap_or_else( || fail("error: -o wants a file name"))),
+            _ if arg.starts_with('-') && arg != "-" => {
+                print_help();
+                fail(&format!("error: unknown option {}", arg))
+            }
+            _ if input.is_none() => input = Some(arg),
+            _ => fail("error: only one input file can be converted at a time")
+        }
+    }
+
+    let input = input.filter( | path | path != "-");
+    let from = from.or_else( || input.as_deref().and_then(Format::from_path));
+    let from = from.unwrap_or_else( || fail("error: can't tell the input format, use --from"));
+    let to = to.or_else( || output.as_deref().and_then(Format::from_path));
+    let to = to.unwrap_or_else( || fail("error: can't tell the output format, use --to"));
+
+    let (name, data) = match &input {
+        Some(path) => (path.clone(), std::fs::read(path).unwrap_or_else( | e | fail(&format!("error: can't read {}: {}", path, e)))),
+        None => {
+            let mut data = Vec::new();
+            std::io::stdin().read_to_end(&mut data).unwrap_or_else( | e | fail(&format!("error: can't read stdin: {}", e)));
+            ("<stdin>".to_string(), data)
+        }
+    };
+
+    let value = read_value(from, &name, data).unwrap_or_else( | e | fail(&e));
+    let data = write_value(to, compact, value).unwrap_or_else( | e | fail(&e));
+
+    let written = match &output {
+        Some(path) => std::fs::write(path, &data),
+        None => std::io::stdout().write_all(&data)
+    };
+    if let Err(e) = written {
+        fail(&format!("error: can't write output: {}", e))
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt;
+
+// the breadcrumb to where a deserialization error happened, like builders["main"].workspaces["makepad"]
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DePathSeg {
+    Field(String),
+    Key(String),
+    Index(usize)
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DePath(pub Vec<DePathSeg>);
+
+impl DePath {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+// written so parse_value_path reads it back
+impl fmt::Display for DePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, seg) in self.0.iter().enumerate() {
+            match seg {
+                DePathSeg::Field(name) => {
+                    if index != 0 {
+                        write!(f, ".") ?;
+                    }
+                    write!(f, "{}", name) ?;
+                }
+                DePathSeg::Key(key) => write!(f, "[{:?}]", key) ?,
+                DePathSeg::Index(index) => write!(f, "[{}]", index) ?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// errors travel outwards, so every level that hands one on puts its own segment in front
+pub trait DeErrAt: Sized {
+    fn path_mut(&mut self) -> &mut DePath;
+
+    fn at(mut self, seg: DePathSeg) -> Self {
+        self.path_mut().0.insert(0, seg);
+        self
+    }
+
+    fn at_field(self, name: &str) -> Self {
+        self.at(DePathSeg::Field(name.to_string()))
+    }
+
+    fn at_key(self, key: &str) -> Self {
+        self.at(DePathSeg::Key(key.to_string()))
+    }
+
+    fn at_index(self, index: usize) -> Self {
+        self.at(DePathSeg::Index(index))
+    }
+}
+
+// prints the message with the source line it points at and a caret under the column,
+// line and col are 0 based
+pub fn render_source_error(name: &str, source: &str, line: usize, col: usize, msg: &str, path: &DePath) -> String {
+    let mut out = format!("error: {}\n", msg);
+    let num = (line + 1).to_string();
+    let pad = " ".repeat(num.len());
+    out.push_str(&format!("{}--> {}:{}:{}\n", pad, name, line + 1, col + 1));
+    if let Some(text) = source.lines().nth(line) {
+        // tabs keep their width so the caret lines up
+        let indent: String = text.chars().take(col).map( | c | if c == '\t' {'\t'} else {' '}).collect();
+        out.push_str(&format!("{} |\n", pad));
+        out.push_str(&format!("{} | {}\n", num, text));
+        out.push_str(&format!("{} | {}^\n", pad, indent));
+    }
+    if !path.is_empty() {
+        out.push_str(&format!("{} = at {}\n", pad, path));
+    }
+    out
+}
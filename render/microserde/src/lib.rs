@@ -1,5 +1,8 @@
 pub use makepad_microserde_derive::*;
- 
+
+mod de_path;
+pub use crate::de_path::*;
+
 mod serde_bin;
 pub use crate::serde_bin::*;
 
@@ -10,4 +13,15 @@ mod serde_ron;
 pub use crate::serde_ron::*;
 
 mod toml;
-pub use crate::toml::*;
\ No newline at end of file
+pub use crate::toml::*;
+
+mod serde_toml;
+pub use crate::serde_toml::*;
+
+mod value;
+pub use crate::value::*;
+
+mod value_convert;
+
+mod schema_json;
+pub use crate::schema_json::*;
\ No newline at end of file
@@ -0,0 +1,365 @@
+use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+use crate::serde_json::*;
+use crate::value::*;
+
+// JSON Schema (draft 2020-12) for what DeJson reads, so editor tooling can validate and complete
+// files like makepad_settings.json without knowing our types
+
+pub const SCHEMA_JSON_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+pub trait SchemaJson {
+    // named types put their schema in defs once and return a $ref to it, which keeps recursive types finite
+    fn schema_json(defs: &mut SchemaJsonDefs) -> JsonValue;
+
+    // a complete document with $schema and $defs around the schema of Self
+    fn schema_json_document() -> JsonValue where Self: Sized {
+        let mut defs = SchemaJsonDefs::default();
+        let root = Self::schema_json(&mut defs);
+        let mut out = vec![("$schema".to_string(), JsonValue::String(SCHEMA_JSON_DRAFT.to_string()))];
+        if let JsonValue::Object(fields) = root {
+            out.extend(fields);
+        }
+        if !defs.defs.is_empty() {
+            out.push(("$defs".to_string(), JsonValue::Object(defs.defs)));
+        }
+        JsonValue::Object(out)
+    }
+
+    fn serialize_schema_json() -> String where Self: Sized {
+        Self::schema_json_document().serialize_json()
+    }
+}
+
+#[derive(Default)]
+pub struct SchemaJsonDefs {
+    pub defs: Vec<(String, JsonValue)>,
+    // the full type name behind every def made by define_type and the key it got
+    pub types: Vec<(String, String)>
+}
+
+impl SchemaJsonDefs {
+    // defines T under schema_json_name, a different type with the same name from another module gets a number behind it
+    pub fn define_type<T: ?Sized>(&mut self, f: impl FnOnce(&mut SchemaJsonDefs) -> JsonValue) -> JsonValue {
+        let type_name = std::any::type_name::<T>();
+        if let Some((_, name)) = self.types.iter().find( | (t, _) | t == type_name) {
+            return schema_json_ref(name)
+        }
+        let base = schema_json_name::<T>();
+        let mut name = base.clone();
+        let mut index = 2;
+        while self.defs.iter().any( | (n, _) | *n == name) {
+            name = format!("{}{}", base, index);
+            index += 1;
+        }
+        self.types.push((type_name.to_string(), name.clone()));
+        self.define(&name, f)
+    }
+
+    // the placeholder goes in before f runs so a type that contains itself finds its own $ref
+    pub fn define(&mut self, name: &str, f: impl FnOnce(&mut SchemaJsonDefs) -> JsonValue) -> JsonValue {
+        if !self.defs.iter().any( | (n, _) | n == name) {
+            let index = self.defs.len();
+            self.defs.push((name.to_string(), JsonValue::Null));
+            let schema = f(self);
+            self.defs[index].1 = schema;
+        }
+        schema_json_ref(name)
+    }
+
+    // follows a $ref into defs, anything else is returned as is
+    pub fn resolve<'a>(&'a self, schema: &'a JsonValue) -> &'a JsonValue {
+        if let Some(name) = schema.get("$ref").and_then( | r | r.as_str()).and_then( | r | r.strip_prefix("#/$defs/")) {
+            if let Some((_, def)) = self.defs.iter().find( | (n, _) | n == name) {
+                return def
+            }
+        }
+        schema
+    }
+}
+
+// the object a derived struct or struct variant builds up one field at a time
+#[derive(Default)]
+pub struct SchemaJsonObject {
+    pub properties: Vec<(String, JsonValue)>,
+    pub required: Vec<String>,
+    // a flattened JsonValue takes every key the struct doesn't know
+    pub open: bool
+}
+
+impl SchemaJsonObject {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field(&mut self, key: &str, schema: JsonValue, required: bool) {
+        self.properties.push((key.to_string(), schema));
+        if required {
+            self.required.push(key.to_string());
+        }
+    }
+
+    // #[nserde(flatten)], the fields of the inner object sit next to ours
+    pub fn flatten(&mut self, schema: JsonValue, defs: &SchemaJsonDefs) {
+        let schema = defs.resolve(&schema);
+        match schema.get("properties").and_then( | p | p.as_object()) {
+            Some(properties) => {
+                self.properties.extend(properties.iter().cloned());
+                if let Some(required) = schema.get("required").and_then( | r | r.as_array()) {
+                    self.required.extend(required.iter().filter_map( | r | r.as_str()).map( | r | r.to_string()));
+                }
+                if schema.get("additionalProperties") != Some(&JsonValue::Bool(false)) {
+                    self.open = true;
+                }
+            }
+            None => self.open = true
+        }
+    }
+
+    pub fn build(self) -> JsonValue {
+        let mut out = vec![
+            ("type".to_string(), JsonValue::String("object".to_string())),
+            ("properties".to_string(), JsonValue::Object(self.properties)),
+        ];
+        if !self.required.is_empty() {
+            out.push(("required".to_string(), JsonValue::Array(self.required.into_iter().map(JsonValue::String).collect())));
+        }
+        if !self.open {
+            out.push(("additionalProperties".to_string(), JsonValue::Bool(false)));
+        }
+        JsonValue::Object(out)
+    }
+}
+
+// the $defs key for a type, its type name without module paths so Wrapper<alloc::string::String> is Wrapper_String
+pub fn schema_json_name<T: ?Sized>() -> String {
+    let mut name = String::new();
+    let mut segment = String::new();
+    for c in std::any::type_name::<T>().chars() {
+        if c.is_alphanumeric() || c == '_' {
+            segment.push(c);
+        }
+        else if c == ':' {
+            segment.clear();
+        }
+        else if !segment.is_empty() {
+            name.push_str(&segment);
+            name.push('_');
+            segment.clear();
+        }
+    }
+    name.push_str(&segment);
+    name.trim_end_matches('_').to_string()
+}
+
+fn schema_json_obj(fields: Vec<(&str, JsonValue)>) -> JsonValue {
+    JsonValue::Object(fields.into_iter().map( | (k, v) | (k.to_string(), v)).collect())
+}
+
+pub fn schema_json_type(ty: &str) -> JsonValue {
+    schema_json_obj(vec![("type", JsonValue::String(ty.to_string()))])
+}
+
+pub fn schema_json_ref(name: &str) -> JsonValue {
+    schema_json_obj(vec![("$ref", JsonValue::String(format!("#/$defs/{}", name)))])
+}
+
+pub fn schema_json_const(label: &str) -> JsonValue {
+    schema_json_obj(vec![("const", JsonValue::String(label.to_string()))])
+}
+
+pub fn schema_json_array(items: JsonValue) -> JsonValue {
+    schema_json_obj(vec![("type", JsonValue::String("array".to_string())), ("items", items)])
+}
+
+// a fixed length array with a schema per position, tuples and tuple structs
+pub fn schema_json_tuple(items: Vec<JsonValue>) -> JsonValue {
+    let len = items.len() as u64;
+    schema_json_obj(vec![
+        ("type", JsonValue::String("array".to_string())),
+        ("prefixItems", JsonValue::Array(items)),
+        ("minItems", JsonValue::U64(len)),
+        ("items", JsonValue::Bool(false)),
+    ])
+}
+
+// exactly one variant matches, tagged enums
+pub fn schema_json_one_of(variants: Vec<JsonValue>) -> JsonValue {
+    schema_json_obj(vec![("oneOf", JsonValue::Array(variants))])
+}
+
+// untagged enums read the first variant that fits, so more than one may match
+pub fn schema_json_any_of(variants: Vec<JsonValue>) -> JsonValue {
+    schema_json_obj(vec![("anyOf", JsonValue::Array(variants))])
+}
+
+macro_rules!impl_schema_json_int {
+    ( $ ( $ ty: ident $ val: ident), *) => {
+        $ (
+            impl SchemaJson for $ ty {
+                fn schema_json(_defs: &mut SchemaJsonDefs) -> JsonValue {
+                    schema_json_obj(vec![
+                        ("type", JsonValue::String("integer".to_string())),
+                        ("minimum", JsonValue:: $ val( $ ty::MIN as _)),
+                        ("maximum", JsonValue:: $ val( $ ty::MAX as _)),
+                    ])
+                }
+            }
+        ) *
+    }
+}
+
+impl_schema_json_int!(u8 U64, u16 U64, u32 U64, u64 U64, usize U64, i8 I64, i16 I64, i32 I64, i64 I64);
+
+impl SchemaJson for u128 {
+    fn schema_json(_defs: &mut SchemaJsonDefs) -> JsonValue {
+        schema_json_obj(vec![("type", JsonValue::String("integer".to_string())), ("minimum", JsonValue::U64(0))])
+    }
+}
+
+impl SchemaJson for i128 {
+    fn schema_json(_defs: &mut SchemaJsonDefs) -> JsonValue {
+        schema_json_type("integer")
+    }
+}
+
+macro_rules!impl_schema_json_simple {
+    ( $ ( $ ty: ty, $ json: expr); *) => {
+        $ (
+            impl SchemaJson for $ ty {
+                fn schema_json(_defs: &mut SchemaJsonDefs) -> JsonValue {
+                    schema_json_type( $ json)
+                }
+            }
+        ) *
+    }
+}
+
+impl_schema_json_simple!(f32, "number"; f64, "number"; bool, "boolean"; String, "string"; str, "string"; (), "null");
+
+impl SchemaJson for char {
+    fn schema_json(_defs: &mut SchemaJsonDefs) -> JsonValue {
+        schema_json_obj(vec![
+            ("type", JsonValue::String("string".to_string())),
+            ("minLength", JsonValue::U64(1)),
+            ("maxLength", JsonValue::U64(1)),
+        ])
+    }
+}
+
+// any json at all
+impl SchemaJson for JsonValue {
+    fn schema_json(_defs: &mut SchemaJsonDefs) -> JsonValue {
+        JsonValue::Object(Vec::new())
+    }
+}
+
+impl<T> SchemaJson for Option<T> where T: SchemaJson {
+    fn schema_json(defs: &mut SchemaJsonDefs) -> JsonValue {
+        schema_json_any_of(vec![T::schema_json(defs), schema_json_type("null")])
+    }
+}
+
+macro_rules!impl_schema_json_seq {
+    ( $ ( $ ty: ident), *) => {
+        $ (
+            impl<T> SchemaJson for $ ty<T> where T: SchemaJson {
+                fn schema_json(defs: &mut SchemaJsonDefs) -> JsonValue {
+                    schema_json_array(T::schema_json(defs))
+                }
+            }
+        ) *
+    }
+}
+
+impl_schema_json_seq!(Vec, VecDeque);
+
+impl<T> SchemaJson for [T] where T: SchemaJson {
+    fn schema_json(defs: &mut SchemaJsonDefs) -> JsonValue {
+        schema_json_array(T::schema_json(defs))
+    }
+}
+
+impl<T, const N: usize> SchemaJson for [T; N] where T: SchemaJson {
+    fn schema_json(defs: &mut SchemaJsonDefs) -> JsonValue {
+        let mut out = schema_json_array(T::schema_json(defs));
+        if let JsonValue::Object(fields) = &mut out {
+            fields.push(("minItems".to_string(), JsonValue::U64(N as u64)));
+            fields.push(("maxItems".to_string(), JsonValue::U64(N as u64)));
+        }
+        out
+    }
+}
+
+macro_rules!impl_schema_json_set {
+    ( $ ( $ ty: ident), *) => {
+        $ (
+            impl<T> SchemaJson for $ ty<T> where T: SchemaJson {
+                fn schema_json(defs: &mut SchemaJsonDefs) -> JsonValue {
+                    let mut out = schema_json_array(T::schema_json(defs));
+                    if let JsonValue::Object(fields) = &mut out {
+                        fields.push(("uniqueItems".to_string(), JsonValue::Bool(true)));
+                    }
+                    out
+                }
+            }
+        ) *
+    }
+}
+
+impl_schema_json_set!(HashSet, BTreeSet);
+
+macro_rules!impl_schema_json_tuple {
+    ( $ ( $ ty: ident), *) => {
+        impl< $ ( $ ty), *> SchemaJson for ( $ ( $ ty), *) where $ ( $ ty: SchemaJson), * {
+            fn schema_json(defs: &mut SchemaJsonDefs) -> JsonValue {
+                schema_json_tuple(vec![ $ ( $ ty::schema_json(defs)), *])
+            }
+        }
+    }
+}
+
+impl_schema_json_tuple!(A, B);
+impl_schema_json_tuple!(A, B, C);
+impl_schema_json_tuple!(A, B, C, D);
+impl_schema_json_tuple!(A, B, C, D, E);
+impl_schema_json_tuple!(A, B, C, D, E, F);
+impl_schema_json_tuple!(A, B, C, D, E, F, G);
+impl_schema_json_tuple!(A, B, C, D, E, F, G, H);
+impl_schema_json_tuple!(A, B, C, D, E, F, G, H, I);
+impl_schema_json_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_schema_json_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_schema_json_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+// the keys are whatever DeJson reads for K, only the values can be described
+macro_rules!impl_schema_json_map {
+    ( $ ( $ ty: ident), *) => {
+        $ (
+            impl<K, V> SchemaJson for $ ty<K, V> where V: SchemaJson {
+                fn schema_json(defs: &mut SchemaJsonDefs) -> JsonValue {
+                    schema_json_obj(vec![
+                        ("type", JsonValue::String("object".to_string())),
+                        ("additionalProperties", V::schema_json(defs)),
+                    ])
+                }
+            }
+        ) *
+    }
+}
+
+impl_schema_json_map!(HashMap, BTreeMap);
+
+macro_rules!impl_schema_json_pointer {
+    ( $ ( $ ty: ident), *) => {
+        $ (
+            impl<T> SchemaJson for $ ty<T> where T: SchemaJson {
+                fn schema_json(defs: &mut SchemaJsonDefs) -> JsonValue {
+                    T::schema_json(defs)
+                }
+            }
+        ) *
+    }
+}
+
+impl_schema_json_pointer!(Box, Rc, Arc);
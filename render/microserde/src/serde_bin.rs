@@ -1,6 +1,9 @@
-use std::collections::{HashMap};
+use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
 use std::hash::Hash;
-use std::convert::TryInto;
+use crate::de_path::*;
+use std::convert::{TryFrom, TryInto};
 
 pub trait SerBin {
     fn serialize_bin(&self)->Vec<u8>{
@@ -8,8 +11,20 @@ pub trait SerBin {
         self.ser_bin(&mut s);
         s
     }
+
+    fn serialize_bin_compact(&self)->Vec<u8>{
+        let mut s = Vec::new();
+        self.ser_bin_compact(&mut s);
+        s
+    }
     
     fn ser_bin(&self, s: &mut Vec<u8>);
+
+    // the compact encoding writes integers and lengths as LEB128 varints, signed ones zigzagged,
+    // types without any write the same bytes as ser_bin
+    fn ser_bin_compact(&self, s: &mut Vec<u8>){
+        self.ser_bin(s)
+    }
 }
 
 pub trait DeBin:Sized {
@@ -17,7 +32,25 @@ pub trait DeBin:Sized {
         DeBin::de_bin(&mut 0, d)
     }
 
+    fn deserialize_bin_compact(d:&[u8])->Result<Self, DeBinErr>{
+        DeBin::de_bin_compact(&mut 0, d)
+    }
+
     fn de_bin(o:&mut usize, d:&[u8]) -> Result<Self, DeBinErr>;
+
+    // reads what ser_bin_compact wrote
+    fn de_bin_compact(o:&mut usize, d:&[u8]) -> Result<Self, DeBinErr>{
+        DeBin::de_bin(o, d)
+    }
+}
+
+// reads the DeBin format but hands out &'a str and &'a [u8] pointing into d instead of copying them out
+pub trait DeBinRef<'a>:Sized {
+    fn deserialize_bin_ref(d:&'a [u8])->Result<Self, DeBinErr>{
+        DeBinRef::de_bin_ref(&mut 0, d)
+    }
+
+    fn de_bin_ref(o:&mut usize, d:&'a [u8]) -> Result<Self, DeBinErr>;
 }
 
 
@@ -25,75 +58,306 @@ pub struct DeBinErr{
     pub msg: String,
     pub o:usize,
     pub l: usize,
-    pub s: usize
+    pub s: usize,
+    pub path: DePath
 }
 
 impl std::fmt::Display for DeBinErr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Bin deserialize error in:{}, at:{} wanted:{} bytes but max size is {}", self.msg, self.o, self.l, self.s)
+        write!(f, "Bin deserialize error in:{}, at:{} wanted:{} bytes but max size is {}", self.msg, self.o, self.l, self.s)?;
+        if !self.path.is_empty(){
+            write!(f, " at {}", self.path)?;
+        }
+        Ok(())
     }
 }
 
 impl std::fmt::Debug for DeBinErr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Bin deserialize error in:{}, at:{} wanted:{} bytes but max size is {}", self.msg, self.o, self.l, self.s)
+        std::fmt::Display::fmt(self, f)
     }
 }
 
+impl DeErrAt for DeBinErr {
+    fn path_mut(&mut self) -> &mut DePath {&mut self.path}
+}
+
+// #[nserde(tagged)] types write their body behind a u32 byte length, and every field and
+// variant behind a u32 tag hashed from its name, so an older reader can skip what it doesn't know
+pub fn bin_tag(name: &str) -> u32 {
+    let mut hash = 0x811c9dc5u32;
+    for b in name.bytes() {
+        hash = (hash ^ b as u32).wrapping_mul(0x01000193);
+    }
+    hash
+}
+
+// reserves the length and returns where it is, ser_bin_len_end fills it in
+pub fn ser_bin_len_begin(s: &mut Vec<u8>) -> usize {
+    let at = s.len();
+    s.extend_from_slice(&[0u8; 4]);
+    at
+}
+
+pub fn ser_bin_len_end(at: usize, s: &mut [u8]) {
+    let len = (s.len() - at - 4) as u32;
+    s[at..at + 4].copy_from_slice(&len.to_le_bytes());
+}
+
+// reads a length and returns the offset where that many bytes end
+pub fn de_bin_len(o: &mut usize, d: &[u8]) -> Result<usize, DeBinErr> {
+    let len: u32 = DeBin::de_bin(o, d) ?;
+    let end = *o + len as usize;
+    if end > d.len() {
+        return Err(DeBinErr{o:*o, l:len as usize, s:d.len(), msg:"tagged length".to_string(), path:DePath::default()})
+    }
+    Ok(end)
+}
+
+// the compact encoding puts the body length as a varint in front of the body. the length isn't known
+// until the body is written, so it gets a slot of the widest varint and when the outermost length
+// in a buffer ends, all slots are squeezed to their real width in one pass over the buffer
+const BIN_LEN_SLOT: usize = 10;
+
+struct BinLenOpen {
+    buf: usize,
+    at: usize,
+    // bytes the lengths inside this body give back once squeezed
+    saved: usize
+}
+
+#[derive(Default)]
+struct BinLenState {
+    open: Vec<BinLenOpen>,
+    // buffer, slot, length of the body after squeezing
+    done: Vec<(usize, usize, usize)>
+}
+
+thread_local! {
+    static BIN_LEN_STATE: std::cell::RefCell<BinLenState> = std::cell::RefCell::new(BinLenState::default());
+}
+
+// a slot belongs to the Vec it was reserved in, so a value written into a scratch buffer halfway doesn't mix
+fn bin_len_buf(s: &Vec<u8>) -> usize {
+    s as *const Vec<u8> as usize
+}
+
+pub fn ser_bin_len_begin_compact(s: &mut Vec<u8>) -> usize {
+    let at = s.len();
+    s.extend_from_slice(&[0u8; BIN_LEN_SLOT]);
+    let buf = bin_len_buf(s);
+    BIN_LEN_STATE.with( | state | {
+        let state = &mut *state.borrow_mut();
+        // anything left over is from a write that panicked halfway
+        if state.open.is_empty() {
+            state.done.clear();
+        }
+        state.open.push(BinLenOpen {buf, at, saved: 0})
+    });
+    at
+}
+
+pub fn ser_bin_len_end_compact(at: usize, s: &mut Vec<u8>) {
+    let buf = bin_len_buf(s);
+    BIN_LEN_STATE.with( | state | {
+        let state = &mut *state.borrow_mut();
+        let index = state.open.iter().rposition( | o | o.buf == buf && o.at == at).expect("ser_bin_len_end_compact without a begin");
+        let open = state.open.remove(index);
+        state.open.truncate(index);
+        let len = s.len() - at - BIN_LEN_SLOT - open.saved;
+        let saved = open.saved + BIN_LEN_SLOT - bin_varint_len(len as u128);
+        state.done.push((buf, at, len));
+        if let Some(parent) = state.open.iter_mut().rev().find( | o | o.buf == buf) {
+            parent.saved += saved;
+            return
+        }
+        // outermost in this buffer, every slot in it is done and they are squeezed front to back
+        let mut slots: Vec<(usize, usize)> = state.done.iter().filter( | d | d.0 == buf).map( | d | (d.1, d.2)).collect();
+        state.done.retain( | d | d.0 != buf);
+        slots.sort_unstable();
+        let mut read = at;
+        let mut write = at;
+        let mut varint = Vec::with_capacity(BIN_LEN_SLOT);
+        for (slot, len) in slots {
+            s.copy_within(read..slot, write);
+            write += slot - read;
+            varint.clear();
+            ser_bin_varint(len as u128, &mut varint);
+            s[write..write + varint.len()].copy_from_slice(&varint);
+            write += varint.len();
+            read = slot + BIN_LEN_SLOT;
+        }
+        let end = s.len();
+        s.copy_within(read..end, write);
+        s.truncate(write + end - read);
+    })
+}
+
+pub fn de_bin_len_compact(o: &mut usize, d: &[u8]) -> Result<usize, DeBinErr> {
+    let len: usize = DeBin::de_bin_compact(o, d) ?;
+    let end = *o + len;
+    if end > d.len() {
+        return Err(DeBinErr{o:*o, l:len, s:d.len(), msg:"tagged length".to_string(), path:DePath::default()})
+    }
+    Ok(end)
+}
+
+// LEB128, 7 bits per byte starting with the lowest, the top bit says another byte follows
+fn ser_bin_varint(mut v: u128, s: &mut Vec<u8>) {
+    while v >= 0x80 {
+        s.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    s.push(v as u8);
+}
+
+fn bin_varint_len(mut v: u128) -> usize {
+    let mut len = 1;
+    while v >= 0x80 {
+        v >>= 7;
+        len += 1;
+    }
+    len
+}
+
+fn de_bin_varint<T: BinVarint>(o: &mut usize, d: &[u8], msg: &str) -> Result<T, DeBinErr> {
+    let at = *o;
+    let mut v = 0u128;
+    let mut shift = 0;
+    loop {
+        if *o >= d.len() {
+            return Err(DeBinErr{o:at, l:*o - at + 1, s:d.len(), msg:msg.to_string(), path:DePath::default()})
+        }
+        let b = d[*o];
+        *o += 1;
+        if shift > 126 || shift == 126 && b & 0x7c != 0 {
+            return Err(DeBinErr{o:at, l:*o - at, s:d.len(), msg:format!("{} varint overflows", msg), path:DePath::default()})
+        }
+        v |= ((b & 0x7f) as u128) << shift;
+        if b & 0x80 == 0 {
+            break
+        }
+        shift += 7;
+    }
+    T::from_varint(v).ok_or_else( || DeBinErr{o:at, l:*o - at, s:d.len(), msg:format!("{} out of range", msg), path:DePath::default()})
+}
+
+// maps an integer onto the unsigned value its varint carries, signed values zigzag so -1 is 1 and 1 is 2
+trait BinVarint: Sized {
+    fn to_varint(self) -> u128;
+    fn from_varint(v: u128) -> Option<Self>;
+}
+
+macro_rules! impl_bin_varint_unsigned {
+    ( $ ( $ ty: ident), *) => {
+        $ (
+            impl BinVarint for $ ty {
+                fn to_varint(self) -> u128 {self as u128}
+                fn from_varint(v: u128) -> Option<Self> {$ ty::try_from(v).ok()}
+            }
+        ) *
+    }
+}
+
+macro_rules! impl_bin_varint_signed {
+    ( $ ( $ ty: ident $ uty: ident), *) => {
+        $ (
+            impl BinVarint for $ ty {
+                fn to_varint(self) -> u128 {((self << 1) ^ (self >> ($ ty::BITS - 1))) as $ uty as u128}
+                fn from_varint(v: u128) -> Option<Self> {
+                    let u = $ uty::try_from(v).ok() ?;
+                    Some((u >> 1) as $ ty ^ -((u & 1) as $ ty))
+                }
+            }
+        ) *
+    }
+}
+
+impl_bin_varint_unsigned!(u16, u32, u64, u128);
+impl_bin_varint_signed!(i16 u16, i32 u32, i64 u64, i128 u128);
+
 
 macro_rules! impl_ser_de_bin_for {
     ($ty:ident) => {
+        impl_ser_de_bin_for!($ty, {}, {});
+    };
+    ($ty:ident, varint) => {
+        impl_ser_de_bin_for!($ty, {
+            fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+                ser_bin_varint(self.to_varint(), s)
+            }
+        }, {
+            fn de_bin_compact(o:&mut usize, d:&[u8]) -> Result<$ty, DeBinErr> {
+                de_bin_varint(o, d, stringify!($ty))
+            }
+        });
+    };
+    ($ty:ident, {$($ser_compact:tt)*}, {$($de_compact:tt)*}) => {
         impl SerBin for $ty {
             fn ser_bin(&self, s: &mut Vec<u8>) {
                 s.extend_from_slice(&self.to_le_bytes());
             }
+            $($ser_compact)*
         }
         
         impl DeBin for $ty {
             fn de_bin(o:&mut usize, d:&[u8]) -> Result<$ty, DeBinErr> {
                 let l = std::mem::size_of::<$ty>();
                 if *o + l > d.len(){
-                    return Err(DeBinErr{o:*o, l:l, s:d.len(), msg:format!("{}", stringify!($ty))})
+                    return Err(DeBinErr{o:*o, l:l, s:d.len(), msg:format!("{}", stringify!($ty)), path:DePath::default()})
                 }
                 let ret = $ty::from_le_bytes(d[*o..*o+l].try_into().unwrap());
                 *o += l;
                 Ok(ret)
             }
+            $($de_compact)*
         }
     };
 }
 
 impl_ser_de_bin_for!(f64);
 impl_ser_de_bin_for!(f32);
-impl_ser_de_bin_for!(u64);
-impl_ser_de_bin_for!(i64);
-impl_ser_de_bin_for!(u32);
-impl_ser_de_bin_for!(i32);
-impl_ser_de_bin_for!(u16);
-impl_ser_de_bin_for!(i16);
+impl_ser_de_bin_for!(u64, varint);
+impl_ser_de_bin_for!(i64, varint);
+impl_ser_de_bin_for!(u32, varint);
+impl_ser_de_bin_for!(i32, varint);
+impl_ser_de_bin_for!(u16, varint);
+impl_ser_de_bin_for!(i16, varint);
+impl_ser_de_bin_for!(i8);
+impl_ser_de_bin_for!(u128, varint);
+impl_ser_de_bin_for!(i128, varint);
 
 impl SerBin for usize {
     fn ser_bin(&self, s: &mut Vec<u8>) {
         s.extend_from_slice(&(*self as u64).to_le_bytes());
     }
+
+    fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+        ser_bin_varint(*self as u128, s)
+    }
 }
 
 impl DeBin for usize {
     fn de_bin(o:&mut usize, d:&[u8]) -> Result<usize, DeBinErr> {
         let l = std::mem::size_of::<u64>();
         if *o + l > d.len(){
-            return Err(DeBinErr{o:*o, l:l, s:d.len(), msg:format!("usize")})
+            return Err(DeBinErr{o:*o, l:l, s:d.len(), msg:format!("usize"), path:DePath::default()})
         }
         let ret = u64::from_le_bytes(d[*o..*o+l].try_into().unwrap()) as usize;
         *o += l;
         Ok(ret)
     }
+
+    fn de_bin_compact(o:&mut usize, d:&[u8]) -> Result<usize, DeBinErr> {
+        let ret: u64 = de_bin_varint(o, d, "usize") ?;
+        Ok(ret as usize)
+    }
 }
 
 impl DeBin for u8 {
     fn de_bin(o:&mut usize, d:&[u8]) -> Result<u8,DeBinErr> {
         if *o + 1 > d.len(){
-            return Err(DeBinErr{o:*o, l:1, s:d.len(), msg:format!("u8")})
+            return Err(DeBinErr{o:*o, l:1, s:d.len(), msg:format!("u8"), path:DePath::default()})
         } 
         let m = d[*o];
         *o += 1;
@@ -116,7 +380,7 @@ impl SerBin for bool {
 impl DeBin for bool {
     fn de_bin(o:&mut usize, d:&[u8]) -> Result<bool, DeBinErr> {
         if *o + 1 > d.len(){
-            return Err(DeBinErr{o:*o, l:1, s:d.len(), msg:format!("bool")})
+            return Err(DeBinErr{o:*o, l:1, s:d.len(), msg:format!("bool"), path:DePath::default()})
         } 
         let m = d[*o];
         *o += 1;
@@ -124,33 +388,101 @@ impl DeBin for bool {
     }
 }
 
-impl SerBin for String {
+impl SerBin for () {
+    fn ser_bin(&self, _s: &mut Vec<u8>) {
+    }
+}
+
+impl DeBin for () {
+    fn de_bin(_o:&mut usize, _d:&[u8]) -> Result<(), DeBinErr> {
+        Ok(())
+    }
+}
+
+impl SerBin for str {
     fn ser_bin(&self, s: &mut Vec<u8>) {
         let len = self.len();
         len.ser_bin(s);
         s.extend_from_slice(self.as_bytes());
     }
+
+    fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+        let len = self.len();
+        len.ser_bin_compact(s);
+        s.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl SerBin for String {
+    fn ser_bin(&self, s: &mut Vec<u8>) {
+        self.as_str().ser_bin(s)
+    }
+
+    fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+        self.as_str().ser_bin_compact(s)
+    }
 }
 
 impl DeBin for String {
     fn de_bin(o:&mut usize, d:&[u8])->Result<String, DeBinErr> {
-        let len:u64 = DeBin::de_bin(o,d)?;
-        if *o + (len as usize) > d.len(){
-            return Err(DeBinErr{o:*o, l:1, s:d.len(), msg:format!("String")})
-        } 
-        let r = std::str::from_utf8(&d[*o..(*o+(len as usize))]).unwrap().to_string();
-        *o += len as usize;
-        Ok(r)
+        Ok(<&str>::de_bin_ref(o, d)?.to_string())
+    }
+
+    fn de_bin_compact(o:&mut usize, d:&[u8])->Result<String, DeBinErr> {
+        let len: usize = DeBin::de_bin_compact(o, d) ?;
+        Ok(de_bin_str(o, d, len)?.to_string())
+    }
+}
+
+impl SerBin for char {
+    fn ser_bin(&self, s: &mut Vec<u8>) {
+        (*self as u32).ser_bin(s)
+    }
+
+    fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+        (*self as u32).ser_bin_compact(s)
+    }
+}
+
+fn de_bin_char(at: usize, val: u32, d:&[u8]) -> Result<char, DeBinErr> {
+    char::from_u32(val).ok_or_else( || DeBinErr{o:at, l:4, s:d.len(), msg:format!("char {:x}", val), path:DePath::default()})
+}
+
+impl DeBin for char {
+    fn de_bin(o:&mut usize, d:&[u8]) -> Result<char, DeBinErr> {
+        let at = *o;
+        let val: u32 = DeBin::de_bin(o, d) ?;
+        de_bin_char(at, val, d)
+    }
+
+    fn de_bin_compact(o:&mut usize, d:&[u8]) -> Result<char, DeBinErr> {
+        let at = *o;
+        let val: u32 = DeBin::de_bin_compact(o, d) ?;
+        de_bin_char(at, val, d)
+    }
+}
+
+fn ser_bin_seq<'a, T: SerBin + 'a>(len: usize, items: impl Iterator<Item = &'a T>, s: &mut Vec<u8>) {
+    (len as u64).ser_bin(s);
+    for item in items {
+        item.ser_bin(s);
+    }
+}
+
+fn ser_bin_seq_compact<'a, T: SerBin + 'a>(len: usize, items: impl Iterator<Item = &'a T>, s: &mut Vec<u8>) {
+    len.ser_bin_compact(s);
+    for item in items {
+        item.ser_bin_compact(s);
     }
 }
 
 impl<T> SerBin for Vec<T> where T: SerBin {
     fn ser_bin(&self, s: &mut Vec<u8>) {
-        let len = self.len() as u64;
-        len.ser_bin(s);
-        for item in self {
-            item.ser_bin(s);
-        }
+        ser_bin_seq(self.len(), self.iter(), s)
+    }
+
+    fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+        ser_bin_seq_compact(self.len(), self.iter(), s)
     }
 }
 
@@ -158,8 +490,17 @@ impl<T> DeBin for Vec<T> where T:DeBin{
     fn de_bin(o:&mut usize, d:&[u8])->Result<Vec<T>, DeBinErr> {
         let len:u64 = DeBin::de_bin(o,d)?;
         let mut out = Vec::new();
-        for _ in 0..len{
-            out.push(DeBin::de_bin(o,d)?)
+        for index in 0..len as usize{
+            out.push(DeBin::de_bin(o,d).map_err(|e| e.at_index(index))?)
+        }
+        Ok(out)
+    }
+
+    fn de_bin_compact(o:&mut usize, d:&[u8])->Result<Vec<T>, DeBinErr> {
+        let len:usize = DeBin::de_bin_compact(o,d)?;
+        let mut out = Vec::new();
+        for index in 0..len{
+            out.push(DeBin::de_bin_compact(o,d).map_err(|e| e.at_index(index))?)
         }
         Ok(out)
     }
@@ -175,12 +516,22 @@ impl<T> SerBin for Option<T> where T: SerBin {
             s.push(0);
         }
     }
+
+    fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+        if let Some(v) = self{
+            s.push(1);
+            v.ser_bin_compact(s);
+        }
+        else{
+            s.push(0);
+        }
+    }
 }
 
 impl<T> DeBin for Option<T> where T:DeBin{
     fn de_bin(o:&mut usize, d:&[u8])->Result<Option<T>, DeBinErr> {
         if *o + 1 > d.len(){
-            return Err(DeBinErr{o:*o, l:1, s:d.len(), msg:format!("Option<T>")})
+            return Err(DeBinErr{o:*o, l:1, s:d.len(), msg:format!("Option<T>"), path:DePath::default()})
         } 
         let m = d[*o];
         *o += 1;
@@ -191,6 +542,15 @@ impl<T> DeBin for Option<T> where T:DeBin{
             Ok(None)
         }
     }
+
+    fn de_bin_compact(o:&mut usize, d:&[u8])->Result<Option<T>, DeBinErr> {
+        if u8::de_bin(o, d).map_err(|mut e| {e.msg = "Option<T>".to_string(); e})? == 1{
+            Ok(Some(DeBin::de_bin_compact(o,d)?))
+        }
+        else{
+            Ok(None)
+        }
+    }
 }
 
 impl<T> SerBin for [T] where T: SerBin {
@@ -199,12 +559,19 @@ impl<T> SerBin for [T] where T: SerBin {
             item.ser_bin(s);
         }
     }
+
+    fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+        for item in self {
+            item.ser_bin_compact(s);
+        }
+    }
 }
 
 
-unsafe fn de_bin_array_impl_inner<T>(top: *mut T, count: usize, o:&mut usize, d:&[u8]) -> Result<(), DeBinErr> where T:DeBin{
+unsafe fn de_bin_array_impl_inner<T>(top: *mut T, count: usize, o:&mut usize, d:&[u8], compact: bool) -> Result<(), DeBinErr> where T:DeBin{
     for c in 0..count {
-        top.add(c).write(DeBin::de_bin(o, d) ?);
+        let item = if compact {DeBin::de_bin_compact(o, d)} else {DeBin::de_bin(o, d)};
+        top.add(c).write(item.map_err( | e | e.at_index(c)) ?);
     }
     Ok(())
 }
@@ -218,7 +585,17 @@ macro_rules!de_bin_array_impl {
                 unsafe{
                     let mut to = std::mem::MaybeUninit::<[T; $count]>::uninit();
                     let top: *mut T = std::mem::transmute(&mut to);
-                    de_bin_array_impl_inner(top, $count, o, d)?;
+                    de_bin_array_impl_inner(top, $count, o, d, false)?;
+                    Ok(to.assume_init())
+                }
+            }
+
+            fn de_bin_compact(o:&mut usize, d:&[u8]) -> Result<Self,
+            DeBinErr> {
+                unsafe{
+                    let mut to = std::mem::MaybeUninit::<[T; $count]>::uninit();
+                    let top: *mut T = std::mem::transmute(&mut to);
+                    de_bin_array_impl_inner(top, $count, o, d, true)?;
                     Ok(to.assume_init())
                 }
             }
@@ -229,77 +606,310 @@ macro_rules!de_bin_array_impl {
 
 de_bin_array_impl!(2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24,25,26,27,28,29,30,31,32);
 
-impl<A,B> SerBin for (A,B) where A: SerBin, B:SerBin {
+impl<T> SerBin for VecDeque<T> where T: SerBin {
     fn ser_bin(&self, s: &mut Vec<u8>) {
-        self.0.ser_bin(s);
-        self.1.ser_bin(s);
+        ser_bin_seq(self.len(), self.iter(), s)
+    }
+
+    fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+        ser_bin_seq_compact(self.len(), self.iter(), s)
     }
 }
 
-impl<A,B> DeBin for (A,B) where A:DeBin, B:DeBin{
-    fn de_bin(o:&mut usize, d:&[u8])->Result<(A,B), DeBinErr> {Ok((DeBin::de_bin(o,d)?,DeBin::de_bin(o,d)?))}
+impl<T> DeBin for VecDeque<T> where T: DeBin {
+    fn de_bin(o:&mut usize, d:&[u8]) -> Result<Self, DeBinErr> {
+        Ok(Vec::<T>::de_bin(o, d) ?.into())
+    }
+
+    fn de_bin_compact(o:&mut usize, d:&[u8]) -> Result<Self, DeBinErr> {
+        Ok(Vec::<T>::de_bin_compact(o, d) ?.into())
+    }
 }
 
-impl<A,B,C> SerBin for (A,B,C) where A: SerBin, B:SerBin, C:SerBin {
+impl<T> SerBin for HashSet<T> where T: SerBin {
     fn ser_bin(&self, s: &mut Vec<u8>) {
-        self.0.ser_bin(s);
-        self.1.ser_bin(s);
-        self.2.ser_bin(s);
-    } 
+        ser_bin_seq(self.len(), self.iter(), s)
+    }
+
+    fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+        ser_bin_seq_compact(self.len(), self.iter(), s)
+    }
 }
 
-impl<A,B,C> DeBin for (A,B,C) where A:DeBin, B:DeBin, C:DeBin{
-    fn de_bin(o:&mut usize, d:&[u8])->Result<(A,B,C), DeBinErr> {Ok((DeBin::de_bin(o,d)?,DeBin::de_bin(o,d)?,DeBin::de_bin(o,d)?))}
+impl<T> DeBin for HashSet<T> where T: DeBin + Eq + Hash {
+    fn de_bin(o:&mut usize, d:&[u8]) -> Result<Self, DeBinErr> {
+        Ok(Vec::<T>::de_bin(o, d) ?.into_iter().collect())
+    }
+
+    fn de_bin_compact(o:&mut usize, d:&[u8]) -> Result<Self, DeBinErr> {
+        Ok(Vec::<T>::de_bin_compact(o, d) ?.into_iter().collect())
+    }
 }
 
-impl<A,B,C,D> SerBin for (A,B,C,D) where A: SerBin, B:SerBin, C:SerBin, D:SerBin {
+impl<T> SerBin for BTreeSet<T> where T: SerBin {
     fn ser_bin(&self, s: &mut Vec<u8>) {
-        self.0.ser_bin(s);
-        self.1.ser_bin(s);
-        self.2.ser_bin(s);
-        self.3.ser_bin(s);
+        ser_bin_seq(self.len(), self.iter(), s)
+    }
+
+    fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+        ser_bin_seq_compact(self.len(), self.iter(), s)
+    }
+}
+
+impl<T> DeBin for BTreeSet<T> where T: DeBin + Ord {
+    fn de_bin(o:&mut usize, d:&[u8]) -> Result<Self, DeBinErr> {
+        Ok(Vec::<T>::de_bin(o, d) ?.into_iter().collect())
+    }
+
+    fn de_bin_compact(o:&mut usize, d:&[u8]) -> Result<Self, DeBinErr> {
+        Ok(Vec::<T>::de_bin_compact(o, d) ?.into_iter().collect())
+    }
+}
+
+macro_rules!impl_ser_de_bin_tuple {
+    ( $ ( $ ty: ident $ index: tt), *) => {
+        impl< $ ( $ ty), *> SerBin for ( $ ( $ ty), *) where $ ( $ ty: SerBin), * {
+            fn ser_bin(&self, s: &mut Vec<u8>) {
+                $ (self. $ index.ser_bin(s);) *
+            }
+
+            fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+                $ (self. $ index.ser_bin_compact(s);) *
+            }
+        }
+        
+        impl< $ ( $ ty), *> DeBin for ( $ ( $ ty), *) where $ ( $ ty: DeBin), * {
+            fn de_bin(o:&mut usize, d:&[u8]) -> Result<Self, DeBinErr> {
+                Ok(( $ ( $ ty::de_bin(o, d).map_err( | e | e.at_index( $ index)) ?), *))
+            }
+
+            fn de_bin_compact(o:&mut usize, d:&[u8]) -> Result<Self, DeBinErr> {
+                Ok(( $ ( $ ty::de_bin_compact(o, d).map_err( | e | e.at_index( $ index)) ?), *))
+            }
+        }
     }
 }
 
-impl<A,B,C,D> DeBin for (A,B,C,D) where A:DeBin, B:DeBin, C:DeBin, D:DeBin{
-    fn de_bin(o:&mut usize, d:&[u8])->Result<(A,B,C,D), DeBinErr> {Ok((DeBin::de_bin(o,d)?,DeBin::de_bin(o,d)?,DeBin::de_bin(o,d)?,DeBin::de_bin(o,d)?))}
+impl_ser_de_bin_tuple!(A 0, B 1);
+impl_ser_de_bin_tuple!(A 0, B 1, C 2);
+impl_ser_de_bin_tuple!(A 0, B 1, C 2, D 3);
+impl_ser_de_bin_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_ser_de_bin_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_ser_de_bin_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_ser_de_bin_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_ser_de_bin_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_ser_de_bin_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_ser_de_bin_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_ser_de_bin_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+fn ser_bin_map<'a, K: SerBin + 'a, V: SerBin + 'a>(len: usize, items: impl Iterator<Item = (&'a K, &'a V)>, s: &mut Vec<u8>) {
+    (len as u64).ser_bin(s);
+    for (k, v) in items {
+        k.ser_bin(s);
+        v.ser_bin(s);
+    }
+}
+
+fn ser_bin_map_compact<'a, K: SerBin + 'a, V: SerBin + 'a>(len: usize, items: impl Iterator<Item = (&'a K, &'a V)>, s: &mut Vec<u8>) {
+    len.ser_bin_compact(s);
+    for (k, v) in items {
+        k.ser_bin_compact(s);
+        v.ser_bin_compact(s);
+    }
 }
 
 impl<K, V> SerBin for HashMap<K, V> where K: SerBin,
 V: SerBin {
     fn ser_bin(&self, s: &mut Vec<u8>) {
-        let len = self.len() as u64;
-        len.ser_bin(s);
-        for (k, v) in self {
-            k.ser_bin(s);
-            v.ser_bin(s);
-        }
+        ser_bin_map(self.len(), self.iter(), s)
+    }
+
+    fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+        ser_bin_map_compact(self.len(), self.iter(), s)
     }
 }
 
 impl<K, V> DeBin for HashMap<K, V> where K: DeBin + Eq + Hash,
 V: DeBin {
     fn de_bin(o:&mut usize, d:&[u8])->Result<Self, DeBinErr>{
+        Ok(Vec::<(K, V)>::de_bin(o, d) ?.into_iter().collect())
+    }
+
+    fn de_bin_compact(o:&mut usize, d:&[u8])->Result<Self, DeBinErr>{
+        Ok(Vec::<(K, V)>::de_bin_compact(o, d) ?.into_iter().collect())
+    }
+}
+
+impl<K, V> SerBin for BTreeMap<K, V> where K: SerBin,
+V: SerBin {
+    fn ser_bin(&self, s: &mut Vec<u8>) {
+        ser_bin_map(self.len(), self.iter(), s)
+    }
+
+    fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+        ser_bin_map_compact(self.len(), self.iter(), s)
+    }
+}
+
+impl<K, V> DeBin for BTreeMap<K, V> where K: DeBin + Ord,
+V: DeBin {
+    fn de_bin(o:&mut usize, d:&[u8])->Result<Self, DeBinErr>{
+        Ok(Vec::<(K, V)>::de_bin(o, d) ?.into_iter().collect())
+    }
+
+    fn de_bin_compact(o:&mut usize, d:&[u8])->Result<Self, DeBinErr>{
+        Ok(Vec::<(K, V)>::de_bin_compact(o, d) ?.into_iter().collect())
+    }
+}
+
+macro_rules!impl_ser_de_bin_pointer {
+    ( $ ty: ident) => {
+        impl<T> SerBin for $ ty<T> where T: SerBin {
+            fn ser_bin(&self, s: &mut Vec<u8>) {
+                (**self).ser_bin(s)
+            }
+
+            fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+                (**self).ser_bin_compact(s)
+            }
+        }
+        
+        impl<T> DeBin for $ ty<T> where T: DeBin {
+            fn de_bin(o:&mut usize, d:&[u8])->Result<$ ty<T>, DeBinErr> {
+                Ok($ ty::new(DeBin::de_bin(o,d)?))
+            }
+
+            fn de_bin_compact(o:&mut usize, d:&[u8])->Result<$ ty<T>, DeBinErr> {
+                Ok($ ty::new(DeBin::de_bin_compact(o,d)?))
+            }
+        }
+    }
+}
+
+impl_ser_de_bin_pointer!(Box);
+impl_ser_de_bin_pointer!(Rc);
+impl_ser_de_bin_pointer!(Arc);
+
+// the len bytes after the length, for either encoding of the length
+fn de_bin_bytes<'a>(o:&mut usize, d:&'a [u8], len: usize, msg: &str)->Result<&'a [u8], DeBinErr> {
+    if *o + len > d.len(){
+        return Err(DeBinErr{o:*o, l:len, s:d.len(), msg:msg.to_string(), path:DePath::default()})
+    }
+    let r = &d[*o..(*o+len)];
+    *o += len;
+    Ok(r)
+}
+
+fn de_bin_str<'a>(o:&mut usize, d:&'a [u8], len: usize)->Result<&'a str, DeBinErr> {
+    let at = *o;
+    let bytes = de_bin_bytes(o, d, len, "String")?;
+    std::str::from_utf8(bytes).map_err(|_| DeBinErr{o:at, l:bytes.len(), s:d.len(), msg:"String is not valid UTF-8".to_string(), path:DePath::default()})
+}
+
+impl<'a> DeBinRef<'a> for &'a [u8] {
+    fn de_bin_ref(o:&mut usize, d:&'a [u8])->Result<Self, DeBinErr> {
+        let len:u64 = DeBin::de_bin(o,d)?;
+        de_bin_bytes(o, d, len as usize, "[u8]")
+    }
+}
+
+impl<'a> DeBinRef<'a> for &'a str {
+    fn de_bin_ref(o:&mut usize, d:&'a [u8])->Result<Self, DeBinErr> {
+        let len:u64 = DeBin::de_bin(o,d).map_err(|mut e| {e.msg = "String".to_string(); e})?;
+        de_bin_str(o, d, len as usize)
+    }
+}
+
+// types that own everything they read borrow nothing
+macro_rules!impl_de_bin_ref_owned {
+    ( $ ( $ ty: ty), *) => {
+        $ (
+            impl<'a> DeBinRef<'a> for $ ty {
+                fn de_bin_ref(o:&mut usize, d:&'a [u8])->Result<Self, DeBinErr> {
+                    DeBin::de_bin(o, d)
+                }
+            }
+        ) *
+    }
+}
+
+impl_de_bin_ref_owned!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, f32, f64, bool, char, (), String);
+
+impl<'a, T> DeBinRef<'a> for Option<T> where T: DeBinRef<'a> {
+    fn de_bin_ref(o:&mut usize, d:&'a [u8])->Result<Self, DeBinErr> {
+        if u8::de_bin(o, d).map_err(|mut e| {e.msg = "Option<T>".to_string(); e})? == 1{
+            Ok(Some(DeBinRef::de_bin_ref(o,d)?))
+        }
+        else{
+            Ok(None)
+        }
+    }
+}
+
+impl<'a, T> DeBinRef<'a> for Vec<T> where T: DeBinRef<'a> {
+    fn de_bin_ref(o:&mut usize, d:&'a [u8])->Result<Self, DeBinErr> {
         let len:u64 = DeBin::de_bin(o,d)?;
-        let mut h = HashMap::new();
-        for _ in 0..len{
-            let k = DeBin::de_bin(o,d)?;
-            let v = DeBin::de_bin(o,d)?;
-            h.insert(k, v);
+        let mut out = Vec::new();
+        for index in 0..len as usize{
+            out.push(DeBinRef::de_bin_ref(o,d).map_err(|e| e.at_index(index))?)
         }
-        Ok(h)
+        Ok(out)
     }
 }
 
+impl<'a, T> DeBinRef<'a> for Box<T> where T: DeBinRef<'a> {
+    fn de_bin_ref(o:&mut usize, d:&'a [u8])->Result<Self, DeBinErr> {
+        Ok(Box::new(DeBinRef::de_bin_ref(o,d)?))
+    }
+}
 
-impl<T> SerBin for Box<T> where T: SerBin {
-    fn ser_bin(&self, s: &mut Vec<u8>) {
-        (**self).ser_bin(s)
+macro_rules!de_bin_ref_array_impl {
+    ( $ ( $ count: expr), *) => {
+        $ (
+            impl<'a, T> DeBinRef<'a> for [T; $ count] where T: DeBinRef<'a> {
+                fn de_bin_ref(o:&mut usize, d:&'a [u8]) -> Result<Self, DeBinErr> {
+                    let mut out = Vec::with_capacity($ count);
+                    for index in 0..$ count {
+                        out.push(DeBinRef::de_bin_ref(o, d).map_err(|e| e.at_index(index))?)
+                    }
+                    match out.try_into() {
+                        Ok(v) => Ok(v),
+                        Err(_) => unreachable!()
+                    }
+                }
+            }
+        ) *
     }
 }
 
-impl<T> DeBin for Box<T> where T: DeBin {
-    fn de_bin(o:&mut usize, d:&[u8])->Result<Box<T>, DeBinErr> {
-        Ok(Box::new(DeBin::de_bin(o,d)?))
+de_bin_ref_array_impl!(2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24,25,26,27,28,29,30,31,32);
+
+macro_rules!impl_de_bin_ref_tuple {
+    ( $ ( $ ty: ident $ index: tt), *) => {
+        impl<'a, $ ( $ ty), *> DeBinRef<'a> for ( $ ( $ ty), *) where $ ( $ ty: DeBinRef<'a>), * {
+            fn de_bin_ref(o:&mut usize, d:&'a [u8]) -> Result<Self, DeBinErr> {
+                Ok(( $ ( $ ty::de_bin_ref(o, d).map_err( | e | e.at_index( $ index)) ?), *))
+            }
+        }
     }
-}
\ No newline at end of file
+}
+
+impl_de_bin_ref_tuple!(A 0, B 1);
+impl_de_bin_ref_tuple!(A 0, B 1, C 2);
+impl_de_bin_ref_tuple!(A 0, B 1, C 2, D 3);
+impl_de_bin_ref_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_de_bin_ref_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+
+impl<'a, K, V> DeBinRef<'a> for HashMap<K, V> where K: DeBinRef<'a> + Eq + Hash,
+V: DeBinRef<'a> {
+    fn de_bin_ref(o:&mut usize, d:&'a [u8])->Result<Self, DeBinErr>{
+        Ok(Vec::<(K, V)>::de_bin_ref(o, d) ?.into_iter().collect())
+    }
+}
+
+impl<'a, K, V> DeBinRef<'a> for BTreeMap<K, V> where K: DeBinRef<'a> + Ord,
+V: DeBinRef<'a> {
+    fn de_bin_ref(o:&mut usize, d:&'a [u8])->Result<Self, DeBinErr>{
+        Ok(Vec::<(K, V)>::de_bin_ref(o, d) ?.into_iter().collect())
+    }
+}
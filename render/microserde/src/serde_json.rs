@@ -1,5 +1,10 @@
-use std::collections::{HashMap};
+use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::io::Read;
 use std::hash::Hash;
+use std::fmt::Write;
+use crate::de_path::*;
 use std::str::Chars;
 
 pub struct SerJsonState {
@@ -66,10 +71,52 @@ pub trait DeJson: Sized {
         DeJson::de_json(&mut state, &mut chars)
     }
     
+    // reads one value from a reader without buffering the whole document
+    fn deserialize_json_reader<'a, R: Read + 'a>(read: R) -> Result<Self,
+    DeJsonErr> {
+        let mut state = DeJsonState::from_reader(read);
+        match state.read_value() {
+            Some(r) => r,
+            None => Err(state.err_token("value"))
+        }
+    }
+    
     fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self,
     DeJsonErr>;
 }
 
+// reads json text that was buffered while looking ahead, the enum derive uses this when a tag
+// turns up after the data it picks the type for. error positions are relative to the text
+pub fn de_json_replay<'a, T>(text: &str, f: impl FnOnce(&mut DeJsonState<'a>, &mut Chars) -> Result<T, DeJsonErr>) -> Result<T, DeJsonErr> {
+    let mut state = DeJsonState::default();
+    let mut chars = text.chars();
+    state.next(&mut chars);
+    state.next_tok(&mut chars) ?;
+    let r = f(&mut state, &mut chars) ?;
+    if state.tok != DeJsonTok::Eof {
+        return Err(state.err_token("end of value"))
+    }
+    Ok(r)
+}
+
+// implemented by the derive for structs with named fields, so they can be inlined with #[nserde(flatten)]
+pub trait SerJsonFlat {
+    fn ser_json_flat(&self, d: usize, s: &mut SerJsonState);
+}
+
+pub trait DeJsonFlat: Sized {
+    type Partial;
+    
+    fn de_json_flat_new() -> Self::Partial;
+    
+    // reads the value for the key in s.strbuf, returns false if the key isn't ours
+    fn de_json_flat_field(p: &mut Self::Partial, s: &mut DeJsonState, i: &mut Chars) -> Result<bool,
+    DeJsonErr>;
+    
+    fn de_json_flat_finish(p: Self::Partial, s: &mut DeJsonState) -> Result<Self,
+    DeJsonErr>;
+}
+
 #[derive(PartialEq, Debug)]
 pub enum DeJsonTok {
     Str,
@@ -95,31 +142,162 @@ impl Default for DeJsonTok {
 }
 
 #[derive(Default)]
-pub struct DeJsonState {
+pub struct DeJsonState<'a> {
     pub cur: char,
     pub tok: DeJsonTok,
     pub strbuf:String,
     pub numbuf:String,
     pub identbuf:String,
     pub line: usize,
-    pub col: usize
+    pub col: usize,
+    // where the current token starts, errors point here
+    pub tok_line: usize,
+    pub tok_col: usize,
+    // when set, characters come from here once the Chars passed in runs dry
+    pub reader: Option<DeJsonReader<'a>>,
+    // nesting of {} and [] so a reader can stop at the end of each top level value
+    pub depth: usize,
+    pub value_done: bool,
+    // cur ended a top level value and hasn't been consumed, reading past it could block a reader
+    pub cur_pending: bool,
+    // holds the text of map keys so it is only copied into a path segment on error
+    pub keybuf: String,
+}
+
+// decodes UTF-8 from an io::Read a chunk at a time, a character split over two reads is kept until it is complete
+pub struct DeJsonReader<'a> {
+    pub read: Box<dyn Read + 'a>,
+    pub buf: Vec<u8>,
+    pub start: usize,
+    pub end: usize,
+    pub err: Option<std::io::Error>
+}
+
+impl<'a> DeJsonReader<'a> {
+    pub fn new<R: Read + 'a>(read: R) -> DeJsonReader<'a> {
+        DeJsonReader {
+            read: Box::new(read),
+            buf: vec![0u8; 4096],
+            start: 0,
+            end: 0,
+            err: None
+        }
+    }
+    
+    // moves what is left to the front and reads more behind it, returns false at the end of the input
+    fn fill(&mut self) -> bool {
+        if self.err.is_some() {
+            return false
+        }
+        self.buf.copy_within(self.start..self.end, 0);
+        self.end -= self.start;
+        self.start = 0;
+        loop {
+            match self.read.read(&mut self.buf[self.end..]) {
+                Ok(0) => return false,
+                Ok(n) => {
+                    self.end += n;
+                    return true
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
+                Err(e) => {
+                    self.err = Some(e);
+                    return false
+                }
+            }
+        }
+    }
+    
+    pub fn next_char(&mut self) -> Option<char> {
+        if self.start == self.end && !self.fill() {
+            return None
+        }
+        let len = match self.buf[self.start] {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 0
+        };
+        while self.end - self.start < len {
+            if !self.fill() {
+                break;
+            }
+        }
+        if len != 0 && self.end - self.start >= len {
+            if let Ok(s) = std::str::from_utf8(&self.buf[self.start..self.start + len]) {
+                self.start += len;
+                return s.chars().next()
+            }
+        }
+        if self.err.is_none() {
+            self.err = Some(std::io::Error::new(std::io::ErrorKind::InvalidData, "stream is not valid UTF-8"));
+        }
+        None
+    }
 }
 
+// line is 0 based, col is 1 based like the col of the state, Debug and render both print line + 1 and col
 pub struct DeJsonErr{
     pub msg:String,
     pub line:usize,
-    pub col:usize
+    pub col:usize,
+    pub path:DePath
 }
 
 impl std::fmt::Debug for DeJsonErr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Json Deserialize error: {}, line:{} col:{}", self.msg, self.line+1, self.col+1)
+        write!(f, "Json Deserialize error: {}, line:{} col:{}", self.msg, self.line+1, self.col)?;
+        if !self.path.is_empty(){
+            write!(f, " at {}", self.path)?;
+        }
+        Ok(())
+    }
+}
+
+impl DeErrAt for DeJsonErr {
+    fn path_mut(&mut self) -> &mut DePath {&mut self.path}
+}
+
+impl DeJsonErr {
+    // the message with the offending line of the source and a caret under it
+    pub fn render(&self, name: &str, source: &str) -> String {
+        render_source_error(name, source, self.line, self.col.saturating_sub(1), &self.msg, &self.path)
     }
 }
 
-impl DeJsonState {
+impl<'a> DeJsonState<'a> {
+    pub fn from_reader<R: Read + 'a>(read: R) -> DeJsonState<'a> {
+        DeJsonState {
+            reader: Some(DeJsonReader::new(read)),
+            ..Default::default()
+        }
+    }
+    
+    // reads the next top level value from the reader, None when the stream has ended
+    pub fn read_value<T: DeJson>(&mut self) -> Option<Result<T, DeJsonErr>> {
+        let mut chars = "".chars();
+        if self.tok == DeJsonTok::Bof {
+            self.next(&mut chars);
+        }
+        self.value_done = false;
+        let r = match self.next_tok(&mut chars) {
+            Ok(()) if self.tok == DeJsonTok::Eof => None,
+            Ok(()) => Some(T::de_json(self, &mut chars)),
+            Err(e) => Some(Err(e))
+        };
+        if let Some(e) = self.reader.as_mut().and_then( | r | r.err.take()) {
+            return Some(Err(DeJsonErr{msg:format!("Cannot read {}", e), line:self.line, col:self.col, path:DePath::default()}))
+        }
+        r
+    }
+    
     pub fn next(&mut self, i: &mut Chars) {
-        if let Some(c) = i.next() {
+        let c = match i.next() {
+            Some(c) => Some(c),
+            None => self.reader.as_mut().and_then( | r | r.next_char())
+        };
+        if let Some(c) = c {
             self.cur = c;
             if self.cur == '\n'{
                 self.line += 1;
@@ -134,32 +312,47 @@ impl DeJsonState {
         }
     }
     
+    // writes the text of a map key before it is read into out, for the error path
+    pub fn key_text(&self, out: &mut String) {
+        out.clear();
+        let _ = match self.tok {
+            DeJsonTok::Str => write!(out, "{}", self.strbuf),
+            DeJsonTok::BareIdent => write!(out, "{}", self.identbuf),
+            DeJsonTok::Char(c) => write!(out, "{}", c),
+            DeJsonTok::U64(v) => write!(out, "{}", v),
+            DeJsonTok::I64(v) => write!(out, "{}", v),
+            DeJsonTok::F64(_) => write!(out, "{}", self.numbuf),
+            DeJsonTok::Bool(v) => write!(out, "{}", v),
+            _ => Ok(())
+        };
+    }
+    
     pub fn err_exp(&self, name: &str) -> DeJsonErr {
-        DeJsonErr{msg:format!("Unexpected key {}", name), line:self.line, col:self.col}
+        DeJsonErr{msg:format!("Unexpected key {}", name), line:self.tok_line, col:self.tok_col, path:DePath::default()}
     }
     
     pub fn err_nf(&self, name: &str) -> DeJsonErr {
-        DeJsonErr{msg:format!("Key not found {}", name), line:self.line, col:self.col}
+        DeJsonErr{msg:format!("Key not found {}", name), line:self.tok_line, col:self.tok_col, path:DePath::default()}
     }
 
     pub fn err_enum(&self, name: &str) -> DeJsonErr {
-        DeJsonErr{msg:format!("Enum not defined {}", name), line:self.line, col:self.col}
+        DeJsonErr{msg:format!("Enum not defined {}", name), line:self.tok_line, col:self.tok_col, path:DePath::default()}
     }
 
     pub fn err_token(&self, what:&str) -> DeJsonErr {
-        DeJsonErr{msg:format!("Unexpected token {:?} expected {} ", self.tok, what), line:self.line, col:self.col}
+        DeJsonErr{msg:format!("Unexpected token {:?} expected {} ", self.tok, what), line:self.tok_line, col:self.tok_col, path:DePath::default()}
     }
 
     pub fn err_range(&self, what:&str) -> DeJsonErr {
-        DeJsonErr{msg:format!("Value out of range {} ", what), line:self.line, col:self.col}
+        DeJsonErr{msg:format!("Value out of range {} ", what), line:self.tok_line, col:self.tok_col, path:DePath::default()}
     }
 
     pub fn err_type(&self, what:&str) -> DeJsonErr {
-        DeJsonErr{msg:format!("Token wrong type {} ", what), line:self.line, col:self.col}
+        DeJsonErr{msg:format!("Token wrong type {} ", what), line:self.tok_line, col:self.tok_col, path:DePath::default()}
     }
 
     pub fn err_parse(&self, what:&str) -> DeJsonErr {
-        DeJsonErr{msg:format!("Cannot parse {} ", what), line:self.line, col:self.col}
+        DeJsonErr{msg:format!("Cannot parse {} ", what), line:self.tok_line, col:self.tok_col, path:DePath::default()}
     }
     
     pub fn eat_comma_block(&mut self, i: &mut Chars) -> Result<(), DeJsonErr> {
@@ -251,6 +444,14 @@ impl DeJsonState {
         Err(self.err_token("]"))
     }
     
+    pub fn null(&mut self, i: &mut Chars) -> Result<(), DeJsonErr> {
+        if self.tok == DeJsonTok::Null {
+            self.next_tok(i) ?;
+            return Ok(())
+        }
+        Err(self.err_token("null"))
+    }
+    
     pub fn curly_open(&mut self, i: &mut Chars) -> Result<(), DeJsonErr> {
         if self.tok == DeJsonTok::CurlyOpen {
             self.next_tok(i) ?;
@@ -307,6 +508,23 @@ impl DeJsonState {
         Err(self.err_token("floating point"))
     }
     
+    pub fn as_u128(&mut self) -> Result<u128, DeJsonErr> {
+        match self.tok {
+            DeJsonTok::U64(value) => Ok(value as u128),
+            DeJsonTok::I64(_) | DeJsonTok::F64(_) => self.numbuf.parse().map_err( | _ | self.err_range(&self.numbuf)),
+            _ => Err(self.err_token("unsigned integer"))
+        }
+    }
+    
+    pub fn as_i128(&mut self) -> Result<i128, DeJsonErr> {
+        match self.tok {
+            DeJsonTok::U64(value) => Ok(value as i128),
+            DeJsonTok::I64(value) => Ok(value as i128),
+            DeJsonTok::F64(_) => self.numbuf.parse().map_err( | _ | self.err_range(&self.numbuf)),
+            _ => Err(self.err_token("signed integer"))
+        }
+    }
+    
     pub fn as_bool(&mut self) -> Result<bool, DeJsonErr> {
         if let DeJsonTok::Bool(value) = self.tok {
             return Ok(value)
@@ -324,9 +542,46 @@ impl DeJsonState {
     }
     
     pub fn next_tok(&mut self, i: &mut Chars) -> Result<(), DeJsonErr> {
+        if self.reader.is_none() {
+            return self.read_tok(i)
+        }
+        // don't read past the end of a top level value, a pipe might not have more yet
+        if self.depth == 0 && self.value_done {
+            self.tok = DeJsonTok::Eof;
+            return Ok(())
+        }
+        self.read_tok(i) ?;
+        match self.tok {
+            DeJsonTok::CurlyOpen | DeJsonTok::BlockOpen => self.depth += 1,
+            DeJsonTok::CurlyClose | DeJsonTok::BlockClose => self.depth = self.depth.saturating_sub(1),
+            _ => ()
+        }
+        if self.depth == 0 {
+            self.value_done = !matches!(self.tok, DeJsonTok::Eof | DeJsonTok::Colon | DeJsonTok::Comma);
+        }
+        Ok(())
+    }
+    
+    // consumes cur, unless it closes a top level value read from a reader
+    fn next_closing(&mut self, i: &mut Chars, depth: usize) {
+        if self.reader.is_some() && self.depth == depth {
+            self.cur_pending = true;
+        }
+        else {
+            self.next(i);
+        }
+    }
+    
+    fn read_tok(&mut self, i: &mut Chars) -> Result<(), DeJsonErr> {
+        if self.cur_pending {
+            self.cur_pending = false;
+            self.next(i);
+        }
         while self.cur == '\n' || self.cur == '\r' || self.cur == '\t' || self.cur == ' ' {
             self.next(i);
         }
+        self.tok_line = self.line;
+        self.tok_col = self.col;
         if self.cur == '\0' {
             self.tok = DeJsonTok::Eof;
             return Ok(())
@@ -348,7 +603,7 @@ impl DeJsonState {
                 return Ok(())
             }
             ']' => {
-                self.next(i);
+                self.next_closing(i, 1);
                 self.tok = DeJsonTok::BlockClose;
                 return Ok(())
             }
@@ -358,7 +613,7 @@ impl DeJsonState {
                 return Ok(())
             }
             '}' => {
-                self.next(i);
+                self.next_closing(i, 1);
                 self.tok = DeJsonTok::CurlyClose;
                 return Ok(())
             }
@@ -397,6 +652,11 @@ impl DeJsonState {
                             self.tok = DeJsonTok::I64(num);
                             return Ok(())
                         }
+                        // wider than 64 bits, as_u128 and as_i128 parse the digits again
+                        else if let Ok(num) = self.numbuf.parse() {
+                            self.tok = DeJsonTok::F64(num);
+                            return Ok(())
+                        }
                         else {
                             return Err(self.err_parse("number"));
                         }
@@ -405,6 +665,11 @@ impl DeJsonState {
                         self.tok = DeJsonTok::U64(num);
                         return Ok(())
                     }
+                    // wider than 64 bits, as_u128 and as_i128 parse the digits again
+                    else if let Ok(num) = self.numbuf.parse() {
+                        self.tok = DeJsonTok::F64(num);
+                        return Ok(())
+                    }
                     else {
                         return Err(self.err_parse("number"));
                     }
@@ -459,7 +724,7 @@ impl DeJsonState {
                         self.next(i);
                     }
                 }
-                self.next(i);
+                self.next_closing(i, 0);
                 self.tok = DeJsonTok::Str;
                 return Ok(())
             },
@@ -538,6 +803,27 @@ impl_ser_de_json_signed!(i64, std::i64::MIN, std::i64::MAX);
 impl_ser_de_json_signed!(i32, std::i64::MIN, std::i64::MAX);
 impl_ser_de_json_signed!(i16, std::i64::MIN, std::i64::MAX);
 impl_ser_de_json_signed!(i8, std::i64::MIN, std::i8::MAX);
+macro_rules!impl_ser_de_json_wide {
+    ( $ ty: ident, $ parse: ident) => {
+        impl SerJson for $ ty {
+            fn ser_json(&self, _d: usize, s: &mut SerJsonState) {
+                s.out.push_str(&self.to_string());
+            }
+        }
+        
+        impl DeJson for $ ty {
+            fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result< $ ty,
+            DeJsonErr> {
+                let val = s. $ parse() ?;
+                s.next_tok(i) ?;
+                return Ok(val);
+            }
+        }
+    }
+}
+
+impl_ser_de_json_wide!(u128, as_u128);
+impl_ser_de_json_wide!(i128, as_i128);
 impl_ser_de_json_float!(f64);
 impl_ser_de_json_float!(f32);
 
@@ -547,7 +833,7 @@ impl<T> SerJson for Option<T> where T: SerJson {
             v.ser_json(d, s);
         }
         else {
-            s.out.push_str("None");
+            s.out.push_str("null");
         }
     }
 }
@@ -563,6 +849,18 @@ impl<T> DeJson for Option<T> where T: DeJson{
     }
 }
 
+impl SerJson for () {
+    fn ser_json(&self, _d: usize, s: &mut SerJsonState) {
+        s.out.push_str("null")
+    }
+}
+
+impl DeJson for () {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<(), DeJsonErr> {
+        s.null(i)
+    }
+}
+
 impl SerJson for bool {
     fn ser_json(&self, _d: usize, s: &mut SerJsonState) {
         if *self {
@@ -582,7 +880,7 @@ impl DeJson for bool {
     }
 }
 
-impl SerJson for String {
+impl SerJson for str {
     fn ser_json(&self, _d: usize, s: &mut SerJsonState) {
         s.out.push('"');
         for c in self.chars() {
@@ -600,6 +898,12 @@ impl SerJson for String {
     }
 }
 
+impl SerJson for String {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        self.as_str().ser_json(d, s)
+    }
+}
+
 impl DeJson for String {
     fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<String, DeJsonErr> {
         let val = s.as_string() ?;
@@ -608,20 +912,40 @@ impl DeJson for String {
     }
 }
 
-impl<T> SerJson for Vec<T> where T: SerJson {
+// a char is a string of one character
+impl SerJson for char {
     fn ser_json(&self, d: usize, s: &mut SerJsonState) {
-        s.out.push('[');
-        if self.len() > 0{
-            let last = self.len() -1;
-            for (index,item) in self.iter().enumerate() {
-                s.indent(d + 1);
-                item.ser_json(d + 1, s);
-                if index != last{
-                    s.out.push(',');
-                }
-            }
+        self.encode_utf8(&mut [0; 4]).ser_json(d, s)
+    }
+}
+
+impl DeJson for char {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<char, DeJsonErr> {
+        let mut chars = s.strbuf.chars();
+        let val = match (&s.tok, chars.next(), chars.next()) {
+            (DeJsonTok::Str, Some(c), None) => c,
+            _ => return Err(s.err_type("char"))
+        };
+        s.next_tok(i) ?;
+        Ok(val)
+    }
+}
+
+fn ser_json_seq<'a, T: SerJson + 'a>(items: impl Iterator<Item = &'a T>, d: usize, s: &mut SerJsonState) {
+    s.out.push('[');
+    for (index, item) in items.enumerate() {
+        if index != 0 {
+            s.out.push(',');
         }
-        s.out.push(']');
+        s.indent(d + 1);
+        item.ser_json(d + 1, s);
+    }
+    s.out.push(']');
+}
+
+impl<T> SerJson for Vec<T> where T: SerJson {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        ser_json_seq(self.iter(), d, s)
     }
 }
 
@@ -631,7 +955,8 @@ impl<T> DeJson for Vec<T> where T: DeJson {
         s.block_open(i) ?;
         
         while s.tok != DeJsonTok::BlockClose {
-            out.push(DeJson::de_json(s, i) ?);
+            let index = out.len();
+            out.push(DeJson::de_json(s, i).map_err( | e | e.at_index(index)) ?);
             s.eat_comma_block(i) ?;
         }
         s.block_close(i) ?;
@@ -641,22 +966,14 @@ impl<T> DeJson for Vec<T> where T: DeJson {
 
 impl<T> SerJson for [T] where T: SerJson {
     fn ser_json(&self, d: usize, s: &mut SerJsonState) {
-        s.out.push('[');
-        let last = self.len() -1;
-        for (index,item) in self.iter().enumerate() {
-            item.ser_json(d + 1, s);
-            if index != last{
-                s.out.push(',');
-            }
-        }
-        s.out.push(']');
+        ser_json_seq(self.iter(), d, s)
     }
 }
 
 unsafe fn de_json_array_impl_inner<T>(top: *mut T, count: usize, s: &mut DeJsonState, i: &mut Chars) -> Result<(), DeJsonErr> where T:DeJson{
     s.block_open(i) ?;
     for c in 0..count {
-        top.add(c).write(DeJson::de_json(s, i) ?);
+        top.add(c).write(DeJson::de_json(s, i).map_err( | e | e.at_index(c)) ?);
         s.eat_comma_block(i) ?;
     }
     s.block_close(i) ?;
@@ -683,105 +1000,120 @@ macro_rules!de_json_array_impl {
 
 de_json_array_impl!(2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24,25,26,27,28,29,30,31,32);
 
-fn de_json_comma_block<T>(s: &mut DeJsonState, i: &mut Chars) -> Result<T, DeJsonErr> where T: DeJson {
-    let t = DeJson::de_json(s, i);
-    s.eat_comma_block(i) ?;
-    t
+impl<T> SerJson for VecDeque<T> where T: SerJson {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        ser_json_seq(self.iter(), d, s)
+    }
+}
+
+impl<T> DeJson for VecDeque<T> where T: DeJson {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self, DeJsonErr> {
+        Ok(Vec::<T>::de_json(s, i) ?.into())
+    }
 }
 
-impl<A, B> SerJson for (A, B) where A: SerJson,
-B: SerJson {
+impl<T> SerJson for HashSet<T> where T: SerJson {
     fn ser_json(&self, d: usize, s: &mut SerJsonState) {
-        s.out.push('[');
-        self.0.ser_json(d, s);
-        s.out.push(',');
-        self.1.ser_json(d, s);
-        s.out.push(']');
+        ser_json_seq(self.iter(), d, s)
     }
 }
 
-impl<A, B> DeJson for (A, B) where A: DeJson,
-B: DeJson {
-    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<(A, B), DeJsonErr> {
-        s.block_open(i) ?;
-        let r = (de_json_comma_block(s, i) ?, de_json_comma_block(s, i) ?);
-        s.block_close(i) ?;
-        Ok(r)
+impl<T> DeJson for HashSet<T> where T: DeJson + Eq + Hash {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self, DeJsonErr> {
+        Ok(Vec::<T>::de_json(s, i) ?.into_iter().collect())
     }
 }
 
-impl<A, B, C> SerJson for (A, B, C) where A: SerJson,
-B: SerJson,
-C: SerJson {
+impl<T> SerJson for BTreeSet<T> where T: SerJson {
     fn ser_json(&self, d: usize, s: &mut SerJsonState) {
-        s.out.push('[');
-        self.0.ser_json(d, s);
-        s.out.push(',');
-        self.1.ser_json(d, s);
-        s.out.push(',');
-        self.2.ser_json(d, s);
-        s.out.push(']');
+        ser_json_seq(self.iter(), d, s)
     }
 }
 
-impl<A, B, C> DeJson for (A, B, C) where A: DeJson,
-B: DeJson,
-C: DeJson {
-    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<(A, B, C), DeJsonErr> {
-        s.block_open(i) ?;
-        let r = (de_json_comma_block(s, i) ?, de_json_comma_block(s, i) ?, de_json_comma_block(s, i) ?);
-        s.block_close(i) ?;
-        Ok(r)
+impl<T> DeJson for BTreeSet<T> where T: DeJson + Ord {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self, DeJsonErr> {
+        Ok(Vec::<T>::de_json(s, i) ?.into_iter().collect())
     }
 }
 
-impl<A, B, C, D> SerJson for (A, B, C, D) where A: SerJson,
-B: SerJson,
-C: SerJson,
-D: SerJson {
-    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
-        s.out.push('[');
-        self.0.ser_json(d, s);
-        s.out.push(',');
-        self.1.ser_json(d, s);
-        s.out.push(',');
-        self.2.ser_json(d, s);
-        s.out.push(',');
-        self.3.ser_json(d, s);
-        s.out.push(']');
-    }
-}
-
-impl<A, B, C, D> DeJson for (A, B, C, D) where A: DeJson,
-B: DeJson,
-C: DeJson,
-D: DeJson {
-    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<(A, B, C, D), DeJsonErr> {
-        s.block_open(i) ?;
-        let r = (de_json_comma_block(s, i) ?, de_json_comma_block(s, i) ?, de_json_comma_block(s, i) ?, de_json_comma_block(s, i) ?);
-        s.block_close(i) ?;
-        Ok(r)
+fn de_json_comma_block<T>(s: &mut DeJsonState, i: &mut Chars, index: usize) -> Result<T, DeJsonErr> where T: DeJson {
+    let t = DeJson::de_json(s, i).map_err( | e | e.at_index(index)) ?;
+    s.eat_comma_block(i) ?;
+    Ok(t)
+}
+
+macro_rules!impl_ser_de_json_tuple {
+    ( $ first_ty: ident $ first: tt $ (, $ ty: ident $ index: tt) *) => {
+        impl< $ first_ty $ (, $ ty) *> SerJson for ( $ first_ty $ (, $ ty) *) where $ first_ty: SerJson $ (, $ ty: SerJson) * {
+            fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+                s.out.push('[');
+                self. $ first.ser_json(d, s);
+                $ (
+                    s.out.push(',');
+                    self. $ index.ser_json(d, s);
+                ) *
+                s.out.push(']');
+            }
+        }
+        
+        impl< $ first_ty $ (, $ ty) *> DeJson for ( $ first_ty $ (, $ ty) *) where $ first_ty: DeJson $ (, $ ty: DeJson) * {
+            fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self, DeJsonErr> {
+                s.block_open(i) ?;
+                let r = (de_json_comma_block::< $ first_ty>(s, i, $ first) ? $ (, de_json_comma_block::< $ ty>(s, i, $ index) ?) *);
+                s.block_close(i) ?;
+                Ok(r)
+            }
+        }
     }
 }
 
+impl_ser_de_json_tuple!(A 0, B 1);
+impl_ser_de_json_tuple!(A 0, B 1, C 2);
+impl_ser_de_json_tuple!(A 0, B 1, C 2, D 3);
+impl_ser_de_json_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_ser_de_json_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_ser_de_json_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_ser_de_json_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_ser_de_json_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_ser_de_json_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_ser_de_json_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_ser_de_json_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+fn ser_json_map<'a, K: SerJson + 'a, V: SerJson + 'a>(items: impl Iterator<Item = (&'a K, &'a V)>, d: usize, s: &mut SerJsonState) {
+    s.out.push('{');
+    for (index, (k, v)) in items.enumerate() {
+        if index != 0 {
+            s.conl();
+        }
+        s.indent(d + 1);
+        k.ser_json(d + 1, s);
+        s.out.push(':');
+        v.ser_json(d + 1, s);
+    }
+    s.indent(d);
+    s.out.push('}');
+}
+
+fn de_json_map<K: DeJson, V: DeJson>(s: &mut DeJsonState, i: &mut Chars, mut insert: impl FnMut(K, V)) -> Result<(), DeJsonErr> {
+    s.curly_open(i) ?;
+    // a nested map takes a fresh buffer, ours goes back when we're done
+    let mut key = std::mem::take(&mut s.keybuf);
+    while s.tok != DeJsonTok::CurlyClose {
+        s.key_text(&mut key);
+        let k = DeJson::de_json(s, i).map_err( | e | e.at_key(&key)) ?;
+        s.colon(i) ?;
+        let v = DeJson::de_json(s, i).map_err( | e | e.at_key(&key)) ?;
+        s.eat_comma_curly(i) ?;
+        insert(k, v);
+    }
+    s.keybuf = key;
+    s.curly_close(i)
+}
+
 impl<K, V> SerJson for HashMap<K, V> where K: SerJson,
 V: SerJson {
     fn ser_json(&self, d: usize, s: &mut SerJsonState) {
-        s.out.push('{');
-        let last = self.len() - 1;
-        let mut index = 0;
-        for (k, v) in self {
-            s.indent(d + 1);
-            k.ser_json(d + 1, s);
-            s.out.push(':');
-            v.ser_json(d + 1, s);
-            if index != last{
-                s.conl();
-            }
-            index += 1;
-        }
-        s.indent(d);
-        s.out.push('}');
+        ser_json_map(self.iter(), d, s)
     }
 }
 
@@ -790,27 +1122,44 @@ V: DeJson  {
     fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self,
     DeJsonErr> {
         let mut h = HashMap::new();
-        s.curly_open(i) ?;
-        while s.tok != DeJsonTok::CurlyClose {
-            let k = DeJson::de_json(s, i) ?;
-            s.colon(i) ?;
-            let v = DeJson::de_json(s, i) ?;
-            s.eat_comma_curly(i) ?;
-            h.insert(k, v);
-        }
-        s.curly_close(i) ?;
+        de_json_map(s, i, | k, v | {h.insert(k, v);}) ?;
         Ok(h)
     }
 }
 
-impl<T> SerJson for Box<T> where T: SerJson {
+impl<K, V> SerJson for BTreeMap<K, V> where K: SerJson,
+V: SerJson {
     fn ser_json(&self, d: usize, s: &mut SerJsonState) {
-        (**self).ser_json(d, s)
+        ser_json_map(self.iter(), d, s)
     }
 }
 
-impl<T> DeJson for Box<T> where T: DeJson {
-    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Box<T>, DeJsonErr> {
-        Ok(Box::new(DeJson::de_json(s, i) ?))
+impl<K, V> DeJson for BTreeMap<K, V> where K: DeJson + Ord,
+V: DeJson  {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self,
+    DeJsonErr> {
+        let mut h = BTreeMap::new();
+        de_json_map(s, i, | k, v | {h.insert(k, v);}) ?;
+        Ok(h)
     }
-}
\ No newline at end of file
+}
+
+macro_rules!impl_ser_de_json_pointer {
+    ( $ ty: ident) => {
+        impl<T> SerJson for $ ty<T> where T: SerJson {
+            fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+                (**self).ser_json(d, s)
+            }
+        }
+        
+        impl<T> DeJson for $ ty<T> where T: DeJson {
+            fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<$ ty<T>, DeJsonErr> {
+                Ok($ ty::new(DeJson::de_json(s, i) ?))
+            }
+        }
+    }
+}
+
+impl_ser_de_json_pointer!(Box);
+impl_ser_de_json_pointer!(Rc);
+impl_ser_de_json_pointer!(Arc);
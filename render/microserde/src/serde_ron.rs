@@ -1,5 +1,9 @@
-use std::collections::{HashMap};
+use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
 use std::hash::Hash;
+use std::fmt::Write;
+use crate::de_path::*;
 use std::str::Chars;
 
 pub struct SerRonState {
@@ -62,6 +66,24 @@ pub trait DeRon: Sized {
     DeRonErr>;
 }
 
+// implemented by the derive for structs with named fields, so they can be inlined with #[nserde(flatten)]
+pub trait SerRonFlat {
+    fn ser_ron_flat(&self, d: usize, s: &mut SerRonState);
+}
+
+pub trait DeRonFlat: Sized {
+    type Partial;
+    
+    fn de_ron_flat_new() -> Self::Partial;
+    
+    // reads the value for the key in s.identbuf, returns false if the key isn't ours
+    fn de_ron_flat_field(p: &mut Self::Partial, s: &mut DeRonState, i: &mut Chars) -> Result<bool,
+    DeRonErr>;
+    
+    fn de_ron_flat_finish(p: Self::Partial, s: &mut DeRonState) -> Result<Self,
+    DeRonErr>;
+}
+
 #[derive(PartialEq, Debug)]
 pub enum DeRonTok {
     Ident,
@@ -95,18 +117,40 @@ pub struct DeRonState {
     pub numbuf: String,
     pub identbuf: String,
     pub line: usize,
-    pub col: usize
+    pub col: usize,
+    // where the current token starts, errors point here
+    pub tok_line: usize,
+    pub tok_col: usize,
+    // holds the text of map keys so it is only copied into a path segment on error
+    pub keybuf: String
 }
 
+// line is 0 based, col is 1 based like the col of the state, Debug and render both print line + 1 and col
 pub struct DeRonErr {
     pub msg: String,
     pub line: usize,
-    pub col: usize
+    pub col: usize,
+    pub path: DePath
 }
 
 impl std::fmt::Debug for DeRonErr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Ron Deserialize error: {}, line:{} col:{}", self.msg, self.line + 1, self.col + 1)
+        write!(f, "Ron Deserialize error: {}, line:{} col:{}", self.msg, self.line + 1, self.col) ?;
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path) ?;
+        }
+        Ok(())
+    }
+}
+
+impl DeErrAt for DeRonErr {
+    fn path_mut(&mut self) -> &mut DePath {&mut self.path}
+}
+
+impl DeRonErr {
+    // the message with the offending line of the source and a caret under it
+    pub fn render(&self, name: &str, source: &str) -> String {
+        render_source_error(name, source, self.line, self.col.saturating_sub(1), &self.msg, &self.path)
     }
 }
 
@@ -119,7 +163,7 @@ impl DeRonState {
                 self.col = 0;
             }
             else {
-                self.col = 0;
+                self.col += 1;
             }
         }
         else {
@@ -127,32 +171,47 @@ impl DeRonState {
         }
     }
     
+    // writes the text of a map key before it is read into out, for the error path
+    pub fn key_text(&self, out: &mut String) {
+        out.clear();
+        let _ = match self.tok {
+            DeRonTok::Str => write!(out, "{}", self.strbuf),
+            DeRonTok::Ident => write!(out, "{}", self.identbuf),
+            DeRonTok::Char(c) => write!(out, "{}", c),
+            DeRonTok::U64(v) => write!(out, "{}", v),
+            DeRonTok::I64(v) => write!(out, "{}", v),
+            DeRonTok::F64(_) => write!(out, "{}", self.numbuf),
+            DeRonTok::Bool(v) => write!(out, "{}", v),
+            _ => Ok(())
+        };
+    }
+    
     pub fn err_exp(&self, name: &str) -> DeRonErr {
-        DeRonErr {msg: format!("Unexpected key {}", name), line: self.line, col: self.col}
+        DeRonErr {msg: format!("Unexpected key {}", name), line: self.tok_line, col: self.tok_col, path: DePath::default()}
     }
     
     pub fn err_nf(&self, name: &str) -> DeRonErr {
-        DeRonErr {msg: format!("Key not found {}", name), line: self.line, col: self.col}
+        DeRonErr {msg: format!("Key not found {}", name), line: self.tok_line, col: self.tok_col, path: DePath::default()}
     }
     
     pub fn err_enum(&self, name: &str) -> DeRonErr {
-        DeRonErr {msg: format!("Enum not defined {}", name), line: self.line, col: self.col}
+        DeRonErr {msg: format!("Enum not defined {}", name), line: self.tok_line, col: self.tok_col, path: DePath::default()}
     }
     
     pub fn err_token(&self, what: &str) -> DeRonErr {
-        DeRonErr {msg: format!("Unexpected token {:?} expected {} ", self.tok, what), line: self.line, col: self.col}
+        DeRonErr {msg: format!("Unexpected token {:?} expected {} ", self.tok, what), line: self.tok_line, col: self.tok_col, path: DePath::default()}
     }
     
     pub fn err_range(&self, what: &str) -> DeRonErr {
-        DeRonErr {msg: format!("Value out of range {} ", what), line: self.line, col: self.col}
+        DeRonErr {msg: format!("Value out of range {} ", what), line: self.tok_line, col: self.tok_col, path: DePath::default()}
     }
     
     pub fn err_type(&self, what: &str) -> DeRonErr {
-        DeRonErr {msg: format!("Token wrong type {} ", what), line: self.line, col: self.col}
+        DeRonErr {msg: format!("Token wrong type {} ", what), line: self.tok_line, col: self.tok_col, path: DePath::default()}
     }
     
     pub fn err_parse(&self, what: &str) -> DeRonErr {
-        DeRonErr {msg: format!("Cannot parse {} ", what), line: self.line, col: self.col}
+        DeRonErr {msg: format!("Cannot parse {} ", what), line: self.tok_line, col: self.tok_col, path: DePath::default()}
     }
     
     pub fn eat_comma_paren(&mut self, i: &mut Chars) -> Result<(), DeRonErr> {
@@ -330,6 +389,23 @@ impl DeRonState {
         Err(self.err_token("floating point"))
     }
     
+    pub fn as_u128(&mut self) -> Result<u128, DeRonErr> {
+        match self.tok {
+            DeRonTok::U64(value) => Ok(value as u128),
+            DeRonTok::I64(_) | DeRonTok::F64(_) => self.numbuf.parse().map_err( | _ | self.err_range(&self.numbuf)),
+            _ => Err(self.err_token("unsigned integer"))
+        }
+    }
+    
+    pub fn as_i128(&mut self) -> Result<i128, DeRonErr> {
+        match self.tok {
+            DeRonTok::U64(value) => Ok(value as i128),
+            DeRonTok::I64(value) => Ok(value as i128),
+            DeRonTok::F64(_) => self.numbuf.parse().map_err( | _ | self.err_range(&self.numbuf)),
+            _ => Err(self.err_token("signed integer"))
+        }
+    }
+    
     pub fn as_bool(&mut self) -> Result<bool, DeRonErr> {
         if let DeRonTok::Bool(value) = self.tok {
             return Ok(value)
@@ -354,6 +430,8 @@ impl DeRonState {
             while self.cur == '\n' || self.cur == '\r' || self.cur == '\t' || self.cur == ' ' {
                 self.next(i);
             }
+            self.tok_line = self.line;
+            self.tok_col = self.col;
             match self.cur {
                 '\0' => {
                     self.tok = DeRonTok::Eof;
@@ -460,6 +538,11 @@ impl DeRonState {
                                 self.tok = DeRonTok::I64(num);
                                 return Ok(())
                             }
+                            // wider than 64 bits, as_u128 and as_i128 parse the digits again
+                            else if let Ok(num) = self.numbuf.parse() {
+                                self.tok = DeRonTok::F64(num);
+                                return Ok(())
+                            }
                             else {
                                 return Err(self.err_parse("number"));
                             }
@@ -468,6 +551,11 @@ impl DeRonState {
                             self.tok = DeRonTok::U64(num);
                             return Ok(())
                         }
+                        // wider than 64 bits, as_u128 and as_i128 parse the digits again
+                        else if let Ok(num) = self.numbuf.parse() {
+                            self.tok = DeRonTok::F64(num);
+                            return Ok(())
+                        }
                         else {
                             return Err(self.err_parse("number"));
                         }
@@ -477,6 +565,7 @@ impl DeRonState {
                     self.identbuf.truncate(0);
                     while self.cur >= 'a' && self.cur <= 'z'
                         || self.cur >= 'A' && self.cur <= 'Z'
+                        || self.cur >= '0' && self.cur <= '9'
                         || self.cur == '_' {
                         self.identbuf.push(self.cur);
                         self.next(i);
@@ -494,16 +583,26 @@ impl DeRonState {
                 },
                 '\'' => {
                     self.next(i);
-                    if self.cur == '\\' {
+                    let chr = if self.cur == '\\' {
                         self.next(i);
+                        match self.cur {
+                            'n' => '\n',
+                            'r' => '\r',
+                            't' => '\t',
+                            '0' => '\0',
+                            c => c
+                        }
                     }
-                    let chr = self.cur;
+                    else {
+                        self.cur
+                    };
                     self.next(i);
                     if self.cur != '\'' {
                         return Err(self.err_token("char"));
                     }
                     self.next(i);
                     self.tok = DeRonTok::Char(chr);
+                    return Ok(())
                 },
                 '"' => {
                     self.strbuf.truncate(0);
@@ -612,6 +711,27 @@ impl_ser_de_ron_signed!(i64, std::i64::MIN, std::i64::MAX);
 impl_ser_de_ron_signed!(i32, std::i64::MIN, std::i64::MAX);
 impl_ser_de_ron_signed!(i16, std::i64::MIN, std::i64::MAX);
 impl_ser_de_ron_signed!(i8, std::i64::MIN, std::i8::MAX);
+macro_rules!impl_ser_de_ron_wide {
+    ( $ ty: ident, $ parse: ident) => {
+        impl SerRon for $ ty {
+            fn ser_ron(&self, _d: usize, s: &mut SerRonState) {
+                s.out.push_str(&self.to_string());
+            }
+        }
+        
+        impl DeRon for $ ty {
+            fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result< $ ty,
+            DeRonErr> {
+                let val = s. $ parse() ?;
+                s.next_tok(i) ?;
+                return Ok(val);
+            }
+        }
+    }
+}
+
+impl_ser_de_ron_wide!(u128, as_u128);
+impl_ser_de_ron_wide!(i128, as_i128);
 impl_ser_de_ron_float!(f64);
 impl_ser_de_ron_float!(f32);
 
@@ -639,6 +759,19 @@ impl<T> DeRon for Option<T> where T: DeRon {
     }
 }
 
+impl SerRon for () {
+    fn ser_ron(&self, _d: usize, s: &mut SerRonState) {
+        s.out.push_str("()")
+    }
+}
+
+impl DeRon for () {
+    fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<(), DeRonErr> {
+        s.paren_open(i) ?;
+        s.paren_close(i)
+    }
+}
+
 impl SerRon for bool {
     fn ser_ron(&self, _d: usize, s: &mut SerRonState) {
         if *self {
@@ -658,24 +791,34 @@ impl DeRon for bool {
     }
 }
 
-impl SerRon for String {
+fn ser_ron_escaped(c: char, quote: char, s: &mut SerRonState) {
+    match c {
+        '\n' => {s.out.push('\\'); s.out.push('n');},
+        '\r' => {s.out.push('\\'); s.out.push('r');},
+        '\t' => {s.out.push('\\'); s.out.push('t');},
+        '\0' => {s.out.push('\\'); s.out.push('0');},
+        '\\' => {s.out.push('\\'); s.out.push('\\');},
+        c if c == quote => {s.out.push('\\'); s.out.push(c);},
+        _ => s.out.push(c)
+    }
+}
+
+impl SerRon for str {
     fn ser_ron(&self, _d: usize, s: &mut SerRonState) {
         s.out.push('"');
         for c in self.chars() {
-            match c {
-                '\n' => {s.out.push('\\'); s.out.push('n');},
-                '\r' => {s.out.push('\\'); s.out.push('r');},
-                '\t' => {s.out.push('\\'); s.out.push('t');},
-                '\0' => {s.out.push('\\'); s.out.push('0');},
-                '\\' => {s.out.push('\\'); s.out.push('\\');},
-                '"' => {s.out.push('\\'); s.out.push('"');},
-                _ => s.out.push(c)
-            }
+            ser_ron_escaped(c, '"', s);
         }
         s.out.push('"');
     }
 }
 
+impl SerRon for String {
+    fn ser_ron(&self, d: usize, s: &mut SerRonState) {
+        self.as_str().ser_ron(d, s)
+    }
+}
+
 impl DeRon for String {
     fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<String, DeRonErr> {
         let val = s.as_string() ?;
@@ -684,16 +827,38 @@ impl DeRon for String {
     }
 }
 
+impl SerRon for char {
+    fn ser_ron(&self, _d: usize, s: &mut SerRonState) {
+        s.out.push('\'');
+        ser_ron_escaped(*self, '\'', s);
+        s.out.push('\'');
+    }
+}
+
+impl DeRon for char {
+    fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<char, DeRonErr> {
+        if let DeRonTok::Char(val) = s.tok {
+            s.next_tok(i) ?;
+            return Ok(val)
+        }
+        Err(s.err_token("char"))
+    }
+}
+
+fn ser_ron_seq<'a, T: SerRon + 'a>(items: impl Iterator<Item = &'a T>, d: usize, s: &mut SerRonState) {
+    s.out.push_str("[\n");
+    for item in items {
+        s.indent(d + 1);
+        item.ser_ron(d + 1, s);
+        s.conl();
+    }
+    s.indent(d);
+    s.out.push(']');
+}
+
 impl<T> SerRon for Vec<T> where T: SerRon {
     fn ser_ron(&self, d: usize, s: &mut SerRonState) {
-        s.out.push_str("[\n");
-        for item in self {
-            s.indent(d + 1);
-            item.ser_ron(d + 1, s);
-            s.conl();
-        }
-        s.indent(d);
-        s.out.push(']');
+        ser_ron_seq(self.iter(), d, s)
     }
 }
 
@@ -703,7 +868,8 @@ impl<T> DeRon for Vec<T> where T: DeRon {
         s.block_open(i) ?;
         
         while s.tok != DeRonTok::BlockClose {
-            out.push(DeRon::de_ron(s, i) ?);
+            let index = out.len();
+            out.push(DeRon::de_ron(s, i).map_err( | e | e.at_index(index)) ?);
             s.eat_comma_block(i) ?;
         }
         s.block_close(i) ?;
@@ -714,12 +880,11 @@ impl<T> DeRon for Vec<T> where T: DeRon {
 impl<T> SerRon for [T] where T: SerRon {
     fn ser_ron(&self, d: usize, s: &mut SerRonState) {
         s.out.push('(');
-        let last = self.len() - 1;
         for (index, item) in self.iter().enumerate() {
-            item.ser_ron(d + 1, s);
-            if index != last {
+            if index != 0 {
                 s.out.push_str(", ");
             }
+            item.ser_ron(d + 1, s);
         }
         s.out.push(')');
     }
@@ -728,7 +893,7 @@ impl<T> SerRon for [T] where T: SerRon {
 unsafe fn de_ron_array_impl_inner<T>(top: *mut T, count: usize, s: &mut DeRonState, i: &mut Chars) -> Result<(), DeRonErr> where T: DeRon {
     s.paren_open(i) ?;
     for c in 0..count {
-        top.add(c).write(DeRon::de_ron(s, i) ?);
+        top.add(c).write(DeRon::de_ron(s, i).map_err( | e | e.at_index(c)) ?);
         s.eat_comma_paren(i) ?;
     }
     s.paren_close(i) ?;
@@ -755,100 +920,118 @@ macro_rules!de_ron_array_impl {
 
 de_ron_array_impl!(2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32);
 
-fn de_ron_comma_paren<T>(s: &mut DeRonState, i: &mut Chars) -> Result<T, DeRonErr> where T: DeRon {
-    let t = DeRon::de_ron(s, i);
-    s.eat_comma_paren(i) ?;
-    t
+impl<T> SerRon for VecDeque<T> where T: SerRon {
+    fn ser_ron(&self, d: usize, s: &mut SerRonState) {
+        ser_ron_seq(self.iter(), d, s)
+    }
+}
+
+impl<T> DeRon for VecDeque<T> where T: DeRon {
+    fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<Self, DeRonErr> {
+        Ok(Vec::<T>::de_ron(s, i) ?.into())
+    }
 }
 
-impl<A, B> SerRon for (A, B) where A: SerRon,
-B: SerRon {
+impl<T> SerRon for HashSet<T> where T: SerRon {
     fn ser_ron(&self, d: usize, s: &mut SerRonState) {
-        s.out.push('(');
-        self.0.ser_ron(d, s);
-        s.out.push_str(", ");
-        self.1.ser_ron(d, s);
-        s.out.push(')');
+        ser_ron_seq(self.iter(), d, s)
     }
 }
 
-impl<A, B> DeRon for (A, B) where A: DeRon,
-B: DeRon {
-    fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<(A, B), DeRonErr> {
-        s.paren_open(i) ?;
-        let r = (de_ron_comma_paren(s, i) ?, de_ron_comma_paren(s, i) ?);
-        s.paren_close(i) ?;
-        Ok(r)
+impl<T> DeRon for HashSet<T> where T: DeRon + Eq + Hash {
+    fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<Self, DeRonErr> {
+        Ok(Vec::<T>::de_ron(s, i) ?.into_iter().collect())
     }
 }
 
-impl<A, B, C> SerRon for (A, B, C) where A: SerRon,
-B: SerRon,
-C: SerRon {
+impl<T> SerRon for BTreeSet<T> where T: SerRon {
     fn ser_ron(&self, d: usize, s: &mut SerRonState) {
-        s.out.push('(');
-        self.0.ser_ron(d, s);
-        s.out.push_str(", ");
-        self.1.ser_ron(d, s);
-        s.out.push_str(", ");
-        self.2.ser_ron(d, s);
-        s.out.push(')');
+        ser_ron_seq(self.iter(), d, s)
     }
 }
 
-impl<A, B, C> DeRon for (A, B, C) where A: DeRon,
-B: DeRon,
-C: DeRon {
-    fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<(A, B, C), DeRonErr> {
-        s.paren_open(i) ?;
-        let r = (de_ron_comma_paren(s, i) ?, de_ron_comma_paren(s, i) ?, de_ron_comma_paren(s, i) ?);
-        s.paren_close(i) ?;
-        Ok(r)
+impl<T> DeRon for BTreeSet<T> where T: DeRon + Ord {
+    fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<Self, DeRonErr> {
+        Ok(Vec::<T>::de_ron(s, i) ?.into_iter().collect())
     }
 }
 
-impl<A, B, C, D> SerRon for (A, B, C, D) where A: SerRon,
-B: SerRon,
-C: SerRon,
-D: SerRon {
-    fn ser_ron(&self, d: usize, s: &mut SerRonState) {
-        s.out.push('(');
-        self.0.ser_ron(d, s);
-        s.out.push_str(", ");
-        self.1.ser_ron(d, s);
-        s.out.push_str(", ");
-        self.2.ser_ron(d, s);
-        s.out.push_str(", ");
-        self.3.ser_ron(d, s);
-        s.out.push(')');
+fn de_ron_comma_paren<T>(s: &mut DeRonState, i: &mut Chars, index: usize) -> Result<T, DeRonErr> where T: DeRon {
+    let t = DeRon::de_ron(s, i).map_err( | e | e.at_index(index)) ?;
+    s.eat_comma_paren(i) ?;
+    Ok(t)
+}
+
+macro_rules!impl_ser_de_ron_tuple {
+    ( $ first_ty: ident $ first: tt $ (, $ ty: ident $ index: tt) *) => {
+        impl< $ first_ty $ (, $ ty) *> SerRon for ( $ first_ty $ (, $ ty) *) where $ first_ty: SerRon $ (, $ ty: SerRon) * {
+            fn ser_ron(&self, d: usize, s: &mut SerRonState) {
+                s.out.push('(');
+                self. $ first.ser_ron(d, s);
+                $ (
+                    s.out.push_str(", ");
+                    self. $ index.ser_ron(d, s);
+                ) *
+                s.out.push(')');
+            }
+        }
+        
+        impl< $ first_ty $ (, $ ty) *> DeRon for ( $ first_ty $ (, $ ty) *) where $ first_ty: DeRon $ (, $ ty: DeRon) * {
+            fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<Self, DeRonErr> {
+                s.paren_open(i) ?;
+                let r = (de_ron_comma_paren::< $ first_ty>(s, i, $ first) ? $ (, de_ron_comma_paren::< $ ty>(s, i, $ index) ?) *);
+                s.paren_close(i) ?;
+                Ok(r)
+            }
+        }
     }
 }
 
-impl<A, B, C, D> DeRon for (A, B, C, D) where A: DeRon,
-B: DeRon,
-C: DeRon,
-D: DeRon {
-    fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<(A, B, C, D), DeRonErr> {
-        s.paren_open(i) ?;
-        let r = (de_ron_comma_paren(s, i) ?, de_ron_comma_paren(s, i) ?, de_ron_comma_paren(s, i) ?, de_ron_comma_paren(s, i) ?);
-        s.paren_close(i) ?;
-        Ok(r)
+impl_ser_de_ron_tuple!(A 0, B 1);
+impl_ser_de_ron_tuple!(A 0, B 1, C 2);
+impl_ser_de_ron_tuple!(A 0, B 1, C 2, D 3);
+impl_ser_de_ron_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_ser_de_ron_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_ser_de_ron_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_ser_de_ron_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_ser_de_ron_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_ser_de_ron_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_ser_de_ron_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_ser_de_ron_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+fn ser_ron_map<'a, K: SerRon + 'a, V: SerRon + 'a>(items: impl Iterator<Item = (&'a K, &'a V)>, d: usize, s: &mut SerRonState) {
+    s.out.push_str("{\n");
+    for (k, v) in items {
+        s.indent(d + 1);
+        k.ser_ron(d + 1, s);
+        s.out.push(':');
+        v.ser_ron(d + 1, s);
+        s.conl();
     }
+    s.indent(d);
+    s.out.push('}');
+}
+
+fn de_ron_map<K: DeRon, V: DeRon>(s: &mut DeRonState, i: &mut Chars, mut insert: impl FnMut(K, V)) -> Result<(), DeRonErr> {
+    // a nested map takes a fresh buffer, ours goes back when we're done
+    let mut key = std::mem::take(&mut s.keybuf);
+    s.curly_open(i) ?;
+    while s.tok != DeRonTok::CurlyClose {
+        s.key_text(&mut key);
+        let k = DeRon::de_ron(s, i).map_err( | e | e.at_key(&key)) ?;
+        s.colon(i) ?;
+        let v = DeRon::de_ron(s, i).map_err( | e | e.at_key(&key)) ?;
+        s.eat_comma_curly(i) ?;
+        insert(k, v);
+    }
+    s.keybuf = key;
+    s.curly_close(i)
 }
 
 impl<K, V> SerRon for HashMap<K, V> where K: SerRon,
 V: SerRon {
     fn ser_ron(&self, d: usize, s: &mut SerRonState) {
-        s.out.push_str("{\n");
-        for (k, v) in self {
-            s.indent(d + 1);
-            k.ser_ron(d + 1, s);
-            s.out.push_str(":");
-            v.ser_ron(d + 1, s);
-            s.conl();
-        }
-        s.indent(d);
-        s.out.push('}');
+        ser_ron_map(self.iter(), d, s)
     }
 }
 
@@ -857,27 +1040,44 @@ V: DeRon {
     fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<Self,
     DeRonErr> {
         let mut h = HashMap::new();
-        s.curly_open(i) ?;
-        while s.tok != DeRonTok::CurlyClose {
-            let k = DeRon::de_ron(s, i) ?;
-            s.colon(i) ?;
-            let v = DeRon::de_ron(s, i) ?;
-            s.eat_comma_curly(i) ?;
-            h.insert(k, v);
-        }
-        s.curly_close(i) ?;
+        de_ron_map(s, i, | k, v | {h.insert(k, v);}) ?;
         Ok(h)
     }
 }
 
-impl<T> SerRon for Box<T> where T: SerRon {
+impl<K, V> SerRon for BTreeMap<K, V> where K: SerRon,
+V: SerRon {
     fn ser_ron(&self, d: usize, s: &mut SerRonState) {
-        (**self).ser_ron(d, s)
+        ser_ron_map(self.iter(), d, s)
+    }
+}
+
+impl<K, V> DeRon for BTreeMap<K, V> where K: DeRon + Ord,
+V: DeRon {
+    fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<Self,
+    DeRonErr> {
+        let mut h = BTreeMap::new();
+        de_ron_map(s, i, | k, v | {h.insert(k, v);}) ?;
+        Ok(h)
     }
 }
 
-impl<T> DeRon for Box<T> where T: DeRon {
-    fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<Box<T>, DeRonErr> {
-        Ok(Box::new(DeRon::de_ron(s, i) ?))
+macro_rules!impl_ser_de_ron_pointer {
+    ( $ ty: ident) => {
+        impl<T> SerRon for $ ty<T> where T: SerRon {
+            fn ser_ron(&self, d: usize, s: &mut SerRonState) {
+                (**self).ser_ron(d, s)
+            }
+        }
+        
+        impl<T> DeRon for $ ty<T> where T: DeRon {
+            fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<$ ty<T>, DeRonErr> {
+                Ok($ ty::new(DeRon::de_ron(s, i) ?))
+            }
+        }
     }
-}
\ No newline at end of file
+}
+
+impl_ser_de_ron_pointer!(Box);
+impl_ser_de_ron_pointer!(Rc);
+impl_ser_de_ron_pointer!(Arc);
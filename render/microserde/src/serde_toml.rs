@@ -0,0 +1,641 @@
+use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet, VecDeque};
+use std::convert::{TryFrom, TryInto};
+use std::hash::Hash;
+use crate::de_path::*;
+use std::rc::Rc;
+use std::sync::Arc;
+use crate::toml::*;
+
+// TOML tables can be defined in any order, so (de)serialisation goes through the Toml tree
+
+#[derive(Default)]
+pub struct SerTomlState {
+    pub out: String
+}
+
+impl SerTomlState {
+    pub fn key(&mut self, key: &str) {
+        if !key.is_empty() && key.chars().all( | c | c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            self.out.push_str(key);
+        }
+        else {
+            self.string(key);
+        }
+    }
+
+    pub fn string(&mut self, val: &str) {
+        self.out.push('"');
+        for c in val.chars() {
+            match c {
+                '\u{8}' => self.out.push_str("\\b"),
+                '\t' => self.out.push_str("\\t"),
+                '\n' => self.out.push_str("\\n"),
+                '\u{c}' => self.out.push_str("\\f"),
+                '\r' => self.out.push_str("\\r"),
+                '"' => self.out.push_str("\\\""),
+                '\\' => self.out.push_str("\\\\"),
+                c if c < ' ' || c == '\u{7f}' => self.out.push_str(&format!("\\u{:04x}", c as u32)),
+                c => self.out.push(c)
+            }
+        }
+        self.out.push('"');
+    }
+
+    // writes a value on a single line, tables become inline tables
+    pub fn value(&mut self, val: &Toml) {
+        match val {
+            Toml::Str(v) => self.string(v),
+            Toml::Bool(v) => self.out.push_str(if *v {"true"} else {"false"}),
+            Toml::Int(v) => self.out.push_str(&v.to_string()),
+            Toml::Num(v) => {
+                if v.is_nan() {
+                    self.out.push_str("nan");
+                }
+                else if v.is_infinite() {
+                    self.out.push_str(if *v > 0.0 {"inf"} else {"-inf"});
+                }
+                else {
+                    let num = format!("{:?}", v);
+                    self.out.push_str(&num);
+                }
+            }
+            Toml::Date(v) => self.out.push_str(&v.to_string()),
+            Toml::Array(items) => {
+                self.out.push('[');
+                for (index, item) in items.iter().enumerate() {
+                    if index != 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.value(item);
+                }
+                self.out.push(']');
+            }
+            Toml::Table(table) => {
+                self.out.push('{');
+                for (index, (key, item)) in table.iter().enumerate() {
+                    self.out.push_str(if index != 0 {", "} else {" "});
+                    self.key(key);
+                    self.out.push_str(" = ");
+                    self.value(item);
+                }
+                self.out.push_str(if table.is_empty() {"}"} else {" }"});
+            }
+        }
+    }
+
+    fn is_table_array(val: &Toml) -> bool {
+        if let Toml::Array(items) = val {
+            !items.is_empty() && items.iter().all( | item | matches!(item, Toml::Table(_)))
+        }
+        else {
+            false
+        }
+    }
+
+    fn header(&mut self, path: &[&str], array: bool) {
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.out.push_str(if array {"[["} else {"["});
+        for (index, key) in path.iter().enumerate() {
+            if index != 0 {
+                self.out.push('.');
+            }
+            self.key(key);
+        }
+        self.out.push_str(if array {"]]\n"} else {"]\n"});
+    }
+
+    // writes the key/values of a table followed by its sub tables with [headers]
+    pub fn table<'a>(&mut self, path: &mut Vec<&'a str>, table: &'a BTreeMap<String, Toml>) {
+        for (key, val) in table {
+            if let Toml::Table(_) = val {
+                continue
+            }
+            if Self::is_table_array(val) {
+                continue
+            }
+            self.key(key);
+            self.out.push_str(" = ");
+            self.value(val);
+            self.out.push('\n');
+        }
+        for (key, val) in table {
+            path.push(key);
+            match val {
+                Toml::Table(sub) => {
+                    self.header(path, false);
+                    self.table(path, sub);
+                }
+                Toml::Array(items) if Self::is_table_array(val) => for item in items {
+                    if let Toml::Table(sub) = item {
+                        self.header(path, true);
+                        self.table(path, sub);
+                    }
+                }
+                _ => ()
+            }
+            path.pop();
+        }
+    }
+}
+
+pub trait SerToml {
+
+    fn serialize_toml(&self) -> String {
+        let mut s = SerTomlState::default();
+        match self.ser_toml() {
+            Toml::Table(table) => s.table(&mut Vec::new(), &table),
+            val => s.value(&val)
+        }
+        s.out
+    }
+
+    fn ser_toml(&self) -> Toml;
+
+    // the value of a table entry, None leaves the key out
+    fn ser_toml_entry(&self) -> Option<Toml> {
+        Some(self.ser_toml())
+    }
+}
+
+pub trait DeToml: Sized {
+
+    fn deserialize_toml(input: &str) -> Result<Self, DeTomlErr> {
+        match TomlParser::parse(input) {
+            Ok(toml) => DeToml::de_toml(&toml),
+            Err(e) => Err(DeTomlErr {msg: format!("{:?}", e), path: DePath::default()})
+        }
+    }
+
+    fn de_toml(t: &Toml) -> Result<Self, DeTomlErr>;
+
+    // the value of a table entry, the counterpart of SerToml::ser_toml_entry
+    fn de_toml_entry(t: &Toml) -> Result<Self, DeTomlErr> {
+        DeToml::de_toml(t)
+    }
+
+    // reads the keys it knows from t and adds them to used, this is how #[nserde(flatten)] shares a table
+    fn de_toml_flat(t: &Toml, used: &mut Vec<String>) -> Result<Self, DeTomlErr> {
+        let _ = used;
+        DeToml::de_toml(t)
+    }
+}
+
+pub struct DeTomlErr {
+    pub msg: String,
+    pub path: DePath,
+}
+
+impl std::fmt::Debug for DeTomlErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Toml Deserialize error: {}", self.msg) ?;
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path) ?;
+        }
+        Ok(())
+    }
+}
+
+impl DeErrAt for DeTomlErr {
+    fn path_mut(&mut self) -> &mut DePath {&mut self.path}
+}
+
+impl DeTomlErr {
+    pub fn exp(what: &str, got: &Toml) -> DeTomlErr {
+        DeTomlErr {msg: format!("Expected {} but got {}", what, got.type_name()), path: DePath::default()}
+    }
+
+    pub fn nf(key: &str) -> DeTomlErr {
+        DeTomlErr {msg: format!("Key not found {}", key), path: DePath::default()}
+    }
+
+    pub fn unknown(key: &str) -> DeTomlErr {
+        DeTomlErr {msg: format!("Unknown key {}", key), path: DePath::default()}
+    }
+
+    pub fn unknown_variant(label: &str) -> DeTomlErr {
+        DeTomlErr {msg: format!("Unknown variant {}", label), path: DePath::default()}
+    }
+
+    pub fn range(val: i64, ty: &str) -> DeTomlErr {
+        DeTomlErr {msg: format!("Value {} out of range for {}", val, ty), path: DePath::default()}
+    }
+}
+
+impl Toml {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Toml::Str(_) => "string",
+            Toml::Bool(_) => "boolean",
+            Toml::Int(_) => "integer",
+            Toml::Num(_) => "float",
+            Toml::Date(_) => "datetime",
+            Toml::Array(_) => "array",
+            Toml::Table(_) => "table",
+        }
+    }
+
+    pub fn de_table(&self) -> Result<&BTreeMap<String, Toml>, DeTomlErr> {
+        if let Toml::Table(t) = self {Ok(t)} else {Err(DeTomlErr::exp("table", self))}
+    }
+
+    pub fn de_array(&self) -> Result<&Vec<Toml>, DeTomlErr> {
+        if let Toml::Array(t) = self {Ok(t)} else {Err(DeTomlErr::exp("array", self))}
+    }
+
+    // an array of exactly len items, used for tuples
+    pub fn de_tuple(&self, len: usize) -> Result<&Vec<Toml>, DeTomlErr> {
+        let items = self.de_array() ?;
+        if items.len() != len {
+            return Err(DeTomlErr {msg: format!("Expected array of {} items but got {}", len, items.len()), path: DePath::default()})
+        }
+        Ok(items)
+    }
+
+    // enums are stored as "Variant" or as a table with a single Variant key
+    pub fn de_variant(&self) -> Result<(&str, Option<&Toml>), DeTomlErr> {
+        match self {
+            Toml::Str(v) => Ok((v, None)),
+            Toml::Table(t) if t.len() == 1 => {
+                let (k, v) = t.iter().next().unwrap();
+                Ok((k, Some(v)))
+            }
+            _ => Err(DeTomlErr::exp("enum variant", self))
+        }
+    }
+
+    pub fn de_variant_data<'a>(data: Option<&'a Toml>, label: &str) -> Result<&'a Toml, DeTomlErr> {
+        data.ok_or_else( || DeTomlErr {msg: format!("Variant {} needs a value", label), path: DePath::default()})
+    }
+}
+
+// TOML integers are 64 bit, wider values are written as a string of digits
+macro_rules!impl_ser_de_toml_int {
+    ( $ ty: ident) => {
+        impl SerToml for $ ty {
+            #[allow(clippy::unnecessary_fallible_conversions)]
+            fn ser_toml(&self) -> Toml {
+                match i64::try_from(*self) {
+                    Ok(v) => Toml::Int(v),
+                    Err(_) => Toml::Str(self.to_string())
+                }
+            }
+        }
+
+        impl DeToml for $ ty {
+            #[allow(clippy::unnecessary_fallible_conversions)]
+            fn de_toml(t: &Toml) -> Result<$ ty, DeTomlErr> {
+                match t {
+                    Toml::Int(v) => $ ty::try_from(*v).map_err( | _ | DeTomlErr::range(*v, stringify!($ ty))),
+                    Toml::Str(v) => v.parse().map_err( | _ | DeTomlErr::exp("integer", t)),
+                    _ => Err(DeTomlErr::exp("integer", t))
+                }
+            }
+        }
+    }
+}
+
+macro_rules!impl_ser_de_toml_float {
+    ( $ ty: ident) => {
+        impl SerToml for $ ty {
+            fn ser_toml(&self) -> Toml {
+                Toml::Num(*self as f64)
+            }
+        }
+
+        impl DeToml for $ ty {
+            fn de_toml(t: &Toml) -> Result<$ ty, DeTomlErr> {
+                match t.as_f64() {
+                    Some(v) => Ok(v as $ ty),
+                    None => Err(DeTomlErr::exp("float", t))
+                }
+            }
+        }
+    }
+}
+
+impl_ser_de_toml_int!(usize);
+impl_ser_de_toml_int!(u64);
+impl_ser_de_toml_int!(u32);
+impl_ser_de_toml_int!(u16);
+impl_ser_de_toml_int!(u8);
+impl_ser_de_toml_int!(i64);
+impl_ser_de_toml_int!(i32);
+impl_ser_de_toml_int!(i16);
+impl_ser_de_toml_int!(i8);
+impl_ser_de_toml_int!(u128);
+impl_ser_de_toml_int!(i128);
+impl_ser_de_toml_float!(f64);
+impl_ser_de_toml_float!(f32);
+
+// TOML has no null, derived structs and maps leave out None values and read missing keys as None.
+// anywhere else, like in an array, an Option is an array of zero or one items: [] or [value]
+impl<T> SerToml for Option<T> where T: SerToml {
+    fn ser_toml(&self) -> Toml {
+        Toml::Array(self.iter().map( | v | v.ser_toml()).collect())
+    }
+
+    fn ser_toml_entry(&self) -> Option<Toml> {
+        self.as_ref().map( | v | v.ser_toml())
+    }
+}
+
+impl<T> DeToml for Option<T> where T: DeToml {
+    fn de_toml(t: &Toml) -> Result<Self, DeTomlErr> {
+        match t {
+            Toml::Array(items) if items.is_empty() => Ok(None),
+            Toml::Array(items) if items.len() == 1 => Ok(Some(DeToml::de_toml(&items[0]).map_err( | e | e.at_index(0)) ?)),
+            _ => Err(DeTomlErr::exp("array of zero or one items", t))
+        }
+    }
+
+    fn de_toml_entry(t: &Toml) -> Result<Self, DeTomlErr> {
+        Ok(Some(DeToml::de_toml(t) ?))
+    }
+}
+
+// tuples are arrays, so the empty tuple is an empty array
+impl SerToml for () {
+    fn ser_toml(&self) -> Toml {
+        Toml::Array(Vec::new())
+    }
+}
+
+impl DeToml for () {
+    fn de_toml(t: &Toml) -> Result<(), DeTomlErr> {
+        t.de_tuple(0) ?;
+        Ok(())
+    }
+}
+
+impl SerToml for bool {
+    fn ser_toml(&self) -> Toml {
+        Toml::Bool(*self)
+    }
+}
+
+impl DeToml for bool {
+    fn de_toml(t: &Toml) -> Result<bool, DeTomlErr> {
+        t.as_bool().ok_or_else( || DeTomlErr::exp("boolean", t))
+    }
+}
+
+impl SerToml for str {
+    fn ser_toml(&self) -> Toml {
+        Toml::Str(self.to_string())
+    }
+}
+
+impl SerToml for String {
+    fn ser_toml(&self) -> Toml {
+        Toml::Str(self.clone())
+    }
+}
+
+impl DeToml for String {
+    fn de_toml(t: &Toml) -> Result<String, DeTomlErr> {
+        match t {
+            Toml::Str(v) => Ok(v.clone()),
+            _ => Err(DeTomlErr::exp("string", t))
+        }
+    }
+}
+
+impl SerToml for char {
+    fn ser_toml(&self) -> Toml {
+        Toml::Str(self.to_string())
+    }
+}
+
+impl DeToml for char {
+    fn de_toml(t: &Toml) -> Result<char, DeTomlErr> {
+        let mut chars = t.as_str().unwrap_or("").chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(DeTomlErr::exp("char", t))
+        }
+    }
+}
+
+impl SerToml for Toml {
+    fn ser_toml(&self) -> Toml {
+        self.clone()
+    }
+}
+
+// every top level key on one line with its value inline, instead of [header] sections
+impl Toml {
+    pub fn serialize_toml_compact(&self) -> String {
+        let mut s = SerTomlState::default();
+        match self {
+            Toml::Table(table) => for (key, val) in table {
+                s.key(key);
+                s.out.push_str(" = ");
+                s.value(val);
+                s.out.push('\n');
+            }
+            val => s.value(val)
+        }
+        s.out
+    }
+}
+
+impl DeToml for Toml {
+    fn de_toml(t: &Toml) -> Result<Toml, DeTomlErr> {
+        Ok(t.clone())
+    }
+
+    // a flattened Toml collects all the keys nobody else used
+    fn de_toml_flat(t: &Toml, used: &mut Vec<String>) -> Result<Toml, DeTomlErr> {
+        let mut rest = BTreeMap::new();
+        for (k, v) in t.de_table() ? {
+            if !used.contains(k) {
+                rest.insert(k.clone(), v.clone());
+            }
+        }
+        used.extend(rest.keys().cloned());
+        Ok(Toml::Table(rest))
+    }
+}
+
+impl<T> SerToml for Vec<T> where T: SerToml {
+    fn ser_toml(&self) -> Toml {
+        Toml::Array(self.iter().map( | v | v.ser_toml()).collect())
+    }
+}
+
+fn de_toml_seq<T: DeToml, C: std::iter::FromIterator<T>>(items: &[Toml]) -> Result<C, DeTomlErr> {
+    items.iter().enumerate().map( | (index, v) | DeToml::de_toml(v).map_err( | e | e.at_index(index))).collect()
+}
+
+impl<T> DeToml for Vec<T> where T: DeToml {
+    fn de_toml(t: &Toml) -> Result<Vec<T>, DeTomlErr> {
+        de_toml_seq(t.de_array() ?)
+    }
+}
+
+impl<T> SerToml for [T] where T: SerToml {
+    fn ser_toml(&self) -> Toml {
+        Toml::Array(self.iter().map( | v | v.ser_toml()).collect())
+    }
+}
+
+macro_rules!de_toml_array_impl {
+    ( $ ( $ count: expr), *) => {
+        $ (
+            impl<T> DeToml for [T; $ count] where T: DeToml {
+                fn de_toml(t: &Toml) -> Result<Self, DeTomlErr> {
+                    let items: Vec<T> = de_toml_seq(t.de_tuple( $ count) ?) ?;
+                    match items.try_into() {
+                        Ok(v) => Ok(v),
+                        Err(_) => unreachable!()
+                    }
+                }
+            }
+        ) *
+    }
+}
+
+de_toml_array_impl!(2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32);
+
+impl<T> SerToml for VecDeque<T> where T: SerToml {
+    fn ser_toml(&self) -> Toml {
+        Toml::Array(self.iter().map( | v | v.ser_toml()).collect())
+    }
+}
+
+impl<T> DeToml for VecDeque<T> where T: DeToml {
+    fn de_toml(t: &Toml) -> Result<Self, DeTomlErr> {
+        de_toml_seq(t.de_array() ?)
+    }
+}
+
+impl<T> SerToml for HashSet<T> where T: SerToml {
+    fn ser_toml(&self) -> Toml {
+        Toml::Array(self.iter().map( | v | v.ser_toml()).collect())
+    }
+}
+
+impl<T> DeToml for HashSet<T> where T: DeToml + Eq + Hash {
+    fn de_toml(t: &Toml) -> Result<Self, DeTomlErr> {
+        de_toml_seq(t.de_array() ?)
+    }
+}
+
+impl<T> SerToml for BTreeSet<T> where T: SerToml {
+    fn ser_toml(&self) -> Toml {
+        Toml::Array(self.iter().map( | v | v.ser_toml()).collect())
+    }
+}
+
+impl<T> DeToml for BTreeSet<T> where T: DeToml + Ord {
+    fn de_toml(t: &Toml) -> Result<Self, DeTomlErr> {
+        de_toml_seq(t.de_array() ?)
+    }
+}
+
+macro_rules!impl_ser_de_toml_tuple {
+    ( $ len: expr; $ ( $ ty: ident $ index: tt), *) => {
+        impl< $ ( $ ty), *> SerToml for ( $ ( $ ty), *) where $ ( $ ty: SerToml), * {
+            fn ser_toml(&self) -> Toml {
+                Toml::Array(vec![ $ (self. $ index.ser_toml()), *])
+            }
+        }
+
+        impl< $ ( $ ty), *> DeToml for ( $ ( $ ty), *) where $ ( $ ty: DeToml), * {
+            fn de_toml(t: &Toml) -> Result<Self, DeTomlErr> {
+                let items = t.de_tuple( $ len) ?;
+                Ok(( $ ( $ ty::de_toml(&items[ $ index]).map_err( | e | e.at_index( $ index)) ?), *))
+            }
+        }
+    }
+}
+
+impl_ser_de_toml_tuple!(2; A 0, B 1);
+impl_ser_de_toml_tuple!(3; A 0, B 1, C 2);
+impl_ser_de_toml_tuple!(4; A 0, B 1, C 2, D 3);
+impl_ser_de_toml_tuple!(5; A 0, B 1, C 2, D 3, E 4);
+impl_ser_de_toml_tuple!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+impl_ser_de_toml_tuple!(7; A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_ser_de_toml_tuple!(8; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_ser_de_toml_tuple!(9; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_ser_de_toml_tuple!(10; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_ser_de_toml_tuple!(11; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_ser_de_toml_tuple!(12; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+// TOML keys are always strings, other key types are written as their value would be
+fn ser_toml_key<K: SerToml>(k: &K) -> String {
+    match k.ser_toml() {
+        Toml::Str(key) => key,
+        val => {
+            let mut s = SerTomlState::default();
+            s.value(&val);
+            s.out
+        }
+    }
+}
+
+fn de_toml_key<K: DeToml>(key: &str) -> Result<K, DeTomlErr> {
+    K::de_toml(&Toml::Str(key.to_string())).or_else( | e | {
+        match TomlParser::parse(&format!("k = {}", key)) {
+            Ok(Toml::Table(t)) if t.len() == 1 => K::de_toml(&t["k"]).map_err( | _ | e),
+            _ => Err(e)
+        }
+    })
+}
+
+fn ser_toml_map<'a, K: SerToml + 'a, V: SerToml + 'a>(iter: impl Iterator<Item = (&'a K, &'a V)>) -> Toml {
+    Toml::Table(iter.filter_map( | (k, v) | Some((ser_toml_key(k), v.ser_toml_entry() ?))).collect())
+}
+
+fn de_toml_map<K: DeToml, V: DeToml, C: std::iter::FromIterator<(K, V)>>(t: &Toml) -> Result<C, DeTomlErr> {
+    t.de_table() ?.iter().map( | (k, v) | Ok((
+        de_toml_key(k).map_err( | e | e.at_key(k)) ?,
+        DeToml::de_toml_entry(v).map_err( | e | e.at_key(k)) ?
+    ))).collect()
+}
+
+impl<K, V> SerToml for HashMap<K, V> where K: SerToml, V: SerToml {
+    fn ser_toml(&self) -> Toml {
+        ser_toml_map(self.iter())
+    }
+}
+
+impl<K, V> DeToml for HashMap<K, V> where K: DeToml + Eq + Hash, V: DeToml {
+    fn de_toml(t: &Toml) -> Result<Self, DeTomlErr> {
+        de_toml_map(t)
+    }
+}
+
+impl<K, V> SerToml for BTreeMap<K, V> where K: SerToml, V: SerToml {
+    fn ser_toml(&self) -> Toml {
+        ser_toml_map(self.iter())
+    }
+}
+
+impl<K, V> DeToml for BTreeMap<K, V> where K: DeToml + Ord, V: DeToml {
+    fn de_toml(t: &Toml) -> Result<Self, DeTomlErr> {
+        de_toml_map(t)
+    }
+}
+
+macro_rules!impl_ser_de_toml_pointer {
+    ( $ ty: ident) => {
+        impl<T> SerToml for $ ty<T> where T: SerToml {
+            fn ser_toml(&self) -> Toml {
+                (**self).ser_toml()
+            }
+        }
+
+        impl<T> DeToml for $ ty<T> where T: DeToml {
+            fn de_toml(t: &Toml) -> Result<$ ty<T>, DeTomlErr> {
+                Ok($ ty::new(DeToml::de_toml(t) ?))
+            }
+        }
+    }
+}
+
+impl_ser_de_toml_pointer!(Box);
+impl_ser_de_toml_pointer!(Rc);
+impl_ser_de_toml_pointer!(Arc);
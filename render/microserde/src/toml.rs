@@ -1,338 +1,853 @@
-use std::collections::{HashMap};
+use std::collections::{HashMap, BTreeMap};
 use std::str::Chars;
+use crate::value::*;
+use crate::de_path::*;
+
+// a TOML 1.0 parser that produces a tree of tables
 
 #[derive(Default)]
 pub struct TomlParser {
     pub cur: char,
     pub line: usize,
-    pub col: usize
+    pub col: usize,
+    // how each table was created, keyed by its canonical path
+    kinds: HashMap<String, TomlTableKind>
 }
 
-#[derive(PartialEq, Debug)]
-pub enum TomlTok {
-    Ident(String),
-    Str(String),
-    U64(u64),
-    I64(i64),
-    F64(f64),
-    Bool(bool),
-    Nan(bool),
-    Inf(bool),
-    Date(String),
-    Equals,
-    BlockOpen,
-    BlockClose,
-    Comma,
-    Bof,
-    Eof
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum TomlTableKind {
+    Implicit,
+    Header,
+    Dotted,
+    TableArray
 }
 
-pub enum Toml{
+#[derive(Clone, Debug, PartialEq)]
+pub enum Toml {
     Str(String),
     Bool(bool),
+    Int(i64),
     Num(f64),
-    Date(String),
+    Date(TomlDateTime),
     Array(Vec<Toml>),
+    Table(BTreeMap<String, Toml>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TomlDate {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TomlTime {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32
 }
 
-pub struct TomlErr{
-    pub msg:String,
-    pub line:usize,
-    pub col:usize
+// covers offset date-times, local date-times, local dates and local times
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TomlDateTime {
+    pub date: Option<TomlDate>,
+    pub time: Option<TomlTime>,
+    // minutes east of UTC, Z is Some(0)
+    pub offset: Option<i16>
+}
+
+pub struct TomlErr {
+    pub msg: String,
+    pub line: usize,
+    pub col: usize
 }
 
 impl std::fmt::Debug for TomlErr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Toml error: {}, line:{} col:{}", self.msg, self.line+1, self.col+1)
+        write!(f, "Toml error: {}, line:{} col:{}", self.msg, self.line + 1, self.col + 1)
     }
 }
 
-impl TomlParser {
-    pub fn to_val(&mut self, tok:TomlTok, i: &mut Chars)->Result<Toml, TomlErr>{
-        match tok{
-            TomlTok::BlockOpen=>{
-                let mut vals = Vec::new();
-                loop{
-                    let tok = self.next_tok(i)?;
-                    if tok == TomlTok::BlockClose || tok == TomlTok::Eof{
-                        break
+impl TomlErr {
+    // the message with the offending line of the source and a caret under it
+    pub fn render(&self, name: &str, source: &str) -> String {
+        render_source_error(name, source, self.line, self.col, &self.msg, &DePath::default())
+    }
+}
+
+impl std::fmt::Display for TomlDateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(d) = &self.date {
+            write!(f, "{:04}-{:02}-{:02}", d.year, d.month, d.day) ?;
+            if self.time.is_some() {
+                write!(f, "T") ?;
+            }
+        }
+        if let Some(t) = &self.time {
+            write!(f, "{:02}:{:02}:{:02}", t.hour, t.minute, t.second) ?;
+            if t.nanosecond != 0 {
+                let frac = format!("{:09}", t.nanosecond);
+                write!(f, ".{}", frac.trim_end_matches('0')) ?;
+            }
+        }
+        match self.offset {
+            Some(0) => write!(f, "Z"),
+            Some(o) => write!(f, "{}{:02}:{:02}", if o < 0 {'-'} else {'+'}, o.abs() / 60, o.abs() % 60),
+            None => Ok(())
+        }
+    }
+}
+
+impl Toml {
+    pub fn as_str(&self) -> Option<&str> {
+        if let Toml::Str(v) = self {Some(v)} else {None}
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        if let Toml::Bool(v) = self {Some(*v)} else {None}
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        if let Toml::Int(v) = self {Some(*v)} else {None}
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Toml::Num(v) => Some(*v),
+            Toml::Int(v) => Some(*v as f64),
+            _ => None
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Toml >> {
+        if let Toml::Array(v) = self {Some(v)} else {None}
+    }
+
+    pub fn as_table(&self) -> Option<&BTreeMap<String, Toml >> {
+        if let Toml::Table(v) = self {Some(v)} else {None}
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Toml> {
+        if let Toml::Table(v) = self {v.get(key)} else {None}
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<&Toml> {
+        if let Toml::Array(v) = self {v.get(index)} else {None}
+    }
+
+    // looks up paths like package.name, bin[0].path or dependencies["makepad-render"].path
+    pub fn path(&self, path: &str) -> Option<&Toml> {
+        let mut cur = self;
+        for seg in parse_value_path(path) ? {
+            cur = match seg {
+                ValuePathSeg::Key(key) => cur.get(&key) ?,
+                ValuePathSeg::Index(index) => cur.get_index(index) ?,
+            };
+        }
+        Some(cur)
+    }
+}
+
+impl std::ops::Index<&str> for Toml {
+    type Output = Toml;
+    fn index(&self, key: &str) -> &Toml {
+        match self.get(key) {
+            Some(v) => v,
+            None => panic!("Toml has no key {}", key)
+        }
+    }
+}
+
+impl std::ops::Index<usize> for Toml {
+    type Output = Toml;
+    fn index(&self, index: usize) -> &Toml {
+        match self.get_index(index) {
+            Some(v) => v,
+            None => panic!("Toml has no index {}", index)
+        }
+    }
+}
+
+// a step from a table into one of its children
+enum TomlStep {
+    Key(String),
+    Index(usize)
+}
+
+fn toml_navigate<'a>(root: &'a mut BTreeMap<String, Toml>, steps: &[TomlStep]) -> &'a mut BTreeMap<String, Toml> {
+    let mut cur = root;
+    let mut it = steps.iter().peekable();
+    while let Some(step) = it.next() {
+        if let TomlStep::Key(key) = step {
+            let node = cur.get_mut(key).unwrap();
+            cur = match node {
+                Toml::Array(items) => {
+                    if let Some(TomlStep::Index(index)) = it.next() {
+                        if let Toml::Table(t) = &mut items[*index] {t} else {unreachable!()}
                     }
-                    if tok != TomlTok::Comma{
-                        vals.push(self.to_val(tok, i)?);
+                    else {
+                        unreachable!()
                     }
                 }
-                Ok(Toml::Array(vals))
-            },
-            TomlTok::Str(v)=>Ok(Toml::Str(v)),
-            TomlTok::U64(v)=>Ok(Toml::Num(v as f64)),
-            TomlTok::I64(v)=>Ok(Toml::Num(v as f64)),
-            TomlTok::F64(v)=>Ok(Toml::Num(v as f64)),
-            TomlTok::Bool(v)=>Ok(Toml::Bool(v)),
-            TomlTok::Nan(v)=>Ok(Toml::Num(if v{-std::f64::NAN}else{std::f64::NAN})),
-            TomlTok::Inf(v)=>Ok(Toml::Num(if v{-std::f64::INFINITY}else{std::f64::INFINITY})),
-            TomlTok::Date(v)=>Ok(Toml::Date(v)),
-            _=>Err(self.err_token(tok))
-        }
-    }
-    
-    pub fn parse_key_value(&mut self, local_scope:&String, key:String, i: &mut Chars, out:&mut HashMap<String, Toml>)->Result<(), TomlErr>{
-        let tok = self.next_tok(i)?;
-        if tok != TomlTok::Equals{
-            return Err(self.err_token(tok));
-        }
-        let tok = self.next_tok(i)?;
-        let val = self.to_val(tok, i)?;
-        let key = if local_scope.len()>0{
-            format!("{}.{}", local_scope, key)
-        }
-        else{
-            key
-        };
-        out.insert(key, val);
-        Ok(())
+                Toml::Table(t) => t,
+                _ => unreachable!()
+            }
+        }
+    }
+    cur
+}
+
+fn is_bare_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+fn is_value_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '+' || c == '.' || c == ':'
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 => if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) {29} else {28},
+        4 | 6 | 9 | 11 => 30,
+        _ => 31
     }
-    
-    pub fn parse(data:&str)->Result<HashMap<String, Toml>, TomlErr>{
+}
+
+impl TomlParser {
+    pub fn parse(data: &str) -> Result<Toml, TomlErr> {
         let i = &mut data.chars();
         let mut t = TomlParser::default();
         t.next(i);
-        let mut out = HashMap::new();
-        let mut local_scope = String::new();
-        loop{
-            let tok = t.next_tok(i)?;
-            match tok{
-                TomlTok::Eof=>{ // at eof. 
-                    return Ok(out);
-                },
-                TomlTok::BlockOpen=>{ // its a scope
-                    // we should expect an ident or a string
-                    let tok = t.next_tok(i)?;
-                    match tok{
-                        TomlTok::Str(key)=>{ // a key
-                            local_scope = key;
-                        },
-                        TomlTok::Ident(key)=>{ // also a key
-                            local_scope = key;
-                        },
-                        _=>return Err(t.err_token(tok))
-                    }
-                    let tok = t.next_tok(i)?;
-                    if tok != TomlTok::BlockClose{
-                        return Err(t.err_token(tok))
-                    }
-                },
-                TomlTok::Str(key)=>{ // a key
-                    t.parse_key_value(&local_scope, key, i, &mut out)?;
-                },
-                TomlTok::Ident(key)=>{ // also a key
-                    t.parse_key_value(&local_scope, key, i, &mut out)?;
-                },
-                _=>return Err(t.err_token(tok))
+        let mut root = BTreeMap::new();
+        let mut current = Vec::new();
+        let mut current_canon = String::new();
+        loop {
+            t.skip_ws(i);
+            match t.cur {
+                '\0' => return Ok(Toml::Table(root)),
+                '#' => t.skip_comment(i) ?,
+                '\n' => t.next(i),
+                '\r' => t.newline(i) ?,
+                '[' => {
+                    let (steps, canon) = t.parse_table_header(&mut root, i) ?;
+                    current = steps;
+                    current_canon = canon;
+                    t.end_of_line(i) ?;
+                }
+                _ => {
+                    let table = toml_navigate(&mut root, &current);
+                    t.parse_key_value(table, &current_canon, i) ?;
+                    t.end_of_line(i) ?;
+                }
             }
         }
     }
-    
+
     pub fn next(&mut self, i: &mut Chars) {
         if let Some(c) = i.next() {
-            self.cur = c;
             if self.cur == '\n' {
                 self.line += 1;
                 self.col = 0;
             }
-            else {
-                self.col = 0;
+            else if self.cur != '\0' {
+                self.col += 1;
             }
+            self.cur = c;
         }
         else {
             self.cur = '\0';
         }
     }
-    
-    pub fn err_token(&self, tok:TomlTok) -> TomlErr {
-        TomlErr{msg:format!("Unexpected token {:?} ", tok), line:self.line, col:self.col}
+
+    fn peek(&self, i: &Chars) -> char {
+        i.clone().next().unwrap_or('\0')
     }
-    
-    pub fn err_parse(&self, what:&str) -> TomlErr {
-        TomlErr{msg:format!("Cannot parse toml {} ", what), line:self.line, col:self.col}
+
+    fn peek2(&self, i: &Chars) -> char {
+        i.clone().nth(1).unwrap_or('\0')
     }
-    
-    pub fn next_tok(&mut self, i: &mut Chars) -> Result<TomlTok, TomlErr> {
-        while self.cur == '\n' || self.cur == '\r' || self.cur == '\t' || self.cur == ' ' {
+
+    pub fn err_parse(&self, what: &str) -> TomlErr {
+        TomlErr {msg: format!("Cannot parse toml {} ", what), line: self.line, col: self.col}
+    }
+
+    pub fn err_msg(&self, msg: String) -> TomlErr {
+        TomlErr {msg, line: self.line, col: self.col}
+    }
+
+    fn err_unexpected(&self, expected: &str) -> TomlErr {
+        let found = if self.cur == '\0' {"end of file".to_string()} else {format!("{:?}", self.cur)};
+        self.err_msg(format!("Unexpected {} expected {}", found, expected))
+    }
+
+    fn expect(&mut self, c: char, i: &mut Chars) -> Result<(), TomlErr> {
+        if self.cur != c {
+            return Err(self.err_unexpected(&format!("{:?}", c)))
+        }
+        self.next(i);
+        Ok(())
+    }
+
+    fn skip_ws(&mut self, i: &mut Chars) {
+        while self.cur == ' ' || self.cur == '\t' {
             self.next(i);
         }
-        loop{
-            if self.cur == '\0' {
-                return Ok(TomlTok::Eof)
+    }
+
+    fn skip_comment(&mut self, i: &mut Chars) -> Result<(), TomlErr> {
+        while self.cur != '\n' && self.cur != '\0' {
+            if self.cur == '\r' && self.peek(i) == '\n' {
+                break;
+            }
+            if self.cur < ' ' && self.cur != '\t' || self.cur == '\u{7f}' {
+                return Err(self.err_parse("control character in comment"))
+            }
+            self.next(i);
+        }
+        Ok(())
+    }
+
+    fn newline(&mut self, i: &mut Chars) -> Result<(), TomlErr> {
+        if self.cur == '\r' {
+            self.next(i);
+            if self.cur != '\n' {
+                return Err(self.err_parse("carriage return without newline"))
             }
+        }
+        self.next(i);
+        Ok(())
+    }
+
+    // whitespace, newlines and comments inside arrays
+    fn skip_ws_nl(&mut self, i: &mut Chars) -> Result<(), TomlErr> {
+        loop {
             match self.cur {
-                ',' => {
+                ' ' | '\t' | '\n' => self.next(i),
+                '\r' => self.newline(i) ?,
+                '#' => self.skip_comment(i) ?,
+                _ => return Ok(())
+            }
+        }
+    }
+
+    fn end_of_line(&mut self, i: &mut Chars) -> Result<(), TomlErr> {
+        self.skip_ws(i);
+        if self.cur == '#' {
+            self.skip_comment(i) ?;
+        }
+        match self.cur {
+            '\0' => Ok(()),
+            '\n' | '\r' => self.newline(i),
+            _ => Err(self.err_unexpected("end of line"))
+        }
+    }
+
+    fn parse_key(&mut self, i: &mut Chars) -> Result<Vec<String>, TomlErr> {
+        let mut keys = Vec::new();
+        loop {
+            self.skip_ws(i);
+            let key = match self.cur {
+                '"' => {
                     self.next(i);
-                    return Ok(TomlTok::Comma)
+                    self.parse_basic_string(i) ?
                 }
-                '[' => {
+                '\'' => {
                     self.next(i);
-                    return Ok(TomlTok::BlockOpen)
+                    self.parse_literal_string(i) ?
                 }
-                ']' => {
+                c if is_bare_key_char(c) => {
+                    let mut key = String::new();
+                    while is_bare_key_char(self.cur) {
+                        key.push(self.cur);
+                        self.next(i);
+                    }
+                    key
+                }
+                _ => return Err(self.err_unexpected("key"))
+            };
+            keys.push(key);
+            self.skip_ws(i);
+            if self.cur != '.' {
+                return Ok(keys)
+            }
+            self.next(i);
+        }
+    }
+
+    fn parse_table_header(&mut self, root: &mut BTreeMap<String, Toml>, i: &mut Chars) -> Result<(Vec<TomlStep>, String), TomlErr> {
+        let (line, col) = (self.line, self.col);
+        let err_at = | msg: String | TomlErr {msg, line, col};
+        self.expect('[', i) ?;
+        let is_array = self.cur == '[';
+        if is_array {
+            self.next(i);
+        }
+        let keys = self.parse_key(i) ?;
+        self.expect(']', i) ?;
+        if is_array {
+            self.expect(']', i) ?;
+        }
+
+        let mut steps = Vec::new();
+        let mut canon = String::new();
+        let mut table = root;
+        for (index, key) in keys.iter().enumerate() {
+            canon.push_str(&format!(".{:?}", key));
+            let is_last = index == keys.len() - 1;
+            let kind = self.kinds.get(&canon).cloned();
+            steps.push(TomlStep::Key(key.clone()));
+            if !table.contains_key(key) {
+                if is_last && is_array {
+                    table.insert(key.clone(), Toml::Array(vec![Toml::Table(BTreeMap::new())]));
+                    self.kinds.insert(canon.clone(), TomlTableKind::TableArray);
+                }
+                else {
+                    table.insert(key.clone(), Toml::Table(BTreeMap::new()));
+                    self.kinds.insert(canon.clone(), if is_last {TomlTableKind::Header} else {TomlTableKind::Implicit});
+                }
+            }
+            else if is_last && !is_array {
+                if kind == Some(TomlTableKind::Implicit) {
+                    self.kinds.insert(canon.clone(), TomlTableKind::Header);
+                }
+                else {
+                    return Err(err_at(format!("Table [{}] defined twice", keys.join("."))))
+                }
+            }
+            else if is_last && is_array {
+                if kind != Some(TomlTableKind::TableArray) {
+                    return Err(err_at(format!("Cannot append to [[{}]], it is not an array of tables", keys.join("."))))
+                }
+                if let Some(Toml::Array(items)) = table.get_mut(key) {
+                    items.push(Toml::Table(BTreeMap::new()));
+                }
+            }
+            else if kind.is_none() {
+                return Err(err_at(format!("Cannot extend {} in table header, it is a value", key)))
+            }
+
+            let node = table.get_mut(key).unwrap();
+            table = match node {
+                Toml::Table(t) => t,
+                Toml::Array(items) => {
+                    let last = items.len() - 1;
+                    steps.push(TomlStep::Index(last));
+                    canon.push_str(&format!("[{}]", last));
+                    if is_last {
+                        self.kinds.insert(canon.clone(), TomlTableKind::Header);
+                    }
+                    if let Toml::Table(t) = &mut items[last] {t} else {unreachable!()}
+                }
+                _ => unreachable!()
+            };
+        }
+        Ok((steps, canon))
+    }
+
+    fn parse_key_value(&mut self, table: &mut BTreeMap<String, Toml>, canon: &str, i: &mut Chars) -> Result<(), TomlErr> {
+        let keys = self.parse_key(i) ?;
+        self.skip_ws(i);
+        self.expect('=', i) ?;
+        self.skip_ws(i);
+        let val = self.parse_value(i) ?;
+
+        let mut canon = canon.to_string();
+        let mut table = table;
+        for key in &keys[0..keys.len() - 1] {
+            canon.push_str(&format!(".{:?}", key));
+            if !table.contains_key(key) {
+                table.insert(key.clone(), Toml::Table(BTreeMap::new()));
+                self.kinds.insert(canon.clone(), TomlTableKind::Dotted);
+            }
+            else if self.kinds.get(&canon) != Some(&TomlTableKind::Dotted) {
+                return Err(self.err_msg(format!("Cannot extend {} with a dotted key", key)))
+            }
+            table = if let Some(Toml::Table(t)) = table.get_mut(key) {t} else {unreachable!()};
+        }
+        let key = keys.last().unwrap();
+        if table.contains_key(key) {
+            return Err(self.err_msg(format!("Key {} defined twice", keys.join("."))))
+        }
+        table.insert(key.clone(), val);
+        Ok(())
+    }
+
+    pub fn parse_value(&mut self, i: &mut Chars) -> Result<Toml, TomlErr> {
+        match self.cur {
+            '"' => {
+                self.next(i);
+                if self.cur == '"' && self.peek(i) == '"' {
+                    self.next(i);
                     self.next(i);
-                    return Ok(TomlTok::BlockClose)
+                    return Ok(Toml::Str(self.parse_multiline_string(true, i) ?))
                 }
-                '=' => {
+                Ok(Toml::Str(self.parse_basic_string(i) ?))
+            }
+            '\'' => {
+                self.next(i);
+                if self.cur == '\'' && self.peek(i) == '\'' {
+                    self.next(i);
                     self.next(i);
-                    return Ok(TomlTok::Equals)
+                    return Ok(Toml::Str(self.parse_multiline_string(false, i) ?))
                 }
-                '+' | '-' | '0'..='9' => {
-                    let mut num = String::new();
-                    let is_neg = if self.cur == '-' {
-                        num.push(self.cur);
-                        self.next(i);
-                        true
+                Ok(Toml::Str(self.parse_literal_string(i) ?))
+            }
+            '[' => {
+                self.next(i);
+                let mut items = Vec::new();
+                loop {
+                    self.skip_ws_nl(i) ?;
+                    if self.cur == ']' {
+                        break;
                     }
-                    else {
-                        if self.cur == '+' {
-                            self.next(i);
-                        }
-                        false
-                    };
-                    if self.cur == 'n' {
+                    items.push(self.parse_value(i) ?);
+                    self.skip_ws_nl(i) ?;
+                    if self.cur == ',' {
                         self.next(i);
-                        if self.cur == 'a' {
-                            self.next(i);
-                            if self.cur == 'n' {
-                                self.next(i);
-                                return Ok(TomlTok::Nan(is_neg))
-                            }
-                            else {
-                                return Err(self.err_parse("nan"))
-                            }
-                        }
-                        else {
-                            return Err(self.err_parse("nan"))
-                        }
                     }
-                     if self.cur == 'i' {
-                        self.next(i);
-                        if self.cur == 'n' {
-                            self.next(i);
-                            if self.cur == 'f' {
-                                self.next(i);
-                                return Ok(TomlTok::Inf(is_neg))
-                            }
-                            else {
-                                return Err(self.err_parse("inf"))
-                            }
-                        }
-                        else {
-                            return Err(self.err_parse("nan"))
-                        }
+                    else if self.cur != ']' {
+                        return Err(self.err_unexpected(", or ]"))
                     }
-                    while self.cur >= '0' && self.cur <= '9' || self.cur == '_' {
-                        if self.cur != '_' {
-                            num.push(self.cur);
+                }
+                self.next(i);
+                Ok(Toml::Array(items))
+            }
+            '{' => {
+                self.next(i);
+                // inline tables get their own dotted key bookkeeping
+                let kinds = std::mem::take(&mut self.kinds);
+                let mut table = BTreeMap::new();
+                self.skip_ws(i);
+                if self.cur != '}' {
+                    loop {
+                        self.parse_key_value(&mut table, "", i) ?;
+                        self.skip_ws(i);
+                        if self.cur == '}' {
+                            break;
                         }
-                        self.next(i);
+                        self.expect(',', i) ?;
                     }
-                    if self.cur == '.' {
-                        num.push(self.cur);
+                }
+                self.next(i);
+                self.kinds = kinds;
+                Ok(Toml::Table(table))
+            }
+            c if is_value_char(c) => {
+                let mut word = String::new();
+                while is_value_char(self.cur) {
+                    word.push(self.cur);
+                    self.next(i);
+                }
+                // a date followed by a space and a time is one value
+                if word.len() == 10 && word.as_bytes()[4] == b'-' && self.cur == ' ' && self.peek(i).is_ascii_digit() && self.peek2(i).is_ascii_digit() {
+                    word.push('T');
+                    self.next(i);
+                    while is_value_char(self.cur) {
+                        word.push(self.cur);
                         self.next(i);
-                        while self.cur >= '0' && self.cur <= '9' || self.cur == '_' {
-                            if self.cur != '_' {
-                                num.push(self.cur);
-                            }
-                            self.next(i);
-                        }
-                        if let Ok(num) = num.parse() {
-                            return Ok(TomlTok::F64(num))
-                        }
-                        else {
-                            return Err(self.err_parse("number"));
-                        }
                     }
-                    else if self.cur == '-' { // lets assume its a date. whatever. i don't feel like more parsing today
-                        num.push(self.cur);
-                        self.next(i);
-                        while self.cur >= '0' && self.cur <= '9' || self.cur == ':' || self.cur == '-' || self.cur == 'T' {
-                            num.push(self.cur);
-                            self.next(i);
-                        }
-                        return Ok(TomlTok::Date(num))
+                }
+                self.parse_word(&word)
+            }
+            _ => Err(self.err_unexpected("value"))
+        }
+    }
+
+    fn parse_word(&self, word: &str) -> Result<Toml, TomlErr> {
+        match word {
+            "true" => return Ok(Toml::Bool(true)),
+            "false" => return Ok(Toml::Bool(false)),
+            "inf" | "+inf" => return Ok(Toml::Num(f64::INFINITY)),
+            "-inf" => return Ok(Toml::Num(f64::NEG_INFINITY)),
+            "nan" | "+nan" => return Ok(Toml::Num(f64::NAN)),
+            "-nan" => return Ok(Toml::Num(-f64::NAN)),
+            _ => ()
+        }
+        let b = word.as_bytes();
+        if b.len() >= 10 && b[4] == b'-' || b.len() >= 8 && b[2] == b':' {
+            return self.parse_datetime(word)
+        }
+        self.parse_number(word)
+    }
+
+    fn check_underscores(&self, digits: &str, radix: u32) -> Result<String, TomlErr> {
+        let mut out = String::new();
+        let mut last_digit = false;
+        for c in digits.chars() {
+            if c == '_' {
+                if !last_digit {
+                    return Err(self.err_parse(&format!("number {}", digits)))
+                }
+                last_digit = false;
+            }
+            else if c.is_digit(radix) {
+                out.push(c);
+                last_digit = true;
+            }
+            else {
+                return Err(self.err_parse(&format!("number {}", digits)))
+            }
+        }
+        if !last_digit {
+            return Err(self.err_parse(&format!("number {}", digits)))
+        }
+        Ok(out)
+    }
+
+    fn parse_number(&self, word: &str) -> Result<Toml, TomlErr> {
+        for (prefix, radix) in &[("0x", 16), ("0o", 8), ("0b", 2)] {
+            if word.starts_with(prefix) {
+                let digits = self.check_underscores(&word[2..], *radix) ?;
+                return match i64::from_str_radix(&digits, *radix) {
+                    Ok(v) => Ok(Toml::Int(v)),
+                    Err(_) => Err(self.err_parse(&format!("integer {} out of range", word)))
+                }
+            }
+        }
+        let (sign, body) = match word.as_bytes().first() {
+            Some(b'+') => ("", &word[1..]),
+            Some(b'-') => ("-", &word[1..]),
+            _ => ("", word)
+        };
+        let (mantissa, exp) = match body.find(['e', 'E']) {
+            Some(pos) => (&body[..pos], Some(&body[pos + 1..])),
+            None => (body, None)
+        };
+        let (int_part, frac) = match mantissa.find('.') {
+            Some(pos) => (&mantissa[..pos], Some(&mantissa[pos + 1..])),
+            None => (mantissa, None)
+        };
+        let int_digits = self.check_underscores(int_part, 10) ?;
+        if int_digits.len() > 1 && int_digits.starts_with('0') {
+            return Err(self.err_parse(&format!("number {}, leading zeros are not allowed", word)))
+        }
+        if frac.is_none() && exp.is_none() {
+            return match format!("{}{}", sign, int_digits).parse() {
+                Ok(v) => Ok(Toml::Int(v)),
+                Err(_) => Err(self.err_parse(&format!("integer {} out of range", word)))
+            }
+        }
+        let mut num = format!("{}{}", sign, int_digits);
+        if let Some(frac) = frac {
+            num.push('.');
+            num.push_str(&self.check_underscores(frac, 10) ?);
+        }
+        if let Some(exp) = exp {
+            num.push('e');
+            let exp = if exp.starts_with('+') || exp.starts_with('-') {
+                num.push_str(&exp[0..1]);
+                &exp[1..]
+            }
+            else {
+                exp
+            };
+            num.push_str(&self.check_underscores(exp, 10) ?);
+        }
+        match num.parse() {
+            Ok(v) => Ok(Toml::Num(v)),
+            Err(_) => Err(self.err_parse(&format!("float {}", word)))
+        }
+    }
+
+    fn parse_datetime(&self, word: &str) -> Result<Toml, TomlErr> {
+        let err = || self.err_parse(&format!("date-time {}", word));
+        let b = word.as_bytes();
+        let digits = | from: usize, len: usize | -> Result<u32, TomlErr> {
+            if from + len > b.len() || !b[from..from + len].iter().all( | c | c.is_ascii_digit()) {
+                return Err(err())
+            }
+            Ok(word[from..from + len].parse().unwrap())
+        };
+        let mut dt = TomlDateTime {date: None, time: None, offset: None};
+        let mut pos = 0;
+        if b.len() >= 10 && b[4] == b'-' {
+            let year = digits(0, 4) ? as u16;
+            let month = digits(5, 2) ? as u8;
+            let day = digits(8, 2) ? as u8;
+            if b[7] != b'-' || !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+                return Err(err())
+            }
+            dt.date = Some(TomlDate {year, month, day});
+            pos = 10;
+            if pos == b.len() {
+                return Ok(Toml::Date(dt))
+            }
+            if b[pos] != b'T' && b[pos] != b't' {
+                return Err(err())
+            }
+            pos += 1;
+        }
+        let hour = digits(pos, 2) ? as u8;
+        let minute = digits(pos + 3, 2) ? as u8;
+        let second = digits(pos + 6, 2) ? as u8;
+        if b[pos + 2] != b':' || b[pos + 5] != b':' || hour > 23 || minute > 59 || second > 60 {
+            return Err(err())
+        }
+        pos += 8;
+        let mut nanosecond = 0;
+        if pos < b.len() && b[pos] == b'.' {
+            pos += 1;
+            let start = pos;
+            while pos < b.len() && b[pos].is_ascii_digit() {
+                pos += 1;
+            }
+            if pos == start {
+                return Err(err())
+            }
+            let frac = &word[start..pos.min(start + 9)];
+            nanosecond = frac.parse::<u32>().unwrap() * 10u32.pow(9 - frac.len() as u32);
+        }
+        dt.time = Some(TomlTime {hour, minute, second, nanosecond});
+        if pos < b.len() {
+            if dt.date.is_none() {
+                return Err(err())
+            }
+            match b[pos] {
+                b'Z' | b'z' if pos + 1 == b.len() => dt.offset = Some(0),
+                b'+' | b'-' if pos + 6 == b.len() && b[pos + 3] == b':' => {
+                    let oh = digits(pos + 1, 2) ? as i16;
+                    let om = digits(pos + 4, 2) ? as i16;
+                    if oh > 23 || om > 59 {
+                        return Err(err())
                     }
-                    else {
-                        if is_neg {
-                            if let Ok(num) = num.parse() {
-                                 return Ok(TomlTok::I64(num))
-                            }
-                            else {
-                                return Err(self.err_parse("number"));
-                            }
-                        }
-                        if let Ok(num) = num.parse() {
-                            return Ok(TomlTok::U64(num))
-                        }
-                        else {
-                            return Err(self.err_parse("number"));
-                        }
+                    dt.offset = Some(if b[pos] == b'-' {-(oh * 60 + om)} else {oh * 60 + om});
+                }
+                _ => return Err(err())
+            }
+        }
+        Ok(Toml::Date(dt))
+    }
+
+    fn parse_escape(&mut self, out: &mut String, i: &mut Chars) -> Result<(), TomlErr> {
+        // cur is the character after the backslash
+        let c = match self.cur {
+            'b' => '\u{8}',
+            't' => '\t',
+            'n' => '\n',
+            'f' => '\u{c}',
+            'r' => '\r',
+            '"' => '"',
+            '\\' => '\\',
+            'u' | 'U' => {
+                let len = if self.cur == 'u' {4} else {8};
+                let mut hex = String::new();
+                for _ in 0..len {
+                    self.next(i);
+                    hex.push(self.cur);
+                }
+                match u32::from_str_radix(&hex, 16).ok().and_then(std::char::from_u32) {
+                    Some(c) => c,
+                    None => return Err(self.err_parse(&format!("unicode escape {}", hex)))
+                }
+            }
+            _ => return Err(self.err_parse("escape sequence"))
+        };
+        out.push(c);
+        self.next(i);
+        Ok(())
+    }
+
+    fn check_string_char(&self) -> Result<(), TomlErr> {
+        if self.cur == '\0' {
+            return Err(self.err_parse("string, unexpected end of file"))
+        }
+        if self.cur < ' ' && self.cur != '\t' || self.cur == '\u{7f}' {
+            return Err(self.err_parse("string, control characters must be escaped"))
+        }
+        Ok(())
+    }
+
+    fn parse_basic_string(&mut self, i: &mut Chars) -> Result<String, TomlErr> {
+        let mut val = String::new();
+        while self.cur != '"' {
+            if self.cur == '\\' {
+                self.next(i);
+                self.parse_escape(&mut val, i) ?;
+            }
+            else {
+                self.check_string_char() ?;
+                val.push(self.cur);
+                self.next(i);
+            }
+        }
+        self.next(i);
+        Ok(val)
+    }
+
+    fn parse_literal_string(&mut self, i: &mut Chars) -> Result<String, TomlErr> {
+        let mut val = String::new();
+        while self.cur != '\'' {
+            self.check_string_char() ?;
+            val.push(self.cur);
+            self.next(i);
+        }
+        self.next(i);
+        Ok(val)
+    }
+
+    // called after the opening triple quote
+    fn parse_multiline_string(&mut self, basic: bool, i: &mut Chars) -> Result<String, TomlErr> {
+        let quote = if basic {'"'} else {'\''};
+        let mut val = String::new();
+        if self.cur == '\n' || self.cur == '\r' && self.peek(i) == '\n' {
+            self.newline(i) ?;
+        }
+        loop {
+            if self.cur == quote {
+                let mut count = 0;
+                while self.cur == quote {
+                    count += 1;
+                    self.next(i);
+                }
+                if count >= 3 {
+                    if count > 5 {
+                        return Err(self.err_parse("string, too many quotes"))
                     }
-                },
-                'a'..='z' | 'A'..='Z' | '_' => {
-                    let mut ident = String::new();
-                    while self.cur >= 'a' && self.cur <= 'z'
-                        || self.cur >= 'A' && self.cur <= 'Z'
-                        || self.cur == '_' || self.cur == '-' {
-                        ident.push(self.cur);
-                        self.next(i);
+                    for _ in 0..count - 3 {
+                        val.push(quote);
                     }
-                    if self.cur == '.' {
-                        while self.cur == '.' {
-                            self.next(i);
-                            while self.cur >= 'a' && self.cur <= 'z'
-                                || self.cur >= 'A' && self.cur <= 'Z'
-                                || self.cur == '_' || self.cur == '-' {
-                                ident.push(self.cur);
-                                self.next(i);
-                            }
+                    return Ok(val)
+                }
+                for _ in 0..count {
+                    val.push(quote);
+                }
+            }
+            else if basic && self.cur == '\\' {
+                self.next(i);
+                // a line ending backslash eats all the whitespace up to the next non whitespace
+                let mut probe = i.clone();
+                let mut c = self.cur;
+                while c == ' ' || c == '\t' {
+                    c = probe.next().unwrap_or('\0');
+                }
+                if c == '\n' || c == '\r' {
+                    while self.cur == ' ' || self.cur == '\t' || self.cur == '\n' || self.cur == '\r' {
+                        if self.cur == '\r' {
+                            self.newline(i) ?;
                         }
-                        return Ok(TomlTok::Ident(ident))
-                    }
-                    if ident == "true" {
-                        return Ok(TomlTok::Bool(true))
-                    }
-                    if ident == "false" {
-                        return Ok(TomlTok::Bool(false))
-                    }
-                    if ident == "inf" {
-                        return Ok(TomlTok::Inf(false))
-                    }
-                    if ident == "nan" {
-                        return Ok(TomlTok::Nan(false))
-                    }
-                    return Ok(TomlTok::Ident(ident))
-                },
-                '#' =>{
-                    while self.cur !='\n' && self.cur != '\0'{
-                        self.next(i);
-                    }
-                },
-                '"' => {
-                    let mut val = String::new();
-                    self.next(i);
-                    while self.cur != '"' {
-                        if self.cur == '\\' {
+                        else {
                             self.next(i);
                         }
-                        if self.cur == '\0' {
-                            return Err(self.err_parse("string"));
-                        }
-                        val.push(self.cur);
-                        self.next(i);
                     }
-                    self.next(i);
-                    return Ok(TomlTok::Str(val))
-                },
-                _ => {
-                    return Err(self.err_parse("tokenizer"));
                 }
+                else {
+                    self.parse_escape(&mut val, i) ?;
+                }
+            }
+            else if self.cur == '\n' {
+                val.push('\n');
+                self.next(i);
+            }
+            else if self.cur == '\r' {
+                self.newline(i) ?;
+                val.push('\n');
+            }
+            else {
+                self.check_string_char() ?;
+                val.push(self.cur);
+                self.next(i);
             }
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,654 @@
+use std::str::Chars;
+use crate::serde_json::*;
+use crate::serde_ron::*;
+
+// owned document trees for when there is no struct to deserialize into
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RonValue {
+    Bool(bool),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    Char(char),
+    String(String),
+    Ident(String),
+    List(Vec<RonValue>),
+    Tuple(Option<String>, Vec<RonValue>),
+    Struct(Option<String>, Vec<(String, RonValue)>),
+    Map(Vec<(RonValue, RonValue)>)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValuePathSeg {
+    Key(String),
+    Index(usize)
+}
+
+// parses paths like builders.main.workspaces["makepad"] or packages[0].name
+pub fn parse_value_path(path: &str) -> Option<Vec<ValuePathSeg>> {
+    let mut out = Vec::new();
+    let mut chars = path.chars().peekable();
+    let mut key = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                if !key.is_empty() {
+                    out.push(ValuePathSeg::Key(std::mem::take(&mut key)));
+                }
+            }
+            '[' => {
+                if !key.is_empty() {
+                    out.push(ValuePathSeg::Key(std::mem::take(&mut key)));
+                }
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some('\\') => key.push(chars.next() ?),
+                            Some(c) => key.push(c),
+                            None => return None
+                        }
+                    }
+                    out.push(ValuePathSeg::Key(std::mem::take(&mut key)));
+                }
+                else {
+                    let mut num = String::new();
+                    while let Some(c) = chars.peek() {
+                        if *c == ']' {
+                            break;
+                        }
+                        num.push(*c);
+                        chars.next();
+                    }
+                    out.push(ValuePathSeg::Index(num.trim().parse().ok() ?));
+                }
+                if chars.next() != Some(']') {
+                    return None
+                }
+            }
+            _ => key.push(c)
+        }
+    }
+    if !key.is_empty() {
+        out.push(ValuePathSeg::Key(key));
+    }
+    Some(out)
+}
+
+static JSON_NULL: JsonValue = JsonValue::Null;
+
+impl JsonValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        if let JsonValue::Bool(v) = self {Some(*v)} else {None}
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            JsonValue::U64(v) => Some(*v),
+            JsonValue::I64(v) if *v >= 0 => Some(*v as u64),
+            _ => None
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            JsonValue::I64(v) => Some(*v),
+            JsonValue::U64(v) if *v <= i64::MAX as u64 => Some(*v as i64),
+            _ => None
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::F64(v) => Some(*v),
+            JsonValue::U64(v) => Some(*v as f64),
+            JsonValue::I64(v) => Some(*v as f64),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        if let JsonValue::String(v) = self {Some(v)} else {None}
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<JsonValue >> {
+        if let JsonValue::Array(v) = self {Some(v)} else {None}
+    }
+
+    pub fn as_object(&self) -> Option<&Vec<(String, JsonValue) >> {
+        if let JsonValue::Object(v) = self {Some(v)} else {None}
+    }
+
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        if let JsonValue::Object(fields) = self {
+            fields.iter().find( | (k, _) | k == key).map( | (_, v) | v)
+        }
+        else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        if let JsonValue::Object(fields) = self {
+            fields.iter_mut().find( | (k, _) | k == key).map( | (_, v) | v)
+        }
+        else {
+            None
+        }
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<&JsonValue> {
+        if let JsonValue::Array(items) = self {items.get(index)} else {None}
+    }
+
+    pub fn path(&self, path: &str) -> Option<&JsonValue> {
+        let mut cur = self;
+        for seg in parse_value_path(path) ? {
+            cur = match seg {
+                ValuePathSeg::Key(key) => cur.get(&key) ?,
+                ValuePathSeg::Index(index) => cur.get_index(index) ?,
+            };
+        }
+        Some(cur)
+    }
+}
+
+impl std::ops::Index<&str> for JsonValue {
+    type Output = JsonValue;
+    fn index(&self, key: &str) -> &JsonValue {
+        self.get(key).unwrap_or(&JSON_NULL)
+    }
+}
+
+impl std::ops::Index<usize> for JsonValue {
+    type Output = JsonValue;
+    fn index(&self, index: usize) -> &JsonValue {
+        self.get_index(index).unwrap_or(&JSON_NULL)
+    }
+}
+
+impl SerJson for JsonValue {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        match self {
+            JsonValue::Null => s.out.push_str("null"),
+            JsonValue::Bool(v) => v.ser_json(d, s),
+            JsonValue::U64(v) => v.ser_json(d, s),
+            JsonValue::I64(v) => v.ser_json(d, s),
+            JsonValue::F64(v) => v.ser_json(d, s),
+            JsonValue::String(v) => v.ser_json(d, s),
+            JsonValue::Array(v) => v.ser_json(d, s),
+            JsonValue::Object(fields) => {
+                s.st_pre();
+                for (index, (k, v)) in fields.iter().enumerate() {
+                    if index != 0 {
+                        s.conl();
+                    }
+                    s.indent(d + 1);
+                    k.ser_json(d + 1, s);
+                    s.out.push(':');
+                    v.ser_json(d + 1, s);
+                }
+                s.st_post(d);
+            }
+        }
+    }
+}
+
+// SerJsonState writes everything on one line, this is the indented form for people to read
+impl JsonValue {
+    pub fn serialize_json_pretty(&self) -> String {
+        let mut out = String::new();
+        self.ser_json_pretty(0, &mut out);
+        out
+    }
+
+    fn ser_json_pretty(&self, d: usize, out: &mut String) {
+        let indent = | out: &mut String, d: usize | for _ in 0..d {out.push_str("    ")};
+        match self {
+            JsonValue::Array(items) if !items.is_empty() => {
+                out.push_str("[\n");
+                for (index, item) in items.iter().enumerate() {
+                    if index != 0 {
+                        out.push_str(",\n");
+                    }
+                    indent(out, d + 1);
+                    item.ser_json_pretty(d + 1, out);
+                }
+                out.push('\n');
+                indent(out, d);
+                out.push(']');
+            }
+            JsonValue::Object(fields) if !fields.is_empty() => {
+                out.push_str("{\n");
+                for (index, (k, v)) in fields.iter().enumerate() {
+                    if index != 0 {
+                        out.push_str(",\n");
+                    }
+                    indent(out, d + 1);
+                    out.push_str(&k.serialize_json());
+                    out.push_str(": ");
+                    v.ser_json_pretty(d + 1, out);
+                }
+                out.push('\n');
+                indent(out, d);
+                out.push('}');
+            }
+            v => out.push_str(&v.serialize_json())
+        }
+    }
+}
+
+impl DeJson for JsonValue {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self, DeJsonErr> {
+        let val = match s.tok {
+            DeJsonTok::Null => JsonValue::Null,
+            DeJsonTok::Bool(v) => JsonValue::Bool(v),
+            DeJsonTok::U64(v) => JsonValue::U64(v),
+            DeJsonTok::I64(v) => JsonValue::I64(v),
+            DeJsonTok::F64(v) => JsonValue::F64(v),
+            DeJsonTok::Str => JsonValue::String(s.as_string() ?),
+            DeJsonTok::BlockOpen => return Ok(JsonValue::Array(DeJson::de_json(s, i) ?)),
+            DeJsonTok::CurlyOpen => {
+                let mut fields = Vec::new();
+                s.curly_open(i) ?;
+                while s.tok != DeJsonTok::CurlyClose {
+                    let k = s.as_string() ?;
+                    s.next_colon(i) ?;
+                    fields.push((k, DeJson::de_json(s, i) ?));
+                    s.eat_comma_curly(i) ?;
+                }
+                s.curly_close(i) ?;
+                return Ok(JsonValue::Object(fields))
+            }
+            _ => return Err(s.err_token("json value"))
+        };
+        s.next_tok(i) ?;
+        Ok(val)
+    }
+}
+
+// flattening a JsonValue into a struct collects all the keys the struct doesn't know
+impl SerJsonFlat for JsonValue {
+    fn ser_json_flat(&self, d: usize, s: &mut SerJsonState) {
+        if let JsonValue::Object(fields) = self {
+            for (k, v) in fields {
+                s.indent(d + 1);
+                k.ser_json(d + 1, s);
+                s.out.push(':');
+                v.ser_json(d + 1, s);
+                s.conl();
+            }
+        }
+    }
+}
+
+impl DeJsonFlat for JsonValue {
+    type Partial = Vec<(String, JsonValue)>;
+
+    fn de_json_flat_new() -> Self::Partial {
+        Vec::new()
+    }
+
+    fn de_json_flat_field(p: &mut Self::Partial, s: &mut DeJsonState, i: &mut Chars) -> Result<bool, DeJsonErr> {
+        let k = s.as_string() ?;
+        s.next_colon(i) ?;
+        p.push((k, DeJson::de_json(s, i) ?));
+        Ok(true)
+    }
+
+    fn de_json_flat_finish(p: Self::Partial, _s: &mut DeJsonState) -> Result<Self, DeJsonErr> {
+        Ok(JsonValue::Object(p))
+    }
+}
+
+// missing keys index to the unit value (), like JsonValue::Null
+static RON_UNIT: RonValue = RonValue::Tuple(None, Vec::new());
+
+impl RonValue {
+    pub fn is_unit(&self) -> bool {
+        if let RonValue::Tuple(None, items) = self {items.is_empty()} else {false}
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        if let RonValue::Bool(v) = self {Some(*v)} else {None}
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            RonValue::U64(v) => Some(*v),
+            RonValue::I64(v) if *v >= 0 => Some(*v as u64),
+            _ => None
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            RonValue::I64(v) => Some(*v),
+            RonValue::U64(v) if *v <= i64::MAX as u64 => Some(*v as i64),
+            _ => None
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            RonValue::F64(v) => Some(*v),
+            RonValue::U64(v) => Some(*v as f64),
+            RonValue::I64(v) => Some(*v as f64),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            RonValue::String(v) | RonValue::Ident(v) => Some(v),
+            _ => None
+        }
+    }
+
+    // the name of a Name(..) struct, tuple or a bare identifier
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            RonValue::Tuple(Some(name), _) | RonValue::Struct(Some(name), _) | RonValue::Ident(name) => Some(name),
+            _ => None
+        }
+    }
+
+    // looks up a field of a struct, or a string or identifier key of a map
+    pub fn get(&self, key: &str) -> Option<&RonValue> {
+        match self {
+            RonValue::Struct(_, fields) => fields.iter().find( | (k, _) | k == key).map( | (_, v) | v),
+            RonValue::Map(items) => items.iter().find( | (k, _) | k.as_str() == Some(key)).map( | (_, v) | v),
+            _ => None
+        }
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<&RonValue> {
+        match self {
+            RonValue::List(items) | RonValue::Tuple(_, items) => items.get(index),
+            _ => None
+        }
+    }
+
+    pub fn path(&self, path: &str) -> Option<&RonValue> {
+        let mut cur = self;
+        for seg in parse_value_path(path) ? {
+            cur = match seg {
+                ValuePathSeg::Key(key) => cur.get(&key) ?,
+                ValuePathSeg::Index(index) => cur.get_index(index) ?,
+            };
+        }
+        Some(cur)
+    }
+}
+
+impl std::ops::Index<&str> for RonValue {
+    type Output = RonValue;
+    fn index(&self, key: &str) -> &RonValue {
+        self.get(key).unwrap_or(&RON_UNIT)
+    }
+}
+
+impl std::ops::Index<usize> for RonValue {
+    type Output = RonValue;
+    fn index(&self, index: usize) -> &RonValue {
+        self.get_index(index).unwrap_or(&RON_UNIT)
+    }
+}
+
+impl SerRon for RonValue {
+    fn ser_ron(&self, d: usize, s: &mut SerRonState) {
+        match self {
+            RonValue::Bool(v) => v.ser_ron(d, s),
+            RonValue::U64(v) => v.ser_ron(d, s),
+            RonValue::I64(v) => v.ser_ron(d, s),
+            RonValue::F64(v) => v.ser_ron(d, s),
+            RonValue::Char(v) => {
+                s.out.push('\'');
+                if *v == '\'' || *v == '\\' {
+                    s.out.push('\\');
+                }
+                s.out.push(*v);
+                s.out.push('\'');
+            }
+            RonValue::String(v) => v.ser_ron(d, s),
+            RonValue::Ident(v) => s.out.push_str(v),
+            RonValue::List(v) => v.ser_ron(d, s),
+            RonValue::Tuple(name, items) => {
+                if let Some(name) = name {
+                    s.out.push_str(name);
+                }
+                s.out.push('(');
+                for (index, item) in items.iter().enumerate() {
+                    if index != 0 {
+                        s.out.push_str(", ");
+                    }
+                    item.ser_ron(d, s);
+                }
+                s.out.push(')');
+            }
+            RonValue::Struct(name, fields) => {
+                if let Some(name) = name {
+                    s.out.push_str(name);
+                }
+                s.st_pre();
+                for (k, v) in fields {
+                    s.field(d + 1, k);
+                    v.ser_ron(d + 1, s);
+                    s.conl();
+                }
+                s.st_post(d);
+            }
+            RonValue::Map(items) => {
+                s.out.push_str("{\n");
+                for (k, v) in items {
+                    s.indent(d + 1);
+                    k.ser_ron(d + 1, s);
+                    s.out.push(':');
+                    v.ser_ron(d + 1, s);
+                    s.conl();
+                }
+                s.indent(d);
+                s.out.push('}');
+            }
+        }
+    }
+}
+
+// SerRonState puts every struct field on its own line, this writes the whole value on one
+impl RonValue {
+    pub fn serialize_ron_compact(&self) -> String {
+        let mut out = String::new();
+        self.ser_ron_compact(&mut out);
+        out
+    }
+
+    fn ser_ron_compact(&self, out: &mut String) {
+        match self {
+            RonValue::List(items) => {
+                out.push('[');
+                for (index, item) in items.iter().enumerate() {
+                    if index != 0 {
+                        out.push(',');
+                    }
+                    item.ser_ron_compact(out);
+                }
+                out.push(']');
+            }
+            RonValue::Tuple(name, items) => {
+                if let Some(name) = name {
+                    out.push_str(name);
+                }
+                out.push('(');
+                for (index, item) in items.iter().enumerate() {
+                    if index != 0 {
+                        out.push(',');
+                    }
+                    item.ser_ron_compact(out);
+                }
+                out.push(')');
+            }
+            RonValue::Struct(name, fields) => {
+                if let Some(name) = name {
+                    out.push_str(name);
+                }
+                out.push('(');
+                for (index, (k, v)) in fields.iter().enumerate() {
+                    if index != 0 {
+                        out.push(',');
+                    }
+                    out.push_str(k);
+                    out.push(':');
+                    v.ser_ron_compact(out);
+                }
+                out.push(')');
+            }
+            RonValue::Map(items) => {
+                out.push('{');
+                for (index, (k, v)) in items.iter().enumerate() {
+                    if index != 0 {
+                        out.push(',');
+                    }
+                    k.ser_ron_compact(out);
+                    out.push(':');
+                    v.ser_ron_compact(out);
+                }
+                out.push('}');
+            }
+            v => out.push_str(&v.serialize_ron())
+        }
+    }
+}
+
+impl RonValue {
+    // reads what follows an identifier, either Name(..) or nothing
+    fn de_ron_named(name: String, s: &mut DeRonState, i: &mut Chars) -> Result<Self, DeRonErr> {
+        if s.tok == DeRonTok::ParenOpen {
+            RonValue::de_ron_paren(Some(name), s, i)
+        }
+        else {
+            Ok(RonValue::Ident(name))
+        }
+    }
+
+    // (a, b) is a tuple, (a: 1) is a struct
+    fn de_ron_paren(name: Option<String>, s: &mut DeRonState, i: &mut Chars) -> Result<Self, DeRonErr> {
+        s.paren_open(i) ?;
+        let mut items = Vec::new();
+        let mut fields = Vec::new();
+        while s.tok != DeRonTok::ParenClose {
+            if let DeRonTok::Ident = s.tok {
+                let ident = std::mem::take(&mut s.identbuf);
+                s.next_tok(i) ?;
+                if s.tok == DeRonTok::Colon && items.is_empty() {
+                    s.next_tok(i) ?;
+                    fields.push((ident, DeRon::de_ron(s, i) ?));
+                }
+                else if fields.is_empty() {
+                    items.push(RonValue::de_ron_named(ident, s, i) ?);
+                }
+                else {
+                    return Err(s.err_token(":"))
+                }
+            }
+            else if fields.is_empty() {
+                items.push(DeRon::de_ron(s, i) ?);
+            }
+            else {
+                return Err(s.err_token("Identifier"))
+            }
+            s.eat_comma_paren(i) ?;
+        }
+        s.paren_close(i) ?;
+        // a bare () is the unit value, which is an empty tuple
+        if !fields.is_empty() {
+            Ok(RonValue::Struct(name, fields))
+        }
+        else {
+            Ok(RonValue::Tuple(name, items))
+        }
+    }
+}
+
+impl DeRon for RonValue {
+    fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<Self, DeRonErr> {
+        let val = match s.tok {
+            DeRonTok::Bool(v) => RonValue::Bool(v),
+            DeRonTok::U64(v) => RonValue::U64(v),
+            DeRonTok::I64(v) => RonValue::I64(v),
+            DeRonTok::F64(v) => RonValue::F64(v),
+            DeRonTok::Char(v) => RonValue::Char(v),
+            DeRonTok::Str => RonValue::String(s.as_string() ?),
+            DeRonTok::Ident => {
+                let name = std::mem::take(&mut s.identbuf);
+                s.next_tok(i) ?;
+                return RonValue::de_ron_named(name, s, i)
+            }
+            DeRonTok::ParenOpen => return RonValue::de_ron_paren(None, s, i),
+            DeRonTok::BlockOpen => return Ok(RonValue::List(DeRon::de_ron(s, i) ?)),
+            DeRonTok::CurlyOpen => {
+                let mut items = Vec::new();
+                s.curly_open(i) ?;
+                while s.tok != DeRonTok::CurlyClose {
+                    let k = DeRon::de_ron(s, i) ?;
+                    s.colon(i) ?;
+                    items.push((k, DeRon::de_ron(s, i) ?));
+                    s.eat_comma_curly(i) ?;
+                }
+                s.curly_close(i) ?;
+                return Ok(RonValue::Map(items))
+            }
+            _ => return Err(s.err_token("ron value"))
+        };
+        s.next_tok(i) ?;
+        Ok(val)
+    }
+}
+
+impl SerRonFlat for RonValue {
+    fn ser_ron_flat(&self, d: usize, s: &mut SerRonState) {
+        if let RonValue::Struct(_, fields) = self {
+            for (k, v) in fields {
+                s.field(d + 1, k);
+                v.ser_ron(d + 1, s);
+                s.conl();
+            }
+        }
+    }
+}
+
+impl DeRonFlat for RonValue {
+    type Partial = Vec<(String, RonValue)>;
+
+    fn de_ron_flat_new() -> Self::Partial {
+        Vec::new()
+    }
+
+    fn de_ron_flat_field(p: &mut Self::Partial, s: &mut DeRonState, i: &mut Chars) -> Result<bool, DeRonErr> {
+        let k = std::mem::take(&mut s.identbuf);
+        s.next_colon(i) ?;
+        p.push((k, DeRon::de_ron(s, i) ?));
+        Ok(true)
+    }
+
+    fn de_ron_flat_finish(p: Self::Partial, _s: &mut DeRonState) -> Result<Self, DeRonErr> {
+        Ok(RonValue::Struct(None, p))
+    }
+}
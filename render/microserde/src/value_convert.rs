@@ -0,0 +1,324 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use crate::de_path::*;
+use crate::serde_bin::*;
+use crate::serde_toml::*;
+use crate::toml::*;
+use crate::value::*;
+
+// RonValue is the tree the formats convert through, it is the only one that keeps enum variants,
+// tuples and non string map keys apart. Values are mapped the way the derives write the same
+// Rust value in each format:
+//
+//   RON               JSON                    TOML
+//   None              null                    left out of its table
+//   Variant           {"Variant":[]}          "Variant"
+//   Variant(a, b)     {"Variant":[a,b]}       {Variant = [a, b]}
+//   Variant(x: 1)     {"Variant":{"x":1}}     {Variant = {x = 1}}
+//
+// JSON and TOML don't say whether an object is a struct or a map, so coming back an object with a
+// single capitalised key holding an array or object is read as a variant, an object whose keys are
+// all identifiers as a struct, and anything else as a map with string keys. A TOML unit variant is
+// only a string, it is read as a variant when it sits in an array next to variants that hold data.
+// Neither guess knows the Rust type, a map with identifier keys comes back as a struct and a lone
+// unit variant as a string, which the RON derives won't read in their place
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => chars.all( | c | c.is_alphanumeric() || c == '_'),
+        _ => false
+    }
+}
+
+fn is_variant_name(s: &str) -> bool {
+    is_ident(s) && s.starts_with( | c: char | c.is_ascii_uppercase())
+}
+
+// map keys as JSON and TOML keys, which are always strings
+fn ron_key_string(key: &RonValue) -> String {
+    match key {
+        RonValue::String(v) | RonValue::Ident(v) => v.clone(),
+        RonValue::Bool(v) => v.to_string(),
+        RonValue::U64(v) => v.to_string(),
+        RonValue::I64(v) => v.to_string(),
+        RonValue::F64(v) => v.to_string(),
+        RonValue::Char(v) => v.to_string(),
+        key => key.serialize_ron_compact()
+    }
+}
+
+fn ron_object(mut fields: Vec<(String, RonValue)>) -> RonValue {
+    if fields.len() == 1 && is_variant_name(&fields[0].0) {
+        match fields.pop().unwrap() {
+            (name, RonValue::List(items)) if items.is_empty() => return RonValue::Ident(name),
+            (name, RonValue::List(items)) => return RonValue::Tuple(Some(name), items),
+            (name, RonValue::Struct(None, inner)) => return RonValue::Struct(Some(name), inner),
+            field => fields.push(field)
+        }
+    }
+    // an empty struct would be written as (), which reads back as the unit value
+    if !fields.is_empty() && fields.iter().all( | (k, _) | is_ident(k)) {
+        RonValue::Struct(None, fields)
+    }
+    else {
+        RonValue::Map(fields.into_iter().map( | (k, v) | (RonValue::String(k), v)).collect())
+    }
+}
+
+fn json_variant(name: String, data: JsonValue) -> JsonValue {
+    JsonValue::Object(vec![(name, data)])
+}
+
+fn json_items(items: Vec<RonValue>) -> JsonValue {
+    JsonValue::Array(items.into_iter().map(JsonValue::from).collect())
+}
+
+fn json_fields(fields: Vec<(String, RonValue)>) -> JsonValue {
+    JsonValue::Object(fields.into_iter().map( | (k, v) | (k, v.into())).collect())
+}
+
+impl From<RonValue> for JsonValue {
+    fn from(v: RonValue) -> JsonValue {
+        match v {
+            RonValue::Bool(v) => JsonValue::Bool(v),
+            RonValue::U64(v) => JsonValue::U64(v),
+            RonValue::I64(v) => JsonValue::I64(v),
+            RonValue::F64(v) => JsonValue::F64(v),
+            RonValue::Char(v) => JsonValue::String(v.to_string()),
+            RonValue::String(v) => JsonValue::String(v),
+            RonValue::Ident(v) if v == "None" => JsonValue::Null,
+            RonValue::Ident(v) => json_variant(v, JsonValue::Array(Vec::new())),
+            RonValue::List(items) | RonValue::Tuple(None, items) => json_items(items),
+            RonValue::Tuple(Some(name), items) => json_variant(name, json_items(items)),
+            RonValue::Struct(None, fields) => json_fields(fields),
+            RonValue::Struct(Some(name), fields) => json_variant(name, json_fields(fields)),
+            RonValue::Map(items) => JsonValue::Object(items.into_iter().map( | (k, v) | (ron_key_string(&k), v.into())).collect()),
+        }
+    }
+}
+
+impl From<JsonValue> for RonValue {
+    fn from(v: JsonValue) -> RonValue {
+        match v {
+            JsonValue::Null => RonValue::Ident("None".to_string()),
+            JsonValue::Bool(v) => RonValue::Bool(v),
+            JsonValue::U64(v) => RonValue::U64(v),
+            JsonValue::I64(v) => RonValue::I64(v),
+            JsonValue::F64(v) => RonValue::F64(v),
+            JsonValue::String(v) => RonValue::String(v),
+            JsonValue::Array(items) => RonValue::List(items.into_iter().map(RonValue::from).collect()),
+            JsonValue::Object(fields) => ron_object(fields.into_iter().map( | (k, v) | (k, v.into())).collect()),
+        }
+    }
+}
+
+fn toml_list(mut items: Vec<RonValue>) -> RonValue {
+    let variants = items.iter().any( | item | matches!(item, RonValue::Tuple(Some(_), _) | RonValue::Struct(Some(_), _)));
+    if variants {
+        for item in &mut items {
+            if let RonValue::String(name) = item {
+                if is_variant_name(name) {
+                    *item = RonValue::Ident(std::mem::take(name));
+                }
+            }
+        }
+    }
+    RonValue::List(items)
+}
+
+impl From<Toml> for RonValue {
+    fn from(v: Toml) -> RonValue {
+        match v {
+            Toml::Str(v) => RonValue::String(v),
+            Toml::Bool(v) => RonValue::Bool(v),
+            Toml::Int(v) if v >= 0 => RonValue::U64(v as u64),
+            Toml::Int(v) => RonValue::I64(v),
+            Toml::Num(v) => RonValue::F64(v),
+            Toml::Date(v) => RonValue::String(v.to_string()),
+            Toml::Array(items) => toml_list(items.into_iter().map(RonValue::from).collect()),
+            Toml::Table(table) => ron_object(table.into_iter().map( | (k, v) | (k, v.into())).collect()),
+        }
+    }
+}
+
+fn toml_items(items: Vec<RonValue>) -> Result<Toml, DeTomlErr> {
+    let mut out = Vec::new();
+    for (index, item) in items.into_iter().enumerate() {
+        out.push(Toml::try_from(item).map_err( | e | e.at_index(index)) ?);
+    }
+    Ok(Toml::Array(out))
+}
+
+// None values are left out, the same as an Option field that isn't set
+fn toml_table(fields: Vec<(String, RonValue)>) -> Result<Toml, DeTomlErr> {
+    let mut out = BTreeMap::new();
+    for (k, v) in fields {
+        if let RonValue::Ident(v) = &v {
+            if v == "None" {
+                continue
+            }
+        }
+        let v = Toml::try_from(v).map_err( | e | if is_ident(&k) {e.at_field(&k)} else {e.at_key(&k)}) ?;
+        out.insert(k, v);
+    }
+    Ok(Toml::Table(out))
+}
+
+fn toml_variant(name: String, data: Toml) -> Toml {
+    let mut out = BTreeMap::new();
+    out.insert(name, data);
+    Toml::Table(out)
+}
+
+impl TryFrom<RonValue> for Toml {
+    type Error = DeTomlErr;
+
+    fn try_from(v: RonValue) -> Result<Toml, DeTomlErr> {
+        Ok(match v {
+            RonValue::Bool(v) => Toml::Bool(v),
+            RonValue::U64(v) => Toml::Int(i64::try_from(v).map_err( | _ | DeTomlErr {
+                msg: format!("{} doesn't fit in a TOML integer, which is 64 bit signed", v),
+                path: DePath::default()
+            }) ?),
+            RonValue::I64(v) => Toml::Int(v),
+            RonValue::F64(v) => Toml::Num(v),
+            RonValue::Char(v) => Toml::Str(v.to_string()),
+            RonValue::String(v) => Toml::Str(v),
+            RonValue::Ident(v) if v == "None" => {
+                return Err(DeTomlErr {msg: "TOML has no null, None can only be left out of a table".to_string(), path: DePath::default()})
+            }
+            RonValue::Ident(v) => Toml::Str(v),
+            RonValue::List(items) | RonValue::Tuple(None, items) => toml_items(items) ?,
+            RonValue::Tuple(Some(name), items) => toml_variant(name, toml_items(items) ?),
+            RonValue::Struct(None, fields) => toml_table(fields) ?,
+            RonValue::Struct(Some(name), fields) => toml_variant(name, toml_table(fields) ?),
+            RonValue::Map(items) => toml_table(items.into_iter().map( | (k, v) | (ron_key_string(&k), v)).collect()) ?,
+        })
+    }
+}
+
+fn ser_bin_mode<T: SerBin + ?Sized>(v: &T, s: &mut Vec<u8>, compact: bool) {
+    if compact {v.ser_bin_compact(s)} else {v.ser_bin(s)}
+}
+
+fn de_bin_mode<T: DeBin>(o: &mut usize, d: &[u8], compact: bool) -> Result<T, DeBinErr> {
+    if compact {T::de_bin_compact(o, d)} else {T::de_bin(o, d)}
+}
+
+// the bin format has no types in it, so the tree writes a kind byte in front of every value
+impl RonValue {
+    fn ser_bin_value(&self, s: &mut Vec<u8>, compact: bool) {
+        match self {
+            RonValue::Bool(v) => {s.push(0); ser_bin_mode(v, s, compact)}
+            RonValue::U64(v) => {s.push(1); ser_bin_mode(v, s, compact)}
+            RonValue::I64(v) => {s.push(2); ser_bin_mode(v, s, compact)}
+            RonValue::F64(v) => {s.push(3); ser_bin_mode(v, s, compact)}
+            RonValue::Char(v) => {s.push(4); ser_bin_mode(v, s, compact)}
+            RonValue::String(v) => {s.push(5); ser_bin_mode(v, s, compact)}
+            RonValue::Ident(v) => {s.push(6); ser_bin_mode(v, s, compact)}
+            RonValue::List(items) => {
+                s.push(7);
+                ser_bin_mode(&items.len(), s, compact);
+                for item in items {
+                    item.ser_bin_value(s, compact);
+                }
+            }
+            RonValue::Tuple(name, items) => {
+                s.push(8);
+                ser_bin_mode(name, s, compact);
+                ser_bin_mode(&items.len(), s, compact);
+                for item in items {
+                    item.ser_bin_value(s, compact);
+                }
+            }
+            RonValue::Struct(name, fields) => {
+                s.push(9);
+                ser_bin_mode(name, s, compact);
+                ser_bin_mode(&fields.len(), s, compact);
+                for (k, v) in fields {
+                    ser_bin_mode(k, s, compact);
+                    v.ser_bin_value(s, compact);
+                }
+            }
+            RonValue::Map(items) => {
+                s.push(10);
+                ser_bin_mode(&items.len(), s, compact);
+                for (k, v) in items {
+                    k.ser_bin_value(s, compact);
+                    v.ser_bin_value(s, compact);
+                }
+            }
+        }
+    }
+
+    fn de_bin_items(o: &mut usize, d: &[u8], compact: bool) -> Result<Vec<RonValue>, DeBinErr> {
+        let len: usize = de_bin_mode(o, d, compact) ?;
+        let mut out = Vec::new();
+        for index in 0..len {
+            out.push(RonValue::de_bin_value(o, d, compact).map_err( | e | e.at_index(index)) ?);
+        }
+        Ok(out)
+    }
+
+    fn de_bin_value(o: &mut usize, d: &[u8], compact: bool) -> Result<RonValue, DeBinErr> {
+        let at = *o;
+        let kind: u8 = DeBin::de_bin(o, d) ?;
+        Ok(match kind {
+            0 => RonValue::Bool(de_bin_mode(o, d, compact) ?),
+            1 => RonValue::U64(de_bin_mode(o, d, compact) ?),
+            2 => RonValue::I64(de_bin_mode(o, d, compact) ?),
+            3 => RonValue::F64(de_bin_mode(o, d, compact) ?),
+            4 => RonValue::Char(de_bin_mode(o, d, compact) ?),
+            5 => RonValue::String(de_bin_mode(o, d, compact) ?),
+            6 => RonValue::Ident(de_bin_mode(o, d, compact) ?),
+            7 => RonValue::List(RonValue::de_bin_items(o, d, compact) ?),
+            8 => {
+                let name = de_bin_mode(o, d, compact) ?;
+                RonValue::Tuple(name, RonValue::de_bin_items(o, d, compact) ?)
+            }
+            9 => {
+                let name = de_bin_mode(o, d, compact) ?;
+                let len: usize = de_bin_mode(o, d, compact) ?;
+                let mut fields = Vec::new();
+                for _ in 0..len {
+                    let k: String = de_bin_mode(o, d, compact) ?;
+                    let v = RonValue::de_bin_value(o, d, compact).map_err( | e | e.at_field(&k)) ?;
+                    fields.push((k, v));
+                }
+                RonValue::Struct(name, fields)
+            }
+            10 => {
+                let len: usize = de_bin_mode(o, d, compact) ?;
+                let mut items = Vec::new();
+                for index in 0..len {
+                    let k = RonValue::de_bin_value(o, d, compact).map_err( | e | e.at_index(index)) ?;
+                    let v = RonValue::de_bin_value(o, d, compact).map_err( | e | e.at_index(index)) ?;
+                    items.push((k, v));
+                }
+                RonValue::Map(items)
+            }
+            _ => return Err(DeBinErr {o: at, l: 1, s: d.len(), msg: format!("RonValue kind {}", kind), path: DePath::default()})
+        })
+    }
+}
+
+impl SerBin for RonValue {
+    fn ser_bin(&self, s: &mut Vec<u8>) {
+        self.ser_bin_value(s, false)
+    }
+
+    fn ser_bin_compact(&self, s: &mut Vec<u8>) {
+        self.ser_bin_value(s, true)
+    }
+}
+
+impl DeBin for RonValue {
+    fn de_bin(o: &mut usize, d: &[u8]) -> Result<RonValue, DeBinErr> {
+        RonValue::de_bin_value(o, d, false)
+    }
+
+    fn de_bin_compact(o: &mut usize, d: &[u8]) -> Result<RonValue, DeBinErr> {
+        RonValue::de_bin_value(o, d, true)
+    }
+}
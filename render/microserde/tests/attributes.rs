@@ -0,0 +1,114 @@
+use makepad_microserde::*;
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerBin, DeBin, PartialEq, Debug)]
+struct Inner {
+    x: u32,
+    #[nserde(default)]
+    y: u32,
+}
+
+fn default_name() -> String {"unnamed".to_string()}
+
+/// doc comments on the item and its fields are skipped
+#[derive(SerJson, DeJson, SerRon, DeRon, SerBin, DeBin, PartialEq, Debug)]
+struct Outer {
+    #[nserde(rename = "$type")]
+    ty: String,
+    /// with a default function
+    #[nserde(default = "default_name")]
+    name: String,
+    #[nserde(skip)]
+    cache: Vec<u32>,
+    #[nserde(flatten)]
+    inner: Inner,
+}
+
+#[derive(SerRon, DeRon, PartialEq, Debug)]
+struct Wrapper {
+    #[nserde(rename = "type")]
+    kind: u32,
+    #[nserde(skip)]
+    cache: u32,
+    #[nserde(flatten)]
+    inner: Inner,
+}
+
+#[derive(SerRon, DeRon, PartialEq, Debug)]
+#[nserde(default)]
+struct Settings {
+    build_on_save: bool,
+    port: u16,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {build_on_save: true, port: 8000}
+    }
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, PartialEq, Debug)]
+enum Shape {
+    #[nserde(rename = "circle")]
+    Circle {#[nserde(rename = "r")] radius: f64},
+    Point,
+}
+
+fn outer() -> Outer {
+    Outer {
+        ty: "a".to_string(),
+        name: "b".to_string(),
+        cache: vec![1, 2],
+        inner: Inner {x: 1, y: 2}
+    }
+}
+
+#[test]
+fn json_rename_skip_flatten() {
+    let json = outer().serialize_json();
+    assert_eq!(json, r#"{"$type":"a","name":"b","x":1,"y":2,}"#);
+    let back: Outer = DeJson::deserialize_json(&json).unwrap();
+    assert_eq!(back.cache, vec![]);
+    assert_eq!(back.inner, Inner {x: 1, y: 2});
+}
+
+#[test]
+fn json_defaults() {
+    let back: Outer = DeJson::deserialize_json(r#"{"$type":"a","x":3}"#).unwrap();
+    assert_eq!(back.name, "unnamed");
+    assert_eq!(back.inner, Inner {x: 3, y: 0});
+    assert!(Outer::deserialize_json(r#"{"$type":"a"}"#).is_err());
+    assert!(Outer::deserialize_json(r#"{"$type":"a","x":3,"z":1}"#).is_err());
+}
+
+#[test]
+fn ron_rename_skip_flatten() {
+    let ron = Wrapper {kind: 3, cache: 4, inner: Inner {x: 1, y: 2}}.serialize_ron();
+    assert_eq!(ron, "(\n    type:3,\n    x:1,\n    y:2,\n)");
+    let back: Wrapper = DeRon::deserialize_ron(&ron).unwrap();
+    assert_eq!(back, Wrapper {kind: 3, cache: 0, inner: Inner {x: 1, y: 2}});
+    let back: Wrapper = DeRon::deserialize_ron("(x:5, type:1)").unwrap();
+    assert_eq!(back.inner, Inner {x: 5, y: 0});
+}
+
+#[test]
+fn ron_container_default() {
+    let s: Settings = DeRon::deserialize_ron("(port:9000)").unwrap();
+    assert_eq!(s, Settings {build_on_save: true, port: 9000});
+}
+
+#[test]
+fn bin_skip() {
+    let bin = outer().serialize_bin();
+    let back: Outer = DeBin::deserialize_bin(&bin).unwrap();
+    assert_eq!(back.cache, vec![]);
+    assert_eq!(back.inner, Inner {x: 1, y: 2});
+}
+
+#[test]
+fn enum_rename() {
+    let json = Shape::Circle {radius: 1.5}.serialize_json();
+    assert_eq!(json, r#"{"circle":{"r":1.5,}}"#);
+    assert_eq!(Shape::deserialize_json(&json).unwrap(), Shape::Circle {radius: 1.5});
+    let ron = Shape::Circle {radius: 1.5}.serialize_ron();
+    assert_eq!(Shape::deserialize_ron(&ron).unwrap(), Shape::Circle {radius: 1.5});
+}
@@ -0,0 +1,122 @@
+use makepad_microserde::*;
+use std::collections::HashMap;
+
+#[derive(SerBin, DeBin, PartialEq, Debug)]
+struct Cursor {
+    head: usize,
+    tail: usize,
+    delta: i32,
+}
+
+#[derive(SerBin, DeBin, PartialEq, Debug)]
+#[nserde(tagged)]
+enum Msg {
+    Connect,
+    ChangeAll {path: String, code: String, cursors: Vec<Cursor>},
+    ChangeFloat {id: u64, float: f32},
+}
+
+#[derive(SerBin, DeBin, PartialEq, Debug)]
+#[nserde(compact)]
+struct Update {
+    ids: Vec<u32>,
+    names: HashMap<String, i64>,
+    pair: (u16, char),
+}
+
+#[derive(SerBin, DeBin, PartialEq, Debug)]
+struct Wrap {
+    own: u32,
+    updates: Vec<(u32, Update)>,
+}
+
+#[test]
+fn compact_varints() {
+    assert_eq!(0u64.serialize_bin_compact(), vec![0]);
+    assert_eq!(127u32.serialize_bin_compact(), vec![0x7f]);
+    assert_eq!(300u16.serialize_bin_compact(), vec![0xac, 0x02]);
+    assert_eq!((-1i32).serialize_bin_compact(), vec![1]);
+    assert_eq!(1i32.serialize_bin_compact(), vec![2]);
+    assert_eq!((-64i64).serialize_bin_compact(), vec![0x7f]);
+    for v in [0i64, 1, -1, 63, -64, i64::MAX, i64::MIN] {
+        assert_eq!(i64::deserialize_bin_compact(&v.serialize_bin_compact()).unwrap(), v);
+    }
+    for v in [0u128, u64::MAX as u128 + 1, u128::MAX] {
+        assert_eq!(u128::deserialize_bin_compact(&v.serialize_bin_compact()).unwrap(), v);
+    }
+    assert_eq!(i128::deserialize_bin_compact(&i128::MIN.serialize_bin_compact()).unwrap(), i128::MIN);
+    assert_eq!("hi".serialize_bin_compact(), vec![2, b'h', b'i']);
+}
+
+#[test]
+fn compact_round_trip() {
+    let msg = Msg::ChangeAll {
+        path: "main.rs".to_string(),
+        code: "fn main(){}".to_string(),
+        cursors: vec![Cursor {head: 5, tail: 5, delta: -3}, Cursor {head: 1000, tail: 12, delta: 0}],
+    };
+    let fixed = msg.serialize_bin();
+    let compact = msg.serialize_bin_compact();
+    assert!(compact.len() * 2 < fixed.len());
+    assert_eq!(Msg::deserialize_bin_compact(&compact).unwrap(), msg);
+    assert_eq!(Msg::deserialize_bin(&fixed).unwrap(), msg);
+
+    let msg = Msg::ChangeFloat {id: 3, float: 0.5};
+    assert_eq!(Msg::deserialize_bin_compact(&msg.serialize_bin_compact()).unwrap(), msg);
+    assert_eq!(Msg::deserialize_bin_compact(&Msg::Connect.serialize_bin_compact()).unwrap(), Msg::Connect);
+}
+
+#[test]
+fn compact_per_type() {
+    let mut names = HashMap::new();
+    names.insert("x".to_string(), -2);
+    let update = Update {ids: vec![1, 2, 3], names, pair: (7, 'é')};
+    // the attribute makes ser_bin write the compact encoding, also when nested in a fixed width type
+    assert_eq!(update.serialize_bin(), update.serialize_bin_compact());
+    let wrap = Wrap {own: 9, updates: vec![(1, update)]};
+    let buf = wrap.serialize_bin();
+    assert_eq!(&buf[0..4], &9u32.to_le_bytes());
+    assert_eq!(Wrap::deserialize_bin(&buf).unwrap(), wrap);
+}
+
+#[derive(SerBin, DeBin, PartialEq, Debug)]
+#[nserde(tagged)]
+struct Node {
+    name: String,
+    children: Vec<Node>,
+}
+
+#[test]
+fn compact_nested_lengths() {
+    let mut node = Node {name: "leaf".to_string(), children: vec![]};
+    for depth in 0..300 {
+        let sibling = Node {name: "x".repeat(depth), children: vec![]};
+        node = Node {name: depth.to_string(), children: vec![sibling, node]};
+    }
+    let buf = node.serialize_bin_compact();
+    assert_eq!(Node::deserialize_bin_compact(&buf).unwrap(), node);
+    // the outer length is written at its real width, covering exactly the rest of the buffer
+    let mut o = 0;
+    let len: usize = DeBin::de_bin_compact(&mut o, &buf).unwrap();
+    assert_eq!(o + len, buf.len());
+    // a compact value written into a scratch buffer while another is open stays separate
+    let mut outer = Vec::new();
+    let at = ser_bin_len_begin_compact(&mut outer);
+    let inner = node.children[1].serialize_bin_compact();
+    outer.extend_from_slice(&inner);
+    ser_bin_len_end_compact(at, &mut outer);
+    let mut o = 0;
+    let len: usize = DeBin::de_bin_compact(&mut o, &outer).unwrap();
+    assert_eq!(len, inner.len());
+    assert_eq!(&outer[o..], &inner[..]);
+}
+
+#[test]
+fn compact_errors() {
+    let buf = Cursor {head: 1000, tail: 1, delta: 1}.serialize_bin_compact();
+    let e = Cursor::deserialize_bin_compact(&buf[0..1]).unwrap_err();
+    assert_eq!(e.path.to_string(), "head");
+    assert!(u16::deserialize_bin_compact(&70000u32.serialize_bin_compact()).is_err());
+    assert!(u8::deserialize_bin_compact(&[]).is_err());
+    assert!(u128::deserialize_bin_compact(&[0xff; 20]).is_err());
+}
@@ -0,0 +1,94 @@
+use makepad_microserde::*;
+use std::collections::HashMap;
+
+#[derive(SerBin, DeBin, PartialEq, Debug)]
+struct FileWrite {
+    uid: u64,
+    path: String,
+    data: Vec<u8>,
+    tags: Vec<String>,
+    digest: Option<Vec<u8>>,
+}
+
+#[derive(DeBinRef, PartialEq, Debug)]
+struct FileWriteRef<'a> {
+    uid: u64,
+    path: &'a str,
+    data: &'a [u8],
+    tags: Vec<&'a str>,
+    digest: Option<&'a [u8]>,
+}
+
+#[derive(SerBin, DeBin, PartialEq, Debug)]
+#[nserde(tagged)]
+enum Msg {
+    Ping,
+    FileReadResponse {uid: u64, path: String, data: Option<Vec<u8>>},
+    FileWriteRequest {uid: u64, path: String, data: Vec<u8>},
+}
+
+// only the file transfer variants, read without copying the payload
+#[derive(DeBinRef, PartialEq, Debug)]
+#[nserde(tagged)]
+enum MsgRef<'a> {
+    FileReadResponse {uid: u64, path: &'a str, data: Option<&'a [u8]>},
+    FileWriteRequest {uid: u64, path: &'a str, data: &'a [u8]},
+}
+
+#[derive(DeBinRef, PartialEq, Debug)]
+struct Keyed<'a, T> where T: Copy {
+    value: T,
+    names: HashMap<&'a str, (u8, &'a str)>,
+}
+
+#[test]
+fn bin_ref_struct() {
+    let owned = FileWrite {
+        uid: 5,
+        path: "src/main.rs".to_string(),
+        data: vec![1, 2, 3, 4],
+        tags: vec!["a".to_string(), "bc".to_string()],
+        digest: Some(vec![9; 16]),
+    };
+    let buf = owned.serialize_bin();
+    let r = FileWriteRef::deserialize_bin_ref(&buf).unwrap();
+    assert_eq!(r, FileWriteRef {uid: 5, path: "src/main.rs", data: &[1, 2, 3, 4], tags: vec!["a", "bc"], digest: Some(&[9; 16])});
+    // the payload points into buf instead of being copied out
+    let range = buf.as_ptr_range();
+    assert!(range.contains(&r.data.as_ptr()));
+    assert!(range.contains(&r.path.as_ptr()));
+}
+
+#[test]
+fn bin_ref_tagged() {
+    let buf = Msg::FileWriteRequest {uid: 1, path: "a.txt".to_string(), data: b"hello".to_vec()}.serialize_bin();
+    assert_eq!(MsgRef::deserialize_bin_ref(&buf).unwrap(), MsgRef::FileWriteRequest {uid: 1, path: "a.txt", data: b"hello"});
+    let buf = Msg::FileReadResponse {uid: 2, path: "b.txt".to_string(), data: None}.serialize_bin();
+    assert_eq!(MsgRef::deserialize_bin_ref(&buf).unwrap(), MsgRef::FileReadResponse {uid: 2, path: "b.txt", data: None});
+    assert!(MsgRef::deserialize_bin_ref(&Msg::Ping.serialize_bin()).is_err());
+}
+
+#[test]
+fn bin_ref_generic() {
+    let mut names = HashMap::new();
+    names.insert("x".to_string(), (1u8, "one".to_string()));
+    let buf = (7u32, names).serialize_bin();
+    let r = Keyed::<u32>::deserialize_bin_ref(&buf).unwrap();
+    assert_eq!(r.value, 7);
+    assert_eq!(r.names["x"], (1, "one"));
+    // arrays are written without a length
+    let buf = [1u8, 2, 3, 4][..].serialize_bin();
+    assert_eq!(<[u8; 4]>::deserialize_bin_ref(&buf).unwrap(), [1, 2, 3, 4]);
+}
+
+#[test]
+fn bin_ref_errors() {
+    let mut buf = FileWrite {uid: 1, path: "ok".to_string(), data: vec![], tags: vec![], digest: None}.serialize_bin();
+    buf[16] = 0xff;
+    let e = FileWriteRef::deserialize_bin_ref(&buf).unwrap_err();
+    assert_eq!(e.path.to_string(), "path");
+    assert!(String::deserialize_bin(&buf[8..]).is_err());
+    buf[16] = b'o';
+    let e = FileWriteRef::deserialize_bin_ref(&buf[0..buf.len() - 1]).unwrap_err();
+    assert_eq!(e.path.to_string(), "digest");
+}
@@ -0,0 +1,111 @@
+use makepad_microserde::*;
+
+// two versions of the same message, as an older and a newer hub would have them
+
+#[derive(SerBin, DeBin, PartialEq, Debug)]
+#[nserde(tagged)]
+struct ConfigV1 {
+    port: u16,
+    name: String,
+}
+
+#[derive(SerBin, DeBin, PartialEq, Debug)]
+#[nserde(tagged)]
+struct ConfigV2 {
+    #[nserde(default = "default_hosts")]
+    hosts: Vec<String>,
+    name: String,
+    port: u16,
+    comment: Option<String>,
+    #[nserde(skip)]
+    cache: u32,
+}
+
+fn default_hosts() -> Vec<String> {vec!["localhost".to_string()]}
+
+#[derive(SerBin, DeBin, PartialEq, Debug)]
+#[nserde(tagged)]
+enum MsgV1 {
+    Connect,
+    Build {uid: u64, config: ConfigV1},
+    Log(u64, String),
+}
+
+#[derive(SerBin, DeBin, PartialEq, Debug)]
+#[nserde(tagged)]
+enum MsgV2 {
+    Kill(u64),
+    Log(u64, String, bool),
+    Build {uid: u64, config: ConfigV2, fresh: Option<bool>},
+    Connect,
+}
+
+#[derive(SerBin, DeBin, PartialEq, Debug)]
+#[nserde(tagged)]
+struct Pair(u32, String);
+
+#[test]
+fn tagged_roundtrip() {
+    let c = ConfigV2 {hosts: vec![], name: "main".to_string(), port: 8000, comment: Some("x".to_string()), cache: 5};
+    let back = ConfigV2::deserialize_bin(&c.serialize_bin()).unwrap();
+    assert_eq!(back, ConfigV2 {cache: 0, ..c});
+    let p = Pair(1, "a".to_string());
+    assert_eq!(Pair::deserialize_bin(&p.serialize_bin()).unwrap(), p);
+    let m = MsgV2::Log(3, "warning".to_string(), true);
+    assert_eq!(MsgV2::deserialize_bin(&m.serialize_bin()).unwrap(), m);
+}
+
+#[test]
+fn tagged_newer_to_older() {
+    let m = MsgV2::Build {
+        uid: 7,
+        config: ConfigV2 {hosts: vec!["a".to_string()], name: "main".to_string(), port: 80, comment: None, cache: 0},
+        fresh: Some(true)
+    };
+    let old = MsgV1::deserialize_bin(&m.serialize_bin()).unwrap();
+    assert_eq!(old, MsgV1::Build {uid: 7, config: ConfigV1 {port: 80, name: "main".to_string()}});
+    let old = MsgV1::deserialize_bin(&MsgV2::Log(1, "x".to_string(), false).serialize_bin()).unwrap();
+    assert_eq!(old, MsgV1::Log(1, "x".to_string()));
+    assert!(MsgV1::deserialize_bin(&MsgV2::Kill(1).serialize_bin()).is_err());
+}
+
+#[test]
+fn tagged_older_to_newer() {
+    let m = MsgV1::Build {uid: 7, config: ConfigV1 {port: 80, name: "main".to_string()}};
+    let new = MsgV2::deserialize_bin(&m.serialize_bin()).unwrap();
+    assert_eq!(new, MsgV2::Build {
+        uid: 7,
+        config: ConfigV2 {hosts: default_hosts(), name: "main".to_string(), port: 80, comment: None, cache: 0},
+        fresh: None
+    });
+    assert_eq!(MsgV2::deserialize_bin(&MsgV1::Connect.serialize_bin()).unwrap(), MsgV2::Connect);
+    // a tuple value that is missing can't be filled in
+    assert!(MsgV2::deserialize_bin(&MsgV1::Log(1, "x".to_string()).serialize_bin()).is_err());
+}
+
+fn tagged_field<T: SerBin>(name: &str, value: &T, s: &mut Vec<u8>) {
+    bin_tag(name).ser_bin(s);
+    let at = ser_bin_len_begin(s);
+    value.ser_bin(s);
+    ser_bin_len_end(at, s);
+}
+
+#[test]
+fn tagged_layout() {
+    let config = ConfigV1 {port: 1, name: "a".to_string()};
+    let bytes = config.serialize_bin();
+    // fields are written in declaration order
+    let mut written = Vec::new();
+    (bytes.len() as u32 - 4).ser_bin(&mut written);
+    tagged_field("port", &1u16, &mut written);
+    tagged_field("name", &"a".to_string(), &mut written);
+    assert_eq!(bytes, written);
+    // and can be read in any order
+    let mut reordered = Vec::new();
+    (bytes.len() as u32 - 4).ser_bin(&mut reordered);
+    tagged_field("name", &"a".to_string(), &mut reordered);
+    tagged_field("port", &1u16, &mut reordered);
+    assert_eq!(ConfigV1::deserialize_bin(&reordered).unwrap(), config);
+    // a length running past the end is an error, not a panic
+    assert!(ConfigV1::deserialize_bin(&bytes[..bytes.len() - 1]).is_err());
+}
@@ -0,0 +1,130 @@
+use makepad_microserde::*;
+
+#[derive(SerJson, DeJson, PartialEq, Debug)]
+struct Target {
+    name: String,
+    kind: Vec<String>,
+}
+
+#[derive(SerJson, DeJson, PartialEq, Debug)]
+struct Artifact {
+    package_id: String,
+    target: Target,
+    fresh: bool,
+}
+
+// the messages cargo writes with --message-format=json
+#[derive(SerJson, DeJson, PartialEq, Debug)]
+#[nserde(tag = "reason")]
+enum CargoMessage {
+    #[nserde(rename = "compiler-artifact")]
+    CompilerArtifact(Artifact),
+    #[nserde(rename = "build-script-executed")]
+    BuildScriptExecuted {package_id: String, out_dir: Option<String>},
+    #[nserde(rename = "build-finished")]
+    BuildFinished {success: bool},
+    #[nserde(rename = "done")]
+    Done,
+}
+
+#[derive(SerJson, DeJson, PartialEq, Debug)]
+#[nserde(tag = "t", content = "c")]
+enum Adjacent {
+    Unit,
+    One(u32),
+    Two(u32, String),
+    Named {x: i32, y: Option<i32>},
+}
+
+#[derive(SerJson, DeJson, PartialEq, Debug)]
+#[nserde(untagged)]
+enum Untagged {
+    Nothing,
+    Pos {x: f64, y: f64},
+    Name(String),
+    Pair(u32, u32),
+    Number(u64),
+}
+
+#[test]
+fn internally_tagged() {
+    let msg = CargoMessage::BuildFinished {success: true};
+    assert_eq!(msg.serialize_json(), r#"{"reason":"build-finished","success":true,}"#);
+    assert_eq!(CargoMessage::Done.serialize_json(), r#"{"reason":"done",}"#);
+    let artifact = CargoMessage::CompilerArtifact(Artifact {
+        package_id: "makepad-hub 0.1.0".to_string(),
+        target: Target {name: "makepad_hub".to_string(), kind: vec!["lib".to_string()]},
+        fresh: true
+    });
+    let json = artifact.serialize_json();
+    assert!(json.starts_with(r#"{"reason":"compiler-artifact","package_id":"makepad-hub 0.1.0","#));
+    assert_eq!(CargoMessage::deserialize_json(&json).unwrap(), artifact);
+    for msg in [msg, CargoMessage::Done] {
+        assert_eq!(CargoMessage::deserialize_json(&msg.serialize_json()).unwrap(), msg);
+    }
+}
+
+#[test]
+fn internally_tagged_key_order() {
+    // the tag doesn't have to come first
+    let msg = CargoMessage::deserialize_json(r#"{"package_id":"a 0.1.0","out_dir":"target/out","reason":"build-script-executed"}"#).unwrap();
+    assert_eq!(msg, CargoMessage::BuildScriptExecuted {package_id: "a 0.1.0".to_string(), out_dir: Some("target/out".to_string())});
+    let msg = CargoMessage::deserialize_json(r#"{"fresh":false,"reason":"compiler-artifact","package_id":"b","target":{"name":"b","kind":[]}}"#).unwrap();
+    assert_eq!(msg, CargoMessage::CompilerArtifact(Artifact {
+        package_id: "b".to_string(),
+        target: Target {name: "b".to_string(), kind: vec![]},
+        fresh: false
+    }));
+    assert!(CargoMessage::deserialize_json(r#"{"success":true}"#).is_err());
+    assert!(CargoMessage::deserialize_json(r#"{"reason":"build-finished"}"#).is_err());
+    assert!(CargoMessage::deserialize_json(r#"{"reason":"done","success":true}"#).is_err());
+    assert!(CargoMessage::deserialize_json(r#"{"reason":"compiler-message"}"#).is_err());
+}
+
+#[test]
+fn adjacently_tagged() {
+    let values = [
+        (Adjacent::Unit, r#"{"t":"Unit",}"#),
+        (Adjacent::One(1), r#"{"t":"One","c":1,}"#),
+        (Adjacent::Two(2, "b".to_string()), r#"{"t":"Two","c":[2,"b"],}"#),
+        (Adjacent::Named {x: -1, y: None}, r#"{"t":"Named","c":{"x":-1,},}"#),
+    ];
+    for (value, json) in values {
+        assert_eq!(value.serialize_json(), json);
+        assert_eq!(Adjacent::deserialize_json(json).unwrap(), value);
+    }
+    // content in front of the tag
+    assert_eq!(Adjacent::deserialize_json(r#"{"c":{"y":3,"x":4},"t":"Named"}"#).unwrap(), Adjacent::Named {x: 4, y: Some(3)});
+    assert_eq!(Adjacent::deserialize_json(r#"{"c":[5,"e"],"t":"Two"}"#).unwrap(), Adjacent::Two(5, "e".to_string()));
+    assert!(Adjacent::deserialize_json(r#"{"t":"One"}"#).is_err());
+    assert!(Adjacent::deserialize_json(r#"{"t":"Three","c":1}"#).is_err());
+    assert!(Adjacent::deserialize_json(r#"{"c":1}"#).is_err());
+}
+
+#[test]
+fn untagged() {
+    let values = [
+        (Untagged::Nothing, "null"),
+        (Untagged::Pos {x: 1.5, y: 2.5}, r#"{"x":1.5,"y":2.5,}"#),
+        (Untagged::Name("n".to_string()), r#""n""#),
+        (Untagged::Pair(1, 2), "[1,2]"),
+        (Untagged::Number(7), "7"),
+    ];
+    for (value, json) in values {
+        assert_eq!(value.serialize_json(), json);
+        assert_eq!(Untagged::deserialize_json(json).unwrap(), value);
+    }
+    assert!(Untagged::deserialize_json("true").is_err());
+    assert!(Untagged::deserialize_json(r#"{"x":1}"#).is_err());
+}
+
+#[test]
+fn tagged_in_a_stream() {
+    let stream = r#"{"reason":"build-script-executed","package_id":"a"}
+{"success":false,"reason":"build-finished"}
+"#;
+    let mut state = DeJsonState::from_reader(stream.as_bytes());
+    assert_eq!(state.read_value::<CargoMessage>().unwrap().unwrap(), CargoMessage::BuildScriptExecuted {package_id: "a".to_string(), out_dir: None});
+    assert_eq!(state.read_value::<CargoMessage>().unwrap().unwrap(), CargoMessage::BuildFinished {success: false});
+    assert!(state.read_value::<CargoMessage>().is_none());
+}
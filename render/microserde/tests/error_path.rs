@@ -0,0 +1,65 @@
+use makepad_microserde::*;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerBin, DeBin, SerToml, DeToml, PartialEq, Debug)]
+struct Builder {
+    port: u16,
+    workspaces: BTreeMap<String, String>,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerBin, DeBin, SerToml, DeToml, PartialEq, Debug)]
+struct Settings {
+    builders: HashMap<String, Builder>,
+    builds: Vec<(String, u32)>,
+}
+
+#[test]
+fn error_path_json() {
+    let data = r#"{"builders":{"main":{"port":80,"workspaces":{"makepad":5}}},"builds":[]}"#;
+    let e = Settings::deserialize_json(data).unwrap_err();
+    assert_eq!(e.path.to_string(), r#"builders["main"].workspaces["makepad"]"#);
+
+    let data = r#"{"builders":{},"builds":[["a",1],["b","x"]]}"#;
+    let e = Settings::deserialize_json(data).unwrap_err();
+    assert_eq!(e.path.to_string(), "builds[1][1]");
+    assert!(format!("{:?}", e).ends_with(" at builds[1][1]"));
+}
+
+#[test]
+fn error_path_ron() {
+    let data = "(\n    builders: {\n        \"main\": (\n            port: 99999,\n            workspaces: {},\n        ),\n    },\n    builds: [],\n)";
+    let e = Settings::deserialize_ron(data).unwrap_err();
+    assert_eq!(e.path.to_string(), r#"builders["main"].port"#);
+    assert_eq!(e.line, 3);
+}
+
+#[test]
+fn error_path_bin() {
+    let mut settings = Settings {builders: HashMap::new(), builds: vec![("a".to_string(), 1), ("b".to_string(), 2)]};
+    settings.builders.insert("main".to_string(), Builder {port: 80, workspaces: BTreeMap::new()});
+    let data = settings.serialize_bin();
+    let e = Settings::deserialize_bin(&data[0..data.len() - 2]).unwrap_err();
+    assert_eq!(e.path.to_string(), "builds[1][1]");
+}
+
+#[test]
+fn error_path_toml() {
+    let data = "builds = []\n[builders.main]\nport = 80\n[builders.main.workspaces]\nmakepad = 1\n";
+    let e = Settings::deserialize_toml(data).unwrap_err();
+    assert_eq!(e.path.to_string(), r#"builders["main"].workspaces["makepad"]"#);
+}
+
+#[test]
+fn error_path_render() {
+    let data = "(\n    builders: {\n        \"main\": (\n            port: \"80\",\n            workspaces: {},\n        ),\n    },\n    builds: [],\n)";
+    let e = Settings::deserialize_ron(data).unwrap_err();
+    let out = e.render("makepad_settings.ron", data);
+    let lines: Vec<&str> = out.lines().collect();
+    assert!(lines[0].starts_with("error: "));
+    assert_eq!(lines[1], " --> makepad_settings.ron:4:19");
+    assert_eq!(lines[3], "4 |             port: \"80\",");
+    assert_eq!(lines[4], "  |                   ^");
+    assert_eq!(lines[5], "  = at builders[\"main\"].port");
+    // Debug points at the same place
+    assert!(format!("{:?}", e).contains("line:4 col:19"));
+}
@@ -0,0 +1,69 @@
+use makepad_microserde::*;
+use std::marker::PhantomData;
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerBin, DeBin, SerToml, DeToml, PartialEq, Debug)]
+struct Wrapper<T> {
+    value: T,
+    list: Vec<T>,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerBin, DeBin, SerToml, DeToml, PartialEq, Debug)]
+struct Pair<A, B: Clone>(A, B) where A: Clone;
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerBin, DeBin, SerToml, DeToml, PartialEq, Debug)]
+struct Borrowed<'a, T: 'a, const N: usize = 4, U = u8> where T: PartialEq {
+    name: String,
+    extra: U,
+    #[nserde(skip)]
+    marker: PhantomData<&'a T>,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerBin, DeBin, SerToml, DeToml, PartialEq, Debug)]
+enum DockItem<TItem> where TItem: Clone {
+    Single(TItem),
+    Tabs {tabs: Vec<TItem>, current: usize},
+    Splitter {first: Box<DockItem<TItem>>, last: Box<DockItem<TItem>>},
+}
+
+fn dock() -> DockItem<String> {
+    DockItem::Splitter {
+        first: Box::new(DockItem::Single("file_tree".to_string())),
+        last: Box::new(DockItem::Tabs {tabs: vec!["main.rs".to_string(), "lib.rs".to_string()], current: 1}),
+    }
+}
+
+#[test]
+fn generics_json() {
+    let w = Wrapper {value: 1u32, list: vec![2, 3]};
+    assert_eq!(Wrapper::<u32>::deserialize_json(&w.serialize_json()).unwrap(), w);
+    let p = Pair("a".to_string(), 2.5f64);
+    assert_eq!(Pair::<String, f64>::deserialize_json(&p.serialize_json()).unwrap(), p);
+    let b: Borrowed<u32> = Borrowed {name: "b".to_string(), extra: 7, marker: PhantomData};
+    assert_eq!(Borrowed::<u32>::deserialize_json(&b.serialize_json()).unwrap(), b);
+    assert_eq!(DockItem::<String>::deserialize_json(&dock().serialize_json()).unwrap(), dock());
+}
+
+#[test]
+fn generics_ron() {
+    let w = Wrapper {value: "x".to_string(), list: vec![]};
+    assert_eq!(Wrapper::<String>::deserialize_ron(&w.serialize_ron()).unwrap(), w);
+    let p = Pair(1u8, vec![true]);
+    assert_eq!(Pair::<u8, Vec<bool>>::deserialize_ron(&p.serialize_ron()).unwrap(), p);
+    assert_eq!(DockItem::<String>::deserialize_ron(&dock().serialize_ron()).unwrap(), dock());
+}
+
+#[test]
+fn generics_bin() {
+    let w = Wrapper {value: -1i64, list: vec![i64::MAX]};
+    assert_eq!(Wrapper::<i64>::deserialize_bin(&w.serialize_bin()).unwrap(), w);
+    let b: Borrowed<String, 2, u64> = Borrowed {name: "b".to_string(), extra: 9, marker: PhantomData};
+    assert_eq!(Borrowed::<String, 2, u64>::deserialize_bin(&b.serialize_bin()).unwrap(), b);
+    assert_eq!(DockItem::<String>::deserialize_bin(&dock().serialize_bin()).unwrap(), dock());
+}
+
+#[test]
+fn generics_toml() {
+    let w = Wrapper {value: 1.5f64, list: vec![2.5]};
+    assert_eq!(Wrapper::<f64>::deserialize_toml(&w.serialize_toml()).unwrap(), w);
+    assert_eq!(DockItem::<String>::deserialize_toml(&dock().serialize_toml()).unwrap(), dock());
+}
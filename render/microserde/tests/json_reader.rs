@@ -0,0 +1,77 @@
+use makepad_microserde::*;
+use std::io::Read;
+
+#[derive(DeJson, Debug, PartialEq)]
+struct Message {
+    reason: String,
+    package_id: Option<String>,
+    fresh: Option<bool>,
+}
+
+// hands out its chunks one read at a time and panics when asked for more
+struct Chunks<'a> {
+    chunks: Vec<&'a [u8]>,
+    index: usize
+}
+
+impl<'a> Read for Chunks<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let chunk = self.chunks.get(self.index).expect("read past the end of the value");
+        self.index += 1;
+        buf[..chunk.len()].copy_from_slice(chunk);
+        Ok(chunk.len())
+    }
+}
+
+#[test]
+fn reader_utf8_boundaries() {
+    let json = "{\"reason\":\"caf\u{e9} \u{1F600}\",\"fresh\":true}";
+    // every byte in its own read, so multi byte characters are split
+    let bytes: Vec<&[u8]> = json.as_bytes().chunks(1).collect();
+    let msg = Message::deserialize_json_reader(Chunks {chunks: bytes, index: 0}).unwrap();
+    assert_eq!(msg.reason, "caf\u{e9} \u{1F600}");
+    assert_eq!(msg.fresh, Some(true));
+}
+
+#[test]
+fn reader_stream_of_values() {
+    let stream = r#"{"reason":"compiler-artifact","package_id":"makepad-hub 0.1.0","fresh":true}
+{"reason":"compiler-message"}
+
+{"reason":"build-finished"}
+"#;
+    let mut state = DeJsonState::from_reader(stream.as_bytes());
+    let mut reasons = Vec::new();
+    while let Some(msg) = state.read_value::<Message>() {
+        reasons.push(msg.unwrap().reason);
+    }
+    assert_eq!(reasons, vec!["compiler-artifact", "compiler-message", "build-finished"]);
+}
+
+#[test]
+fn reader_stops_at_end_of_value() {
+    let mut state = DeJsonState::from_reader(Chunks {chunks: vec![b"{\"reason\":\"a\"}\n", b"{\"reason\":\"b\"}", b""], index: 0});
+    assert_eq!(state.read_value::<Message>().unwrap().unwrap().reason, "a");
+    assert_eq!(state.read_value::<Message>().unwrap().unwrap().reason, "b");
+    assert!(state.read_value::<Message>().is_none());
+    
+    // a pipe that blocks after each value, reading one more char would hit the panic in Chunks
+    let mut state = DeJsonState::from_reader(Chunks {chunks: vec![b"{\"reason\":\"a\"}", b"[1,2]", b"\"b\""], index: 0});
+    assert_eq!(state.read_value::<Message>().unwrap().unwrap().reason, "a");
+    assert_eq!(state.read_value::<Vec<u32>>().unwrap().unwrap(), vec![1, 2]);
+    assert_eq!(state.read_value::<String>().unwrap().unwrap(), "b");
+    
+    // a top level array is the last thing the pipe has
+    let mut state = DeJsonState::from_reader(Chunks {chunks: vec![b"{\"reason\":\"a\"}", b"[1,2]"], index: 0});
+    assert_eq!(state.read_value::<Message>().unwrap().unwrap().reason, "a");
+    assert_eq!(state.read_value::<Vec<u32>>().unwrap().unwrap(), vec![1, 2]);
+    let mut state = DeJsonState::from_reader(Chunks {chunks: vec![b"[{\"reason\":\"a\"}]"], index: 0});
+    assert_eq!(state.read_value::<JsonValue>().unwrap().unwrap()[0]["reason"].as_str(), Some("a"));
+}
+
+#[test]
+fn reader_errors() {
+    assert!(Message::deserialize_json_reader(&b"{\"reason\":\"\xff\"}"[..]).is_err());
+    assert!(Message::deserialize_json_reader(&b""[..]).is_err());
+    assert!(Message::deserialize_json_reader(&b"{\"reason\":"[..]).is_err());
+}
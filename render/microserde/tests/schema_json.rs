@@ -0,0 +1,148 @@
+use makepad_microserde::*;
+use std::collections::HashMap;
+
+#[derive(SerJson, DeJson, SchemaJson, PartialEq, Debug)]
+struct Builder {
+    port: u16,
+    #[nserde(rename = "work-spaces")]
+    workspaces: HashMap<String, String>,
+    proxy: Option<String>,
+    #[nserde(default)]
+    verbose: bool,
+    #[nserde(skip)]
+    cache: Vec<u8>,
+}
+
+#[derive(SerJson, DeJson, SchemaJson, PartialEq, Debug)]
+struct Window {
+    pos: (f32, f32),
+    #[nserde(flatten)]
+    extra: JsonValue,
+}
+
+#[derive(SerJson, DeJson, SchemaJson, PartialEq, Debug)]
+struct Settings {
+    builder: Builder,
+    windows: Vec<Window>,
+    dock: Dock<String>,
+}
+
+#[derive(SerJson, DeJson, SchemaJson, PartialEq, Debug)]
+enum Dock<T> {
+    Single(T),
+    Tabs {tabs: Vec<T>, current: usize},
+    Splitter {first: Box<Dock<T>>, last: Box<Dock<T>>},
+    Empty,
+}
+
+#[derive(SerJson, DeJson, SchemaJson, PartialEq, Debug)]
+#[nserde(tag = "type")]
+enum Shape {
+    Circle {radius: f64},
+    Point,
+}
+
+#[derive(SerJson, DeJson, SchemaJson, PartialEq, Debug)]
+#[nserde(tag = "t", content = "c")]
+enum Cmd {
+    Run(String),
+    Stop,
+}
+
+#[derive(SerJson, DeJson, SchemaJson, PartialEq, Debug)]
+#[nserde(untagged)]
+enum Port {
+    Number(u16),
+    Named(String),
+}
+
+fn strings(v: &JsonValue) -> Vec<&str> {
+    v.as_array().unwrap().iter().map( | s | s.as_str().unwrap()).collect()
+}
+
+#[test]
+fn schema_struct() {
+    let doc = Settings::schema_json_document();
+    assert_eq!(doc["$schema"].as_str(), Some(SCHEMA_JSON_DRAFT));
+    assert_eq!(doc["$ref"].as_str(), Some("#/$defs/Settings"));
+    let builder = &doc["$defs"]["Builder"];
+    assert_eq!(builder["type"].as_str(), Some("object"));
+    assert_eq!(builder["additionalProperties"], JsonValue::Bool(false));
+    assert_eq!(strings(&builder["required"]), vec!["port", "work-spaces"]);
+    let keys: Vec<&str> = builder["properties"].as_object().unwrap().iter().map( | (k, _) | k.as_str()).collect();
+    assert_eq!(keys, vec!["port", "work-spaces", "proxy", "verbose"]);
+    assert_eq!(builder.path("properties.port.maximum"), Some(&JsonValue::U64(65535)));
+    assert_eq!(builder.path("properties.proxy.anyOf[1].type").and_then( | t | t.as_str()), Some("null"));
+    assert_eq!(builder.path("properties[\"work-spaces\"].additionalProperties.type").and_then( | t | t.as_str()), Some("string"));
+
+    // the flattened JsonValue takes the keys Window doesn't know
+    let window = &doc["$defs"]["Window"];
+    assert_eq!(window.get("additionalProperties"), None);
+    assert_eq!(window.path("properties.pos.prefixItems[1].type").and_then( | t | t.as_str()), Some("number"));
+}
+
+#[test]
+fn schema_enums() {
+    let doc = Settings::schema_json_document();
+    let dock = &doc["$defs"]["Dock_String"];
+    assert_eq!(dock["oneOf"].as_array().unwrap().len(), 4);
+    assert_eq!(dock.path("oneOf[0].properties.Single.prefixItems[0].type").and_then( | t | t.as_str()), Some("string"));
+    assert_eq!(dock.path("oneOf[2].properties.Splitter.properties.first.$ref").and_then( | t | t.as_str()), Some("#/$defs/Dock_String"));
+    assert_eq!(dock.path("oneOf[3].properties.Empty.minItems"), Some(&JsonValue::U64(0)));
+
+    let doc = Shape::schema_json_document();
+    let circle = &doc["$defs"]["Shape"]["oneOf"][0];
+    assert_eq!(circle.path("properties.type.const").and_then( | t | t.as_str()), Some("Circle"));
+    assert_eq!(strings(&circle["required"]), vec!["type", "radius"]);
+
+    let doc = Cmd::schema_json_document();
+    let cmd = &doc["$defs"]["Cmd"]["oneOf"];
+    assert_eq!(cmd.path("[0].properties.c.type").and_then( | t | t.as_str()), Some("string"));
+    assert_eq!(strings(&cmd[1]["required"]), vec!["t"]);
+
+    let doc = Port::schema_json_document();
+    assert_eq!(doc["$defs"]["Port"]["anyOf"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn schema_document() {
+    // a non derived root keeps its schema at the top level and has no $defs
+    let doc = <Vec<Option<u8>>>::schema_json_document();
+    assert_eq!(doc["type"].as_str(), Some("array"));
+    assert_eq!(doc.get("$defs"), None);
+    let text = Settings::serialize_schema_json();
+    assert_eq!(JsonValue::deserialize_json(&text).unwrap(), Settings::schema_json_document());
+}
+
+mod left {
+    use makepad_microserde::*;
+    #[derive(SchemaJson)]
+    #[allow(dead_code)]
+    pub struct Pos {pub x: f32}
+}
+
+mod right {
+    use makepad_microserde::*;
+    #[derive(SchemaJson)]
+    #[allow(dead_code)]
+    pub struct Pos {pub name: String}
+}
+
+#[derive(SchemaJson)]
+#[allow(dead_code)]
+struct Both {
+    a: left::Pos,
+    b: right::Pos,
+    c: left::Pos,
+}
+
+#[test]
+fn schema_same_names() {
+    // same named types from two modules each get their own $defs entry
+    let doc = Both::schema_json_document();
+    assert_eq!(doc.path("$defs.Both.properties.a.$ref").and_then( | r | r.as_str()), Some("#/$defs/Pos"));
+    assert_eq!(doc.path("$defs.Both.properties.b.$ref").and_then( | r | r.as_str()), Some("#/$defs/Pos2"));
+    assert_eq!(doc.path("$defs.Both.properties.c.$ref").and_then( | r | r.as_str()), Some("#/$defs/Pos"));
+    assert_eq!(doc.path("$defs.Pos.properties.x.type").and_then( | t | t.as_str()), Some("number"));
+    assert_eq!(doc.path("$defs.Pos2.properties.name.type").and_then( | t | t.as_str()), Some("string"));
+}
@@ -0,0 +1,112 @@
+use makepad_microserde::*;
+use std::collections::HashMap;
+
+#[derive(SerToml, DeToml, PartialEq, Debug)]
+enum HttpServer {
+    Offline,
+    Localhost(u16),
+    InterfaceV4(u16, [u8; 4]),
+    Proxy {url: String, #[nserde(rename = "retry-count")] retries: Option<u32>},
+}
+
+#[derive(SerToml, DeToml, PartialEq, Debug)]
+struct Builder {
+    http_server: HttpServer,
+    workspaces: HashMap<String, String>,
+}
+
+#[derive(SerToml, DeToml, PartialEq, Debug)]
+struct Target {
+    builder: String,
+    package: String,
+    config: Option<String>,
+    #[nserde(default)]
+    jobs: u8,
+}
+
+#[derive(SerToml, DeToml, PartialEq, Debug)]
+struct Settings {
+    build_on_save: bool,
+    scale: f32,
+    builders: HashMap<String, Builder>,
+    builds: Vec<Target>,
+    #[nserde(skip)]
+    dirty: bool,
+}
+
+fn settings() -> Settings {
+    let mut workspaces = HashMap::new();
+    workspaces.insert("makepad".to_string(), ".".to_string());
+    let mut builders = HashMap::new();
+    builders.insert("main".to_string(), Builder {http_server: HttpServer::Localhost(8000), workspaces});
+    builders.insert("win".to_string(), Builder {http_server: HttpServer::InterfaceV4(80, [127, 0, 0, 1]), workspaces: HashMap::new()});
+    Settings {
+        build_on_save: true,
+        scale: 1.5,
+        builders,
+        builds: vec![
+            Target {builder: "main".to_string(), package: "webgl_example_wasm".to_string(), config: Some("release".to_string()), jobs: 4},
+            Target {builder: "win".to_string(), package: "makepad".to_string(), config: None, jobs: 0},
+        ],
+        dirty: false
+    }
+}
+
+#[test]
+fn toml_roundtrip() {
+    let toml = settings().serialize_toml();
+    assert_eq!(Settings::deserialize_toml(&toml).unwrap(), settings());
+    assert!(toml.contains("[[builds]]\nbuilder = \"win\"\njobs = 0\npackage = \"makepad\"\n"));
+    assert!(toml.contains("[builders.main.workspaces]\nmakepad = \".\"\n"));
+}
+
+#[test]
+fn toml_hand_written() {
+    let settings: Settings = DeToml::deserialize_toml(r#"
+build_on_save = false
+scale = 2
+
+[builders.main]
+http_server = "Offline"
+workspaces = {makepad = "."}
+
+[builders.proxy]
+http_server.Proxy = {url = "http://localhost", retry-count = 3}
+workspaces = {}
+
+[[builds]]
+builder = "main"
+package = "makepad"
+"#).unwrap();
+    assert_eq!(settings.scale, 2.0);
+    assert_eq!(settings.builders["main"].http_server, HttpServer::Offline);
+    assert_eq!(settings.builders["proxy"].http_server, HttpServer::Proxy {url: "http://localhost".to_string(), retries: Some(3)});
+    assert_eq!(settings.builds[0].config, None);
+    assert_eq!(settings.builds[0].jobs, 0);
+}
+
+#[test]
+fn toml_errors() {
+    assert!(Target::deserialize_toml("builder = \"main\"").is_err());
+    assert!(Target::deserialize_toml("builder = \"main\"\npackage = \"a\"\nextra = 1").is_err());
+    assert!(Target::deserialize_toml("builder = \"main\"\npackage = \"a\"\njobs = 300").is_err());
+    assert!(Target::deserialize_toml("builder = 1\npackage = \"a\"").is_err());
+    assert!(HttpServer::deserialize_toml("Unknown = 1").is_err());
+}
+
+#[derive(SerToml, DeToml, PartialEq, Debug)]
+struct Package {
+    name: String,
+    #[nserde(flatten)]
+    target: Target,
+    #[nserde(flatten)]
+    rest: Toml,
+}
+
+#[test]
+fn toml_flatten() {
+    let p = Package::deserialize_toml("name = \"a\"\nbuilder = \"main\"\npackage = \"b\"\n[metadata]\nx = 1").unwrap();
+    assert_eq!(p.target.package, "b");
+    assert_eq!(p.rest.path("metadata.x"), Some(&Toml::Int(1)));
+    assert_eq!(Package::deserialize_toml(&p.serialize_toml()).unwrap(), p);
+}
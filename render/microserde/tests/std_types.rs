@@ -0,0 +1,132 @@
+use makepad_microserde::*;
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerBin, DeBin, SerToml, DeToml, PartialEq, Debug)]
+struct Everything {
+    map: BTreeMap<String, u32>,
+    set: BTreeSet<u64>,
+    hash_set: HashSet<String>,
+    deque: VecDeque<i8>,
+    chr: char,
+    escaped: char,
+    big: u128,
+    small: i128,
+    rc: Rc<String>,
+    arc: Arc<Vec<u16>>,
+    unit: (),
+    wide: (u8, i16, u32, i64, f32, f64, bool, String, char, u8, u16, u32),
+}
+
+fn everything() -> Everything {
+    Everything {
+        map: vec![("a".to_string(), 1), ("b".to_string(), 2)].into_iter().collect(),
+        set: vec![3, 1, 2].into_iter().collect(),
+        hash_set: vec!["x".to_string()].into_iter().collect(),
+        deque: vec![-1, 0, 1].into_iter().collect(),
+        chr: '\u{1F600}',
+        escaped: '\'',
+        big: u128::MAX,
+        small: i128::MIN,
+        rc: Rc::new("rc".to_string()),
+        arc: Arc::new(vec![1, 2]),
+        unit: (),
+        wide: (1, -2, 3, -4, 5.5, 6.25, true, "eight".to_string(), '\n', 10, 11, 12),
+    }
+}
+
+#[test]
+fn std_types_json() {
+    let json = everything().serialize_json();
+    assert!(json.contains(r#""big":340282366920938463463374607431768211455,"#));
+    assert!(json.contains(r#""unit":null,"#));
+    assert_eq!(Everything::deserialize_json(&json).unwrap(), everything());
+}
+
+#[test]
+fn std_types_ron() {
+    let ron = everything().serialize_ron();
+    assert_eq!(Everything::deserialize_ron(&ron).unwrap(), everything());
+}
+
+#[test]
+fn std_types_bin() {
+    let bin = everything().serialize_bin();
+    assert_eq!(Everything::deserialize_bin(&bin).unwrap(), everything());
+    // not every u32 is a char
+    assert!(char::deserialize_bin(&0xd800u32.serialize_bin()).is_err());
+}
+
+#[test]
+fn std_types_toml() {
+    let toml = everything().serialize_toml();
+    // integers wider than TOML's 64 bits are written as strings
+    assert!(toml.contains("big = \"340282366920938463463374607431768211455\""));
+    assert_eq!(Everything::deserialize_toml(&toml).unwrap(), everything());
+}
+
+#[derive(SerToml, DeToml, PartialEq, Debug)]
+struct TomlMaps {
+    ids: BTreeMap<u32, String>,
+    pairs: BTreeMap<(u8, bool), char>,
+    maybe: BTreeMap<String, Option<u32>>,
+    opt: Option<u32>,
+}
+
+#[test]
+fn std_types_toml_maps() {
+    let maps = TomlMaps {
+        ids: vec![(1, "one".to_string()), (20, "twenty".to_string())].into_iter().collect(),
+        pairs: vec![((1, true), 'a')].into_iter().collect(),
+        maybe: vec![("a".to_string(), Some(1)), ("b".to_string(), None)].into_iter().collect(),
+        opt: Some(5),
+    };
+    let toml = maps.serialize_toml();
+    assert!(toml.contains("20 = \"twenty\""));
+    assert!(toml.contains("\"[1, true]\" = \"a\""));
+    // None values have no TOML form and are left out, reading them back gives nothing for the key
+    assert!(!toml.contains("b ="));
+    let back = TomlMaps::deserialize_toml(&toml).unwrap();
+    assert_eq!(back.ids, maps.ids);
+    assert_eq!(back.pairs, maps.pairs);
+    assert_eq!(back.maybe, vec![("a".to_string(), Some(1))].into_iter().collect());
+    assert_eq!(back.opt, Some(5));
+    
+    assert_eq!(Some(3u32).serialize_toml(), "[3]");
+    assert!(BTreeMap::<u32, u32>::deserialize_toml("x = 1").is_err());
+}
+
+#[derive(SerToml, DeToml, PartialEq, Debug)]
+struct TomlOptions {
+    v: Vec<Option<u32>>,
+    nested: Option<Option<u32>>,
+    empty: Option<Vec<u32>>,
+}
+
+#[test]
+fn std_types_toml_options() {
+    // outside a table entry None and Some need their own forms, that is [] and [value]
+    let options = TomlOptions {v: vec![Some(1), None, Some(3)], nested: Some(None), empty: Some(vec![])};
+    let toml = options.serialize_toml();
+    assert!(toml.contains("v = [[1], [], [3]]"));
+    assert!(toml.contains("nested = []"));
+    assert!(toml.contains("empty = []"));
+    assert_eq!(TomlOptions::deserialize_toml(&toml).unwrap(), options);
+    let options = TomlOptions {v: vec![], nested: None, empty: None};
+    assert_eq!(TomlOptions::deserialize_toml(&options.serialize_toml()).unwrap(), options);
+    assert!(TomlOptions::deserialize_toml("v = [[3, 4]]").is_err());
+    assert!(TomlOptions::deserialize_toml("v = [3]").is_err());
+}
+
+#[test]
+fn std_types_errors() {
+    assert!(char::deserialize_json("\"ab\"").is_err());
+    assert!(u128::deserialize_json("-1").is_err());
+    assert!(i128::deserialize_json("1.5").is_err());
+    assert_eq!(u64::deserialize_json("18446744073709551615").unwrap(), u64::MAX);
+    assert!(u64::deserialize_json("18446744073709551616").is_err());
+    assert_eq!(Vec::<u32>::new().serialize_json(), "[]");
+    assert_eq!(BTreeMap::<String, u32>::new().serialize_json(), "{}");
+    assert_eq!(Option::<u32>::None.serialize_json(), "null");
+}
@@ -0,0 +1,150 @@
+use makepad_microserde::*;
+use std::path::{Path, PathBuf};
+
+fn find_cargo_tomls(dir: &Path, out: &mut Vec<PathBuf>) {
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            if path.is_dir() {
+                if name != "target" && name != ".git" {
+                    find_cargo_tomls(&path, out);
+                }
+            }
+            else if name == "Cargo.toml" {
+                out.push(path);
+            }
+        }
+    }
+}
+
+#[test]
+fn parse_repo_cargo_tomls() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let mut files = Vec::new();
+    find_cargo_tomls(&root, &mut files);
+    assert!(files.len() > 10);
+    for file in files {
+        let data = std::fs::read_to_string(&file).unwrap();
+        let toml = match TomlParser::parse(&data) {
+            Ok(toml) => toml,
+            Err(e) => panic!("{}: {:?}", file.display(), e)
+        };
+        assert!(toml.path("package.name").is_some() || toml.path("workspace.members").is_some(), "{}", file.display());
+    }
+    let workspace = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    let toml = TomlParser::parse(&workspace).unwrap();
+    let members = toml.path("workspace.members").and_then( | m | m.as_array()).unwrap();
+    assert!(members.iter().any( | m | m.as_str() == Some("makepad")));
+}
+
+#[test]
+fn tables_and_dotted_keys() {
+    let toml = TomlParser::parse(r#"
+# a comment
+title = "makepad"
+site."google.com" = true
+
+[package]
+name = "makepad-hub" # trailing comment
+version = '0.1.0'
+
+[dependencies]
+makepad-microserde = { path = "../../render/microserde", features = ["a", "b"] }
+
+[target.'cfg(windows)'.dependencies.winapi]
+version = "0.3"
+"#).unwrap();
+    assert_eq!(toml["title"].as_str(), Some("makepad"));
+    assert_eq!(toml.path(r#"site["google.com"]"#), Some(&Toml::Bool(true)));
+    assert_eq!(toml.path("package.version").and_then( | v | v.as_str()), Some("0.1.0"));
+    assert_eq!(toml.path(r#"dependencies["makepad-microserde"].features[1]"#).and_then( | v | v.as_str()), Some("b"));
+    assert_eq!(toml.path(r#"target["cfg(windows)"].dependencies.winapi.version"#).and_then( | v | v.as_str()), Some("0.3"));
+}
+
+#[test]
+fn arrays_of_tables() {
+    let toml = TomlParser::parse(r#"
+[[bin]]
+name = "a"
+[bin.meta]
+x = 1
+[[bin]]
+name = "b"
+[[bin.sub]]
+y = 2
+"#).unwrap();
+    assert_eq!(toml.path("bin[0].name").and_then( | v | v.as_str()), Some("a"));
+    assert_eq!(toml.path("bin[0].meta.x").and_then( | v | v.as_i64()), Some(1));
+    assert_eq!(toml.path("bin[1].name").and_then( | v | v.as_str()), Some("b"));
+    assert_eq!(toml.path("bin[1].sub[0].y").and_then( | v | v.as_i64()), Some(2));
+}
+
+#[test]
+fn strings_and_numbers() {
+    let toml = TomlParser::parse("
+a = \"\"\"
+one \\
+    two\"\"\"\"
+b = '''
+raw \\n ''line'''
+c = \"tab\\t\\u00e9\\U0001F600\"
+d = [
+  1_000, # first
+  0xff, 0o17, 0b101,
+  -17,
+]
+e = [6.5e-1, +inf, 1e3]
+").unwrap();
+    assert_eq!(toml["a"].as_str(), Some("one two\""));
+    assert_eq!(toml["b"].as_str(), Some("raw \\n ''line"));
+    assert_eq!(toml["c"].as_str(), Some("tab\t\u{e9}\u{1F600}"));
+    assert_eq!(toml["d"], Toml::Array(vec![Toml::Int(1000), Toml::Int(255), Toml::Int(15), Toml::Int(5), Toml::Int(-17)]));
+    assert_eq!(toml.path("e[0]").and_then( | v | v.as_f64()), Some(0.65));
+    assert_eq!(toml.path("e[1]").and_then( | v | v.as_f64()), Some(std::f64::INFINITY));
+    assert_eq!(toml.path("e[2]"), Some(&Toml::Num(1000.0)));
+}
+
+#[test]
+fn datetimes() {
+    let toml = TomlParser::parse("
+odt = 1979-05-27T07:32:00.5-07:00
+ldt = 1979-05-27 07:32:00
+ld = 1979-05-27
+lt = 00:32:00.999999
+z = 2020-02-29T00:00:00Z
+").unwrap();
+    let date = | key: &str | if let Toml::Date(d) = &toml[key] {d.to_string()} else {panic!()};
+    assert_eq!(date("odt"), "1979-05-27T07:32:00.5-07:00");
+    assert_eq!(date("ldt"), "1979-05-27T07:32:00");
+    assert_eq!(date("ld"), "1979-05-27");
+    assert_eq!(date("lt"), "00:32:00.999999");
+    assert_eq!(date("z"), "2020-02-29T00:00:00Z");
+    assert!(TomlParser::parse("a = 2021-02-29").is_err());
+}
+
+#[test]
+fn errors() {
+    let err = TomlParser::parse("[a]\nb = 1\n[a]\n").unwrap_err();
+    assert_eq!((err.line, err.col), (2, 0));
+    let err = TomlParser::parse("a = 1\n  b = 01\n").unwrap_err();
+    assert_eq!(err.line, 1);
+    assert!(TomlParser::parse("a = 1\na = 2").is_err());
+    assert!(TomlParser::parse("a = {b = 1}\n[a]").is_err());
+    assert!(TomlParser::parse("a = [1]\n[[a]]").is_err());
+    assert!(TomlParser::parse("[a]\nb.c = 1\n[a.b]").is_err());
+    assert!(TomlParser::parse("a = {b = 1,}").is_err());
+    assert!(TomlParser::parse("a = \"open").is_err());
+    assert!(TomlParser::parse("a = 1 b = 2").is_err());
+    assert!(TomlParser::parse("[a.b]\n[a]\nc = 1").is_ok());
+    
+    // line and col are 0 based, the rendered source and Debug show them 1 based
+    let source = "a = 1\nb = = 2\n";
+    let err = TomlParser::parse(source).unwrap_err();
+    assert!(format!("{:?}", err).contains("line:2 col:5"));
+    let out = err.render("x.toml", source);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[1], " --> x.toml:2:5");
+    assert_eq!(lines[3], "2 | b = = 2");
+    assert_eq!(lines[4], "  |     ^");
+}
@@ -0,0 +1,104 @@
+use makepad_microserde::*;
+
+const METADATA: &str = r#"{
+    "packages": [
+        {"name": "makepad-hub", "version": "0.1.0", "targets": [{"kind": ["lib"], "name": "makepad_hub"}]},
+        {"name": "makepad", "version": "0.2.0", "targets": []}
+    ],
+    "workspace_root": "/home/makepad",
+    "resolve": null,
+    "version": 1,
+    "offset": -3,
+    "ratio": 0.5,
+    "members": {"makepad": true}
+}"#;
+
+#[test]
+fn json_value_lookup() {
+    let v = JsonValue::deserialize_json(METADATA).unwrap();
+    assert_eq!(v["workspace_root"].as_str(), Some("/home/makepad"));
+    assert_eq!(v["packages"][1]["name"].as_str(), Some("makepad"));
+    assert!(v["resolve"].is_null());
+    assert!(v["nonexistent"][3].is_null());
+    assert_eq!(v.path("packages[0].targets[0].kind[0]").and_then( | v | v.as_str()), Some("lib"));
+    assert_eq!(v.path(r#"members["makepad"]"#), Some(&JsonValue::Bool(true)));
+    assert_eq!(v.path("offset").and_then( | v | v.as_i64()), Some(-3));
+    assert_eq!(v.path("ratio").and_then( | v | v.as_f64()), Some(0.5));
+    assert_eq!(v.path("packages[2]"), None);
+}
+
+#[test]
+fn json_value_roundtrip() {
+    let v = JsonValue::deserialize_json(METADATA).unwrap();
+    let out = v.serialize_json();
+    assert_eq!(JsonValue::deserialize_json(&out).unwrap(), v);
+    assert_eq!(JsonValue::Object(vec![("a".to_string(), JsonValue::Array(vec![]))]).serialize_json(), r#"{"a":[]}"#);
+}
+
+#[derive(DeJson, SerJson)]
+struct Message {
+    reason: String,
+    #[nserde(flatten)]
+    rest: JsonValue,
+}
+
+#[test]
+fn json_value_flatten_collects_unknown() {
+    let m = Message::deserialize_json(r#"{"reason":"build-finished","success":true,"extra":[1]}"#).unwrap();
+    assert_eq!(m.reason, "build-finished");
+    assert_eq!(m.rest["success"], JsonValue::Bool(true));
+    assert_eq!(m.rest.path("extra[0]").and_then( | v | v.as_u64()), Some(1));
+    assert_eq!(m.serialize_json(), r#"{"reason":"build-finished","success":true,"extra":[1],}"#);
+    
+    let m = Message::deserialize_json(r#"{"reason":"x","a\"b":1}"#).unwrap();
+    let out = m.serialize_json();
+    assert_eq!(out, r#"{"reason":"x","a\"b":1,}"#);
+    assert_eq!(Message::deserialize_json(&out).unwrap().rest["a\"b"], JsonValue::U64(1));
+}
+
+const SETTINGS: &str = r#"(
+    build_on_save:true,
+    hub_server:Offline,
+    builders:{
+        "main":(
+            http_server:Localhost(8000),
+            workspaces:{"makepad":"."},
+        ),
+    },
+    builds:[(builder:"main", package:"webgl_example_wasm")],
+    key:'x',
+    off:None,
+)"#;
+
+#[test]
+fn ron_value_lookup() {
+    let v = RonValue::deserialize_ron(SETTINGS).unwrap();
+    assert_eq!(v["build_on_save"], RonValue::Bool(true));
+    assert_eq!(v["hub_server"].name(), Some("Offline"));
+    assert_eq!(v.path(r#"builders["main"].workspaces["makepad"]"#).and_then( | v | v.as_str()), Some("."));
+    assert_eq!(v.path(r#"builders.main.http_server"#), Some(&RonValue::Tuple(Some("Localhost".to_string()), vec![RonValue::U64(8000)])));
+    assert_eq!(v.path("builds[0].package").and_then( | v | v.as_str()), Some("webgl_example_wasm"));
+    assert_eq!(v["key"], RonValue::Char('x'));
+    assert_eq!(v["off"], RonValue::Ident("None".to_string()));
+    assert!(v["nonexistent"][3].is_unit());
+    assert!(RonValue::deserialize_ron("()").unwrap().is_unit());
+    assert_eq!(RonValue::deserialize_ron("(a: ())").unwrap()["a"], RonValue::Tuple(None, vec![]));
+}
+
+#[test]
+fn ron_value_roundtrip() {
+    let v = RonValue::deserialize_ron(SETTINGS).unwrap();
+    let out = v.serialize_ron();
+    assert_eq!(RonValue::deserialize_ron(&out).unwrap(), v);
+}
+
+#[test]
+fn value_path_parse() {
+    assert_eq!(parse_value_path(r#"a.b[2]["c.d"]"#), Some(vec![
+        ValuePathSeg::Key("a".to_string()),
+        ValuePathSeg::Key("b".to_string()),
+        ValuePathSeg::Index(2),
+        ValuePathSeg::Key("c.d".to_string()),
+    ]));
+    assert_eq!(parse_value_path("a[x]"), None);
+}
@@ -0,0 +1,138 @@
+use makepad_microserde::*;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerToml, DeToml, PartialEq, Debug)]
+struct Settings {
+    name: String,
+    port: u16,
+    offset: i32,
+    proxy: Option<String>,
+    builders: BTreeMap<String, Builder>,
+    dock: Vec<Dock>,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerToml, DeToml, PartialEq, Debug)]
+struct Builder {
+    workspaces: Vec<String>,
+    ratio: f64,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerToml, DeToml, PartialEq, Debug)]
+enum Dock {
+    Empty,
+    Single(String),
+    Split(u32, u32),
+    Tabs {current: usize, tabs: Vec<String>},
+}
+
+fn settings() -> Settings {
+    let mut builders = BTreeMap::new();
+    builders.insert("main".to_string(), Builder {workspaces: vec!["makepad".to_string()], ratio: 0.5});
+    Settings {
+        name: "edit".to_string(),
+        port: 7243,
+        offset: -3,
+        proxy: None,
+        builders,
+        dock: vec![Dock::Empty, Dock::Single("log".to_string()), Dock::Split(1, 2), Dock::Tabs {current: 1, tabs: vec!["a".to_string(), "b".to_string()]}],
+    }
+}
+
+#[test]
+fn convert_between_formats() {
+    let ron = RonValue::deserialize_ron(&settings().serialize_ron()).unwrap();
+
+    let json = JsonValue::from(ron.clone());
+    assert_eq!(json["dock"][0], JsonValue::Object(vec![("Empty".to_string(), JsonValue::Array(vec![]))]));
+    assert_eq!(Settings::deserialize_json(&json.serialize_json()).unwrap(), settings());
+    assert_eq!(Settings::deserialize_json(&json.serialize_json_pretty()).unwrap(), settings());
+    // the builders map comes back from json as a struct, json doesn't tell them apart
+    let back = RonValue::from(json.clone());
+    assert_eq!(back["builders"], RonValue::Struct(None, vec![("main".to_string(), ron["builders"]["main"].clone())]));
+    assert_eq!(back["dock"], ron["dock"]);
+    assert_eq!(JsonValue::from(back), json);
+
+    // None fields are left out of the toml tables
+    let toml = Toml::try_from(ron.clone()).unwrap();
+    assert_eq!(Settings::deserialize_toml(&toml.serialize_toml()).unwrap(), settings());
+    assert_eq!(Settings::deserialize_toml(&toml.serialize_toml_compact()).unwrap(), settings());
+    // the unit variant toml wrote as a string is read as one next to the other variants
+    let back = RonValue::from(toml.clone());
+    assert_eq!(back["dock"], ron["dock"]);
+    assert_eq!(Vec::<Dock>::deserialize_ron(&back["dock"].serialize_ron()).unwrap(), settings().dock);
+    assert_eq!(Toml::try_from(back).unwrap(), toml);
+
+    assert_eq!(Settings::deserialize_ron(&ron.serialize_ron_compact()).unwrap(), settings());
+    assert!(!ron.serialize_ron_compact().contains('\n'));
+}
+
+#[test]
+fn convert_keys_and_errors() {
+    // non identifier keys can only be a map, a lone capitalised key holding data is a variant
+    let json = JsonValue::deserialize_json(r#"{"a b":1,"c":{"Pos":[1,-2]},"d":{"Pos":3}}"#).unwrap();
+    let ron = RonValue::from(json);
+    assert!(matches!(ron, RonValue::Map(_)));
+    assert_eq!(ron["d"], RonValue::Struct(None, vec![("Pos".to_string(), RonValue::U64(3))]));
+    assert_eq!(ron["c"], RonValue::Tuple(Some("Pos".to_string()), vec![RonValue::U64(1), RonValue::I64(-2)]));
+
+    // the unit value is an empty JSON array, an empty JSON object stays one through RON
+    assert_eq!(JsonValue::from(RonValue::deserialize_ron("()").unwrap()), JsonValue::Array(vec![]));
+    let ron = RonValue::from(JsonValue::deserialize_json("{}").unwrap()).serialize_ron();
+    assert_eq!(JsonValue::from(RonValue::deserialize_ron(&ron).unwrap()), JsonValue::Object(vec![]));
+
+    let ron = RonValue::deserialize_ron("(a: [1, None])").unwrap();
+    let err = Toml::try_from(ron).unwrap_err();
+    assert_eq!(err.path.to_string(), "a[1]");
+    let err = Toml::try_from(RonValue::deserialize_ron("(a: 9223372036854775808)").unwrap()).unwrap_err();
+    assert_eq!(err.path.to_string(), "a");
+    assert!(err.msg.contains("9223372036854775808"));
+
+    // a string is only a unit variant next to other variants
+    let toml = TomlParser::parse("a = [\"Empty\", {Split = [1, 2]}]\nb = [\"Empty\"]").unwrap();
+    let ron = RonValue::from(toml);
+    assert_eq!(ron["a"][0], RonValue::Ident("Empty".to_string()));
+    assert_eq!(ron["b"][0], RonValue::String("Empty".to_string()));
+}
+
+#[test]
+fn convert_bin_roundtrip() {
+    let ron = RonValue::deserialize_ron(&settings().serialize_ron()).unwrap();
+    assert_eq!(RonValue::deserialize_bin(&ron.serialize_bin()).unwrap(), ron);
+    assert_eq!(RonValue::deserialize_bin_compact(&ron.serialize_bin_compact()).unwrap(), ron);
+    assert!(ron.serialize_bin_compact().len() < ron.serialize_bin().len());
+    assert_eq!(RonValue::deserialize_bin(&[11]).unwrap_err().msg, "RonValue kind 11");
+}
+
+#[test]
+fn convert_cli() {
+    let dir = std::env::temp_dir().join(format!("microserde_cli_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let ron = dir.join("settings.ron");
+    let json = dir.join("settings.json");
+    std::fs::write(&ron, settings().serialize_ron()).unwrap();
+
+    let run = | args: &[&str] | std::process::Command::new(env!("CARGO_BIN_EXE_microserde")).args(args).output().unwrap();
+    let out = run(&[ron.to_str().unwrap(), "-o", json.to_str().unwrap(), "--compact"]);
+    assert!(out.status.success());
+    let text = std::fs::read_to_string(&json).unwrap();
+    assert_eq!(text.lines().count(), 1);
+    assert_eq!(Settings::deserialize_json(&text).unwrap(), settings());
+
+    std::fs::write(&json, "{\"name\":}").unwrap();
+    let out = run(&[json.to_str().unwrap(), "--to", "toml"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("settings.json:1:9"));
+
+    // bin and bin-compact are named, a .bin file is read as the fixed encoding
+    let bin = dir.join("settings.bin");
+    let out = run(&[ron.to_str().unwrap(), "--to", "bin-compact", "-o", bin.to_str().unwrap()]);
+    assert!(out.status.success());
+    let data = std::fs::read(&bin).unwrap();
+    assert_eq!(RonValue::deserialize_bin_compact(&data).unwrap(), RonValue::deserialize_ron(&settings().serialize_ron()).unwrap());
+    let out = run(&[bin.to_str().unwrap(), "--from", "bin-compact", "--to", "ron"]);
+    assert_eq!(Settings::deserialize_ron(&String::from_utf8_lossy(&out.stdout)).unwrap(), settings());
+    let out = run(&[bin.to_str().unwrap(), "--to", "ron"]);
+    assert_eq!(out.status.code(), Some(1));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -189,6 +189,7 @@ impl Default for Cx {
                 format: TextureFormat::ImageBGRA,
                 width: Some(4),
                 height: Some(4),
+                depth: None,
                 multisample: None
             },
             image_u32: vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
@@ -13,6 +13,7 @@ use std::ffi;
 
 use makepad_live_compiler::generate_hlsl;
 use makepad_live_compiler::analyse::ShaderCompileOptions;
+use makepad_live_compiler::optimise::ShaderOptimisations;
 use makepad_live_compiler::shaderast::ShaderAst;
 //use std::ffi::c_void;
 //use std::sync::Mutex;
@@ -140,6 +141,31 @@ impl Cx {
                             }
                             d3d11_cx.set_shader_resource(i, &cxtexture.platform.shader_resource);
                         },
+                        TextureFormat::ImageCubeBGRA => {
+                            if cxtexture.update_image {
+                                cxtexture.update_image = false;
+                                d3d11_cx.update_platform_texture_image_cube_bgra(
+                                    &mut cxtexture.platform,
+                                    cxtexture.desc.width.unwrap(),
+                                    cxtexture.desc.height.unwrap(),
+                                    &cxtexture.image_u32,
+                                );
+                            }
+                            d3d11_cx.set_shader_resource(i, &cxtexture.platform.shader_resource);
+                        },
+                        TextureFormat::ImageVolumeBGRA => {
+                            if cxtexture.update_image {
+                                cxtexture.update_image = false;
+                                d3d11_cx.update_platform_texture_image_volume_bgra(
+                                    &mut cxtexture.platform,
+                                    cxtexture.desc.width.unwrap(),
+                                    cxtexture.desc.height.unwrap(),
+                                    cxtexture.desc.depth.unwrap_or(1),
+                                    &cxtexture.image_u32,
+                                );
+                            }
+                            d3d11_cx.set_shader_resource(i, &cxtexture.platform.shader_resource);
+                        },
                         
                         TextureFormat::RenderBGRA | TextureFormat::RenderBGRAf16 | TextureFormat::RenderBGRAf32 => {
                             d3d11_cx.set_shader_resource(i, &cxtexture.platform.shader_resource);
@@ -297,7 +323,8 @@ impl Cx {
         let options = ShaderCompileOptions {
             gather_all: false,
             create_const_table: false,
-            no_const_collapse: false
+            no_const_collapse: false,
+            optimisations: ShaderOptimisations::NONE,
         };
         
         for (live_id, shader) in &self.live_styles.shader_alloc {
@@ -331,7 +358,8 @@ impl Cx {
         let options = ShaderCompileOptions {
             gather_all: true,
             create_const_table: true,
-            no_const_collapse: false
+            no_const_collapse: false,
+            optimisations: ShaderOptimisations::NONE,
         };
         
         for (live_id, change) in &self.live_styles.changed_shaders {
@@ -1339,6 +1367,102 @@ impl D3d11Cx {
         }
     }
     
+    pub fn update_platform_texture_image_cube_bgra(&self, res: &mut CxPlatformTexture, width: usize, height: usize, image_u32: &Vec<u32>) {
+        
+        if image_u32.len() != width * height * 6 {
+            println!("update_platform_texture_image_cube_bgra with wrong buffer_u32 size!");
+            return;
+        }
+        
+        let mut sub_data = Vec::new();
+        for face in 0..6 {
+            sub_data.push(d3d11::D3D11_SUBRESOURCE_DATA {
+                pSysMem: image_u32[face * width * height..].as_ptr() as *const _,
+                SysMemPitch: (width * 4) as u32,
+                SysMemSlicePitch: 0
+            });
+        }
+        
+        let texture_desc = d3d11::D3D11_TEXTURE2D_DESC {
+            Width: width as u32,
+            Height: height as u32,
+            MipLevels: 1,
+            ArraySize: 6,
+            Format: dxgiformat::DXGI_FORMAT_R8G8B8A8_UNORM,
+            SampleDesc: dxgitype::DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0
+            },
+            Usage: d3d11::D3D11_USAGE_DEFAULT,
+            BindFlags: d3d11::D3D11_BIND_SHADER_RESOURCE,
+            CPUAccessFlags: 0,
+            MiscFlags: d3d11::D3D11_RESOURCE_MISC_TEXTURECUBE,
+        };
+        let mut texture = ptr::null_mut();
+        let hr = unsafe {self.device.CreateTexture2D(&texture_desc, sub_data.as_ptr(), &mut texture as *mut *mut _)};
+        if winerror::SUCCEEDED(hr) {
+            // the default view of a TEXTURECUBE resource is a cube view
+            let mut shader_resource = ptr::null_mut();
+            unsafe {self.device.CreateShaderResourceView(
+                texture as *mut _,
+                ptr::null(),
+                &mut shader_resource as *mut *mut _
+            )};
+            res.width = width;
+            res.height = height;
+            res.texture = Some(unsafe {ComPtr::from_raw(texture as *mut _)});
+            res.shader_resource = Some(unsafe {ComPtr::from_raw(shader_resource as *mut _)});
+        }
+        else {
+            panic!("update_platform_texture_image_cube_bgra failed");
+        }
+    }
+    
+    pub fn update_platform_texture_image_volume_bgra(&self, res: &mut CxPlatformTexture, width: usize, height: usize, depth: usize, image_u32: &Vec<u32>) {
+        
+        if image_u32.len() != width * height * depth {
+            println!("update_platform_texture_image_volume_bgra with wrong buffer_u32 size!");
+            return;
+        }
+        
+        let sub_data = d3d11::D3D11_SUBRESOURCE_DATA {
+            pSysMem: image_u32.as_ptr() as *const _,
+            SysMemPitch: (width * 4) as u32,
+            SysMemSlicePitch: (width * height * 4) as u32
+        };
+        
+        let texture_desc = d3d11::D3D11_TEXTURE3D_DESC {
+            Width: width as u32,
+            Height: height as u32,
+            Depth: depth as u32,
+            MipLevels: 1,
+            Format: dxgiformat::DXGI_FORMAT_R8G8B8A8_UNORM,
+            Usage: d3d11::D3D11_USAGE_DEFAULT,
+            BindFlags: d3d11::D3D11_BIND_SHADER_RESOURCE,
+            CPUAccessFlags: 0,
+            MiscFlags: 0,
+        };
+        let mut texture = ptr::null_mut();
+        let hr = unsafe {self.device.CreateTexture3D(&texture_desc, &sub_data, &mut texture as *mut *mut _)};
+        if winerror::SUCCEEDED(hr) {
+            let mut shader_resource = ptr::null_mut();
+            unsafe {self.device.CreateShaderResourceView(
+                texture as *mut _,
+                ptr::null(),
+                &mut shader_resource as *mut *mut _
+            )};
+            res.width = width;
+            res.height = height;
+            // a 3D texture isn't an ID3D11Texture2D, so it is kept as a plain resource
+            res.texture = None;
+            res.d3d11_resource = Some(unsafe {ComPtr::from_raw(texture as *mut _)});
+            res.shader_resource = Some(unsafe {ComPtr::from_raw(shader_resource as *mut _)});
+        }
+        else {
+            panic!("update_platform_texture_image_volume_bgra failed");
+        }
+    }
+    
     
 }
 
@@ -8,6 +8,7 @@ use makepad_objc_sys::runtime::YES;
 
 use makepad_live_compiler::generate_metal;
 use makepad_live_compiler::analyse::ShaderCompileOptions;
+use makepad_live_compiler::optimise::ShaderOptimisations;
 use makepad_live_compiler::shaderast::ShaderAst;
 
 //use metal::*;
@@ -518,6 +519,60 @@ impl MetalCx {
                         ]};
                     }
                 },
+                TextureFormat::ImageCubeBGRA => {
+                    unsafe {
+                        let () = msg_send![mdesc, setTextureType: MTLTextureType::Cube];
+                        let () = msg_send![mdesc, setPixelFormat: MTLPixelFormat::BGRA8Unorm];
+                    }
+                    if cxtexture.image_u32.len() != width * height * 6 {
+                        println!("update_platform_texture_image2d with wrong cube buffer_u32 size!");
+                        return;
+                    }
+                    let tex: id = unsafe {msg_send![self.device, newTextureWithDescriptor: mdesc]};
+                    cxtexture.platform.mtl_texture = Some(tex);
+                    let region = MTLRegion {
+                        origin: MTLOrigin {x: 0, y: 0, z: 0},
+                        size: MTLSize {width: width as u64, height: height as u64, depth: 1}
+                    };
+                    for face in 0..6 {
+                        let () = unsafe {msg_send![
+                            tex,
+                            replaceRegion: region
+                            mipmapLevel: 0
+                            slice: face as u64
+                            withBytes: cxtexture.image_u32[face * width * height..].as_ptr() as *const std::ffi::c_void
+                            bytesPerRow: (width * std::mem::size_of::<u32>()) as u64
+                            bytesPerImage: (width * height * std::mem::size_of::<u32>()) as u64
+                        ]};
+                    }
+                },
+                TextureFormat::ImageVolumeBGRA => {
+                    let depth = cxtexture.desc.depth.unwrap_or(1);
+                    unsafe {
+                        let () = msg_send![mdesc, setTextureType: MTLTextureType::D3];
+                        let () = msg_send![mdesc, setDepth: depth as u64];
+                        let () = msg_send![mdesc, setPixelFormat: MTLPixelFormat::BGRA8Unorm];
+                    }
+                    if cxtexture.image_u32.len() != width * height * depth {
+                        println!("update_platform_texture_image2d with wrong volume buffer_u32 size!");
+                        return;
+                    }
+                    let tex: id = unsafe {msg_send![self.device, newTextureWithDescriptor: mdesc]};
+                    cxtexture.platform.mtl_texture = Some(tex);
+                    let region = MTLRegion {
+                        origin: MTLOrigin {x: 0, y: 0, z: 0},
+                        size: MTLSize {width: width as u64, height: height as u64, depth: depth as u64}
+                    };
+                    let () = unsafe {msg_send![
+                        tex,
+                        replaceRegion: region
+                        mipmapLevel: 0
+                        slice: 0u64
+                        withBytes: cxtexture.image_u32.as_ptr() as *const std::ffi::c_void
+                        bytesPerRow: (width * std::mem::size_of::<u32>()) as u64
+                        bytesPerImage: (width * height * std::mem::size_of::<u32>()) as u64
+                    ]};
+                },
                 _ => {
                     println!("update_platform_texture_image2d with unsupported format");
                     return;
@@ -766,7 +821,8 @@ impl Cx {
         let options = ShaderCompileOptions {
             gather_all: false,
             create_const_table: false,
-            no_const_collapse: false
+            no_const_collapse: false,
+            optimisations: ShaderOptimisations::NONE,
         };
         
         for (live_id, shader) in &self.live_styles.shader_alloc {
@@ -799,7 +855,8 @@ impl Cx {
         let options = ShaderCompileOptions {
             gather_all: true,
             create_const_table: true,
-            no_const_collapse: false
+            no_const_collapse: false,
+            optimisations: ShaderOptimisations::NONE,
         };
         
         for (live_id, change) in &self.live_styles.changed_shaders {
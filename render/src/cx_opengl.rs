@@ -8,6 +8,7 @@ use std::ptr;
 use std::mem;
 use makepad_live_compiler::generate_glsl;
 use makepad_live_compiler::analyse::ShaderCompileOptions;
+use makepad_live_compiler::optimise::ShaderOptimisations;
 use makepad_live_compiler::shaderast::ShaderAst;
 
 impl Cx {
@@ -158,11 +159,12 @@ impl Cx {
                         }
                         // get the loc
                         gl::ActiveTexture(gl::TEXTURE0 + i as u32);
+                        let target = OpenglCx::texture_target(&cxtexture.desc.format);
                         if let Some(texture) = cxtexture.platform.gl_texture {
-                            gl::BindTexture(gl::TEXTURE_2D, texture);
+                            gl::BindTexture(target, texture);
                         }
                         else {
-                            gl::BindTexture(gl::TEXTURE_2D, 0);
+                            gl::BindTexture(target, 0);
                         }
                     }
                     
@@ -603,11 +605,15 @@ impl Cx {
         let options = ShaderCompileOptions {
             gather_all: false,
             create_const_table: false,
-            no_const_collapse: false
+            no_const_collapse: false,
+            optimisations: ShaderOptimisations::NONE,
         };
         
         for (live_id, shader) in &self.live_styles.shader_alloc {
-            match self.live_styles.collect_and_analyse_shader(*live_id, options) {
+            match self.live_styles.collect_and_analyse_shader(*live_id, options).and_then( | (shader_ast, default_geometry) | {
+                generate_glsl::check_shader_support(&shader_ast, generate_glsl::GlslVersion::Es100).map_err( | err | self.live_styles.live_error_to_live_body_error(err)) ?;
+                Ok((shader_ast, default_geometry))
+            }) {
                 Err(err) => {
                     eprintln!("{}", err);
                     panic!()
@@ -637,13 +643,17 @@ impl Cx {
         let options = ShaderCompileOptions {
             gather_all: true,
             create_const_table: true,
-            no_const_collapse: false
+            no_const_collapse: false,
+            optimisations: ShaderOptimisations::NONE,
         };
         
         for (live_id, change) in &self.live_styles.changed_shaders {
             match change {
                 LiveChangeType::Recompile => {
-                    match self.live_styles.collect_and_analyse_shader(*live_id, options) {
+                    match self.live_styles.collect_and_analyse_shader(*live_id, options).and_then( | (shader_ast, default_geometry) | {
+                        generate_glsl::check_shader_support(&shader_ast, generate_glsl::GlslVersion::Es100).map_err( | err | self.live_styles.live_error_to_live_body_error(err)) ?;
+                        Ok((shader_ast, default_geometry))
+                    }) {
                         Err(err) => {
                             errors.push(err);
                         },
@@ -1017,6 +1027,13 @@ impl OpenglCx {
         
     }
     
+    pub fn texture_target(format: &TextureFormat) -> u32 {
+        match format {
+            TextureFormat::ImageCubeBGRA => gl::TEXTURE_CUBE_MAP,
+            _ => gl::TEXTURE_2D
+        }
+    }
+    
     pub fn update_platform_texture_image2d(&self, cxtexture: &mut CxTexture) {
         
         if cxtexture.desc.width.is_none() || cxtexture.desc.height.is_none() {
@@ -1048,12 +1065,31 @@ impl OpenglCx {
                     gl_texture_old
                 }
             };
+            let target = OpenglCx::texture_target(&cxtexture.desc.format);
             unsafe {
-                gl::BindTexture(gl::TEXTURE_2D, gl_texture);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-                gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA as i32, width as i32, height as i32, 0, gl::RGBA, gl::UNSIGNED_BYTE, cxtexture.image_u32.as_ptr() as *const _);
-                gl::BindTexture(gl::TEXTURE_2D, 0);
+                gl::BindTexture(target, gl_texture);
+                gl::TexParameteri(target, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+                gl::TexParameteri(target, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+                match cxtexture.desc.format {
+                    TextureFormat::ImageCubeBGRA => {
+                        if cxtexture.image_u32.len() != width * height * 6 {
+                            println!("update_platform_texture_image2d with wrong cube buffer_u32 size!");
+                        }
+                        else {
+                            for face in 0..6 {
+                                gl::TexImage2D(gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32, 0, gl::RGBA as i32, width as i32, height as i32, 0, gl::RGBA, gl::UNSIGNED_BYTE, cxtexture.image_u32[face * width * height..].as_ptr() as *const _);
+                            }
+                        }
+                    }
+                    TextureFormat::ImageVolumeBGRA => {
+                        // shaders are generated as GLSL ES 1.00, which has no texture3D to sample it with
+                        println!("update_platform_texture_image2d with a volume texture, OpenGL has no texture3D");
+                    }
+                    _ => {
+                        gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA as i32, width as i32, height as i32, 0, gl::RGBA, gl::UNSIGNED_BYTE, cxtexture.image_u32.as_ptr() as *const _);
+                    }
+                }
+                gl::BindTexture(target, 0);
             }
         }
         
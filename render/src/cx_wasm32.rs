@@ -945,6 +945,8 @@ impl FromWasm {
                 Ty::Float => "float",
                 Ty::Mat4 => "mat4",
                 Ty::Texture2D => "sampler2D",
+                Ty::TextureCube => "samplerCube",
+                // texture3D is rejected by check_shader_support before a shader gets here
                 _ => panic!("unexpected type in add_propdefvec")
             });
             self.add_string(&prop_def.name);
@@ -1059,13 +1061,15 @@ impl FromWasm {
     }
     
     pub fn update_texture_image2d(&mut self, texture_id: usize, texture: &mut CxTexture) {
-        //usize, width: usize, height: usize, data: &Vec<u32>
-        self.fit(5);
+        //usize, width: usize, height: usize, data: &Vec<u32>, is_cube: bool
+        // a cube texture holds its six faces one after the other
+        self.fit(6);
         self.mu32(9);
         self.mu32(texture_id as u32);
         self.mu32(texture.desc.width.unwrap() as u32);
         self.mu32(texture.desc.height.unwrap() as u32);
-        self.mu32(texture.image_u32.as_ptr() as u32)
+        self.mu32(texture.image_u32.as_ptr() as u32);
+        self.mu32(if let TextureFormat::ImageCubeBGRA = texture.desc.format {1} else {0})
     }
     
     pub fn request_animation_frame(&mut self) {
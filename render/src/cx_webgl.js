@@ -1769,18 +1769,29 @@
             gl.bindBuffer(gl.ELEMENT_ARRAY_BUFFER, null);
         }
         
-        alloc_texture(texture_id, width, height, data_ptr) {
+        alloc_texture(texture_id, width, height, data_ptr, is_cube) {
             var gl = this.gl;
             var gl_tex = this.textures[texture_id] || gl.createTexture()
-            
-            gl.bindTexture(gl.TEXTURE_2D, gl_tex)
-            gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MAG_FILTER, gl.LINEAR)
-            gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MIN_FILTER, gl.LINEAR)
-            gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_S, gl.CLAMP_TO_EDGE)
-            gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_T, gl.CLAMP_TO_EDGE)
-            
-            let data = new Uint8Array(this.memory.buffer, data_ptr, width * height * 4);
-            gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA, width, height, 0, gl.RGBA, gl.UNSIGNED_BYTE, data);
+            var target = is_cube? gl.TEXTURE_CUBE_MAP: gl.TEXTURE_2D;
+            
+            gl.bindTexture(target, gl_tex)
+            gl.texParameteri(target, gl.TEXTURE_MAG_FILTER, gl.LINEAR)
+            gl.texParameteri(target, gl.TEXTURE_MIN_FILTER, gl.LINEAR)
+            gl.texParameteri(target, gl.TEXTURE_WRAP_S, gl.CLAMP_TO_EDGE)
+            gl.texParameteri(target, gl.TEXTURE_WRAP_T, gl.CLAMP_TO_EDGE)
+            
+            if (is_cube) {
+                // the six faces follow each other in +x, -x, +y, -y, +z, -z order
+                let face_size = width * height * 4;
+                for (let face = 0; face < 6; face ++) {
+                    let data = new Uint8Array(this.memory.buffer, data_ptr + face * face_size, face_size);
+                    gl.texImage2D(gl.TEXTURE_CUBE_MAP_POSITIVE_X + face, 0, gl.RGBA, width, height, 0, gl.RGBA, gl.UNSIGNED_BYTE, data);
+                }
+            }
+            else {
+                let data = new Uint8Array(this.memory.buffer, data_ptr, width * height * 4);
+                gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA, width, height, 0, gl.RGBA, gl.UNSIGNED_BYTE, data);
+            }
             //gl.bindTexture(gl.TEXTURE_2D,0);
             this.textures[texture_id] = gl_tex;
         }
@@ -1886,7 +1897,7 @@
                 let tex_id = this.baseu32[(textures_ptr >> 2) + i];
                 let tex_obj = this.textures[tex_id];
                 gl.activeTexture(gl.TEXTURE0 + i);
-                gl.bindTexture(gl.TEXTURE_2D, tex_obj);
+                gl.bindTexture(tex_slot.ty == "samplerCube"? gl.TEXTURE_CUBE_MAP: gl.TEXTURE_2D, tex_obj);
                 gl.uniform1i(tex_slot.loc, i);
             }
             let indices = index_buffer.length;
@@ -2017,7 +2028,8 @@
             let width = self.mu32[self.parse ++];
             let height = self.mu32[self.parse ++];
             let data_ptr = self.mu32[self.parse ++];
-            self.alloc_texture(texture_id, width, height, data_ptr);
+            let is_cube = self.mu32[self.parse ++];
+            self.alloc_texture(texture_id, width, height, data_ptr, is_cube);
         },
         function request_animation_frame_10(self) {
             self.request_animation_frame()
@@ -2,6 +2,7 @@
 use crate::cx::*;
 use makepad_live_compiler::generate_glsl;
 use makepad_live_compiler::analyse::ShaderCompileOptions;
+use makepad_live_compiler::optimise::ShaderOptimisations;
 use makepad_live_compiler::shaderast::ShaderAst;
 
 impl Cx {
@@ -250,11 +251,15 @@ impl Cx {
         let options = ShaderCompileOptions {
             gather_all: !self.gpu_info.is_low_on_uniform_vectors(),
             create_const_table: false,
-            no_const_collapse: false
+            no_const_collapse: false,
+            optimisations: ShaderOptimisations::NONE,
         };
         
         for (live_id,_shader) in &self.live_styles.shader_alloc{
-            match self.live_styles.collect_and_analyse_shader(*live_id, options) {
+            match self.live_styles.collect_and_analyse_shader(*live_id, options).and_then( | (shader_ast, default_geometry) | {
+                generate_glsl::check_shader_support(&shader_ast, generate_glsl::GlslVersion::Es100).map_err( | err | self.live_styles.live_error_to_live_body_error(err)) ?;
+                Ok((shader_ast, default_geometry))
+            }) {
                 Err(err) => {
                     self.platform.from_wasm.log(&format!("{}", err))
                 },
@@ -279,13 +284,17 @@ impl Cx {
         let options = ShaderCompileOptions {
             gather_all: !self.gpu_info.is_low_on_uniform_vectors(),
             create_const_table: true,
-            no_const_collapse: false
+            no_const_collapse: false,
+            optimisations: ShaderOptimisations::NONE,
         };
         
         for (live_id, change) in &self.live_styles.changed_shaders {
             match change {
                 LiveChangeType::Recompile => {
-                    match self.live_styles.collect_and_analyse_shader(*live_id, options) {
+                    match self.live_styles.collect_and_analyse_shader(*live_id, options).and_then( | (shader_ast, default_geometry) | {
+                        generate_glsl::check_shader_support(&shader_ast, generate_glsl::GlslVersion::Es100).map_err( | err | self.live_styles.live_error_to_live_body_error(err)) ?;
+                        Ok((shader_ast, default_geometry))
+                    }) {
                         Err(err) => {
                             errors.push(err);
                         },
@@ -180,7 +180,7 @@ pub struct FileWriteEvent {
     error: Option<String>
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, SerBin, DeBin, SerJson, DeJson, SerRon, DeRon)]
 pub struct LiveRecompileEvent {
     pub changed_live_bodies: BTreeSet<LiveBodyId>,
     pub errors: Vec<LiveBodyError>
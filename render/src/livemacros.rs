@@ -14,7 +14,7 @@ impl Cx {
         let errors = self.process_live_styles_changes();
 
         for error in &errors{
-            eprintln!("{}", error);
+            eprint!("{}", error.rendered);
         }
         if errors.len()>0{
             panic!();
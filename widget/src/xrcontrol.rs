@@ -7,6 +7,7 @@ use std::collections::{HashMap, BTreeMap};
 pub struct XRUserId(pub u32);
 
 #[derive(Clone, Default, Debug, SerBin, DeBin)]
+#[nserde(tagged)]
 pub struct XRChannelUser {
     head_transform: Transform,
     left_input: XRInput,